@@ -0,0 +1,43 @@
+-- 044: Add login security tables
+-- Description: Per-account progressive lockout and login session history
+--              used for new-device / new-location detection
+-- Date: 2026-10-18
+
+-- 1. 账户锁定状态（连续失败次数 + 锁定截止时间）
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS failed_login_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_failed_login_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
+
+COMMENT ON COLUMN users.failed_login_attempts IS '连续登录失败次数，登录成功后清零';
+COMMENT ON COLUMN users.locked_until IS '账户锁定截止时间（指数退避）';
+
+-- 2. 登录会话（对应 jive-core domain::user::Session / DeviceInfo）
+CREATE TABLE IF NOT EXISTS user_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Device info
+    device_fingerprint VARCHAR(200) NOT NULL,
+    device_type VARCHAR(20) NOT NULL,
+    os VARCHAR(50) NOT NULL,
+    browser VARCHAR(50),
+    app_version VARCHAR(50),
+    user_agent TEXT,
+
+    -- Location (network prefix stands in for geo location)
+    ip_address INET,
+    network_prefix VARCHAR(64),
+
+    -- Lifecycle
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_id ON user_sessions(user_id);
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_fingerprint ON user_sessions(user_id, device_fingerprint);
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_network ON user_sessions(user_id, network_prefix);
+
+COMMENT ON TABLE user_sessions IS '用户登录会话及设备/网络历史，用于新设备与新位置检测';
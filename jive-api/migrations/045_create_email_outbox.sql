@@ -0,0 +1,33 @@
+-- 045: Create email outbox
+-- Description: Persisted outgoing mail queue; rows are rendered at enqueue time
+--              and delivered by a scheduled worker with exponential backoff
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS email_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    to_address VARCHAR(255) NOT NULL,
+    template VARCHAR(50) NOT NULL,
+    locale VARCHAR(10) NOT NULL DEFAULT 'zh-CN',
+    subject TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    html_body TEXT,
+
+    -- Delivery state: pending -> sending -> sent | failed
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sending', 'sent', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMPTZ,
+    sent_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_outbox_due
+    ON email_outbox(next_attempt_at)
+    WHERE status IN ('pending', 'sending');
+CREATE INDEX IF NOT EXISTS idx_email_outbox_to ON email_outbox(to_address);
+
+COMMENT ON TABLE email_outbox IS '邮件发件箱：业务入队，定时任务投递并按指数退避重试';
@@ -0,0 +1,27 @@
+-- 046: Add password reset support
+-- Description: Hashed single-use reset codes (Postgres fallback when Redis is
+--              unavailable) and a per-user session revocation watermark
+-- Date: 2026-10-18
+
+-- 1. 会话吊销水位线：签发时间早于该时间的 JWT 一律失效
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS sessions_revoked_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN users.sessions_revoked_at IS '早于该时间签发的令牌全部失效（重置密码等场景）';
+
+-- 2. 重置密码验证码（仅保存哈希）
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    request_ip INET,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user
+    ON password_reset_tokens(user_id, created_at DESC);
+
+COMMENT ON TABLE password_reset_tokens IS '重置密码验证码（SHA-256 哈希，单次有效）';
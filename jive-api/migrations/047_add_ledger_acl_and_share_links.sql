@@ -0,0 +1,39 @@
+-- 047: Add per-ledger ACL and read-only share links
+-- Description: Ledger-level role overrides for family members and hashed,
+--              expiring, revocable tokens exposing a ledger's reports
+-- Date: 2026-10-18
+
+-- 1. 账本 ACL：存在记录时覆盖成员的家庭角色（'none' 表示禁止访问）
+CREATE TABLE IF NOT EXISTS ledger_acl (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ledger_id UUID NOT NULL REFERENCES ledgers(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('none', 'viewer', 'editor', 'admin')),
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (ledger_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_acl_user ON ledger_acl(user_id);
+
+COMMENT ON TABLE ledger_acl IS '账本级权限覆盖（优先于家庭角色，家庭 owner 不受限制）';
+
+-- 2. 只读分享链接（仅保存令牌的 SHA-256 哈希）
+CREATE TABLE IF NOT EXISTS ledger_share_links (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ledger_id UUID NOT NULL REFERENCES ledgers(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(100),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    last_accessed_at TIMESTAMPTZ,
+    access_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_share_links_ledger
+    ON ledger_share_links(ledger_id, created_at DESC);
+
+COMMENT ON TABLE ledger_share_links IS '账本报表只读分享链接（可过期、可撤销）';
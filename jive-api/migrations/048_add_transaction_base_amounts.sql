@@ -0,0 +1,52 @@
+-- 048: Add transaction base-currency valuation
+-- Description: Store each transaction's amount converted to the family base currency
+--              at the rate in effect on its transaction date, plus a resumable
+--              job queue used for backfill and recomputation
+-- Date: 2026-10-18
+
+ALTER TABLE transactions
+    ADD COLUMN IF NOT EXISTS base_currency VARCHAR(10),
+    ADD COLUMN IF NOT EXISTS base_amount DECIMAL(15, 2),
+    ADD COLUMN IF NOT EXISTS base_rate DECIMAL(30, 12),
+    ADD COLUMN IF NOT EXISTS base_rate_date DATE,
+    ADD COLUMN IF NOT EXISTS base_valued_at TIMESTAMPTZ;
+
+-- Rows still waiting for a valuation (no rate available yet, or never processed)
+CREATE INDEX IF NOT EXISTS idx_transactions_base_pending
+    ON transactions(id)
+    WHERE base_amount IS NULL AND deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS valuation_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- NULL = all families
+    family_id UUID REFERENCES families(id) ON DELETE CASCADE,
+    reason VARCHAR(30) NOT NULL
+        CHECK (reason IN ('backfill', 'base_currency_changed', 'rate_corrected', 'retry_missing')),
+    -- Only transactions whose own or base currency is in this list (NULL = any)
+    currencies TEXT[],
+    -- Only transactions dated on/after this day (NULL = any)
+    since_date DATE,
+    -- Only transactions that have no base amount yet
+    only_missing BOOLEAN NOT NULL DEFAULT false,
+
+    -- pending -> running -> completed; a crashed or failed run resumes from cursor_id
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'completed')),
+    cursor_id UUID,
+    processed_count BIGINT NOT NULL DEFAULT 0,
+    unresolved_count BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    locked_at TIMESTAMPTZ,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_valuation_jobs_open
+    ON valuation_jobs(created_at)
+    WHERE status IN ('pending', 'running');
+
+-- Value all existing rows once
+INSERT INTO valuation_jobs (reason) VALUES ('backfill');
@@ -0,0 +1,65 @@
+-- 049: Create rate alerts and notifications
+-- Description: User watch rules on exchange rates / crypto prices, evaluated after each
+--              scheduled refresh, plus a per-user notification store they deliver into
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID REFERENCES families(id) ON DELETE CASCADE,
+    -- e.g. rate_alert
+    kind VARCHAR(50) NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created
+    ON notifications(user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_unread
+    ON notifications(user_id)
+    WHERE read_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS rate_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Pair value = price of 1 base_currency in quote_currency (USD/CNY, BTC/USD)
+    base_currency VARCHAR(15) NOT NULL,
+    quote_currency VARCHAR(15) NOT NULL,
+    -- above/below compare the rate with threshold; change_* compare the percentage move over window
+    condition VARCHAR(20) NOT NULL
+        CHECK (condition IN ('above', 'below', 'change_up', 'change_down', 'change_abs')),
+    threshold DECIMAL(30, 12) NOT NULL,
+    change_window VARCHAR(5)
+        CHECK (change_window IN ('24h', '7d', '30d')),
+    cooldown_minutes INTEGER NOT NULL DEFAULT 360 CHECK (cooldown_minutes >= 0),
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    note TEXT,
+
+    last_triggered_at TIMESTAMPTZ,
+    last_value DECIMAL(30, 12),
+    trigger_count INTEGER NOT NULL DEFAULT 0,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (condition IN ('above', 'below') OR change_window IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rate_alerts_user ON rate_alerts(user_id);
+CREATE INDEX IF NOT EXISTS idx_rate_alerts_active
+    ON rate_alerts(base_currency, quote_currency)
+    WHERE is_active;
+
+-- Pair values observed at each evaluation; reference points for change_* rules
+-- (crypto_prices keeps only the latest price, so intraday history lives here)
+CREATE TABLE IF NOT EXISTS rate_alert_samples (
+    base_currency VARCHAR(15) NOT NULL,
+    quote_currency VARCHAR(15) NOT NULL,
+    sampled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    value DECIMAL(30, 12) NOT NULL,
+    PRIMARY KEY (base_currency, quote_currency, sampled_at)
+);
@@ -0,0 +1,105 @@
+-- 050: Create investment tables
+-- Description: Securities catalog, per-account trade log (buys, sells, splits), tax lots and
+--              per-lot disposals for realized gains; cost basis method per account
+-- Date: 2026-10-18
+
+ALTER TABLE accounts
+    ADD COLUMN IF NOT EXISTS cost_basis_method VARCHAR(10) NOT NULL DEFAULT 'fifo';
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'check_accounts_cost_basis_method'
+    ) THEN
+        ALTER TABLE accounts
+            ADD CONSTRAINT check_accounts_cost_basis_method
+            CHECK (cost_basis_method IN ('fifo', 'lifo', 'average'));
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS securities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticker VARCHAR(32) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    security_type VARCHAR(20) NOT NULL DEFAULT 'stock'
+        CHECK (security_type IN ('stock', 'etf', 'mutual_fund', 'bond', 'option',
+                                 'cryptocurrency', 'commodity', 'index')),
+    exchange VARCHAR(20),
+    currency VARCHAR(10) NOT NULL DEFAULT 'CNY',
+    current_price DECIMAL(20, 8),
+    price_updated_at TIMESTAMPTZ,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_securities_ticker_exchange
+    ON securities (UPPER(ticker), COALESCE(UPPER(exchange), ''));
+
+-- Event log per account. Splits are stored here too so replaying an account is one ordered scan:
+-- for buy/sell, quantity and price are as traded; for split, quantity is the resulting position
+CREATE TABLE IF NOT EXISTS investment_trades (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    security_id UUID NOT NULL REFERENCES securities(id),
+    trade_type VARCHAR(10) NOT NULL CHECK (trade_type IN ('buy', 'sell', 'split')),
+    quantity DECIMAL(24, 8) NOT NULL CHECK (quantity >= 0),
+    price DECIMAL(20, 8) NOT NULL DEFAULT 0 CHECK (price >= 0),
+    commission DECIMAL(15, 2) NOT NULL DEFAULT 0 CHECK (commission >= 0),
+    -- buy: cost including commission; sell: proceeds net of commission
+    total_amount DECIMAL(20, 8) NOT NULL DEFAULT 0,
+    realized_gain DECIMAL(20, 8),
+    cost_basis_method VARCHAR(10),
+    split_from DECIMAL(20, 8),
+    split_to DECIMAL(20, 8),
+    trade_date DATE NOT NULL,
+    settlement_date DATE,
+    notes TEXT,
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (trade_type <> 'split' OR (split_from > 0 AND split_to > 0))
+);
+
+CREATE INDEX IF NOT EXISTS idx_investment_trades_account
+    ON investment_trades (account_id, trade_date DESC, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_investment_trades_security
+    ON investment_trades (security_id, trade_date);
+
+-- Quantities and cost are kept in post-split terms; cost_basis is the remaining total cost
+CREATE TABLE IF NOT EXISTS investment_lots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    security_id UUID NOT NULL REFERENCES securities(id),
+    open_trade_id UUID REFERENCES investment_trades(id) ON DELETE SET NULL,
+    acquired_date DATE NOT NULL,
+    original_quantity DECIMAL(24, 8) NOT NULL CHECK (original_quantity > 0),
+    quantity DECIMAL(24, 8) NOT NULL CHECK (quantity >= 0),
+    cost_basis DECIMAL(20, 8) NOT NULL CHECK (cost_basis >= 0),
+    closed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_investment_lots_open
+    ON investment_lots (account_id, security_id, acquired_date)
+    WHERE quantity > 0;
+
+CREATE TABLE IF NOT EXISTS investment_lot_disposals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    trade_id UUID NOT NULL REFERENCES investment_trades(id) ON DELETE CASCADE,
+    lot_id UUID NOT NULL REFERENCES investment_lots(id) ON DELETE CASCADE,
+    quantity DECIMAL(24, 8) NOT NULL CHECK (quantity > 0),
+    cost_basis DECIMAL(20, 8) NOT NULL,
+    proceeds DECIMAL(20, 8) NOT NULL,
+    realized_gain DECIMAL(20, 8) NOT NULL,
+    acquired_date DATE NOT NULL,
+    disposed_date DATE NOT NULL,
+    holding_days INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_investment_lot_disposals_trade
+    ON investment_lot_disposals (trade_id);
+CREATE INDEX IF NOT EXISTS idx_investment_lot_disposals_lot
+    ON investment_lot_disposals (lot_id);
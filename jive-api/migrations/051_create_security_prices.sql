@@ -0,0 +1,19 @@
+-- 051: Create security price history
+-- Description: Daily closing prices per security, used to compare portfolio performance
+--              against a benchmark security over arbitrary periods
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS security_prices (
+    security_id UUID NOT NULL REFERENCES securities(id) ON DELETE CASCADE,
+    price_date DATE NOT NULL,
+    close_price DECIMAL(20, 8) NOT NULL CHECK (close_price > 0),
+    -- e.g. manual
+    source VARCHAR(50) NOT NULL DEFAULT 'manual',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (security_id, price_date)
+);
+
+-- Performance reports pick one valuation per account and day
+CREATE INDEX IF NOT EXISTS idx_valuations_account_date
+    ON valuations (account_id, valuation_date);
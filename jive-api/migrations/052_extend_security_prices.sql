@@ -0,0 +1,18 @@
+-- 052: Extend security price history
+-- Description: Optional OHLCV columns for imported / fetched daily quotes, and a lookup index for
+--              the scheduled quote refresh that revalues holdings
+-- Date: 2026-10-18
+
+ALTER TABLE security_prices
+    ADD COLUMN IF NOT EXISTS open_price DECIMAL(20, 8),
+    ADD COLUMN IF NOT EXISTS high_price DECIMAL(20, 8),
+    ADD COLUMN IF NOT EXISTS low_price DECIMAL(20, 8),
+    ADD COLUMN IF NOT EXISTS volume DECIMAL(24, 4);
+
+CREATE INDEX IF NOT EXISTS idx_security_prices_date
+    ON security_prices (price_date);
+
+-- Accounts revalued by the quote refresh
+CREATE INDEX IF NOT EXISTS idx_investment_lots_account_open
+    ON investment_lots (account_id)
+    WHERE quantity > 0;
@@ -0,0 +1,46 @@
+-- 053: Create investment income and corporate actions
+-- Description: Dividends / interest per security (cash or reinvested, with withholding tax), and
+--              merger / spin-off events that reallocate lot cost basis
+-- Date: 2026-10-18
+
+-- Reinvestments and corporate actions join buys/sells/splits in the per-account event log.
+-- For merger / spin-off rows: security_id is the original security, related_security_id the new
+-- one, split_from:split_to the share ratio, cost_allocation the fraction of cost moved to the new
+-- security, price the cash paid per original share and total_amount the total cash received
+ALTER TABLE investment_trades
+    ADD COLUMN IF NOT EXISTS related_security_id UUID REFERENCES securities(id),
+    ADD COLUMN IF NOT EXISTS cost_allocation DECIMAL(10, 8);
+
+ALTER TABLE investment_trades DROP CONSTRAINT IF EXISTS investment_trades_trade_type_check;
+ALTER TABLE investment_trades
+    ADD CONSTRAINT investment_trades_trade_type_check
+    CHECK (trade_type IN ('buy', 'sell', 'split', 'reinvest', 'merger', 'spinoff'));
+
+CREATE TABLE IF NOT EXISTS investment_income (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    security_id UUID NOT NULL REFERENCES securities(id),
+    income_type VARCHAR(30) NOT NULL
+        CHECK (income_type IN ('dividend', 'special_dividend', 'interest',
+                               'capital_gain_distribution')),
+    amount_per_share DECIMAL(20, 8) NOT NULL CHECK (amount_per_share >= 0),
+    -- Eligible shares on the ex-date
+    shares DECIMAL(24, 8) NOT NULL CHECK (shares > 0),
+    gross_amount DECIMAL(20, 8) NOT NULL CHECK (gross_amount >= 0),
+    withholding_tax DECIMAL(20, 8) NOT NULL DEFAULT 0 CHECK (withholding_tax >= 0),
+    net_amount DECIMAL(20, 8) NOT NULL,
+    ex_date DATE NOT NULL,
+    payment_date DATE NOT NULL,
+    reinvested BOOLEAN NOT NULL DEFAULT false,
+    reinvest_trade_id UUID REFERENCES investment_trades(id) ON DELETE SET NULL,
+    notes TEXT,
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (withholding_tax <= gross_amount)
+);
+
+CREATE INDEX IF NOT EXISTS idx_investment_income_account_date
+    ON investment_income (account_id, payment_date DESC);
+CREATE INDEX IF NOT EXISTS idx_investment_income_security
+    ON investment_income (security_id, payment_date);
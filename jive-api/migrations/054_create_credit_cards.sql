@@ -0,0 +1,76 @@
+-- 054: Create credit cards and statements
+-- Description: Billing terms for credit_card / huabei / jd_white_bar accounts, generated monthly
+--              statements with minimum due, and interest / late-fee assessment
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS credit_cards (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL UNIQUE REFERENCES accounts(id) ON DELETE CASCADE,
+    card_number_last4 VARCHAR(4),
+    bank_name VARCHAR(100),
+    card_network VARCHAR(30),
+    credit_limit DECIMAL(15, 2),
+    -- Statement (bill) day; months without that day close on their last day
+    bill_day SMALLINT NOT NULL CHECK (bill_day BETWEEN 1 AND 31),
+    -- true: spending on the bill day belongs to the statement issued that day (the period
+    -- before it); false: it rolls into the next statement
+    bill_calculation_in_previous_period BOOLEAN NOT NULL DEFAULT false,
+    payment_date_type VARCHAR(20) NOT NULL DEFAULT 'fixed_date'
+        CHECK (payment_date_type IN ('fixed_date', 'days_after_bill')),
+    payment_day SMALLINT CHECK (payment_day BETWEEN 1 AND 31),
+    payment_days_after_bill SMALLINT CHECK (payment_days_after_bill BETWEEN 1 AND 28),
+    apr DECIMAL(7, 4) NOT NULL DEFAULT 0.1825 CHECK (apr >= 0),
+    minimum_payment_rate DECIMAL(5, 4) NOT NULL DEFAULT 0.1
+        CHECK (minimum_payment_rate > 0 AND minimum_payment_rate <= 1),
+    minimum_payment_floor DECIMAL(15, 2) NOT NULL DEFAULT 0 CHECK (minimum_payment_floor >= 0),
+    late_fee DECIMAL(15, 2) NOT NULL DEFAULT 0 CHECK (late_fee >= 0),
+    -- Charged on the unpaid part of the minimum due, on top of late_fee
+    late_fee_rate DECIMAL(5, 4) NOT NULL DEFAULT 0 CHECK (late_fee_rate >= 0 AND late_fee_rate <= 1),
+    reminder_days_before SMALLINT NOT NULL DEFAULT 3 CHECK (reminder_days_before BETWEEN 0 AND 28),
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'closed')),
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (payment_date_type <> 'fixed_date' OR payment_day IS NOT NULL),
+    CHECK (payment_date_type <> 'days_after_bill' OR payment_days_after_bill IS NOT NULL)
+);
+
+-- Amounts are owed amounts (positive = debt), derived from the card account's transactions:
+-- expenses / transfers out are charges, income rows are payments and credits
+CREATE TABLE IF NOT EXISTS credit_card_statements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    card_id UUID NOT NULL REFERENCES credit_cards(id) ON DELETE CASCADE,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    statement_date DATE NOT NULL,
+    due_date DATE NOT NULL,
+    opening_balance DECIMAL(15, 2) NOT NULL,
+    new_charges DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    payments DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    interest_charged DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    fees_charged DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    closing_balance DECIMAL(15, 2) NOT NULL,
+    minimum_due DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    -- Payments after the statement date, up to the next statement
+    paid_amount DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    paid_by_due DECIMAL(15, 2),
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'paid', 'minimum_paid', 'overdue')),
+    late_fee DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    late_fee_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    interest_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    assessed_at TIMESTAMPTZ,
+    reminder_sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (card_id, statement_date),
+    CHECK (period_start <= period_end)
+);
+
+CREATE INDEX IF NOT EXISTS idx_credit_card_statements_card_date
+    ON credit_card_statements (card_id, statement_date DESC);
+CREATE INDEX IF NOT EXISTS idx_credit_card_statements_unsettled
+    ON credit_card_statements (due_date)
+    WHERE status <> 'paid';
@@ -0,0 +1,83 @@
+-- 055: Create installment plans
+-- Description: Purchases on credit / BNPL accounts split into 3/6/12... installments with a
+--              per-period fee and early payoff; statements bill the monthly slices and budgets
+--              can count either the full purchase or the slices
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS installment_plans (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- The original purchase; one plan per purchase
+    transaction_id UUID NOT NULL UNIQUE REFERENCES transactions(id) ON DELETE CASCADE,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    principal DECIMAL(15, 2) NOT NULL CHECK (principal > 0),
+    installments SMALLINT NOT NULL CHECK (installments BETWEEN 2 AND 60),
+    -- none: interest-free; rate: principal * fee_rate each period; fixed: fee_per_period
+    fee_mode VARCHAR(10) NOT NULL DEFAULT 'none' CHECK (fee_mode IN ('none', 'rate', 'fixed')),
+    fee_rate DECIMAL(7, 6) NOT NULL DEFAULT 0 CHECK (fee_rate >= 0 AND fee_rate < 1),
+    fee_per_period DECIMAL(15, 2) NOT NULL DEFAULT 0 CHECK (fee_per_period >= 0),
+    -- Charged on the remaining principal when paid off early
+    early_payoff_fee_rate DECIMAL(7, 6) NOT NULL DEFAULT 0
+        CHECK (early_payoff_fee_rate >= 0 AND early_payoff_fee_rate < 1),
+    -- The statement containing this date receives a credit for the converted principal
+    converted_on DATE NOT NULL,
+    first_posting_date DATE NOT NULL,
+    -- full: budgets count the purchase when it happens; monthly: each slice on its posting date
+    budget_mode VARCHAR(10) NOT NULL DEFAULT 'full' CHECK (budget_mode IN ('full', 'monthly')),
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed', 'paid_off')),
+    paid_off_on DATE,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (first_posting_date >= converted_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_installment_plans_account ON installment_plans (account_id);
+
+CREATE TABLE IF NOT EXISTS installment_periods (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES installment_plans(id) ON DELETE CASCADE,
+    period_number SMALLINT NOT NULL,
+    posting_date DATE NOT NULL,
+    principal DECIMAL(15, 2) NOT NULL,
+    fee DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    -- The single period replacing all remaining ones after an early payoff
+    is_payoff BOOLEAN NOT NULL DEFAULT false,
+    fee_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    posted_at TIMESTAMPTZ,
+
+    UNIQUE (plan_id, period_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_installment_periods_unposted
+    ON installment_periods (posting_date)
+    WHERE posted_at IS NULL;
+
+-- Installment lines on credit card statements (owed amounts, positive = debt)
+ALTER TABLE credit_card_statements
+    ADD COLUMN IF NOT EXISTS installment_principal DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS installment_converted DECIMAL(15, 2) NOT NULL DEFAULT 0;
+
+-- Expense rows as budgets see them: the converted part of a purchase in a 'monthly' plan is
+-- replaced by its slices on their posting dates
+CREATE OR REPLACE VIEW budget_expenses AS
+SELECT t.id, t.ledger_id, t.category_id, t.transaction_type, t.transaction_date,
+       t.amount - COALESCE(p.principal, 0) AS amount,
+       CASE WHEN p.id IS NULL THEN t.base_amount
+            WHEN t.base_amount IS NULL OR t.amount = 0 THEN NULL
+            ELSE ROUND((t.amount - p.principal) * t.base_amount / t.amount, 2) END AS base_amount,
+       t.status, t.deleted_at
+FROM transactions t
+LEFT JOIN installment_plans p ON p.transaction_id = t.id AND p.budget_mode = 'monthly'
+WHERE t.transaction_type = 'expense'
+  AND (p.id IS NULL OR t.amount > p.principal)
+UNION ALL
+SELECT t.id, t.ledger_id, t.category_id, t.transaction_type, ip.posting_date,
+       ip.principal,
+       CASE WHEN t.base_amount IS NULL OR t.amount = 0 THEN NULL
+            ELSE ROUND(ip.principal * t.base_amount / t.amount, 2) END,
+       t.status, t.deleted_at
+FROM installment_periods ip
+JOIN installment_plans p ON p.id = ip.plan_id
+JOIN transactions t ON t.id = p.transaction_id
+WHERE p.budget_mode = 'monthly';
@@ -0,0 +1,67 @@
+-- 056: Create loan terms
+-- Description: Amortization terms for loan / mortgage accounts (equal payment 等额本息 or equal
+--              principal 等额本金), variable-rate resets and recorded payments split into
+--              principal and interest transactions
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS loan_terms (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL UNIQUE REFERENCES accounts(id) ON DELETE CASCADE,
+    principal DECIMAL(15, 2) NOT NULL CHECK (principal > 0),
+    annual_rate DECIMAL(7, 6) NOT NULL CHECK (annual_rate >= 0 AND annual_rate < 1),
+    term_months SMALLINT NOT NULL CHECK (term_months BETWEEN 1 AND 600),
+    start_date DATE NOT NULL,
+    first_payment_date DATE NOT NULL,
+    repayment_method VARCHAR(20) NOT NULL
+        CHECK (repayment_method IN ('equal_payment', 'equal_principal')),
+    -- Current state, advanced by each recorded payment. Installments due before the terms were
+    -- entered are treated as already paid.
+    outstanding_principal DECIMAL(15, 2) NOT NULL CHECK (outstanding_principal >= 0),
+    next_period SMALLINT NOT NULL,
+    remaining_periods SMALLINT NOT NULL CHECK (remaining_periods >= 0),
+    -- Fixed installment for equal_payment (re-amortized on rate resets and prepayments);
+    -- per-period principal for equal_principal
+    current_installment DECIMAL(15, 2) NOT NULL,
+    current_rate DECIMAL(7, 6) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'paid_off')),
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (first_payment_date > start_date)
+);
+
+-- Variable-rate resets (e.g. LPR repricing); a period uses the rate in effect on its due date
+CREATE TABLE IF NOT EXISTS loan_rate_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loan_terms(id) ON DELETE CASCADE,
+    effective_date DATE NOT NULL,
+    annual_rate DECIMAL(7, 6) NOT NULL CHECK (annual_rate >= 0 AND annual_rate < 1),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (loan_id, effective_date)
+);
+
+CREATE TABLE IF NOT EXISTS loan_payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loan_terms(id) ON DELETE CASCADE,
+    -- NULL for prepayments
+    period_number SMALLINT,
+    payment_date DATE NOT NULL,
+    amount DECIMAL(15, 2) NOT NULL CHECK (amount > 0),
+    principal DECIMAL(15, 2) NOT NULL,
+    interest DECIMAL(15, 2) NOT NULL,
+    balance_after DECIMAL(15, 2) NOT NULL,
+    -- shorten_term / reduce_payment for prepayments
+    prepayment_strategy VARCHAR(20)
+        CHECK (prepayment_strategy IN ('shorten_term', 'reduce_payment')),
+    from_account_id UUID NOT NULL REFERENCES accounts(id),
+    principal_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    interest_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (loan_id, period_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_payments_loan_date ON loan_payments (loan_id, payment_date);
@@ -0,0 +1,78 @@
+-- 057: Create bank connections
+-- Description: Bank aggregation connections (Plaid and the built-in fake institution), linked
+--              provider accounts, the cursor used for incremental transaction sync and a log of
+--              received webhooks. Synced transactions carry the provider's transaction id so
+--              updates, removals and pending -> posted reconciliation can find them again.
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS bank_connections (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    family_id UUID NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    ledger_id UUID NOT NULL REFERENCES ledgers(id) ON DELETE CASCADE,
+    provider VARCHAR(20) NOT NULL,
+    institution_id VARCHAR(100),
+    institution_name VARCHAR(200),
+    -- Provider item id (Plaid item_id); webhooks are routed by it
+    external_item_id VARCHAR(200) NOT NULL,
+    access_token TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'reauth_required', 'error')),
+    -- Opaque cursor returned by the last completed sync; NULL = full history
+    sync_cursor TEXT,
+    last_synced_at TIMESTAMPTZ,
+    last_error TEXT,
+    -- Consent expiry announced by the provider (PENDING_EXPIRATION)
+    consent_expires_at TIMESTAMPTZ,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (provider, external_item_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bank_connections_ledger ON bank_connections (ledger_id);
+
+CREATE TABLE IF NOT EXISTS bank_connection_accounts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    connection_id UUID NOT NULL REFERENCES bank_connections(id) ON DELETE CASCADE,
+    external_account_id VARCHAR(200) NOT NULL,
+    -- Jive account receiving the transactions; NULL = not imported
+    account_id UUID REFERENCES accounts(id) ON DELETE SET NULL,
+    name VARCHAR(200) NOT NULL,
+    mask VARCHAR(10),
+    account_type VARCHAR(30) NOT NULL,
+    account_subtype VARCHAR(50),
+    currency VARCHAR(10),
+    current_balance DECIMAL(15, 2),
+    available_balance DECIMAL(15, 2),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (connection_id, external_account_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bank_connection_accounts_account
+    ON bank_connection_accounts (account_id);
+
+ALTER TABLE transactions
+    ADD COLUMN IF NOT EXISTS bank_connection_id UUID REFERENCES bank_connections(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS external_id VARCHAR(200);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_bank_external
+    ON transactions (bank_connection_id, external_id)
+    WHERE bank_connection_id IS NOT NULL AND external_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS bank_webhook_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    provider VARCHAR(20) NOT NULL,
+    connection_id UUID REFERENCES bank_connections(id) ON DELETE SET NULL,
+    webhook_type VARCHAR(50) NOT NULL,
+    webhook_code VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_bank_webhook_events_connection
+    ON bank_webhook_events (connection_id, received_at DESC);
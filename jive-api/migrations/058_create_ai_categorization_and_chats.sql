@@ -0,0 +1,59 @@
+-- 058: Create AI categorization log and assistant chats
+-- Description: Every automatic categorization suggestion is logged with its source, the
+--              provider/model that produced it, the confidence and a hash of the (redacted)
+--              prompt, whether or not it was applied. Assistant chats keep the full message
+--              history including tool calls so a conversation can be resumed.
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS transaction_categorizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    ledger_id UUID NOT NULL REFERENCES ledgers(id) ON DELETE CASCADE,
+    -- Suggested category; NULL = the model found no suitable category
+    category_id UUID REFERENCES categories(id) ON DELETE SET NULL,
+    -- Suggestion source, e.g. 'llm'
+    source VARCHAR(20) NOT NULL,
+    -- Provider name ('openai', 'openai_compatible', 'stub') and model
+    provider VARCHAR(50) NOT NULL,
+    model VARCHAR(100),
+    confidence DOUBLE PRECISION NOT NULL CHECK (confidence BETWEEN 0 AND 1),
+    reasoning TEXT,
+    -- SHA-256 of the redacted prompt sent to the provider
+    prompt_sha256 VARCHAR(64),
+    -- Whether the suggestion was written to the transaction
+    applied BOOLEAN NOT NULL DEFAULT false,
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_categorizations_transaction
+    ON transaction_categorizations (transaction_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS ai_chats (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    title VARCHAR(200),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_chats_user ON ai_chats (user_id, updated_at DESC);
+
+CREATE TABLE IF NOT EXISTS ai_chat_messages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chat_id UUID NOT NULL REFERENCES ai_chats(id) ON DELETE CASCADE,
+    -- Insertion order within the chat (messages of one turn share a timestamp)
+    seq BIGSERIAL,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('user', 'assistant', 'tool')),
+    content TEXT,
+    -- Assistant messages: [{"id", "name", "arguments"}]
+    tool_calls JSONB,
+    -- Tool messages: id of the call being answered
+    tool_call_id VARCHAR(100),
+    provider VARCHAR(50),
+    model VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_chat_messages_chat ON ai_chat_messages (chat_id, seq);
@@ -0,0 +1,39 @@
+-- 059: Create per-family categorization models
+-- Description: Offline naive Bayes classifier trained on each family's own confirmed
+--              categorizations. transactions.category_source records who set the category:
+--              NULL = the user (training data), 'rule' / 'llm' / 'ml' = applied automatically
+--              (never learned from). Every learned transaction keeps its features so that a
+--              later edit or deletion can be unlearned incrementally.
+-- Date: 2026-10-18
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS category_source VARCHAR(20);
+
+-- Incremental training scans transactions changed since the last run
+CREATE INDEX IF NOT EXISTS idx_transactions_updated_at ON transactions (updated_at, id);
+
+CREATE TABLE IF NOT EXISTS category_models (
+    family_id UUID PRIMARY KEY REFERENCES families(id) ON DELETE CASCADE,
+    -- Serialized classifiers: description/payee/amount/weekday → category, and → payee
+    category_model JSONB NOT NULL DEFAULT '{}',
+    payee_model JSONB NOT NULL DEFAULT '{}',
+    example_count INTEGER NOT NULL DEFAULT 0,
+    -- Watermark: transactions updated after this are not yet learned
+    trained_until TIMESTAMPTZ,
+    trained_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS category_model_examples (
+    -- No foreign key: examples of hard-deleted transactions are unlearned on the next run
+    transaction_id UUID PRIMARY KEY,
+    family_id UUID NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    category_id UUID NOT NULL,
+    -- Label learned by the payee model (NULL = not learned)
+    payee TEXT,
+    features TEXT[] NOT NULL,
+    learned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_category_model_examples_family
+    ON category_model_examples (family_id);
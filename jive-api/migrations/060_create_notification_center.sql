@@ -0,0 +1,61 @@
+-- 060: Create notification center
+-- Description: Extends the in-app notification store with priority, action links and
+--              dismissal, adds per-user notification preferences (muted kinds, quiet hours,
+--              email digest frequency, personal webhook) and a delivery queue for the
+--              out-of-app channels. In-app notifications are always stored and pushed over
+--              WebSocket; email and webhook deliveries are scheduled around quiet hours and
+--              the digest frequency, then handed to the email outbox or POSTed with an
+--              HMAC-SHA256 signature.
+-- Date: 2026-10-18
+
+ALTER TABLE notifications
+    ADD COLUMN IF NOT EXISTS priority VARCHAR(10) NOT NULL DEFAULT 'normal'
+        CHECK (priority IN ('low', 'normal', 'high', 'urgent')),
+    ADD COLUMN IF NOT EXISTS action_url TEXT,
+    ADD COLUMN IF NOT EXISTS dismissed_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_inbox
+    ON notifications (user_id, created_at DESC)
+    WHERE dismissed_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    email_enabled BOOLEAN NOT NULL DEFAULT false,
+    -- Personal webhook; requests carry X-Jive-Signature: t=<unix>,v1=<hmac>
+    webhook_url TEXT,
+    webhook_secret VARCHAR(100),
+    -- Notification kinds the user does not want at all (e.g. rate_alert)
+    muted_kinds TEXT[] NOT NULL DEFAULT '{}',
+    -- Local wall-clock window in which email / webhook deliveries are held back
+    quiet_hours_start TIME,
+    quiet_hours_end TIME,
+    timezone VARCHAR(50) NOT NULL DEFAULT 'Asia/Shanghai',
+    digest_frequency VARCHAR(10) NOT NULL DEFAULT 'realtime'
+        CHECK (digest_frequency IN ('realtime', 'daily', 'weekly', 'never')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((quiet_hours_start IS NULL) = (quiet_hours_end IS NULL))
+);
+
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    notification_id UUID NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('email', 'webhook')),
+    -- skipped = notification already read / dismissed before a deferred email went out
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sending', 'sent', 'failed', 'skipped')),
+    deliver_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    locked_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_due
+    ON notification_deliveries (deliver_after)
+    WHERE status IN ('pending', 'sending');
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_notification
+    ON notification_deliveries (notification_id);
@@ -0,0 +1,49 @@
+-- 061: Create push devices
+-- Description: Device registrations for mobile and browser push notifications (FCM
+--              registration tokens and Web Push subscriptions). Each registration is linked
+--              to the login session / device info it was made from. Devices are pruned when
+--              the push provider reports the token as unregistered or the linked session is
+--              revoked. Adds a `push` channel to the notification delivery queue and a
+--              per-user switch in notification preferences.
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS push_devices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    session_id UUID REFERENCES user_sessions(id) ON DELETE SET NULL,
+    provider VARCHAR(20) NOT NULL CHECK (provider IN ('webpush', 'fcm')),
+    -- FCM registration token, or the Web Push subscription endpoint URL
+    token TEXT NOT NULL,
+    -- Web Push subscription keys (base64url): client ECDH public key and auth secret
+    p256dh VARCHAR(100),
+    auth_secret VARCHAR(50),
+
+    -- Device info (same parsing as user_sessions)
+    device_fingerprint VARCHAR(200) NOT NULL,
+    device_type VARCHAR(20) NOT NULL,
+    os VARCHAR(50) NOT NULL,
+    browser VARCHAR(50),
+    app_version VARCHAR(50),
+
+    -- Consecutive transient send failures; reset on success or re-registration
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_success_at TIMESTAMPTZ,
+    UNIQUE (provider, token)
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_devices_user_id ON push_devices(user_id);
+CREATE INDEX IF NOT EXISTS idx_push_devices_session_id ON push_devices(session_id);
+
+COMMENT ON TABLE push_devices IS '推送设备注册（FCM 令牌 / Web Push 订阅），与登录会话关联';
+
+ALTER TABLE notification_preferences
+    ADD COLUMN IF NOT EXISTS push_enabled BOOLEAN NOT NULL DEFAULT true;
+
+ALTER TABLE notification_deliveries
+    DROP CONSTRAINT IF EXISTS notification_deliveries_channel_check;
+ALTER TABLE notification_deliveries
+    ADD CONSTRAINT notification_deliveries_channel_check
+        CHECK (channel IN ('email', 'webhook', 'push'));
@@ -0,0 +1,28 @@
+-- 062: Create financial digests
+-- Description: Weekly and monthly financial digests generated per family member: spending vs
+--              the previous period, top categories, budgets at risk, upcoming payments,
+--              unusual transactions and net worth change, limited to the ledgers the member
+--              can see. One row per member, family, period type and period start makes the
+--              scheduled job idempotent; the rendered report is kept for the digests API.
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS financial_digests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    period VARCHAR(10) NOT NULL CHECK (period IN ('weekly', 'monthly')),
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    currency VARCHAR(10) NOT NULL,
+    report JSONB NOT NULL,
+    -- NULL when the period had no activity (recorded so the job does not rebuild it)
+    notification_id UUID REFERENCES notifications(id) ON DELETE SET NULL,
+    email_outbox_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, family_id, period, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_financial_digests_user
+    ON financial_digests(user_id, created_at DESC);
+
+COMMENT ON TABLE financial_digests IS '按成员生成的周度 / 月度财务摘要';
@@ -0,0 +1,81 @@
+-- 063: Create family webhooks
+-- Description: Family-configured outbound webhook endpoints subscribed to event types
+--              (transaction.created/updated/deleted, budget.exceeded, member.joined,
+--              import.completed). Every event is stored once and fanned out to a delivery
+--              per subscribed endpoint; deliveries are retried with exponential backoff and
+--              each HTTP attempt is kept in a delivery log. Redelivering creates a new
+--              delivery for the same event.
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS webhook_endpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    family_id UUID NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    description VARCHAR(200),
+    -- HMAC-SHA256 key for the X-Jive-Signature header
+    secret VARCHAR(100) NOT NULL,
+    event_types TEXT[] NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    last_delivery_at TIMESTAMPTZ,
+    last_delivery_status VARCHAR(20),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_endpoints_family
+    ON webhook_endpoints (family_id) WHERE is_active;
+
+CREATE TABLE IF NOT EXISTS webhook_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    family_id UUID NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    -- events that must fire once (budget.exceeded per budget period); NULL for the rest
+    dedupe_key VARCHAR(200),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (family_id, dedupe_key)
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    endpoint_id UUID NOT NULL REFERENCES webhook_endpoints(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES webhook_events(id) ON DELETE CASCADE,
+    -- set when the delivery was created by a manual redeliver
+    redelivery_of UUID REFERENCES webhook_deliveries(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sending', 'sent', 'failed')),
+    deliver_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    last_error TEXT,
+    locked_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries (deliver_after)
+    WHERE status IN ('pending', 'sending');
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_endpoint
+    ON webhook_deliveries (endpoint_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    delivery_id UUID NOT NULL REFERENCES webhook_deliveries(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    -- NULL when the request never got a response (DNS, connect, timeout)
+    response_status INTEGER,
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery
+    ON webhook_delivery_attempts (delivery_id, attempt);
+
+COMMENT ON TABLE webhook_endpoints IS '家庭配置的外发 Webhook 地址';
+COMMENT ON TABLE webhook_events IS '家庭事件，按订阅扇出为投递';
+COMMENT ON TABLE webhook_deliveries IS 'Webhook 投递队列（指数退避重试）';
+COMMENT ON TABLE webhook_delivery_attempts IS 'Webhook 每次请求的投递日志';
@@ -0,0 +1,14 @@
+-- 064: Add per-user password reset limits
+-- Description: Counts reset codes issued and failed verifications per user in a rolling
+--              window, so requesting a new code (from any IP) does not grant a fresh
+--              attempt budget
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS password_reset_limits (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    window_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    codes_issued INTEGER NOT NULL DEFAULT 0,
+    failed_attempts INTEGER NOT NULL DEFAULT 0
+);
+
+COMMENT ON TABLE password_reset_limits IS '找回密码的每用户限额（窗口内的发码次数与验证失败次数）';
@@ -0,0 +1,9 @@
+-- 065: Support pruning of login sessions
+-- Description: A daily job deletes user_sessions rows that have expired or been revoked and
+--              were last seen longer ago than AUTH_SESSION_RETENTION_DAYS (default 90).
+--              Device fingerprints now include the OS major version and device model;
+--              rows written before this change keep the old "type|os|browser" format and
+--              are still matched until they are pruned.
+-- Date: 2026-10-19
+
+CREATE INDEX IF NOT EXISTS idx_user_sessions_last_seen ON user_sessions(last_seen_at);
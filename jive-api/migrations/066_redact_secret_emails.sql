@@ -0,0 +1,24 @@
+-- 066: Stop keeping one-time codes in the email outbox
+-- Description: Verification and password reset emails carry a live code. Such rows are
+--              flagged secret with the code's expiry; subject and bodies are cleared once
+--              they are sent or fail, and pending rows past expires_at are dropped unsent.
+-- Date: 2026-10-19
+
+ALTER TABLE email_outbox
+    ADD COLUMN IF NOT EXISTS contains_secret BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+-- Existing reset / verification rows may still hold codes
+UPDATE email_outbox
+SET subject = '', text_body = '', html_body = NULL,
+    status = CASE WHEN status IN ('pending', 'sending') THEN 'failed' ELSE status END,
+    last_error = CASE WHEN status IN ('pending', 'sending') THEN 'expired' ELSE last_error END,
+    contains_secret = TRUE
+WHERE template IN ('password_reset', 'verification_code');
+
+CREATE INDEX IF NOT EXISTS idx_email_outbox_secret_expiry
+    ON email_outbox(expires_at)
+    WHERE contains_secret AND text_body <> '';
+
+COMMENT ON COLUMN email_outbox.contains_secret IS '含一次性验证码：发送或失败后清空主题与正文';
+COMMENT ON COLUMN email_outbox.expires_at IS '验证码过期时间：过期仍未发送则作废并清空主题与正文';
@@ -0,0 +1,46 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+# Vendor a pinned swagger-ui-dist release for /api/v1/docs.
+# The docs page loads these files from the API origin, so no third-party
+# script runs there. The tarball is checked against the integrity hash npm
+# publishes for this exact (immutable) version before anything is extracted.
+#
+# Usage: scripts/fetch_swagger_ui.sh [dest_dir]   (default: static/swagger-ui)
+
+VERSION="5.17.14"
+DEST="${1:-${SWAGGER_UI_DIR:-$(cd "$(dirname "$0")/.." && pwd)/static/swagger-ui}}"
+FILES=(swagger-ui.css swagger-ui-bundle.js)
+
+for tool in curl jq openssl tar; do
+  command -v "$tool" >/dev/null || { echo "FAIL: $tool is required" >&2; exit 1; }
+done
+
+meta=$(curl -fsSL "https://registry.npmjs.org/swagger-ui-dist/${VERSION}")
+tarball_url=$(jq -r '.dist.tarball' <<<"$meta")
+integrity=$(jq -r '.dist.integrity' <<<"$meta")
+if [[ "$integrity" != sha512-* ]]; then
+  echo "FAIL: unexpected integrity for swagger-ui-dist@${VERSION}: ${integrity}" >&2
+  exit 2
+fi
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+curl -fsSL "$tarball_url" -o "$tmp/package.tgz"
+
+actual="sha512-$(openssl dgst -sha512 -binary "$tmp/package.tgz" | openssl base64 -A)"
+if [[ "$actual" != "$integrity" ]]; then
+  echo "FAIL: integrity mismatch for swagger-ui-dist@${VERSION}" >&2
+  echo "  expected ${integrity}" >&2
+  echo "  actual   ${actual}" >&2
+  exit 3
+fi
+
+tar -xzf "$tmp/package.tgz" -C "$tmp" "${FILES[@]/#/package/}"
+mkdir -p "$DEST"
+for f in "${FILES[@]}"; do
+  cp "$tmp/package/$f" "$DEST/$f"
+done
+echo "$VERSION" > "$DEST/VERSION"
+
+echo "OK: swagger-ui-dist@${VERSION} -> ${DEST}"
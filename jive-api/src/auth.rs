@@ -2,20 +2,25 @@
 
 use axum::{
     async_trait,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     http::{request::Parts, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// 获取 JWT 密钥（优先环境变量 JWT_SECRET；未设置时使用不安全占位并在非测试模式下警告）
 fn jwt_secret() -> &'static str {
     // Use once_cell to cache environment lookup
-    use std::sync::OnceLock;
     static SECRET: OnceLock<String> = OnceLock::new();
     SECRET.get_or_init(|| {
         match std::env::var("JWT_SECRET") {
@@ -90,6 +95,76 @@ impl Claims {
     pub fn user_id(&self) -> Result<Uuid, AuthError> {
         Uuid::parse_str(&self.sub).map_err(|_| AuthError::InvalidToken)
     }
+
+    /// 令牌是否已被吊销（签发时间不晚于 users.sessions_revoked_at，如重置密码后）
+    ///
+    /// 用户不存在或查询失败时视为已吊销；吊销时间按用户缓存 `REVOCATION_CACHE_TTL`
+    pub async fn is_revoked(&self, pool: &PgPool) -> bool {
+        let Ok(user_id) = self.user_id() else {
+            return true;
+        };
+        let revoked_at = match cached_revocation(user_id) {
+            Some(revoked_at) => revoked_at,
+            None => {
+                let row = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+                    "SELECT sessions_revoked_at FROM users WHERE id = $1",
+                )
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await;
+                match row {
+                    Ok(Some(revoked_at)) => {
+                        cache_revocation(user_id, revoked_at);
+                        revoked_at
+                    }
+                    // 用户已删除
+                    Ok(None) => return true,
+                    Err(e) => {
+                        tracing::warn!(user_id = %user_id, error = ?e, "failed to check token revocation");
+                        return true;
+                    }
+                }
+            }
+        };
+        // iat 精度为秒，同一秒内签发的令牌按已吊销处理（重新登录即可）
+        revoked_at.is_some_and(|revoked_at| (self.iat as i64) <= revoked_at.timestamp())
+    }
+}
+
+/// 吊销时间缓存有效期：其他实例上的吊销最多延迟这么久生效，本实例吊销时立即清除
+const REVOCATION_CACHE_TTL: Duration = Duration::from_secs(30);
+/// 缓存条目超过该数量时清理过期条目
+const REVOCATION_CACHE_PRUNE_AT: usize = 10_000;
+
+type RevocationCache = Mutex<HashMap<Uuid, (Option<DateTime<Utc>>, Instant)>>;
+
+fn revocation_cache() -> &'static RevocationCache {
+    static CACHE: OnceLock<RevocationCache> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+fn cached_revocation(user_id: Uuid) -> Option<Option<DateTime<Utc>>> {
+    let cache = revocation_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .get(&user_id)
+        .filter(|(_, cached_at)| cached_at.elapsed() < REVOCATION_CACHE_TTL)
+        .map(|(revoked_at, _)| *revoked_at)
+}
+
+fn cache_revocation(user_id: Uuid, revoked_at: Option<DateTime<Utc>>) {
+    let mut cache = revocation_cache().lock().unwrap_or_else(|e| e.into_inner());
+    if cache.len() >= REVOCATION_CACHE_PRUNE_AT {
+        cache.retain(|_, (_, cached_at)| cached_at.elapsed() < REVOCATION_CACHE_TTL);
+    }
+    cache.insert(user_id, (revoked_at, Instant::now()));
+}
+
+/// 用户的令牌被吊销后调用，使本实例立即生效
+pub fn forget_revocation(user_id: Uuid) {
+    revocation_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&user_id);
 }
 
 /// 认证错误
@@ -132,10 +207,11 @@ impl std::error::Error for AuthError {}
 impl<S> FromRequestParts<S> for Claims
 where
     S: Send + Sync,
+    PgPool: FromRef<S>,
 {
     type Rejection = AuthError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         // 提取Authorization头
         let auth_header = parts
             .headers
@@ -154,19 +230,24 @@ where
         // 验证令牌并提取claims
         let claims = Claims::from_token(token)?;
 
+        // 已吊销的令牌（例如重置密码前签发的）拒绝访问
+        if claims.is_revoked(&PgPool::from_ref(state)).await {
+            return Err(AuthError::InvalidToken);
+        }
+
         Ok(claims)
     }
 }
 
 /// 登录请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
 
 /// 登录响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
     pub user_id: Uuid,
@@ -175,7 +256,7 @@ pub struct LoginResponse {
 }
 
 /// 注册请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RegisterRequest {
     pub email: String,
     pub password: String,
@@ -210,7 +291,7 @@ fn default_date_format() -> String {
 }
 
 /// 注册响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RegisterResponse {
     pub user_id: Uuid,
     pub email: String,
@@ -0,0 +1,151 @@
+//! 离线导入历史汇率
+//!
+//! 用法：
+//!   import_fx_history <file> [--format auto|ecb-csv|ecb-xml|csv] [--source NAME]
+//!                     [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--currencies USD,CNY]
+//!                     [--dry-run]
+//!
+//! ECB 历史文件下载地址：https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.zip
+//! （解压后得到 eurofxref-hist.csv）或 eurofxref-hist.xml。
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use jive_money_api::services::fx_history_import::{
+    parse_rates, FxHistoryImporter, FxImportFormat, FxImportOptions,
+};
+use sqlx::PgPool;
+use std::env;
+use std::fs;
+
+const USAGE: &str = "Usage: import_fx_history <file> [--format auto|ecb-csv|ecb-xml|csv] \
+[--source NAME] [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--currencies USD,CNY] [--dry-run]";
+
+struct Args {
+    path: String,
+    format: Option<FxImportFormat>,
+    options: FxImportOptions,
+    dry_run: bool,
+}
+
+fn parse_date_arg(flag: &str, value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("{} expects YYYY-MM-DD, got {}", flag, value))
+}
+
+fn parse_args() -> Result<Args> {
+    let mut path = None;
+    let mut format = None;
+    let mut options = FxImportOptions::default();
+    let mut dry_run = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .with_context(|| format!("{} requires a value\n{}", flag, USAGE))
+        };
+        match arg.as_str() {
+            "--format" => {
+                let raw = value("--format")?;
+                if !raw.eq_ignore_ascii_case("auto") {
+                    format = Some(raw.parse::<FxImportFormat>().map_err(anyhow::Error::msg)?);
+                }
+            }
+            "--source" => options.source = Some(value("--source")?),
+            "--since" => options.since = Some(parse_date_arg("--since", &value("--since")?)?),
+            "--until" => options.until = Some(parse_date_arg("--until", &value("--until")?)?),
+            "--currencies" => {
+                options.currencies = Some(
+                    value("--currencies")?
+                        .split(',')
+                        .map(|c| c.trim().to_uppercase())
+                        .filter(|c| !c.is_empty())
+                        .collect(),
+                )
+            }
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other if other.starts_with("--") => bail!("Unknown option {}\n{}", other, USAGE),
+            other => path = Some(other.to_string()),
+        }
+    }
+
+    let path = path.with_context(|| USAGE.to_string())?;
+    Ok(Args {
+        path,
+        format,
+        options,
+        dry_run,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let args = parse_args()?;
+    if args.path.to_ascii_lowercase().ends_with(".zip") {
+        bail!(
+            "Please unzip {} first (expects eurofxref-hist.csv/.xml)",
+            args.path
+        );
+    }
+
+    println!("📖 Reading rates from: {}", args.path);
+    let content =
+        fs::read_to_string(&args.path).with_context(|| format!("Failed to read {}", args.path))?;
+    let format = args
+        .format
+        .unwrap_or_else(|| FxImportFormat::detect(&content));
+    println!("🔍 Format: {}", format.as_str());
+
+    if args.dry_run {
+        let parsed = parse_rates(format, &content).map_err(anyhow::Error::msg)?;
+        let selected = args.options.select(parsed.rates);
+        println!(
+            "📊 Parsed {} rates, {} rejected",
+            selected.len(),
+            parsed.rejected
+        );
+        if let (Some(first), Some(last)) = (selected.first(), selected.last()) {
+            println!("  Date range: {} .. {}", first.date, last.date);
+        }
+        for error in &parsed.errors {
+            println!("  ⚠️  {}", error);
+        }
+        println!("Dry run, nothing written.");
+        return Ok(());
+    }
+
+    let database_url = env::var("DATABASE_URL").context("DATABASE_URL must be set")?;
+    println!("🔌 Connecting to database...");
+    let pool = PgPool::connect(&database_url)
+        .await
+        .context("Failed to connect to database")?;
+
+    println!("📥 Importing...");
+    let summary = FxHistoryImporter::new(pool)
+        .import_content(&content, Some(format), &args.options)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    println!("✅ Import completed (source: {})", summary.source);
+    println!("  Selected: {}", summary.selected);
+    println!("  Inserted: {}", summary.inserted);
+    println!("  Updated: {}", summary.updated);
+    println!("  Unchanged: {}", summary.unchanged);
+    println!("  Rejected: {}", summary.rejected);
+    if let (Some(start), Some(end)) = (summary.start_date, summary.end_date) {
+        println!("  Date range: {} .. {}", start, end);
+    }
+    println!("  Currency pairs: {}", summary.currency_pairs);
+    println!("  Rate changes recomputed: {}", summary.changes_recomputed);
+    for error in &summary.errors {
+        println!("  ⚠️  {}", error);
+    }
+    Ok(())
+}
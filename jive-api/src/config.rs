@@ -1,4 +1,7 @@
 use rust_decimal::Decimal;
+use std::time::Duration;
+
+use crate::middleware::metrics_guard::Cidr;
 
 #[derive(Debug, Clone)]
 pub struct TransactionConfig {
@@ -17,6 +20,23 @@ impl Default for TransactionConfig {
     }
 }
 
+/// 应用配置：启动时从环境变量读取一次，经 `AppState` 传给各服务
+#[derive(Debug, Clone, Default)]
+pub struct AppConfig {
+    pub login_security: LoginSecurityConfig,
+    pub email: EmailConfig,
+    pub password_reset: PasswordResetConfig,
+    pub fx_providers: FxProviderConfig,
+    pub rate_resolver: RateResolverConfig,
+    pub bank_connectors: BankConnectorConfig,
+    pub llm: LlmConfig,
+    pub classifier: ClassifierConfig,
+    pub notification: NotificationConfig,
+    pub push: PushConfig,
+    pub webhook: WebhookConfig,
+    pub docs: DocsConfig,
+}
+
 fn parse_bool_env(key: &str, default: bool) -> bool {
     match std::env::var(key) {
         Ok(v) => matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"),
@@ -24,3 +44,581 @@ fn parse_bool_env(key: &str, default: bool) -> bool {
     }
 }
 
+/// 登录安全配置：账户锁定（指数退避）、可信代理与可疑登录提醒
+#[derive(Debug, Clone)]
+pub struct LoginSecurityConfig {
+    /// 连续失败多少次后开始锁定
+    pub lockout_threshold: u32,
+    /// 首次锁定时长（秒），之后每多失败一次翻倍
+    pub lockout_base_secs: u64,
+    /// 锁定时长上限（秒）
+    pub lockout_max_secs: u64,
+    /// 可信反向代理网段；仅当直连地址命中时才采信 X-Forwarded-For
+    pub trusted_proxies: Vec<Cidr>,
+    /// 新设备/新位置登录或账户锁定时是否发送邮件提醒
+    pub alert_email_enabled: bool,
+    /// 过期会话保留天数；保留期内的会话用于识别新设备 / 新位置
+    pub session_retention_days: u32,
+}
+
+impl Default for LoginSecurityConfig {
+    fn default() -> Self {
+        Self {
+            lockout_threshold: parse_env("AUTH_LOCKOUT_THRESHOLD", 5),
+            lockout_base_secs: parse_env("AUTH_LOCKOUT_BASE_SECS", 60),
+            lockout_max_secs: parse_env("AUTH_LOCKOUT_MAX_SECS", 24 * 60 * 60),
+            trusted_proxies: std::env::var("TRUSTED_PROXIES")
+                .map(|v| v.split(',').filter_map(|s| Cidr::parse(s.trim())).collect())
+                .unwrap_or_default(),
+            alert_email_enabled: parse_bool_env("AUTH_LOGIN_ALERT_EMAIL", false),
+            session_retention_days: parse_env("AUTH_SESSION_RETENTION_DAYS", 90),
+        }
+    }
+}
+
+impl LoginSecurityConfig {
+    /// 根据连续失败次数计算锁定时长；未达到阈值时返回 None
+    pub fn lockout_duration(&self, consecutive_failures: u32) -> Option<Duration> {
+        if self.lockout_threshold == 0 || consecutive_failures < self.lockout_threshold {
+            return None;
+        }
+        let exponent = (consecutive_failures - self.lockout_threshold).min(20);
+        let secs = self
+            .lockout_base_secs
+            .saturating_mul(1u64 << exponent)
+            .min(self.lockout_max_secs);
+        Some(Duration::from_secs(secs))
+    }
+}
+
+/// 邮件发送后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailBackend {
+    /// 通过 SMTP 投递
+    Smtp,
+    /// 写入本地目录（.eml），用于开发与测试
+    File,
+    /// 打印到标准输出
+    Stdout,
+    /// 不发送（outbox 中的邮件保持 pending）
+    Disabled,
+}
+
+impl EmailBackend {
+    fn from_env_value(v: &str) -> Self {
+        match v.trim().to_ascii_lowercase().as_str() {
+            "smtp" => Self::Smtp,
+            "file" => Self::File,
+            "none" | "off" | "disabled" => Self::Disabled,
+            _ => Self::Stdout,
+        }
+    }
+}
+
+/// SMTP 连接加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// 明文连接后 STARTTLS 升级（587）
+    StartTls,
+    /// 隐式 TLS（465）
+    Tls,
+    /// 不加密（仅限本地 MailHog 等）
+    None,
+}
+
+/// 邮件配置
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub backend: EmailBackend,
+    /// 发件人，例如 "Jive Money <noreply@jive.money>"
+    pub from: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_tls: SmtpTls,
+    /// File 后端的输出目录
+    pub file_dir: String,
+    /// 默认语言（收件人无偏好时使用）
+    pub default_locale: String,
+    /// outbox 轮询间隔（秒）
+    pub outbox_interval_secs: u64,
+    /// 单封邮件最大尝试次数，超过后标记为 failed
+    pub outbox_max_attempts: u32,
+    /// 每次轮询最多处理的邮件数
+    pub outbox_batch_size: i64,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            backend: EmailBackend::from_env_value(
+                &std::env::var("EMAIL_BACKEND").unwrap_or_default(),
+            ),
+            from: std::env::var("EMAIL_FROM")
+                .unwrap_or_else(|_| "Jive Money <noreply@jive.money>".to_string()),
+            smtp_host: std::env::var("SMTP_HOST").unwrap_or_else(|_| "localhost".to_string()),
+            smtp_port: parse_env("SMTP_PORT", 587),
+            smtp_username: std::env::var("SMTP_USERNAME")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            smtp_password: std::env::var("SMTP_PASSWORD")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            smtp_tls: match std::env::var("SMTP_TLS")
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "tls" | "ssl" => SmtpTls::Tls,
+                "none" | "off" => SmtpTls::None,
+                _ => SmtpTls::StartTls,
+            },
+            file_dir: std::env::var("EMAIL_FILE_DIR").unwrap_or_else(|_| "./tmp/mail".to_string()),
+            default_locale: std::env::var("EMAIL_DEFAULT_LOCALE")
+                .unwrap_or_else(|_| "zh-CN".to_string()),
+            outbox_interval_secs: parse_env("EMAIL_OUTBOX_INTERVAL_SECS", 30),
+            outbox_max_attempts: parse_env("EMAIL_OUTBOX_MAX_ATTEMPTS", 8),
+            outbox_batch_size: parse_env("EMAIL_OUTBOX_BATCH_SIZE", 50),
+        }
+    }
+}
+
+/// 找回密码配置
+#[derive(Debug, Clone)]
+pub struct PasswordResetConfig {
+    /// 验证码 HMAC 密钥；验证码只有 10^6 种取值，不加密钥的哈希可被离线穷举
+    pub code_secret: String,
+}
+
+impl Default for PasswordResetConfig {
+    fn default() -> Self {
+        let code_secret = ["PASSWORD_RESET_SECRET", "JWT_SECRET"]
+            .iter()
+            .find_map(|key| std::env::var(key).ok().filter(|v| !v.trim().is_empty()))
+            .unwrap_or_else(|| {
+                if !cfg!(test) {
+                    eprintln!("WARNING: PASSWORD_RESET_SECRET not set; using insecure default key");
+                }
+                "insecure-dev-password-reset-secret".to_string()
+            });
+        Self { code_secret }
+    }
+}
+
+/// 汇率/加密货币价格数据源配置
+#[derive(Debug, Clone)]
+pub struct FxProviderConfig {
+    /// 法币汇率数据源顺序（按顺序降级）
+    pub fiat_order: Vec<String>,
+    /// 加密货币价格数据源顺序（按顺序降级）
+    pub crypto_order: Vec<String>,
+    /// 连续失败多少次后熔断
+    pub breaker_failure_threshold: u32,
+    /// 熔断后多久允许一次探测请求（秒）
+    pub breaker_cooldown_secs: u64,
+    /// 单次请求超时（秒）
+    pub request_timeout_secs: u64,
+    /// 本地数据源：JSON 文件路径（离线开发与测试）
+    pub local_file: Option<String>,
+    /// 本地数据源：返回同格式 JSON 的 HTTP 桩地址
+    pub local_url: Option<String>,
+    pub coinmarketcap_api_key: Option<String>,
+}
+
+impl Default for FxProviderConfig {
+    fn default() -> Self {
+        Self {
+            fiat_order: parse_list_env(
+                "FIAT_PROVIDER_ORDER",
+                "exchangerate-api,frankfurter,fxrates",
+            ),
+            crypto_order: parse_list_env(
+                "CRYPTO_PROVIDER_ORDER",
+                "coingecko,okx,gateio,coinmarketcap,binance,coincap",
+            ),
+            breaker_failure_threshold: parse_env("FX_BREAKER_FAILURE_THRESHOLD", 3),
+            breaker_cooldown_secs: parse_env("FX_BREAKER_COOLDOWN_SECS", 300),
+            request_timeout_secs: parse_env("FX_REQUEST_TIMEOUT_SECS", 10),
+            local_file: std::env::var("FX_LOCAL_FILE")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            local_url: std::env::var("FX_LOCAL_URL").ok().filter(|v| !v.is_empty()),
+            coinmarketcap_api_key: std::env::var("COINMARKETCAP_API_KEY")
+                .ok()
+                .filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+/// 汇率换算路径配置
+#[derive(Debug, Clone)]
+pub struct RateResolverConfig {
+    /// 参与换算的汇率最多可以比目标日期早多少天；0 表示不限制
+    pub max_rate_age_days: i64,
+    /// 换算路径最多经过多少段汇率
+    pub max_hops: usize,
+    /// 视为 1:1 挂钩的币种对，例如 USDT:USD
+    pub pegs: Vec<(String, String)>,
+}
+
+impl Default for RateResolverConfig {
+    fn default() -> Self {
+        Self {
+            max_rate_age_days: parse_env("FX_MAX_RATE_AGE_DAYS", 30),
+            max_hops: parse_env("FX_MAX_PATH_HOPS", 4),
+            pegs: parse_list_env("FX_PEGGED_CURRENCIES", "usdt:usd,usdc:usd")
+                .iter()
+                .filter_map(|pair| {
+                    let (a, b) = pair.split_once(':')?;
+                    Some((a.trim().to_uppercase(), b.trim().to_uppercase()))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 银行聚合连接（Plaid 等）配置
+#[derive(Debug, Clone)]
+pub struct BankConnectorConfig {
+    pub plaid_client_id: Option<String>,
+    pub plaid_secret: Option<String>,
+    /// sandbox / development / production
+    pub plaid_env: String,
+    /// 覆盖 Plaid API 地址（例如指向本地模拟机构服务器）
+    pub plaid_base_url: Option<String>,
+    /// 创建 Link token 时登记的 webhook 地址
+    pub webhook_url: Option<String>,
+    /// 启用内置的模拟机构（provider = "fake"），仅用于开发与测试
+    pub fake_enabled: bool,
+    /// 模拟机构 webhook 的 HMAC 密钥
+    pub fake_webhook_secret: String,
+    /// 单次请求超时（秒）
+    pub request_timeout_secs: u64,
+    /// webhook 时间戳允许的最大偏差（秒）
+    pub webhook_tolerance_secs: i64,
+}
+
+impl Default for BankConnectorConfig {
+    fn default() -> Self {
+        Self {
+            plaid_client_id: std::env::var("PLAID_CLIENT_ID")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            plaid_secret: std::env::var("PLAID_SECRET").ok().filter(|v| !v.is_empty()),
+            plaid_env: std::env::var("PLAID_ENV").unwrap_or_else(|_| "sandbox".to_string()),
+            plaid_base_url: std::env::var("PLAID_BASE_URL")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            webhook_url: std::env::var("BANK_WEBHOOK_URL")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            fake_enabled: parse_env("BANK_FAKE_ENABLED", false),
+            fake_webhook_secret: std::env::var("BANK_FAKE_WEBHOOK_SECRET")
+                .unwrap_or_else(|_| "fake-webhook-secret".to_string()),
+            request_timeout_secs: parse_env("BANK_REQUEST_TIMEOUT_SECS", 30),
+            webhook_tolerance_secs: parse_env("BANK_WEBHOOK_TOLERANCE_SECS", 300),
+        }
+    }
+}
+
+impl BankConnectorConfig {
+    /// Plaid API 地址
+    pub fn plaid_url(&self) -> String {
+        if let Some(url) = &self.plaid_base_url {
+            return url.trim_end_matches('/').to_string();
+        }
+        match self.plaid_env.to_ascii_lowercase().as_str() {
+            "production" => "https://production.plaid.com".to_string(),
+            "development" => "https://development.plaid.com".to_string(),
+            _ => "https://sandbox.plaid.com".to_string(),
+        }
+    }
+}
+
+/// 大模型后端（AI 分类与对话）配置
+///
+/// 支持任意 OpenAI 兼容的 `/chat/completions` 接口，包括 Ollama（`http://localhost:11434/v1`）
+/// 与 llama.cpp server（`http://localhost:8080/v1`）等本地部署。
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    /// openai（OpenAI 兼容接口）/ stub（确定性离线实现）/ disabled
+    pub provider: String,
+    pub base_url: String,
+    /// 本地模型服务通常不需要
+    pub api_key: Option<String>,
+    /// 分类使用的模型
+    pub categorize_model: String,
+    /// 对话使用的模型，未配置时与分类模型相同
+    pub chat_model: String,
+    /// 单次请求超时（秒）
+    pub request_timeout_secs: u64,
+    /// 每次请求发送的交易数量上限
+    pub categorize_batch_size: usize,
+    /// 置信度不低于该值时才自动写入分类
+    pub min_confidence: f64,
+    /// 单条对话消息中最多执行的工具调用轮数
+    pub max_tool_rounds: usize,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        let categorize_model =
+            std::env::var("LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+        Self {
+            provider: std::env::var("LLM_PROVIDER")
+                .unwrap_or_else(|_| "openai".to_string())
+                .trim()
+                .to_lowercase(),
+            base_url: std::env::var("LLM_BASE_URL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            api_key: std::env::var("LLM_API_KEY").ok().filter(|v| !v.is_empty()),
+            chat_model: std::env::var("LLM_CHAT_MODEL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| categorize_model.clone()),
+            categorize_model,
+            request_timeout_secs: parse_env("LLM_TIMEOUT_SECS", 60),
+            categorize_batch_size: parse_env("LLM_CATEGORIZE_BATCH_SIZE", 50),
+            min_confidence: parse_env("LLM_MIN_CONFIDENCE", 0.6),
+            max_tool_rounds: parse_env("LLM_MAX_TOOL_ROUNDS", 5),
+        }
+    }
+}
+
+impl LlmConfig {
+    /// 是否可用：OpenAI 官方接口必须配置密钥，自定义地址（本地模型）无需密钥
+    pub fn is_enabled(&self) -> bool {
+        match self.provider.as_str() {
+            "stub" => true,
+            "openai" => self.api_key.is_some() || !self.base_url.contains("api.openai.com"),
+            _ => false,
+        }
+    }
+}
+
+/// 离线分类模型（按家庭训练的朴素贝叶斯）配置
+#[derive(Debug, Clone)]
+pub struct ClassifierConfig {
+    /// 已学习样本少于该数量时只给建议，不自动写入分类
+    pub min_examples: u32,
+    /// 自动写入分类所需的最低置信度
+    pub min_confidence: f64,
+    /// 模型距上次训练超过该秒数时，查询前先增量训练
+    pub retrain_interval_secs: i64,
+}
+
+impl Default for ClassifierConfig {
+    fn default() -> Self {
+        Self {
+            min_examples: parse_env("CLASSIFIER_MIN_EXAMPLES", 20),
+            min_confidence: parse_env("CLASSIFIER_MIN_CONFIDENCE", 0.8),
+            retrain_interval_secs: parse_env("CLASSIFIER_RETRAIN_SECS", 60),
+        }
+    }
+}
+
+/// 通知投递（邮件、Webhook 渠道）配置
+#[derive(Debug, Clone)]
+pub struct NotificationConfig {
+    /// 投递队列轮询间隔（秒）
+    pub delivery_interval_secs: u64,
+    /// 每次轮询最多处理的投递数
+    pub delivery_batch_size: i64,
+    /// 每日 / 每周摘要在用户本地时间几点发送
+    pub digest_hour: u32,
+    /// Webhook 请求超时（秒）
+    pub webhook_timeout_secs: u64,
+    /// Webhook / 推送最大尝试次数，超过后标记为 failed
+    pub webhook_max_attempts: u32,
+    /// 是否生成周度 / 月度财务摘要
+    pub financial_digest_enabled: bool,
+    /// 财务摘要任务检查间隔（秒）；摘要在周一 / 每月 1 日的 `digest_hour` 之后生成
+    pub financial_digest_interval_secs: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            delivery_interval_secs: parse_env("NOTIFICATION_DELIVERY_INTERVAL_SECS", 30),
+            delivery_batch_size: parse_env("NOTIFICATION_DELIVERY_BATCH_SIZE", 100),
+            digest_hour: parse_env("NOTIFICATION_DIGEST_HOUR", 8u32).min(23),
+            webhook_timeout_secs: parse_env("NOTIFICATION_WEBHOOK_TIMEOUT_SECS", 10),
+            webhook_max_attempts: parse_env("NOTIFICATION_WEBHOOK_MAX_ATTEMPTS", 6),
+            financial_digest_enabled: parse_bool_env("FINANCIAL_DIGEST_ENABLED", true),
+            financial_digest_interval_secs: parse_env("FINANCIAL_DIGEST_INTERVAL_SECS", 3600),
+        }
+    }
+}
+
+/// 推送发送后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushBackend {
+    /// 按凭据启用 Web Push（VAPID）与 FCM
+    Live,
+    /// 所有设备都交给本地模拟推送网关，用于开发与测试
+    Mock,
+    /// 不推送
+    Disabled,
+}
+
+impl PushBackend {
+    fn from_env_value(v: &str) -> Self {
+        match v.trim().to_ascii_lowercase().as_str() {
+            "mock" => Self::Mock,
+            "none" | "off" | "disabled" => Self::Disabled,
+            _ => Self::Live,
+        }
+    }
+}
+
+/// 推送通知配置
+#[derive(Debug, Clone)]
+pub struct PushConfig {
+    pub backend: PushBackend,
+    /// VAPID 公钥（base64url，未压缩 P-256 点），也是浏览器订阅时的 applicationServerKey
+    pub vapid_public_key: Option<String>,
+    /// VAPID 私钥（base64url，32 字节）
+    pub vapid_private_key: Option<String>,
+    /// VAPID 联系方式（mailto: 或 https: URL）
+    pub vapid_subject: String,
+    /// FCM 服务账号 JSON：文件路径或 JSON 原文
+    pub fcm_credentials: Option<String>,
+    /// FCM HTTP v1 API 地址（可指向本地模拟推送网关）
+    pub fcm_endpoint: String,
+    /// 推送服务为离线设备保留消息的时长（秒）
+    pub ttl_secs: u64,
+    /// 单次请求超时（秒）
+    pub request_timeout_secs: u64,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            backend: PushBackend::from_env_value(
+                &std::env::var("PUSH_BACKEND").unwrap_or_default(),
+            ),
+            vapid_public_key: std::env::var("PUSH_VAPID_PUBLIC_KEY")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            vapid_private_key: std::env::var("PUSH_VAPID_PRIVATE_KEY")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            vapid_subject: std::env::var("PUSH_VAPID_SUBJECT")
+                .unwrap_or_else(|_| "mailto:noreply@jive.money".to_string()),
+            fcm_credentials: std::env::var("PUSH_FCM_CREDENTIALS")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            fcm_endpoint: std::env::var("PUSH_FCM_ENDPOINT")
+                .unwrap_or_else(|_| "https://fcm.googleapis.com".to_string()),
+            ttl_secs: parse_env("PUSH_TTL_SECS", 24 * 60 * 60),
+            request_timeout_secs: parse_env("PUSH_REQUEST_TIMEOUT_SECS", 10),
+        }
+    }
+}
+
+/// 家庭 Webhook 投递配置
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// 是否运行投递任务；关闭后事件仍会入队
+    pub enabled: bool,
+    /// 投递队列轮询间隔（秒）
+    pub delivery_interval_secs: u64,
+    /// 每次轮询最多处理的投递数
+    pub delivery_batch_size: i64,
+    /// 单次请求超时（秒）
+    pub timeout_secs: u64,
+    /// 最大尝试次数，超过后标记为 failed（可手动重新投递）
+    pub max_attempts: u32,
+    /// 每个家庭最多配置的地址数
+    pub max_endpoints: i64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: parse_bool_env("WEBHOOK_DELIVERY_ENABLED", true),
+            delivery_interval_secs: parse_env("WEBHOOK_DELIVERY_INTERVAL_SECS", 15),
+            delivery_batch_size: parse_env("WEBHOOK_DELIVERY_BATCH_SIZE", 100),
+            timeout_secs: parse_env("WEBHOOK_TIMEOUT_SECS", 10),
+            max_attempts: parse_env("WEBHOOK_MAX_ATTEMPTS", 8),
+            max_endpoints: parse_env("WEBHOOK_MAX_ENDPOINTS", 10),
+        }
+    }
+}
+
+/// 接口文档页面配置
+#[derive(Debug, Clone)]
+pub struct DocsConfig {
+    /// Swagger UI 静态资源目录（scripts/fetch_swagger_ui.sh 下载的固定版本），同源提供
+    pub swagger_ui_dir: String,
+}
+
+impl Default for DocsConfig {
+    fn default() -> Self {
+        Self {
+            swagger_ui_dir: std::env::var("SWAGGER_UI_DIR")
+                .unwrap_or_else(|_| "static/swagger-ui".to_string()),
+        }
+    }
+}
+
+fn parse_list_env(key: &str, default: &str) -> Vec<String> {
+    std::env::var(key)
+        .unwrap_or_else(|_| default.to_string())
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(threshold: u32, base: u64, max: u64) -> LoginSecurityConfig {
+        LoginSecurityConfig {
+            lockout_threshold: threshold,
+            lockout_base_secs: base,
+            lockout_max_secs: max,
+            trusted_proxies: vec![],
+            alert_email_enabled: false,
+            session_retention_days: 90,
+        }
+    }
+
+    #[test]
+    fn test_no_lockout_below_threshold() {
+        let cfg = config(5, 60, 3600);
+        assert_eq!(cfg.lockout_duration(0), None);
+        assert_eq!(cfg.lockout_duration(4), None);
+    }
+
+    #[test]
+    fn test_lockout_doubles_and_caps() {
+        let cfg = config(5, 60, 3600);
+        assert_eq!(cfg.lockout_duration(5), Some(Duration::from_secs(60)));
+        assert_eq!(cfg.lockout_duration(6), Some(Duration::from_secs(120)));
+        assert_eq!(cfg.lockout_duration(8), Some(Duration::from_secs(480)));
+        assert_eq!(cfg.lockout_duration(12), Some(Duration::from_secs(3600)));
+        assert_eq!(
+            cfg.lockout_duration(u32::MAX),
+            Some(Duration::from_secs(3600))
+        );
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_lockout() {
+        let cfg = config(0, 60, 3600);
+        assert_eq!(cfg.lockout_duration(100), None);
+    }
+}
@@ -37,6 +37,9 @@ pub enum ApiError {
     #[error("Cache error: {0}")]
     Cache(String),
 
+    #[error("Account locked, retry after {retry_after}s")]
+    AccountLocked { retry_after: u64 },
+
     #[error("Internal server error")]
     InternalServerError,
 }
@@ -102,6 +105,14 @@ impl IntoResponse for ApiError {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 ApiErrorResponse::new("CACHE_ERROR", msg),
             ),
+            ApiError::AccountLocked { retry_after } => (
+                StatusCode::LOCKED,
+                ApiErrorResponse::new(
+                    "ACCOUNT_LOCKED",
+                    "Too many failed login attempts. Account temporarily locked.",
+                )
+                .with_retry_after(retry_after),
+            ),
             ApiError::InternalServerError => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 ApiErrorResponse::new("INTERNAL_ERROR", "Internal server error"),
@@ -11,13 +11,17 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, QueryBuilder, Row};
 use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use crate::auth::Claims;
 use crate::error::{ApiError, ApiResult};
-use crate::models::{AccountMainType, AccountSubType};
+use crate::handlers::ledger_access::access_error;
+use crate::models::{AccountMainType, AccountSubType, Permission};
+use crate::services::{AuthService, LedgerAclService, LedgerResource};
 
 /// 账户查询参数
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct AccountQuery {
     pub ledger_id: Option<Uuid>,
     pub account_type: Option<String>,
@@ -27,7 +31,7 @@ pub struct AccountQuery {
 }
 
 /// 创建账户请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateAccountRequest {
     pub ledger_id: Uuid,
     pub bank_id: Option<Uuid>,
@@ -47,7 +51,7 @@ pub struct CreateAccountRequest {
 }
 
 /// 更新账户请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAccountRequest {
     pub bank_id: Option<Uuid>,
     pub name: Option<String>,
@@ -60,7 +64,7 @@ pub struct UpdateAccountRequest {
 }
 
 /// 账户响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AccountResponse {
     pub id: Uuid,
     pub ledger_id: Uuid,
@@ -83,7 +87,7 @@ pub struct AccountResponse {
 }
 
 /// 账户统计响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AccountStatistics {
     pub total_accounts: i64,
     pub total_assets: Decimal,
@@ -92,7 +96,7 @@ pub struct AccountStatistics {
     pub by_type: Vec<TypeStatistics>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TypeStatistics {
     pub account_type: String,
     pub count: i64,
@@ -100,10 +104,44 @@ pub struct TypeStatistics {
 }
 
 /// 获取账户列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts",
+    tag = "accounts",
+    params(AccountQuery),
+    responses((status = 200, description = "成功", body = Vec<AccountResponse>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn list_accounts(
+    claims: Claims,
     Query(params): Query<AccountQuery>,
     State(pool): State<PgPool>,
 ) -> ApiResult<Json<Vec<AccountResponse>>> {
+    let user_id = claims.user_id()?;
+    let acl = LedgerAclService::new(pool.clone());
+
+    // 可见账本：指定账本时单独校验，否则取当前家庭中有查看权限的账本
+    let ledger_ids = match params.ledger_id {
+        Some(ledger_id) => {
+            acl.authorize_user(user_id, ledger_id, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?;
+            vec![ledger_id]
+        }
+        None => {
+            let family_id = claims
+                .family_id
+                .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+            let ctx = AuthService::new(pool.clone())
+                .validate_family_access(user_id, family_id)
+                .await
+                .map_err(|_| ApiError::Forbidden)?;
+            acl.authorized_ledgers(&ctx, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?
+        }
+    };
+
     // 构建查询
     let mut query = QueryBuilder::new(
         "SELECT id, ledger_id, bank_id, name, account_type, account_number, institution_name,
@@ -112,14 +150,12 @@ pub async fn list_accounts(
          available_balance::numeric as available_balance,
          credit_limit::numeric as credit_limit,
          status, is_manual, color, icon, notes, created_at, updated_at
-         FROM accounts WHERE 1=1",
+         FROM accounts WHERE ledger_id = ANY(",
     );
+    query.push_bind(ledger_ids);
+    query.push(")");
 
     // 添加过滤条件
-    if let Some(ledger_id) = params.ledger_id {
-        query.push(" AND ledger_id = ");
-        query.push_bind(ledger_id);
-    }
 
     if let Some(account_type) = params.account_type {
         query.push(" AND account_type = ");
@@ -178,10 +214,30 @@ pub async fn list_accounts(
 }
 
 /// 获取单个账户
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/{id}",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = AccountResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_account(
+    claims: Claims,
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
 ) -> ApiResult<Json<AccountResponse>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user_resource(
+            user_id,
+            LedgerResource::Account,
+            id,
+            Permission::ViewAccounts,
+        )
+        .await
+        .map_err(access_error)?;
+
     let row = sqlx::query(
         r#"
         SELECT id, ledger_id, bank_id, name, account_type, account_number, institution_name,
@@ -248,10 +304,25 @@ pub async fn get_account(
 }
 
 /// 创建账户
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts",
+    tag = "accounts",
+    request_body = CreateAccountRequest,
+    responses((status = 200, description = "成功", body = AccountResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_account(
+    claims: Claims,
     State(pool): State<PgPool>,
     Json(req): Json<CreateAccountRequest>,
 ) -> ApiResult<Json<AccountResponse>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::CreateAccounts)
+        .await
+        .map_err(access_error)?;
+
     let main_type =
         AccountMainType::from_str(&req.account_main_type).map_err(ApiError::BadRequest)?;
     let sub_type = AccountSubType::from_str(&req.account_sub_type).map_err(ApiError::BadRequest)?;
@@ -367,11 +438,32 @@ pub async fn create_account(
 }
 
 /// 更新账户
+#[utoipa::path(
+    put,
+    path = "/api/v1/accounts/{id}",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateAccountRequest,
+    responses((status = 200, description = "成功", body = AccountResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_account(
+    claims: Claims,
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
     Json(req): Json<UpdateAccountRequest>,
 ) -> ApiResult<Json<AccountResponse>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user_resource(
+            user_id,
+            LedgerResource::Account,
+            id,
+            Permission::EditAccounts,
+        )
+        .await
+        .map_err(access_error)?;
+
     // 构建动态更新查询
     let mut query = QueryBuilder::new("UPDATE accounts SET updated_at = NOW()");
 
@@ -455,10 +547,30 @@ pub async fn update_account(
 }
 
 /// 删除账户（软删除）
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accounts/{id}",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_account(
+    claims: Claims,
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
 ) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user_resource(
+            user_id,
+            LedgerResource::Account,
+            id,
+            Permission::DeleteAccounts,
+        )
+        .await
+        .map_err(access_error)?;
+
     let result = sqlx::query!(
         r#"
         UPDATE accounts 
@@ -479,13 +591,27 @@ pub async fn delete_account(
 }
 
 /// 获取账户统计
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/statistics",
+    tag = "accounts",
+    params(AccountQuery),
+    responses((status = 200, description = "成功", body = AccountStatistics), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_account_statistics(
+    claims: Claims,
     Query(params): Query<AccountQuery>,
     State(pool): State<PgPool>,
 ) -> ApiResult<Json<AccountStatistics>> {
+    let user_id = claims.user_id()?;
     let ledger_id = params
         .ledger_id
         .ok_or(ApiError::BadRequest("ledger_id is required".to_string()))?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, ledger_id, Permission::ViewAccounts)
+        .await
+        .map_err(access_error)?;
 
     // 获取总体统计（使用动态查询以避免 SQLx 离线缓存耦合）
     let stats_row = sqlx::query(
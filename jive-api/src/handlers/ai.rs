@@ -0,0 +1,244 @@
+//! AI 接口：大模型交易分类、分类来源记录与财务助手对话
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::access_error;
+use crate::models::Permission;
+use crate::services::ai_service::{
+    AiChat, AiChatDetail, AiService, AiStatus, CategorizationRecord, CategorizeRequest,
+    CategorizeSummary, ChatMessageRequest, ChatReply, CreateChatRequest,
+};
+use crate::services::{AuthService, LedgerAclService, LedgerResource, ServiceError};
+use crate::AppState;
+
+/// 大模型接口错误单独映射，其余沿用账本权限的映射
+fn ai_error(e: ServiceError) -> ApiError {
+    match e {
+        ServiceError::ExternalApi { message } => ApiError::ExternalService(message),
+        other => access_error(other),
+    }
+}
+
+fn ai_service(state: &AppState) -> AiService {
+    AiService::new(state.pool.clone(), state.llm.clone(), &state.config.llm)
+}
+
+/// 当前家庭上下文：(用户 id, 家庭 id)
+async fn family_scope(pool: &PgPool, claims: &Claims) -> ApiResult<(Uuid, Uuid)> {
+    let user_id = claims.user_id()?;
+    let family_id = claims
+        .family_id
+        .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+    AuthService::new(pool.clone())
+        .validate_family_access(user_id, family_id)
+        .await
+        .map_err(|_| ApiError::Forbidden)?;
+    Ok((user_id, family_id))
+}
+
+/// GET /api/v1/ai/status
+///
+/// 是否已配置大模型，以及使用的提供方与模型
+#[utoipa::path(
+    get,
+    path = "/api/v1/ai/status",
+    tag = "ai",
+    responses((status = 200, description = "成功", body = AiStatus), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn ai_status(State(state): State<AppState>, claims: Claims) -> ApiResult<Json<AiStatus>> {
+    claims.user_id()?;
+    Ok(Json(ai_service(&state).status()))
+}
+
+/// POST /api/v1/ai/categorize
+///
+/// 为账本中的未分类交易生成分类建议；置信度足够时写入（`dry_run` 时只返回建议）
+#[utoipa::path(
+    post,
+    path = "/api/v1/ai/categorize",
+    tag = "ai",
+    request_body = CategorizeRequest,
+    responses((status = 200, description = "成功", body = CategorizeSummary), (status = 400, description = "AI 未启用或账本没有分类"), (status = 403, description = "无权限"), (status = 502, description = "模型接口错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn categorize_transactions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(req): Json<CategorizeRequest>,
+) -> ApiResult<Json<CategorizeSummary>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(state.pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::EditTransactions)
+        .await
+        .map_err(access_error)?;
+    let summary = ai_service(&state)
+        .auto_categorize_with_ai(user_id, &req)
+        .await
+        .map_err(ai_error)?;
+    Ok(Json(summary))
+}
+
+/// GET /api/v1/ai/categorizations/:transaction_id
+///
+/// 交易的自动分类记录（来源、模型、置信度），最新在前
+#[utoipa::path(
+    get,
+    path = "/api/v1/ai/categorizations/{transaction_id}",
+    tag = "ai",
+    params(("transaction_id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Vec<CategorizationRecord>), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn categorization_history(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(transaction_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<CategorizationRecord>>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(state.pool.clone())
+        .authorize_user_resource(
+            user_id,
+            LedgerResource::Transaction,
+            transaction_id,
+            Permission::ViewTransactions,
+        )
+        .await
+        .map_err(access_error)?;
+    let records = ai_service(&state)
+        .categorization_history(transaction_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(records))
+}
+
+/// GET /api/v1/ai/chats
+#[utoipa::path(
+    get,
+    path = "/api/v1/ai/chats",
+    tag = "ai",
+    responses((status = 200, description = "成功", body = Vec<AiChat>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_chats(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<Vec<AiChat>>> {
+    let (user_id, family_id) = family_scope(&state.pool, &claims).await?;
+    let chats = ai_service(&state)
+        .list_chats(user_id, family_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(chats))
+}
+
+/// POST /api/v1/ai/chats
+#[utoipa::path(
+    post,
+    path = "/api/v1/ai/chats",
+    tag = "ai",
+    request_body = CreateChatRequest,
+    responses((status = 201, description = "已创建", body = AiChat), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_chat(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(req): Json<CreateChatRequest>,
+) -> ApiResult<(StatusCode, Json<AiChat>)> {
+    let (user_id, family_id) = family_scope(&state.pool, &claims).await?;
+    let chat = ai_service(&state)
+        .create_chat(user_id, family_id, req.title)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(chat)))
+}
+
+/// GET /api/v1/ai/chats/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/ai/chats/{id}",
+    tag = "ai",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = AiChatDetail), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_chat(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<AiChatDetail>> {
+    let (user_id, family_id) = family_scope(&state.pool, &claims).await?;
+    let detail = ai_service(&state)
+        .get_chat(user_id, family_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(detail))
+}
+
+/// DELETE /api/v1/ai/chats/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/ai/chats/{id}",
+    tag = "ai",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "已删除"), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_chat(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let (user_id, family_id) = family_scope(&state.pool, &claims).await?;
+    ai_service(&state)
+        .delete_chat(user_id, family_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/ai/chats/:id/messages
+///
+/// 发送消息；助手可查询当前家庭中用户有权查看交易的账本
+#[utoipa::path(
+    post,
+    path = "/api/v1/ai/chats/{id}/messages",
+    tag = "ai",
+    params(("id" = Uuid, Path)),
+    request_body = ChatMessageRequest,
+    responses((status = 200, description = "成功", body = ChatReply), (status = 400, description = "AI 未启用"), (status = 404, description = "不存在"), (status = 502, description = "模型接口错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn send_chat_message(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ChatMessageRequest>,
+) -> ApiResult<Json<ChatReply>> {
+    let user_id = claims.user_id()?;
+    let family_id = claims
+        .family_id
+        .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+    let ctx = AuthService::new(state.pool.clone())
+        .validate_family_access(user_id, family_id)
+        .await
+        .map_err(|_| ApiError::Forbidden)?;
+    let ledger_ids = LedgerAclService::new(state.pool.clone())
+        .authorized_ledgers(&ctx, Permission::ViewTransactions)
+        .await
+        .map_err(access_error)?;
+    let reply = ai_service(&state)
+        .chat(user_id, family_id, id, ledger_ids, &req.content)
+        .await
+        .map_err(ai_error)?;
+    Ok(Json(reply))
+}
@@ -0,0 +1,64 @@
+//! OpenAPI 文档接口
+//!
+//! - `GET /api/v1/openapi.json`：由 handler 类型生成的 OpenAPI 3.1 文档
+//! - `GET /api/v1/docs`：内嵌 Swagger UI 页面
+//!
+//! Swagger UI 静态资源由 `scripts/fetch_swagger_ui.sh` 下载固定版本并校验完整性，
+//! 挂在 `/api/v1/docs/assets` 下同源提供，页面不加载任何第三方脚本。
+
+use axum::response::{Html, Json};
+use utoipa::OpenApi;
+
+use crate::openapi::ApiDoc;
+
+const DOCS_HTML: &str = r##"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+  <meta charset="utf-8" />
+  <meta name="viewport" content="width=device-width, initial-scale=1" />
+  <title>Jive Money API 文档</title>
+  <link rel="stylesheet" href="/api/v1/docs/assets/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="/api/v1/docs/assets/swagger-ui-bundle.js"></script>
+  <script>
+    window.onload = () => {
+      if (typeof SwaggerUIBundle === "undefined") {
+        document.getElementById("swagger-ui").textContent =
+          "Swagger UI 静态资源缺失：请运行 scripts/fetch_swagger_ui.sh，或直接查看 /api/v1/openapi.json";
+        return;
+      }
+      window.ui = SwaggerUIBundle({
+        url: "/api/v1/openapi.json",
+        dom_id: "#swagger-ui",
+        deepLinking: true,
+        persistAuthorization: true,
+      });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// OpenAPI 3.1 文档（JSON）
+#[utoipa::path(
+    get,
+    path = "/api/v1/openapi.json",
+    tag = "docs",
+    responses((status = 200, description = "OpenAPI 文档", body = Object))
+)]
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// 接口文档查看页面
+#[utoipa::path(
+    get,
+    path = "/api/v1/docs",
+    tag = "docs",
+    responses((status = 200, description = "Swagger UI 页面", content_type = "text/html", body = String))
+)]
+pub async fn docs_page() -> Html<&'static str> {
+    Html(DOCS_HTML)
+}
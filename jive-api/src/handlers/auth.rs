@@ -6,17 +6,26 @@ use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
-use axum::{extract::State, http::StatusCode, response::Json, Extension};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Json,
+    Extension,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::PgPool;
+use std::net::SocketAddr;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use super::family_handler::{ApiError as FamilyApiError, ApiResponse};
 use crate::auth::{Claims, LoginRequest, LoginResponse, RegisterRequest, RegisterResponse};
 use crate::error::{ApiError, ApiResult};
-use crate::services::AuthService;
+use crate::middleware::client_ip::resolve_client_ip;
+use crate::services::login_security_service::FailureOutcome;
+use crate::services::{AuthService, LoginSecurityService, PasswordResetService, ServiceError};
 use crate::{AppMetrics, AppState}; // for metrics
 
 /// 用户模型
@@ -35,6 +44,13 @@ pub struct User {
 }
 
 /// 增强的注册（创建个人Family）
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses((status = 200, description = "成功", body = RegisterResponse))
+)]
 pub async fn register_with_family(
     State(pool): State<PgPool>,
     Json(req): Json<RegisterRequest>,
@@ -199,11 +215,29 @@ pub async fn register(
 }
 
 /// 用户登录
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses((status = 200, description = "成功", body = Object))
+)]
 pub async fn login(
     State(state): State<crate::AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Json(req): Json<LoginRequest>,
 ) -> ApiResult<Json<Value>> {
     let pool = &state.pool;
+    let security = LoginSecurityService::new(pool.clone(), &state.config);
+    let client_ip = resolve_client_ip(
+        &headers,
+        connect_info.map(|ConnectInfo(addr)| addr.ip()),
+        &state.config.login_security.trusted_proxies,
+    );
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
     // 允许在输入为“superadmin”时映射为统一邮箱（便于本地/测试环境）
     // 不影响密码校验，仅做标识规范化
     let mut login_input = req.email.trim().to_string();
@@ -289,6 +323,16 @@ pub async fn login(
         return Err(ApiError::Forbidden);
     }
 
+    // 账户锁定期间直接拒绝，不再校验密码
+    if let Some(retry_after) = security
+        .check_lockout(user.id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+    {
+        state.metrics.inc_login_locked();
+        return Err(ApiError::AccountLocked { retry_after });
+    }
+
     // 验证密码（调试信息仅在 debug 构建下输出）
     #[cfg(debug_assertions)]
     {
@@ -310,7 +354,7 @@ pub async fn login(
         .map(|v| matches!(v.as_str(), "1" | "true" | "TRUE"))
         .unwrap_or(true);
 
-    if hash.starts_with("$argon2") {
+    let verified = if hash.starts_with("$argon2") {
         let parsed_hash = PasswordHash::new(hash).map_err(|e| {
             #[cfg(debug_assertions)]
             println!("DEBUG[login]: failed to parse Argon2 hash: {:?}", e);
@@ -320,16 +364,12 @@ pub async fn login(
         let argon2 = Argon2::default();
         argon2
             .verify_password(req.password.as_bytes(), &parsed_hash)
-            .map_err(|_| ApiError::Unauthorized)?;
+            .is_ok()
     } else if hash.starts_with("$2") {
         // bcrypt format ($2a$, $2b$, $2y$)
         let ok = bcrypt::verify(&req.password, hash).unwrap_or(false);
-        if !ok {
-            state.metrics.increment_login_fail();
-            return Err(ApiError::Unauthorized);
-        }
 
-        if enable_rehash {
+        if ok && enable_rehash {
             // Password rehash: transparently upgrade bcrypt to Argon2id on successful login
             // Non-blocking: failures only logged.
             let argon2 = Argon2::default();
@@ -361,23 +401,35 @@ pub async fn login(
                 }
             }
         }
+        ok
     } else {
         // Unknown format: try Argon2 parse as best-effort, otherwise unauthorized
         match PasswordHash::new(hash) {
-            Ok(parsed) => {
-                let argon2 = Argon2::default();
-                argon2
-                    .verify_password(req.password.as_bytes(), &parsed)
-                    .map_err(|_| {
-                        state.metrics.increment_login_fail();
-                        ApiError::Unauthorized
-                    })?;
-            }
-            Err(_) => {
-                state.metrics.increment_login_fail();
-                return Err(ApiError::Unauthorized);
-            }
+            Ok(parsed) => Argon2::default()
+                .verify_password(req.password.as_bytes(), &parsed)
+                .is_ok(),
+            Err(_) => false,
         }
+    };
+
+    if !verified {
+        state.metrics.increment_login_fail();
+        let outcome = security
+            .record_failure(user.id, &user.email, client_ip, user_agent)
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        return Err(match outcome {
+            FailureOutcome::Locked { retry_after } => ApiError::AccountLocked { retry_after },
+            FailureOutcome::Recorded { .. } => ApiError::Unauthorized,
+        });
+    }
+
+    // 重置失败计数并记录会话；可疑登录检测失败不影响登录
+    if let Err(e) = security
+        .record_success(user.id, &user.email, client_ip, user_agent)
+        .await
+    {
+        tracing::warn!(user_id=%user.id, error=?e, "failed to record login session");
     }
 
     // 获取用户的family_id（如果有）
@@ -432,6 +484,13 @@ pub async fn login(
 }
 
 /// 刷新令牌
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    tag = "auth",
+    responses((status = 200, description = "成功", body = LoginResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn refresh_token(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -471,6 +530,13 @@ pub async fn refresh_token(
 }
 
 /// 获取当前用户信息
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/user",
+    tag = "auth",
+    responses((status = 200, description = "成功", body = UserProfile), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_current_user(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -513,6 +579,14 @@ pub async fn get_current_user(
 }
 
 /// 更新用户信息
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/user",
+    tag = "auth",
+    request_body = UpdateUserRequest,
+    responses((status = 200, description = "成功"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_user(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -533,6 +607,14 @@ pub async fn update_user(
 }
 
 /// 修改密码
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password",
+    tag = "auth",
+    request_body = ChangePasswordRequest,
+    responses((status = 200, description = "成功"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn change_password(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -611,6 +693,96 @@ pub async fn change_password(
     Ok(StatusCode::OK)
 }
 
+/// 忘记密码：发送重置验证码
+///
+/// 无论邮箱是否注册都返回相同响应，避免账户枚举
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/forgot",
+    tag = "auth",
+    request_body = ForgotPasswordRequest,
+    responses((status = 200, description = "成功", body = Object))
+)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(req): Json<ForgotPasswordRequest>,
+) -> ApiResult<Json<Value>> {
+    let client_ip = resolve_client_ip(
+        &headers,
+        connect_info.map(|ConnectInfo(addr)| addr.ip()),
+        &state.config.login_security.trusted_proxies,
+    );
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
+
+    let service = PasswordResetService::new(state.pool.clone(), state.redis.clone(), &state.config);
+    if let Err(e) = service
+        .request_reset(&req.email, client_ip, user_agent)
+        .await
+    {
+        tracing::error!(error = ?e, "password reset request failed");
+        return Err(ApiError::InternalServerError);
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "如果该邮箱已注册，重置验证码将发送至该邮箱",
+    })))
+}
+
+/// 使用验证码重置密码；成功后该用户所有已登录会话失效
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/reset",
+    tag = "auth",
+    request_body = ResetPasswordRequest,
+    responses((status = 200, description = "成功", body = Object))
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> ApiResult<Json<Value>> {
+    let client_ip = resolve_client_ip(
+        &headers,
+        connect_info.map(|ConnectInfo(addr)| addr.ip()),
+        &state.config.login_security.trusted_proxies,
+    );
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
+
+    let service = PasswordResetService::new(state.pool.clone(), state.redis.clone(), &state.config);
+    match service
+        .reset_password(
+            &req.email,
+            &req.code,
+            &req.new_password,
+            client_ip,
+            user_agent,
+        )
+        .await
+    {
+        Ok(_) => {
+            state.metrics.inc_password_reset();
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "message": "密码已重置，请使用新密码重新登录",
+            })))
+        }
+        Err(ServiceError::ValidationError(msg)) => Err(ApiError::ValidationError(msg)),
+        Err(ServiceError::AuthenticationError(msg)) => Err(ApiError::BadRequest(msg)),
+        Err(e) => {
+            tracing::error!(error = ?e, "password reset failed");
+            Err(ApiError::InternalServerError)
+        }
+    }
+}
+
 /// 获取用户上下文（包含所有Family）
 pub async fn get_user_context(
     State(pool): State<PgPool>,
@@ -625,7 +797,7 @@ pub async fn get_user_context(
 }
 
 /// 用户信息响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserProfile {
     pub id: Uuid,
     pub email: String,
@@ -637,25 +809,47 @@ pub struct UserProfile {
 }
 
 /// 更新用户请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserRequest {
     pub name: Option<String>,
 }
 
 /// 修改密码请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ChangePasswordRequest {
     pub old_password: String,
     pub new_password: String,
 }
 
+/// 忘记密码请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+/// 重置密码请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub email: String,
+    pub code: String,
+    pub new_password: String,
+}
+
 // Delete user account with verification
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct DeleteAccountRequest {
     pub verification_code: String,
     pub confirm_delete: bool, // Extra confirmation
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/delete",
+    tag = "auth",
+    request_body = DeleteAccountRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_account(
     State(pool): State<PgPool>,
     State(redis): State<Option<redis::aio::ConnectionManager>>,
@@ -825,7 +1019,7 @@ pub async fn delete_account(
 }
 
 /// Update avatar request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAvatarRequest {
     pub avatar_type: String,
     pub avatar_data: Option<String>,
@@ -834,6 +1028,14 @@ pub struct UpdateAvatarRequest {
 }
 
 /// Update user avatar
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/avatar",
+    tag = "auth",
+    request_body = UpdateAvatarRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_avatar(
     State(pool): State<PgPool>,
     claims: Claims,
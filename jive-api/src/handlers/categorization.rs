@@ -0,0 +1,143 @@
+//! 本地分类模型接口：新建交易时的分类建议、离线自动分类与模型训练
+
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::config::AppConfig;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::access_error;
+use crate::models::Permission;
+use crate::services::ai_service::{CategorizeRequest, CategorizeSummary};
+use crate::services::category_suggestion_service::{
+    CategorySuggestionService, ModelStatus, SuggestionQuery, SuggestionResponse, TrainQuery,
+    TrainReport,
+};
+use crate::services::{AuthService, LedgerAclService};
+
+/// 当前家庭上下文，并要求具备指定权限
+async fn family_with_permission(
+    pool: &PgPool,
+    claims: &Claims,
+    permission: Permission,
+) -> ApiResult<Uuid> {
+    let user_id = claims.user_id()?;
+    let family_id = claims
+        .family_id
+        .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+    let ctx = AuthService::new(pool.clone())
+        .validate_family_access(user_id, family_id)
+        .await
+        .map_err(|_| ApiError::Forbidden)?;
+    ctx.require_permission(permission).map_err(access_error)?;
+    Ok(family_id)
+}
+
+/// GET /api/v1/transactions/suggestions
+///
+/// 按描述、收款方、金额与日期给出分类建议（置信度降序）；未填收款方时同时建议收款方
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions/suggestions",
+    tag = "categorization",
+    params(SuggestionQuery),
+    responses((status = 200, description = "成功", body = SuggestionResponse), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn suggest_for_transaction(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Query(query): Query<SuggestionQuery>,
+) -> ApiResult<Json<SuggestionResponse>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, query.ledger_id, Permission::CreateTransactions)
+        .await
+        .map_err(access_error)?;
+    let suggestions = CategorySuggestionService::new(pool, &config.classifier)
+        .suggest(&query)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(suggestions))
+}
+
+/// POST /api/v1/categorization/auto
+///
+/// 用本地模型为未分类交易分类；模型样本足够且置信度达到门槛时写入（`dry_run` 时只返回建议）
+#[utoipa::path(
+    post,
+    path = "/api/v1/categorization/auto",
+    tag = "categorization",
+    request_body = CategorizeRequest,
+    responses((status = 200, description = "成功", body = CategorizeSummary), (status = 400, description = "个人账本"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn auto_categorize(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Json(req): Json<CategorizeRequest>,
+) -> ApiResult<Json<CategorizeSummary>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::EditTransactions)
+        .await
+        .map_err(access_error)?;
+    let summary = CategorySuggestionService::new(pool, &config.classifier)
+        .auto_categorize(user_id, &req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(summary))
+}
+
+/// GET /api/v1/categorization/model
+#[utoipa::path(
+    get,
+    path = "/api/v1/categorization/model",
+    tag = "categorization",
+    responses((status = 200, description = "成功", body = ModelStatus), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn model_status(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+) -> ApiResult<Json<ModelStatus>> {
+    let family_id = family_with_permission(&pool, &claims, Permission::ViewCategories).await?;
+    let status = CategorySuggestionService::new(pool, &config.classifier)
+        .status(family_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(status))
+}
+
+/// POST /api/v1/categorization/model/train
+///
+/// 立即增量训练；`full=true` 时丢弃现有模型，从全部历史重新训练
+#[utoipa::path(
+    post,
+    path = "/api/v1/categorization/model/train",
+    tag = "categorization",
+    params(TrainQuery),
+    responses((status = 200, description = "成功", body = TrainReport), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn train_model(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Query(query): Query<TrainQuery>,
+) -> ApiResult<Json<TrainReport>> {
+    let family_id = family_with_permission(&pool, &claims, Permission::ManageCategories).await?;
+    let report = CategorySuggestionService::new(pool, &config.classifier)
+        .train(family_id, query.full.unwrap_or(false))
+        .await
+        .map_err(access_error)?;
+    Ok(Json(report))
+}
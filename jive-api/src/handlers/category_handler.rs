@@ -6,17 +6,22 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::auth::Claims;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::access_error;
+use crate::models::permission::Permission;
+use crate::services::{AuthService, LedgerAclService, LedgerResource};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct ListParams {
     pub ledger_id: Option<Uuid>,
     pub classification: Option<String>, // expense|income|transfer
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CategoryDto {
     pub id: Uuid,
     pub ledger_id: Uuid,
@@ -30,7 +35,7 @@ pub struct CategoryDto {
     pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCategoryRequest {
     pub ledger_id: Uuid,
     pub name: String,
@@ -40,7 +45,7 @@ pub struct CreateCategoryRequest {
     pub parent_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCategoryRequest {
     pub name: Option<String>,
     pub color: Option<String>,
@@ -49,41 +54,64 @@ pub struct UpdateCategoryRequest {
     pub parent_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ReorderItem {
     pub id: Uuid,
     pub position: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ReorderRequest {
     pub items: Vec<ReorderItem>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/categories",
+    tag = "categories",
+    params(ListParams),
+    responses((status = 200, description = "成功", body = Vec<CategoryDto>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn list_categories(
     claims: Claims,
     State(pool): State<PgPool>,
     Query(params): Query<ListParams>,
-) -> Result<Json<Vec<CategoryDto>>, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
+) -> ApiResult<Json<Vec<CategoryDto>>> {
+    let user_id = claims.user_id()?;
+    let acl = LedgerAclService::new(pool.clone());
+    let ledger_ids = match params.ledger_id {
+        Some(ledger) => {
+            acl.authorize_user(user_id, ledger, Permission::ViewCategories)
+                .await
+                .map_err(access_error)?;
+            vec![ledger]
+        }
+        None => {
+            let family_id = claims
+                .family_id
+                .ok_or_else(|| ApiError::BadRequest("ledger_id is required".into()))?;
+            let ctx = AuthService::new(pool.clone())
+                .validate_family_access(user_id, family_id)
+                .await
+                .map_err(|_| ApiError::Forbidden)?;
+            acl.authorized_ledgers(&ctx, Permission::ViewCategories)
+                .await
+                .map_err(access_error)?
+        }
+    };
 
     let mut query = sqlx::QueryBuilder::new(
         "SELECT id, ledger_id, name, color, icon, classification, parent_id, position, usage_count, last_used_at \
-         FROM categories WHERE is_deleted = false"
+         FROM categories WHERE is_deleted = false AND ledger_id = ANY("
     );
-    if let Some(ledger) = params.ledger_id {
-        query.push(" AND ledger_id = ").push_bind(ledger);
-    }
+    query.push_bind(ledger_ids).push(")");
     if let Some(classif) = params.classification {
         query.push(" AND classification = ").push_bind(classif);
     }
     query.push(" ORDER BY parent_id NULLS FIRST, position ASC, LOWER(name)");
 
-    let rows = query
-        .build()
-        .fetch_all(&pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rows = query.build().fetch_all(&pool).await?;
     let mut items = Vec::with_capacity(rows.len());
     for r in rows {
         items.push(CategoryDto {
@@ -102,12 +130,24 @@ pub async fn list_categories(
     Ok(Json(items))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/categories",
+    tag = "categories",
+    request_body = CreateCategoryRequest,
+    responses((status = 200, description = "成功", body = CategoryDto), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_category(
     claims: Claims,
     State(pool): State<PgPool>,
     Json(req): Json<CreateCategoryRequest>,
-) -> Result<Json<CategoryDto>, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
+) -> ApiResult<Json<CategoryDto>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::ManageCategories)
+        .await
+        .map_err(access_error)?;
 
     let rec = sqlx::query(
         r#"INSERT INTO categories (id, ledger_id, name, color, icon, classification, parent_id, position, usage_count)
@@ -121,7 +161,7 @@ pub async fn create_category(
     .bind(&req.icon)
     .bind(&req.classification)
     .bind(req.parent_id)
-    .fetch_one(&pool).await.map_err(|e|{ eprintln!("create_category err: {:?}", e); StatusCode::BAD_REQUEST })?;
+    .fetch_one(&pool).await.map_err(|e|{ eprintln!("create_category err: {:?}", e); ApiError::BadRequest(e.to_string()) })?;
 
     Ok(Json(CategoryDto {
         id: rec.get("id"),
@@ -137,13 +177,31 @@ pub async fn create_category(
     }))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/categories/{id}",
+    tag = "categories",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateCategoryRequest,
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_category(
     claims: Claims,
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateCategoryRequest>,
-) -> Result<StatusCode, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user_resource(
+            user_id,
+            LedgerResource::Category,
+            id,
+            Permission::ManageCategories,
+        )
+        .await
+        .map_err(access_error)?;
 
     let mut qb = sqlx::QueryBuilder::new("UPDATE categories SET updated_at = NOW()");
     if let Some(name) = req.name {
@@ -166,80 +224,126 @@ pub async fn update_category(
         .build()
         .execute(&pool)
         .await
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
     if res.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(ApiError::NotFound("Category not found".into()));
     }
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/categories/{id}",
+    tag = "categories",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_category(
     claims: Claims,
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user_resource(
+            user_id,
+            LedgerResource::Category,
+            id,
+            Permission::ManageCategories,
+        )
+        .await
+        .map_err(access_error)?;
     // MVP: forbid deletion if used
     let in_use: (i64,) = sqlx::query_as("SELECT COUNT(1) FROM transactions WHERE category_id = $1")
         .bind(id)
         .fetch_one(&pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .await?;
     if in_use.0 > 0 {
-        return Err(StatusCode::CONFLICT);
+        return Err(ApiError::BadRequest(
+            "Category is used by transactions".into(),
+        ));
     }
     let res = sqlx::query("UPDATE categories SET is_deleted=true, deleted_at=NOW() WHERE id=$1")
         .bind(id)
         .execute(&pool)
         .await
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
     if res.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(ApiError::NotFound("Category not found".into()));
     }
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/categories/reorder",
+    tag = "categories",
+    request_body = ReorderRequest,
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn reorder_categories(
     claims: Claims,
     State(pool): State<PgPool>,
     Json(req): Json<ReorderRequest>,
-) -> Result<StatusCode, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    // 涉及的每个账本都需要分类管理权限
+    let ids: Vec<Uuid> = req.items.iter().map(|i| i.id).collect();
+    let ledgers: Vec<Uuid> =
+        sqlx::query_scalar("SELECT DISTINCT ledger_id FROM categories WHERE id = ANY($1)")
+            .bind(&ids)
+            .fetch_all(&pool)
+            .await?;
+    let acl = LedgerAclService::new(pool.clone());
+    for ledger_id in ledgers {
+        acl.authorize_user(user_id, ledger_id, Permission::ManageCategories)
+            .await
+            .map_err(access_error)?;
+    }
+    let mut tx = pool.begin().await?;
     for item in req.items {
         sqlx::query("UPDATE categories SET position=$1, updated_at=NOW() WHERE id=$2")
             .bind(item.position)
             .bind(item.id)
             .execute(&mut *tx)
             .await
-            .map_err(|_| StatusCode::BAD_REQUEST)?;
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
     }
-    tx.commit()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ImportTemplateRequest {
     pub ledger_id: Uuid,
     pub template_id: Uuid,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/categories/import-template",
+    tag = "categories",
+    request_body = ImportTemplateRequest,
+    responses((status = 200, description = "成功", body = CategoryDto), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn import_template(
     claims: Claims,
     State(pool): State<PgPool>,
     Json(req): Json<ImportTemplateRequest>,
-) -> Result<Json<CategoryDto>, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
+) -> ApiResult<Json<CategoryDto>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::ManageCategories)
+        .await
+        .map_err(access_error)?;
 
     let tpl = sqlx::query(
         r#"SELECT id, name, name_en, name_zh, classification, color, icon, version FROM system_category_templates WHERE id = $1 AND is_active = true"#
-    ).bind(req.template_id).fetch_optional(&pool).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-     .ok_or(StatusCode::NOT_FOUND)?;
+    ).bind(req.template_id).fetch_optional(&pool).await?
+     .ok_or_else(|| ApiError::NotFound("Template not found".into()))?;
 
     let id = Uuid::new_v4();
     let rec = sqlx::query(
@@ -257,7 +361,7 @@ pub async fn import_template(
     .bind::<String>(tpl.get("classification"))
     .bind::<Uuid>(tpl.get("id"))
     .bind::<String>(tpl.get("version"))
-    .fetch_one(&pool).await.map_err(|e|{ eprintln!("import_template err: {:?}", e); StatusCode::BAD_REQUEST })?;
+    .fetch_one(&pool).await.map_err(|e|{ eprintln!("import_template err: {:?}", e); ApiError::BadRequest(e.to_string()) })?;
 
     Ok(Json(CategoryDto {
         id: rec.get("id"),
@@ -275,7 +379,7 @@ pub async fn import_template(
 
 // -------- Batch import from system templates --------
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ImportOverride {
     pub name: Option<String>,
     pub color: Option<String>,
@@ -284,14 +388,14 @@ pub struct ImportOverride {
     pub parent_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ImportItem {
     pub template_id: Uuid,
     #[serde(default)]
     pub overrides: Option<ImportOverride>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct BatchImportRequest {
     pub ledger_id: Uuid,
     #[serde(default)]
@@ -308,7 +412,7 @@ pub struct BatchImportRequest {
     pub dry_run: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BatchImportResult {
     pub imported: i32,
     pub skipped: i32,
@@ -318,7 +422,7 @@ pub struct BatchImportResult {
     pub details: Vec<ImportActionDetail>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ImportActionKind {
     Imported,
@@ -328,7 +432,7 @@ pub enum ImportActionKind {
     Failed,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ImportActionDetail {
     pub template_id: Uuid,
     pub action: ImportActionKind,
@@ -341,12 +445,24 @@ pub struct ImportActionDetail {
     pub reason: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/categories/import",
+    tag = "categories",
+    request_body = BatchImportRequest,
+    responses((status = 200, description = "成功", body = BatchImportResult), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn batch_import_templates(
     claims: Claims,
     State(pool): State<PgPool>,
     Json(req): Json<BatchImportRequest>,
-) -> Result<Json<BatchImportResult>, StatusCode> {
-    let _user_id = claims.user_id().map_err(|_| StatusCode::UNAUTHORIZED)?;
+) -> ApiResult<Json<BatchImportResult>> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::ManageCategories)
+        .await
+        .map_err(access_error)?;
 
     // Normalize request into items
     let mut items: Vec<ImportItem> = Vec::new();
@@ -363,7 +479,7 @@ pub async fn batch_import_templates(
             .collect();
     }
     if items.is_empty() {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(ApiError::BadRequest("No templates to import".into()));
     }
 
     // Resolve conflict strategy
@@ -423,7 +539,7 @@ pub async fn batch_import_templates(
         // First, check existence by name (case-insensitive) for active categories within ledger
         let exists: Option<(Uuid,)> = sqlx::query_as(
             "SELECT id FROM categories WHERE ledger_id=$1 AND LOWER(name)=LOWER($2) AND is_deleted=false LIMIT 1"
-        ).bind(req.ledger_id).bind(&name).fetch_optional(&pool).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        ).bind(req.ledger_id).bind(&name).fetch_optional(&pool).await?;
 
         if let Some((existing_id,)) = exists {
             match strategy.as_str() {
@@ -449,11 +565,11 @@ pub async fn batch_import_templates(
                         .bind(&icon)
                         .bind(&classification)
                         .bind(existing_id)
-                        .execute(&pool).await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                        .execute(&pool).await.map_err(|e| ApiError::BadRequest(e.to_string()))?;
                         // Return updated row
                         let row = sqlx::query(
                             "SELECT id, ledger_id, name, color, icon, classification, parent_id, position, usage_count, last_used_at FROM categories WHERE id=$1"
-                        ).bind(existing_id).fetch_one(&pool).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                        ).bind(existing_id).fetch_one(&pool).await?;
                         result_items.push(CategoryDto {
                             id: row.get("id"),
                             ledger_id: row.get("ledger_id"),
@@ -486,7 +602,7 @@ pub async fn batch_import_templates(
                         let candidate = format!("{} ({})", base, suffix);
                         let taken: Option<(Uuid,)> = sqlx::query_as(
                             "SELECT id FROM categories WHERE ledger_id=$1 AND LOWER(name)=LOWER($2) AND is_deleted=false LIMIT 1"
-                        ).bind(req.ledger_id).bind(&candidate).fetch_optional(&pool).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                        ).bind(req.ledger_id).bind(&candidate).fetch_optional(&pool).await?;
                         if taken.is_none() {
                             name = candidate;
                             break;
@@ -0,0 +1,308 @@
+//! 银行连接 API：Link 授权、连接管理、手动同步、重新授权与机构 webhook
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::{access_error, authorize_account};
+use crate::models::Permission;
+use crate::services::bank_connectors::LinkToken;
+use crate::services::bank_sync_service::{
+    BankConnection, BankConnectionDetail, BankSyncService, CreateConnectionRequest,
+    CreateLinkTokenRequest, LinkConnectionAccountRequest, SyncSummary,
+};
+use crate::services::{AuthService, LedgerAclService, ServiceError};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ConnectionQuery {
+    pub ledger_id: Option<Uuid>,
+}
+
+fn sync_service(state: &AppState) -> BankSyncService {
+    BankSyncService::new(
+        state.pool.clone(),
+        state.bank_connectors.clone(),
+        state.config.clone(),
+        state.ws_manager.clone(),
+    )
+}
+
+/// 读取连接并校验其账本权限，返回用户 id
+async fn authorize_connection(
+    state: &AppState,
+    claims: &Claims,
+    connection_id: Uuid,
+    permission: Permission,
+) -> ApiResult<Uuid> {
+    let user_id = claims.user_id()?;
+    let connection = sync_service(state)
+        .get_connection(connection_id)
+        .await
+        .map_err(access_error)?;
+    LedgerAclService::new(state.pool.clone())
+        .authorize_user(user_id, connection.ledger_id, permission)
+        .await
+        .map_err(access_error)?;
+    Ok(user_id)
+}
+
+/// GET /api/v1/connections
+#[utoipa::path(
+    get,
+    path = "/api/v1/connections",
+    tag = "connections",
+    params(ConnectionQuery),
+    responses((status = 200, description = "成功", body = Vec<BankConnection>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_connections(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ConnectionQuery>,
+) -> ApiResult<Json<Vec<BankConnection>>> {
+    let user_id = claims.user_id()?;
+    let acl = LedgerAclService::new(state.pool.clone());
+    let ledger_ids = match query.ledger_id {
+        Some(ledger_id) => {
+            acl.authorize_user(user_id, ledger_id, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?;
+            vec![ledger_id]
+        }
+        None => {
+            let family_id = claims
+                .family_id
+                .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+            let ctx = AuthService::new(state.pool.clone())
+                .validate_family_access(user_id, family_id)
+                .await
+                .map_err(|_| ApiError::Forbidden)?;
+            acl.authorized_ledgers(&ctx, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?
+        }
+    };
+    let connections = sync_service(&state)
+        .list_connections(&ledger_ids)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(connections))
+}
+
+/// POST /api/v1/connections/link-token
+///
+/// 创建 Link token，客户端用它打开机构授权界面
+#[utoipa::path(
+    post,
+    path = "/api/v1/connections/link-token",
+    tag = "connections",
+    request_body = CreateLinkTokenRequest,
+    responses((status = 200, description = "成功", body = LinkToken), (status = 400, description = "数据源未启用"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_link_token(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(req): Json<CreateLinkTokenRequest>,
+) -> ApiResult<Json<LinkToken>> {
+    let user_id = claims.user_id()?;
+    let token = sync_service(&state)
+        .create_link_token(user_id, &req.provider)
+        .await
+        .map_err(connector_error)?;
+    Ok(Json(token))
+}
+
+/// POST /api/v1/connections
+///
+/// 用 public token 建立连接，导入机构账户并做首次同步
+#[utoipa::path(
+    post,
+    path = "/api/v1/connections",
+    tag = "connections",
+    request_body = CreateConnectionRequest,
+    responses((status = 201, description = "已连接", body = BankConnectionDetail), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(req): Json<CreateConnectionRequest>,
+) -> ApiResult<(StatusCode, Json<BankConnectionDetail>)> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(state.pool.clone())
+        .authorize_user(user_id, req.ledger_id, Permission::CreateAccounts)
+        .await
+        .map_err(access_error)?;
+    let detail = sync_service(&state)
+        .connect(user_id, req)
+        .await
+        .map_err(connector_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// GET /api/v1/connections/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/connections/{id}",
+    tag = "connections",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = BankConnectionDetail), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<BankConnectionDetail>> {
+    authorize_connection(&state, &claims, id, Permission::ViewAccounts).await?;
+    let detail = sync_service(&state)
+        .get_connection_detail(id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(detail))
+}
+
+/// DELETE /api/v1/connections/:id
+///
+/// 撤销机构授权并删除连接，已导入的账户与交易保留
+#[utoipa::path(
+    delete,
+    path = "/api/v1/connections/{id}",
+    tag = "connections",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "已删除"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    authorize_connection(&state, &claims, id, Permission::EditAccounts).await?;
+    sync_service(&state)
+        .disconnect(id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/connections/:id/sync
+#[utoipa::path(
+    post,
+    path = "/api/v1/connections/{id}/sync",
+    tag = "connections",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = SyncSummary), (status = 400, description = "需要重新授权"), (status = 502, description = "机构接口错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn sync_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<SyncSummary>> {
+    authorize_connection(&state, &claims, id, Permission::CreateTransactions).await?;
+    let summary = sync_service(&state)
+        .sync_connection(id)
+        .await
+        .map_err(connector_error)?;
+    Ok(Json(summary))
+}
+
+/// POST /api/v1/connections/:id/reauth
+///
+/// 重新授权（Link 更新模式）的 token；完成后调用同步接口恢复连接
+#[utoipa::path(
+    post,
+    path = "/api/v1/connections/{id}/reauth",
+    tag = "connections",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = LinkToken), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn reauth_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<LinkToken>> {
+    let user_id = authorize_connection(&state, &claims, id, Permission::EditAccounts).await?;
+    let token = sync_service(&state)
+        .create_reauth_token(user_id, id)
+        .await
+        .map_err(connector_error)?;
+    Ok(Json(token))
+}
+
+/// PUT /api/v1/connections/:id/accounts/:account_id
+///
+/// 设置机构账户导入到哪个 Jive 账户；修改后下次同步会重新导入完整历史
+#[utoipa::path(
+    put,
+    path = "/api/v1/connections/{id}/accounts/{account_id}",
+    tag = "connections",
+    params(("id" = Uuid, Path), ("account_id" = Uuid, Path, description = "连接账户 id")),
+    request_body = LinkConnectionAccountRequest,
+    responses((status = 200, description = "成功", body = BankConnectionDetail), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn link_connection_account(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, connection_account_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<LinkConnectionAccountRequest>,
+) -> ApiResult<Json<BankConnectionDetail>> {
+    authorize_connection(&state, &claims, id, Permission::EditAccounts).await?;
+    if let Some(account_id) = req.account_id {
+        authorize_account(&state.pool, &claims, account_id, Permission::EditAccounts).await?;
+    }
+    let detail = sync_service(&state)
+        .link_account(id, connection_account_id, req.account_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(detail))
+}
+
+/// POST /api/v1/webhooks/bank/:provider
+///
+/// 机构 webhook（无需登录，按数据源的签名方式验签）
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/bank/{provider}",
+    tag = "connections",
+    params(("provider" = String, Path, description = "plaid / fake")),
+    request_body(content = String, description = "机构原始请求体", content_type = "application/json"),
+    responses((status = 200, description = "已接收"), (status = 401, description = "签名无效"))
+)]
+pub async fn bank_webhook(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<Json<serde_json::Value>> {
+    sync_service(&state)
+        .handle_webhook(&provider, &headers, &body)
+        .await
+        .map_err(|e| match e {
+            ServiceError::SerializationError(e) => ApiError::BadRequest(e.to_string()),
+            other => connector_error(other),
+        })?;
+    Ok(Json(json!({ "received": true })))
+}
+
+/// 机构接口错误单独映射，其余沿用账本权限的映射
+fn connector_error(e: ServiceError) -> ApiError {
+    match e {
+        ServiceError::ExternalApi { message } => ApiError::ExternalService(message),
+        other => access_error(other),
+    }
+}
@@ -0,0 +1,252 @@
+//! 信用卡 API：账单规则、账单与当前周期
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::config::AppConfig;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::{access_error, authorize_account};
+use crate::models::Permission;
+use crate::services::credit_card_service::{
+    CreateCreditCardRequest, CreditCard, CreditCardService, CreditCardStatement, CycleSummary,
+    UpdateCreditCardRequest,
+};
+use crate::services::{AuthService, LedgerAclService};
+use crate::ws::WsConnectionManager;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CreditCardQuery {
+    pub ledger_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CycleQuery {
+    /// 查看该日所在的账单周期，默认今天
+    pub date: Option<NaiveDate>,
+}
+
+/// 读取卡片并校验其账户权限
+async fn authorize_card(
+    pool: &PgPool,
+    claims: &Claims,
+    card_id: Uuid,
+    permission: Permission,
+) -> ApiResult<CreditCard> {
+    let card = CreditCardService::new(pool.clone())
+        .get_card(card_id)
+        .await
+        .map_err(access_error)?;
+    authorize_account(pool, claims, card.account_id, permission).await?;
+    Ok(card)
+}
+
+/// GET /api/v1/credit-cards
+#[utoipa::path(
+    get,
+    path = "/api/v1/credit-cards",
+    tag = "credit-cards",
+    params(CreditCardQuery),
+    responses((status = 200, description = "成功", body = Vec<CreditCard>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_credit_cards(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<CreditCardQuery>,
+) -> ApiResult<Json<Vec<CreditCard>>> {
+    let user_id = claims.user_id()?;
+    let acl = LedgerAclService::new(pool.clone());
+    let ledger_ids = match query.ledger_id {
+        Some(ledger_id) => {
+            acl.authorize_user(user_id, ledger_id, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?;
+            vec![ledger_id]
+        }
+        None => {
+            let family_id = claims
+                .family_id
+                .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+            let ctx = AuthService::new(pool.clone())
+                .validate_family_access(user_id, family_id)
+                .await
+                .map_err(|_| ApiError::Forbidden)?;
+            acl.authorized_ledgers(&ctx, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?
+        }
+    };
+    let cards = CreditCardService::new(pool)
+        .list_cards(&ledger_ids)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(cards))
+}
+
+/// POST /api/v1/credit-cards
+#[utoipa::path(
+    post,
+    path = "/api/v1/credit-cards",
+    tag = "credit-cards",
+    request_body = CreateCreditCardRequest,
+    responses((status = 201, description = "已创建", body = CreditCard), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_credit_card(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Json(req): Json<CreateCreditCardRequest>,
+) -> ApiResult<(StatusCode, Json<CreditCard>)> {
+    let user_id =
+        authorize_account(&pool, &claims, req.account_id, Permission::EditAccounts).await?;
+    let card = CreditCardService::new(pool)
+        .create_card(user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(card)))
+}
+
+/// GET /api/v1/credit-cards/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/credit-cards/{id}",
+    tag = "credit-cards",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = CreditCard), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_credit_card(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<CreditCard>> {
+    let card = authorize_card(&pool, &claims, id, Permission::ViewAccounts).await?;
+    Ok(Json(card))
+}
+
+/// PUT /api/v1/credit-cards/:id
+#[utoipa::path(
+    put,
+    path = "/api/v1/credit-cards/{id}",
+    tag = "credit-cards",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateCreditCardRequest,
+    responses((status = 200, description = "成功", body = CreditCard), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_credit_card(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateCreditCardRequest>,
+) -> ApiResult<Json<CreditCard>> {
+    authorize_card(&pool, &claims, id, Permission::EditAccounts).await?;
+    let card = CreditCardService::new(pool)
+        .update_card(id, req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(card))
+}
+
+/// DELETE /api/v1/credit-cards/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/credit-cards/{id}",
+    tag = "credit-cards",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "已删除"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_credit_card(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    authorize_card(&pool, &claims, id, Permission::EditAccounts).await?;
+    CreditCardService::new(pool)
+        .delete_card(id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/credit-cards/:id/statements
+#[utoipa::path(
+    get,
+    path = "/api/v1/credit-cards/{id}/statements",
+    tag = "credit-cards",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Vec<CreditCardStatement>), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_statements(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<CreditCardStatement>>> {
+    authorize_card(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let statements = CreditCardService::new(pool)
+        .list_statements(id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(statements))
+}
+
+/// POST /api/v1/credit-cards/:id/statements
+///
+/// 立即执行定时任务对该卡的处理，返回新生成的账单
+#[utoipa::path(
+    post,
+    path = "/api/v1/credit-cards/{id}/statements",
+    tag = "credit-cards",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "新生成的账单", body = Vec<CreditCardStatement>), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn generate_statements(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<CreditCardStatement>>> {
+    authorize_card(&pool, &claims, id, Permission::EditAccounts).await?;
+    let (statements, _) = CreditCardService::new(pool)
+        .process_card(id, Utc::now().date_naive(), &config, &ws)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(statements))
+}
+
+/// GET /api/v1/credit-cards/:id/cycle
+#[utoipa::path(
+    get,
+    path = "/api/v1/credit-cards/{id}/cycle",
+    tag = "credit-cards",
+    params(("id" = Uuid, Path), CycleQuery),
+    responses((status = 200, description = "成功", body = CycleSummary), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_current_cycle(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CycleQuery>,
+) -> ApiResult<Json<CycleSummary>> {
+    authorize_card(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let summary = CreditCardService::new(pool)
+        .current_cycle(id, query.date)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(summary))
+}
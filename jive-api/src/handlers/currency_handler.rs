@@ -4,10 +4,11 @@ use axum::{
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json, Response},
 };
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
 use super::family_handler::ApiResponse;
 use crate::auth::Claims;
@@ -18,10 +19,24 @@ use crate::services::currency_service::{
 };
 use crate::services::currency_service::{ClearManualRateRequest, ClearManualRatesBatchRequest};
 use crate::services::exchange_rate_api::EXCHANGE_RATE_SERVICE;
-use crate::services::{CurrencyService, ExchangeRate, FamilyCurrencySettings};
+use crate::services::fx_gain_loss_service::{FxGainLossReport, FxGainLossService};
+use crate::services::fx_history_import::{
+    FxHistoryImporter, FxImportFormat, FxImportOptions, FxImportSummary,
+};
+use crate::services::rate_graph::RateLeg;
+use crate::services::{CurrencyService, ExchangeRate, FamilyCurrencySettings, ServiceError};
 use crate::AppState; // Redis-enabled handlers
 
 /// 获取所有支持的货币
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies",
+    tag = "currencies",
+    responses(
+        (status = 200, description = "成功（带 ETag）", body = ApiResponse<Vec<crate::services::Currency>>),
+        (status = 304, description = "未修改（If-None-Match 命中）")
+    )
+)]
 pub async fn get_supported_currencies(
     State(app_state): State<AppState>,
     headers: HeaderMap,
@@ -66,6 +81,13 @@ pub async fn get_supported_currencies(
 }
 
 /// 获取用户的货币偏好
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/preferences",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<Vec<CurrencyPreference>>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_user_currency_preferences(
     State(app_state): State<AppState>,
     claims: Claims,
@@ -81,13 +103,21 @@ pub async fn get_user_currency_preferences(
     Ok(Json(ApiResponse::success(preferences)))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SetCurrencyPreferencesRequest {
     pub currencies: Vec<String>,
     pub primary_currency: String,
 }
 
 /// 设置用户的货币偏好
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/preferences",
+    tag = "currencies",
+    request_body = SetCurrencyPreferencesRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn set_user_currency_preferences(
     State(app_state): State<AppState>,
     claims: Claims,
@@ -105,6 +135,13 @@ pub async fn set_user_currency_preferences(
 }
 
 /// 获取家庭的货币设置
+#[utoipa::path(
+    get,
+    path = "/api/v1/family/currency-settings",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<FamilyCurrencySettings>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_family_currency_settings(
     State(app_state): State<AppState>,
     claims: Claims,
@@ -123,6 +160,14 @@ pub async fn get_family_currency_settings(
 }
 
 /// 更新家庭的货币设置
+#[utoipa::path(
+    put,
+    path = "/api/v1/family/currency-settings",
+    tag = "currencies",
+    request_body = UpdateCurrencySettingsRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<FamilyCurrencySettings>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_family_currency_settings(
     State(app_state): State<AppState>,
     claims: Claims,
@@ -141,7 +186,7 @@ pub async fn update_family_currency_settings(
     Ok(Json(ApiResponse::success(settings)))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct GetExchangeRateQuery {
     pub from: String,
     pub to: String,
@@ -149,13 +194,25 @@ pub struct GetExchangeRateQuery {
 }
 
 /// 获取汇率
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/rate",
+    tag = "currencies",
+    params(GetExchangeRateQuery),
+    responses((status = 200, description = "成功", body = ApiResponse<ExchangeRateResponse>))
+)]
 pub async fn get_exchange_rate(
     State(app_state): State<AppState>,
     Query(query): Query<GetExchangeRateQuery>,
 ) -> ApiResult<Json<ApiResponse<ExchangeRateResponse>>> {
     let service = CurrencyService::new(app_state.pool);
     let rate = service
-        .get_exchange_rate(&query.from, &query.to, query.date)
+        .get_exchange_rate(
+            &query.from,
+            &query.to,
+            query.date,
+            &app_state.config.rate_resolver,
+        )
         .await
         .map_err(|_e| ApiError::NotFound("Exchange rate not found".to_string()))?;
 
@@ -169,7 +226,7 @@ pub async fn get_exchange_rate(
     })))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ExchangeRateResponse {
     pub from_currency: String,
     pub to_currency: String,
@@ -177,7 +234,7 @@ pub struct ExchangeRateResponse {
     pub date: NaiveDate,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct GetBatchExchangeRatesRequest {
     pub base_currency: String,
     pub target_currencies: Vec<String>,
@@ -185,13 +242,25 @@ pub struct GetBatchExchangeRatesRequest {
 }
 
 /// 批量获取汇率
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/rates",
+    tag = "currencies",
+    request_body = GetBatchExchangeRatesRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<HashMap<String, Decimal>>))
+)]
 pub async fn get_batch_exchange_rates(
     State(app_state): State<AppState>,
     Json(req): Json<GetBatchExchangeRatesRequest>,
 ) -> ApiResult<Json<ApiResponse<HashMap<String, Decimal>>>> {
     let service = CurrencyService::new(app_state.pool);
     let rates = service
-        .get_exchange_rates(&req.base_currency, req.target_currencies, req.date)
+        .get_exchange_rates(
+            &req.base_currency,
+            req.target_currencies,
+            req.date,
+            &app_state.config.rate_resolver,
+        )
         .await
         .map_err(|_e| ApiError::InternalServerError)?;
 
@@ -199,6 +268,14 @@ pub async fn get_batch_exchange_rates(
 }
 
 /// 添加或更新汇率
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/rates/add",
+    tag = "currencies",
+    request_body = AddExchangeRateRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<ExchangeRate>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn add_exchange_rate(
     State(app_state): State<AppState>,
     _claims: Claims, // 需要管理员权限
@@ -214,6 +291,14 @@ pub async fn add_exchange_rate(
 }
 
 /// 清除当日手动汇率（回退到自动来源）
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/rates/clear-manual",
+    tag = "currencies",
+    request_body = ClearManualRateRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn clear_manual_exchange_rate(
     State(app_state): State<AppState>,
     _claims: Claims, // 需要管理员/有权限
@@ -230,6 +315,14 @@ pub async fn clear_manual_exchange_rate(
 }
 
 /// 批量清除手动汇率（按条件）
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/rates/clear-manual-batch",
+    tag = "currencies",
+    request_body = ClearManualRatesBatchRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn clear_manual_exchange_rates_batch(
     State(app_state): State<AppState>,
     _claims: Claims,
@@ -246,7 +339,7 @@ pub async fn clear_manual_exchange_rates_batch(
     }))))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ConvertAmountRequest {
     pub amount: Decimal,
     pub from_currency: String,
@@ -254,27 +347,51 @@ pub struct ConvertAmountRequest {
     pub date: Option<NaiveDate>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConvertAmountResponse {
     pub original_amount: Decimal,
     pub converted_amount: Decimal,
     pub from_currency: String,
     pub to_currency: String,
     pub exchange_rate: Decimal,
+    /// 换算所基于的日期
+    pub as_of: NaiveDate,
+    /// 路径中最旧一段汇率的生效日期
+    pub oldest_rate_date: NaiveDate,
+    /// 换算路径（直连时只有一段；经中间货币时按顺序列出每段汇率及其来源时间）
+    pub path: Vec<RateLeg>,
 }
 
 /// 货币转换
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/convert",
+    tag = "currencies",
+    request_body = ConvertAmountRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<ConvertAmountResponse>))
+)]
 pub async fn convert_amount(
     State(app_state): State<AppState>,
     Json(req): Json<ConvertAmountRequest>,
 ) -> ApiResult<Json<ApiResponse<ConvertAmountResponse>>> {
     let service = CurrencyService::new(app_state.pool.clone());
 
-    // 获取汇率
-    let rate = service
-        .get_exchange_rate(&req.from_currency, &req.to_currency, req.date)
+    // 获取汇率及换算路径
+    let resolved = service
+        .resolve_exchange_rate(
+            &req.from_currency,
+            &req.to_currency,
+            req.date,
+            &app_state.config.rate_resolver,
+        )
         .await
-        .map_err(|_e| ApiError::NotFound("Exchange rate not found".to_string()))?;
+        .map_err(|e| match e {
+            ServiceError::NotFound { .. } => {
+                ApiError::NotFound("Exchange rate not found".to_string())
+            }
+            _ => ApiError::InternalServerError,
+        })?;
+    let rate = resolved.rate;
 
     // 获取货币信息以确定小数位数
     let currencies = service
@@ -306,10 +423,13 @@ pub async fn convert_amount(
         from_currency: req.from_currency,
         to_currency: req.to_currency,
         exchange_rate: rate,
+        as_of: resolved.as_of,
+        oldest_rate_date: resolved.oldest_effective_date,
+        path: resolved.path,
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct GetExchangeRateHistoryQuery {
     pub from: String,
     pub to: String,
@@ -317,6 +437,13 @@ pub struct GetExchangeRateHistoryQuery {
 }
 
 /// 获取汇率历史
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/history",
+    tag = "currencies",
+    params(GetExchangeRateHistoryQuery),
+    responses((status = 200, description = "成功", body = ApiResponse<Vec<ExchangeRate>>))
+)]
 pub async fn get_exchange_rate_history(
     State(app_state): State<AppState>,
     Query(query): Query<GetExchangeRateHistoryQuery>,
@@ -332,7 +459,141 @@ pub async fn get_exchange_rate_history(
     Ok(Json(ApiResponse::success(history)))
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FxGainLossQuery {
+    /// 默认当年 1 月 1 日
+    pub start_date: Option<NaiveDate>,
+    /// 默认今天
+    pub end_date: Option<NaiveDate>,
+}
+
+/// 外币账户汇兑损益（资金流动 / 汇率重估 / 已实现与未实现损益）
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/fx-gain-loss",
+    tag = "currencies",
+    params(FxGainLossQuery),
+    responses(
+        (status = 200, description = "成功", body = ApiResponse<FxGainLossReport>),
+        (status = 400, description = "日期范围无效或未选择家庭"),
+        (status = 401, description = "未认证")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_fx_gain_loss(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<FxGainLossQuery>,
+) -> ApiResult<Json<ApiResponse<FxGainLossReport>>> {
+    let family_id = claims
+        .family_id
+        .ok_or_else(|| ApiError::BadRequest("No family selected".to_string()))?;
+
+    let end_date = query
+        .end_date
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let start_date = query
+        .start_date
+        .or_else(|| NaiveDate::from_ymd_opt(end_date.year(), 1, 1))
+        .unwrap_or(end_date);
+
+    let report = FxGainLossService::new(app_state.pool)
+        .report(family_id, start_date, end_date, &app_state.config.rate_resolver)
+        .await
+        .map_err(|e| match e {
+            ServiceError::ValidationError(msg) => ApiError::BadRequest(msg),
+            other => ApiError::DatabaseError(other.to_string()),
+        })?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ImportHistoricalRatesQuery {
+    /// ecb-csv / ecb-xml / csv；为空时按内容自动识别
+    pub format: Option<String>,
+    /// 写入 exchange_rates.source，默认 ECB 格式为 ecb，通用 CSV 为 import
+    pub source: Option<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// 逗号分隔，只导入涉及这些币种的汇率
+    pub currencies: Option<String>,
+}
+
+/// 导入历史汇率（系统管理员）
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/exchange-rates/import",
+    tag = "currencies",
+    params(ImportHistoricalRatesQuery),
+    request_body(
+        content = String,
+        content_type = "text/plain",
+        description = "ECB eurofxref-hist CSV/XML 或 date,from,to,rate CSV"
+    ),
+    responses(
+        (status = 200, description = "成功", body = ApiResponse<FxImportSummary>),
+        (status = 400, description = "文件格式错误"),
+        (status = 401, description = "未认证"),
+        (status = 403, description = "需要系统管理员权限")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn import_historical_rates(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ImportHistoricalRatesQuery>,
+    body: String,
+) -> ApiResult<Json<ApiResponse<FxImportSummary>>> {
+    let user_id = claims.user_id()?;
+    let role: Option<String> =
+        sqlx::query_scalar("SELECT role FROM users WHERE id = $1 AND is_active = true")
+            .bind(user_id)
+            .fetch_optional(&app_state.pool)
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .flatten();
+    if !matches!(role.as_deref(), Some("admin") | Some("superadmin")) {
+        return Err(ApiError::Forbidden);
+    }
+
+    let format = query
+        .format
+        .as_deref()
+        .filter(|f| !f.trim().is_empty() && !f.eq_ignore_ascii_case("auto"))
+        .map(str::parse::<FxImportFormat>)
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let options = FxImportOptions {
+        source: query.source,
+        since: query.since,
+        until: query.until,
+        currencies: query.currencies.map(|list| {
+            list.split(',')
+                .map(|c| c.trim().to_uppercase())
+                .filter(|c| !c.is_empty())
+                .collect()
+        }),
+    };
+
+    let summary = FxHistoryImporter::new(app_state.pool.clone())
+        .import_content(&body, format, &options)
+        .await
+        .map_err(|e| match e {
+            ServiceError::ValidationError(msg) => ApiError::BadRequest(msg),
+            other => ApiError::DatabaseError(other.to_string()),
+        })?;
+
+    Ok(Json(ApiResponse::success(summary)))
+}
+
 /// 获取常用汇率对
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/popular-pairs",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<Vec<ExchangePair>>))
+)]
 pub async fn get_popular_exchange_pairs(
     State(_app_state): State<AppState>,
 ) -> ApiResult<Json<ApiResponse<Vec<ExchangePair>>>> {
@@ -373,7 +634,7 @@ pub async fn get_popular_exchange_pairs(
     Ok(Json(ApiResponse::success(pairs)))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ExchangePair {
     pub from: String,
     pub to: String,
@@ -381,6 +642,13 @@ pub struct ExchangePair {
 }
 
 /// 刷新汇率（从外部API获取）
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/refresh",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn refresh_exchange_rates(
     State(app_state): State<AppState>,
     _claims: Claims, // 需要管理员权限
@@ -401,6 +669,12 @@ pub async fn refresh_exchange_rates(
 }
 
 /// 获取全球加密货币市场统计数据
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/global-market-stats",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<GlobalMarketStats>))
+)]
 pub async fn get_global_market_stats(
     State(_app_state): State<AppState>,
 ) -> ApiResult<Json<ApiResponse<GlobalMarketStats>>> {
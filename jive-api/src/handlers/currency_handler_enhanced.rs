@@ -8,16 +8,20 @@ use serde::de::{self, Deserializer, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use super::family_handler::ApiResponse;
 use crate::auth::Claims;
+use crate::config::AppConfig;
 use crate::error::{ApiError, ApiResult};
 use crate::services::currency_service::CurrencyPreference;
 use crate::services::exchange_rate_api::ExchangeRateApiService;
+use crate::services::fx_providers::ProviderRegistry;
 use crate::services::CurrencyService;
 
 /// Enhanced Currency model with all fields needed by Flutter
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(as = CurrencyDetail)]
 pub struct Currency {
     pub code: String,
     pub name: String,
@@ -31,7 +35,7 @@ pub struct Currency {
 }
 
 /// User currency preferences with enhanced fields
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserCurrencySettings {
     pub multi_currency_enabled: bool,
     pub crypto_enabled: bool,
@@ -43,6 +47,12 @@ pub struct UserCurrencySettings {
 }
 
 /// Get all currencies with enhanced information
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/all",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<CurrenciesResponse>))
+)]
 pub async fn get_all_currencies(
     State(pool): State<PgPool>,
 ) -> ApiResult<Json<ApiResponse<CurrenciesResponse>>> {
@@ -99,13 +109,20 @@ pub async fn get_all_currencies(
     })))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CurrenciesResponse {
     pub fiat_currencies: Vec<Currency>,
     pub crypto_currencies: Vec<Currency>,
 }
 
 /// Get user's currency settings
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/user-settings",
+    tag = "currencies",
+    responses((status = 200, description = "成功", body = ApiResponse<UserCurrencySettings>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_user_currency_settings(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -166,7 +183,7 @@ pub async fn get_user_currency_settings(
     Ok(Json(ApiResponse::success(settings)))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserCurrencySettingsRequest {
     pub multi_currency_enabled: Option<bool>,
     pub crypto_enabled: Option<bool>,
@@ -177,6 +194,14 @@ pub struct UpdateUserCurrencySettingsRequest {
 }
 
 /// Update user's currency settings
+#[utoipa::path(
+    put,
+    path = "/api/v1/currencies/user-settings",
+    tag = "currencies",
+    request_body = UpdateUserCurrencySettingsRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<UserCurrencySettings>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_user_currency_settings(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -222,6 +247,13 @@ pub async fn update_user_currency_settings(
 }
 
 /// Get real-time exchange rates (with caching)
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/realtime-rates",
+    tag = "currencies",
+    params(RealtimeRatesQuery),
+    responses((status = 200, description = "成功", body = ApiResponse<RealtimeRatesResponse>))
+)]
 pub async fn get_realtime_exchange_rates(
     State(pool): State<PgPool>,
     Query(query): Query<RealtimeRatesQuery>,
@@ -278,13 +310,13 @@ pub async fn get_realtime_exchange_rates(
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct RealtimeRatesQuery {
     pub base_currency: Option<String>,
     pub force_refresh: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RealtimeRatesResponse {
     pub base_currency: String,
     pub rates: HashMap<String, Decimal>,
@@ -292,13 +324,13 @@ pub struct RealtimeRatesResponse {
     pub cache_duration_minutes: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct DetailedRatesRequest {
     pub base_currency: String,
     pub target_currencies: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DetailedRateItem {
     pub rate: Decimal,
     pub source: String,
@@ -306,20 +338,20 @@ pub struct DetailedRateItem {
     pub manual_rate_expiry: Option<chrono::NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DetailedRatesResponse {
     pub base_currency: String,
     pub rates: HashMap<String, DetailedRateItem>,
 }
 
 // List manual overrides for current business date
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct ManualOverridesQuery {
     pub base_currency: String,
     pub only_active: Option<bool>, // if true: expiry is NULL or > NOW()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ManualOverrideItem {
     pub to_currency: String,
     pub rate: Decimal,
@@ -327,12 +359,19 @@ pub struct ManualOverrideItem {
     pub updated_at: chrono::NaiveDateTime,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ManualOverridesResponse {
     pub base_currency: String,
     pub overrides: Vec<ManualOverrideItem>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/manual-overrides",
+    tag = "currencies",
+    params(ManualOverridesQuery),
+    responses((status = 200, description = "成功", body = ApiResponse<ManualOverridesResponse>))
+)]
 pub async fn get_manual_overrides(
     State(pool): State<PgPool>,
     Query(q): Query<ManualOverridesQuery>,
@@ -382,11 +421,19 @@ pub async fn get_manual_overrides(
 }
 
 /// Get detailed batch rates (supports fiat and crypto) with source label
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/rates-detailed",
+    tag = "currencies",
+    request_body = DetailedRatesRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<DetailedRatesResponse>))
+)]
 pub async fn get_detailed_batch_rates(
     State(pool): State<PgPool>,
+    State(fx_providers): State<Arc<ProviderRegistry>>,
     Json(req): Json<DetailedRatesRequest>,
 ) -> ApiResult<Json<ApiResponse<DetailedRatesResponse>>> {
-    let mut api = ExchangeRateApiService::new();
+    let mut api = ExchangeRateApiService::with_registry(fx_providers);
     let base = req.base_currency.to_uppercase();
     let targets: Vec<String> = req
         .target_currencies
@@ -408,13 +455,7 @@ pub async fn get_detailed_batch_rates(
     // Fetch fiat rates for base if needed
     if !base_is_crypto {
         // Merge per-target from providers in priority order, so missing ones are filled by next providers
-        let order_env = std::env::var("FIAT_PROVIDER_ORDER")
-            .unwrap_or_else(|_| "exchangerate-api,frankfurter,fxrates".to_string());
-        let providers: Vec<String> = order_env
-            .split(',')
-            .map(|s| s.trim().to_lowercase())
-            .filter(|s| !s.is_empty())
-            .collect();
+        let providers: Vec<String> = api.registry().fiat_order().to_vec();
 
         // Accumulator for merged rates and a map to track source per currency
         let mut merged: std::collections::HashMap<String, rust_decimal::Decimal> =
@@ -618,6 +659,13 @@ pub async fn get_detailed_batch_rates(
 }
 
 /// Get crypto prices with proper caching
+#[utoipa::path(
+    get,
+    path = "/api/v1/currencies/crypto-prices",
+    tag = "currencies",
+    params(CryptoPricesQuery),
+    responses((status = 200, description = "成功", body = ApiResponse<CryptoPricesResponse>))
+)]
 pub async fn get_crypto_prices(
     State(pool): State<PgPool>,
     Query(query): Query<CryptoPricesQuery>,
@@ -680,7 +728,7 @@ pub async fn get_crypto_prices(
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct CryptoPricesQuery {
     pub fiat_currency: Option<String>,
     // 支持两种格式：
@@ -755,7 +803,7 @@ where
     deserializer.deserialize_any(CodesVisitor)
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CryptoPricesResponse {
     pub fiat_currency: String,
     pub prices: HashMap<String, Decimal>,
@@ -764,8 +812,16 @@ pub struct CryptoPricesResponse {
 }
 
 /// Convert between any two currencies (fiat or crypto)
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/convert-any",
+    tag = "currencies",
+    request_body = ConvertCurrencyRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<ConvertCurrencyResponse>))
+)]
 pub async fn convert_currency(
     State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
     Json(req): Json<ConvertCurrencyRequest>,
 ) -> ApiResult<Json<ApiResponse<ConvertCurrencyResponse>>> {
     let service = CurrencyService::new(pool.clone());
@@ -780,7 +836,7 @@ pub async fn convert_currency(
     } else {
         // Regular fiat conversion
         service
-            .get_exchange_rate(&req.from, &req.to, None)
+            .get_exchange_rate(&req.from, &req.to, None, &config.rate_resolver)
             .await
             .map_err(|_| ApiError::NotFound("Exchange rate not found".to_string()))?
     };
@@ -797,14 +853,14 @@ pub async fn convert_currency(
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ConvertCurrencyRequest {
     pub from: String,
     pub to: String,
     pub amount: Decimal,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConvertCurrencyResponse {
     pub from: String,
     pub to: String,
@@ -815,6 +871,14 @@ pub struct ConvertCurrencyResponse {
 }
 
 /// Manual refresh of exchange rates
+#[utoipa::path(
+    post,
+    path = "/api/v1/currencies/manual-refresh",
+    tag = "currencies",
+    request_body = ManualRefreshRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<RefreshResponse>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn manual_refresh_rates(
     State(_pool): State<PgPool>,
     _claims: Claims,
@@ -835,13 +899,13 @@ pub async fn manual_refresh_rates(
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ManualRefreshRequest {
     pub base_currency: Option<String>,
     pub include_crypto: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RefreshResponse {
     pub success: bool,
     pub message: String,
@@ -976,6 +1040,7 @@ fn get_mock_rate(from: &str, to: &str) -> Decimal {
 }
 
 use rust_decimal::prelude::FromStr;
+use utoipa::{IntoParams, ToSchema};
 
 fn decimal_from_str(s: &str) -> Decimal {
     Decimal::from_str(s).unwrap_or(Decimal::ZERO)
@@ -0,0 +1,95 @@
+//! 财务摘要接口：查看已生成的周报 / 月报，预览当前家庭上一周期的摘要
+//!
+//! 摘要只属于生成时的成员；预览按成员当前的账本权限实时统计。
+
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::access_error;
+use crate::services::financial_digest_service::{
+    DigestPeriod, DigestPreviewQuery, DigestQuery, DigestReport, FinancialDigest,
+    FinancialDigestService,
+};
+
+/// GET /api/v1/digests
+#[utoipa::path(
+    get,
+    path = "/api/v1/digests",
+    tag = "digests",
+    params(DigestQuery),
+    responses((status = 200, description = "成功", body = [FinancialDigest]), (status = 400, description = "参数错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_digests(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<DigestQuery>,
+) -> ApiResult<Json<Vec<FinancialDigest>>> {
+    let user_id = claims.user_id()?;
+    let digests = FinancialDigestService::new(pool)
+        .list(user_id, &query)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(digests))
+}
+
+/// GET /api/v1/digests/preview
+///
+/// 当前家庭上一个完整周期的摘要（不保存、不发送通知）
+#[utoipa::path(
+    get,
+    path = "/api/v1/digests/preview",
+    tag = "digests",
+    params(DigestPreviewQuery),
+    responses((status = 200, description = "成功", body = DigestReport), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn preview_digest(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<DigestPreviewQuery>,
+) -> ApiResult<Json<DigestReport>> {
+    let user_id = claims.user_id()?;
+    let family_id = claims
+        .family_id
+        .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+    let period = match query.period.as_deref() {
+        None => DigestPeriod::Weekly,
+        Some(value) => DigestPeriod::parse(value).ok_or(ApiError::BadRequest(
+            "period 只能是 weekly 或 monthly".to_string(),
+        ))?,
+    };
+    let report = FinancialDigestService::new(pool)
+        .preview(user_id, family_id, period)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(report))
+}
+
+/// GET /api/v1/digests/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/digests/{id}",
+    tag = "digests",
+    params(("id" = Uuid, Path, description = "摘要 ID")),
+    responses((status = 200, description = "成功", body = FinancialDigest), (status = 404, description = "摘要不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_digest(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<FinancialDigest>> {
+    let user_id = claims.user_id()?;
+    let digest = FinancialDigestService::new(pool)
+        .get(user_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(digest))
+}
@@ -6,6 +6,7 @@ use axum::{extract::State, http::StatusCode, response::Json};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use super::family_handler::ApiResponse;
@@ -15,7 +16,7 @@ use crate::models::family::CreateFamilyRequest;
 use crate::services::{AvatarService, FamilyService};
 
 /// Enhanced User Profile with preferences
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EnhancedUserProfile {
     pub id: Uuid,
     pub email: String,
@@ -36,7 +37,7 @@ pub struct EnhancedUserProfile {
 }
 
 /// Update user preferences request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdatePreferencesRequest {
     pub name: Option<String>,
     pub country: Option<String>,
@@ -47,6 +48,13 @@ pub struct UpdatePreferencesRequest {
 }
 
 /// Enhanced registration with user preferences
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register-enhanced",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>))
+)]
 pub async fn register_with_preferences(
     State(pool): State<PgPool>,
     Json(req): Json<RegisterRequest>,
@@ -185,6 +193,13 @@ pub async fn register_with_preferences(
 }
 
 /// Get enhanced user profile with preferences
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/profile-enhanced",
+    tag = "auth",
+    responses((status = 200, description = "成功", body = ApiResponse<EnhancedUserProfile>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_enhanced_profile(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -320,6 +335,14 @@ pub async fn get_enhanced_profile(
 }
 
 /// Update user preferences
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/preferences",
+    tag = "auth",
+    request_body = UpdatePreferencesRequest,
+    responses((status = 200, description = "成功"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_preferences(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -402,6 +425,12 @@ pub async fn update_preferences(
 }
 
 /// Get supported locales
+#[utoipa::path(
+    get,
+    path = "/api/v1/locales",
+    tag = "auth",
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>))
+)]
 pub async fn get_supported_locales() -> Json<ApiResponse<serde_json::Value>> {
     let locales = serde_json::json!({
         "countries": [
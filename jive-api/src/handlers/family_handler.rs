@@ -6,19 +6,23 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::models::family::{CreateFamilyRequest, Family, UpdateFamilyRequest};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct JoinFamilyRequest {
     pub invite_code: String,
 }
 
+use crate::config::AppConfig;
+use crate::services::email::EmailOutbox;
 use crate::services::{FamilyService, ServiceContext, ServiceError};
 use sqlx::PgPool;
+use std::sync::Arc;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
@@ -26,10 +30,11 @@ pub struct ApiResponse<T> {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiError {
     pub code: String,
     pub message: String,
+    #[schema(value_type = Option<Object>)]
     pub details: Option<Value>,
 }
 
@@ -58,6 +63,14 @@ impl<T> ApiResponse<T> {
 }
 
 // Create new family
+#[utoipa::path(
+    post,
+    path = "/api/v1/families",
+    tag = "families",
+    request_body = CreateFamilyRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<Family>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_family(
     State(pool): State<PgPool>,
     claims: crate::auth::Claims,
@@ -90,6 +103,13 @@ pub async fn create_family(
 }
 
 // List user's families
+#[utoipa::path(
+    get,
+    path = "/api/v1/families",
+    tag = "families",
+    responses((status = 200, description = "成功", body = ApiResponse<Vec<Family>>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn list_families(
     State(pool): State<PgPool>,
     claims: crate::auth::Claims,
@@ -111,6 +131,13 @@ pub async fn list_families(
 }
 
 // Get family details
+#[utoipa::path(
+    get,
+    path = "/api/v1/families/{id}",
+    tag = "families",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = ApiResponse<Family>))
+)]
 pub async fn get_family(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -134,6 +161,14 @@ pub async fn get_family(
 }
 
 // Update family
+#[utoipa::path(
+    put,
+    path = "/api/v1/families/{id}",
+    tag = "families",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateFamilyRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<Family>))
+)]
 pub async fn update_family(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -158,6 +193,14 @@ pub async fn update_family(
 }
 
 // Delete family
+#[utoipa::path(
+    delete,
+    path = "/api/v1/families/{id}",
+    tag = "families",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_family(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -205,6 +248,14 @@ pub async fn delete_family(
 }
 
 // Join family by invite code
+#[utoipa::path(
+    post,
+    path = "/api/v1/families/join",
+    tag = "families",
+    request_body = JoinFamilyRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<Family>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn join_family(
     State(pool): State<PgPool>,
     claims: crate::auth::Claims,
@@ -273,6 +324,14 @@ pub async fn switch_family(
 }
 
 // Get family statistics
+#[utoipa::path(
+    get,
+    path = "/api/v1/families/{id}/statistics",
+    tag = "families",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_family_statistics(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -331,21 +390,30 @@ pub async fn regenerate_invite_code(
 }
 
 // Request verification code for sensitive operations
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RequestVerificationRequest {
     pub operation: String, // "leave_family" or "delete_user"
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct VerificationCodeResponse {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub code: Option<String>, // Only for testing, remove in production
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/verification/request",
+    tag = "families",
+    request_body = RequestVerificationRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<VerificationCodeResponse>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn request_verification_code(
     State(pool): State<PgPool>,
     State(redis): State<Option<redis::aio::ConnectionManager>>,
+    State(config): State<Arc<AppConfig>>,
     claims: crate::auth::Claims,
     Json(request): Json<RequestVerificationRequest>,
 ) -> Result<Json<ApiResponse<VerificationCodeResponse>>, StatusCode> {
@@ -355,7 +423,8 @@ pub async fn request_verification_code(
     };
 
     if let Some(redis_conn) = redis {
-        let verification_service = crate::services::VerificationService::new(Some(redis_conn));
+        let verification_service = crate::services::VerificationService::new(Some(redis_conn))
+            .with_email_outbox(EmailOutbox::new(pool.clone(), &config.email));
 
         // Get user email for sending code
         let email: Option<String> = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
@@ -411,12 +480,20 @@ pub async fn request_verification_code(
 }
 
 // Leave family with verification
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LeaveFamilyRequest {
     pub family_id: Uuid,
     pub verification_code: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/families/leave",
+    tag = "families",
+    request_body = LeaveFamilyRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn leave_family(
     State(pool): State<PgPool>,
     State(redis): State<Option<redis::aio::ConnectionManager>>,
@@ -507,6 +584,14 @@ pub async fn leave_family(
     }
 }
 // Get family action permissions
+#[utoipa::path(
+    get,
+    path = "/api/v1/families/{id}/actions",
+    tag = "families",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_family_actions(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -554,6 +639,12 @@ pub async fn get_family_actions(
 }
 
 // Get role descriptions
+#[utoipa::path(
+    get,
+    path = "/api/v1/roles/descriptions",
+    tag = "families",
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>))
+)]
 pub async fn get_role_descriptions() -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
     let roles = serde_json::json!({
         "roles": [
@@ -718,12 +809,21 @@ pub async fn get_role_descriptions() -> Result<Json<ApiResponse<serde_json::Valu
 }
 
 // Transfer family ownership
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TransferOwnershipRequest {
     pub new_owner_id: Uuid,
     pub verification_code: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/families/{id}/transfer-ownership",
+    tag = "families",
+    params(("id" = Uuid, Path)),
+    request_body = TransferOwnershipRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn transfer_ownership(
     State(pool): State<PgPool>,
     State(redis): State<Option<redis::aio::ConnectionManager>>,
@@ -0,0 +1,189 @@
+//! 分期 API：消费转分期、还款计划与提前还款
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::config::AppConfig;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::{access_error, authorize_account};
+use crate::models::Permission;
+use crate::services::installment_service::{
+    CreateInstallmentPlanRequest, InstallmentPlan, InstallmentPlanDetail, InstallmentService,
+    PayoffInstallmentRequest, UpdateInstallmentPlanRequest,
+};
+use crate::services::{AuthService, LedgerAclService};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct InstallmentQuery {
+    pub ledger_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+}
+
+/// 读取分期计划并校验其账户权限
+async fn authorize_plan(
+    pool: &PgPool,
+    claims: &Claims,
+    plan_id: Uuid,
+    permission: Permission,
+) -> ApiResult<InstallmentPlan> {
+    let plan = InstallmentService::new(pool.clone())
+        .get_plan(plan_id)
+        .await
+        .map_err(access_error)?;
+    authorize_account(pool, claims, plan.account_id, permission).await?;
+    Ok(plan)
+}
+
+/// GET /api/v1/installments
+#[utoipa::path(
+    get,
+    path = "/api/v1/installments",
+    tag = "installments",
+    params(InstallmentQuery),
+    responses((status = 200, description = "成功", body = Vec<InstallmentPlan>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_installment_plans(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<InstallmentQuery>,
+) -> ApiResult<Json<Vec<InstallmentPlan>>> {
+    let user_id = claims.user_id()?;
+    let acl = LedgerAclService::new(pool.clone());
+    let ledger_ids = match query.ledger_id {
+        Some(ledger_id) => {
+            acl.authorize_user(user_id, ledger_id, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?;
+            vec![ledger_id]
+        }
+        None => {
+            let family_id = claims
+                .family_id
+                .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+            let ctx = AuthService::new(pool.clone())
+                .validate_family_access(user_id, family_id)
+                .await
+                .map_err(|_| ApiError::Forbidden)?;
+            acl.authorized_ledgers(&ctx, Permission::ViewAccounts)
+                .await
+                .map_err(access_error)?
+        }
+    };
+    let plans = InstallmentService::new(pool)
+        .list_plans(&ledger_ids, query.account_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(plans))
+}
+
+/// POST /api/v1/installments
+#[utoipa::path(
+    post,
+    path = "/api/v1/installments",
+    tag = "installments",
+    request_body = CreateInstallmentPlanRequest,
+    responses((status = 201, description = "已创建", body = InstallmentPlanDetail), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_installment_plan(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Json(req): Json<CreateInstallmentPlanRequest>,
+) -> ApiResult<(StatusCode, Json<InstallmentPlanDetail>)> {
+    let service = InstallmentService::new(pool.clone());
+    let account_id = service
+        .purchase_account(req.transaction_id)
+        .await
+        .map_err(access_error)?;
+    let user_id =
+        authorize_account(&pool, &claims, account_id, Permission::EditTransactions).await?;
+    let detail = service
+        .create_plan(user_id, req, &config.rate_resolver)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// GET /api/v1/installments/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/installments/{id}",
+    tag = "installments",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = InstallmentPlanDetail), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_installment_plan(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<InstallmentPlanDetail>> {
+    authorize_plan(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let detail = InstallmentService::new(pool)
+        .get_plan_detail(id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(detail))
+}
+
+/// PUT /api/v1/installments/:id
+///
+/// 切换预算统计方式（全额 / 按月分摊）
+#[utoipa::path(
+    put,
+    path = "/api/v1/installments/{id}",
+    tag = "installments",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateInstallmentPlanRequest,
+    responses((status = 200, description = "成功", body = InstallmentPlan), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_installment_plan(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateInstallmentPlanRequest>,
+) -> ApiResult<Json<InstallmentPlan>> {
+    authorize_plan(&pool, &claims, id, Permission::EditTransactions).await?;
+    let plan = InstallmentService::new(pool)
+        .update_plan(id, req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(plan))
+}
+
+/// POST /api/v1/installments/:id/payoff
+#[utoipa::path(
+    post,
+    path = "/api/v1/installments/{id}/payoff",
+    tag = "installments",
+    params(("id" = Uuid, Path)),
+    request_body = PayoffInstallmentRequest,
+    responses((status = 200, description = "已提前还款", body = InstallmentPlanDetail), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn pay_off_installment_plan(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<PayoffInstallmentRequest>,
+) -> ApiResult<Json<InstallmentPlanDetail>> {
+    authorize_plan(&pool, &claims, id, Permission::EditTransactions).await?;
+    let detail = InstallmentService::new(pool)
+        .pay_off(id, req.date, &config.rate_resolver)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(detail))
+}
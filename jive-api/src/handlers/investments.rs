@@ -0,0 +1,499 @@
+//! 投资账户 API：证券、交易、拆股、公司行动、持仓、批次、已实现损益与股息收益
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::{access_error, authorize_account};
+use crate::models::Permission;
+use crate::services::investment_income_service::{
+    IncomeHistory, IncomeProjection, IncomeRecordResult, InvestmentIncomeService,
+    RecordIncomeRequest,
+};
+use crate::services::investment_performance_service::{
+    InvestmentPerformanceService, PortfolioPerformanceReport,
+};
+use crate::services::investment_service::{
+    CorporateActionRequest, CorporateActionResult, CostBasisMethod, CreateSecurityRequest, Holding,
+    InvestmentService, InvestmentTrade, RealizedGainsReport, Security, SplitRequest, TaxLot,
+    TradeExecution, TradeRequest,
+};
+use crate::services::security_price_service::{
+    QuoteImportSummary, SecurityPrice, SecurityPriceService,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SecurityQuery {
+    /// 按代码或名称模糊搜索
+    pub q: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TradeListQuery {
+    pub security_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LotQuery {
+    pub security_id: Option<Uuid>,
+    /// 包含已结清的批次
+    pub include_closed: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RealizedGainsQuery {
+    /// 按卖出年份筛选
+    pub year: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IncomeHistoryQuery {
+    /// 按派息年份筛选明细
+    pub year: Option<i32>,
+    pub security_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IncomeProjectionQuery {
+    /// 预测起始日，默认今天
+    pub from_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PerformanceQuery {
+    /// 默认一年前
+    pub start_date: Option<NaiveDate>,
+    /// 默认今天
+    pub end_date: Option<NaiveDate>,
+    /// 作为基准的证券（需有价格历史）
+    pub benchmark_security_id: Option<Uuid>,
+    /// 年化无风险利率，用于夏普比率，默认 0
+    pub risk_free_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PriceHistoryQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ImportPricesQuery {
+    /// 文件不含代码列时，行情所属的证券
+    pub security_id: Option<Uuid>,
+    /// 写入 security_prices.source，默认 import
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CostBasisMethodRequest {
+    pub method: CostBasisMethod,
+}
+
+/// GET /api/v1/investments/securities
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/securities",
+    tag = "investments",
+    params(SecurityQuery),
+    responses((status = 200, description = "成功", body = Vec<Security>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_securities(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<SecurityQuery>,
+) -> ApiResult<Json<Vec<Security>>> {
+    claims.user_id()?;
+    let securities = InvestmentService::new(pool)
+        .list_securities(query.q.as_deref())
+        .await
+        .map_err(access_error)?;
+    Ok(Json(securities))
+}
+
+/// POST /api/v1/investments/securities
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/securities",
+    tag = "investments",
+    request_body = CreateSecurityRequest,
+    responses((status = 201, description = "已创建", body = Security), (status = 400, description = "参数错误或已存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_security(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Json(req): Json<CreateSecurityRequest>,
+) -> ApiResult<(StatusCode, Json<Security>)> {
+    claims.user_id()?;
+    let security = InvestmentService::new(pool)
+        .create_security(req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(security)))
+}
+
+/// GET /api/v1/investments/accounts/:id/trades
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/trades",
+    tag = "investments",
+    params(("id" = Uuid, Path), TradeListQuery),
+    responses((status = 200, description = "成功", body = Vec<InvestmentTrade>), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_trades(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<TradeListQuery>,
+) -> ApiResult<Json<Vec<InvestmentTrade>>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let trades = InvestmentService::new(pool)
+        .list_trades(id, query.security_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(trades))
+}
+
+/// POST /api/v1/investments/accounts/:id/trades
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/accounts/{id}/trades",
+    tag = "investments",
+    params(("id" = Uuid, Path)),
+    request_body = TradeRequest,
+    responses((status = 201, description = "已成交", body = TradeExecution), (status = 400, description = "参数错误或持仓不足"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn execute_trade(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<TradeRequest>,
+) -> ApiResult<(StatusCode, Json<TradeExecution>)> {
+    let user_id = authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    let execution = InvestmentService::new(pool)
+        .execute_trade(id, user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(execution)))
+}
+
+/// POST /api/v1/investments/accounts/:id/splits
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/accounts/{id}/splits",
+    tag = "investments",
+    params(("id" = Uuid, Path)),
+    request_body = SplitRequest,
+    responses((status = 201, description = "已记录", body = InvestmentTrade), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn record_split(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SplitRequest>,
+) -> ApiResult<(StatusCode, Json<InvestmentTrade>)> {
+    let user_id = authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    let trade = InvestmentService::new(pool)
+        .record_split(id, user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(trade)))
+}
+
+/// GET /api/v1/investments/accounts/:id/holdings
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/holdings",
+    tag = "investments",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Vec<Holding>), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_holdings(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<Holding>>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let holdings = InvestmentService::new(pool)
+        .get_holdings(id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(holdings))
+}
+
+/// GET /api/v1/investments/accounts/:id/lots
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/lots",
+    tag = "investments",
+    params(("id" = Uuid, Path), LotQuery),
+    responses((status = 200, description = "成功", body = Vec<TaxLot>), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_lots(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<LotQuery>,
+) -> ApiResult<Json<Vec<TaxLot>>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let lots = InvestmentService::new(pool)
+        .list_lots(id, query.security_id, query.include_closed.unwrap_or(false))
+        .await
+        .map_err(access_error)?;
+    Ok(Json(lots))
+}
+
+/// GET /api/v1/investments/accounts/:id/realized-gains
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/realized-gains",
+    tag = "investments",
+    params(("id" = Uuid, Path), RealizedGainsQuery),
+    responses((status = 200, description = "成功", body = RealizedGainsReport), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_realized_gains(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RealizedGainsQuery>,
+) -> ApiResult<Json<RealizedGainsReport>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let report = InvestmentService::new(pool)
+        .realized_gains(id, query.year)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(report))
+}
+
+/// POST /api/v1/investments/accounts/:id/corporate-actions
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/accounts/{id}/corporate-actions",
+    tag = "investments",
+    params(("id" = Uuid, Path)),
+    request_body = CorporateActionRequest,
+    responses((status = 201, description = "已记录", body = CorporateActionResult), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn record_corporate_action(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CorporateActionRequest>,
+) -> ApiResult<(StatusCode, Json<CorporateActionResult>)> {
+    let user_id = authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    let result = InvestmentService::new(pool)
+        .record_corporate_action(id, user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+/// POST /api/v1/investments/accounts/:id/income
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/accounts/{id}/income",
+    tag = "investments",
+    params(("id" = Uuid, Path)),
+    request_body = RecordIncomeRequest,
+    responses((status = 201, description = "已记录", body = IncomeRecordResult), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn record_income(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RecordIncomeRequest>,
+) -> ApiResult<(StatusCode, Json<IncomeRecordResult>)> {
+    let user_id = authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    let result = InvestmentIncomeService::new(pool)
+        .record_income(id, user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+/// GET /api/v1/investments/accounts/:id/income
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/income",
+    tag = "investments",
+    params(("id" = Uuid, Path), IncomeHistoryQuery),
+    responses((status = 200, description = "成功", body = IncomeHistory), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_income_history(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<IncomeHistoryQuery>,
+) -> ApiResult<Json<IncomeHistory>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let history = InvestmentIncomeService::new(pool)
+        .income_history(id, query.year, query.security_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(history))
+}
+
+/// GET /api/v1/investments/accounts/:id/income/projection
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/income/projection",
+    tag = "investments",
+    params(("id" = Uuid, Path), IncomeProjectionQuery),
+    responses((status = 200, description = "成功", body = IncomeProjection), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_income_projection(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<IncomeProjectionQuery>,
+) -> ApiResult<Json<IncomeProjection>> {
+    authorize_account(&pool, &claims, id, Permission::ViewReports).await?;
+    let projection = InvestmentIncomeService::new(pool)
+        .projected_income(id, query.from_date)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(projection))
+}
+
+/// PUT /api/v1/investments/accounts/:id/cost-basis-method
+#[utoipa::path(
+    put,
+    path = "/api/v1/investments/accounts/{id}/cost-basis-method",
+    tag = "investments",
+    params(("id" = Uuid, Path)),
+    request_body = CostBasisMethodRequest,
+    responses((status = 204, description = "成功，无返回内容"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn set_cost_basis_method(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CostBasisMethodRequest>,
+) -> ApiResult<StatusCode> {
+    authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    InvestmentService::new(pool)
+        .set_cost_basis_method(id, req.method)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/investments/accounts/:id/performance
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/accounts/{id}/performance",
+    tag = "investments",
+    params(("id" = Uuid, Path), PerformanceQuery),
+    responses((status = 200, description = "成功", body = PortfolioPerformanceReport), (status = 400, description = "参数错误或估值不足"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_performance(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<PerformanceQuery>,
+) -> ApiResult<Json<PortfolioPerformanceReport>> {
+    authorize_account(&pool, &claims, id, Permission::ViewReports).await?;
+    let end = query.end_date.unwrap_or_else(|| Utc::now().date_naive());
+    let start = query.start_date.unwrap_or(end - Duration::days(365));
+    let report = InvestmentPerformanceService::new(pool)
+        .report(
+            id,
+            start,
+            end,
+            query.benchmark_security_id,
+            query.risk_free_rate.unwrap_or(0.0),
+        )
+        .await
+        .map_err(access_error)?;
+    Ok(Json(report))
+}
+
+/// GET /api/v1/investments/securities/:id/prices
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/securities/{id}/prices",
+    tag = "investments",
+    params(("id" = Uuid, Path), PriceHistoryQuery),
+    responses((status = 200, description = "成功", body = Vec<SecurityPrice>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_security_prices(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<PriceHistoryQuery>,
+) -> ApiResult<Json<Vec<SecurityPrice>>> {
+    claims.user_id()?;
+    let prices = SecurityPriceService::new(pool)
+        .list_prices(id, query.start_date, query.end_date)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(prices))
+}
+
+/// POST /api/v1/admin/security-prices/import
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/security-prices/import",
+    tag = "investments",
+    params(ImportPricesQuery),
+    request_body(
+        content = String,
+        content_type = "text/plain",
+        description = "date,ticker,close CSV，或带表头的 Date,Open,High,Low,Close,Volume 行情文件"
+    ),
+    responses(
+        (status = 200, description = "成功", body = QuoteImportSummary),
+        (status = 400, description = "文件格式错误"),
+        (status = 401, description = "未认证"),
+        (status = 403, description = "需要系统管理员权限")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn import_security_prices(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<ImportPricesQuery>,
+    body: String,
+) -> ApiResult<Json<QuoteImportSummary>> {
+    let user_id = claims.user_id()?;
+    let role: Option<String> =
+        sqlx::query_scalar("SELECT role FROM users WHERE id = $1 AND is_active = true")
+            .bind(user_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .flatten();
+    if !matches!(role.as_deref(), Some("admin") | Some("superadmin")) {
+        return Err(ApiError::Forbidden);
+    }
+
+    let summary = SecurityPriceService::new(pool)
+        .import_csv(&body, query.security_id, query.source)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(summary))
+}
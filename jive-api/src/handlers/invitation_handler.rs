@@ -5,8 +5,10 @@ use axum::{
     Extension,
 };
 use serde::Serialize;
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::config::AppConfig;
 use crate::models::invitation::{
     AcceptInvitationRequest, CreateInvitationRequest, InvitationResponse,
 };
@@ -18,12 +20,16 @@ use super::family_handler::ApiResponse;
 // Create invitation
 pub async fn create_invitation(
     State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
     Extension(ctx): Extension<ServiceContext>,
     Json(request): Json<CreateInvitationRequest>,
 ) -> Result<Json<ApiResponse<InvitationResponse>>, StatusCode> {
     let service = InvitationService::new(pool.clone());
 
-    match service.create_invitation(&ctx, request).await {
+    match service
+        .create_invitation(&ctx, request, &config.email)
+        .await
+    {
         Ok(invitation) => Ok(Json(ApiResponse::success(invitation))),
         Err(ServiceError::PermissionDenied) => Err(StatusCode::FORBIDDEN),
         Err(ServiceError::Conflict(_)) => Err(StatusCode::CONFLICT),
@@ -0,0 +1,263 @@
+//! 账本级权限（ACL）与只读分享链接 API
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::{ApiError, ApiResult};
+use crate::models::permission::{LedgerRole, Permission};
+use crate::services::ledger_acl_service::{
+    CreatedShareLink, LedgerAclEntry, ShareLink, ShareReportQuery, SharedLedgerReport,
+};
+use crate::services::{LedgerAclService, LedgerResource, ServiceError};
+
+/// 账本权限相关的 ServiceError -> ApiError
+pub(crate) fn access_error(e: ServiceError) -> ApiError {
+    match e {
+        ServiceError::PermissionDenied => ApiError::Forbidden,
+        ServiceError::NotFound { resource_type, .. } => {
+            ApiError::NotFound(format!("{} not found", resource_type))
+        }
+        ServiceError::ValidationError(msg) => ApiError::ValidationError(msg),
+        ServiceError::BusinessRuleViolation(msg) | ServiceError::Conflict(msg) => {
+            ApiError::BadRequest(msg)
+        }
+        ServiceError::CannotChangeOwnerRole => {
+            ApiError::BadRequest("Cannot change owner role".to_string())
+        }
+        ServiceError::AuthenticationError(_) => ApiError::Unauthorized,
+        ServiceError::DatabaseError(e) => ApiError::DatabaseError(e.to_string()),
+        _ => ApiError::InternalServerError,
+    }
+}
+
+/// 校验用户对账户所在账本的权限，返回用户 id
+pub(crate) async fn authorize_account(
+    pool: &PgPool,
+    claims: &Claims,
+    account_id: Uuid,
+    permission: Permission,
+) -> ApiResult<Uuid> {
+    let user_id = claims.user_id()?;
+    LedgerAclService::new(pool.clone())
+        .authorize_user_resource(user_id, LedgerResource::Account, account_id, permission)
+        .await
+        .map_err(access_error)?;
+    Ok(user_id)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetLedgerAclRequest {
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateShareLinkRequest {
+    pub name: Option<String>,
+    pub expires_in_days: Option<i64>,
+}
+
+/// GET /api/v1/ledgers/:id/acl
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers/{id}/acl",
+    tag = "ledger-access",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Vec<LedgerAclEntry>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_ledger_acl(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<LedgerAclEntry>>> {
+    let user_id = claims.user_id()?;
+    let service = LedgerAclService::new(pool);
+    let ctx = service
+        .context_for_ledger(user_id, ledger_id)
+        .await
+        .map_err(access_error)?;
+
+    let entries = service
+        .list_acl(&ctx, ledger_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(entries))
+}
+
+/// PUT /api/v1/ledgers/:id/acl/:user_id
+#[utoipa::path(
+    put,
+    path = "/api/v1/ledgers/{id}/acl/{user_id}",
+    tag = "ledger-access",
+    params(("id" = Uuid, Path), ("user_id" = Uuid, Path)),
+    request_body = SetLedgerAclRequest,
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn set_ledger_acl(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path((ledger_id, member_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<SetLedgerAclRequest>,
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    let role = LedgerRole::from_str_name(&req.role)
+        .ok_or_else(|| ApiError::ValidationError(format!("Invalid ledger role: {}", req.role)))?;
+
+    let service = LedgerAclService::new(pool);
+    let ctx = service
+        .context_for_ledger(user_id, ledger_id)
+        .await
+        .map_err(access_error)?;
+    service
+        .set_acl(&ctx, ledger_id, member_id, role)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/v1/ledgers/:id/acl/:user_id —— 恢复为家庭角色
+#[utoipa::path(
+    delete,
+    path = "/api/v1/ledgers/{id}/acl/{user_id}",
+    tag = "ledger-access",
+    params(("id" = Uuid, Path), ("user_id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn remove_ledger_acl(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path((ledger_id, member_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    let service = LedgerAclService::new(pool);
+    let ctx = service
+        .context_for_ledger(user_id, ledger_id)
+        .await
+        .map_err(access_error)?;
+    service
+        .remove_acl(&ctx, ledger_id, member_id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/ledgers/:id/share-links
+#[utoipa::path(
+    post,
+    path = "/api/v1/ledgers/{id}/share-links",
+    tag = "ledger-access",
+    params(("id" = Uuid, Path)),
+    request_body = CreateShareLinkRequest,
+    responses((status = 201, description = "已创建", body = CreatedShareLink), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_share_link(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(ledger_id): Path<Uuid>,
+    Json(req): Json<CreateShareLinkRequest>,
+) -> ApiResult<(StatusCode, Json<CreatedShareLink>)> {
+    let user_id = claims.user_id()?;
+    let service = LedgerAclService::new(pool);
+    let ctx = service
+        .context_for_ledger(user_id, ledger_id)
+        .await
+        .map_err(access_error)?;
+
+    let created = service
+        .create_share_link(&ctx, ledger_id, req.name, req.expires_in_days)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// GET /api/v1/ledgers/:id/share-links
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers/{id}/share-links",
+    tag = "ledger-access",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Vec<ShareLink>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_share_links(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<ShareLink>>> {
+    let user_id = claims.user_id()?;
+    let service = LedgerAclService::new(pool);
+    let ctx = service
+        .context_for_ledger(user_id, ledger_id)
+        .await
+        .map_err(access_error)?;
+
+    let links = service
+        .list_share_links(&ctx, ledger_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(links))
+}
+
+/// DELETE /api/v1/ledgers/:id/share-links/:link_id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/ledgers/{id}/share-links/{link_id}",
+    tag = "ledger-access",
+    params(("id" = Uuid, Path), ("link_id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_share_link(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path((ledger_id, link_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    let service = LedgerAclService::new(pool);
+    let ctx = service
+        .context_for_ledger(user_id, ledger_id)
+        .await
+        .map_err(access_error)?;
+    service
+        .revoke_share_link(&ctx, ledger_id, link_id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/shared/:token/report —— 无需登录，令牌即凭证
+#[utoipa::path(
+    get,
+    path = "/api/v1/shared/{token}/report",
+    tag = "ledger-access",
+    params(("token" = String, Path), ShareReportQuery),
+    responses((status = 200, description = "成功", body = SharedLedgerReport))
+)]
+pub async fn get_shared_report(
+    State(pool): State<PgPool>,
+    Path(token): Path<String>,
+    Query(query): Query<ShareReportQuery>,
+) -> ApiResult<Json<SharedLedgerReport>> {
+    let report = LedgerAclService::new(pool)
+        .shared_report(&token, &query)
+        .await
+        .map_err(|e| match e {
+            // 无效、过期、撤销统一返回 404，不暴露链接状态
+            ServiceError::AuthenticationError(_) => {
+                ApiError::NotFound("Share link not found".to_string())
+            }
+            other => access_error(other),
+        })?;
+    Ok(Json(report))
+}
@@ -11,9 +11,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Ledger {
     pub id: Uuid,
     pub family_id: Option<Uuid>,
@@ -29,7 +30,7 @@ pub struct Ledger {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateLedgerRequest {
     pub name: String,
     #[serde(rename = "type")]
@@ -39,19 +40,27 @@ pub struct CreateLedgerRequest {
     pub is_default: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateLedgerRequest {
     pub name: Option<String>,
     pub currency: Option<String>,
     pub is_default: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct ListLedgersQuery {
     pub page: Option<u32>,
     pub limit: Option<u32>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers",
+    tag = "ledgers",
+    params(ListLedgersQuery),
+    responses((status = 200, description = "成功", body = Object), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn list_ledgers(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -121,6 +130,13 @@ pub async fn list_ledgers(
     })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers/current",
+    tag = "ledgers",
+    responses((status = 200, description = "成功", body = Ledger), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_current_ledger(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -167,6 +183,14 @@ pub async fn get_current_ledger(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/ledgers",
+    tag = "ledgers",
+    request_body = CreateLedgerRequest,
+    responses((status = 200, description = "成功", body = Ledger), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_ledger(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -225,6 +249,14 @@ pub async fn create_ledger(
     Ok(Json(ledger))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers/{id}",
+    tag = "ledgers",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Ledger), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_ledger(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -266,6 +298,15 @@ pub async fn get_ledger(
     Ok(Json(ledger))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/ledgers/{id}",
+    tag = "ledgers",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateLedgerRequest,
+    responses((status = 200, description = "成功", body = Ledger), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_ledger(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -344,6 +385,14 @@ pub async fn update_ledger(
     Ok(Json(ledger))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/ledgers/{id}",
+    tag = "ledgers",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_ledger(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -431,6 +480,14 @@ async fn create_default_ledger(
 }
 
 // Get ledger statistics
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers/{id}/statistics",
+    tag = "ledgers",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Object), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_ledger_statistics(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -496,6 +553,14 @@ pub async fn get_ledger_statistics(
 }
 
 // Get ledger members
+#[utoipa::path(
+    get,
+    path = "/api/v1/ledgers/{id}/members",
+    tag = "ledgers",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = Object), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_ledger_members(
     State(pool): State<PgPool>,
     claims: Claims,
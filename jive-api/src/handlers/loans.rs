@@ -0,0 +1,196 @@
+//! 贷款 API：账户的贷款条款、还款计划、还款录入与提前还款测算
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::config::AppConfig;
+use crate::error::ApiResult;
+use crate::handlers::ledger_access::{access_error, authorize_account};
+use crate::models::Permission;
+use crate::services::loan_service::{
+    CreateLoanTermsRequest, LoanPaymentResult, LoanSchedule, LoanService, LoanTerms,
+    PrepaymentSimulation, RateChangeRequest, RecordLoanPaymentRequest, SimulatePrepaymentRequest,
+};
+
+/// GET /api/v1/accounts/:id/loan
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/{id}/loan",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = LoanTerms), (status = 404, description = "未设置贷款条款"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_loan_terms(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<LoanTerms>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let terms = LoanService::new(pool)
+        .get_terms(id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(terms))
+}
+
+/// POST /api/v1/accounts/:id/loan
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/loan",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    request_body = CreateLoanTermsRequest,
+    responses((status = 201, description = "已创建", body = LoanTerms), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_loan_terms(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateLoanTermsRequest>,
+) -> ApiResult<(StatusCode, Json<LoanTerms>)> {
+    let user_id = authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    let terms = LoanService::new(pool)
+        .create_terms(user_id, id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(terms)))
+}
+
+/// DELETE /api/v1/accounts/:id/loan
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accounts/{id}/loan",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "已删除"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_loan_terms(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    LoanService::new(pool)
+        .delete_terms(id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/accounts/:id/loan/schedule
+///
+/// 已还记录、剩余还款计划与预计结清日期
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/{id}/loan/schedule",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = LoanSchedule), (status = 404, description = "未设置贷款条款"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_loan_schedule(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<LoanSchedule>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let schedule = LoanService::new(pool)
+        .schedule(id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(schedule))
+}
+
+/// POST /api/v1/accounts/:id/loan/payments
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/loan/payments",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    request_body = RecordLoanPaymentRequest,
+    responses((status = 201, description = "已记录", body = LoanPaymentResult), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn record_loan_payment(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RecordLoanPaymentRequest>,
+) -> ApiResult<(StatusCode, Json<LoanPaymentResult>)> {
+    let user_id = authorize_account(&pool, &claims, id, Permission::CreateTransactions).await?;
+    authorize_account(
+        &pool,
+        &claims,
+        req.from_account_id,
+        Permission::CreateTransactions,
+    )
+    .await?;
+    let result = LoanService::new(pool)
+        .record_payment(user_id, id, req, &config.rate_resolver)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+/// POST /api/v1/accounts/:id/loan/rate-changes
+///
+/// 设置浮动利率重定价，返回更新后的还款计划
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/loan/rate-changes",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    request_body = RateChangeRequest,
+    responses((status = 200, description = "成功", body = LoanSchedule), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn set_loan_rate_change(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RateChangeRequest>,
+) -> ApiResult<Json<LoanSchedule>> {
+    authorize_account(&pool, &claims, id, Permission::EditAccounts).await?;
+    let schedule = LoanService::new(pool)
+        .set_rate_change(id, req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(schedule))
+}
+
+/// POST /api/v1/accounts/:id/loan/simulate
+///
+/// 提前还款测算，对比节省的利息与期数，不落库
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/loan/simulate",
+    tag = "accounts",
+    params(("id" = Uuid, Path)),
+    request_body = SimulatePrepaymentRequest,
+    responses((status = 200, description = "成功", body = PrepaymentSimulation), (status = 400, description = "参数错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn simulate_loan_prepayment(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SimulatePrepaymentRequest>,
+) -> ApiResult<Json<PrepaymentSimulation>> {
+    authorize_account(&pool, &claims, id, Permission::ViewAccounts).await?;
+    let simulation = LoanService::new(pool)
+        .simulate(id, req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(simulation))
+}
@@ -4,6 +4,7 @@ use axum::{
     response::Json,
 };
 use serde::Deserialize;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::models::{
@@ -17,25 +18,33 @@ use sqlx::PgPool;
 use super::family_handler::ApiResponse;
 
 // Add member request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AddMemberRequest {
     pub user_id: Uuid,
     pub role: MemberRole,
 }
 
 // Update role request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateRoleRequest {
     pub role: MemberRole,
 }
 
 // Update permissions request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdatePermissionsRequest {
     pub permissions: Vec<Permission>,
 }
 
 // Get family members
+#[utoipa::path(
+    get,
+    path = "/api/v1/families/{id}/members",
+    tag = "members",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_family_members(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -112,6 +121,15 @@ pub async fn get_family_members(
 }
 
 // Add member to family
+#[utoipa::path(
+    post,
+    path = "/api/v1/families/{id}/members",
+    tag = "members",
+    params(("id" = Uuid, Path)),
+    request_body = AddMemberRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<FamilyMember>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn add_member(
     State(pool): State<PgPool>,
     Path(family_id): Path<Uuid>,
@@ -147,6 +165,14 @@ pub async fn add_member(
 }
 
 // Remove member from family
+#[utoipa::path(
+    delete,
+    path = "/api/v1/families/{id}/members/{user_id}",
+    tag = "members",
+    params(("id" = Uuid, Path), ("user_id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn remove_member(
     State(pool): State<PgPool>,
     Path((family_id, member_id)): Path<(Uuid, Uuid)>,
@@ -178,6 +204,15 @@ pub async fn remove_member(
 }
 
 // Update member role
+#[utoipa::path(
+    put,
+    path = "/api/v1/families/{id}/members/{user_id}/role",
+    tag = "members",
+    params(("id" = Uuid, Path), ("user_id" = Uuid, Path)),
+    request_body = UpdateRoleRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<FamilyMember>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_member_role(
     State(pool): State<PgPool>,
     Path((family_id, member_id)): Path<(Uuid, Uuid)>,
@@ -213,6 +248,15 @@ pub async fn update_member_role(
 }
 
 // Update member permissions
+#[utoipa::path(
+    put,
+    path = "/api/v1/families/{id}/members/{user_id}/permissions",
+    tag = "members",
+    params(("id" = Uuid, Path), ("user_id" = Uuid, Path)),
+    request_body = UpdatePermissionsRequest,
+    responses((status = 200, description = "成功", body = ApiResponse<FamilyMember>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_member_permissions(
     State(pool): State<PgPool>,
     Path((family_id, member_id)): Path<(Uuid, Uuid)>,
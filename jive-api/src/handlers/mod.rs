@@ -1,17 +1,31 @@
 pub mod accounts;
+pub mod ai;
+pub mod api_docs;
 pub mod audit_handler;
 pub mod auth;
 pub mod auth_handler;
 pub mod banks;
+pub mod categorization;
+pub mod connections;
+pub mod credit_cards;
+pub mod digests;
 pub mod family_handler;
+pub mod installments;
+pub mod investments;
 pub mod invitation_handler;
+pub mod ledger_access;
 pub mod ledgers;
+pub mod loans;
 pub mod member_handler;
+pub mod notifications;
+pub mod push;
 pub mod payees;
+pub mod rate_alerts;
 pub mod rules;
 pub mod template_handler;
 pub mod transactions;
 pub mod transactions_shadow_example;
+pub mod webhooks;
 // Demo endpoints are optional
 pub mod category_handler;
 pub mod currency_handler;
@@ -0,0 +1,192 @@
+//! 通知中心接口：收件箱、已读 / 忽略与投递偏好
+//!
+//! 通知只属于接收人本人，不需要家庭权限校验。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::config::AppConfig;
+use crate::error::ApiResult;
+use crate::handlers::ledger_access::access_error;
+use crate::services::notification_service::{
+    MarkAllReadRequest, MarkAllReadResult, Notification, NotificationPreferences,
+    NotificationQuery, NotificationService, UnreadCount, UpdatePreferencesRequest,
+};
+use crate::ws::WsConnectionManager;
+
+/// GET /api/v1/notifications
+///
+/// 按时间倒序返回当前用户的通知；默认不含已忽略的通知
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications",
+    tag = "notifications",
+    params(NotificationQuery),
+    responses((status = 200, description = "成功", body = [Notification]), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_notifications(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Query(query): Query<NotificationQuery>,
+) -> ApiResult<Json<Vec<Notification>>> {
+    let user_id = claims.user_id()?;
+    let notifications = NotificationService::new(pool, &config.notification, ws)
+        .list(user_id, &query)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(notifications))
+}
+
+/// GET /api/v1/notifications/unread-count
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/unread-count",
+    tag = "notifications",
+    responses((status = 200, description = "成功", body = UnreadCount), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn unread_count(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+) -> ApiResult<Json<UnreadCount>> {
+    let user_id = claims.user_id()?;
+    let count = NotificationService::new(pool, &config.notification, ws)
+        .unread_count(user_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(count))
+}
+
+/// POST /api/v1/notifications/:id/read
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/{id}/read",
+    tag = "notifications",
+    params(("id" = Uuid, Path, description = "通知 ID")),
+    responses((status = 200, description = "成功", body = Notification), (status = 404, description = "通知不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn mark_read(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Notification>> {
+    let user_id = claims.user_id()?;
+    let notification = NotificationService::new(pool, &config.notification, ws)
+        .mark_read(user_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(notification))
+}
+
+/// POST /api/v1/notifications/read-all
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/read-all",
+    tag = "notifications",
+    request_body = MarkAllReadRequest,
+    responses((status = 200, description = "成功", body = MarkAllReadResult), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn mark_all_read(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Json(req): Json<MarkAllReadRequest>,
+) -> ApiResult<Json<MarkAllReadResult>> {
+    let user_id = claims.user_id()?;
+    let result = NotificationService::new(pool, &config.notification, ws)
+        .mark_all_read(user_id, req.kind.as_deref())
+        .await
+        .map_err(access_error)?;
+    Ok(Json(result))
+}
+
+/// POST /api/v1/notifications/:id/dismiss
+///
+/// 从收件箱隐藏通知（同时标记为已读）
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/{id}/dismiss",
+    tag = "notifications",
+    params(("id" = Uuid, Path, description = "通知 ID")),
+    responses((status = 204, description = "已忽略"), (status = 404, description = "通知不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn dismiss_notification(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    NotificationService::new(pool, &config.notification, ws)
+        .dismiss(user_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/notifications/preferences
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/preferences",
+    tag = "notifications",
+    responses((status = 200, description = "成功", body = NotificationPreferences), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_preferences(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+) -> ApiResult<Json<NotificationPreferences>> {
+    let user_id = claims.user_id()?;
+    let preferences = NotificationService::new(pool, &config.notification, ws)
+        .preferences(user_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(preferences))
+}
+
+/// PUT /api/v1/notifications/preferences
+///
+/// 整体替换投递偏好；首次设置 Webhook 或 `rotate_webhook_secret=true` 时生成新的签名密钥
+#[utoipa::path(
+    put,
+    path = "/api/v1/notifications/preferences",
+    tag = "notifications",
+    request_body = UpdatePreferencesRequest,
+    responses((status = 200, description = "成功", body = NotificationPreferences), (status = 400, description = "参数错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_preferences(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Json(req): Json<UpdatePreferencesRequest>,
+) -> ApiResult<Json<NotificationPreferences>> {
+    let user_id = claims.user_id()?;
+    let preferences = NotificationService::new(pool, &config.notification, ws)
+        .update_preferences(user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(preferences))
+}
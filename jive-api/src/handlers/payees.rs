@@ -9,12 +9,13 @@ use axum::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, QueryBuilder, Row};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::error::{ApiError, ApiResult};
 
 /// 收款人查询参数
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PayeeQuery {
     pub ledger_id: Option<Uuid>,
     pub search: Option<String>,
@@ -24,7 +25,7 @@ pub struct PayeeQuery {
 }
 
 /// 创建收款人请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreatePayeeRequest {
     pub ledger_id: Uuid,
     pub name: String,
@@ -37,7 +38,7 @@ pub struct CreatePayeeRequest {
 }
 
 /// 更新收款人请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdatePayeeRequest {
     pub name: Option<String>,
     pub category_id: Option<Uuid>,
@@ -50,7 +51,7 @@ pub struct UpdatePayeeRequest {
 }
 
 /// 收款人响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PayeeResponse {
     pub id: Uuid,
     pub ledger_id: Uuid,
@@ -72,7 +73,7 @@ pub struct PayeeResponse {
 }
 
 /// 收款人建议响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PayeeSuggestion {
     pub id: Uuid,
     pub name: String,
@@ -83,7 +84,7 @@ pub struct PayeeSuggestion {
 }
 
 /// 收款人统计
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PayeeStatistics {
     pub total_payees: i64,
     pub active_payees: i64,
@@ -93,7 +94,7 @@ pub struct PayeeStatistics {
     pub by_category: Vec<PayeeCategoryStats>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PayeeUsageStats {
     pub payee_id: Uuid,
     pub payee_name: String,
@@ -102,7 +103,7 @@ pub struct PayeeUsageStats {
     pub last_used: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PayeeCategoryStats {
     pub category_id: Option<Uuid>,
     pub category_name: Option<String>,
@@ -110,6 +111,13 @@ pub struct PayeeCategoryStats {
 }
 
 /// 获取收款人列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/payees",
+    tag = "payees",
+    params(PayeeQuery),
+    responses((status = 200, description = "成功", body = Vec<PayeeResponse>))
+)]
 pub async fn list_payees(
     Query(params): Query<PayeeQuery>,
     State(pool): State<PgPool>,
@@ -196,6 +204,13 @@ pub async fn list_payees(
 }
 
 /// 获取单个收款人
+#[utoipa::path(
+    get,
+    path = "/api/v1/payees/{id}",
+    tag = "payees",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = PayeeResponse))
+)]
 pub async fn get_payee(
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -247,6 +262,13 @@ pub async fn get_payee(
 }
 
 /// 创建收款人
+#[utoipa::path(
+    post,
+    path = "/api/v1/payees",
+    tag = "payees",
+    request_body = CreatePayeeRequest,
+    responses((status = 200, description = "成功", body = PayeeResponse))
+)]
 pub async fn create_payee(
     State(pool): State<PgPool>,
     Json(req): Json<CreatePayeeRequest>,
@@ -299,6 +321,14 @@ pub async fn create_payee(
 }
 
 /// 更新收款人
+#[utoipa::path(
+    put,
+    path = "/api/v1/payees/{id}",
+    tag = "payees",
+    params(("id" = Uuid, Path)),
+    request_body = UpdatePayeeRequest,
+    responses((status = 200, description = "成功", body = PayeeResponse))
+)]
 pub async fn update_payee(
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -366,6 +396,13 @@ pub async fn update_payee(
 }
 
 /// 删除收款人（软删除）
+#[utoipa::path(
+    delete,
+    path = "/api/v1/payees/{id}",
+    tag = "payees",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"))
+)]
 pub async fn delete_payee(
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -386,6 +423,13 @@ pub async fn delete_payee(
 }
 
 /// 获取收款人建议（基于输入文本）
+#[utoipa::path(
+    get,
+    path = "/api/v1/payees/suggestions",
+    tag = "payees",
+    params(PayeeSuggestionQuery),
+    responses((status = 200, description = "成功", body = Vec<PayeeSuggestion>))
+)]
 pub async fn get_payee_suggestions(
     Query(params): Query<PayeeSuggestionQuery>,
     State(pool): State<PgPool>,
@@ -448,13 +492,20 @@ pub async fn get_payee_suggestions(
 }
 
 /// 收款人建议查询参数
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PayeeSuggestionQuery {
     pub ledger_id: Option<Uuid>,
     pub text: Option<String>,
 }
 
 /// 获取收款人统计
+#[utoipa::path(
+    get,
+    path = "/api/v1/payees/statistics",
+    tag = "payees",
+    params(PayeeQuery),
+    responses((status = 200, description = "成功", body = PayeeStatistics))
+)]
 pub async fn get_payee_statistics(
     Query(params): Query<PayeeQuery>,
     State(pool): State<PgPool>,
@@ -558,6 +609,13 @@ pub async fn get_payee_statistics(
 }
 
 /// 合并重复的收款人
+#[utoipa::path(
+    post,
+    path = "/api/v1/payees/merge",
+    tag = "payees",
+    request_body = MergePayeesRequest,
+    responses((status = 200, description = "成功", body = PayeeResponse))
+)]
 pub async fn merge_payees(
     State(pool): State<PgPool>,
     Json(req): Json<MergePayeesRequest>,
@@ -597,7 +655,7 @@ pub async fn merge_payees(
 }
 
 /// 合并收款人请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MergePayeesRequest {
     pub target_id: Uuid,
     pub source_ids: Vec<Uuid>,
@@ -0,0 +1,121 @@
+//! 推送设备接口：客户端获取推送配置并注册 FCM 令牌 / Web Push 订阅
+//!
+//! 设备只属于当前用户；注册时根据 User-Agent 关联登录会话。
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::ApiResult;
+use crate::handlers::ledger_access::access_error;
+use crate::services::push::{
+    PushDevice, PushDeviceService, PushProvider, PushRegistry, RegisterDeviceRequest,
+};
+
+/// 客户端推送配置
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushConfigResponse {
+    /// 服务端已启用的推送渠道
+    pub providers: Vec<PushProvider>,
+    /// Web Push 订阅时使用的 applicationServerKey（base64url）
+    pub vapid_public_key: Option<String>,
+}
+
+/// GET /api/v1/push/config
+#[utoipa::path(
+    get,
+    path = "/api/v1/push/config",
+    tag = "push",
+    responses((status = 200, description = "成功", body = PushConfigResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn push_config(
+    State(registry): State<Arc<PushRegistry>>,
+    claims: Claims,
+) -> ApiResult<Json<PushConfigResponse>> {
+    claims.user_id()?;
+    Ok(Json(PushConfigResponse {
+        providers: registry.providers(),
+        vapid_public_key: registry.vapid_public_key().map(str::to_string),
+    }))
+}
+
+/// GET /api/v1/push/devices
+#[utoipa::path(
+    get,
+    path = "/api/v1/push/devices",
+    tag = "push",
+    responses((status = 200, description = "成功", body = [PushDevice]), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_devices(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> ApiResult<Json<Vec<PushDevice>>> {
+    let user_id = claims.user_id()?;
+    let devices = PushDeviceService::new(pool)
+        .list(user_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(devices))
+}
+
+/// POST /api/v1/push/devices
+///
+/// 注册或刷新设备；同一令牌再次注册时转移到当前用户与会话
+#[utoipa::path(
+    post,
+    path = "/api/v1/push/devices",
+    tag = "push",
+    request_body = RegisterDeviceRequest,
+    responses((status = 200, description = "成功", body = PushDevice), (status = 400, description = "令牌无效或推送渠道未启用"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn register_device(
+    State(pool): State<PgPool>,
+    State(registry): State<Arc<PushRegistry>>,
+    claims: Claims,
+    headers: HeaderMap,
+    Json(req): Json<RegisterDeviceRequest>,
+) -> ApiResult<Json<PushDevice>> {
+    let user_id = claims.user_id()?;
+    registry.require(req.provider).map_err(access_error)?;
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
+    let device = PushDeviceService::new(pool)
+        .register(user_id, user_agent, &req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(device))
+}
+
+/// DELETE /api/v1/push/devices/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/push/devices/{id}",
+    tag = "push",
+    params(("id" = Uuid, Path, description = "设备 ID")),
+    responses((status = 204, description = "已删除"), (status = 404, description = "设备不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn unregister_device(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    PushDeviceService::new(pool)
+        .unregister(user_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,110 @@
+//! 汇率 / 加密货币价格提醒 API
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::ApiResult;
+use crate::handlers::ledger_access::access_error;
+use crate::services::rate_alert_service::{
+    CreateRateAlertRequest, RateAlert, RateAlertService, UpdateRateAlertRequest,
+};
+use crate::ws::WsConnectionManager;
+
+/// GET /api/v1/rate-alerts
+#[utoipa::path(
+    get,
+    path = "/api/v1/rate-alerts",
+    tag = "rate-alerts",
+    responses((status = 200, description = "成功", body = Vec<RateAlert>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_rate_alerts(
+    State(pool): State<PgPool>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+) -> ApiResult<Json<Vec<RateAlert>>> {
+    let user_id = claims.user_id()?;
+    let alerts = RateAlertService::new(pool, ws)
+        .list(user_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(alerts))
+}
+
+/// POST /api/v1/rate-alerts
+#[utoipa::path(
+    post,
+    path = "/api/v1/rate-alerts",
+    tag = "rate-alerts",
+    request_body = CreateRateAlertRequest,
+    responses((status = 201, description = "已创建", body = RateAlert), (status = 400, description = "参数错误"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_rate_alert(
+    State(pool): State<PgPool>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Json(req): Json<CreateRateAlertRequest>,
+) -> ApiResult<(StatusCode, Json<RateAlert>)> {
+    let user_id = claims.user_id()?;
+    let alert = RateAlertService::new(pool, ws)
+        .create(user_id, req)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(alert)))
+}
+
+/// PUT /api/v1/rate-alerts/:id
+#[utoipa::path(
+    put,
+    path = "/api/v1/rate-alerts/{id}",
+    tag = "rate-alerts",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateRateAlertRequest,
+    responses((status = 200, description = "成功", body = RateAlert), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_rate_alert(
+    State(pool): State<PgPool>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateRateAlertRequest>,
+) -> ApiResult<Json<RateAlert>> {
+    let user_id = claims.user_id()?;
+    let alert = RateAlertService::new(pool, ws)
+        .update(user_id, id, req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(alert))
+}
+
+/// DELETE /api/v1/rate-alerts/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rate-alerts/{id}",
+    tag = "rate-alerts",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 404, description = "不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_rate_alert(
+    State(pool): State<PgPool>,
+    State(ws): State<Arc<WsConnectionManager>>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let user_id = claims.user_id()?;
+    RateAlertService::new(pool, ws)
+        .delete(user_id, id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -10,12 +10,13 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, QueryBuilder, Row};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::error::{ApiError, ApiResult};
 
 /// 规则查询参数
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct RuleQuery {
     pub ledger_id: Option<Uuid>,
     pub is_active: Option<bool>,
@@ -25,7 +26,7 @@ pub struct RuleQuery {
 }
 
 /// 创建规则请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateRuleRequest {
     pub ledger_id: Uuid,
     pub name: String,
@@ -39,7 +40,7 @@ pub struct CreateRuleRequest {
 }
 
 /// 更新规则请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateRuleRequest {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -50,7 +51,7 @@ pub struct UpdateRuleRequest {
 }
 
 /// 规则响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RuleResponse {
     pub id: Uuid,
     pub ledger_id: Uuid,
@@ -68,7 +69,7 @@ pub struct RuleResponse {
 }
 
 /// 规则执行结果
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RuleExecutionResult {
     pub rule_id: Uuid,
     pub rule_name: String,
@@ -96,7 +97,7 @@ pub struct RuleAction {
 }
 
 /// 批量规则执行请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ExecuteRulesRequest {
     pub transaction_ids: Option<Vec<Uuid>>,
     pub rule_ids: Option<Vec<Uuid>>,
@@ -104,6 +105,13 @@ pub struct ExecuteRulesRequest {
 }
 
 /// 获取规则列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/rules",
+    tag = "rules",
+    params(RuleQuery),
+    responses((status = 200, description = "成功", body = Vec<RuleResponse>))
+)]
 pub async fn list_rules(
     Query(params): Query<RuleQuery>,
     State(pool): State<PgPool>,
@@ -178,6 +186,13 @@ pub async fn list_rules(
 }
 
 /// 获取单个规则
+#[utoipa::path(
+    get,
+    path = "/api/v1/rules/{id}",
+    tag = "rules",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = RuleResponse))
+)]
 pub async fn get_rule(
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -220,6 +235,13 @@ pub async fn get_rule(
 }
 
 /// 创建规则
+#[utoipa::path(
+    post,
+    path = "/api/v1/rules",
+    tag = "rules",
+    request_body = CreateRuleRequest,
+    responses((status = 200, description = "成功", body = RuleResponse))
+)]
 pub async fn create_rule(
     State(pool): State<PgPool>,
     Json(req): Json<CreateRuleRequest>,
@@ -261,6 +283,14 @@ pub async fn create_rule(
 }
 
 /// 更新规则
+#[utoipa::path(
+    put,
+    path = "/api/v1/rules/{id}",
+    tag = "rules",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateRuleRequest,
+    responses((status = 200, description = "成功", body = RuleResponse))
+)]
 pub async fn update_rule(
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -318,6 +348,13 @@ pub async fn update_rule(
 }
 
 /// 删除规则（软删除）
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rules/{id}",
+    tag = "rules",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"))
+)]
 pub async fn delete_rule(
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -338,6 +375,13 @@ pub async fn delete_rule(
 }
 
 /// 执行规则
+#[utoipa::path(
+    post,
+    path = "/api/v1/rules/execute",
+    tag = "rules",
+    request_body = ExecuteRulesRequest,
+    responses((status = 200, description = "成功", body = Vec<RuleExecutionResult>))
+)]
 pub async fn execute_rules(
     State(pool): State<PgPool>,
     Json(req): Json<ExecuteRulesRequest>,
@@ -528,7 +572,7 @@ async fn apply_rule_actions(
                         if let Some(category_id) = action.target_value.as_str() {
                             if let Ok(uuid) = Uuid::parse_str(category_id) {
                                 sqlx::query(
-                                    "UPDATE transactions SET category_id = $1, updated_at = NOW() WHERE id = $2"
+                                    "UPDATE transactions SET category_id = $1, category_source = 'rule', updated_at = NOW() WHERE id = $2"
                                 )
                                 .bind(uuid)
                                 .bind(transaction_id)
@@ -5,6 +5,7 @@ use axum::{
 };
 use serde::Deserialize;
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use super::family_handler::ApiResponse;
@@ -14,13 +15,13 @@ use crate::{
     error::{ApiError, ApiResult},
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct ListQuery {
     pub q: Option<String>,
     pub archived: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTag {
     pub name: String,
     pub color: Option<String>,
@@ -28,7 +29,7 @@ pub struct CreateTag {
     pub group_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateTag {
     pub name: Option<String>,
     pub color: Option<String>,
@@ -37,12 +38,24 @@ pub struct UpdateTag {
     pub archived: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MergeTags {
     pub from_ids: Vec<Uuid>,
     pub to_id: Uuid,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/tags",
+    tag = "tags",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "成功（带 ETag），data 为 {items: [...]}", body = ApiResponse<serde_json::Value>),
+        (status = 304, description = "未修改（If-None-Match 命中）"),
+        (status = 401, description = "未认证")
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn list_tags(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -90,6 +103,14 @@ pub async fn list_tags(
     Ok(resp)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/tags",
+    tag = "tags",
+    request_body = CreateTag,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_tag(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -109,6 +130,15 @@ pub async fn create_tag(
     Ok(Json(ApiResponse::success(serde_json::json!({"tag": tag}))))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/tags/{id}",
+    tag = "tags",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateTag,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_tag(
     State(pool): State<PgPool>,
     _claims: Claims,
@@ -123,6 +153,14 @@ pub async fn update_tag(
     Ok(Json(ApiResponse::success(serde_json::json!({"tag": tag}))))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tags/{id}",
+    tag = "tags",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_tag(
     State(pool): State<PgPool>,
     _claims: Claims,
@@ -136,6 +174,14 @@ pub async fn delete_tag(
     Ok(Json(ApiResponse::success(serde_json::json!({"ok": true}))))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/tags/merge",
+    tag = "tags",
+    request_body = MergeTags,
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn merge_tags(
     State(pool): State<PgPool>,
     claims: Claims,
@@ -154,6 +200,13 @@ pub async fn merge_tags(
     )))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/tags/summary",
+    tag = "tags",
+    responses((status = 200, description = "成功", body = ApiResponse<serde_json::Value>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn tag_summary(
     State(pool): State<PgPool>,
     claims: Claims,
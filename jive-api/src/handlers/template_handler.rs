@@ -9,10 +9,11 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 /// 模板查询参数
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct TemplateQuery {
     #[allow(dead_code)]
     pub lang: Option<String>,
@@ -27,7 +28,7 @@ pub struct TemplateQuery {
 }
 
 /// 模板响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TemplateResponse {
     pub templates: Vec<SystemTemplate>,
     pub version: String,
@@ -36,7 +37,7 @@ pub struct TemplateResponse {
 }
 
 /// 图标响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct IconResponse {
     pub icons: HashMap<String, String>,
     pub cdn_base: String,
@@ -44,14 +45,14 @@ pub struct IconResponse {
 }
 
 /// 更新响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UpdateResponse {
     pub updates: Vec<TemplateUpdate>,
     pub has_more: bool,
 }
 
 /// 系统模板
-#[derive(Debug, Serialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
 pub struct SystemTemplate {
     pub id: Uuid,
     pub name: String,
@@ -72,7 +73,7 @@ pub struct SystemTemplate {
 }
 
 /// 模板更新记录
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TemplateUpdate {
     pub action: String, // "add", "update", "delete"
     pub template_id: Uuid,
@@ -81,7 +82,7 @@ pub struct TemplateUpdate {
 }
 
 /// 创建模板请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTemplateRequest {
     pub name: String,
     pub name_en: Option<String>,
@@ -96,7 +97,7 @@ pub struct CreateTemplateRequest {
 }
 
 /// 更新模板请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateTemplateRequest {
     pub name: Option<String>,
     pub name_en: Option<String>,
@@ -112,6 +113,16 @@ pub struct UpdateTemplateRequest {
 }
 
 /// 获取模板列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/templates/list",
+    tag = "templates",
+    params(TemplateQuery),
+    responses(
+        (status = 200, body = TemplateResponse),
+        (status = 304, description = "ETag 未变化")
+    )
+)]
 pub async fn get_templates(
     Query(params): Query<TemplateQuery>,
     State(pool): State<PgPool>,
@@ -229,6 +240,12 @@ pub async fn get_templates(
 }
 
 /// 获取图标列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/icons/list",
+    tag = "templates",
+    responses((status = 200, body = IconResponse))
+)]
 pub async fn get_icons(State(_pool): State<PgPool>) -> Json<IconResponse> {
     // 模拟图标映射
     let mut icons = HashMap::new();
@@ -249,6 +266,13 @@ pub async fn get_icons(State(_pool): State<PgPool>) -> Json<IconResponse> {
 }
 
 /// 获取模板更新（增量同步）
+#[utoipa::path(
+    get,
+    path = "/api/v1/templates/updates",
+    tag = "templates",
+    params(TemplateQuery),
+    responses((status = 200, body = UpdateResponse))
+)]
 pub async fn get_template_updates(
     Query(params): Query<TemplateQuery>,
     State(pool): State<PgPool>,
@@ -293,6 +317,13 @@ pub async fn get_template_updates(
 }
 
 /// 创建新模板（超级管理员）
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/templates",
+    tag = "admin",
+    request_body = CreateTemplateRequest,
+    responses((status = 200, body = SystemTemplate))
+)]
 pub async fn create_template(
     State(pool): State<PgPool>,
     Json(req): Json<CreateTemplateRequest>,
@@ -332,6 +363,14 @@ pub async fn create_template(
 }
 
 /// 更新模板（超级管理员）
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/templates/{template_id}",
+    tag = "admin",
+    params(("template_id" = Uuid, Path)),
+    request_body = UpdateTemplateRequest,
+    responses((status = 200, body = SystemTemplate), (status = 404))
+)]
 pub async fn update_template(
     Path(template_id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -441,6 +480,13 @@ pub async fn update_template(
 }
 
 /// 删除模板（超级管理员）
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/templates/{template_id}",
+    tag = "admin",
+    params(("template_id" = Uuid, Path)),
+    responses((status = 204), (status = 404))
+)]
 pub async fn delete_template(
     Path(template_id): Path<Uuid>,
     State(pool): State<PgPool>,
@@ -464,6 +510,13 @@ pub async fn delete_template(
 }
 
 /// 提交使用统计
+#[utoipa::path(
+    post,
+    path = "/api/v1/templates/usage",
+    tag = "templates",
+    request_body = Object,
+    responses((status = 200))
+)]
 pub async fn submit_usage(
     State(pool): State<PgPool>,
     Json(usage): Json<serde_json::Value>,
@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Executor, PgPool, QueryBuilder, Row};
 use std::convert::Infallible;
 use std::pin::Pin;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{
@@ -86,12 +87,36 @@ fn csv_escape_cell(mut s: String, delimiter: char) -> String {
         s
     }
 }
+use crate::config::{AppConfig, RateResolverConfig};
+use crate::handlers::ledger_access::access_error;
 use crate::models::permission::Permission;
 use crate::services::context::ServiceContext;
-use crate::services::{AuditService, AuthService};
+use crate::services::webhook_service::WebhookEventType;
+use crate::services::{
+    AuditService, AuthService, CurrencyService, LedgerAclService, LedgerResource,
+    TransactionValuationService, WebhookService,
+};
+
+/// 成员拥有该权限的账本（账本 ACL 优先于家庭角色）；
+/// 指定的账本无权限或没有任何可用账本时返回 Forbidden
+async fn permitted_ledgers(
+    pool: &PgPool,
+    ctx: &ServiceContext,
+    permission: Permission,
+    requested: Option<Uuid>,
+) -> ApiResult<Vec<Uuid>> {
+    let ledgers = LedgerAclService::new(pool.clone())
+        .authorized_ledgers(ctx, permission)
+        .await
+        .map_err(access_error)?;
+    if ledgers.is_empty() || requested.is_some_and(|id| !ledgers.contains(&id)) {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(ledgers)
+}
 
 /// 导出交易请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
 pub struct ExportTransactionsRequest {
     pub format: Option<String>, // csv, excel, pdf, json
     pub account_id: Option<Uuid>,
@@ -103,6 +128,14 @@ pub struct ExportTransactionsRequest {
 }
 
 /// 导出交易（返回 data:URL 形式的下载链接，避免服务器存储文件）
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/export",
+    tag = "transactions",
+    request_body = ExportTransactionsRequest,
+    responses((status = 200, description = "导出结果（含 data URL 形式的文件内容）", body = Object), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn export_transactions(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -119,8 +152,8 @@ pub async fn export_transactions(
         .validate_family_access(user_id, family_id)
         .await
         .map_err(|_| ApiError::Forbidden)?;
-    ctx.require_permission(Permission::ExportData)
-        .map_err(|_| ApiError::Forbidden)?;
+    let ledger_ids =
+        permitted_ledgers(&pool, &ctx, Permission::ExportData, req.ledger_id).await?;
     // 仅实现 CSV/JSON，其他格式返回错误提示
     let fmt = req.format.as_deref().unwrap_or("csv").to_lowercase();
     if fmt != "csv" && fmt != "json" {
@@ -134,14 +167,19 @@ pub async fn export_transactions(
     let mut query = QueryBuilder::new(
         "SELECT t.id, t.account_id, t.ledger_id, t.amount, t.transaction_type, t.transaction_date, \
          t.category_id, c.name as category_name, t.payee_id, p.name as payee_name, \
-         t.description, t.notes \
+         t.description, t.notes, COALESCE(a.currency, t.currency) as currency, \
+         t.base_amount, t.base_currency \
          FROM transactions t \
          JOIN ledgers l ON t.ledger_id = l.id \
+         LEFT JOIN accounts a ON t.account_id = a.id \
          LEFT JOIN categories c ON t.category_id = c.id \
          LEFT JOIN payees p ON t.payee_id = p.id \
          WHERE t.deleted_at IS NULL AND l.family_id = "
     );
     query.push_bind(ctx.family_id);
+    query.push(" AND t.ledger_id = ANY(");
+    query.push_bind(ledger_ids);
+    query.push(")");
 
     if let Some(account_id) = req.account_id {
         query.push(" AND t.account_id = ");
@@ -194,6 +232,9 @@ pub async fn export_transactions(
                 "payee_name": row.try_get::<String,_>("payee_name").ok(),
                 "description": row.try_get::<String,_>("description").ok(),
                 "notes": row.try_get::<String,_>("notes").ok(),
+                "currency": row.try_get::<String,_>("currency").ok(),
+                "base_amount": row.try_get::<Decimal,_>("base_amount").ok(),
+                "base_currency": row.try_get::<String,_>("base_currency").ok(),
             }));
         }
         let bytes =
@@ -311,13 +352,8 @@ pub async fn export_transactions(
             let mut out = String::new();
             if cfg.include_header {
                 out.push_str(&format!(
-                    "Date{}Description{}Amount{}Category{}Account{}Payee{}Type\n",
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter
+                    "Date{d}Description{d}Amount{d}Category{d}Account{d}Payee{d}Type{d}Currency{d}BaseAmount{d}BaseCurrency\n",
+                    d = cfg.delimiter
                 ));
             }
             for row in rows.into_iter() {
@@ -343,6 +379,11 @@ pub async fn export_transactions(
                     account_id.to_string(),
                     csv_escape_cell(payee.unwrap_or_default(), cfg.delimiter),
                     csv_escape_cell(ttype, cfg.delimiter),
+                    row.try_get::<String, _>("currency").unwrap_or_default(),
+                    row.try_get::<Decimal, _>("base_amount")
+                        .map(|v| v.to_string())
+                        .unwrap_or_default(),
+                    row.try_get::<String, _>("base_currency").unwrap_or_default(),
                 ];
                 out.push_str(&fields.join(&cfg.delimiter.to_string()));
                 out.push('\n');
@@ -410,6 +451,17 @@ pub async fn export_transactions(
 }
 
 /// 流式 CSV 下载（更适合浏览器原生下载）
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions/export.csv",
+    tag = "transactions",
+    params(ExportTransactionsRequest),
+    responses(
+        (status = 200, description = "CSV 文件流", content_type = "text/csv", body = String),
+        (status = 401, description = "未认证")
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn export_transactions_csv_stream(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -425,21 +477,25 @@ pub async fn export_transactions_csv_stream(
         .validate_family_access(user_id, family_id)
         .await
         .map_err(|_| ApiError::Forbidden)?;
-    ctx.require_permission(Permission::ExportData)
-        .map_err(|_| ApiError::Forbidden)?;
+    let ledger_ids = permitted_ledgers(&pool, &ctx, Permission::ExportData, q.ledger_id).await?;
 
     // 复用查询逻辑（与 JSON/CSV data:URL 相同条件，限定家庭）
     let mut query = QueryBuilder::new(
         "SELECT t.id, t.account_id, t.ledger_id, t.amount, t.transaction_type, t.transaction_date, \
          t.category_id, c.name as category_name, t.payee_id, p.name as payee_name, \
-         t.description, t.notes \
+         t.description, t.notes, COALESCE(a.currency, t.currency) as currency, \
+         t.base_amount, t.base_currency \
          FROM transactions t \
          JOIN ledgers l ON t.ledger_id = l.id \
+         LEFT JOIN accounts a ON t.account_id = a.id \
          LEFT JOIN categories c ON t.category_id = c.id \
          LEFT JOIN payees p ON t.payee_id = p.id \
          WHERE t.deleted_at IS NULL AND l.family_id = "
     );
     query.push_bind(ctx.family_id);
+    query.push(" AND t.ledger_id = ANY(");
+    query.push_bind(ledger_ids.clone());
+    query.push(")");
     if let Some(account_id) = q.account_id {
         query.push(" AND t.account_id = ");
         query.push_bind(account_id);
@@ -519,13 +575,8 @@ pub async fn export_transactions_csv_stream(
             let mut out = String::new();
             if cfg.include_header {
                 out.push_str(&format!(
-                    "Date{}Description{}Amount{}Category{}Account{}Payee{}Type\n",
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter,
-                    cfg.delimiter
+                    "Date{d}Description{d}Amount{d}Category{d}Account{d}Payee{d}Type{d}Currency{d}BaseAmount{d}BaseCurrency\n",
+                    d = cfg.delimiter
                 ));
             }
             for row in rows_all.iter() {
@@ -550,6 +601,11 @@ pub async fn export_transactions_csv_stream(
                     account_id.to_string(),
                     csv_escape_cell(payee.clone().unwrap_or_default(), cfg.delimiter),
                     csv_escape_cell(ttype, cfg.delimiter),
+                    row.try_get::<String, _>("currency").unwrap_or_default(),
+                    row.try_get::<Decimal, _>("base_amount")
+                        .map(|v| v.to_string())
+                        .unwrap_or_default(),
+                    row.try_get::<String, _>("base_currency").unwrap_or_default(),
                 ];
                 out.push_str(&fields.join(&cfg.delimiter.to_string()));
                 out.push('\n');
@@ -562,6 +618,9 @@ pub async fn export_transactions_csv_stream(
         "SELECT COUNT(*) AS c FROM transactions t JOIN ledgers l ON t.ledger_id = l.id WHERE t.deleted_at IS NULL AND l.family_id = "
     );
     count_q.push_bind(ctx.family_id);
+    count_q.push(" AND t.ledger_id = ANY(");
+    count_q.push_bind(ledger_ids);
+    count_q.push(")");
     if let Some(account_id) = q.account_id {
         count_q.push(" AND t.account_id = ");
         count_q.push_bind(account_id);
@@ -649,7 +708,7 @@ pub async fn export_transactions_csv_stream(
 }
 
 /// 交易查询参数
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct TransactionQuery {
     pub account_id: Option<Uuid>,
     pub ledger_id: Option<Uuid>,
@@ -669,7 +728,7 @@ pub struct TransactionQuery {
 }
 
 /// 创建交易请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTransactionRequest {
     pub account_id: Uuid,
     pub ledger_id: Uuid,
@@ -689,7 +748,7 @@ pub struct CreateTransactionRequest {
 }
 
 /// 更新交易请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateTransactionRequest {
     pub amount: Option<Decimal>,
     pub transaction_date: Option<NaiveDate>,
@@ -705,7 +764,7 @@ pub struct UpdateTransactionRequest {
 }
 
 /// 交易响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TransactionResponse {
     pub id: Uuid,
     pub account_id: Uuid,
@@ -730,9 +789,13 @@ pub struct TransactionResponse {
 }
 
 /// 交易统计
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TransactionStatistics {
+    /// 金额单位：家庭本位币
+    pub base_currency: String,
     pub total_count: i64,
+    /// 尚未折算为本位币的交易数；这些交易不计入任何金额合计
+    pub unvalued_count: i64,
     pub total_income: Decimal,
     pub total_expense: Decimal,
     pub net_amount: Decimal,
@@ -741,7 +804,7 @@ pub struct TransactionStatistics {
     pub by_month: Vec<MonthlyStatistics>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CategoryStatistics {
     pub category_id: Uuid,
     pub category_name: String,
@@ -750,7 +813,7 @@ pub struct CategoryStatistics {
     pub percentage: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MonthlyStatistics {
     pub month: String,
     pub income: Decimal,
@@ -760,7 +823,7 @@ pub struct MonthlyStatistics {
 }
 
 /// 批量交易操作请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct BulkTransactionRequest {
     pub transaction_ids: Vec<Uuid>,
     pub operation: String, // delete, update_category, update_status
@@ -769,6 +832,14 @@ pub struct BulkTransactionRequest {
 }
 
 /// 获取交易列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions",
+    tag = "transactions",
+    params(TransactionQuery),
+    responses((status = 200, description = "成功", body = Vec<TransactionResponse>), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn list_transactions(
     claims: Claims,
     Query(params): Query<TransactionQuery>,
@@ -791,9 +862,9 @@ pub async fn list_transactions(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    // 验证查看权限
-    ctx.require_permission(Permission::ViewTransactions)
-        .map_err(|_| ApiError::Forbidden)?;
+    // 验证查看权限（按账本 ACL 过滤可见账本）
+    let ledger_ids =
+        permitted_ledgers(&pool, &ctx, Permission::ViewTransactions, params.ledger_id).await?;
 
     // 构建基础查询 - 限制在用户的family范围内
     let mut query = QueryBuilder::new(
@@ -809,6 +880,9 @@ pub async fn list_transactions(
          WHERE t.deleted_at IS NULL AND l.family_id = ",
     );
     query.push_bind(family_id);
+    query.push(" AND t.ledger_id = ANY(");
+    query.push_bind(ledger_ids);
+    query.push(")");
 
     // 添加过滤条件
     if let Some(account_id) = params.account_id {
@@ -962,6 +1036,14 @@ pub async fn list_transactions(
 }
 
 /// 获取单个交易
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions/{id}",
+    tag = "transactions",
+    params(("id" = Uuid, Path)),
+    responses((status = 200, description = "成功", body = TransactionResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_transaction(
     claims: Claims,
     Path(id): Path<Uuid>,
@@ -979,8 +1061,15 @@ pub async fn get_transaction(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    ctx.require_permission(Permission::ViewTransactions)
-        .map_err(|_| ApiError::Forbidden)?;
+    LedgerAclService::new(pool.clone())
+        .authorize_resource(
+            &ctx,
+            LedgerResource::Transaction,
+            id,
+            Permission::ViewTransactions,
+        )
+        .await
+        .map_err(access_error)?;
 
     // 查询交易，确保属于用户的family
     let row = sqlx::query(
@@ -1047,10 +1136,19 @@ pub async fn get_transaction(
 }
 
 /// 创建交易
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions",
+    tag = "transactions",
+    request_body = CreateTransactionRequest,
+    responses((status = 200, description = "成功", body = TransactionResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_transaction(
     claims: Claims,
     State(pool): State<PgPool>,
     State(adapter): State<Option<std::sync::Arc<crate::adapters::transaction_adapter::TransactionAdapter>>>,
+    State(config): State<std::sync::Arc<AppConfig>>,
     Json(req): Json<CreateTransactionRequest>,
 ) -> ApiResult<Json<TransactionResponse>> {
     // 验证权限
@@ -1065,9 +1163,6 @@ pub async fn create_transaction(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    ctx.require_permission(Permission::CreateTransactions)
-        .map_err(|_| ApiError::Forbidden)?;
-
     // 验证ledger属于用户的family
     let ledger_check = sqlx::query("SELECT family_id FROM ledgers WHERE id = $1")
         .bind(req.ledger_id)
@@ -1081,6 +1176,12 @@ pub async fn create_transaction(
         return Err(ApiError::Forbidden);
     }
 
+    // 账本 ACL 优先于家庭角色
+    LedgerAclService::new(pool.clone())
+        .authorize(&ctx, req.ledger_id, Permission::CreateTransactions)
+        .await
+        .map_err(access_error)?;
+
     // 使用 adapter 创建交易 (新架构) 或回退到 legacy 实现
     if let Some(adapter) = adapter {
         // ✅ 新架构：通过 Adapter → AppService 处理
@@ -1104,6 +1205,14 @@ pub async fn create_transaction(
 
         // Note: adapter returns models::transaction::TransactionResponse which is wrapped in Json already
         let Json(adapter_response) = adapter.create_transaction(adapter_req).await?;
+        refresh_base_amount(&pool, adapter_response.id, &config.rate_resolver).await;
+        WebhookService::new(pool.clone())
+            .transactions_changed(
+                family_id,
+                &[adapter_response.id],
+                WebhookEventType::TransactionCreated,
+            )
+            .await;
 
         // Convert to handler's TransactionResponse format
         let response = TransactionResponse {
@@ -1215,17 +1324,32 @@ pub async fn create_transaction(
             .await
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
+        refresh_base_amount(&pool, id, &config.rate_resolver).await;
+        WebhookService::new(pool.clone())
+            .transactions_changed(family_id, &[id], WebhookEventType::TransactionCreated)
+            .await;
+
         // 查询完整的交易信息
         get_transaction(claims, Path(id), State(pool)).await
     }
 }
 
 /// 更新交易
+#[utoipa::path(
+    put,
+    path = "/api/v1/transactions/{id}",
+    tag = "transactions",
+    params(("id" = Uuid, Path)),
+    request_body = UpdateTransactionRequest,
+    responses((status = 200, description = "成功", body = TransactionResponse), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_transaction(
     claims: Claims,
     Path(id): Path<Uuid>,
     State(pool): State<PgPool>,
     State(adapter): State<Option<std::sync::Arc<crate::adapters::transaction_adapter::TransactionAdapter>>>,
+    State(config): State<std::sync::Arc<AppConfig>>,
     Json(req): Json<UpdateTransactionRequest>,
 ) -> ApiResult<Json<TransactionResponse>> {
     // 验证权限
@@ -1240,8 +1364,15 @@ pub async fn update_transaction(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    ctx.require_permission(Permission::EditTransactions)
-        .map_err(|_| ApiError::Forbidden)?;
+    LedgerAclService::new(pool.clone())
+        .authorize_resource(
+            &ctx,
+            LedgerResource::Transaction,
+            id,
+            Permission::EditTransactions,
+        )
+        .await
+        .map_err(access_error)?;
 
     // 验证交易属于用户的family
     let _transaction_check = sqlx::query(
@@ -1262,16 +1393,32 @@ pub async fn update_transaction(
     ))?;
 
     // 使用 adapter 更新交易 (新架构) 或回退到 legacy 实现
-    if let Some(_adapter) = adapter {
+    let response = if let Some(_adapter) = adapter {
         // ✅ 新架构：通过 Adapter → AppService 处理
         // Note: adapter.update_transaction expects CreateTransactionRequest with all fields
         // We need to convert UpdateTransactionRequest, but for now use legacy path
         // TODO: Enhance adapter to support partial updates
         // For now, fallback to legacy for update operations
-        legacy_update_transaction(id, req, pool, claims).await
+        legacy_update_transaction(id, req, pool.clone(), claims, &config.rate_resolver)
+            .await?
     } else {
         // ⚠️ Legacy 实现
-        legacy_update_transaction(id, req, pool, claims).await
+        legacy_update_transaction(id, req, pool.clone(), claims, &config.rate_resolver)
+            .await?
+    };
+    WebhookService::new(pool)
+        .transactions_changed(family_id, &[id], WebhookEventType::TransactionUpdated)
+        .await;
+    Ok(response)
+}
+
+/// 按交易日汇率刷新本位币金额；失败时由后台估值任务补齐
+async fn refresh_base_amount(pool: &PgPool, id: Uuid, config: &RateResolverConfig) {
+    if let Err(e) = TransactionValuationService::new(pool.clone())
+        .value_transaction(id, config)
+        .await
+    {
+        tracing::warn!("Failed to value transaction {}: {:?}", id, e);
     }
 }
 
@@ -1281,7 +1428,10 @@ async fn legacy_update_transaction(
     req: UpdateTransactionRequest,
     pool: PgPool,
     claims: Claims,
+    config: &RateResolverConfig,
 ) -> ApiResult<Json<TransactionResponse>> {
+    let revalue = req.amount.is_some() || req.transaction_date.is_some();
+
     // 构建动态更新查询
     let mut query = QueryBuilder::new("UPDATE transactions SET updated_at = NOW()");
 
@@ -1298,6 +1448,8 @@ async fn legacy_update_transaction(
     if let Some(category_id) = req.category_id {
         query.push(", category_id = ");
         query.push_bind(category_id);
+        // 用户手动设置的分类，作为分类模型的训练样本
+        query.push(", category_source = NULL");
     }
 
     if let Some(payee_id) = req.payee_id {
@@ -1354,11 +1506,23 @@ async fn legacy_update_transaction(
         return Err(ApiError::NotFound("Transaction not found".to_string()));
     }
 
+    if revalue {
+        refresh_base_amount(&pool, id, config).await;
+    }
+
     // 返回更新后的交易
     get_transaction(claims, Path(id), State(pool)).await
 }
 
 /// 删除交易（软删除）
+#[utoipa::path(
+    delete,
+    path = "/api/v1/transactions/{id}",
+    tag = "transactions",
+    params(("id" = Uuid, Path)),
+    responses((status = 204, description = "成功，无返回内容"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_transaction(
     claims: Claims,
     Path(id): Path<Uuid>,
@@ -1377,18 +1541,30 @@ pub async fn delete_transaction(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    ctx.require_permission(Permission::DeleteTransactions)
-        .map_err(|_| ApiError::Forbidden)?;
+    LedgerAclService::new(pool.clone())
+        .authorize_resource(
+            &ctx,
+            LedgerResource::Transaction,
+            id,
+            Permission::DeleteTransactions,
+        )
+        .await
+        .map_err(access_error)?;
+
+    // 删除前取快照，删除成功后发布 transaction.deleted
+    let webhooks = WebhookService::new(pool.clone());
+    let snapshots = webhooks.snapshots(family_id, &[id]).await;
 
     // 使用 adapter 删除交易 (新架构) 或回退到 legacy 实现
     if let Some(adapter) = adapter {
         // ✅ 新架构：通过 Adapter → AppService 处理
         adapter.delete_transaction(id).await?;
-        Ok(StatusCode::NO_CONTENT)
     } else {
         // ⚠️ Legacy 实现
-        legacy_delete_transaction(id, family_id, pool).await
+        legacy_delete_transaction(id, family_id, pool).await?;
     }
+    webhooks.transactions_deleted(family_id, snapshots).await;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Legacy delete implementation (extracted for reuse)
@@ -1461,6 +1637,14 @@ async fn legacy_delete_transaction(
 }
 
 /// 批量操作交易
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/bulk",
+    tag = "transactions",
+    request_body = BulkTransactionRequest,
+    responses((status = 200, description = "成功", body = Object), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn bulk_transaction_operations(
     claims: Claims,
     State(pool): State<PgPool>,
@@ -1478,18 +1662,13 @@ pub async fn bulk_transaction_operations(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    // 根据操作类型验证权限
-    match req.operation.as_str() {
-        "delete" => {
-            ctx.require_permission(Permission::DeleteTransactions)
-                .map_err(|_| ApiError::Forbidden)?;
-        }
-        "update_category" | "update_status" => {
-            ctx.require_permission(Permission::EditTransactions)
-                .map_err(|_| ApiError::Forbidden)?;
-        }
+    // 根据操作类型验证权限，只作用于有权限的账本
+    let permission = match req.operation.as_str() {
+        "delete" => Permission::DeleteTransactions,
+        "update_category" | "update_status" => Permission::EditTransactions,
         _ => return Err(ApiError::BadRequest("Invalid operation".to_string())),
-    }
+    };
+    let ledger_ids = permitted_ledgers(&pool, &ctx, permission, None).await?;
 
     match req.operation.as_str() {
         "delete" => {
@@ -1507,6 +1686,9 @@ pub async fn bulk_transaction_operations(
                  WHERE l.family_id = ",
             );
             fetch_query.push_bind(family_id);
+            fetch_query.push(" AND t.ledger_id = ANY(");
+            fetch_query.push_bind(ledger_ids.clone());
+            fetch_query.push(")");
             fetch_query.push(" AND t.id IN (");
             let mut separated = fetch_query.separated(", ");
             for id in &req.transaction_ids {
@@ -1548,6 +1730,9 @@ pub async fn bulk_transaction_operations(
                  WHERE t.ledger_id = l.id AND l.family_id = ",
             );
             delete_query.push_bind(family_id);
+            delete_query.push(" AND t.ledger_id = ANY(");
+            delete_query.push_bind(ledger_ids);
+            delete_query.push(")");
             delete_query.push(" AND t.id IN (");
             let mut separated = delete_query.separated(", ");
             for id in &req.transaction_ids {
@@ -1566,6 +1751,11 @@ pub async fn bulk_transaction_operations(
                 .await
                 .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
+            let webhooks = WebhookService::new(pool.clone());
+            let deleted: Vec<Uuid> = transactions_to_delete.iter().map(|r| r.get("id")).collect();
+            let snapshots = webhooks.snapshots(family_id, &deleted).await;
+            webhooks.transactions_deleted(family_id, snapshots).await;
+
             Ok(Json(serde_json::json!({
                 "operation": "delete",
                 "affected": transactions_to_delete.len()
@@ -1580,26 +1770,32 @@ pub async fn bulk_transaction_operations(
             let mut query = QueryBuilder::new("UPDATE transactions t SET category_id = ");
             query.push_bind(category_id);
             query.push(
-                ", updated_at = NOW() FROM ledgers l WHERE t.ledger_id = l.id AND l.family_id = ",
+                ", category_source = NULL, updated_at = NOW() FROM ledgers l WHERE t.ledger_id = l.id AND l.family_id = ",
             );
             query.push_bind(family_id);
+            query.push(" AND t.ledger_id = ANY(");
+            query.push_bind(ledger_ids.clone());
+            query.push(")");
             query.push(" AND t.id IN (");
 
             let mut separated = query.separated(", ");
             for id in &req.transaction_ids {
                 separated.push_bind(id);
             }
-            query.push(") AND t.deleted_at IS NULL");
+            query.push(") AND t.deleted_at IS NULL RETURNING t.id");
 
-            let result = query
-                .build()
-                .execute(&pool)
+            let updated: Vec<Uuid> = query
+                .build_query_scalar()
+                .fetch_all(&pool)
                 .await
                 .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            WebhookService::new(pool.clone())
+                .transactions_changed(family_id, &updated, WebhookEventType::TransactionUpdated)
+                .await;
 
             Ok(Json(serde_json::json!({
                 "operation": "update_category",
-                "affected": result.rows_affected()
+                "affected": updated.len()
             })))
         }
         "update_status" => {
@@ -1614,23 +1810,29 @@ pub async fn bulk_transaction_operations(
                 ", updated_at = NOW() FROM ledgers l WHERE t.ledger_id = l.id AND l.family_id = ",
             );
             query.push_bind(family_id);
+            query.push(" AND t.ledger_id = ANY(");
+            query.push_bind(ledger_ids.clone());
+            query.push(")");
             query.push(" AND t.id IN (");
 
             let mut separated = query.separated(", ");
             for id in &req.transaction_ids {
                 separated.push_bind(id);
             }
-            query.push(") AND t.deleted_at IS NULL");
+            query.push(") AND t.deleted_at IS NULL RETURNING t.id");
 
-            let result = query
-                .build()
-                .execute(&pool)
+            let updated: Vec<Uuid> = query
+                .build_query_scalar()
+                .fetch_all(&pool)
                 .await
                 .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            WebhookService::new(pool.clone())
+                .transactions_changed(family_id, &updated, WebhookEventType::TransactionUpdated)
+                .await;
 
             Ok(Json(serde_json::json!({
                 "operation": "update_status",
-                "affected": result.rows_affected()
+                "affected": updated.len()
             })))
         }
         _ => Err(ApiError::BadRequest("Invalid operation".to_string())),
@@ -1638,6 +1840,14 @@ pub async fn bulk_transaction_operations(
 }
 
 /// 获取交易统计
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions/statistics",
+    tag = "transactions",
+    params(TransactionQuery),
+    responses((status = 200, description = "成功", body = TransactionStatistics), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_transaction_statistics(
     claims: Claims,
     Query(params): Query<TransactionQuery>,
@@ -1655,9 +1865,6 @@ pub async fn get_transaction_statistics(
         .await
         .map_err(|_| ApiError::Forbidden)?;
 
-    ctx.require_permission(Permission::ViewTransactions)
-        .map_err(|_| ApiError::Forbidden)?;
-
     let ledger_id = params
         .ledger_id
         .ok_or(ApiError::BadRequest("ledger_id is required".to_string()))?;
@@ -1675,13 +1882,19 @@ pub async fn get_transaction_statistics(
         return Err(ApiError::Forbidden);
     }
 
+    LedgerAclService::new(pool.clone())
+        .authorize(&ctx, ledger_id, Permission::ViewTransactions)
+        .await
+        .map_err(access_error)?;
+
     // 获取总体统计
     let stats = sqlx::query(
         r#"
         SELECT
             COUNT(*) as total_count,
-            SUM(CASE WHEN transaction_type = 'income' THEN amount ELSE 0 END) as total_income,
-            SUM(CASE WHEN transaction_type = 'expense' THEN amount ELSE 0 END) as total_expense
+            COUNT(*) FILTER (WHERE base_amount IS NULL) as unvalued_count,
+            SUM(CASE WHEN transaction_type = 'income' THEN base_amount ELSE 0 END) as total_income,
+            SUM(CASE WHEN transaction_type = 'expense' THEN base_amount ELSE 0 END) as total_expense
         FROM transactions
         WHERE ledger_id = $1 AND deleted_at IS NULL
         "#,
@@ -1692,13 +1905,15 @@ pub async fn get_transaction_statistics(
     .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
     let total_count: i64 = stats.try_get("total_count").unwrap_or(0);
+    let unvalued_count: i64 = stats.try_get("unvalued_count").unwrap_or(0);
     let total_income: Option<Decimal> = stats.try_get("total_income").ok();
     let total_expense: Option<Decimal> = stats.try_get("total_expense").ok();
     let total_income = total_income.unwrap_or(Decimal::ZERO);
     let total_expense = total_expense.unwrap_or(Decimal::ZERO);
     let net_amount = total_income - total_expense;
-    let average_transaction = if total_count > 0 {
-        (total_income + total_expense) / Decimal::from(total_count)
+    let valued_count = total_count - unvalued_count;
+    let average_transaction = if valued_count > 0 {
+        (total_income + total_expense) / Decimal::from(valued_count)
     } else {
         Decimal::ZERO
     };
@@ -1710,7 +1925,7 @@ pub async fn get_transaction_statistics(
             category_id,
             category_name,
             COUNT(*) as count,
-            SUM(amount) as total_amount
+            COALESCE(SUM(base_amount), 0) as total_amount
         FROM transactions
         WHERE ledger_id = $1 AND deleted_at IS NULL AND category_id IS NOT NULL
         GROUP BY category_id, category_name
@@ -1766,8 +1981,8 @@ pub async fn get_transaction_statistics(
         r#"
         SELECT
             TO_CHAR(transaction_date, 'YYYY-MM') as month,
-            SUM(CASE WHEN transaction_type = 'income' THEN amount ELSE 0 END) as income,
-            SUM(CASE WHEN transaction_type = 'expense' THEN amount ELSE 0 END) as expense,
+            SUM(CASE WHEN transaction_type = 'income' THEN base_amount ELSE 0 END) as income,
+            SUM(CASE WHEN transaction_type = 'expense' THEN base_amount ELSE 0 END) as expense,
             COUNT(*) as transaction_count
         FROM transactions
         WHERE ledger_id = $1
@@ -1803,8 +2018,15 @@ pub async fn get_transaction_statistics(
         })
         .collect();
 
+    let base_currency = CurrencyService::new(pool.clone())
+        .family_base_currency(family_id)
+        .await
+        .map_err(access_error)?;
+
     let response = TransactionStatistics {
+        base_currency,
         total_count,
+        unvalued_count,
         total_income,
         total_expense,
         net_amount,
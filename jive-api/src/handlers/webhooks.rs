@@ -0,0 +1,219 @@
+//! 家庭 Webhook 接口：管理外发地址、查看投递日志与手动重新投递
+//!
+//! 作用于当前家庭（`family_id` 上下文），需要 ManageIntegrations 权限。
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::config::AppConfig;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::ledger_access::access_error;
+use crate::services::webhook_service::{
+    CreateWebhookRequest, UpdateWebhookRequest, WebhookDelivery, WebhookDeliveryDetail,
+    WebhookDeliveryQuery, WebhookEndpoint, WebhookService,
+};
+use crate::services::{AuthService, ServiceContext};
+
+async fn family_context(pool: &PgPool, claims: &Claims) -> ApiResult<ServiceContext> {
+    let user_id = claims.user_id()?;
+    let family_id = claims
+        .family_id
+        .ok_or(ApiError::BadRequest("缺少 family_id 上下文".to_string()))?;
+    AuthService::new(pool.clone())
+        .validate_family_access(user_id, family_id)
+        .await
+        .map_err(|_| ApiError::Forbidden)
+}
+
+/// GET /api/v1/webhooks
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    responses((status = 200, description = "成功", body = [WebhookEndpoint]), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_webhooks(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> ApiResult<Json<Vec<WebhookEndpoint>>> {
+    let ctx = family_context(&pool, &claims).await?;
+    let endpoints = WebhookService::new(pool)
+        .list(&ctx)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(endpoints))
+}
+
+/// POST /api/v1/webhooks
+///
+/// 新建地址；响应中的 `secret` 只返回这一次
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses((status = 201, description = "已创建", body = WebhookEndpoint), (status = 400, description = "参数错误"), (status = 403, description = "无权限"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_webhook(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<AppConfig>>,
+    claims: Claims,
+    Json(req): Json<CreateWebhookRequest>,
+) -> ApiResult<(StatusCode, Json<WebhookEndpoint>)> {
+    let ctx = family_context(&pool, &claims).await?;
+    let endpoint = WebhookService::new(pool)
+        .create(&ctx, &req, &config.webhook)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::CREATED, Json(endpoint)))
+}
+
+/// GET /api/v1/webhooks/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    responses((status = 200, description = "成功", body = WebhookEndpoint), (status = 404, description = "Webhook 不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_webhook(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<WebhookEndpoint>> {
+    let ctx = family_context(&pool, &claims).await?;
+    let endpoint = WebhookService::new(pool)
+        .get(&ctx, id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(endpoint))
+}
+
+/// PUT /api/v1/webhooks/:id
+///
+/// `rotate_secret: true` 时生成新密钥并在响应中返回
+#[utoipa::path(
+    put,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    request_body = UpdateWebhookRequest,
+    responses((status = 200, description = "成功", body = WebhookEndpoint), (status = 400, description = "参数错误"), (status = 404, description = "Webhook 不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_webhook(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateWebhookRequest>,
+) -> ApiResult<Json<WebhookEndpoint>> {
+    let ctx = family_context(&pool, &claims).await?;
+    let endpoint = WebhookService::new(pool)
+        .update(&ctx, id, &req)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(endpoint))
+}
+
+/// DELETE /api/v1/webhooks/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    responses((status = 204, description = "已删除"), (status = 404, description = "Webhook 不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_webhook(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let ctx = family_context(&pool, &claims).await?;
+    WebhookService::new(pool)
+        .delete(&ctx, id)
+        .await
+        .map_err(access_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/webhooks/:id/deliveries
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook ID"), WebhookDeliveryQuery),
+    responses((status = 200, description = "成功", body = [WebhookDelivery]), (status = 404, description = "Webhook 不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_deliveries(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<WebhookDeliveryQuery>,
+) -> ApiResult<Json<Vec<WebhookDelivery>>> {
+    let ctx = family_context(&pool, &claims).await?;
+    let deliveries = WebhookService::new(pool)
+        .deliveries(&ctx, id, &query)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(deliveries))
+}
+
+/// GET /api/v1/webhooks/:id/deliveries/:delivery_id
+///
+/// 请求体与每次请求的响应码、错误和耗时
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries/{delivery_id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook ID"), ("delivery_id" = Uuid, Path, description = "投递 ID")),
+    responses((status = 200, description = "成功", body = WebhookDeliveryDetail), (status = 404, description = "投递不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_delivery(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<WebhookDeliveryDetail>> {
+    let ctx = family_context(&pool, &claims).await?;
+    let detail = WebhookService::new(pool)
+        .delivery(&ctx, id, delivery_id)
+        .await
+        .map_err(access_error)?;
+    Ok(Json(detail))
+}
+
+/// POST /api/v1/webhooks/:id/deliveries/:delivery_id/redeliver
+///
+/// 以原请求体新建一条立即投递的记录
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook ID"), ("delivery_id" = Uuid, Path, description = "投递 ID")),
+    responses((status = 202, description = "已排队", body = WebhookDelivery), (status = 400, description = "Webhook 已停用"), (status = 404, description = "投递不存在"), (status = 401, description = "未认证")),
+    security(("bearer_auth" = []))
+)]
+pub async fn redeliver(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<(StatusCode, Json<WebhookDelivery>)> {
+    let ctx = family_context(&pool, &claims).await?;
+    let delivery = WebhookService::new(pool)
+        .redeliver(&ctx, id, delivery_id)
+        .await
+        .map_err(access_error)?;
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
@@ -9,6 +9,7 @@ pub mod handlers;
 pub mod metrics;
 pub mod middleware;
 pub mod models;
+pub mod openapi;
 pub mod services;
 pub mod shadow_mode;
 pub mod utils;
@@ -23,10 +24,21 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
-    pub ws_manager: Option<std::sync::Arc<crate::ws::WsConnectionManager>>, // Optional WebSocket manager
+    /// WebSocket 连接管理器，处理器与后台任务通过它向在线用户推送
+    pub ws_manager: Arc<crate::ws::WsConnectionManager>,
     pub redis: Option<redis::aio::ConnectionManager>,
     pub metrics: AppMetrics,
     pub transaction_adapter: Option<Arc<crate::adapters::transaction_adapter::TransactionAdapter>>, // Transaction adapter for clean architecture
+    /// 启动时读取的应用配置
+    pub config: Arc<crate::config::AppConfig>,
+    /// 汇率数据源注册表（熔断与健康状态）
+    pub fx_providers: Arc<crate::services::fx_providers::ProviderRegistry>,
+    /// 银行聚合连接器
+    pub bank_connectors: Arc<crate::services::bank_connectors::ConnectorRegistry>,
+    /// 推送发送通道
+    pub push: Arc<crate::services::push::PushRegistry>,
+    /// 大模型后端；未启用时为 None
+    pub llm: Option<Arc<crate::services::llm_providers::LlmBackend>>,
 }
 
 /// Application metrics
@@ -63,6 +75,8 @@ pub struct AppMetrics {
     pub rehash_fail_hash: Arc<AtomicU64>,
     pub rehash_fail_update: Arc<AtomicU64>,
     pub auth_login_rate_limited: Arc<AtomicU64>,
+    pub auth_login_locked: Arc<AtomicU64>,
+    pub auth_password_reset_total: Arc<AtomicU64>,
 }
 
 impl Default for AppMetrics {
@@ -103,6 +117,8 @@ impl AppMetrics {
             rehash_fail_hash: Arc::new(AtomicU64::new(0)),
             rehash_fail_update: Arc::new(AtomicU64::new(0)),
             auth_login_rate_limited: Arc::new(AtomicU64::new(0)),
+            auth_login_locked: Arc::new(AtomicU64::new(0)),
+            auth_password_reset_total: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -252,6 +268,19 @@ impl AppMetrics {
     pub fn get_login_rate_limited(&self) -> u64 {
         self.auth_login_rate_limited.load(Ordering::Relaxed)
     }
+    pub fn inc_login_locked(&self) {
+        self.auth_login_locked.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn get_login_locked(&self) -> u64 {
+        self.auth_login_locked.load(Ordering::Relaxed)
+    }
+    pub fn inc_password_reset(&self) {
+        self.auth_password_reset_total
+            .fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn get_password_reset(&self) -> u64 {
+        self.auth_password_reset_total.load(Ordering::Relaxed)
+    }
 }
 
 // 实现FromRef trait以便子状态可以从AppState中提取
@@ -282,6 +311,43 @@ impl FromRef<AppState> for Option<Arc<crate::adapters::transaction_adapter::Tran
     }
 }
 
+// AppConfig FromRef implementation
+impl FromRef<AppState> for Arc<crate::config::AppConfig> {
+    fn from_ref(app_state: &AppState) -> Arc<crate::config::AppConfig> {
+        app_state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<crate::ws::WsConnectionManager> {
+    fn from_ref(app_state: &AppState) -> Arc<crate::ws::WsConnectionManager> {
+        app_state.ws_manager.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<crate::services::fx_providers::ProviderRegistry> {
+    fn from_ref(app_state: &AppState) -> Arc<crate::services::fx_providers::ProviderRegistry> {
+        app_state.fx_providers.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<crate::services::bank_connectors::ConnectorRegistry> {
+    fn from_ref(app_state: &AppState) -> Arc<crate::services::bank_connectors::ConnectorRegistry> {
+        app_state.bank_connectors.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<crate::services::push::PushRegistry> {
+    fn from_ref(app_state: &AppState) -> Arc<crate::services::push::PushRegistry> {
+        app_state.push.clone()
+    }
+}
+
+impl FromRef<AppState> for Option<Arc<crate::services::llm_providers::LlmBackend>> {
+    fn from_ref(app_state: &AppState) -> Option<Arc<crate::services::llm_providers::LlmBackend>> {
+        app_state.llm.clone()
+    }
+}
+
 // Re-export commonly used types
 pub use error::{ApiError, ApiResult};
 pub use services::{ServiceContext, ServiceError};
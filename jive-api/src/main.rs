@@ -2,7 +2,7 @@
 //! 修复了所有模块依赖问题
 
 use axum::{
-    extract::{ws::WebSocketUpgrade, Query, State},
+    extract::{ws::WebSocketUpgrade, DefaultBodyLimit, Query, State},
     http::StatusCode,
     response::{Json, Response},
     routing::{delete, get, post, put},
@@ -18,43 +18,65 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
+use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // 使用库中的模块
-use jive_money_api::{adapters::transaction_adapter::TransactionAdapter, config::TransactionConfig, handlers, services, ws};
+use jive_money_api::config::AppConfig;
+use jive_money_api::{
+    adapters::transaction_adapter::TransactionAdapter, config::TransactionConfig, handlers,
+    services, ws,
+};
 
 // 导入处理器
 use handlers::accounts::*;
+use handlers::ai;
 #[cfg(feature = "demo_endpoints")]
 use handlers::audit_handler::{cleanup_audit_logs, export_audit_logs, get_audit_logs};
 use handlers::auth as auth_handlers;
+use handlers::categorization;
 use handlers::category_handler;
+use handlers::connections;
+use handlers::credit_cards;
 use handlers::currency_handler;
 use handlers::currency_handler_enhanced;
+use handlers::digests;
 use handlers::enhanced_profile;
 use handlers::family_handler::{
     create_family, delete_family, get_family, get_family_actions, get_family_statistics,
     get_role_descriptions, join_family, leave_family, list_families, request_verification_code,
     transfer_ownership, update_family,
 };
+use handlers::installments;
+use handlers::investments;
+use handlers::ledger_access::{
+    create_share_link, get_shared_report, list_ledger_acl, list_share_links, remove_ledger_acl,
+    revoke_share_link, set_ledger_acl,
+};
 use handlers::ledgers::{
     create_ledger, delete_ledger, get_current_ledger, get_ledger, get_ledger_members,
     get_ledger_statistics, list_ledgers, update_ledger,
 };
+use handlers::loans;
 use handlers::member_handler::{
     add_member, get_family_members, remove_member, update_member_permissions, update_member_role,
 };
+use handlers::notifications;
 use handlers::payees::*;
 #[cfg(feature = "demo_endpoints")]
 use handlers::placeholder::{activity_logs, advanced_settings, export_data, family_settings};
+use handlers::push;
+use handlers::rate_alerts;
 use handlers::rules::*;
 use handlers::tag_handler;
 use handlers::template_handler::*;
 use handlers::transactions::*;
+use handlers::webhooks;
 
 // 使用库中的 AppState
+use jive_money_api::middleware::rate_limit::{login_rate_limit, RateLimiter};
 use jive_money_api::AppState;
 
 /// WebSocket 查询参数
@@ -70,6 +92,7 @@ async fn handle_websocket(
     State(app_state): State<AppState>,
 ) -> Response {
     let pool = app_state.pool.clone();
+    let manager = app_state.ws_manager.clone();
     // 验证 token（简化版本）
     let token = query.token.unwrap_or_default();
     if token.is_empty() {
@@ -85,7 +108,7 @@ async fn handle_websocket(
     );
 
     // 升级为 WebSocket 连接
-    ws.on_upgrade(move |socket| ws::handle_socket(socket, token, pool))
+    ws.on_upgrade(move |socket| ws::handle_socket(socket, token, pool, manager))
 }
 
 #[tokio::main]
@@ -233,30 +256,72 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
+    // 读取应用配置并构建外部数据源注册表
+    let config = Arc::new(AppConfig::default());
+    let fx_providers = Arc::new(services::fx_providers::ProviderRegistry::from_config(
+        &config.fx_providers,
+    ));
+    services::exchange_rate_api::EXCHANGE_RATE_SERVICE
+        .lock()
+        .await
+        .set_registry(fx_providers.clone());
+    let bank_connectors = Arc::new(services::bank_connectors::ConnectorRegistry::from_config(
+        &config.bank_connectors,
+    ));
+    let push = Arc::new(services::push::PushRegistry::from_config(&config.push));
+    let llm = services::llm_providers::LlmBackend::from_config(&config.llm).map(Arc::new);
+
     // 创建应用状态
     let app_state = AppState {
         pool: pool.clone(),
-        ws_manager: Some(ws_manager.clone()),
+        ws_manager: ws_manager.clone(),
         redis: redis_manager,
         metrics,
         transaction_adapter,
+        config: config.clone(),
+        fx_providers,
+        bank_connectors,
+        push: push.clone(),
+        llm,
     };
 
     // 启动定时任务（汇率更新等）
     info!("🕒 Starting scheduled tasks...");
     let pool_arc = Arc::new(pool.clone());
-    services::scheduled_tasks::init_scheduled_tasks(pool_arc).await;
+    services::scheduled_tasks::init_scheduled_tasks(pool_arc, config, push, ws_manager).await;
     info!("✅ Scheduled tasks started");
 
     // 统一使用 middleware/cors.rs 中的 CORS 配置，避免与其它入口重复/漂移
     use jive_money_api::middleware::cors::create_cors_layer;
     let cors = create_cors_layer();
 
+    // 登录限流（按 IP + 邮箱），AUTH_RATE_LIMIT 格式为 "次数/秒"
+    let (login_max, login_window) = std::env::var("AUTH_RATE_LIMIT")
+        .ok()
+        .and_then(|v| {
+            let (max, window) = v.split_once('/')?;
+            Some((max.trim().parse().ok()?, window.trim().parse().ok()?))
+        })
+        .unwrap_or((30, 60));
+    let login_limiter = RateLimiter::new(login_max, login_window);
+    // 找回密码接口单独计数，避免与登录共享配额
+    let password_reset_limiter = RateLimiter::new(login_max, login_window);
+
+    // Swagger UI 静态资源（固定版本，同源提供）
+    let swagger_ui = ServeDir::new(&app_state.config.docs.swagger_ui_dir);
+
     // 路由配置
     let app = Router::new()
         // 健康检查
         .route("/health", get(health_check))
         .route("/", get(api_info))
+        // OpenAPI 文档
+        .route(
+            "/api/v1/openapi.json",
+            get(handlers::api_docs::openapi_json),
+        )
+        .route("/api/v1/docs", get(handlers::api_docs::docs_page))
+        .nest_service("/api/v1/docs/assets", swagger_ui)
         // WebSocket 端点
         .route("/ws", get(handle_websocket))
         // 分类模板 API
@@ -277,6 +342,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             get(get_account).put(update_account).delete(delete_account),
         )
         .route("/api/v1/accounts/statistics", get(get_account_statistics))
+        // 贷款：还款计划、还款录入与提前还款测算
+        .route(
+            "/api/v1/accounts/:id/loan",
+            get(loans::get_loan_terms)
+                .post(loans::create_loan_terms)
+                .delete(loans::delete_loan_terms),
+        )
+        .route(
+            "/api/v1/accounts/:id/loan/schedule",
+            get(loans::get_loan_schedule),
+        )
+        .route(
+            "/api/v1/accounts/:id/loan/payments",
+            post(loans::record_loan_payment),
+        )
+        .route(
+            "/api/v1/accounts/:id/loan/rate-changes",
+            post(loans::set_loan_rate_change),
+        )
+        .route(
+            "/api/v1/accounts/:id/loan/simulate",
+            post(loans::simulate_loan_prepayment),
+        )
         // 交易管理 API
         .route(
             "/api/v1/transactions",
@@ -301,6 +389,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/v1/transactions/statistics",
             get(get_transaction_statistics),
         )
+        .route(
+            "/api/v1/transactions/suggestions",
+            get(categorization::suggest_for_transaction),
+        )
         // 收款人管理 API
         .route("/api/v1/payees", get(list_payees).post(create_payee))
         .route(
@@ -322,8 +414,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/v1/auth/register",
             post(auth_handlers::register_with_family),
         )
-        .route("/api/v1/auth/login", post(auth_handlers::login))
+        .route(
+            "/api/v1/auth/login",
+            post(auth_handlers::login).layer(axum::middleware::from_fn_with_state(
+                (login_limiter, app_state.clone()),
+                login_rate_limit,
+            )),
+        )
         .route("/api/v1/auth/refresh", post(auth_handlers::refresh_token))
+        .route(
+            "/api/v1/auth/password/forgot",
+            post(auth_handlers::forgot_password).layer(axum::middleware::from_fn_with_state(
+                (password_reset_limiter.clone(), app_state.clone()),
+                login_rate_limit,
+            )),
+        )
+        .route(
+            "/api/v1/auth/password/reset",
+            post(auth_handlers::reset_password).layer(axum::middleware::from_fn_with_state(
+                (password_reset_limiter, app_state.clone()),
+                login_rate_limit,
+            )),
+        )
         .route(
             "/api/v1/auth/user",
             get(auth_handlers::get_current_user).put(auth_handlers::update_user),
@@ -401,6 +513,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .route("/api/v1/ledgers/:id/statistics", get(get_ledger_statistics))
         .route("/api/v1/ledgers/:id/members", get(get_ledger_members))
+        // 账本级权限与只读分享
+        .route("/api/v1/ledgers/:id/acl", get(list_ledger_acl))
+        .route(
+            "/api/v1/ledgers/:id/acl/:user_id",
+            put(set_ledger_acl).delete(remove_ledger_acl),
+        )
+        .route(
+            "/api/v1/ledgers/:id/share-links",
+            get(list_share_links).post(create_share_link),
+        )
+        .route(
+            "/api/v1/ledgers/:id/share-links/:link_id",
+            delete(revoke_share_link),
+        )
+        .route("/api/v1/shared/:token/report", get(get_shared_report))
         // 货币管理 API - 基础功能
         .route(
             "/api/v1/currencies",
@@ -439,6 +566,226 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/v1/currencies/history",
             get(currency_handler::get_exchange_rate_history),
         )
+        .route(
+            "/api/v1/currencies/fx-gain-loss",
+            get(currency_handler::get_fx_gain_loss),
+        )
+        .route(
+            "/api/v1/admin/exchange-rates/import",
+            // ECB 全量历史文件约数 MB，放宽默认的 2MB 请求体限制
+            post(currency_handler::import_historical_rates)
+                .layer(DefaultBodyLimit::max(32 * 1024 * 1024)),
+        )
+        // 汇率 / 加密货币价格提醒
+        .route(
+            "/api/v1/rate-alerts",
+            get(rate_alerts::list_rate_alerts).post(rate_alerts::create_rate_alert),
+        )
+        .route(
+            "/api/v1/rate-alerts/:id",
+            put(rate_alerts::update_rate_alert).delete(rate_alerts::delete_rate_alert),
+        )
+        // 投资账户：证券、交易批次、持仓
+        .route(
+            "/api/v1/investments/securities",
+            get(investments::list_securities).post(investments::create_security),
+        )
+        .route(
+            "/api/v1/investments/securities/:id/prices",
+            get(investments::list_security_prices),
+        )
+        .route(
+            "/api/v1/admin/security-prices/import",
+            post(investments::import_security_prices)
+                .layer(DefaultBodyLimit::max(32 * 1024 * 1024)),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/trades",
+            get(investments::list_trades).post(investments::execute_trade),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/splits",
+            post(investments::record_split),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/corporate-actions",
+            post(investments::record_corporate_action),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/income",
+            get(investments::get_income_history).post(investments::record_income),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/income/projection",
+            get(investments::get_income_projection),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/holdings",
+            get(investments::get_holdings),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/lots",
+            get(investments::list_lots),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/realized-gains",
+            get(investments::get_realized_gains),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/performance",
+            get(investments::get_performance),
+        )
+        .route(
+            "/api/v1/investments/accounts/:id/cost-basis-method",
+            put(investments::set_cost_basis_method),
+        )
+        // 信用卡：账单规则与月度账单
+        .route(
+            "/api/v1/credit-cards",
+            get(credit_cards::list_credit_cards).post(credit_cards::create_credit_card),
+        )
+        .route(
+            "/api/v1/credit-cards/:id",
+            get(credit_cards::get_credit_card)
+                .put(credit_cards::update_credit_card)
+                .delete(credit_cards::delete_credit_card),
+        )
+        .route(
+            "/api/v1/credit-cards/:id/statements",
+            get(credit_cards::list_statements).post(credit_cards::generate_statements),
+        )
+        .route(
+            "/api/v1/credit-cards/:id/cycle",
+            get(credit_cards::get_current_cycle),
+        )
+        // 分期：消费转分期与提前还款
+        .route(
+            "/api/v1/installments",
+            get(installments::list_installment_plans).post(installments::create_installment_plan),
+        )
+        .route(
+            "/api/v1/installments/:id",
+            get(installments::get_installment_plan).put(installments::update_installment_plan),
+        )
+        .route(
+            "/api/v1/installments/:id/payoff",
+            post(installments::pay_off_installment_plan),
+        )
+        // 银行连接：Link 授权、增量同步、重新授权与机构 webhook
+        .route(
+            "/api/v1/connections",
+            get(connections::list_connections).post(connections::create_connection),
+        )
+        .route(
+            "/api/v1/connections/link-token",
+            post(connections::create_link_token),
+        )
+        .route(
+            "/api/v1/connections/:id",
+            get(connections::get_connection).delete(connections::delete_connection),
+        )
+        .route(
+            "/api/v1/connections/:id/sync",
+            post(connections::sync_connection),
+        )
+        .route(
+            "/api/v1/connections/:id/reauth",
+            post(connections::reauth_connection),
+        )
+        .route(
+            "/api/v1/connections/:id/accounts/:account_id",
+            put(connections::link_connection_account),
+        )
+        .route(
+            "/api/v1/webhooks/bank/:provider",
+            post(connections::bank_webhook),
+        )
+        // AI：大模型分类与财务助手对话
+        .route("/api/v1/ai/status", get(ai::ai_status))
+        .route("/api/v1/ai/categorize", post(ai::categorize_transactions))
+        .route(
+            "/api/v1/ai/categorizations/:transaction_id",
+            get(ai::categorization_history),
+        )
+        .route(
+            "/api/v1/ai/chats",
+            get(ai::list_chats).post(ai::create_chat),
+        )
+        .route(
+            "/api/v1/ai/chats/:id",
+            get(ai::get_chat).delete(ai::delete_chat),
+        )
+        .route("/api/v1/ai/chats/:id/messages", post(ai::send_chat_message))
+        // 本地分类模型（按家庭训练）
+        .route(
+            "/api/v1/categorization/auto",
+            post(categorization::auto_categorize),
+        )
+        .route(
+            "/api/v1/categorization/model",
+            get(categorization::model_status),
+        )
+        .route(
+            "/api/v1/categorization/model/train",
+            post(categorization::train_model),
+        )
+        // 通知中心
+        .route(
+            "/api/v1/notifications",
+            get(notifications::list_notifications),
+        )
+        .route(
+            "/api/v1/notifications/unread-count",
+            get(notifications::unread_count),
+        )
+        .route(
+            "/api/v1/notifications/read-all",
+            post(notifications::mark_all_read),
+        )
+        .route(
+            "/api/v1/notifications/preferences",
+            get(notifications::get_preferences).put(notifications::update_preferences),
+        )
+        .route(
+            "/api/v1/notifications/:id/read",
+            post(notifications::mark_read),
+        )
+        .route(
+            "/api/v1/notifications/:id/dismiss",
+            post(notifications::dismiss_notification),
+        )
+        .route("/api/v1/push/config", get(push::push_config))
+        .route("/api/v1/digests", get(digests::list_digests))
+        .route("/api/v1/digests/preview", get(digests::preview_digest))
+        .route("/api/v1/digests/:id", get(digests::get_digest))
+        .route(
+            "/api/v1/push/devices",
+            get(push::list_devices).post(push::register_device),
+        )
+        .route("/api/v1/push/devices/:id", delete(push::unregister_device))
+        // 家庭 Webhook
+        .route(
+            "/api/v1/webhooks",
+            get(webhooks::list_webhooks).post(webhooks::create_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:id",
+            get(webhooks::get_webhook)
+                .put(webhooks::update_webhook)
+                .delete(webhooks::delete_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries",
+            get(webhooks::list_deliveries),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries/:delivery_id",
+            get(webhooks::get_delivery),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries/:delivery_id/redeliver",
+            post(webhooks::redeliver),
+        )
         .route(
             "/api/v1/currencies/popular-pairs",
             get(currency_handler::get_popular_exchange_pairs),
@@ -578,6 +925,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("  🔐 Authentication API:");
     info!("    POST /api/v1/auth/register     - 用户注册");
     info!("    POST /api/v1/auth/login        - 用户登录");
+    info!("    POST /api/v1/auth/password/forgot - 找回密码（发送验证码）");
+    info!("    POST /api/v1/auth/password/reset  - 重置密码");
     info!("    POST /api/v1/auth/refresh      - 刷新令牌");
     info!("    GET  /api/v1/auth/user         - 获取用户信息");
     info!("    PUT  /api/v1/auth/user         - 更新用户信息");
@@ -593,13 +942,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("    /api/v1/rules                   - 规则引擎");
     info!("    /api/v1/templates               - 分类模板");
     info!("    /api/v1/ledgers                 - 账本管理");
+    info!("    /api/v1/shared/:token/report    - 账本只读分享报表");
+    info!("");
+    info!("  📖 OpenAPI:");
+    info!("    GET  /api/v1/openapi.json      - OpenAPI 3.1 文档");
+    info!("    GET  /api/v1/docs              - 文档查看页面");
     info!("");
     info!("💡 Tips:");
     info!("  - Use Authorization header with 'Bearer <token>' for authenticated requests");
     info!("  - WebSocket requires token in query parameter");
     info!("  - All timestamps are in UTC");
 
-    axum::serve(listener, app).await?;
+    // 注入 ConnectInfo，供客户端 IP 解析（登录限流 / 登录安全）使用
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -667,7 +1026,8 @@ async fn health_check(State(state): State<AppState>) -> Json<serde_json::Value>
                 "todays_rows": todays_rows,
                 "manual_overrides_active": manual_active,
                 "manual_overrides_expired": manual_expired
-            }
+            },
+            "fx_providers": state.fx_providers.health()
         },
         "timestamp": chrono::Utc::now().to_rfc3339()
     }))
@@ -699,7 +1059,8 @@ async fn api_info() -> Json<serde_json::Value> {
             "auth": "/api/v1/auth",
             "ledgers": "/api/v1/ledgers"
         },
-        "documentation": "https://github.com/yourusername/jive-money-api/wiki"
+        "documentation": "/api/v1/docs",
+        "openapi": "/api/v1/openapi.json"
     }))
 }
 
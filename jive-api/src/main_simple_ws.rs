@@ -11,13 +11,20 @@ use jive_money_api::middleware::cors::create_cors_layer;
 use serde_json::json;
 use sqlx::postgres::PgPoolOptions;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use jive_money_api::config::AppConfig;
 use jive_money_api::handlers;
+use jive_money_api::services::bank_connectors::ConnectorRegistry;
+use jive_money_api::services::fx_providers::ProviderRegistry;
+use jive_money_api::services::llm_providers::LlmBackend;
+use jive_money_api::services::push::PushRegistry;
+use jive_money_api::ws::WsConnectionManager;
 // WebSocket模块暂时不包含，避免编译错误
 
 use handlers::accounts::*;
@@ -75,12 +82,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // 创建应用状态
+    let config = Arc::new(AppConfig::default());
     let app_state = jive_money_api::AppState {
         pool: pool.clone(),
-        ws_manager: None,
+        ws_manager: Arc::new(WsConnectionManager::new()),
         redis: None,
         metrics: jive_money_api::AppMetrics::new(),
         transaction_adapter: None, // No adapter in simple mode (uses legacy SQL)
+        config: config.clone(),
+        fx_providers: Arc::new(ProviderRegistry::from_config(&config.fx_providers)),
+        bank_connectors: Arc::new(ConnectorRegistry::from_config(&config.bank_connectors)),
+        push: Arc::new(PushRegistry::from_config(&config.push)),
+        llm: LlmBackend::from_config(&config.llm).map(Arc::new),
     };
 
     // 使用统一的 CORS Layer（支持 CORS_DEV=1 开发模式）
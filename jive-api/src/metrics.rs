@@ -96,6 +96,8 @@ pub async fn metrics_handler(
         let secs = start.elapsed().as_secs_f64();
         format!("process_uptime_seconds {}\n", secs)
     };
+    // FX provider health is in-memory and cheap; keep it out of the 30s cache.
+    let uptime_line = format!("{}{}", uptime_line, fx_provider_metrics(&state.fx_providers));
     if let Some(base) = cached_base { return (StatusCode::OK, [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], format!("{}{}", base, uptime_line)); }
     let pool: &PgPool = &state.pool;
     // Build info gauge (value always 1) emitted once per scrape
@@ -135,6 +137,8 @@ pub async fn metrics_handler(
     let pw_change = state.metrics.get_password_change();
     let pw_change_rehash = state.metrics.get_password_change_rehash();
     let login_rate_limited = state.metrics.get_login_rate_limited();
+    let login_locked = state.metrics.get_login_locked();
+    let pw_reset = state.metrics.get_password_reset();
     // Histogram exports: convert ns sum back to seconds for Prometheus _sum
     let buf_sum_sec = state.metrics.export_dur_buf_sum_ns.load(std::sync::atomic::Ordering::Relaxed) as f64 / 1e9;
     let buf_count = state.metrics.export_dur_buf_count.load(std::sync::atomic::Ordering::Relaxed);
@@ -194,6 +198,9 @@ pub async fn metrics_handler(
     buf.push_str("# HELP auth_login_rate_limited_total Login attempts blocked by rate limiter.\n");
     buf.push_str("# TYPE auth_login_rate_limited_total counter\n");
     buf.push_str(&format!("auth_login_rate_limited_total {}\n", login_rate_limited));
+    buf.push_str("# HELP auth_login_locked_total Login attempts rejected because the account is locked.\n");
+    buf.push_str("# TYPE auth_login_locked_total counter\n");
+    buf.push_str(&format!("auth_login_locked_total {}\n", login_locked));
 
     // Password change counters
     buf.push_str("# HELP auth_password_change_total Successful password changes.\n");
@@ -202,6 +209,9 @@ pub async fn metrics_handler(
     buf.push_str("# HELP auth_password_change_rehash_total Password change events where legacy bcrypt was upgraded to Argon2id.\n");
     buf.push_str("# TYPE auth_password_change_rehash_total counter\n");
     buf.push_str(&format!("auth_password_change_rehash_total {}\n", pw_change_rehash));
+    buf.push_str("# HELP auth_password_reset_total Successful password resets via emailed code.\n");
+    buf.push_str("# TYPE auth_password_reset_total counter\n");
+    buf.push_str(&format!("auth_password_reset_total {}\n", pw_reset));
 
     // Export buffered duration histogram
     buf.push_str("# HELP export_duration_buffered_seconds Export (buffered) duration histogram.\n");
@@ -296,3 +306,44 @@ pub async fn metrics_handler(
     let full = format!("{}{}", buf, uptime_line);
     (StatusCode::OK, [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], full)
 }
+
+// Per-provider FX/crypto source health (circuit state, failures, latency).
+fn fx_provider_metrics(registry: &crate::services::fx_providers::ProviderRegistry) -> String {
+    use crate::services::fx_providers::CircuitState;
+
+    let health = registry.health();
+    let mut buf = String::new();
+    buf.push_str("# HELP fx_provider_up Provider circuit closed (1) or open/half-open (0).\n");
+    buf.push_str("# TYPE fx_provider_up gauge\n");
+    for h in &health {
+        let up = if h.state == CircuitState::Closed { 1 } else { 0 };
+        buf.push_str(&format!("fx_provider_up{{provider=\"{}\"}} {}\n", h.name, up));
+    }
+    buf.push_str("# HELP fx_provider_requests_total Provider requests since start.\n");
+    buf.push_str("# TYPE fx_provider_requests_total counter\n");
+    for h in &health {
+        buf.push_str(&format!(
+            "fx_provider_requests_total{{provider=\"{}\"}} {}\n",
+            h.name, h.total_requests
+        ));
+    }
+    buf.push_str("# HELP fx_provider_failures_total Failed provider requests since start.\n");
+    buf.push_str("# TYPE fx_provider_failures_total counter\n");
+    for h in &health {
+        buf.push_str(&format!(
+            "fx_provider_failures_total{{provider=\"{}\"}} {}\n",
+            h.name, h.total_failures
+        ));
+    }
+    buf.push_str("# HELP fx_provider_latency_ms Moving average provider latency in milliseconds.\n");
+    buf.push_str("# TYPE fx_provider_latency_ms gauge\n");
+    for h in &health {
+        if let Some(avg) = h.avg_latency_ms {
+            buf.push_str(&format!(
+                "fx_provider_latency_ms{{provider=\"{}\"}} {:.1}\n",
+                h.name, avg
+            ));
+        }
+    }
+    buf
+}
@@ -0,0 +1,125 @@
+//! 客户端真实 IP 解析
+//!
+//! X-Forwarded-For 可以被客户端任意伪造，只有当直连地址属于可信代理时才采信，
+//! 并从右向左跳过可信代理，取第一个不可信的地址作为客户端 IP。
+
+use axum::http::HeaderMap;
+use std::net::IpAddr;
+
+use super::metrics_guard::Cidr;
+
+/// 解析客户端 IP
+///
+/// * `peer` - TCP 直连地址（ConnectInfo），测试或未启用 ConnectInfo 时为 None
+/// * `trusted_proxies` - 可信代理网段；为空时永不采信 X-Forwarded-For
+pub fn resolve_client_ip(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    trusted_proxies: &[Cidr],
+) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|c| c.contains(ip));
+
+    let peer = peer?;
+    if !is_trusted(&peer) {
+        return Some(peer);
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|s| s.trim().parse().ok())
+        .collect();
+
+    // 从最靠近本机的一跳开始向左回溯
+    let mut client = peer;
+    for hop in forwarded.into_iter().rev() {
+        client = hop;
+        if !is_trusted(&hop) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// 将 IP 归一为网段（IPv4 /24，IPv6 /48），作为"登录位置"的粗粒度标识
+pub fn network_prefix(ip: &IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            format!("{}.{}.{}.0/24", o[0], o[1], o[2])
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(xff: &str) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        h.insert("x-forwarded-for", HeaderValue::from_str(xff).unwrap());
+        h
+    }
+
+    fn proxies() -> Vec<Cidr> {
+        vec![Cidr::parse("10.0.0.0/8").unwrap()]
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_forwarded_header() {
+        let ip = resolve_client_ip(
+            &headers("1.2.3.4"),
+            Some("203.0.113.9".parse().unwrap()),
+            &proxies(),
+        );
+        assert_eq!(ip, Some("203.0.113.9".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_trusted_peer_uses_rightmost_untrusted_hop() {
+        // 客户端伪造了最左侧地址，真实地址由可信代理追加在右侧
+        let ip = resolve_client_ip(
+            &headers("6.6.6.6, 198.51.100.7, 10.0.0.3"),
+            Some("10.0.0.2".parse().unwrap()),
+            &proxies(),
+        );
+        assert_eq!(ip, Some("198.51.100.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_trusted_peer_without_header_falls_back_to_peer() {
+        let ip = resolve_client_ip(
+            &HeaderMap::new(),
+            Some("10.0.0.2".parse().unwrap()),
+            &proxies(),
+        );
+        assert_eq!(ip, Some("10.0.0.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_missing_peer_is_unknown() {
+        assert_eq!(
+            resolve_client_ip(&headers("1.2.3.4"), None, &proxies()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_network_prefix() {
+        assert_eq!(
+            network_prefix(&"192.168.31.77".parse().unwrap()),
+            "192.168.31.0/24"
+        );
+        assert_eq!(
+            network_prefix(&"2001:db8:abcd:12::1".parse().unwrap()),
+            "2001:db8:abcd::/48"
+        );
+    }
+}
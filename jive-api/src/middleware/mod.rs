@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod client_ip;
 pub mod cors;
 pub mod error_handler;
 pub mod metrics_guard;
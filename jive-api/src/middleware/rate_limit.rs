@@ -1,7 +1,7 @@
 use crate::AppState;
 use axum::{
     body::Body,
-    extract::State,
+    extract::{ConnectInfo, State},
     http::{HeaderValue, Request, StatusCode},
     middleware::Next,
     response::Response,
@@ -9,12 +9,15 @@ use axum::{
 use sha2::{Digest, Sha256};
 use std::{
     collections::HashMap,
+    net::SocketAddr,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 use tower::BoxError;
 use tracing::warn;
 
+use super::client_ip::resolve_client_ip;
+
 #[derive(Clone)]
 pub struct RateLimiter {
     pub inner: Arc<Mutex<HashMap<String, (u32, Instant)>>>, // key -> (count, window_start)
@@ -75,14 +78,17 @@ pub async fn login_rate_limit(
                 .unwrap());
         }
     };
-    let ip = parts
-        .headers
-        .get("x-forwarded-for")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.split(',').next())
-        .unwrap_or("unknown")
-        .trim()
-        .to_string();
+    let peer = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let ip = resolve_client_ip(
+        &parts.headers,
+        peer,
+        &app_state.config.login_security.trusted_proxies,
+    )
+    .map(|ip| ip.to_string())
+    .unwrap_or_else(|| "unknown".to_string());
     let email_key = extract_email_key(&bytes, limiter.hash_email);
     let key = format!("{}:{}", ip, email_key.unwrap_or_else(|| "_".into()));
     let (allowed, _remain, retry_after) = limiter.check(&key);
@@ -38,6 +38,12 @@ pub enum AuditAction {
     MemberRemoved,
     RoleChanged,
     PermissionChanged,
+    LoginFailed,
+    AccountLocked,
+    NewDeviceLogin,
+    NewLocationLogin,
+    PasswordResetRequested,
+    PasswordReset,
 }
 
 impl TryFrom<String> for AuditAction {
@@ -59,6 +65,14 @@ impl TryFrom<String> for AuditAction {
             "MEMBERREMOVED" | "MEMBER_REMOVED" => Ok(AuditAction::MemberRemoved),
             "ROLECHANGED" | "ROLE_CHANGED" => Ok(AuditAction::RoleChanged),
             "PERMISSIONCHANGED" | "PERMISSION_CHANGED" => Ok(AuditAction::PermissionChanged),
+            "LOGINFAILED" | "LOGIN_FAILED" => Ok(AuditAction::LoginFailed),
+            "ACCOUNTLOCKED" | "ACCOUNT_LOCKED" => Ok(AuditAction::AccountLocked),
+            "NEWDEVICELOGIN" | "NEW_DEVICE_LOGIN" => Ok(AuditAction::NewDeviceLogin),
+            "NEWLOCATIONLOGIN" | "NEW_LOCATION_LOGIN" => Ok(AuditAction::NewLocationLogin),
+            "PASSWORDRESETREQUESTED" | "PASSWORD_RESET_REQUESTED" => {
+                Ok(AuditAction::PasswordResetRequested)
+            }
+            "PASSWORDRESET" | "PASSWORD_RESET" => Ok(AuditAction::PasswordReset),
             _ => Err(format!("Invalid audit action: {}", value)),
         }
     }
@@ -81,6 +95,12 @@ impl std::fmt::Display for AuditAction {
             AuditAction::MemberRemoved => "MEMBER_REMOVED",
             AuditAction::RoleChanged => "ROLE_CHANGED",
             AuditAction::PermissionChanged => "PERMISSION_CHANGED",
+            AuditAction::LoginFailed => "LOGIN_FAILED",
+            AuditAction::AccountLocked => "ACCOUNT_LOCKED",
+            AuditAction::NewDeviceLogin => "NEW_DEVICE_LOGIN",
+            AuditAction::NewLocationLogin => "NEW_LOCATION_LOGIN",
+            AuditAction::PasswordResetRequested => "PASSWORD_RESET_REQUESTED",
+            AuditAction::PasswordReset => "PASSWORD_RESET",
         };
         write!(f, "{}", s)
     }
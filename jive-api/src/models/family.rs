@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Family {
     pub id: Uuid,
     pub name: String,
@@ -46,7 +47,7 @@ impl Default for FamilySettings {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateFamilyRequest {
     pub name: Option<String>,
     pub currency: Option<String>,
@@ -54,7 +55,7 @@ pub struct CreateFamilyRequest {
     pub locale: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateFamilyRequest {
     pub name: Option<String>,
     pub currency: Option<String>,
@@ -8,6 +8,7 @@ pub mod global_market;
 pub mod invitation;
 pub mod membership;
 pub mod permission;
+pub mod session;
 pub mod transaction;
 
 // #[allow(unused_imports)]
@@ -28,7 +29,7 @@ pub use invitation::{
 #[allow(unused_imports)]
 pub use membership::{CreateMemberRequest, FamilyMember, MemberWithUserInfo, UpdateMemberRequest};
 #[allow(unused_imports)]
-pub use permission::{MemberRole, Permission};
+pub use permission::{LedgerRole, MemberRole, Permission};
 
 use thiserror::Error;
 
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use utoipa::ToSchema;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "PascalCase")]
 pub enum Permission {
     // Family管理权限
@@ -142,7 +143,7 @@ impl fmt::Display for Permission {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum MemberRole {
     Owner,
@@ -231,6 +232,94 @@ impl fmt::Display for MemberRole {
     }
 }
 
+/// 账本级角色（ledger_acl），存在时覆盖家庭角色在该账本上的权限
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerRole {
+    /// 显式禁止访问该账本
+    None,
+    Viewer,
+    Editor,
+    Admin,
+}
+
+impl LedgerRole {
+    /// 受账本 ACL 约束的权限；其余（家庭、成员、系统管理）仍只看家庭角色
+    pub fn is_ledger_scoped(permission: Permission) -> bool {
+        LedgerRole::Admin.permissions().contains(&permission)
+    }
+
+    pub fn permissions(&self) -> Vec<Permission> {
+        match self {
+            LedgerRole::None => vec![],
+            LedgerRole::Viewer => vec![
+                Permission::ViewAccounts,
+                Permission::ViewTransactions,
+                Permission::ViewCategories,
+                Permission::ViewBudgets,
+                Permission::ViewReports,
+            ],
+            LedgerRole::Editor => {
+                let mut perms = LedgerRole::Viewer.permissions();
+                perms.extend([
+                    Permission::CreateAccounts,
+                    Permission::EditAccounts,
+                    Permission::CreateTransactions,
+                    Permission::EditTransactions,
+                    Permission::DeleteTransactions,
+                    Permission::BulkEditTransactions,
+                    Permission::ExportData,
+                ]);
+                perms
+            }
+            LedgerRole::Admin => {
+                let mut perms = LedgerRole::Editor.permissions();
+                perms.extend([
+                    Permission::DeleteAccounts,
+                    Permission::ManageCategories,
+                    Permission::ManageBudgets,
+                ]);
+                perms
+            }
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+
+    /// 没有 ACL 记录时，家庭角色对应的账本角色
+    pub fn from_member_role(role: MemberRole) -> LedgerRole {
+        match role {
+            MemberRole::Owner | MemberRole::Admin => LedgerRole::Admin,
+            MemberRole::Member => LedgerRole::Editor,
+            MemberRole::Viewer => LedgerRole::Viewer,
+        }
+    }
+
+    pub fn from_str_name(s: &str) -> Option<LedgerRole> {
+        match s.to_lowercase().as_str() {
+            "none" => Some(LedgerRole::None),
+            "viewer" => Some(LedgerRole::Viewer),
+            "editor" => Some(LedgerRole::Editor),
+            "admin" => Some(LedgerRole::Admin),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LedgerRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LedgerRole::None => "none",
+            LedgerRole::Viewer => "viewer",
+            LedgerRole::Editor => "editor",
+            LedgerRole::Admin => "admin",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +354,27 @@ mod tests {
         assert!(!viewer_perms.contains(&Permission::DeleteFamily));
         assert!(!viewer_perms.contains(&Permission::CreateTransactions));
     }
+
+    #[test]
+    fn test_ledger_role_hierarchy() {
+        assert!(LedgerRole::Viewer.allows(Permission::ViewTransactions));
+        assert!(!LedgerRole::Viewer.allows(Permission::CreateTransactions));
+        assert!(LedgerRole::Editor.allows(Permission::CreateTransactions));
+        assert!(!LedgerRole::Editor.allows(Permission::ManageCategories));
+        assert!(LedgerRole::Admin.allows(Permission::ManageCategories));
+        assert!(LedgerRole::None.permissions().is_empty());
+        assert!(LedgerRole::Editor > LedgerRole::Viewer);
+    }
+
+    #[test]
+    fn test_ledger_scope_excludes_family_permissions() {
+        assert!(LedgerRole::is_ledger_scoped(Permission::ViewAccounts));
+        assert!(!LedgerRole::is_ledger_scoped(Permission::InviteMembers));
+        assert!(!LedgerRole::is_ledger_scoped(Permission::ManageSettings));
+        assert_eq!(
+            LedgerRole::from_member_role(MemberRole::Member),
+            LedgerRole::Editor
+        );
+        assert_eq!(LedgerRole::from_str_name("None"), Some(LedgerRole::None));
+    }
 }
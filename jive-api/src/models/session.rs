@@ -0,0 +1,216 @@
+//! 登录会话与设备信息
+//!
+//! 对应 jive-core `domain::user::{Session, DeviceInfo}`。API 默认不链接 jive-core，
+//! User-Agent 解析与设备指纹只在这里维护。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 设备信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub device_type: String,     // mobile/desktop/tablet
+    pub os: String,              // iOS/Android/Windows/macOS/Linux
+    pub browser: Option<String>, // Chrome/Safari/Firefox
+    pub app_version: String,
+    /// 系统主版本号（如 "17"、"14"）
+    pub os_version: Option<String>,
+    /// 机型（仅 Android UA 提供，如 "Pixel 8"）
+    pub model: Option<String>,
+}
+
+impl DeviceInfo {
+    /// 从 User-Agent 粗略解析设备信息（仅用于新设备识别，不追求精确）
+    pub fn from_user_agent(user_agent: &str) -> Self {
+        let ua = user_agent.to_lowercase();
+
+        let os = if ua.contains("iphone") || ua.contains("ipad") || ua.contains("ios") {
+            "iOS"
+        } else if ua.contains("android") {
+            "Android"
+        } else if ua.contains("windows") {
+            "Windows"
+        } else if ua.contains("mac os") || ua.contains("macintosh") {
+            "macOS"
+        } else if ua.contains("linux") {
+            "Linux"
+        } else {
+            "Unknown"
+        };
+
+        let device_type = if ua.contains("ipad") || ua.contains("tablet") {
+            "tablet"
+        } else if ua.contains("mobile") || ua.contains("iphone") || ua.contains("android") {
+            "mobile"
+        } else {
+            "desktop"
+        };
+
+        // 顺序有意义：Edge/Chrome 的 UA 同时包含 "safari"
+        let browser = if ua.contains("edg/") {
+            Some("Edge")
+        } else if ua.contains("firefox/") {
+            Some("Firefox")
+        } else if ua.contains("chrome/") {
+            Some("Chrome")
+        } else if ua.contains("safari/") {
+            Some("Safari")
+        } else {
+            None
+        };
+
+        // Flutter 客户端约定 UA 形如 "JiveMoney/1.2.3 (Android 14; Pixel 8)"
+        let app_version = user_agent
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix("JiveMoney/"))
+            .unwrap_or("unknown")
+            .to_string();
+
+        let os_version = match os {
+            "iOS" => major_version_after(&ua, &["iphone os ", "cpu os ", "ios "]),
+            "Android" => major_version_after(&ua, &["android "]),
+            "Windows" => major_version_after(&ua, &["windows nt "]),
+            "macOS" => major_version_after(&ua, &["mac os x "]),
+            _ => None,
+        };
+        let model = if os == "Android" {
+            android_model(user_agent)
+        } else {
+            None
+        };
+
+        Self {
+            device_type: device_type.to_string(),
+            os: os.to_string(),
+            browser: browser.map(|b| b.to_string()),
+            app_version,
+            os_version,
+            model,
+        }
+    }
+
+    /// 设备指纹：设备类型 + 系统及主版本 + 机型 + 浏览器
+    ///
+    /// 不含应用与浏览器版本号，升级客户端不会被识别为新设备；系统大版本升级后会识别一次。
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{}|{}|{}|{}|{}",
+            self.device_type,
+            self.os,
+            self.os_version.as_deref().unwrap_or("-"),
+            self.model.as_deref().unwrap_or("-"),
+            self.browser.as_deref().unwrap_or("-")
+        )
+        .to_lowercase()
+    }
+
+    /// 旧版指纹（设备类型 + 系统 + 浏览器），仅用于匹配升级前记录的会话
+    pub fn legacy_fingerprint(&self) -> String {
+        format!(
+            "{}|{}|{}",
+            self.device_type,
+            self.os,
+            self.browser.as_deref().unwrap_or("-")
+        )
+        .to_lowercase()
+    }
+}
+
+/// `marker` 之后的主版本号（"iphone os 17_0" -> "17"）
+fn major_version_after(ua: &str, markers: &[&str]) -> Option<String> {
+    markers.iter().find_map(|marker| {
+        let rest = &ua[ua.find(marker)? + marker.len()..];
+        let major: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        (!major.is_empty()).then_some(major)
+    })
+}
+
+/// Android UA 括号内紧跟 "Android x" 的一段即机型；"K" 是 Chrome 精简 UA 的占位符
+fn android_model(user_agent: &str) -> Option<String> {
+    let start = user_agent.find('(')?;
+    let end = start + user_agent[start..].find(')')?;
+    let mut parts = user_agent[start + 1..end].split(';').map(str::trim);
+    parts.find(|part| part.to_lowercase().starts_with("android"))?;
+    let model = parts.next()?.split(" Build/").next()?.trim();
+    (!model.is_empty() && model != "K").then(|| model.to_string())
+}
+
+/// user_sessions 表记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device_fingerprint: String,
+    pub device_type: String,
+    pub os: String,
+    pub browser: Option<String>,
+    pub app_version: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub network_prefix: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mobile_app_user_agent() {
+        let info = DeviceInfo::from_user_agent("JiveMoney/2.0.1 (Android 14; Pixel 8)");
+        assert_eq!(info.device_type, "mobile");
+        assert_eq!(info.os, "Android");
+        assert_eq!(info.app_version, "2.0.1");
+        assert_eq!(info.os_version.as_deref(), Some("14"));
+        assert_eq!(info.model.as_deref(), Some("Pixel 8"));
+        assert_eq!(info.fingerprint(), "mobile|android|14|pixel 8|-");
+        assert_eq!(info.legacy_fingerprint(), "mobile|android|-");
+    }
+
+    #[test]
+    fn test_parse_desktop_browser_user_agent() {
+        let info = DeviceInfo::from_user_agent(
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/537.36 Chrome/120.0 Safari/537.36 Edg/120.0",
+        );
+        assert_eq!(info.device_type, "desktop");
+        assert_eq!(info.os, "macOS");
+        assert_eq!(info.os_version.as_deref(), Some("14"));
+        assert_eq!(info.browser.as_deref(), Some("Edge"));
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_devices_but_not_upgrades() {
+        let pixel = DeviceInfo::from_user_agent(
+            "Mozilla/5.0 (Linux; Android 14; Pixel 8 Build/UD1A) AppleWebKit/537.36 Chrome/120.0 Mobile Safari/537.36",
+        );
+        let galaxy = DeviceInfo::from_user_agent(
+            "Mozilla/5.0 (Linux; Android 14; SM-S918B) AppleWebKit/537.36 Chrome/120.0 Mobile Safari/537.36",
+        );
+        assert_eq!(pixel.model.as_deref(), Some("Pixel 8"));
+        assert_ne!(pixel.fingerprint(), galaxy.fingerprint());
+        assert_eq!(pixel.legacy_fingerprint(), galaxy.legacy_fingerprint());
+
+        let old = DeviceInfo::from_user_agent("JiveMoney/1.0.0 (Android 14; Pixel 8)");
+        let new = DeviceInfo::from_user_agent("JiveMoney/1.1.0 (Android 14; Pixel 8)");
+        assert_eq!(old.fingerprint(), new.fingerprint());
+
+        let reduced = DeviceInfo::from_user_agent(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 Chrome/120.0 Mobile Safari/537.36",
+        );
+        assert_eq!(reduced.model, None);
+
+        let ios16 = DeviceInfo::from_user_agent(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 Version/16.6 Mobile/15E148 Safari/604.1",
+        );
+        let ios17 = DeviceInfo::from_user_agent(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 Version/17.0 Mobile/15E148 Safari/604.1",
+        );
+        assert_eq!(ios17.os_version.as_deref(), Some("17"));
+        assert_ne!(ios16.fingerprint(), ios17.fingerprint());
+    }
+}
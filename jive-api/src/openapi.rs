@@ -0,0 +1,496 @@
+//! OpenAPI 3.1 文档
+//!
+//! 由各 handler 上的 `#[utoipa::path]` 与请求/响应类型的 `ToSchema` 派生生成，
+//! 通过 `/api/v1/openapi.json` 提供，`/api/v1/docs` 为内嵌的查看页面。
+//! 新增路由时需同时在此登记，否则 `test_all_routes_documented` 会失败。
+
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::handlers;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Jive Money API",
+        description = "Jive Money 后端接口（由 handler 类型自动生成）"
+    ),
+    paths(
+        binary_routes::health_check,
+        binary_routes::api_info,
+        binary_routes::websocket,
+        binary_routes::serve_icon,
+        binary_routes::get_profile,
+        handlers::api_docs::openapi_json,
+        handlers::api_docs::docs_page,
+        handlers::template_handler::get_templates,
+        handlers::template_handler::get_icons,
+        handlers::template_handler::get_template_updates,
+        handlers::template_handler::submit_usage,
+        handlers::template_handler::create_template,
+        handlers::template_handler::update_template,
+        handlers::template_handler::delete_template,
+        handlers::accounts::list_accounts,
+        handlers::accounts::create_account,
+        handlers::accounts::get_account,
+        handlers::accounts::update_account,
+        handlers::accounts::delete_account,
+        handlers::accounts::get_account_statistics,
+        handlers::loans::get_loan_terms,
+        handlers::loans::create_loan_terms,
+        handlers::loans::delete_loan_terms,
+        handlers::loans::get_loan_schedule,
+        handlers::loans::record_loan_payment,
+        handlers::loans::set_loan_rate_change,
+        handlers::loans::simulate_loan_prepayment,
+        handlers::transactions::list_transactions,
+        handlers::transactions::create_transaction,
+        handlers::transactions::export_transactions,
+        handlers::transactions::export_transactions_csv_stream,
+        handlers::transactions::get_transaction,
+        handlers::transactions::update_transaction,
+        handlers::transactions::delete_transaction,
+        handlers::transactions::bulk_transaction_operations,
+        handlers::transactions::get_transaction_statistics,
+        handlers::payees::list_payees,
+        handlers::payees::create_payee,
+        handlers::payees::get_payee,
+        handlers::payees::update_payee,
+        handlers::payees::delete_payee,
+        handlers::payees::get_payee_suggestions,
+        handlers::payees::get_payee_statistics,
+        handlers::payees::merge_payees,
+        handlers::rules::list_rules,
+        handlers::rules::create_rule,
+        handlers::rules::get_rule,
+        handlers::rules::update_rule,
+        handlers::rules::delete_rule,
+        handlers::rules::execute_rules,
+        handlers::auth::register_with_family,
+        handlers::auth::login,
+        handlers::auth::refresh_token,
+        handlers::auth::forgot_password,
+        handlers::auth::reset_password,
+        handlers::auth::get_current_user,
+        handlers::auth::update_user,
+        handlers::auth::update_avatar,
+        handlers::auth::change_password,
+        handlers::auth::delete_account,
+        handlers::enhanced_profile::register_with_preferences,
+        handlers::enhanced_profile::get_enhanced_profile,
+        handlers::enhanced_profile::update_preferences,
+        handlers::enhanced_profile::get_supported_locales,
+        handlers::family_handler::list_families,
+        handlers::family_handler::create_family,
+        handlers::family_handler::join_family,
+        handlers::family_handler::leave_family,
+        handlers::family_handler::get_family,
+        handlers::family_handler::update_family,
+        handlers::family_handler::delete_family,
+        handlers::family_handler::get_family_statistics,
+        handlers::family_handler::get_family_actions,
+        handlers::family_handler::transfer_ownership,
+        handlers::family_handler::get_role_descriptions,
+        handlers::member_handler::get_family_members,
+        handlers::member_handler::add_member,
+        handlers::member_handler::remove_member,
+        handlers::member_handler::update_member_role,
+        handlers::member_handler::update_member_permissions,
+        handlers::family_handler::request_verification_code,
+        handlers::ledgers::list_ledgers,
+        handlers::ledgers::create_ledger,
+        handlers::ledgers::get_current_ledger,
+        handlers::ledgers::get_ledger,
+        handlers::ledgers::update_ledger,
+        handlers::ledgers::delete_ledger,
+        handlers::ledgers::get_ledger_statistics,
+        handlers::ledgers::get_ledger_members,
+        handlers::ledger_access::list_ledger_acl,
+        handlers::ledger_access::set_ledger_acl,
+        handlers::ledger_access::remove_ledger_acl,
+        handlers::ledger_access::list_share_links,
+        handlers::ledger_access::create_share_link,
+        handlers::ledger_access::revoke_share_link,
+        handlers::ledger_access::get_shared_report,
+        handlers::currency_handler::get_supported_currencies,
+        handlers::currency_handler::get_user_currency_preferences,
+        handlers::currency_handler::set_user_currency_preferences,
+        handlers::currency_handler::get_exchange_rate,
+        handlers::currency_handler::get_batch_exchange_rates,
+        handlers::currency_handler::add_exchange_rate,
+        handlers::currency_handler::clear_manual_exchange_rate,
+        handlers::currency_handler::clear_manual_exchange_rates_batch,
+        handlers::currency_handler::convert_amount,
+        handlers::currency_handler::import_historical_rates,
+        handlers::currency_handler::get_exchange_rate_history,
+        handlers::currency_handler::get_fx_gain_loss,
+        handlers::currency_handler::get_popular_exchange_pairs,
+        handlers::currency_handler::refresh_exchange_rates,
+        handlers::currency_handler::get_global_market_stats,
+        handlers::currency_handler::get_family_currency_settings,
+        handlers::currency_handler::update_family_currency_settings,
+        handlers::currency_handler_enhanced::get_all_currencies,
+        handlers::currency_handler_enhanced::get_user_currency_settings,
+        handlers::currency_handler_enhanced::update_user_currency_settings,
+        handlers::currency_handler_enhanced::get_realtime_exchange_rates,
+        handlers::currency_handler_enhanced::get_detailed_batch_rates,
+        handlers::currency_handler_enhanced::get_manual_overrides,
+        handlers::currency_handler_enhanced::get_crypto_prices,
+        handlers::currency_handler_enhanced::convert_currency,
+        handlers::currency_handler_enhanced::manual_refresh_rates,
+        handlers::rate_alerts::list_rate_alerts,
+        handlers::rate_alerts::create_rate_alert,
+        handlers::rate_alerts::update_rate_alert,
+        handlers::rate_alerts::delete_rate_alert,
+        handlers::investments::list_securities,
+        handlers::investments::create_security,
+        handlers::investments::list_security_prices,
+        handlers::investments::import_security_prices,
+        handlers::investments::list_trades,
+        handlers::investments::execute_trade,
+        handlers::investments::record_split,
+        handlers::investments::record_corporate_action,
+        handlers::investments::record_income,
+        handlers::investments::get_income_history,
+        handlers::investments::get_income_projection,
+        handlers::investments::get_holdings,
+        handlers::investments::list_lots,
+        handlers::investments::get_realized_gains,
+        handlers::investments::get_performance,
+        handlers::investments::set_cost_basis_method,
+        handlers::credit_cards::list_credit_cards,
+        handlers::credit_cards::create_credit_card,
+        handlers::credit_cards::get_credit_card,
+        handlers::credit_cards::update_credit_card,
+        handlers::credit_cards::delete_credit_card,
+        handlers::credit_cards::list_statements,
+        handlers::credit_cards::generate_statements,
+        handlers::credit_cards::get_current_cycle,
+        handlers::installments::list_installment_plans,
+        handlers::installments::create_installment_plan,
+        handlers::installments::get_installment_plan,
+        handlers::installments::update_installment_plan,
+        handlers::installments::pay_off_installment_plan,
+        handlers::connections::list_connections,
+        handlers::connections::create_link_token,
+        handlers::connections::create_connection,
+        handlers::connections::get_connection,
+        handlers::connections::delete_connection,
+        handlers::connections::sync_connection,
+        handlers::connections::reauth_connection,
+        handlers::connections::link_connection_account,
+        handlers::connections::bank_webhook,
+        handlers::ai::ai_status,
+        handlers::ai::categorize_transactions,
+        handlers::ai::categorization_history,
+        handlers::ai::list_chats,
+        handlers::ai::create_chat,
+        handlers::ai::get_chat,
+        handlers::ai::delete_chat,
+        handlers::ai::send_chat_message,
+        handlers::categorization::suggest_for_transaction,
+        handlers::categorization::auto_categorize,
+        handlers::categorization::model_status,
+        handlers::categorization::train_model,
+        handlers::notifications::list_notifications,
+        handlers::notifications::unread_count,
+        handlers::notifications::mark_read,
+        handlers::notifications::mark_all_read,
+        handlers::notifications::dismiss_notification,
+        handlers::notifications::get_preferences,
+        handlers::notifications::update_preferences,
+        handlers::push::push_config,
+        handlers::push::list_devices,
+        handlers::push::register_device,
+        handlers::push::unregister_device,
+        handlers::digests::list_digests,
+        handlers::digests::preview_digest,
+        handlers::digests::get_digest,
+        handlers::webhooks::list_webhooks,
+        handlers::webhooks::create_webhook,
+        handlers::webhooks::get_webhook,
+        handlers::webhooks::update_webhook,
+        handlers::webhooks::delete_webhook,
+        handlers::webhooks::list_deliveries,
+        handlers::webhooks::get_delivery,
+        handlers::webhooks::redeliver,
+        handlers::tag_handler::list_tags,
+        handlers::tag_handler::create_tag,
+        handlers::tag_handler::update_tag,
+        handlers::tag_handler::delete_tag,
+        handlers::tag_handler::merge_tags,
+        handlers::tag_handler::tag_summary,
+        handlers::category_handler::list_categories,
+        handlers::category_handler::create_category,
+        handlers::category_handler::update_category,
+        handlers::category_handler::delete_category,
+        handlers::category_handler::reorder_categories,
+        handlers::category_handler::import_template,
+        handlers::category_handler::batch_import_templates,
+    ),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "system", description = "健康检查与服务信息"),
+        (name = "docs", description = "接口文档"),
+        (name = "templates", description = "分类模板"),
+        (name = "admin", description = "超级管理员"),
+        (name = "accounts", description = "账户"),
+        (name = "transactions", description = "交易"),
+        (name = "payees", description = "收款人"),
+        (name = "rules", description = "规则引擎"),
+        (name = "auth", description = "认证与用户资料"),
+        (name = "families", description = "家庭"),
+        (name = "members", description = "家庭成员"),
+        (name = "ledgers", description = "账本"),
+        (name = "ledger-access", description = "账本权限与只读分享"),
+        (name = "currencies", description = "货币与汇率"),
+        (name = "rate-alerts", description = "汇率与加密货币价格提醒"),
+        (name = "investments", description = "投资账户、税务批次、已实现损益与股息收益"),
+        (name = "credit-cards", description = "信用卡账单、还款提醒与利息"),
+        (name = "installments", description = "分期计划与提前还款"),
+        (name = "connections", description = "银行连接与交易同步"),
+        (name = "ai", description = "大模型分类与财务助手"),
+        (name = "categorization", description = "按家庭训练的本地分类模型"),
+        (name = "notifications", description = "通知中心与投递偏好"),
+        (name = "push", description = "推送设备注册"),
+        (name = "digests", description = "周度 / 月度财务摘要"),
+        (name = "webhooks", description = "家庭事件 Webhook"),
+        (name = "tags", description = "标签"),
+        (name = "categories", description = "分类"),
+    )
+)]
+pub struct ApiDoc;
+
+/// 实现在 main.rs（二进制 crate）中的路由，库内无法引用，仅用于生成文档
+#[allow(dead_code)]
+mod binary_routes {
+    /// 健康检查（含运行模式与近期指标）
+    #[utoipa::path(
+        get,
+        path = "/health",
+        tag = "system",
+        responses((status = 200, description = "服务状态", body = Object))
+    )]
+    pub async fn health_check() {}
+
+    /// API 基本信息
+    #[utoipa::path(
+        get,
+        path = "/",
+        tag = "system",
+        responses((status = 200, description = "服务信息与主要入口", body = Object))
+    )]
+    pub async fn api_info() {}
+
+    /// WebSocket 连接（token 通过查询参数传递）
+    #[utoipa::path(
+        get,
+        path = "/ws",
+        tag = "system",
+        params(("token" = Option<String>, Query, description = "JWT 访问令牌")),
+        responses(
+            (status = 101, description = "协议升级为 WebSocket"),
+            (status = 401, description = "令牌缺失或无效")
+        )
+    )]
+    pub async fn websocket() {}
+
+    /// 静态图标
+    #[utoipa::path(
+        get,
+        path = "/static/icons/{path}",
+        tag = "system",
+        params(("path" = String, Path, description = "图标相对路径")),
+        responses((status = 200, description = "图标服务信息", body = Object))
+    )]
+    pub async fn serve_icon() {}
+
+    /// 获取当前用户信息（`/api/v1/auth/user` 的别名，供 Flutter 客户端使用）
+    #[utoipa::path(
+        get,
+        path = "/api/v1/auth/profile",
+        tag = "auth",
+        responses(
+            (status = 200, description = "成功", body = crate::handlers::auth::UserProfile),
+            (status = 401, description = "未认证")
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn get_profile() {}
+}
+
+/// 注册 Bearer JWT 认证方案
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                Http::builder()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "delete", "patch"];
+
+    /// 提取 main.rs 路由构建（`Router::new()` 到 `.with_state(`）中的 (路径, 方法)，
+    /// 跳过 demo_endpoints 特性下的语句
+    fn routed_endpoints() -> BTreeSet<(String, String)> {
+        parse_router(include_str!("main.rs"))
+    }
+
+    fn parse_router(src: &str) -> BTreeSet<(String, String)> {
+        let start = src.find("let app = Router::new()").expect("router block");
+        let end = start + src[start..].find(".with_state(").expect("router block end");
+
+        let mut out = BTreeSet::new();
+        for statement in src[start..end].split(';') {
+            let let_pos = statement.find("let app").expect("router statement");
+            if statement[..let_pos].contains("feature = \"demo_endpoints\"") {
+                continue;
+            }
+            collect_routes(&statement[let_pos..], &mut out);
+        }
+        out
+    }
+
+    /// 提取一条语句中全部 `.route(...)` 调用的 (路径, 方法)
+    fn collect_routes(block: &str, out: &mut BTreeSet<(String, String)>) {
+        let mut rest = block;
+        while let Some(pos) = rest.find(".route(") {
+            let call = &rest[pos + ".route(".len()..];
+            // 找到与 .route( 匹配的右括号
+            let mut depth = 1;
+            let mut close = 0;
+            for (i, c) in call.char_indices() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            close = i;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let args = &call[..close];
+            let path = args.split('"').nth(1).expect("route path literal");
+            let path = to_openapi_path(path);
+            let handlers = &args[args.find(',').expect("route handler") + 1..];
+            // 形如 `get(handler)` / `.put(handler)` 的方法路由
+            for method in METHODS {
+                let pattern = format!("{}(", method);
+                let found = handlers.match_indices(&pattern).any(|(i, _)| {
+                    !handlers[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                });
+                if found {
+                    out.insert((path.clone(), method.to_string()));
+                }
+            }
+            rest = &call[close..];
+        }
+    }
+
+    /// `:id` / `*path` -> `{id}` / `{path}`
+    fn to_openapi_path(path: &str) -> String {
+        path.split('/')
+            .map(
+                |seg| match seg.strip_prefix(':').or_else(|| seg.strip_prefix('*')) {
+                    Some(name) => format!("{{{}}}", name),
+                    None => seg.to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn documented_endpoints() -> BTreeSet<(String, String)> {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut out = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for method in METHODS {
+                if item.get(method).is_some() {
+                    out.insert((path.clone(), method.to_string()));
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_to_openapi_path() {
+        assert_eq!(
+            to_openapi_path("/api/v1/families/:id/members/:user_id"),
+            "/api/v1/families/{id}/members/{user_id}"
+        );
+        assert_eq!(
+            to_openapi_path("/static/icons/*path"),
+            "/static/icons/{path}"
+        );
+    }
+
+    #[test]
+    fn test_all_routes_documented() {
+        let routed = routed_endpoints();
+        assert!(
+            routed.len() > 100,
+            "router parse looks wrong: {}",
+            routed.len()
+        );
+
+        let documented = documented_endpoints();
+        let missing: Vec<_> = routed.difference(&documented).collect();
+        assert!(
+            missing.is_empty(),
+            "routes missing from OpenAPI spec: {:?}",
+            missing
+        );
+
+        // 文档中也不应出现未挂载的路由
+        let stale: Vec<_> = documented.difference(&routed).collect();
+        assert!(
+            stale.is_empty(),
+            "OpenAPI paths not mounted in main.rs: {:?}",
+            stale
+        );
+    }
+
+    #[test]
+    fn test_routes_after_first_statement_are_parsed() {
+        let src = r#"
+            let app = Router::new()
+                .route("/a", get(a));
+            #[cfg(feature = "demo_endpoints")]
+            let app = app.route("/demo", get(demo));
+            let app = app
+                .route("/b/:id", get(b).delete(c))
+                .with_state(state);
+        "#;
+        let expected: BTreeSet<_> = [("/a", "get"), ("/b/{id}", "get"), ("/b/{id}", "delete")]
+            .into_iter()
+            .map(|(p, m)| (p.to_string(), m.to_string()))
+            .collect();
+        assert_eq!(parse_router(src), expected);
+    }
+
+    #[test]
+    fn test_spec_metadata() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"));
+        assert!(spec["components"]["securitySchemes"]["bearer_auth"].is_object());
+        assert!(spec["components"]["schemas"]["TransactionResponse"].is_object());
+    }
+}
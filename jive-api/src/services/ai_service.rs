@@ -0,0 +1,1197 @@
+//! AI 分类与财务助手对话
+//!
+//! 发往模型的所有消息统一脱敏（卡号、账号、手机号只保留后 4 位）。分类请求中交易与分类
+//! 使用短引用（t1、c1）代替 id，模型返回的未知引用或类型不一致的分类会被丢弃；每条建议
+//! 都记录在 `transaction_categorizations` 中（来源、提供方、模型、置信度、提示哈希），
+//! 只有置信度达到 `LLM_MIN_CONFIDENCE` 且交易仍未分类时才写入交易。
+//!
+//! 对话采用工具调用循环：模型可调用账户、近期交易、资产负债三个只读工具，工具只查询
+//! 用户有权查看的账本；达到 `LLM_MAX_TOOL_ROUNDS` 轮后不再提供工具，要求模型直接回答。
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::llm_providers::{
+    ChatMessage, ChatRole, CompletionRequest, LlmBackend, LlmError, ToolCall, ToolDefinition,
+};
+use super::ServiceError;
+use crate::config::LlmConfig;
+use crate::utils::redact::mask_account_numbers;
+
+/// 分类建议来源
+pub const SOURCE_LLM: &str = "llm";
+/// 单次分类最多处理的交易数
+const MAX_CATEGORIZE_TRANSACTIONS: i64 = 200;
+/// 对话时带上的历史消息条数
+const CHAT_HISTORY_LIMIT: i64 = 30;
+/// 单条对话消息的最大长度（字符）
+const MAX_CHAT_MESSAGE_CHARS: usize = 4000;
+/// get_transactions 工具单次返回的最大笔数
+const MAX_TOOL_TRANSACTIONS: i64 = 50;
+
+const CATEGORIZE_PROMPT: &str = "You categorize personal finance transactions. The user message is JSON with \
+`categories` (ref, name, type, parent) and `transactions` (ref, type, amount, date, description, payee). \
+For every transaction pick the best category ref of the same type, or null when nothing fits. \
+Respond with a JSON object only: \
+{\"results\": [{\"ref\": \"t1\", \"category\": \"c2\", \"confidence\": 0.85, \"reason\": \"short reason\"}]} \
+where confidence is between 0 and 1.";
+
+const TOOL_ROUNDS_EXHAUSTED: &str = "抱歉，这个问题需要查询的数据太多，请把问题拆小一些再试。";
+
+fn chat_system_prompt(today: NaiveDate) -> String {
+    format!(
+        "你是 Jive Money 的家庭财务助手。今天是 {}。回答账户、交易、资产负债相关的问题时，\
+         先调用工具查询数据，只依据工具返回的结果作答，不要编造数字；金额注明币种。\
+         回答使用用户提问的语言，简洁明了。",
+        today
+    )
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TransactionForAi {
+    pub id: Uuid,
+    pub transaction_type: String,
+    pub amount: Decimal,
+    pub transaction_date: NaiveDate,
+    pub description: Option<String>,
+    pub payee: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategoryForAi {
+    pub id: Uuid,
+    pub name: String,
+    /// expense / income
+    pub category_type: String,
+    pub parent_name: Option<String>,
+}
+
+/// 一条分类建议
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AiCategorization {
+    pub transaction_id: Uuid,
+    /// 为空表示模型没有找到合适的分类
+    pub category_id: Option<Uuid>,
+    pub category_name: Option<String>,
+    /// 0 - 1
+    pub confidence: f64,
+    pub reasoning: Option<String>,
+    /// 是否已写入交易
+    pub applied: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CategorizeRequest {
+    pub ledger_id: Uuid,
+    /// 为空时处理账本中最近的未分类交易
+    pub transaction_ids: Option<Vec<Uuid>>,
+    /// 只返回建议，不写入交易
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategorizeSummary {
+    pub provider: String,
+    pub model: String,
+    pub processed: usize,
+    pub applied: usize,
+    pub results: Vec<AiCategorization>,
+}
+
+/// 分类建议记录（来源追溯）
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CategorizationRecord {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub category_id: Option<Uuid>,
+    pub source: String,
+    pub provider: String,
+    pub model: Option<String>,
+    pub confidence: f64,
+    pub reasoning: Option<String>,
+    pub prompt_sha256: Option<String>,
+    pub applied: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AiStatus {
+    pub enabled: bool,
+    pub provider: Option<String>,
+    pub categorize_model: Option<String>,
+    pub chat_model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AiChat {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub title: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AiChatMessage {
+    pub id: Uuid,
+    /// user / assistant / tool
+    pub role: String,
+    pub content: Option<String>,
+    /// assistant 发起的工具调用：[{"id", "name", "arguments"}]
+    #[schema(value_type = Option<Object>)]
+    pub tool_calls: Option<Value>,
+    pub tool_call_id: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AiChatDetail {
+    pub chat: AiChat,
+    pub messages: Vec<AiChatMessage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateChatRequest {
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChatMessageRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChatReply {
+    pub chat_id: Uuid,
+    /// 助手的最终回答
+    pub reply: String,
+    /// 本轮新增的消息（用户消息、工具调用与结果、回答）
+    pub messages: Vec<AiChatMessage>,
+}
+
+const CHAT_SELECT: &str =
+    "SELECT id, user_id, family_id, title, created_at, updated_at FROM ai_chats";
+const MESSAGE_COLUMNS: &str =
+    "id, role, content, tool_calls, tool_call_id, provider, model, created_at";
+
+/// 对发往模型的消息内容脱敏
+pub fn redact_messages(messages: &mut [ChatMessage]) {
+    for message in messages {
+        if let Some(Cow::Owned(masked)) = message.content.as_deref().map(mask_account_numbers) {
+            message.content = Some(masked);
+        }
+    }
+}
+
+/// 提示内容的 SHA-256（记录在分类来源中）
+pub fn prompt_hash(messages: &[ChatMessage]) -> String {
+    let mut hasher = Sha256::new();
+    for message in messages {
+        hasher.update(message.role.as_str().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(message.content.as_deref().unwrap_or("").as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// 构建分类请求；交易与分类用 t1 / c1 这样的短引用标识
+pub fn build_categorization_messages(
+    transactions: &[TransactionForAi],
+    categories: &[CategoryForAi],
+) -> Vec<ChatMessage> {
+    let payload = json!({
+        "categories": categories
+            .iter()
+            .enumerate()
+            .map(|(i, c)| json!({
+                "ref": format!("c{}", i + 1),
+                "name": c.name,
+                "type": c.category_type,
+                "parent": c.parent_name,
+            }))
+            .collect::<Vec<_>>(),
+        "transactions": transactions
+            .iter()
+            .enumerate()
+            .map(|(i, t)| json!({
+                "ref": format!("t{}", i + 1),
+                "type": t.transaction_type,
+                "amount": t.amount.abs().round_dp(2).to_string(),
+                "date": t.transaction_date.to_string(),
+                "description": t.description,
+                "payee": t.payee,
+            }))
+            .collect::<Vec<_>>(),
+    });
+    vec![
+        ChatMessage::system(CATEGORIZE_PROMPT),
+        ChatMessage::user(payload.to_string()),
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+struct CategorizeResponse {
+    #[serde(default)]
+    results: Vec<CategorizeItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategorizeItem {
+    #[serde(rename = "ref")]
+    reference: String,
+    category: Option<String>,
+    #[serde(default)]
+    confidence: f64,
+    reason: Option<String>,
+}
+
+/// 取出回复中的 JSON 对象（本地模型常在外面包一层 ``` 代码块或说明文字）
+fn extract_json_object(content: &str) -> Option<&str> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    (end > start).then(|| &content[start..=end])
+}
+
+/// 解析分类回复，按输入顺序返回每笔交易的建议
+pub fn parse_categorization_response(
+    content: &str,
+    transactions: &[TransactionForAi],
+    categories: &[CategoryForAi],
+) -> Result<Vec<AiCategorization>, ServiceError> {
+    let json = extract_json_object(content)
+        .ok_or_else(|| LlmError::InvalidResponse("no JSON object in reply".to_string()))?;
+    let response: CategorizeResponse =
+        serde_json::from_str(json).map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+    let items: HashMap<&str, &CategorizeItem> = response
+        .results
+        .iter()
+        .map(|item| (item.reference.as_str(), item))
+        .collect();
+
+    Ok(transactions
+        .iter()
+        .enumerate()
+        .map(|(i, transaction)| {
+            let item = items.get(format!("t{}", i + 1).as_str()).copied();
+            let category = item
+                .and_then(|item| item.category.as_deref())
+                .and_then(|r| r.strip_prefix('c')?.parse::<usize>().ok())
+                .and_then(|n| categories.get(n.checked_sub(1)?))
+                .filter(|c| c.category_type == transaction.transaction_type);
+            let confidence = match (category, item) {
+                (Some(_), Some(item)) if item.confidence.is_finite() => {
+                    item.confidence.clamp(0.0, 1.0)
+                }
+                _ => 0.0,
+            };
+            AiCategorization {
+                transaction_id: transaction.id,
+                category_id: category.map(|c| c.id),
+                category_name: category.map(|c| c.name.clone()),
+                confidence,
+                reasoning: item.and_then(|item| item.reason.clone()),
+                applied: false,
+            }
+        })
+        .collect())
+}
+
+/// 一批分类的结果与来源
+#[derive(Debug, Clone)]
+pub struct CategorizationBatch {
+    pub provider: String,
+    pub model: String,
+    pub prompt_sha256: String,
+    pub suggestions: Vec<AiCategorization>,
+}
+
+/// 调用模型为一批交易给出分类建议（不访问数据库）
+pub async fn categorize(
+    backend: &LlmBackend,
+    transactions: &[TransactionForAi],
+    categories: &[CategoryForAi],
+) -> Result<CategorizationBatch, ServiceError> {
+    let mut messages = build_categorization_messages(transactions, categories);
+    redact_messages(&mut messages);
+    let prompt_sha256 = prompt_hash(&messages);
+    let completion = backend
+        .provider
+        .complete(&CompletionRequest {
+            model: backend.categorize_model.clone(),
+            messages,
+            tools: Vec::new(),
+            temperature: 0.0,
+            json_response: true,
+        })
+        .await?;
+    let content = completion.message.content.unwrap_or_default();
+    Ok(CategorizationBatch {
+        provider: backend.provider_name().to_string(),
+        model: completion.model,
+        prompt_sha256,
+        suggestions: parse_categorization_response(&content, transactions, categories)?,
+    })
+}
+
+/// 对话中可供模型调用的工具
+#[async_trait]
+pub trait ToolExecutor: Send + Sync {
+    fn definitions(&self) -> Vec<ToolDefinition>;
+
+    async fn execute(&self, name: &str, arguments: &Value) -> Result<Value, ServiceError>;
+}
+
+/// 工具调用循环的结果
+#[derive(Debug, Clone)]
+pub struct ToolLoopOutcome {
+    /// 新增的消息：工具调用、工具结果与最终回答
+    pub messages: Vec<ChatMessage>,
+    pub model: String,
+}
+
+impl ToolLoopOutcome {
+    pub fn reply(&self) -> &str {
+        self.messages
+            .last()
+            .and_then(|m| m.content.as_deref())
+            .unwrap_or("")
+    }
+}
+
+/// 运行工具调用循环，直到模型给出文本回答
+///
+/// 工具执行失败时把错误作为工具结果返回给模型，由模型决定如何回答。
+pub async fn run_tool_loop(
+    backend: &LlmBackend,
+    mut messages: Vec<ChatMessage>,
+    tools: &dyn ToolExecutor,
+    max_rounds: usize,
+) -> Result<ToolLoopOutcome, ServiceError> {
+    let start = messages.len();
+    let definitions = tools.definitions();
+    let mut model = backend.chat_model.clone();
+
+    for round in 0..=max_rounds {
+        let mut request = CompletionRequest {
+            model: backend.chat_model.clone(),
+            messages: messages.clone(),
+            tools: if round < max_rounds {
+                definitions.clone()
+            } else {
+                Vec::new()
+            },
+            temperature: 0.2,
+            json_response: false,
+        };
+        redact_messages(&mut request.messages);
+        let completion = backend.provider.complete(&request).await?;
+        model = completion.model;
+        let reply = completion.message;
+
+        if reply.tool_calls.is_empty() {
+            messages.push(reply);
+            break;
+        }
+        if round == max_rounds {
+            messages.push(ChatMessage::assistant(TOOL_ROUNDS_EXHAUSTED));
+            break;
+        }
+        let calls = reply.tool_calls.clone();
+        messages.push(reply);
+        for call in calls {
+            let result = match serde_json::from_str::<Value>(&call.arguments) {
+                Ok(arguments) => tools
+                    .execute(&call.name, &arguments)
+                    .await
+                    .unwrap_or_else(|e| json!({ "error": e.to_string() })),
+                Err(e) => json!({ "error": format!("invalid arguments: {}", e) }),
+            };
+            messages.push(ChatMessage::tool_result(call.id, result.to_string()));
+        }
+    }
+
+    Ok(ToolLoopOutcome {
+        messages: messages.split_off(start),
+        model,
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GetTransactionsArgs {
+    limit: Option<i64>,
+    days: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ToolAccountRow {
+    ledger_name: String,
+    name: String,
+    account_type: String,
+    currency: Option<String>,
+    current_balance: Option<Decimal>,
+    institution_name: Option<String>,
+    account_number: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ToolTransactionRow {
+    transaction_date: NaiveDate,
+    transaction_type: String,
+    amount: Decimal,
+    currency: Option<String>,
+    description: Option<String>,
+    payee: Option<String>,
+    category_name: Option<String>,
+    account_name: String,
+}
+
+/// 只读查询指定账本的工具
+pub struct LedgerTools {
+    pool: PgPool,
+    ledger_ids: Vec<Uuid>,
+}
+
+impl LedgerTools {
+    pub fn new(pool: PgPool, ledger_ids: Vec<Uuid>) -> Self {
+        Self { pool, ledger_ids }
+    }
+
+    async fn accounts(&self) -> Result<Value, ServiceError> {
+        let rows: Vec<ToolAccountRow> = sqlx::query_as(
+            r#"
+            SELECT l.name AS ledger_name, a.name, a.account_type, a.currency, a.current_balance,
+                   a.institution_name, a.account_number
+            FROM accounts a
+            JOIN ledgers l ON l.id = a.ledger_id
+            WHERE a.ledger_id = ANY($1) AND a.deleted_at IS NULL
+            ORDER BY l.name, a.display_order, a.name
+            "#,
+        )
+        .bind(&self.ledger_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        let accounts: Vec<Value> = rows
+            .into_iter()
+            .map(|row| {
+                // 只暴露账号后 4 位
+                let mask = row.account_number.map(|number| {
+                    let chars: Vec<char> =
+                        number.chars().filter(char::is_ascii_alphanumeric).collect();
+                    chars[chars.len().saturating_sub(4)..]
+                        .iter()
+                        .collect::<String>()
+                });
+                json!({
+                    "ledger": row.ledger_name,
+                    "name": row.name,
+                    "type": row.account_type,
+                    "currency": row.currency,
+                    "balance": row.current_balance.unwrap_or(Decimal::ZERO).to_string(),
+                    "institution": row.institution_name,
+                    "mask": mask,
+                })
+            })
+            .collect();
+        Ok(json!({ "accounts": accounts }))
+    }
+
+    async fn transactions(&self, args: GetTransactionsArgs) -> Result<Value, ServiceError> {
+        let limit = args.limit.unwrap_or(20).clamp(1, MAX_TOOL_TRANSACTIONS);
+        let days = args.days.unwrap_or(30).clamp(1, 366);
+        let since = Utc::now().date_naive() - Duration::days(days);
+        let rows: Vec<ToolTransactionRow> = sqlx::query_as(
+            r#"
+            SELECT t.transaction_date, t.transaction_type, t.amount, t.currency, t.description,
+                   COALESCE(t.payee, t.merchant) AS payee, t.category_name,
+                   a.name AS account_name
+            FROM transactions t
+            JOIN accounts a ON a.id = t.account_id
+            WHERE t.ledger_id = ANY($1) AND t.deleted_at IS NULL AND t.transaction_date >= $2
+            ORDER BY t.transaction_date DESC, t.created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(&self.ledger_ids)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        let transactions: Vec<Value> = rows
+            .into_iter()
+            .map(|row| {
+                json!({
+                    "date": row.transaction_date.to_string(),
+                    "type": row.transaction_type,
+                    "amount": row.amount.to_string(),
+                    "currency": row.currency,
+                    "description": row.description,
+                    "payee": row.payee,
+                    "category": row.category_name,
+                    "account": row.account_name,
+                })
+            })
+            .collect();
+        Ok(json!({ "since": since.to_string(), "transactions": transactions }))
+    }
+
+    async fn balance_sheet(&self) -> Result<Value, ServiceError> {
+        let rows: Vec<(String, Decimal, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT COALESCE(currency, 'CNY'),
+                   COALESCE(SUM(CASE WHEN COALESCE(account_main_type, 'asset') = 'asset'
+                                     THEN COALESCE(current_balance, 0) ELSE 0 END), 0)::numeric,
+                   COALESCE(SUM(CASE WHEN account_main_type = 'liability'
+                                     THEN ABS(COALESCE(current_balance, 0)) ELSE 0 END), 0)::numeric
+            FROM accounts
+            WHERE ledger_id = ANY($1) AND deleted_at IS NULL
+              AND COALESCE(is_included_in_total, true)
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(&self.ledger_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        let currencies: Vec<Value> = rows
+            .into_iter()
+            .map(|(currency, assets, liabilities)| {
+                json!({
+                    "currency": currency,
+                    "assets": assets.to_string(),
+                    "liabilities": liabilities.to_string(),
+                    "net_worth": (assets - liabilities).to_string(),
+                })
+            })
+            .collect();
+        Ok(json!({ "by_currency": currencies }))
+    }
+}
+
+/// 账户、近期交易、资产负债三个只读工具的定义
+pub fn ledger_tool_definitions() -> Vec<ToolDefinition> {
+    vec![
+        ToolDefinition {
+            name: "get_accounts".to_string(),
+            description: "List the family's accounts with type, currency and current balance"
+                .to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+        },
+        ToolDefinition {
+            name: "get_transactions".to_string(),
+            description: "Recent transactions, newest first".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_TOOL_TRANSACTIONS },
+                    "days": { "type": "integer", "minimum": 1, "maximum": 366, "description": "Look back this many days" },
+                },
+            }),
+        },
+        ToolDefinition {
+            name: "get_balance_sheet".to_string(),
+            description: "Total assets, liabilities and net worth per currency".to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+        },
+    ]
+}
+
+#[async_trait]
+impl ToolExecutor for LedgerTools {
+    fn definitions(&self) -> Vec<ToolDefinition> {
+        ledger_tool_definitions()
+    }
+
+    async fn execute(&self, name: &str, arguments: &Value) -> Result<Value, ServiceError> {
+        match name {
+            "get_accounts" => self.accounts().await,
+            "get_transactions" => {
+                self.transactions(serde_json::from_value(arguments.clone()).unwrap_or_default())
+                    .await
+            }
+            "get_balance_sheet" => self.balance_sheet().await,
+            other => Err(ServiceError::validation(format!("Unknown tool {}", other))),
+        }
+    }
+}
+
+fn to_chat_message(row: &AiChatMessage) -> Option<ChatMessage> {
+    let role = match row.role.as_str() {
+        "user" => ChatRole::User,
+        "assistant" => ChatRole::Assistant,
+        "tool" => ChatRole::Tool,
+        _ => return None,
+    };
+    Some(ChatMessage {
+        role,
+        content: row.content.clone(),
+        tool_calls: row
+            .tool_calls
+            .clone()
+            .and_then(|calls| serde_json::from_value::<Vec<ToolCall>>(calls).ok())
+            .unwrap_or_default(),
+        tool_call_id: row.tool_call_id.clone(),
+    })
+}
+
+/// 账本中可用的收支分类
+pub(crate) async fn ledger_categories(
+    pool: &PgPool,
+    ledger_id: Uuid,
+) -> Result<Vec<CategoryForAi>, ServiceError> {
+    Ok(sqlx::query_as(
+        r#"
+        SELECT c.id, c.name, c.type AS category_type, p.name AS parent_name
+        FROM categories c
+        LEFT JOIN categories p ON p.id = c.parent_id
+        WHERE c.ledger_id = $1
+          AND COALESCE(c.is_deleted, false) = false
+          AND COALESCE(c.is_active, true)
+          AND c.type IN ('expense', 'income')
+        ORDER BY c.display_order, c.name
+        "#,
+    )
+    .bind(ledger_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// 待自动分类的交易：请求指定的（或账本中最近的）未分类收支交易
+pub(crate) async fn uncategorized_transactions(
+    pool: &PgPool,
+    req: &CategorizeRequest,
+) -> Result<Vec<TransactionForAi>, ServiceError> {
+    Ok(sqlx::query_as(
+        r#"
+        SELECT id, transaction_type, amount, transaction_date, description,
+               COALESCE(payee, merchant) AS payee
+        FROM transactions
+        WHERE ledger_id = $1
+          AND deleted_at IS NULL
+          AND category_id IS NULL
+          AND transaction_type IN ('expense', 'income')
+          AND ($2::uuid[] IS NULL OR id = ANY($2))
+        ORDER BY transaction_date DESC, created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(req.ledger_id)
+    .bind(&req.transaction_ids)
+    .bind(MAX_CATEGORIZE_TRANSACTIONS)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub struct AiService {
+    pool: PgPool,
+    backend: Option<Arc<LlmBackend>>,
+    config: LlmConfig,
+}
+
+impl AiService {
+    pub fn new(pool: PgPool, backend: Option<Arc<LlmBackend>>, config: &LlmConfig) -> Self {
+        Self {
+            pool,
+            backend,
+            config: config.clone(),
+        }
+    }
+
+    fn backend(&self) -> Result<&LlmBackend, ServiceError> {
+        self.backend.as_deref().ok_or_else(|| {
+            ServiceError::business_rule("AI 功能未启用，请配置 LLM_PROVIDER / LLM_BASE_URL")
+        })
+    }
+
+    pub fn status(&self) -> AiStatus {
+        AiStatus {
+            enabled: self.backend.is_some(),
+            provider: self.backend.as_ref().map(|b| b.provider_name().to_string()),
+            categorize_model: self.backend.as_ref().map(|b| b.categorize_model.clone()),
+            chat_model: self.backend.as_ref().map(|b| b.chat_model.clone()),
+        }
+    }
+
+    /// 为账本中的未分类交易生成分类建议，置信度足够时写入
+    pub async fn auto_categorize_with_ai(
+        &self,
+        user_id: Uuid,
+        req: &CategorizeRequest,
+    ) -> Result<CategorizeSummary, ServiceError> {
+        let backend = self.backend()?;
+        let config = &self.config;
+        let dry_run = req.dry_run.unwrap_or(false);
+
+        let transactions = uncategorized_transactions(&self.pool, req).await?;
+
+        let mut summary = CategorizeSummary {
+            provider: backend.provider_name().to_string(),
+            model: backend.categorize_model.clone(),
+            processed: 0,
+            applied: 0,
+            results: Vec::new(),
+        };
+        if transactions.is_empty() {
+            return Ok(summary);
+        }
+        let categories = ledger_categories(&self.pool, req.ledger_id).await?;
+        if categories.is_empty() {
+            return Err(ServiceError::business_rule("账本中没有可用的分类"));
+        }
+
+        for chunk in transactions.chunks(config.categorize_batch_size.max(1)) {
+            let batch = categorize(backend, chunk, &categories).await?;
+            summary.model = batch.model.clone();
+
+            let mut tx = self.pool.begin().await?;
+            for mut suggestion in batch.suggestions {
+                if !dry_run && suggestion.confidence >= config.min_confidence {
+                    if let Some(category_id) = suggestion.category_id {
+                        // 只写入仍未分类的交易，避免覆盖用户刚刚设置的分类
+                        let result = sqlx::query(
+                            r#"
+                            UPDATE transactions
+                            SET category_id = $2, category_name = $3, category_source = $4,
+                                updated_at = NOW()
+                            WHERE id = $1 AND category_id IS NULL
+                            "#,
+                        )
+                        .bind(suggestion.transaction_id)
+                        .bind(category_id)
+                        .bind(&suggestion.category_name)
+                        .bind(SOURCE_LLM)
+                        .execute(&mut *tx)
+                        .await?;
+                        suggestion.applied = result.rows_affected() == 1;
+                    }
+                }
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO transaction_categorizations (
+                        transaction_id, ledger_id, category_id, source, provider, model,
+                        confidence, reasoning, prompt_sha256, applied, created_by
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    "#,
+                )
+                .bind(suggestion.transaction_id)
+                .bind(req.ledger_id)
+                .bind(suggestion.category_id)
+                .bind(SOURCE_LLM)
+                .bind(&batch.provider)
+                .bind(&batch.model)
+                .bind(suggestion.confidence)
+                .bind(&suggestion.reasoning)
+                .bind(&batch.prompt_sha256)
+                .bind(suggestion.applied)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+
+                summary.processed += 1;
+                summary.applied += usize::from(suggestion.applied);
+                summary.results.push(suggestion);
+            }
+            tx.commit().await?;
+        }
+        Ok(summary)
+    }
+
+    /// 交易的分类建议记录（最新在前）
+    pub async fn categorization_history(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Vec<CategorizationRecord>, ServiceError> {
+        Ok(sqlx::query_as(
+            r#"
+            SELECT id, transaction_id, category_id, source, provider, model, confidence,
+                   reasoning, prompt_sha256, applied, created_by, created_at
+            FROM transaction_categorizations
+            WHERE transaction_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn list_chats(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> Result<Vec<AiChat>, ServiceError> {
+        Ok(sqlx::query_as(&format!(
+            "{} WHERE user_id = $1 AND family_id = $2 ORDER BY updated_at DESC",
+            CHAT_SELECT
+        ))
+        .bind(user_id)
+        .bind(family_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn create_chat(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        title: Option<String>,
+    ) -> Result<AiChat, ServiceError> {
+        let title = title
+            .map(|t| t.trim().chars().take(200).collect::<String>())
+            .filter(|t| !t.is_empty());
+        Ok(sqlx::query_as(
+            r#"
+            INSERT INTO ai_chats (user_id, family_id, title)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, family_id, title, created_at, updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(family_id)
+        .bind(title)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// 对话只对创建者可见
+    async fn owned_chat(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        chat_id: Uuid,
+    ) -> Result<AiChat, ServiceError> {
+        sqlx::query_as(&format!(
+            "{} WHERE id = $1 AND user_id = $2 AND family_id = $3",
+            CHAT_SELECT
+        ))
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(family_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("AiChat", chat_id))
+    }
+
+    pub async fn get_chat(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        chat_id: Uuid,
+    ) -> Result<AiChatDetail, ServiceError> {
+        let chat = self.owned_chat(user_id, family_id, chat_id).await?;
+        let messages = sqlx::query_as(&format!(
+            "SELECT {} FROM ai_chat_messages WHERE chat_id = $1 ORDER BY seq",
+            MESSAGE_COLUMNS
+        ))
+        .bind(chat_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(AiChatDetail { chat, messages })
+    }
+
+    pub async fn delete_chat(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        chat_id: Uuid,
+    ) -> Result<(), ServiceError> {
+        self.owned_chat(user_id, family_id, chat_id).await?;
+        sqlx::query("DELETE FROM ai_chats WHERE id = $1")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 发送一条消息并运行工具调用循环；工具只查询 `ledger_ids` 中的账本
+    pub async fn chat(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        chat_id: Uuid,
+        ledger_ids: Vec<Uuid>,
+        content: &str,
+    ) -> Result<ChatReply, ServiceError> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(ServiceError::validation("消息不能为空"));
+        }
+        if content.chars().count() > MAX_CHAT_MESSAGE_CHARS {
+            return Err(ServiceError::validation(format!(
+                "消息不能超过 {} 个字符",
+                MAX_CHAT_MESSAGE_CHARS
+            )));
+        }
+        let backend = self.backend()?;
+        self.owned_chat(user_id, family_id, chat_id).await?;
+
+        let mut history: Vec<AiChatMessage> = sqlx::query_as(&format!(
+            "SELECT {} FROM ai_chat_messages WHERE chat_id = $1 ORDER BY seq DESC LIMIT $2",
+            MESSAGE_COLUMNS
+        ))
+        .bind(chat_id)
+        .bind(CHAT_HISTORY_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+        history.reverse();
+        // 截断后的历史必须从用户消息开始，避免出现没有对应调用的工具结果
+        let first_user = history
+            .iter()
+            .position(|m| m.role == "user")
+            .unwrap_or(history.len());
+
+        let mut messages = vec![ChatMessage::system(chat_system_prompt(
+            Utc::now().date_naive(),
+        ))];
+        messages.extend(history[first_user..].iter().filter_map(to_chat_message));
+        messages.push(ChatMessage::user(content));
+
+        let tools = LedgerTools::new(self.pool.clone(), ledger_ids);
+        let outcome = run_tool_loop(backend, messages, &tools, self.config.max_tool_rounds).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut stored = Vec::with_capacity(outcome.messages.len() + 1);
+        let user_message = ChatMessage::user(content);
+        for message in std::iter::once(&user_message).chain(outcome.messages.iter()) {
+            let tool_calls = if message.tool_calls.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_value(&message.tool_calls)?)
+            };
+            let (provider, model) = if message.role == ChatRole::Assistant {
+                (
+                    Some(backend.provider_name().to_string()),
+                    Some(outcome.model.clone()),
+                )
+            } else {
+                (None, None)
+            };
+            let row: AiChatMessage = sqlx::query_as(&format!(
+                r#"
+                INSERT INTO ai_chat_messages
+                    (chat_id, role, content, tool_calls, tool_call_id, provider, model)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING {}
+                "#,
+                MESSAGE_COLUMNS
+            ))
+            .bind(chat_id)
+            .bind(message.role.as_str())
+            .bind(&message.content)
+            .bind(tool_calls)
+            .bind(&message.tool_call_id)
+            .bind(provider)
+            .bind(model)
+            .fetch_one(&mut *tx)
+            .await?;
+            stored.push(row);
+        }
+        sqlx::query(
+            r#"
+            UPDATE ai_chats
+            SET title = COALESCE(title, LEFT($2, 50)), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(chat_id)
+        .bind(content)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(ChatReply {
+            chat_id,
+            reply: outcome.reply().to_string(),
+            messages: stored,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm_providers::StubProvider;
+    use std::sync::Mutex;
+
+    fn stub_backend() -> (Arc<StubProvider>, LlmBackend) {
+        let stub = Arc::new(StubProvider::new());
+        let backend = LlmBackend::new(stub.clone(), "stub-categorize", "stub-chat");
+        (stub, backend)
+    }
+
+    fn transaction(kind: &str, description: &str) -> TransactionForAi {
+        TransactionForAi {
+            id: Uuid::new_v4(),
+            transaction_type: kind.to_string(),
+            amount: Decimal::new(3550, 2),
+            transaction_date: NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+            description: Some(description.to_string()),
+            payee: None,
+        }
+    }
+
+    fn category(kind: &str, name: &str) -> CategoryForAi {
+        CategoryForAi {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            category_type: kind.to_string(),
+            parent_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_categorize_with_stub_redacts_prompt() {
+        let (stub, backend) = stub_backend();
+        let transactions = vec![
+            transaction("expense", "STARBUCKS 卡号 6222021234567890"),
+            transaction("income", "十月工资"),
+            transaction("expense", "ATM withdrawal"),
+        ];
+        let categories = vec![category("expense", "餐饮"), category("income", "工资")];
+
+        let batch = categorize(&backend, &transactions, &categories)
+            .await
+            .unwrap();
+        assert_eq!(batch.provider, "stub");
+        assert_eq!(batch.model, "stub-categorize");
+        assert_eq!(batch.prompt_sha256.len(), 64);
+
+        let picked: Vec<(Option<Uuid>, f64)> = batch
+            .suggestions
+            .iter()
+            .map(|s| (s.category_id, s.confidence))
+            .collect();
+        assert_eq!(
+            picked,
+            vec![
+                (Some(categories[0].id), 0.7),
+                (Some(categories[1].id), 0.9),
+                (None, 0.0),
+            ]
+        );
+        assert_eq!(batch.suggestions[0].transaction_id, transactions[0].id);
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].json_response);
+        let prompt = requests[0].messages[1].content.as_deref().unwrap();
+        assert!(prompt.contains("****7890"));
+        assert!(!prompt.contains("6222021234567890"));
+        // 不向模型发送内部 id
+        assert!(!prompt.contains(&transactions[0].id.to_string()));
+    }
+
+    #[test]
+    fn test_parse_categorization_response_validates_refs() {
+        let transactions = vec![
+            transaction("expense", "a"),
+            transaction("expense", "b"),
+            transaction("expense", "c"),
+            transaction("expense", "d"),
+        ];
+        let categories = vec![category("expense", "餐饮"), category("income", "工资")];
+        let reply = r#"好的：
+```json
+{"results": [
+  {"ref": "t1", "category": "c1", "confidence": 1.7, "reason": "coffee"},
+  {"ref": "t2", "category": "c2", "confidence": 0.9},
+  {"ref": "t3", "category": "c9", "confidence": 0.9}
+]}
+```"#;
+        let parsed = parse_categorization_response(reply, &transactions, &categories).unwrap();
+        assert_eq!(parsed[0].category_id, Some(categories[0].id));
+        assert_eq!(parsed[0].category_name.as_deref(), Some("餐饮"));
+        assert_eq!(parsed[0].confidence, 1.0);
+        assert_eq!(parsed[0].reasoning.as_deref(), Some("coffee"));
+        // 类型不一致、未知引用、缺失结果
+        for suggestion in &parsed[1..] {
+            assert_eq!((suggestion.category_id, suggestion.confidence), (None, 0.0));
+        }
+        assert!(parse_categorization_response("no idea", &transactions, &categories).is_err());
+    }
+
+    /// 固定返回值的工具，记录被调用的工具名
+    #[derive(Default)]
+    struct StaticTools {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for StaticTools {
+        fn definitions(&self) -> Vec<ToolDefinition> {
+            ledger_tool_definitions()
+        }
+
+        async fn execute(&self, name: &str, _arguments: &Value) -> Result<Value, ServiceError> {
+            self.calls.lock().unwrap().push(name.to_string());
+            Ok(json!({
+                "by_currency": [{ "currency": "CNY", "net_worth": "12345678.90" }],
+                "note": "还款卡 6222021234567890",
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_tool_loop_with_stub() {
+        let (stub, backend) = stub_backend();
+        let tools = StaticTools::default();
+        let outcome = run_tool_loop(
+            &backend,
+            vec![
+                ChatMessage::system("sys"),
+                ChatMessage::user("我的净资产是多少？"),
+            ],
+            &tools,
+            5,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(*tools.calls.lock().unwrap(), vec!["get_balance_sheet"]);
+        let roles: Vec<ChatRole> = outcome.messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![ChatRole::Assistant, ChatRole::Tool, ChatRole::Assistant]
+        );
+        assert_eq!(
+            outcome.messages[1].tool_call_id.as_deref(),
+            Some(outcome.messages[0].tool_calls[0].id.as_str())
+        );
+        assert!(outcome.reply().contains("12345678.90"));
+        assert_eq!(outcome.model, "stub-chat");
+
+        // 工具结果发给模型前已脱敏（金额保持不变）
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 2);
+        let sent = requests[1].messages[3].content.as_deref().unwrap();
+        assert!(sent.contains("****7890") && !sent.contains("6222021234567890"));
+        assert!(outcome.messages[1]
+            .content
+            .as_deref()
+            .unwrap()
+            .contains("6222021234567890"));
+
+        // 不提供工具时直接回答
+        let direct = run_tool_loop(&backend, vec![ChatMessage::user("你好")], &tools, 0)
+            .await
+            .unwrap();
+        assert_eq!(direct.messages.len(), 1);
+        assert_eq!(direct.reply(), "收到：你好");
+        assert!(stub.requests()[2].tools.is_empty());
+    }
+}
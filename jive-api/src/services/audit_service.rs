@@ -235,6 +235,36 @@ impl AuditService {
         self.insert_log(log).await
     }
 
+    /// 记录与家庭无关的用户级事件（登录失败、账户锁定、新设备登录等）到 audit_logs
+    pub async fn log_user_event(
+        &self,
+        user_id: Uuid,
+        action: AuditAction,
+        details: Option<serde_json::Value>,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_logs (
+                id, user_id, action, entity_type, entity_id,
+                new_value, ip_address, user_agent, created_at
+            )
+            VALUES ($1, $2, $3, 'user', $2, $4, $5::inet, $6, NOW())
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(action.to_string())
+        .bind(details)
+        .bind(ip_address)
+        .bind(user_agent)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     async fn insert_log(&self, log: AuditLog) -> Result<(), ServiceError> {
         sqlx::query(
             r#"
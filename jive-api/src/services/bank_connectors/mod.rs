@@ -0,0 +1,348 @@
+//! 银行聚合连接器
+//!
+//! - `BankConnector`：数据源抽象（Link 授权、账户、基于游标的增量交易同步、移除、重新授权、webhook 验签）
+//! - `PlaidConnector`：Plaid REST API 实现（也可通过 `PLAID_BASE_URL` 指向本地模拟机构服务器）
+//! - `FakeInstitution`：内存中的模拟机构，既可直接作为连接器使用，也可通过 `router()` 提供
+//!   与 Plaid 兼容的 HTTP 接口，用于离线开发与集成测试
+//!
+//! 交易金额沿用 Plaid 的符号约定：正数为流出（支出），负数为流入（收入）。
+
+pub mod fake;
+pub mod plaid;
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::BankConnectorConfig;
+use crate::services::ServiceError;
+
+pub use fake::{FakeConnector, FakeInstitution};
+pub use plaid::PlaidConnector;
+
+/// 连接器错误
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ConnectorError {
+    /// 机构登录失效或授权过期，需要用户通过 Link 更新模式重新授权
+    #[error("Re-authentication required: {0}")]
+    ReauthRequired(String),
+    /// 分页同步期间数据发生变化，需要从本轮起始游标重新同步
+    #[error("Data changed during pagination")]
+    MutationDuringPagination,
+    /// webhook 签名或内容无效
+    #[error("Invalid webhook: {0}")]
+    InvalidWebhook(String),
+    #[error("Provider error: {0}")]
+    Provider(String),
+}
+
+impl From<ConnectorError> for ServiceError {
+    fn from(e: ConnectorError) -> Self {
+        match e {
+            ConnectorError::ReauthRequired(msg) => {
+                ServiceError::business_rule(format!("需要重新授权: {}", msg))
+            }
+            ConnectorError::InvalidWebhook(msg) => ServiceError::AuthenticationError(msg),
+            other => ServiceError::ExternalApi {
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+/// 创建 Link token 的参数
+#[derive(Debug, Clone)]
+pub struct LinkTokenRequest {
+    pub user_id: Uuid,
+    /// 更新模式（重新授权）时传入已有连接的 access token
+    pub access_token: Option<String>,
+    pub webhook_url: Option<String>,
+}
+
+/// 客户端打开 Link 所需的一次性 token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LinkToken {
+    pub link_token: String,
+    pub expiration: DateTime<Utc>,
+}
+
+/// 交换 public token 后得到的连接凭据
+#[derive(Debug, Clone)]
+pub struct ItemCredentials {
+    pub item_id: String,
+    pub access_token: String,
+    pub institution_id: Option<String>,
+    pub institution_name: Option<String>,
+}
+
+/// 机构侧账户
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalAccount {
+    pub account_id: String,
+    pub name: String,
+    pub mask: Option<String>,
+    /// depository / credit / loan / investment / other
+    pub account_type: String,
+    /// checking / savings / credit card / mortgage ...
+    pub subtype: Option<String>,
+    pub currency: Option<String>,
+    pub current_balance: Option<Decimal>,
+    pub available_balance: Option<Decimal>,
+}
+
+/// 机构侧交易
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalTransaction {
+    pub transaction_id: String,
+    pub account_id: String,
+    /// 正数为流出，负数为流入
+    pub amount: Decimal,
+    pub currency: Option<String>,
+    pub date: NaiveDate,
+    pub name: String,
+    pub merchant_name: Option<String>,
+    pub category: Option<String>,
+    pub pending: bool,
+    /// 入账交易对应的待入账交易 id（机构支持时提供）
+    pub pending_transaction_id: Option<String>,
+}
+
+/// 一页增量同步结果
+#[derive(Debug, Clone, Default)]
+pub struct SyncPage {
+    pub added: Vec<ExternalTransaction>,
+    pub modified: Vec<ExternalTransaction>,
+    /// 被机构删除的交易 id（包括入账后被替换的待入账交易）
+    pub removed: Vec<String>,
+    pub next_cursor: String,
+    pub has_more: bool,
+}
+
+/// 解析后的 webhook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// 有新的增量数据可同步
+    SyncAvailable { item_id: String },
+    /// 机构删除了交易
+    TransactionsRemoved {
+        item_id: String,
+        transaction_ids: Vec<String>,
+    },
+    /// 授权即将过期
+    PendingExpiration {
+        item_id: String,
+        consent_expires_at: Option<DateTime<Utc>>,
+    },
+    /// 登录失效，需要重新授权
+    LoginRequired { item_id: String },
+    /// 其他未处理的类型
+    Other {
+        item_id: Option<String>,
+        webhook_type: String,
+        webhook_code: String,
+    },
+}
+
+impl WebhookEvent {
+    pub fn item_id(&self) -> Option<&str> {
+        match self {
+            WebhookEvent::SyncAvailable { item_id }
+            | WebhookEvent::TransactionsRemoved { item_id, .. }
+            | WebhookEvent::PendingExpiration { item_id, .. }
+            | WebhookEvent::LoginRequired { item_id } => Some(item_id),
+            WebhookEvent::Other { item_id, .. } => item_id.as_deref(),
+        }
+    }
+}
+
+/// 银行聚合数据源
+#[async_trait]
+pub trait BankConnector: Send + Sync {
+    /// 数据源名称（与 bank_connections.provider 一致）
+    fn provider(&self) -> &str;
+
+    /// 创建 Link token；传入 access token 时为重新授权（更新模式）
+    async fn create_link_token(&self, req: &LinkTokenRequest) -> Result<LinkToken, ConnectorError>;
+
+    /// 用 Link 返回的 public token 换取长期 access token
+    async fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> Result<ItemCredentials, ConnectorError>;
+
+    async fn accounts(&self, access_token: &str) -> Result<Vec<ExternalAccount>, ConnectorError>;
+
+    /// 从 `cursor` 之后拉取一页增量；`None` 表示从头开始
+    async fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+    ) -> Result<SyncPage, ConnectorError>;
+
+    /// 撤销机构侧授权
+    async fn remove_item(&self, access_token: &str) -> Result<(), ConnectorError>;
+
+    /// 校验 webhook 签名，`body` 为原始请求体
+    async fn verify_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), ConnectorError>;
+
+    fn parse_webhook(&self, body: &[u8]) -> Result<WebhookEvent, ConnectorError> {
+        parse_plaid_webhook(body)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookBody {
+    webhook_type: String,
+    webhook_code: String,
+    item_id: Option<String>,
+    #[serde(default)]
+    removed_transactions: Vec<String>,
+    consent_expiration_time: Option<DateTime<Utc>>,
+    error: Option<WebhookError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookError {
+    error_code: Option<String>,
+}
+
+/// 解析 Plaid 格式的 webhook 请求体（模拟机构使用相同格式）
+pub fn parse_plaid_webhook(body: &[u8]) -> Result<WebhookEvent, ConnectorError> {
+    let body: WebhookBody = serde_json::from_slice(body)
+        .map_err(|e| ConnectorError::InvalidWebhook(format!("invalid body: {}", e)))?;
+    let Some(item_id) = body.item_id.clone() else {
+        return Ok(WebhookEvent::Other {
+            item_id: None,
+            webhook_type: body.webhook_type,
+            webhook_code: body.webhook_code,
+        });
+    };
+    let event = match (body.webhook_type.as_str(), body.webhook_code.as_str()) {
+        ("TRANSACTIONS", "SYNC_UPDATES_AVAILABLE")
+        | ("TRANSACTIONS", "INITIAL_UPDATE")
+        | ("TRANSACTIONS", "HISTORICAL_UPDATE")
+        | ("TRANSACTIONS", "DEFAULT_UPDATE") => WebhookEvent::SyncAvailable { item_id },
+        ("TRANSACTIONS", "TRANSACTIONS_REMOVED") => WebhookEvent::TransactionsRemoved {
+            item_id,
+            transaction_ids: body.removed_transactions,
+        },
+        ("ITEM", "PENDING_EXPIRATION") | ("ITEM", "PENDING_DISCONNECT") => {
+            WebhookEvent::PendingExpiration {
+                item_id,
+                consent_expires_at: body.consent_expiration_time,
+            }
+        }
+        ("ITEM", "ERROR")
+            if body.error.as_ref().and_then(|e| e.error_code.as_deref())
+                == Some("ITEM_LOGIN_REQUIRED") =>
+        {
+            WebhookEvent::LoginRequired { item_id }
+        }
+        ("ITEM", "LOGIN_REPAIRED") => WebhookEvent::SyncAvailable { item_id },
+        _ => WebhookEvent::Other {
+            item_id: Some(item_id),
+            webhook_type: body.webhook_type,
+            webhook_code: body.webhook_code,
+        },
+    };
+    Ok(event)
+}
+
+/// 已启用的连接器
+pub struct ConnectorRegistry {
+    connectors: HashMap<String, Arc<dyn BankConnector>>,
+}
+
+impl ConnectorRegistry {
+    pub fn new(connectors: Vec<Arc<dyn BankConnector>>) -> Self {
+        Self {
+            connectors: connectors
+                .into_iter()
+                .map(|c| (c.provider().to_string(), c))
+                .collect(),
+        }
+    }
+
+    /// 按配置构建：配置了 Plaid 凭据时启用 Plaid，`BANK_FAKE_ENABLED` 时启用模拟机构
+    pub fn from_config(config: &BankConnectorConfig) -> Self {
+        let mut connectors: Vec<Arc<dyn BankConnector>> = Vec::new();
+        if let (Some(client_id), Some(secret)) = (&config.plaid_client_id, &config.plaid_secret) {
+            connectors.push(Arc::new(
+                PlaidConnector::new(
+                    config.plaid_url(),
+                    client_id.clone(),
+                    secret.clone(),
+                    config.request_timeout_secs,
+                )
+                .with_webhook_tolerance(config.webhook_tolerance_secs),
+            ));
+        }
+        if config.fake_enabled {
+            connectors.push(Arc::new(
+                FakeConnector::new(FakeInstitution::demo(), config.fake_webhook_secret.clone())
+                    .with_webhook_tolerance(config.webhook_tolerance_secs),
+            ));
+        }
+        Self::new(connectors)
+    }
+
+    pub fn get(&self, provider: &str) -> Result<Arc<dyn BankConnector>, ServiceError> {
+        self.connectors.get(provider).cloned().ok_or_else(|| {
+            ServiceError::validation(format!("Bank provider {} is not enabled", provider))
+        })
+    }
+
+    pub fn providers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.connectors.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plaid_webhook_codes() {
+        let removed = br#"{"webhook_type":"TRANSACTIONS","webhook_code":"TRANSACTIONS_REMOVED","item_id":"item-1","removed_transactions":["tx-1","tx-2"]}"#;
+        assert_eq!(
+            parse_plaid_webhook(removed).unwrap(),
+            WebhookEvent::TransactionsRemoved {
+                item_id: "item-1".to_string(),
+                transaction_ids: vec!["tx-1".to_string(), "tx-2".to_string()],
+            }
+        );
+
+        let expiring = br#"{"webhook_type":"ITEM","webhook_code":"PENDING_EXPIRATION","item_id":"item-1","consent_expiration_time":"2026-11-01T00:00:00Z"}"#;
+        assert!(matches!(
+            parse_plaid_webhook(expiring).unwrap(),
+            WebhookEvent::PendingExpiration {
+                consent_expires_at: Some(_),
+                ..
+            }
+        ));
+
+        let login = br#"{"webhook_type":"ITEM","webhook_code":"ERROR","item_id":"item-1","error":{"error_code":"ITEM_LOGIN_REQUIRED"}}"#;
+        assert_eq!(
+            parse_plaid_webhook(login).unwrap(),
+            WebhookEvent::LoginRequired {
+                item_id: "item-1".to_string()
+            }
+        );
+
+        let other =
+            br#"{"webhook_type":"HOLDINGS","webhook_code":"DEFAULT_UPDATE","item_id":"item-1"}"#;
+        assert!(matches!(
+            parse_plaid_webhook(other).unwrap(),
+            WebhookEvent::Other { .. }
+        ));
+        assert!(parse_plaid_webhook(b"not json").is_err());
+    }
+}
@@ -0,0 +1,452 @@
+//! Plaid 连接器：Link token、public token 交换、账户、`/transactions/sync` 增量同步与 webhook 验签
+//!
+//! Webhook 验签：`Plaid-Verification` 头是 ES256 签名的 JWT，公钥通过
+//! `/webhook_verification_key/get` 按 `kid` 获取（缓存）；JWT 的 `iat` 不得早于 5 分钟，
+//! `request_body_sha256` 必须与原始请求体的 SHA-256 一致。
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::{NaiveDate, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use super::{
+    BankConnector, ConnectorError, ExternalAccount, ExternalTransaction, ItemCredentials,
+    LinkToken, LinkTokenRequest, SyncPage,
+};
+
+/// 单页同步的最大交易数（Plaid 上限 500）
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Plaid 连接器
+pub struct PlaidConnector {
+    client: reqwest::Client,
+    base_url: String,
+    client_id: String,
+    secret: String,
+    page_size: u32,
+    webhook_tolerance_secs: i64,
+    /// kid -> (x, y)
+    verification_keys: Mutex<HashMap<String, (String, String)>>,
+}
+
+impl PlaidConnector {
+    pub fn new(base_url: String, client_id: String, secret: String, timeout_secs: u64) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(timeout_secs))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client_id,
+            secret,
+            page_size: DEFAULT_PAGE_SIZE,
+            webhook_tolerance_secs: 300,
+            verification_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.clamp(1, 500);
+        self
+    }
+
+    pub fn with_webhook_tolerance(mut self, secs: i64) -> Self {
+        self.webhook_tolerance_secs = secs;
+        self
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        mut body: serde_json::Value,
+    ) -> Result<T, ConnectorError> {
+        body["client_id"] = json!(self.client_id);
+        body["secret"] = json!(self.secret);
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ConnectorError::Provider(format!("Plaid {} failed: {}", path, e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error =
+                response
+                    .json::<PlaidErrorBody>()
+                    .await
+                    .unwrap_or_else(|_| PlaidErrorBody {
+                        error_type: "API_ERROR".to_string(),
+                        error_code: format!("HTTP_{}", status.as_u16()),
+                        error_message: format!("Plaid {} returned {}", path, status),
+                    });
+            return Err(error.into());
+        }
+        response.json::<T>().await.map_err(|e| {
+            ConnectorError::Provider(format!("Invalid Plaid {} response: {}", path, e))
+        })
+    }
+
+    /// 按 kid 获取 webhook 验签公钥（缓存）
+    async fn verification_key(&self, kid: &str) -> Result<(String, String), ConnectorError> {
+        if let Some(key) = self.verification_keys.lock().unwrap().get(kid) {
+            return Ok(key.clone());
+        }
+        let response: VerificationKeyResponse = self
+            .post("/webhook_verification_key/get", json!({ "key_id": kid }))
+            .await?;
+        let key = response.key;
+        if key.kty != "EC" || key.crv != "P-256" {
+            return Err(ConnectorError::InvalidWebhook(format!(
+                "unsupported key {} {}",
+                key.kty, key.crv
+            )));
+        }
+        if key.expired_at.is_some() {
+            return Err(ConnectorError::InvalidWebhook(format!(
+                "verification key {} expired",
+                kid
+            )));
+        }
+        let pair = (key.x, key.y);
+        self.verification_keys
+            .lock()
+            .unwrap()
+            .insert(kid.to_string(), pair.clone());
+        Ok(pair)
+    }
+}
+
+#[async_trait]
+impl BankConnector for PlaidConnector {
+    fn provider(&self) -> &str {
+        "plaid"
+    }
+
+    async fn create_link_token(&self, req: &LinkTokenRequest) -> Result<LinkToken, ConnectorError> {
+        let mut body = json!({
+            "client_name": "Jive Money",
+            "language": "en",
+            "country_codes": ["US"],
+            "user": { "client_user_id": req.user_id.to_string() },
+        });
+        match &req.access_token {
+            // 更新模式不能指定 products
+            Some(access_token) => body["access_token"] = json!(access_token),
+            None => body["products"] = json!(["transactions"]),
+        }
+        if let Some(webhook) = &req.webhook_url {
+            body["webhook"] = json!(webhook);
+        }
+        self.post("/link/token/create", body).await
+    }
+
+    async fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> Result<ItemCredentials, ConnectorError> {
+        let exchanged: ExchangeResponse = self
+            .post(
+                "/item/public_token/exchange",
+                json!({ "public_token": public_token }),
+            )
+            .await?;
+        let item: ItemResponse = self
+            .post(
+                "/item/get",
+                json!({ "access_token": exchanged.access_token }),
+            )
+            .await?;
+        Ok(ItemCredentials {
+            item_id: exchanged.item_id,
+            access_token: exchanged.access_token,
+            institution_id: item.item.institution_id,
+            institution_name: item.item.institution_name,
+        })
+    }
+
+    async fn accounts(&self, access_token: &str) -> Result<Vec<ExternalAccount>, ConnectorError> {
+        let response: AccountsResponse = self
+            .post("/accounts/get", json!({ "access_token": access_token }))
+            .await?;
+        Ok(response.accounts.into_iter().map(Into::into).collect())
+    }
+
+    async fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+    ) -> Result<SyncPage, ConnectorError> {
+        let mut body = json!({ "access_token": access_token, "count": self.page_size });
+        if let Some(cursor) = cursor {
+            body["cursor"] = json!(cursor);
+        }
+        let response: SyncResponse = self.post("/transactions/sync", body).await?;
+        Ok(SyncPage {
+            added: response.added.into_iter().map(Into::into).collect(),
+            modified: response.modified.into_iter().map(Into::into).collect(),
+            removed: response
+                .removed
+                .into_iter()
+                .map(|r| r.transaction_id)
+                .collect(),
+            next_cursor: response.next_cursor,
+            has_more: response.has_more,
+        })
+    }
+
+    async fn remove_item(&self, access_token: &str) -> Result<(), ConnectorError> {
+        let _: serde_json::Value = self
+            .post("/item/remove", json!({ "access_token": access_token }))
+            .await?;
+        Ok(())
+    }
+
+    async fn verify_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), ConnectorError> {
+        let token = headers
+            .get("plaid-verification")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| {
+                ConnectorError::InvalidWebhook("missing Plaid-Verification header".to_string())
+            })?;
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|e| ConnectorError::InvalidWebhook(format!("invalid JWT header: {}", e)))?;
+        if header.alg != Algorithm::ES256 {
+            return Err(ConnectorError::InvalidWebhook(format!(
+                "unexpected algorithm {:?}",
+                header.alg
+            )));
+        }
+        let kid = header
+            .kid
+            .ok_or_else(|| ConnectorError::InvalidWebhook("missing kid".to_string()))?;
+        let (x, y) = self.verification_key(&kid).await?;
+        let key = DecodingKey::from_ec_components(&x, &y)
+            .map_err(|e| ConnectorError::InvalidWebhook(format!("invalid key: {}", e)))?;
+
+        // 没有 exp，改为检查 iat
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.validate_exp = false;
+        validation.validate_aud = false;
+        validation.required_spec_claims = HashSet::new();
+        let claims = jsonwebtoken::decode::<WebhookClaims>(token, &key, &validation)
+            .map_err(|e| ConnectorError::InvalidWebhook(format!("invalid signature: {}", e)))?
+            .claims;
+        if Utc::now().timestamp() - claims.iat > self.webhook_tolerance_secs {
+            return Err(ConnectorError::InvalidWebhook(
+                "webhook too old".to_string(),
+            ));
+        }
+        let digest = hex::encode(Sha256::digest(body));
+        if !digest.eq_ignore_ascii_case(&claims.request_body_sha256) {
+            return Err(ConnectorError::InvalidWebhook(
+                "body hash mismatch".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ===== Plaid 报文格式（模拟机构服务器复用） =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct PlaidErrorBody {
+    pub error_type: String,
+    pub error_code: String,
+    pub error_message: String,
+}
+
+impl From<PlaidErrorBody> for ConnectorError {
+    fn from(e: PlaidErrorBody) -> Self {
+        match e.error_code.as_str() {
+            "ITEM_LOGIN_REQUIRED" | "PENDING_EXPIRATION" | "ACCESS_NOT_GRANTED" => {
+                ConnectorError::ReauthRequired(e.error_message)
+            }
+            "TRANSACTIONS_SYNC_MUTATION_DURING_PAGINATION" => {
+                ConnectorError::MutationDuringPagination
+            }
+            _ => ConnectorError::Provider(format!("{}: {}", e.error_code, e.error_message)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct ExchangeResponse {
+    pub access_token: String,
+    pub item_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct ItemResponse {
+    pub item: PlaidItem,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidItem {
+    pub item_id: String,
+    pub institution_id: Option<String>,
+    pub institution_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct AccountsResponse {
+    pub accounts: Vec<PlaidAccount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidAccount {
+    pub account_id: String,
+    pub name: String,
+    pub mask: Option<String>,
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub subtype: Option<String>,
+    pub balances: PlaidBalances,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidBalances {
+    pub current: Option<f64>,
+    pub available: Option<f64>,
+    pub iso_currency_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct SyncResponse {
+    pub added: Vec<PlaidTransaction>,
+    pub modified: Vec<PlaidTransaction>,
+    pub removed: Vec<PlaidRemovedTransaction>,
+    pub next_cursor: String,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidTransaction {
+    pub transaction_id: String,
+    pub account_id: String,
+    pub amount: f64,
+    pub iso_currency_code: Option<String>,
+    pub date: NaiveDate,
+    pub name: String,
+    pub merchant_name: Option<String>,
+    pub pending: bool,
+    pub pending_transaction_id: Option<String>,
+    pub personal_finance_category: Option<PlaidCategory>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidCategory {
+    pub primary: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidRemovedTransaction {
+    pub transaction_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct VerificationKeyResponse {
+    pub key: PlaidJwk,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PlaidJwk {
+    pub alg: String,
+    pub kid: String,
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub created_at: i64,
+    pub expired_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct WebhookClaims {
+    pub iat: i64,
+    pub request_body_sha256: String,
+}
+
+fn to_decimal(value: Option<f64>) -> Option<Decimal> {
+    value.and_then(Decimal::from_f64).map(|d| d.round_dp(2))
+}
+
+fn to_f64(value: Option<Decimal>) -> Option<f64> {
+    value.and_then(|d| d.to_f64())
+}
+
+impl From<PlaidAccount> for ExternalAccount {
+    fn from(a: PlaidAccount) -> Self {
+        Self {
+            account_id: a.account_id,
+            name: a.name,
+            mask: a.mask,
+            account_type: a.account_type,
+            subtype: a.subtype,
+            currency: a.balances.iso_currency_code,
+            current_balance: to_decimal(a.balances.current),
+            available_balance: to_decimal(a.balances.available),
+        }
+    }
+}
+
+impl From<&ExternalAccount> for PlaidAccount {
+    fn from(a: &ExternalAccount) -> Self {
+        Self {
+            account_id: a.account_id.clone(),
+            name: a.name.clone(),
+            mask: a.mask.clone(),
+            account_type: a.account_type.clone(),
+            subtype: a.subtype.clone(),
+            balances: PlaidBalances {
+                current: to_f64(a.current_balance),
+                available: to_f64(a.available_balance),
+                iso_currency_code: a.currency.clone(),
+            },
+        }
+    }
+}
+
+impl From<PlaidTransaction> for ExternalTransaction {
+    fn from(t: PlaidTransaction) -> Self {
+        Self {
+            transaction_id: t.transaction_id,
+            account_id: t.account_id,
+            amount: to_decimal(Some(t.amount)).unwrap_or_default(),
+            currency: t.iso_currency_code,
+            date: t.date,
+            name: t.name,
+            merchant_name: t.merchant_name,
+            category: t.personal_finance_category.map(|c| c.primary),
+            pending: t.pending,
+            pending_transaction_id: t.pending_transaction_id,
+        }
+    }
+}
+
+impl From<&ExternalTransaction> for PlaidTransaction {
+    fn from(t: &ExternalTransaction) -> Self {
+        Self {
+            transaction_id: t.transaction_id.clone(),
+            account_id: t.account_id.clone(),
+            amount: t.amount.to_f64().unwrap_or_default(),
+            iso_currency_code: t.currency.clone(),
+            date: t.date,
+            name: t.name.clone(),
+            merchant_name: t.merchant_name.clone(),
+            pending: t.pending,
+            pending_transaction_id: t.pending_transaction_id.clone(),
+            personal_finance_category: t.category.clone().map(|primary| PlaidCategory { primary }),
+        }
+    }
+}
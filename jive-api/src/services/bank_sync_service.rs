@@ -0,0 +1,1267 @@
+//! 银行连接：授权、账户导入、增量交易同步、待入账对账与 webhook 处理
+//!
+//! 同步流程：从上次保存的游标开始逐页拉取，每页在一个数据库事务内写入，全部页拉取完成后
+//! 才保存新游标。写入按 (连接, 机构交易 id) 幂等，因此机构返回“分页期间数据变化”时可以
+//! 从本轮起始游标重新同步。
+//!
+//! 待入账 → 入账对账：入账交易优先按机构提供的 `pending_transaction_id` 找到原待入账交易，
+//! 否则按同账户、7 天内、金额或商户名相同的规则匹配；匹配成功时原地更新这笔交易（保留
+//! 用户设置的分类、备注与标签），而不是新增一笔。
+//!
+//! 账户余额以机构返回的当前余额为准，同步完成后整体刷新，不按单笔交易增减。
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::bank_connectors::{
+    BankConnector, ConnectorError, ConnectorRegistry, ExternalAccount, ExternalTransaction,
+    LinkToken, LinkTokenRequest, SyncPage, WebhookEvent,
+};
+use super::notification_service::{NewNotification, NotificationPriority, NotificationService};
+use super::transaction_valuation_service::TransactionValuationService;
+use super::webhook_service::{WebhookEventType, WebhookService};
+use super::ServiceError;
+use crate::config::AppConfig;
+use crate::ws::WsConnectionManager;
+
+/// 入账交易与待入账交易的最大日期间隔（天）
+const PENDING_MATCH_DAYS: i64 = 7;
+/// 分页期间数据变化时的最大重试次数
+const MAX_SYNC_RESTARTS: usize = 3;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct BankConnection {
+    pub id: Uuid,
+    pub family_id: Uuid,
+    pub ledger_id: Uuid,
+    pub provider: String,
+    pub institution_id: Option<String>,
+    pub institution_name: Option<String>,
+    /// active / reauth_required / error
+    pub status: String,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consent_expires_at: Option<DateTime<Utc>>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+const CONNECTION_SELECT: &str = r#"
+    SELECT id, family_id, ledger_id, provider, institution_id, institution_name, status,
+           last_synced_at, last_error, consent_expires_at, created_by, created_at, updated_at
+    FROM bank_connections
+"#;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct BankConnectionAccount {
+    pub id: Uuid,
+    pub connection_id: Uuid,
+    pub external_account_id: String,
+    /// 接收交易的 Jive 账户；为空表示不导入
+    pub account_id: Option<Uuid>,
+    pub name: String,
+    pub mask: Option<String>,
+    pub account_type: String,
+    pub account_subtype: Option<String>,
+    pub currency: Option<String>,
+    pub current_balance: Option<Decimal>,
+    pub available_balance: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BankConnectionDetail {
+    pub connection: BankConnection,
+    pub accounts: Vec<BankConnectionAccount>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateLinkTokenRequest {
+    /// plaid / fake
+    pub provider: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateConnectionRequest {
+    pub provider: String,
+    /// Link 完成后返回的 public token
+    pub public_token: String,
+    pub ledger_id: Uuid,
+    /// 为每个机构账户自动创建 Jive 账户（默认 true）
+    pub import_accounts: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LinkConnectionAccountRequest {
+    /// 接收交易的 Jive 账户；为空表示停止导入
+    pub account_id: Option<Uuid>,
+}
+
+/// 一次同步的结果
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub modified: usize,
+    pub removed: usize,
+    /// 待入账交易转为入账（原地更新）的笔数
+    pub reconciled: usize,
+    /// 属于未导入账户而跳过的笔数
+    pub skipped: usize,
+}
+
+impl SyncSummary {
+    /// 本地交易是否有变化
+    pub fn has_changes(&self) -> bool {
+        self.added + self.modified + self.removed + self.reconciled > 0
+    }
+}
+
+/// 对账候选：本地仍为待入账状态的同步交易
+#[derive(Debug, Clone)]
+pub struct PendingCandidate {
+    pub id: Uuid,
+    /// Plaid 符号约定：正数为流出
+    pub amount: Decimal,
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// 为入账交易挑选对应的待入账交易
+///
+/// 候选必须方向相同、日期不晚于入账日 1 天且不早于 7 天。优先级：金额与名称都相同 >
+/// 金额相同 > 名称相同且金额相差不超过 20%（小费、预授权差额）；同级取日期最近的。
+pub fn match_pending(
+    candidates: &[PendingCandidate],
+    posted: &ExternalTransaction,
+) -> Option<Uuid> {
+    let posted_name = normalize_name(posted.merchant_name.as_deref().unwrap_or(&posted.name));
+    candidates
+        .iter()
+        .filter(|c| c.amount.is_sign_negative() == posted.amount.is_sign_negative())
+        .filter_map(|c| {
+            let gap = (posted.date - c.date).num_days();
+            if !(-1..=PENDING_MATCH_DAYS).contains(&gap) {
+                return None;
+            }
+            let same_amount = c.amount == posted.amount;
+            let same_name = !posted_name.is_empty() && normalize_name(&c.name) == posted_name;
+            let score = match (same_amount, same_name) {
+                (true, true) => 3,
+                (true, false) => 2,
+                (false, true)
+                    if !c.amount.is_zero()
+                        && ((posted.amount - c.amount) / c.amount).abs() <= Decimal::new(2, 1) =>
+                {
+                    1
+                }
+                _ => return None,
+            };
+            Some((score, gap.abs(), c.id))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(_, _, id)| id)
+}
+
+/// 机构账户类型 -> (account_main_type, account_sub_type)
+pub fn jive_account_type(
+    account_type: &str,
+    subtype: Option<&str>,
+) -> (&'static str, &'static str) {
+    match (account_type, subtype.unwrap_or_default()) {
+        ("credit", _) => ("liability", "credit_card"),
+        ("loan", "mortgage" | "home equity") => ("liability", "mortgage"),
+        ("loan", _) => ("liability", "loan"),
+        ("investment" | "brokerage", _) => ("asset", "investment"),
+        ("depository", "savings" | "cd" | "money market") => ("asset", "savings_account"),
+        ("depository", "prepaid") => ("asset", "prepaid_card"),
+        _ => ("asset", "checking"),
+    }
+}
+
+/// 机构余额 -> Jive 账户余额（负债为负数）
+fn signed_balance(main_type: &str, balance: Decimal) -> Decimal {
+    if main_type == "liability" {
+        -balance.abs()
+    } else {
+        balance
+    }
+}
+
+/// 同步需要的连接内部字段
+#[derive(Debug, sqlx::FromRow)]
+struct ConnectionSecret {
+    id: Uuid,
+    family_id: Uuid,
+    ledger_id: Uuid,
+    provider: String,
+    institution_name: Option<String>,
+    access_token: String,
+    sync_cursor: Option<String>,
+    created_by: Uuid,
+}
+
+#[derive(Debug, Clone)]
+struct LinkedAccount {
+    account_id: Uuid,
+    currency: String,
+}
+
+enum Applied {
+    Inserted(Uuid),
+    Updated(Uuid),
+    Reconciled(Uuid),
+    Skipped,
+}
+
+#[derive(Clone)]
+pub struct BankSyncService {
+    pool: PgPool,
+    registry: Arc<ConnectorRegistry>,
+    config: Arc<AppConfig>,
+    ws: Arc<WsConnectionManager>,
+}
+
+impl BankSyncService {
+    pub fn new(
+        pool: PgPool,
+        registry: Arc<ConnectorRegistry>,
+        config: Arc<AppConfig>,
+        ws: Arc<WsConnectionManager>,
+    ) -> Self {
+        Self {
+            pool,
+            registry,
+            config,
+            ws,
+        }
+    }
+
+    pub fn providers(&self) -> Vec<String> {
+        self.registry.providers()
+    }
+
+    fn webhook_url(&self, provider: &str) -> Option<String> {
+        self.config
+            .bank_connectors
+            .webhook_url
+            .as_ref()
+            .map(|base| format!("{}/{}", base.trim_end_matches('/'), provider))
+    }
+
+    async fn value_transactions(&self, ids: &[Uuid]) {
+        let valuation = TransactionValuationService::new(self.pool.clone());
+        for id in ids {
+            if let Err(e) = valuation
+                .value_transaction(*id, &self.config.rate_resolver)
+                .await
+            {
+                tracing::warn!("Failed to value synced transaction {}: {}", id, e);
+            }
+        }
+    }
+
+    pub async fn list_connections(
+        &self,
+        ledger_ids: &[Uuid],
+    ) -> Result<Vec<BankConnection>, ServiceError> {
+        Ok(sqlx::query_as::<_, BankConnection>(&format!(
+            "{} WHERE ledger_id = ANY($1) ORDER BY created_at",
+            CONNECTION_SELECT
+        ))
+        .bind(ledger_ids)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_connection(&self, id: Uuid) -> Result<BankConnection, ServiceError> {
+        sqlx::query_as::<_, BankConnection>(&format!("{} WHERE id = $1", CONNECTION_SELECT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("bank_connection", id))
+    }
+
+    pub async fn get_connection_detail(
+        &self,
+        id: Uuid,
+    ) -> Result<BankConnectionDetail, ServiceError> {
+        let connection = self.get_connection(id).await?;
+        let accounts = sqlx::query_as::<_, BankConnectionAccount>(
+            r#"
+            SELECT id, connection_id, external_account_id, account_id, name, mask, account_type,
+                   account_subtype, currency, current_balance, available_balance
+            FROM bank_connection_accounts
+            WHERE connection_id = $1
+            ORDER BY name
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(BankConnectionDetail {
+            connection,
+            accounts,
+        })
+    }
+
+    async fn secret(&self, id: Uuid) -> Result<ConnectionSecret, ServiceError> {
+        sqlx::query_as::<_, ConnectionSecret>(
+            r#"
+            SELECT id, family_id, ledger_id, provider, institution_name, access_token,
+                   sync_cursor, created_by
+            FROM bank_connections WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("bank_connection", id))
+    }
+
+    /// 新建连接的 Link token
+    pub async fn create_link_token(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+    ) -> Result<LinkToken, ServiceError> {
+        let connector = self.registry.get(provider)?;
+        Ok(connector
+            .create_link_token(&LinkTokenRequest {
+                user_id,
+                access_token: None,
+                webhook_url: self.webhook_url(provider),
+            })
+            .await?)
+    }
+
+    /// 重新授权（Link 更新模式）的 token；完成后调用同步即可恢复连接
+    pub async fn create_reauth_token(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<LinkToken, ServiceError> {
+        let conn = self.secret(id).await?;
+        let connector = self.registry.get(&conn.provider)?;
+        Ok(connector
+            .create_link_token(&LinkTokenRequest {
+                user_id,
+                access_token: Some(conn.access_token),
+                webhook_url: self.webhook_url(&conn.provider),
+            })
+            .await?)
+    }
+
+    /// 交换 public token，保存连接与机构账户，并立即做一次同步
+    pub async fn connect(
+        &self,
+        user_id: Uuid,
+        req: CreateConnectionRequest,
+    ) -> Result<BankConnectionDetail, ServiceError> {
+        let connector = self.registry.get(&req.provider)?;
+        let family_id: Uuid = sqlx::query_scalar("SELECT family_id FROM ledgers WHERE id = $1")
+            .bind(req.ledger_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("ledger", req.ledger_id))?;
+
+        let credentials = connector.exchange_public_token(&req.public_token).await?;
+        let accounts = connector.accounts(&credentials.access_token).await?;
+
+        let mut tx = self.pool.begin().await?;
+        // 同一机构连接重复授权时更新凭据，保留游标与账户映射
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO bank_connections (
+                family_id, ledger_id, provider, institution_id, institution_name,
+                external_item_id, access_token, created_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (provider, external_item_id) DO UPDATE
+            SET access_token = EXCLUDED.access_token, status = 'active', last_error = NULL,
+                updated_at = NOW()
+            RETURNING id
+            "#,
+        )
+        .bind(family_id)
+        .bind(req.ledger_id)
+        .bind(&req.provider)
+        .bind(&credentials.institution_id)
+        .bind(&credentials.institution_name)
+        .bind(&credentials.item_id)
+        .bind(&credentials.access_token)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let import = req.import_accounts.unwrap_or(true);
+        for account in &accounts {
+            let linked: Option<Option<Uuid>> = sqlx::query_scalar(
+                "SELECT account_id FROM bank_connection_accounts WHERE connection_id = $1 AND external_account_id = $2",
+            )
+            .bind(id)
+            .bind(&account.account_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let account_id = match linked {
+                Some(existing) => existing,
+                None if import => Some(
+                    create_jive_account(
+                        &mut tx,
+                        req.ledger_id,
+                        credentials.institution_name.as_deref(),
+                        account,
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+            upsert_connection_account(&mut tx, id, account, account_id).await?;
+        }
+        tx.commit().await?;
+
+        if let Err(e) = self.sync_connection(id).await {
+            tracing::warn!("Initial sync of bank connection {} failed: {}", id, e);
+        }
+        self.get_connection_detail(id).await
+    }
+
+    /// 设置机构账户对应的 Jive 账户；映射变化后重置游标，下次同步重新导入完整历史
+    pub async fn link_account(
+        &self,
+        connection_id: Uuid,
+        connection_account_id: Uuid,
+        account_id: Option<Uuid>,
+    ) -> Result<BankConnectionDetail, ServiceError> {
+        let conn = self.get_connection(connection_id).await?;
+        if let Some(account_id) = account_id {
+            let ledger_id: Option<Uuid> =
+                sqlx::query_scalar("SELECT ledger_id FROM accounts WHERE id = $1")
+                    .bind(account_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            if ledger_id != Some(conn.ledger_id) {
+                return Err(ServiceError::validation(
+                    "Account must belong to the connection's ledger",
+                ));
+            }
+        }
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE bank_connection_accounts SET account_id = $3, updated_at = NOW()
+            WHERE id = $2 AND connection_id = $1
+            "#,
+        )
+        .bind(connection_id)
+        .bind(connection_account_id)
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(ServiceError::not_found(
+                "bank_connection_account",
+                connection_account_id,
+            ));
+        }
+        sqlx::query(
+            "UPDATE bank_connections SET sync_cursor = NULL, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(connection_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        self.get_connection_detail(connection_id).await
+    }
+
+    /// 撤销机构侧授权并删除连接；已导入的账户与交易保留
+    pub async fn disconnect(&self, id: Uuid) -> Result<(), ServiceError> {
+        let conn = self.secret(id).await?;
+        match self.registry.get(&conn.provider) {
+            Ok(connector) => {
+                if let Err(e) = connector.remove_item(&conn.access_token).await {
+                    tracing::warn!("Failed to remove item of bank connection {}: {}", id, e);
+                }
+            }
+            Err(e) => tracing::warn!("Bank connection {}: {}", id, e),
+        }
+        sqlx::query("DELETE FROM bank_connections WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 增量同步；成功后连接恢复为 active，需要重新授权时标记并通知创建者
+    pub async fn sync_connection(&self, id: Uuid) -> Result<SyncSummary, ServiceError> {
+        let conn = self.secret(id).await?;
+        let connector = self.registry.get(&conn.provider)?;
+
+        let mut restarts = 0;
+        let result = loop {
+            match self.pull(&conn, connector.as_ref()).await {
+                Err(SyncFailure::Connector(ConnectorError::MutationDuringPagination))
+                    if restarts < MAX_SYNC_RESTARTS =>
+                {
+                    restarts += 1;
+                }
+                other => break other,
+            }
+        };
+
+        match result {
+            Ok(summary) => {
+                if let Err(e) = self.refresh_balances(&conn, connector.as_ref()).await {
+                    tracing::warn!(
+                        "Failed to refresh balances of bank connection {}: {}",
+                        id,
+                        e
+                    );
+                }
+                if summary.has_changes() {
+                    WebhookService::new(self.pool.clone())
+                        .emit(
+                            conn.family_id,
+                            WebhookEventType::ImportCompleted,
+                            serde_json::json!({
+                                "source": "bank_connection",
+                                "connection_id": conn.id,
+                                "ledger_id": conn.ledger_id,
+                                "provider": conn.provider,
+                                "institution_name": conn.institution_name,
+                                "summary": &summary,
+                            }),
+                        )
+                        .await;
+                }
+                Ok(summary)
+            }
+            Err(SyncFailure::Connector(ConnectorError::ReauthRequired(msg))) => {
+                self.mark_reauth_required(&conn, &msg).await?;
+                Err(ConnectorError::ReauthRequired(msg).into())
+            }
+            Err(SyncFailure::Connector(e)) => {
+                self.mark_error(id, &e.to_string()).await?;
+                Err(e.into())
+            }
+            Err(SyncFailure::Service(e)) => {
+                self.mark_error(id, &e.to_string()).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// 从保存的游标拉取到最新，全部成功后保存新游标
+    async fn pull(
+        &self,
+        conn: &ConnectionSecret,
+        connector: &dyn BankConnector,
+    ) -> Result<SyncSummary, SyncFailure> {
+        let linked = self.linked_accounts(conn.id).await?;
+        let mut summary = SyncSummary::default();
+        let mut cursor = conn.sync_cursor.clone();
+        loop {
+            let page = connector
+                .sync_transactions(&conn.access_token, cursor.as_deref())
+                .await
+                .map_err(SyncFailure::Connector)?;
+            let touched = self.apply_page(conn, &linked, &page, &mut summary).await?;
+            self.value_transactions(&touched).await;
+            cursor = Some(page.next_cursor);
+            if !page.has_more {
+                break;
+            }
+        }
+        sqlx::query(
+            r#"
+            UPDATE bank_connections
+            SET sync_cursor = $2, last_synced_at = NOW(), status = 'active', last_error = NULL,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(conn.id)
+        .bind(cursor)
+        .execute(&self.pool)
+        .await
+        .map_err(ServiceError::from)?;
+        Ok(summary)
+    }
+
+    async fn linked_accounts(
+        &self,
+        connection_id: Uuid,
+    ) -> Result<HashMap<String, LinkedAccount>, ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT ca.external_account_id, a.id AS account_id, a.currency
+            FROM bank_connection_accounts ca
+            JOIN accounts a ON a.id = ca.account_id
+            WHERE ca.connection_id = $1
+            "#,
+        )
+        .bind(connection_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.get::<String, _>("external_account_id"),
+                    LinkedAccount {
+                        account_id: row.get("account_id"),
+                        currency: row
+                            .get::<Option<String>, _>("currency")
+                            .unwrap_or_else(|| "CNY".to_string()),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// 在一个事务内写入一页变更，返回需要重新计算本位币金额的交易
+    async fn apply_page(
+        &self,
+        conn: &ConnectionSecret,
+        linked: &HashMap<String, LinkedAccount>,
+        page: &SyncPage,
+        summary: &mut SyncSummary,
+    ) -> Result<Vec<Uuid>, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let mut touched = Vec::new();
+        for (incoming, modified) in page
+            .added
+            .iter()
+            .map(|t| (t, false))
+            .chain(page.modified.iter().map(|t| (t, true)))
+        {
+            match apply_transaction(&mut tx, conn, linked, incoming).await? {
+                Applied::Inserted(id) => {
+                    summary.added += 1;
+                    touched.push(id);
+                }
+                Applied::Updated(id) => {
+                    if modified {
+                        summary.modified += 1;
+                    }
+                    touched.push(id);
+                }
+                Applied::Reconciled(id) => {
+                    summary.reconciled += 1;
+                    touched.push(id);
+                }
+                Applied::Skipped => summary.skipped += 1,
+            }
+        }
+        summary.removed += remove_transactions(&mut tx, conn.id, &page.removed).await?;
+        tx.commit().await?;
+        Ok(touched)
+    }
+
+    /// 以机构余额刷新机构账户与已导入的 Jive 账户
+    async fn refresh_balances(
+        &self,
+        conn: &ConnectionSecret,
+        connector: &dyn BankConnector,
+    ) -> Result<(), ServiceError> {
+        let accounts = connector.accounts(&conn.access_token).await?;
+        let mut tx = self.pool.begin().await?;
+        for account in &accounts {
+            let linked: Option<Option<Uuid>> = sqlx::query_scalar(
+                "SELECT account_id FROM bank_connection_accounts WHERE connection_id = $1 AND external_account_id = $2",
+            )
+            .bind(conn.id)
+            .bind(&account.account_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            // 机构新增的账户只登记，不自动导入
+            let account_id = linked.flatten();
+            upsert_connection_account(&mut tx, conn.id, account, account_id).await?;
+            if let (Some(account_id), Some(balance)) = (account_id, account.current_balance) {
+                sqlx::query(
+                    r#"
+                    UPDATE accounts
+                    SET current_balance = CASE WHEN account_main_type = 'liability'
+                                               THEN -ABS($2) ELSE $2 END,
+                        last_synced_at = NOW(), sync_error = NULL, updated_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(account_id)
+                .bind(balance)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn mark_error(&self, id: Uuid, message: &str) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE bank_connections SET status = 'error', last_error = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .bind(message)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_reauth_required(
+        &self,
+        conn: &ConnectionSecret,
+        message: &str,
+    ) -> Result<(), ServiceError> {
+        let previous: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE bank_connections b
+            SET status = 'reauth_required', last_error = $2, updated_at = NOW()
+            FROM (SELECT status FROM bank_connections WHERE id = $1) old
+            WHERE b.id = $1
+            RETURNING old.status
+            "#,
+        )
+        .bind(conn.id)
+        .bind(message)
+        .fetch_optional(&self.pool)
+        .await?;
+        // 只在状态变化时通知一次
+        if previous.as_deref() != Some("reauth_required") {
+            self.notify(
+                conn,
+                "bank_reauth_required",
+                "银行连接需要重新授权",
+                format!(
+                    "{} 的登录信息已失效，请重新授权后继续同步",
+                    conn.institution_name.as_deref().unwrap_or("银行")
+                ),
+            )
+            .await;
+        }
+        Ok(())
+    }
+
+    async fn notify(&self, conn: &ConnectionSecret, kind: &str, title: &str, body: String) {
+        let notifier = NotificationService::new(
+            self.pool.clone(),
+            &self.config.notification,
+            self.ws.clone(),
+        );
+        let result = notifier
+            .notify(NewNotification {
+                user_id: conn.created_by,
+                family_id: Some(conn.family_id),
+                kind: kind.to_string(),
+                // 连接失效会中断同步，需要用户尽快处理
+                priority: NotificationPriority::High,
+                title: title.to_string(),
+                body,
+                data: serde_json::json!({
+                    "connection_id": conn.id,
+                    "ledger_id": conn.ledger_id,
+                }),
+                action_url: None,
+            })
+            .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to notify bank connection {}: {}", conn.id, e);
+        }
+    }
+
+    /// 验签、记录并处理 webhook；增量同步在后台执行，尽快响应机构
+    pub async fn handle_webhook(
+        &self,
+        provider: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<(), ServiceError> {
+        let connector = self.registry.get(provider)?;
+        connector.verify_webhook(headers, body).await?;
+        let event = connector.parse_webhook(body)?;
+        let payload: serde_json::Value = serde_json::from_slice(body)?;
+        let field = |name: &str| {
+            payload
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let connection_id: Option<Uuid> =
+            match event.item_id() {
+                Some(item_id) => sqlx::query_scalar(
+                    "SELECT id FROM bank_connections WHERE provider = $1 AND external_item_id = $2",
+                )
+                .bind(provider)
+                .bind(item_id)
+                .fetch_optional(&self.pool)
+                .await?,
+                None => None,
+            };
+        let event_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO bank_webhook_events (provider, connection_id, webhook_type, webhook_code, payload)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(provider)
+        .bind(connection_id)
+        .bind(field("webhook_type"))
+        .bind(field("webhook_code"))
+        .bind(&payload)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let result = match connection_id {
+            Some(id) => self.process_event(id, event).await,
+            None => Ok(()),
+        };
+        sqlx::query(
+            "UPDATE bank_webhook_events SET processed_at = NOW(), error = $2 WHERE id = $1",
+        )
+        .bind(event_id)
+        .bind(result.as_ref().err().map(|e| e.to_string()))
+        .execute(&self.pool)
+        .await?;
+        result
+    }
+
+    async fn process_event(&self, id: Uuid, event: WebhookEvent) -> Result<(), ServiceError> {
+        match event {
+            WebhookEvent::SyncAvailable { .. } => {
+                let service = self.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = service.sync_connection(id).await {
+                        tracing::warn!("Webhook sync of bank connection {} failed: {}", id, e);
+                    }
+                });
+            }
+            WebhookEvent::TransactionsRemoved {
+                transaction_ids, ..
+            } => {
+                let mut tx = self.pool.begin().await?;
+                remove_transactions(&mut tx, id, &transaction_ids).await?;
+                tx.commit().await?;
+            }
+            WebhookEvent::PendingExpiration {
+                consent_expires_at, ..
+            } => {
+                let conn = self.secret(id).await?;
+                sqlx::query(
+                    "UPDATE bank_connections SET consent_expires_at = $2, updated_at = NOW() WHERE id = $1",
+                )
+                .bind(id)
+                .bind(consent_expires_at)
+                .execute(&self.pool)
+                .await?;
+                let when = consent_expires_at
+                    .map(|t| format!("将于 {} 过期", t.format("%Y-%m-%d")))
+                    .unwrap_or_else(|| "即将过期".to_string());
+                self.notify(
+                    &conn,
+                    "bank_consent_expiring",
+                    "银行连接授权即将过期",
+                    format!(
+                        "{} 的授权{}，请及时重新授权",
+                        conn.institution_name.as_deref().unwrap_or("银行"),
+                        when
+                    ),
+                )
+                .await;
+            }
+            WebhookEvent::LoginRequired { .. } => {
+                let conn = self.secret(id).await?;
+                self.mark_reauth_required(&conn, "ITEM_LOGIN_REQUIRED")
+                    .await?;
+            }
+            WebhookEvent::Other { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+enum SyncFailure {
+    Connector(ConnectorError),
+    Service(ServiceError),
+}
+
+impl From<ServiceError> for SyncFailure {
+    fn from(e: ServiceError) -> Self {
+        SyncFailure::Service(e)
+    }
+}
+
+/// 机构符号约定 -> (transaction_type, 金额绝对值)
+fn split_amount(amount: Decimal) -> (&'static str, Decimal) {
+    if amount.is_sign_negative() {
+        ("income", -amount)
+    } else {
+        ("expense", amount)
+    }
+}
+
+async fn apply_transaction(
+    tx: &mut Transaction<'_, Postgres>,
+    conn: &ConnectionSecret,
+    linked: &HashMap<String, LinkedAccount>,
+    incoming: &ExternalTransaction,
+) -> Result<Applied, ServiceError> {
+    let Some(account) = linked.get(&incoming.account_id) else {
+        return Ok(Applied::Skipped);
+    };
+
+    // 已同步过：按最新内容更新（被机构删除后又出现时恢复）
+    let existing: Option<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM transactions WHERE bank_connection_id = $1 AND external_id = $2",
+    )
+    .bind(conn.id)
+    .bind(&incoming.transaction_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if let Some(id) = existing {
+        update_synced(tx, id, account, incoming).await?;
+        return Ok(Applied::Updated(id));
+    }
+
+    if !incoming.pending {
+        if let Some(id) = find_pending(tx, conn.id, account.account_id, incoming).await? {
+            update_synced(tx, id, account, incoming).await?;
+            return Ok(Applied::Reconciled(id));
+        }
+    }
+
+    let (transaction_type, amount) = split_amount(incoming.amount);
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (
+            id, ledger_id, account_id, transaction_date, amount, transaction_type, currency,
+            description, merchant, payee, status, is_manual, bank_connection_id, external_id,
+            created_by, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, $10, false, $11, $12, $13, NOW(), NOW())
+        "#,
+    )
+    .bind(id)
+    .bind(conn.ledger_id)
+    .bind(account.account_id)
+    .bind(incoming.date)
+    .bind(amount)
+    .bind(transaction_type)
+    .bind(incoming.currency.as_deref().unwrap_or(&account.currency))
+    .bind(&incoming.name)
+    .bind(&incoming.merchant_name)
+    .bind(if incoming.pending {
+        "pending"
+    } else {
+        "completed"
+    })
+    .bind(conn.id)
+    .bind(&incoming.transaction_id)
+    .bind(conn.created_by)
+    .execute(&mut **tx)
+    .await?;
+    Ok(Applied::Inserted(id))
+}
+
+/// 入账交易对应的本地待入账交易
+async fn find_pending(
+    tx: &mut Transaction<'_, Postgres>,
+    connection_id: Uuid,
+    account_id: Uuid,
+    posted: &ExternalTransaction,
+) -> Result<Option<Uuid>, ServiceError> {
+    if let Some(pending_id) = &posted.pending_transaction_id {
+        // 待入账交易可能已在更早的页中被删除，一并找回
+        let found: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM transactions
+            WHERE bank_connection_id = $1 AND external_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(connection_id)
+        .bind(pending_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, amount, transaction_type, transaction_date, COALESCE(merchant, description) AS name
+        FROM transactions
+        WHERE bank_connection_id = $1 AND account_id = $2 AND status = 'pending'
+          AND deleted_at IS NULL
+          AND transaction_date BETWEEN $3 - $4::int AND $3 + 1
+        "#,
+    )
+    .bind(connection_id)
+    .bind(account_id)
+    .bind(posted.date)
+    .bind(PENDING_MATCH_DAYS as i32)
+    .fetch_all(&mut **tx)
+    .await?;
+    let candidates: Vec<PendingCandidate> = rows
+        .into_iter()
+        .map(|row| {
+            let amount: Decimal = row.get("amount");
+            PendingCandidate {
+                id: row.get("id"),
+                amount: if row.get::<String, _>("transaction_type") == "income" {
+                    -amount
+                } else {
+                    amount
+                },
+                date: row.get("transaction_date"),
+                name: row.get::<Option<String>, _>("name").unwrap_or_default(),
+            }
+        })
+        .collect();
+    Ok(match_pending(&candidates, posted))
+}
+
+/// 用机构数据更新同步交易；分类、备注、标签等用户字段保持不变
+async fn update_synced(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    account: &LinkedAccount,
+    incoming: &ExternalTransaction,
+) -> Result<(), ServiceError> {
+    let (transaction_type, amount) = split_amount(incoming.amount);
+    sqlx::query(
+        r#"
+        UPDATE transactions
+        SET external_id = $2, account_id = $3, transaction_date = $4, amount = $5,
+            transaction_type = $6, currency = $7, merchant = COALESCE($8, merchant),
+            status = $9, deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&incoming.transaction_id)
+    .bind(account.account_id)
+    .bind(incoming.date)
+    .bind(amount)
+    .bind(transaction_type)
+    .bind(incoming.currency.as_deref().unwrap_or(&account.currency))
+    .bind(&incoming.merchant_name)
+    .bind(if incoming.pending {
+        "pending"
+    } else {
+        "completed"
+    })
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// 软删除机构删除的交易
+async fn remove_transactions(
+    tx: &mut Transaction<'_, Postgres>,
+    connection_id: Uuid,
+    external_ids: &[String],
+) -> Result<usize, ServiceError> {
+    if external_ids.is_empty() {
+        return Ok(0);
+    }
+    let result = sqlx::query(
+        r#"
+        UPDATE transactions SET deleted_at = NOW(), updated_at = NOW()
+        WHERE bank_connection_id = $1 AND external_id = ANY($2) AND deleted_at IS NULL
+        "#,
+    )
+    .bind(connection_id)
+    .bind(external_ids)
+    .execute(&mut **tx)
+    .await?;
+    Ok(result.rows_affected() as usize)
+}
+
+async fn create_jive_account(
+    tx: &mut Transaction<'_, Postgres>,
+    ledger_id: Uuid,
+    institution_name: Option<&str>,
+    account: &ExternalAccount,
+) -> Result<Uuid, ServiceError> {
+    let (main_type, sub_type) =
+        jive_account_type(&account.account_type, account.subtype.as_deref());
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO accounts (
+            id, ledger_id, name, account_type, account_main_type, account_sub_type,
+            account_number, institution_name, currency, current_balance, status, is_manual,
+            last_synced_at, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $4, $6, $7, $8, $9, 'active', false, NOW(), NOW(), NOW())
+        "#,
+    )
+    .bind(id)
+    .bind(ledger_id)
+    .bind(&account.name)
+    .bind(sub_type)
+    .bind(main_type)
+    .bind(&account.mask)
+    .bind(institution_name)
+    .bind(account.currency.as_deref().unwrap_or("CNY"))
+    .bind(signed_balance(
+        main_type,
+        account.current_balance.unwrap_or_default(),
+    ))
+    .execute(&mut **tx)
+    .await?;
+    Ok(id)
+}
+
+async fn upsert_connection_account(
+    tx: &mut Transaction<'_, Postgres>,
+    connection_id: Uuid,
+    account: &ExternalAccount,
+    account_id: Option<Uuid>,
+) -> Result<(), ServiceError> {
+    sqlx::query(
+        r#"
+        INSERT INTO bank_connection_accounts (
+            connection_id, external_account_id, account_id, name, mask, account_type,
+            account_subtype, currency, current_balance, available_balance
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (connection_id, external_account_id) DO UPDATE
+        SET account_id = EXCLUDED.account_id, name = EXCLUDED.name, mask = EXCLUDED.mask,
+            account_type = EXCLUDED.account_type, account_subtype = EXCLUDED.account_subtype,
+            currency = EXCLUDED.currency, current_balance = EXCLUDED.current_balance,
+            available_balance = EXCLUDED.available_balance, updated_at = NOW()
+        "#,
+    )
+    .bind(connection_id)
+    .bind(&account.account_id)
+    .bind(account_id)
+    .bind(&account.name)
+    .bind(&account.mask)
+    .bind(&account.account_type)
+    .bind(&account.subtype)
+    .bind(&account.currency)
+    .bind(account.current_balance)
+    .bind(account.available_balance)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn posted(amount: Decimal, date: NaiveDate, name: &str) -> ExternalTransaction {
+        ExternalTransaction {
+            transaction_id: "posted".to_string(),
+            account_id: "acc".to_string(),
+            amount,
+            currency: None,
+            date,
+            name: name.to_string(),
+            merchant_name: None,
+            category: None,
+            pending: false,
+            pending_transaction_id: None,
+        }
+    }
+
+    fn candidate(n: u128, amount: Decimal, date: NaiveDate, name: &str) -> PendingCandidate {
+        PendingCandidate {
+            id: Uuid::from_u128(n),
+            amount,
+            date,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_match_pending_priorities() {
+        let candidates = vec![
+            candidate(1, dec!(45.00), date(2026, 10, 10), "STARBUCKS #1234"),
+            candidate(2, dec!(45.00), date(2026, 10, 12), "Didi"),
+            candidate(3, dec!(30.00), date(2026, 10, 13), "Blue Bottle"),
+            candidate(4, dec!(-45.00), date(2026, 10, 13), "Refund"),
+        ];
+        // 金额与名称都相同优先于日期更近的同金额交易
+        assert_eq!(
+            match_pending(
+                &candidates,
+                &posted(dec!(45.00), date(2026, 10, 14), "Starbucks 5678")
+            ),
+            Some(Uuid::from_u128(1))
+        );
+        // 只有金额相同时取日期最近的
+        assert_eq!(
+            match_pending(
+                &candidates,
+                &posted(dec!(45.00), date(2026, 10, 14), "Unknown")
+            ),
+            Some(Uuid::from_u128(2))
+        );
+        // 小费导致金额变化：名称相同且差额不超过 20%
+        assert_eq!(
+            match_pending(
+                &candidates,
+                &posted(dec!(35.00), date(2026, 10, 14), "BLUE BOTTLE")
+            ),
+            Some(Uuid::from_u128(3))
+        );
+        assert_eq!(
+            match_pending(
+                &candidates,
+                &posted(dec!(60.00), date(2026, 10, 14), "Blue Bottle")
+            ),
+            None
+        );
+        // 方向不同或超出 7 天不匹配
+        assert_eq!(
+            match_pending(
+                &candidates,
+                &posted(dec!(-30.00), date(2026, 10, 14), "Blue Bottle")
+            ),
+            None
+        );
+        assert_eq!(
+            match_pending(
+                &candidates,
+                &posted(dec!(45.00), date(2026, 10, 25), "Didi")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_jive_account_type_mapping() {
+        assert_eq!(
+            jive_account_type("credit", Some("credit card")),
+            ("liability", "credit_card")
+        );
+        assert_eq!(
+            jive_account_type("loan", Some("mortgage")),
+            ("liability", "mortgage")
+        );
+        assert_eq!(
+            jive_account_type("depository", Some("savings")),
+            ("asset", "savings_account")
+        );
+        assert_eq!(jive_account_type("depository", None), ("asset", "checking"));
+        assert_eq!(signed_balance("liability", dec!(451.50)), dec!(-451.50));
+    }
+}
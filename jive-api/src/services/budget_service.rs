@@ -42,6 +42,8 @@ pub struct BudgetProgress {
     pub average_daily_spend: f64,
     pub projected_overspend: Option<f64>,
     pub categories: Vec<CategorySpending>,
+    /// 尚未折算为本位币、未计入 spent_amount 的交易数
+    pub unvalued_count: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -120,11 +122,13 @@ impl BudgetService {
         // 计算当前期间
         let (period_start, period_end) = self.get_current_period(&budget)?;
 
-        // 获取期间内的支出
-        let spent: (Option<f64>,) = sqlx::query_as(
+        // 获取期间内的支出（按月计入预算的分期只计当期分摊本金）；
+        // 未折算的交易币种不同，不能直接相加，单独计数
+        let spent: (Option<f64>, i64) = sqlx::query_as(
             r#"
-            SELECT SUM(amount) as total_spent
-            FROM transactions
+            SELECT SUM(base_amount) as total_spent,
+                   COUNT(*) FILTER (WHERE base_amount IS NULL) as unvalued_count
+            FROM budget_expenses
             WHERE ledger_id = $1
             AND transaction_type = 'expense'
             AND transaction_date BETWEEN $2 AND $3
@@ -184,6 +188,7 @@ impl BudgetService {
             average_daily_spend,
             projected_overspend,
             categories,
+            unvalued_count: spent.1,
         })
     }
 
@@ -200,10 +205,10 @@ impl BudgetService {
             SELECT 
                 c.id as category_id,
                 c.name as category_name,
-                COALESCE(SUM(t.amount), 0) as amount_spent,
+                COALESCE(SUM(t.base_amount), 0) as amount_spent,
                 COUNT(t.id) as transaction_count
             FROM categories c
-            LEFT JOIN transactions t ON t.category_id = c.id
+            LEFT JOIN budget_expenses t ON t.category_id = c.id
                 AND t.ledger_id = $1
                 AND t.transaction_type = 'expense'
                 AND t.transaction_date BETWEEN $2 AND $3
@@ -211,7 +216,7 @@ impl BudgetService {
             WHERE c.ledger_id = $1
             AND ($4::uuid IS NULL OR c.id = $4)
             GROUP BY c.id, c.name
-            HAVING SUM(t.amount) > 0
+            HAVING SUM(t.base_amount) > 0
             ORDER BY amount_spent DESC
             "#,
         )
@@ -356,11 +361,13 @@ impl BudgetService {
         let mut budget_summaries = Vec::new();
         let mut total_budgeted = 0.0;
         let mut total_spent = 0.0;
+        let mut unvalued_count = 0;
 
         for budget in budgets {
             let progress = self.get_budget_progress(budget.id).await?;
             total_budgeted += budget.amount;
             total_spent += progress.spent_amount;
+            unvalued_count += progress.unvalued_count;
 
             budget_summaries.push(BudgetSummary {
                 budget_name: budget.name,
@@ -374,8 +381,8 @@ impl BudgetService {
         // 获取无预算支出
         let unbudgeted_spending: (Option<f64>,) = sqlx::query_as(
             r#"
-            SELECT SUM(amount) 
-            FROM transactions
+            SELECT SUM(base_amount)
+            FROM budget_expenses
             WHERE ledger_id = $1
             AND transaction_type = 'expense'
             AND transaction_date BETWEEN $2 AND $3
@@ -405,6 +412,7 @@ impl BudgetService {
             overall_percentage: (total_spent / total_budgeted * 100.0).min(100.0),
             budget_summaries,
             unbudgeted_spending: unbudgeted_spending.0.unwrap_or(0.0),
+            unvalued_count,
             generated_at: Utc::now(),
         })
     }
@@ -510,6 +518,8 @@ pub struct BudgetReport {
     pub overall_percentage: f64,
     pub budget_summaries: Vec<BudgetSummary>,
     pub unbudgeted_spending: f64,
+    /// 各预算中尚未折算为本位币的交易数合计
+    pub unvalued_count: i64,
     pub generated_at: DateTime<Utc>,
 }
 
@@ -0,0 +1,335 @@
+//! 轻量级交易分类器：多项式朴素贝叶斯
+//!
+//! 特征由描述、收款方、金额区间与星期几组成。中文没有空格分词，连续的中日韩字符按
+//! 单字与相邻双字（字符 n-gram）切分，例如“星巴克咖啡”→ 星 / 巴 / 克 / 咖 / 啡 / 星巴 /
+//! 巴克 / 克咖 / 咖啡；其他文字按非字母数字字符切分为小写单词，纯数字（卡号、门店号）丢弃。
+//!
+//! 模型只保存计数，`learn` / `unlearn` 成对使用即可增量训练与撤销，序列化后整体存入数据库。
+
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// 拉普拉斯平滑系数
+const SMOOTHING: f64 = 1.0;
+/// 金额区间的上界（按绝对值）
+const AMOUNT_BUCKETS: [i64; 7] = [10, 30, 100, 300, 1_000, 3_000, 10_000];
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{4E00}'..='\u{9FFF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{AC00}'..='\u{D7AF}')
+}
+
+/// 把文本切分为词元：中日韩字符取单字与双字，其他按单词
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk: Vec<char> = Vec::new();
+
+    fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+        if word.chars().count() >= 2 && !word.chars().all(|c| c.is_ascii_digit()) {
+            tokens.push(std::mem::take(word));
+        }
+        word.clear();
+    }
+    fn flush_cjk(cjk: &mut Vec<char>, tokens: &mut Vec<String>) {
+        tokens.extend(cjk.iter().map(|c| c.to_string()));
+        tokens.extend(cjk.windows(2).map(|pair| pair.iter().collect::<String>()));
+        cjk.clear();
+    }
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(c);
+        } else if c.is_alphanumeric() {
+            flush_cjk(&mut cjk, &mut tokens);
+            word.extend(c.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+    tokens
+}
+
+/// 金额区间编号（0 = 小于 10，依次递增）
+pub fn amount_bucket(amount: Decimal) -> usize {
+    let amount = amount.abs().to_i64().unwrap_or(i64::MAX);
+    AMOUNT_BUCKETS
+        .iter()
+        .position(|bound| amount < *bound)
+        .unwrap_or(AMOUNT_BUCKETS.len())
+}
+
+/// 提取特征所需的交易字段
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureInput<'a> {
+    pub description: Option<&'a str>,
+    pub payee: Option<&'a str>,
+    pub amount: Option<Decimal>,
+    pub date: Option<NaiveDate>,
+}
+
+/// 交易特征（去重、有序）：`d:` 描述词元、`p:` 收款方词元、`payee=` 完整收款方、
+/// `amt:` 金额区间、`wd:` 星期几
+pub fn extract_features(input: &FeatureInput) -> Vec<String> {
+    let mut features = BTreeSet::new();
+    if let Some(description) = input.description {
+        features.extend(
+            tokenize(description)
+                .into_iter()
+                .map(|t| format!("d:{}", t)),
+        );
+    }
+    if let Some(payee) = input.payee.map(str::trim).filter(|p| !p.is_empty()) {
+        features.extend(tokenize(payee).into_iter().map(|t| format!("p:{}", t)));
+        features.insert(format!("payee={}", payee.to_lowercase()));
+    }
+    if let Some(amount) = input.amount {
+        features.insert(format!("amt:{}", amount_bucket(amount)));
+    }
+    if let Some(date) = input.date {
+        features.insert(format!("wd:{}", date.weekday().num_days_from_monday()));
+    }
+    features.into_iter().collect()
+}
+
+/// 收款方预测使用的特征：去掉收款方本身
+pub fn payee_features(features: &[String]) -> Vec<String> {
+    features
+        .iter()
+        .filter(|f| !f.starts_with("p:") && !f.starts_with("payee="))
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct ClassCounts {
+    documents: u32,
+    /// 该类所有特征出现次数之和
+    total: u32,
+    features: HashMap<String, u32>,
+}
+
+/// 一条预测结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prediction {
+    pub label: String,
+    /// 在候选类别中归一化后的后验概率
+    pub confidence: f64,
+}
+
+/// 多项式朴素贝叶斯模型（类别标签为字符串）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NaiveBayes {
+    #[serde(default)]
+    classes: HashMap<String, ClassCounts>,
+    /// 特征 → 全部类别中的出现次数
+    #[serde(default)]
+    vocabulary: HashMap<String, u32>,
+    #[serde(default)]
+    documents: u32,
+}
+
+impl NaiveBayes {
+    /// 已学习的样本数
+    pub fn documents(&self) -> u32 {
+        self.documents
+    }
+
+    pub fn labels(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn learn(&mut self, label: &str, features: &[String]) {
+        let class = self.classes.entry(label.to_string()).or_default();
+        class.documents += 1;
+        for feature in features {
+            *class.features.entry(feature.clone()).or_default() += 1;
+            *self.vocabulary.entry(feature.clone()).or_default() += 1;
+            class.total += 1;
+        }
+        self.documents += 1;
+    }
+
+    /// 撤销一次 `learn`（用户修改或删除了已学习的交易）
+    pub fn unlearn(&mut self, label: &str, features: &[String]) {
+        let Some(class) = self.classes.get_mut(label) else {
+            return;
+        };
+        for feature in features {
+            if let Some(count) = class.features.get_mut(feature) {
+                *count -= 1;
+                class.total -= 1;
+                if *count == 0 {
+                    class.features.remove(feature);
+                }
+                if let Some(seen) = self.vocabulary.get_mut(feature) {
+                    *seen -= 1;
+                    if *seen == 0 {
+                        self.vocabulary.remove(feature);
+                    }
+                }
+            }
+        }
+        class.documents = class.documents.saturating_sub(1);
+        if class.documents == 0 {
+            self.classes.remove(label);
+        }
+        self.documents = self.documents.saturating_sub(1);
+    }
+
+    /// 按后验概率返回前 `limit` 个类别；`candidates` 限定可选类别。
+    /// 没有任何已知特征时不做预测（只剩先验，没有参考价值）。
+    pub fn predict(
+        &self,
+        features: &[String],
+        candidates: Option<&HashSet<String>>,
+        limit: usize,
+    ) -> Vec<Prediction> {
+        let known: Vec<&String> = features
+            .iter()
+            .filter(|f| self.vocabulary.contains_key(*f))
+            .collect();
+        if known.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let vocabulary = self.vocabulary.len() as f64;
+        let class_count = self.classes.len() as f64;
+        let mut scores: Vec<(&String, f64)> = self
+            .classes
+            .iter()
+            .filter(|(label, _)| candidates.is_none_or(|c| c.contains(*label)))
+            .map(|(label, class)| {
+                let prior = ((class.documents as f64 + SMOOTHING)
+                    / (self.documents as f64 + SMOOTHING * class_count))
+                    .ln();
+                let denominator = class.total as f64 + SMOOTHING * vocabulary;
+                let likelihood: f64 = known
+                    .iter()
+                    .map(|f| {
+                        let count = class.features.get(*f).copied().unwrap_or(0) as f64;
+                        ((count + SMOOTHING) / denominator).ln()
+                    })
+                    .sum();
+                (label, prior + likelihood)
+            })
+            .collect();
+        if scores.is_empty() {
+            return Vec::new();
+        }
+
+        // softmax（减去最大值避免下溢）
+        let max = scores
+            .iter()
+            .map(|(_, s)| *s)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        scores
+            .into_iter()
+            .take(limit)
+            .map(|(label, score)| Prediction {
+                label: label.clone(),
+                confidence: (score - max).exp() / total,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(description: &str, payee: Option<&str>, amount: i64) -> Vec<String> {
+        extract_features(&FeatureInput {
+            description: Some(description),
+            payee,
+            amount: Some(Decimal::from(amount)),
+            date: NaiveDate::from_ymd_opt(2026, 10, 17),
+        })
+    }
+
+    #[test]
+    fn test_tokenize_mixed_text() {
+        assert_eq!(
+            tokenize("星巴克咖啡 POS#12345 Latte"),
+            vec![
+                "星", "巴", "克", "咖", "啡", "星巴", "巴克", "克咖", "咖啡", "pos", "latte"
+            ]
+        );
+        assert_eq!(
+            tokenize("滴滴出行-快车"),
+            vec!["滴", "滴", "出", "行", "滴滴", "滴出", "出行", "快", "车", "快车"]
+        );
+        assert!(tokenize("123 4 a").is_empty());
+
+        assert_eq!(amount_bucket(Decimal::new(-3550, 2)), 2);
+        assert_eq!(amount_bucket(Decimal::from(25_000)), 7);
+        // 2026-10-17 是星期六
+        assert!(features("x", None, 5).contains(&"wd:5".to_string()));
+    }
+
+    #[test]
+    fn test_naive_bayes_learns_and_ranks() {
+        let mut model = NaiveBayes::default();
+        for (description, payee, amount, label) in [
+            ("星巴克咖啡", Some("星巴克"), 35, "food"),
+            ("瑞幸咖啡 拿铁", Some("瑞幸"), 18, "food"),
+            ("午餐 外卖", Some("美团"), 45, "food"),
+            ("滴滴快车", Some("滴滴出行"), 28, "transport"),
+            ("地铁充值", None, 100, "transport"),
+            ("Uber trip", Some("Uber"), 60, "transport"),
+        ] {
+            model.learn(label, &features(description, payee, amount));
+        }
+        assert_eq!(model.documents(), 6);
+
+        let ranked = model.predict(&features("Manner 咖啡", None, 25), None, 3);
+        assert_eq!(ranked[0].label, "food");
+        assert!(ranked[0].confidence > 0.5);
+        let sum: f64 = ranked.iter().map(|p| p.confidence).sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+
+        let ranked = model.predict(&features("滴滴出行 打车", None, 30), None, 1);
+        assert_eq!(ranked[0].label, "transport");
+
+        // 候选限定、完全陌生的输入
+        let only_transport: HashSet<String> = ["transport".to_string()].into();
+        let ranked = model.predict(&features("星巴克", None, 35), Some(&only_transport), 3);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].label, "transport");
+        assert!(model.predict(&["d:zzz".to_string()], None, 3).is_empty());
+    }
+
+    #[test]
+    fn test_unlearn_reverts_learn_and_model_roundtrips() {
+        let mut model = NaiveBayes::default();
+        model.learn("food", &features("星巴克咖啡", Some("星巴克"), 35));
+        let snapshot = model.clone();
+
+        let corrected = features("公司团建", Some("海底捞"), 800);
+        model.learn("food", &corrected);
+        model.learn("social", &corrected);
+        model.unlearn("social", &corrected);
+        model.unlearn("food", &corrected);
+        assert_eq!(model, snapshot);
+
+        let json = serde_json::to_value(&model).unwrap();
+        let restored: NaiveBayes = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, model);
+        // 空对象（新建家庭的初始值）可以直接反序列化
+        let empty: NaiveBayes = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty.documents(), 0);
+    }
+}
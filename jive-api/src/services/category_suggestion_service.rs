@@ -0,0 +1,776 @@
+//! 离线分类建议：按家庭训练的朴素贝叶斯模型（见 `category_classifier`）
+//!
+//! 训练数据只来自用户确认的分类（`transactions.category_source IS NULL`），规则、大模型与
+//! 本模型自动写入的分类不参与训练，避免模型强化自己的错误。训练是增量的：每次只扫描
+//! 上次水位线之后更新过的交易，与 `category_model_examples` 中记录的特征比对，修改或删除
+//! 过的交易先撤销旧样本再学习新样本；模型序列化后存入 `category_models`。
+//!
+//! 查询建议前若模型超过 `CLASSIFIER_RETRAIN_SECS` 未训练，先做一次增量训练，因此不需要
+//! 单独的定时任务。
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::{HashMap, HashSet};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::ai_service::{
+    ledger_categories, uncategorized_transactions, AiCategorization, CategorizeRequest,
+    CategorizeSummary, CategoryForAi,
+};
+use super::category_classifier::{
+    extract_features, payee_features, FeatureInput, NaiveBayes, Prediction,
+};
+use super::ServiceError;
+use crate::config::ClassifierConfig;
+
+/// 分类建议来源
+pub const SOURCE_ML: &str = "ml";
+/// 分类记录中的提供方与模型名
+const PROVIDER_LOCAL: &str = "local";
+const MODEL_NAIVE_BAYES: &str = "naive_bayes";
+/// 增量训练每页扫描的交易数
+const TRAIN_PAGE_SIZE: i64 = 500;
+/// 水位线回退（秒）：覆盖在上次训练期间才提交的更新
+const WATERMARK_OVERLAP_SECS: i64 = 600;
+/// 单次最多返回的建议数
+const MAX_SUGGESTIONS: usize = 10;
+const DEFAULT_SUGGESTIONS: usize = 5;
+
+/// 分类建议查询（新建交易时随输入实时调用）
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SuggestionQuery {
+    pub ledger_id: Uuid,
+    /// expense / income，默认 expense
+    pub transaction_type: Option<String>,
+    pub description: Option<String>,
+    pub payee: Option<String>,
+    pub amount: Option<Decimal>,
+    /// 默认今天
+    pub transaction_date: Option<NaiveDate>,
+    /// 返回条数（默认 5，最多 10）
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct CategorySuggestion {
+    pub category_id: Uuid,
+    pub name: String,
+    pub parent_name: Option<String>,
+    /// 0 - 1
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PayeeSuggestion {
+    pub payee: String,
+    /// 0 - 1
+    pub confidence: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SuggestionResponse {
+    /// 按置信度降序
+    pub categories: Vec<CategorySuggestion>,
+    /// 仅在未填写收款方时返回
+    pub payees: Vec<PayeeSuggestion>,
+    /// 模型已学习的样本数
+    pub example_count: i32,
+    /// 样本数是否达到自动分类的门槛
+    pub model_ready: bool,
+}
+
+/// 家庭模型状态
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ModelStatus {
+    pub family_id: Uuid,
+    pub example_count: i32,
+    /// 模型中出现过的分类数
+    pub category_count: usize,
+    /// 模型中出现过的收款方数
+    pub payee_count: usize,
+    pub model_ready: bool,
+    pub trained_until: Option<DateTime<Utc>>,
+    pub trained_at: Option<DateTime<Utc>>,
+}
+
+/// 一次训练的结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TrainReport {
+    /// 本次扫描的交易数
+    pub scanned: usize,
+    /// 新学习的样本数
+    pub learned: usize,
+    /// 撤销的样本数（分类被修改、交易被删除或不再符合条件）
+    pub unlearned: usize,
+    pub model: ModelStatus,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TrainQuery {
+    /// 丢弃现有模型，从全部历史重新训练
+    pub full: Option<bool>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ModelRow {
+    category_model: Json<NaiveBayes>,
+    payee_model: Json<NaiveBayes>,
+    trained_until: Option<DateTime<Utc>>,
+    trained_at: Option<DateTime<Utc>>,
+}
+
+/// 家庭的两个模型：交易 → 分类，交易（不含收款方）→ 收款方
+struct FamilyModels {
+    family_id: Uuid,
+    category: NaiveBayes,
+    payee: NaiveBayes,
+    trained_until: Option<DateTime<Utc>>,
+    trained_at: Option<DateTime<Utc>>,
+}
+
+impl FamilyModels {
+    fn empty(family_id: Uuid) -> Self {
+        Self {
+            family_id,
+            category: NaiveBayes::default(),
+            payee: NaiveBayes::default(),
+            trained_until: None,
+            trained_at: None,
+        }
+    }
+
+    fn from_row(family_id: Uuid, row: ModelRow) -> Self {
+        Self {
+            family_id,
+            category: row.category_model.0,
+            payee: row.payee_model.0,
+            trained_until: row.trained_until,
+            trained_at: row.trained_at,
+        }
+    }
+
+    fn example_count(&self) -> i32 {
+        self.category.documents() as i32
+    }
+
+    fn is_ready(&self, config: &ClassifierConfig) -> bool {
+        self.category.documents() >= config.min_examples.max(1)
+    }
+
+    fn status(&self, config: &ClassifierConfig) -> ModelStatus {
+        ModelStatus {
+            family_id: self.family_id,
+            example_count: self.example_count(),
+            category_count: self.category.labels(),
+            payee_count: self.payee.labels(),
+            model_ready: self.is_ready(config),
+            trained_until: self.trained_until,
+            trained_at: self.trained_at,
+        }
+    }
+
+    fn learn(&mut self, example: &Example) {
+        self.category
+            .learn(&example.category_id.to_string(), &example.features);
+        if let Some(payee) = &example.payee {
+            self.payee.learn(payee, &payee_features(&example.features));
+        }
+    }
+
+    fn unlearn(&mut self, example: &Example) {
+        self.category
+            .unlearn(&example.category_id.to_string(), &example.features);
+        if let Some(payee) = &example.payee {
+            self.payee
+                .unlearn(payee, &payee_features(&example.features));
+        }
+    }
+}
+
+/// 已学习的样本（与 `category_model_examples` 一一对应）
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+struct Example {
+    transaction_id: Uuid,
+    category_id: Uuid,
+    payee: Option<String>,
+    features: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TrainingRow {
+    id: Uuid,
+    transaction_type: String,
+    amount: Decimal,
+    transaction_date: NaiveDate,
+    description: Option<String>,
+    payee: Option<String>,
+    category_id: Option<Uuid>,
+    category_source: Option<String>,
+    deleted_at: Option<DateTime<Utc>>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TrainingRow {
+    /// 交易当前应对应的样本；不符合训练条件时为 None
+    fn example(&self) -> Option<Example> {
+        if self.deleted_at.is_some()
+            || self.category_source.is_some()
+            || !matches!(self.transaction_type.as_str(), "expense" | "income")
+        {
+            return None;
+        }
+        let category_id = self.category_id?;
+        let payee = self
+            .payee
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty());
+        let features = extract_features(&FeatureInput {
+            description: self.description.as_deref(),
+            payee,
+            amount: Some(self.amount),
+            date: Some(self.transaction_date),
+        });
+        // 没有描述时收款方模型学不到任何东西
+        let payee = payee
+            .filter(|_| features.iter().any(|f| f.starts_with("d:")))
+            .map(str::to_string);
+        Some(Example {
+            transaction_id: self.id,
+            category_id,
+            payee,
+            features,
+        })
+    }
+}
+
+/// 把模型预测映射为候选分类（标签为分类 id）
+fn category_suggestions(
+    predictions: Vec<Prediction>,
+    candidates: &HashMap<String, &CategoryForAi>,
+) -> Vec<CategorySuggestion> {
+    predictions
+        .into_iter()
+        .filter_map(|p| {
+            let category = candidates.get(&p.label)?;
+            Some(CategorySuggestion {
+                category_id: category.id,
+                name: category.name.clone(),
+                parent_name: category.parent_name.clone(),
+                confidence: p.confidence,
+            })
+        })
+        .collect()
+}
+
+pub struct CategorySuggestionService {
+    pool: PgPool,
+    config: ClassifierConfig,
+}
+
+impl CategorySuggestionService {
+    pub fn new(pool: PgPool, config: &ClassifierConfig) -> Self {
+        Self {
+            pool,
+            config: config.clone(),
+        }
+    }
+
+    async fn ledger_family(&self, ledger_id: Uuid) -> Result<Option<Uuid>, ServiceError> {
+        let family_id: Option<Option<Uuid>> =
+            sqlx::query_scalar("SELECT family_id FROM ledgers WHERE id = $1")
+                .bind(ledger_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        family_id.ok_or_else(|| ServiceError::not_found("Ledger", ledger_id))
+    }
+
+    async fn load(&self, family_id: Uuid) -> Result<FamilyModels, ServiceError> {
+        let row: Option<ModelRow> = sqlx::query_as(
+            r#"
+            SELECT category_model, payee_model, trained_until, trained_at
+            FROM category_models WHERE family_id = $1
+            "#,
+        )
+        .bind(family_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map_or_else(
+            || FamilyModels::empty(family_id),
+            |row| FamilyModels::from_row(family_id, row),
+        ))
+    }
+
+    /// 读取模型；超过重训间隔时先增量训练
+    async fn fresh_models(&self, family_id: Uuid) -> Result<FamilyModels, ServiceError> {
+        let config = &self.config;
+        let models = self.load(family_id).await?;
+        let stale = models
+            .trained_at
+            .is_none_or(|at| Utc::now() - at >= Duration::seconds(config.retrain_interval_secs));
+        if !stale {
+            return Ok(models);
+        }
+        let (models, _) = self.train_models(family_id, false).await?;
+        Ok(models)
+    }
+
+    pub async fn status(&self, family_id: Uuid) -> Result<ModelStatus, ServiceError> {
+        Ok(self.load(family_id).await?.status(&self.config))
+    }
+
+    /// 增量训练（`full` 时从全部历史重新训练）
+    pub async fn train(&self, family_id: Uuid, full: bool) -> Result<TrainReport, ServiceError> {
+        Ok(self.train_models(family_id, full).await?.1)
+    }
+
+    async fn train_models(
+        &self,
+        family_id: Uuid,
+        full: bool,
+    ) -> Result<(FamilyModels, TrainReport), ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        // 行锁串行化同一家庭的并发训练
+        sqlx::query("INSERT INTO category_models (family_id) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(family_id)
+            .execute(&mut *tx)
+            .await?;
+        let row: ModelRow = sqlx::query_as(
+            r#"
+            SELECT category_model, payee_model, trained_until, trained_at
+            FROM category_models WHERE family_id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(family_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut models = if full {
+            sqlx::query("DELETE FROM category_model_examples WHERE family_id = $1")
+                .bind(family_id)
+                .execute(&mut *tx)
+                .await?;
+            FamilyModels::empty(family_id)
+        } else {
+            FamilyModels::from_row(family_id, row)
+        };
+        let mut report = TrainReport {
+            scanned: 0,
+            learned: 0,
+            unlearned: 0,
+            model: models.status(&self.config),
+        };
+
+        // 已被物理删除（或移出家庭）的交易
+        let orphans: Vec<Example> = sqlx::query_as(
+            r#"
+            SELECT e.transaction_id, e.category_id, e.payee, e.features
+            FROM category_model_examples e
+            WHERE e.family_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM transactions t
+                  JOIN ledgers l ON l.id = t.ledger_id
+                  WHERE t.id = e.transaction_id AND l.family_id = $1
+              )
+            "#,
+        )
+        .bind(family_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for example in &orphans {
+            models.unlearn(example);
+            report.unlearned += 1;
+        }
+        if !orphans.is_empty() {
+            let ids: Vec<Uuid> = orphans.iter().map(|e| e.transaction_id).collect();
+            sqlx::query("DELETE FROM category_model_examples WHERE transaction_id = ANY($1)")
+                .bind(&ids)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let mut cursor: Option<(DateTime<Utc>, Uuid)> = models
+            .trained_until
+            .map(|at| (at - Duration::seconds(WATERMARK_OVERLAP_SECS), Uuid::nil()));
+        loop {
+            let rows: Vec<TrainingRow> = sqlx::query_as(
+                r#"
+                SELECT t.id, t.transaction_type, t.amount, t.transaction_date, t.description,
+                       COALESCE(t.payee, t.merchant) AS payee, t.category_id,
+                       t.category_source, t.deleted_at, t.updated_at
+                FROM transactions t
+                JOIN ledgers l ON l.id = t.ledger_id
+                WHERE l.family_id = $1
+                  AND t.updated_at IS NOT NULL
+                  AND ($2::timestamptz IS NULL OR (t.updated_at, t.id) > ($2, $3))
+                ORDER BY t.updated_at, t.id
+                LIMIT $4
+                "#,
+            )
+            .bind(family_id)
+            .bind(cursor.map(|c| c.0))
+            .bind(cursor.map(|c| c.1))
+            .bind(TRAIN_PAGE_SIZE)
+            .fetch_all(&mut *tx)
+            .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            cursor = Some((last.updated_at, last.id));
+            report.scanned += rows.len();
+
+            let ids: Vec<Uuid> = rows.iter().map(|r| r.id).collect();
+            let stored: HashMap<Uuid, Example> = sqlx::query_as::<_, Example>(
+                r#"
+                SELECT transaction_id, category_id, payee, features
+                FROM category_model_examples
+                WHERE transaction_id = ANY($1)
+                "#,
+            )
+            .bind(&ids)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|e| (e.transaction_id, e))
+            .collect();
+
+            for row in &rows {
+                let current = row.example();
+                let previous = stored.get(&row.id);
+                if current.as_ref() == previous {
+                    continue;
+                }
+                if let Some(previous) = previous {
+                    models.unlearn(previous);
+                    report.unlearned += 1;
+                }
+                match &current {
+                    Some(example) => {
+                        models.learn(example);
+                        report.learned += 1;
+                        save_example(&mut tx, family_id, example).await?;
+                    }
+                    None => {
+                        sqlx::query(
+                            "DELETE FROM category_model_examples WHERE transaction_id = $1",
+                        )
+                        .bind(row.id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+            }
+
+            if (rows.len() as i64) < TRAIN_PAGE_SIZE {
+                break;
+            }
+        }
+
+        // 水位线只前进不后退
+        if let Some((scanned_until, _)) = cursor {
+            models.trained_until = Some(
+                models
+                    .trained_until
+                    .map_or(scanned_until, |at| at.max(scanned_until)),
+            );
+        }
+        models.trained_at = Some(Utc::now());
+        sqlx::query(
+            r#"
+            UPDATE category_models
+            SET category_model = $2, payee_model = $3, example_count = $4,
+                trained_until = $5, trained_at = $6, updated_at = NOW()
+            WHERE family_id = $1
+            "#,
+        )
+        .bind(family_id)
+        .bind(Json(&models.category))
+        .bind(Json(&models.payee))
+        .bind(models.example_count())
+        .bind(models.trained_until)
+        .bind(models.trained_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        report.model = models.status(&self.config);
+        Ok((models, report))
+    }
+
+    /// 新建交易时的分类与收款方建议
+    pub async fn suggest(
+        &self,
+        query: &SuggestionQuery,
+    ) -> Result<SuggestionResponse, ServiceError> {
+        let config = &self.config;
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_SUGGESTIONS)
+            .clamp(1, MAX_SUGGESTIONS);
+        let transaction_type = query.transaction_type.as_deref().unwrap_or("expense");
+        if !matches!(transaction_type, "expense" | "income") {
+            return Err(ServiceError::validation(
+                "transaction_type 只能是 expense 或 income",
+            ));
+        }
+
+        let mut response = SuggestionResponse {
+            categories: Vec::new(),
+            payees: Vec::new(),
+            example_count: 0,
+            model_ready: false,
+        };
+        // 个人账本不属于任何家庭，没有模型
+        let Some(family_id) = self.ledger_family(query.ledger_id).await? else {
+            return Ok(response);
+        };
+        let models = self.fresh_models(family_id).await?;
+        response.example_count = models.example_count();
+        response.model_ready = models.is_ready(config);
+
+        let payee = query
+            .payee
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty());
+        let features = extract_features(&FeatureInput {
+            description: query.description.as_deref(),
+            payee,
+            amount: query.amount,
+            date: Some(
+                query
+                    .transaction_date
+                    .unwrap_or_else(|| Utc::now().date_naive()),
+            ),
+        });
+
+        let categories = ledger_categories(&self.pool, query.ledger_id).await?;
+        let candidates: HashMap<String, &CategoryForAi> = categories
+            .iter()
+            .filter(|c| c.category_type == transaction_type)
+            .map(|c| (c.id.to_string(), c))
+            .collect();
+        let labels: HashSet<String> = candidates.keys().cloned().collect();
+        response.categories = category_suggestions(
+            models.category.predict(&features, Some(&labels), limit),
+            &candidates,
+        );
+
+        if payee.is_none() {
+            response.payees = models
+                .payee
+                .predict(&payee_features(&features), None, limit)
+                .into_iter()
+                .map(|p| PayeeSuggestion {
+                    payee: p.label,
+                    confidence: p.confidence,
+                })
+                .collect();
+        }
+        Ok(response)
+    }
+
+    /// 用本地模型为未分类交易分类；样本数与置信度都达到门槛时写入
+    pub async fn auto_categorize(
+        &self,
+        user_id: Uuid,
+        req: &CategorizeRequest,
+    ) -> Result<CategorizeSummary, ServiceError> {
+        let config = &self.config;
+        let dry_run = req.dry_run.unwrap_or(false);
+        let mut summary = CategorizeSummary {
+            provider: PROVIDER_LOCAL.to_string(),
+            model: MODEL_NAIVE_BAYES.to_string(),
+            processed: 0,
+            applied: 0,
+            results: Vec::new(),
+        };
+        let Some(family_id) = self.ledger_family(req.ledger_id).await? else {
+            return Err(ServiceError::business_rule("个人账本不支持本地分类模型"));
+        };
+        let transactions = uncategorized_transactions(&self.pool, req).await?;
+        if transactions.is_empty() {
+            return Ok(summary);
+        }
+        let models = self.fresh_models(family_id).await?;
+        let ready = models.is_ready(config);
+        let categories = ledger_categories(&self.pool, req.ledger_id).await?;
+        let reasoning = format!("基于 {} 条已确认分类", models.example_count());
+
+        let mut tx = self.pool.begin().await?;
+        for transaction in &transactions {
+            let candidates: HashMap<String, &CategoryForAi> = categories
+                .iter()
+                .filter(|c| c.category_type == transaction.transaction_type)
+                .map(|c| (c.id.to_string(), c))
+                .collect();
+            let labels: HashSet<String> = candidates.keys().cloned().collect();
+            let features = extract_features(&FeatureInput {
+                description: transaction.description.as_deref(),
+                payee: transaction.payee.as_deref(),
+                amount: Some(transaction.amount),
+                date: Some(transaction.transaction_date),
+            });
+            let best = category_suggestions(
+                models.category.predict(&features, Some(&labels), 1),
+                &candidates,
+            )
+            .into_iter()
+            .next();
+
+            let mut result = AiCategorization {
+                transaction_id: transaction.id,
+                category_id: best.as_ref().map(|b| b.category_id),
+                category_name: best.as_ref().map(|b| b.name.clone()),
+                confidence: best.as_ref().map_or(0.0, |b| b.confidence),
+                reasoning: Some(reasoning.clone()),
+                applied: false,
+            };
+            if !dry_run && ready && result.confidence >= config.min_confidence {
+                if let Some(category_id) = result.category_id {
+                    // 只写入仍未分类的交易，避免覆盖用户刚刚设置的分类
+                    let applied = sqlx::query(
+                        r#"
+                        UPDATE transactions
+                        SET category_id = $2, category_name = $3, category_source = $4,
+                            updated_at = NOW()
+                        WHERE id = $1 AND category_id IS NULL
+                        "#,
+                    )
+                    .bind(transaction.id)
+                    .bind(category_id)
+                    .bind(&result.category_name)
+                    .bind(SOURCE_ML)
+                    .execute(&mut *tx)
+                    .await?;
+                    result.applied = applied.rows_affected() == 1;
+                }
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_categorizations (
+                    transaction_id, ledger_id, category_id, source, provider, model,
+                    confidence, reasoning, applied, created_by
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                "#,
+            )
+            .bind(result.transaction_id)
+            .bind(req.ledger_id)
+            .bind(result.category_id)
+            .bind(SOURCE_ML)
+            .bind(PROVIDER_LOCAL)
+            .bind(MODEL_NAIVE_BAYES)
+            .bind(result.confidence)
+            .bind(&result.reasoning)
+            .bind(result.applied)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            summary.processed += 1;
+            summary.applied += usize::from(result.applied);
+            summary.results.push(result);
+        }
+        tx.commit().await?;
+        Ok(summary)
+    }
+}
+
+async fn save_example(
+    tx: &mut Transaction<'_, Postgres>,
+    family_id: Uuid,
+    example: &Example,
+) -> Result<(), ServiceError> {
+    sqlx::query(
+        r#"
+        INSERT INTO category_model_examples (
+            transaction_id, family_id, category_id, payee, features, learned_at
+        ) VALUES ($1, $2, $3, $4, $5, NOW())
+        ON CONFLICT (transaction_id) DO UPDATE
+        SET family_id = EXCLUDED.family_id, category_id = EXCLUDED.category_id,
+            payee = EXCLUDED.payee, features = EXCLUDED.features, learned_at = NOW()
+        "#,
+    )
+    .bind(example.transaction_id)
+    .bind(family_id)
+    .bind(example.category_id)
+    .bind(&example.payee)
+    .bind(&example.features)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(category_source: Option<&str>) -> TrainingRow {
+        TrainingRow {
+            id: Uuid::new_v4(),
+            transaction_type: "expense".to_string(),
+            amount: Decimal::new(-3500, 2),
+            transaction_date: NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+            description: Some("星巴克 拿铁".to_string()),
+            payee: Some(" 星巴克 ".to_string()),
+            category_id: Some(Uuid::new_v4()),
+            category_source: category_source.map(str::to_string),
+            deleted_at: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_only_confirmed_categorizations_are_examples() {
+        let confirmed = row(None);
+        let example = confirmed.example().unwrap();
+        assert_eq!(example.category_id, confirmed.category_id.unwrap());
+        assert_eq!(example.payee.as_deref(), Some("星巴克"));
+        assert!(example.features.contains(&"payee=星巴克".to_string()));
+        assert!(example.features.contains(&"d:拿铁".to_string()));
+
+        for source in ["llm", "ml", "rule"] {
+            assert!(row(Some(source)).example().is_none());
+        }
+        let mut deleted = row(None);
+        deleted.deleted_at = Some(Utc::now());
+        assert!(deleted.example().is_none());
+        let mut transfer = row(None);
+        transfer.transaction_type = "transfer".to_string();
+        assert!(transfer.example().is_none());
+        let mut uncategorized = row(None);
+        uncategorized.category_id = None;
+        assert!(uncategorized.example().is_none());
+
+        // 没有描述时不学习收款方
+        let mut no_description = row(None);
+        no_description.description = None;
+        assert_eq!(no_description.example().unwrap().payee, None);
+    }
+
+    #[test]
+    fn test_learn_then_unlearn_example_restores_models() {
+        let mut models = FamilyModels::empty(Uuid::new_v4());
+        let first = row(None).example().unwrap();
+        models.learn(&first);
+        let snapshot = (models.category.clone(), models.payee.clone());
+
+        // 用户修改了分类：撤销旧样本、学习新样本，再改回
+        let mut edited = first.clone();
+        edited.category_id = Uuid::new_v4();
+        models.unlearn(&first);
+        models.learn(&edited);
+        assert_eq!(models.category.labels(), 1);
+        models.unlearn(&edited);
+        models.learn(&first);
+        assert_eq!((models.category.clone(), models.payee.clone()), snapshot);
+        assert_eq!(models.example_count(), 1);
+        assert_eq!(models.payee.documents(), 1);
+    }
+}
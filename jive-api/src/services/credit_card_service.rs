@@ -0,0 +1,1461 @@
+//! 信用卡：账单周期、月度账单、还款提醒与利息 / 违约金
+//!
+//! 信用卡挂在子类型为 credit_card / huabei / jd_white_bar 的账户上。账单金额均以欠款计
+//! （正数为欠款），由卡账户的交易推算：支出与转出为消费，收入（含转入的还款、退款）为还款。
+//!
+//! 定时任务每天依次：
+//! 1. 到期日已过的账单按到期日前的还款判定状态，未还足最低还款额时在到期次日记一笔违约金；
+//! 2. 为已结束的账单周期生成账单。上期账单未在到期日前全额还款时，对上期欠款按日计息
+//!    （每日余额 × APR / 365，还款当日起减少计息余额），在本期账单日记一笔利息；
+//! 3. 更新未结清账单的已还金额，并在到期日前 `reminder_days_before` 天发送还款提醒。
+//!
+//! 利息与违约金作为卡账户的支出交易记账，因此账户余额与账单保持一致。转分期的消费在账单
+//! 中按期计入（见 `installment_service`），账单欠款因此可能低于账户欠款。
+
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::installment_service::{
+    statement_installments, unbilled_installments, InstallmentService,
+};
+use super::notification_service::{NewNotification, NotificationPriority, NotificationService};
+use super::transaction_valuation_service::TransactionValuationService;
+use super::ServiceError;
+use crate::config::AppConfig;
+use crate::ws::WsConnectionManager;
+
+/// 可挂信用卡的账户子类型
+pub const CREDIT_ACCOUNT_SUB_TYPES: &[&str] = &["credit_card", "huabei", "jd_white_bar"];
+
+/// 单次最多补生成的账单数
+const MAX_STATEMENTS_PER_RUN: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentDateType {
+    /// 每月固定日期还款
+    FixedDate,
+    /// 账单日后 N 天还款
+    DaysAfterBill,
+}
+
+impl PaymentDateType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentDateType::FixedDate => "fixed_date",
+            PaymentDateType::DaysAfterBill => "days_after_bill",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fixed_date" => Some(PaymentDateType::FixedDate),
+            "days_after_bill" => Some(PaymentDateType::DaysAfterBill),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementStatus {
+    /// 未到还款日且未全额还款
+    Open,
+    Paid,
+    /// 到期日前已还最低还款额
+    MinimumPaid,
+    /// 到期日前未还足最低还款额
+    Overdue,
+}
+
+impl StatementStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementStatus::Open => "open",
+            StatementStatus::Paid => "paid",
+            StatementStatus::MinimumPaid => "minimum_paid",
+            StatementStatus::Overdue => "overdue",
+        }
+    }
+}
+
+/// 账单周期规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct BillingTerms {
+    pub bill_day: u32,
+    /// 账单日当天的消费计入当天出的账单（否则计入下一期）
+    pub bill_calculation_in_previous_period: bool,
+    pub payment_date_type: PaymentDateType,
+    pub payment_day: Option<u32>,
+    pub payment_days_after_bill: Option<u32>,
+}
+
+/// 一个账单周期
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct BillingCycle {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub statement_date: NaiveDate,
+    pub due_date: NaiveDate,
+}
+
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.checked_add_months(Months::new(1)))
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+/// 某月的第 `day` 日，该月没有这一天时取月末
+fn clamped_day(year: i32, month: u32, day: u32) -> NaiveDate {
+    let day = day.clamp(1, last_day_of_month(year, month));
+    NaiveDate::from_ymd_opt(year, month, day).expect("valid clamped date")
+}
+
+fn add_months(date: NaiveDate, months: i32) -> (i32, u32) {
+    let index = date.year() * 12 + date.month0() as i32 + months;
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+impl BillingTerms {
+    /// `date` 当天或之后的第一个账单日
+    pub fn next_statement_date(&self, date: NaiveDate) -> NaiveDate {
+        let this_month = clamped_day(date.year(), date.month(), self.bill_day);
+        if this_month >= date {
+            this_month
+        } else {
+            let (y, m) = add_months(date, 1);
+            clamped_day(y, m, self.bill_day)
+        }
+    }
+
+    /// 早于 `statement_date` 的上一个账单日
+    pub fn previous_statement_date(&self, statement_date: NaiveDate) -> NaiveDate {
+        let (y, m) = add_months(statement_date, -1);
+        clamped_day(y, m, self.bill_day)
+    }
+
+    /// 账单覆盖的最后一天
+    pub fn cycle_end(&self, statement_date: NaiveDate) -> NaiveDate {
+        if self.bill_calculation_in_previous_period {
+            statement_date
+        } else {
+            statement_date - Duration::days(1)
+        }
+    }
+
+    pub fn due_date(&self, statement_date: NaiveDate) -> NaiveDate {
+        match self.payment_date_type {
+            PaymentDateType::FixedDate => {
+                let day = self.payment_day.unwrap_or(self.bill_day);
+                let same_month = clamped_day(statement_date.year(), statement_date.month(), day);
+                if same_month > statement_date {
+                    same_month
+                } else {
+                    let (y, m) = add_months(statement_date, 1);
+                    clamped_day(y, m, day)
+                }
+            }
+            PaymentDateType::DaysAfterBill => {
+                statement_date + Duration::days(self.payment_days_after_bill.unwrap_or(20) as i64)
+            }
+        }
+    }
+
+    /// 以 `statement_date` 出账的账单周期
+    pub fn cycle(&self, statement_date: NaiveDate) -> BillingCycle {
+        BillingCycle {
+            start_date: self.cycle_end(self.previous_statement_date(statement_date))
+                + Duration::days(1),
+            end_date: self.cycle_end(statement_date),
+            statement_date,
+            due_date: self.due_date(statement_date),
+        }
+    }
+
+    /// `date` 当天的消费所属的账单周期
+    pub fn cycle_containing(&self, date: NaiveDate) -> BillingCycle {
+        let statement_date = if self.bill_calculation_in_previous_period {
+            self.next_statement_date(date)
+        } else {
+            self.next_statement_date(date + Duration::days(1))
+        };
+        self.cycle(statement_date)
+    }
+}
+
+/// 最低还款额：本金部分 × 比例 + 全部利息与费用，不低于下限、不超过欠款
+pub fn minimum_due(
+    balance: Decimal,
+    interest_and_fees: Decimal,
+    rate: Decimal,
+    floor: Decimal,
+) -> Decimal {
+    if balance <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let principal = (balance - interest_and_fees).max(Decimal::ZERO);
+    (principal * rate + interest_and_fees)
+        .max(floor)
+        .min(balance)
+        .round_dp(2)
+}
+
+/// 按日计息：`carried` 从 `from` 起计息，每笔还款自其日期起减少计息余额
+pub fn daily_balance_interest(
+    carried: Decimal,
+    payments: &[(NaiveDate, Decimal)],
+    from: NaiveDate,
+    to: NaiveDate,
+    apr: Decimal,
+) -> Decimal {
+    if carried <= Decimal::ZERO || apr <= Decimal::ZERO || to < from {
+        return Decimal::ZERO;
+    }
+    let mut balance_days = Decimal::ZERO;
+    let mut day = from;
+    while day <= to {
+        let paid: Decimal = payments
+            .iter()
+            .filter(|(date, _)| *date <= day)
+            .map(|(_, amount)| *amount)
+            .sum();
+        balance_days += (carried - paid).max(Decimal::ZERO);
+        day += Duration::days(1);
+    }
+    (balance_days * apr / Decimal::from(365)).round_dp(2)
+}
+
+/// 违约金：固定金额 + 未还最低还款额 × 比例
+pub fn late_fee(
+    minimum_due: Decimal,
+    paid_by_due: Decimal,
+    fixed: Decimal,
+    rate: Decimal,
+) -> Decimal {
+    let unpaid = minimum_due - paid_by_due;
+    if unpaid <= Decimal::ZERO {
+        Decimal::ZERO
+    } else {
+        (fixed + unpaid * rate).round_dp(2)
+    }
+}
+
+pub fn statement_status(
+    closing_balance: Decimal,
+    minimum_due: Decimal,
+    paid_amount: Decimal,
+    paid_by_due: Option<Decimal>,
+) -> StatementStatus {
+    if closing_balance <= Decimal::ZERO || paid_amount >= closing_balance {
+        return StatementStatus::Paid;
+    }
+    match paid_by_due {
+        None => StatementStatus::Open,
+        Some(paid) if paid >= minimum_due => StatementStatus::MinimumPaid,
+        Some(_) => StatementStatus::Overdue,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CreditCard {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub ledger_id: Uuid,
+    pub family_id: Uuid,
+    pub currency: Option<String>,
+    pub card_number_last4: Option<String>,
+    pub bank_name: Option<String>,
+    pub card_network: Option<String>,
+    pub credit_limit: Option<Decimal>,
+    /// 当前欠款（账户余额取反）
+    pub current_debt: Decimal,
+    pub available_credit: Option<Decimal>,
+    pub bill_day: i16,
+    pub bill_calculation_in_previous_period: bool,
+    pub payment_date_type: String,
+    pub payment_day: Option<i16>,
+    pub payment_days_after_bill: Option<i16>,
+    pub apr: Decimal,
+    pub minimum_payment_rate: Decimal,
+    pub minimum_payment_floor: Decimal,
+    pub late_fee: Decimal,
+    pub late_fee_rate: Decimal,
+    pub reminder_days_before: i16,
+    pub status: String,
+    pub created_by: Uuid,
+}
+
+impl CreditCard {
+    pub fn terms(&self) -> BillingTerms {
+        BillingTerms {
+            bill_day: self.bill_day as u32,
+            bill_calculation_in_previous_period: self.bill_calculation_in_previous_period,
+            payment_date_type: PaymentDateType::parse(&self.payment_date_type)
+                .unwrap_or(PaymentDateType::FixedDate),
+            payment_day: self.payment_day.map(|d| d as u32),
+            payment_days_after_bill: self.payment_days_after_bill.map(|d| d as u32),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCreditCardRequest {
+    pub account_id: Uuid,
+    pub card_number_last4: Option<String>,
+    pub bank_name: Option<String>,
+    pub card_network: Option<String>,
+    /// 默认取账户的 credit_limit
+    pub credit_limit: Option<Decimal>,
+    pub bill_day: i16,
+    pub bill_calculation_in_previous_period: Option<bool>,
+    pub payment_date_type: Option<PaymentDateType>,
+    pub payment_day: Option<i16>,
+    pub payment_days_after_bill: Option<i16>,
+    pub apr: Option<Decimal>,
+    pub minimum_payment_rate: Option<Decimal>,
+    pub minimum_payment_floor: Option<Decimal>,
+    pub late_fee: Option<Decimal>,
+    pub late_fee_rate: Option<Decimal>,
+    pub reminder_days_before: Option<i16>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateCreditCardRequest {
+    pub card_number_last4: Option<String>,
+    pub bank_name: Option<String>,
+    pub card_network: Option<String>,
+    pub credit_limit: Option<Decimal>,
+    pub bill_day: Option<i16>,
+    pub bill_calculation_in_previous_period: Option<bool>,
+    pub payment_date_type: Option<PaymentDateType>,
+    pub payment_day: Option<i16>,
+    pub payment_days_after_bill: Option<i16>,
+    pub apr: Option<Decimal>,
+    pub minimum_payment_rate: Option<Decimal>,
+    pub minimum_payment_floor: Option<Decimal>,
+    pub late_fee: Option<Decimal>,
+    pub late_fee_rate: Option<Decimal>,
+    pub reminder_days_before: Option<i16>,
+    /// active / closed
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CreditCardStatement {
+    pub id: Uuid,
+    pub card_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub statement_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub opening_balance: Decimal,
+    pub new_charges: Decimal,
+    pub payments: Decimal,
+    pub interest_charged: Decimal,
+    pub fees_charged: Decimal,
+    /// 本期计入的分期本金
+    pub installment_principal: Decimal,
+    /// 本期转分期冲减的消费本金
+    pub installment_converted: Decimal,
+    pub closing_balance: Decimal,
+    pub minimum_due: Decimal,
+    pub paid_amount: Decimal,
+    pub paid_by_due: Option<Decimal>,
+    pub status: String,
+    pub late_fee: Decimal,
+}
+
+/// 当前账单周期的实时汇总
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CycleSummary {
+    pub card_id: Uuid,
+    pub cycle: BillingCycle,
+    pub opening_balance: Decimal,
+    pub charges: Decimal,
+    pub payments: Decimal,
+    pub installment_principal: Decimal,
+    pub installment_converted: Decimal,
+    /// 截至今天的欠款
+    pub current_debt: Decimal,
+    pub available_credit: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct CreditCardRunStats {
+    pub cards: usize,
+    pub statements_generated: usize,
+    pub statements_assessed: usize,
+    pub reminders_sent: usize,
+    pub failed: usize,
+}
+
+const CARD_SELECT: &str = r#"
+    SELECT c.id, c.account_id, a.name AS account_name, a.ledger_id, l.family_id, a.currency,
+           c.card_number_last4, c.bank_name, c.card_network, c.credit_limit,
+           -COALESCE(a.current_balance, 0) AS current_debt,
+           c.credit_limit + COALESCE(a.current_balance, 0) AS available_credit,
+           c.bill_day, c.bill_calculation_in_previous_period, c.payment_date_type, c.payment_day,
+           c.payment_days_after_bill, c.apr, c.minimum_payment_rate, c.minimum_payment_floor,
+           c.late_fee, c.late_fee_rate, c.reminder_days_before, c.status, c.created_by
+    FROM credit_cards c
+    JOIN accounts a ON a.id = c.account_id
+    JOIN ledgers l ON l.id = a.ledger_id
+"#;
+
+const STATEMENT_COLUMNS: &str = r#"
+    id, card_id, period_start, period_end, statement_date, due_date, opening_balance, new_charges,
+    payments, interest_charged, fees_charged, installment_principal, installment_converted,
+    closing_balance, minimum_due, paid_amount, paid_by_due, status, late_fee
+"#;
+
+fn validate_terms(
+    bill_day: i16,
+    payment_date_type: PaymentDateType,
+    payment_day: Option<i16>,
+    payment_days_after_bill: Option<i16>,
+) -> Result<(), ServiceError> {
+    if !(1..=31).contains(&bill_day) {
+        return Err(ServiceError::validation(
+            "bill_day must be between 1 and 31",
+        ));
+    }
+    match payment_date_type {
+        PaymentDateType::FixedDate if !payment_day.is_some_and(|d| (1..=31).contains(&d)) => Err(
+            ServiceError::validation("payment_day (1-31) is required for fixed_date"),
+        ),
+        PaymentDateType::DaysAfterBill
+            if !payment_days_after_bill.is_some_and(|d| (1..=28).contains(&d)) =>
+        {
+            Err(ServiceError::validation(
+                "payment_days_after_bill (1-28) is required for days_after_bill",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_rates(
+    apr: Option<Decimal>,
+    minimum_payment_rate: Option<Decimal>,
+    late_fee_rate: Option<Decimal>,
+) -> Result<(), ServiceError> {
+    if apr.is_some_and(|r| r < Decimal::ZERO || r > Decimal::from(5)) {
+        return Err(ServiceError::validation("apr must be between 0 and 5"));
+    }
+    if minimum_payment_rate.is_some_and(|r| r <= Decimal::ZERO || r > Decimal::ONE) {
+        return Err(ServiceError::validation(
+            "minimum_payment_rate must be in (0, 1]",
+        ));
+    }
+    if late_fee_rate.is_some_and(|r| r < Decimal::ZERO || r > Decimal::ONE) {
+        return Err(ServiceError::validation(
+            "late_fee_rate must be between 0 and 1",
+        ));
+    }
+    Ok(())
+}
+
+pub struct CreditCardService {
+    pool: PgPool,
+}
+
+impl CreditCardService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_cards(&self, ledger_ids: &[Uuid]) -> Result<Vec<CreditCard>, ServiceError> {
+        let cards = sqlx::query_as::<_, CreditCard>(&format!(
+            "{} WHERE a.ledger_id = ANY($1) AND a.deleted_at IS NULL ORDER BY a.name",
+            CARD_SELECT
+        ))
+        .bind(ledger_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(cards)
+    }
+
+    pub async fn get_card(&self, id: Uuid) -> Result<CreditCard, ServiceError> {
+        sqlx::query_as::<_, CreditCard>(&format!("{} WHERE c.id = $1", CARD_SELECT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("credit_card", id))
+    }
+
+    /// 为信用卡类账户设置账单规则
+    pub async fn create_card(
+        &self,
+        user_id: Uuid,
+        request: CreateCreditCardRequest,
+    ) -> Result<CreditCard, ServiceError> {
+        let payment_date_type = request
+            .payment_date_type
+            .unwrap_or(PaymentDateType::FixedDate);
+        validate_terms(
+            request.bill_day,
+            payment_date_type,
+            request.payment_day,
+            request.payment_days_after_bill,
+        )?;
+        validate_rates(
+            request.apr,
+            request.minimum_payment_rate,
+            request.late_fee_rate,
+        )?;
+
+        let account = sqlx::query(
+            r#"
+            SELECT account_type, account_sub_type, credit_limit
+            FROM accounts
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(request.account_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("account", request.account_id))?;
+        let sub_type: Option<String> = account.get("account_sub_type");
+        let account_type: String = account.get("account_type");
+        let is_credit = match sub_type.as_deref() {
+            Some(sub_type) => CREDIT_ACCOUNT_SUB_TYPES.contains(&sub_type),
+            None => account_type == "credit",
+        };
+        if !is_credit {
+            return Err(ServiceError::business_rule(
+                "Credit cards can only be linked to credit_card, huabei or jd_white_bar accounts",
+            ));
+        }
+        let linked: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM credit_cards WHERE account_id = $1)")
+                .bind(request.account_id)
+                .fetch_one(&self.pool)
+                .await?;
+        if linked {
+            return Err(ServiceError::conflict(
+                "The account already has a credit card",
+            ));
+        }
+
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO credit_cards
+                (account_id, card_number_last4, bank_name, card_network, credit_limit, bill_day,
+                 bill_calculation_in_previous_period, payment_date_type, payment_day,
+                 payment_days_after_bill, apr, minimum_payment_rate, minimum_payment_floor,
+                 late_fee, late_fee_rate, reminder_days_before, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                    COALESCE($11, 0.1825), COALESCE($12, 0.1), COALESCE($13, 0),
+                    COALESCE($14, 0), COALESCE($15, 0), COALESCE($16, 3), $17)
+            RETURNING id
+            "#,
+        )
+        .bind(request.account_id)
+        .bind(&request.card_number_last4)
+        .bind(&request.bank_name)
+        .bind(&request.card_network)
+        .bind(
+            request
+                .credit_limit
+                .or_else(|| account.get::<Option<Decimal>, _>("credit_limit")),
+        )
+        .bind(request.bill_day)
+        .bind(request.bill_calculation_in_previous_period.unwrap_or(false))
+        .bind(payment_date_type.as_str())
+        .bind(request.payment_day)
+        .bind(request.payment_days_after_bill)
+        .bind(request.apr)
+        .bind(request.minimum_payment_rate)
+        .bind(request.minimum_payment_floor)
+        .bind(request.late_fee)
+        .bind(request.late_fee_rate)
+        .bind(request.reminder_days_before)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        self.get_card(id).await
+    }
+
+    /// 修改账单规则；已生成的账单不受影响
+    pub async fn update_card(
+        &self,
+        id: Uuid,
+        request: UpdateCreditCardRequest,
+    ) -> Result<CreditCard, ServiceError> {
+        let current = self.get_card(id).await?;
+        let payment_date_type = request
+            .payment_date_type
+            .unwrap_or(current.terms().payment_date_type);
+        validate_terms(
+            request.bill_day.unwrap_or(current.bill_day),
+            payment_date_type,
+            request.payment_day.or(current.payment_day),
+            request
+                .payment_days_after_bill
+                .or(current.payment_days_after_bill),
+        )?;
+        validate_rates(
+            request.apr,
+            request.minimum_payment_rate,
+            request.late_fee_rate,
+        )?;
+        if let Some(status) = &request.status {
+            if status != "active" && status != "closed" {
+                return Err(ServiceError::validation("status must be active or closed"));
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE credit_cards SET
+                card_number_last4 = COALESCE($2, card_number_last4),
+                bank_name = COALESCE($3, bank_name),
+                card_network = COALESCE($4, card_network),
+                credit_limit = COALESCE($5, credit_limit),
+                bill_day = COALESCE($6, bill_day),
+                bill_calculation_in_previous_period =
+                    COALESCE($7, bill_calculation_in_previous_period),
+                payment_date_type = $8,
+                payment_day = COALESCE($9, payment_day),
+                payment_days_after_bill = COALESCE($10, payment_days_after_bill),
+                apr = COALESCE($11, apr),
+                minimum_payment_rate = COALESCE($12, minimum_payment_rate),
+                minimum_payment_floor = COALESCE($13, minimum_payment_floor),
+                late_fee = COALESCE($14, late_fee),
+                late_fee_rate = COALESCE($15, late_fee_rate),
+                reminder_days_before = COALESCE($16, reminder_days_before),
+                status = COALESCE($17, status),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(&request.card_number_last4)
+        .bind(&request.bank_name)
+        .bind(&request.card_network)
+        .bind(request.credit_limit)
+        .bind(request.bill_day)
+        .bind(request.bill_calculation_in_previous_period)
+        .bind(payment_date_type.as_str())
+        .bind(request.payment_day)
+        .bind(request.payment_days_after_bill)
+        .bind(request.apr)
+        .bind(request.minimum_payment_rate)
+        .bind(request.minimum_payment_floor)
+        .bind(request.late_fee)
+        .bind(request.late_fee_rate)
+        .bind(request.reminder_days_before)
+        .bind(&request.status)
+        .execute(&self.pool)
+        .await?;
+        self.get_card(id).await
+    }
+
+    /// 解除信用卡与账户的关联，账单一并删除，账户与交易保留
+    pub async fn delete_card(&self, id: Uuid) -> Result<(), ServiceError> {
+        let result = sqlx::query("DELETE FROM credit_cards WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("credit_card", id));
+        }
+        Ok(())
+    }
+
+    pub async fn list_statements(
+        &self,
+        card_id: Uuid,
+    ) -> Result<Vec<CreditCardStatement>, ServiceError> {
+        let statements = sqlx::query_as::<_, CreditCardStatement>(&format!(
+            "SELECT {} FROM credit_card_statements WHERE card_id = $1 ORDER BY statement_date DESC",
+            STATEMENT_COLUMNS
+        ))
+        .bind(card_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(statements)
+    }
+
+    /// `date` 所在账单周期截至今天的消费与还款
+    pub async fn current_cycle(
+        &self,
+        card_id: Uuid,
+        date: Option<NaiveDate>,
+    ) -> Result<CycleSummary, ServiceError> {
+        let card = self.get_card(card_id).await?;
+        let today = Utc::now().date_naive();
+        let cycle = card.terms().cycle_containing(date.unwrap_or(today));
+        let until = cycle.end_date.min(today);
+
+        let mut conn = self.pool.acquire().await?;
+        let opening = statement_debt_at(
+            &mut conn,
+            card.account_id,
+            card.current_debt,
+            cycle.start_date - Duration::days(1),
+        )
+        .await?;
+        let (charges, payments) =
+            period_totals(&mut *conn, card.account_id, cycle.start_date, until).await?;
+        let (installment_principal, installment_converted) =
+            statement_installments(&mut *conn, card.account_id, cycle.start_date, until).await?;
+        Ok(CycleSummary {
+            card_id,
+            cycle,
+            opening_balance: opening,
+            charges,
+            payments,
+            installment_principal,
+            installment_converted,
+            current_debt: card.current_debt,
+            available_credit: card.available_credit,
+        })
+    }
+
+    /// 处理一张卡：判定到期账单、补生成账单、更新已还金额并发送提醒
+    ///
+    /// 返回本次生成的账单。
+    pub async fn process_card(
+        &self,
+        card_id: Uuid,
+        today: NaiveDate,
+        config: &AppConfig,
+        ws: &Arc<WsConnectionManager>,
+    ) -> Result<(Vec<CreditCardStatement>, CreditCardRunStats), ServiceError> {
+        let card = self.get_card(card_id).await?;
+        // 先记入已到期的分期手续费，使其进入本次生成的账单
+        InstallmentService::new(self.pool.clone())
+            .post_due(Some(card.account_id), today, &config.rate_resolver)
+            .await?;
+        let card = self.get_card(card_id).await?;
+        let mut stats = CreditCardRunStats {
+            cards: 1,
+            ..Default::default()
+        };
+        let mut posted = Vec::new();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT id FROM credit_cards WHERE id = $1 FOR UPDATE")
+            .bind(card_id)
+            .execute(&mut *tx)
+            .await?;
+        let overdue = self
+            .assess_due_statements(&mut tx, &card, today, &mut posted)
+            .await?;
+        stats.statements_assessed = overdue.len();
+        let generated = self
+            .generate_statements(&mut tx, &card, today, &mut posted)
+            .await?;
+        stats.statements_generated = generated.len();
+        self.refresh_payments(&mut tx, &card, today).await?;
+        let reminders = self.due_reminders(&mut tx, &card, today).await?;
+        stats.reminders_sent = reminders.len();
+        tx.commit().await?;
+
+        // 交易落库后再估值与通知，失败不影响账单
+        let valuation = TransactionValuationService::new(self.pool.clone());
+        for id in posted {
+            if let Err(e) = valuation.value_transaction(id, &config.rate_resolver).await {
+                tracing::warn!("Failed to value credit card charge {}: {:?}", id, e);
+            }
+        }
+        let notifier =
+            NotificationService::new(self.pool.clone(), &config.notification, ws.clone());
+        let notifications = overdue
+            .iter()
+            .map(|s| overdue_notification(&card, s))
+            .chain(
+                generated
+                    .iter()
+                    .filter_map(|s| statement_notification(&card, s)),
+            )
+            .chain(reminders.iter().map(|s| reminder_notification(&card, s)));
+        for notification in notifications {
+            if let Err(e) = notifier.notify(notification).await {
+                tracing::warn!("Failed to send credit card notification: {:?}", e);
+            }
+        }
+        Ok((generated, stats))
+    }
+
+    /// 处理全部启用中的信用卡
+    pub async fn run_due(
+        &self,
+        today: NaiveDate,
+        config: &AppConfig,
+        ws: &Arc<WsConnectionManager>,
+    ) -> Result<CreditCardRunStats, ServiceError> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT c.id
+            FROM credit_cards c
+            JOIN accounts a ON a.id = c.account_id
+            WHERE c.status = 'active' AND a.deleted_at IS NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut total = CreditCardRunStats::default();
+        for id in ids {
+            match self.process_card(id, today, config, ws).await {
+                Ok((_, stats)) => {
+                    total.cards += 1;
+                    total.statements_generated += stats.statements_generated;
+                    total.statements_assessed += stats.statements_assessed;
+                    total.reminders_sent += stats.reminders_sent;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to process credit card {}: {:?}", id, e);
+                    total.failed += 1;
+                }
+            }
+        }
+        Ok(total)
+    }
+
+    /// 到期日已过且尚未判定的账单：记录到期前还款额、状态与违约金
+    async fn assess_due_statements(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        card: &CreditCard,
+        today: NaiveDate,
+        posted: &mut Vec<Uuid>,
+    ) -> Result<Vec<CreditCardStatement>, ServiceError> {
+        let pending = sqlx::query_as::<_, CreditCardStatement>(&format!(
+            r#"
+            SELECT {} FROM credit_card_statements
+            WHERE card_id = $1 AND assessed_at IS NULL AND due_date < $2
+            ORDER BY statement_date
+            "#,
+            STATEMENT_COLUMNS
+        ))
+        .bind(card.id)
+        .bind(today)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let mut overdue = Vec::new();
+        for statement in pending {
+            let (_, paid_by_due) = period_totals(
+                &mut **tx,
+                card.account_id,
+                statement.period_end + Duration::days(1),
+                statement.due_date,
+            )
+            .await?;
+            let fee = late_fee(
+                statement.minimum_due,
+                paid_by_due,
+                card.late_fee,
+                card.late_fee_rate,
+            );
+            let fee_transaction = if fee > Decimal::ZERO {
+                let id = post_charge(
+                    tx,
+                    card,
+                    statement.due_date + Duration::days(1),
+                    fee,
+                    "信用卡违约金",
+                )
+                .await?;
+                posted.push(id);
+                Some(id)
+            } else {
+                None
+            };
+            let status = statement_status(
+                statement.closing_balance,
+                statement.minimum_due,
+                statement.paid_amount.max(paid_by_due),
+                Some(paid_by_due),
+            );
+            let updated = sqlx::query_as::<_, CreditCardStatement>(&format!(
+                r#"
+                UPDATE credit_card_statements
+                SET paid_by_due = $2, late_fee = $3, late_fee_transaction_id = $4,
+                    status = $5, assessed_at = NOW(), updated_at = NOW()
+                WHERE id = $1
+                RETURNING {}
+                "#,
+                STATEMENT_COLUMNS
+            ))
+            .bind(statement.id)
+            .bind(paid_by_due)
+            .bind(fee)
+            .bind(fee_transaction)
+            .bind(status.as_str())
+            .fetch_one(&mut **tx)
+            .await?;
+            if status == StatementStatus::Overdue {
+                overdue.push(updated);
+            }
+        }
+        Ok(overdue)
+    }
+
+    /// 为结束于今天之前的账单周期补生成账单
+    async fn generate_statements(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        card: &CreditCard,
+        today: NaiveDate,
+        posted: &mut Vec<Uuid>,
+    ) -> Result<Vec<CreditCardStatement>, ServiceError> {
+        let terms = card.terms();
+        let mut last = sqlx::query_as::<_, CreditCardStatement>(&format!(
+            r#"
+            SELECT {} FROM credit_card_statements
+            WHERE card_id = $1
+            ORDER BY statement_date DESC
+            LIMIT 1
+            "#,
+            STATEMENT_COLUMNS
+        ))
+        .bind(card.id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        let mut statement_date = match &last {
+            Some(s) => terms.next_statement_date(s.statement_date + Duration::days(1)),
+            None => {
+                let created: NaiveDate =
+                    sqlx::query_scalar("SELECT created_at::date FROM credit_cards WHERE id = $1")
+                        .bind(card.id)
+                        .fetch_one(&mut **tx)
+                        .await?;
+                terms.cycle_containing(created).statement_date
+            }
+        };
+
+        let mut generated = Vec::new();
+        while generated.len() < MAX_STATEMENTS_PER_RUN {
+            let mut cycle = terms.cycle(statement_date);
+            if cycle.end_date >= today {
+                break;
+            }
+            // 修改账单日后，新周期从上一张账单之后开始
+            if let Some(prev) = &last {
+                cycle.start_date = prev.period_end + Duration::days(1);
+            }
+            if cycle.start_date > cycle.end_date {
+                statement_date = terms.next_statement_date(statement_date + Duration::days(1));
+                continue;
+            }
+
+            let opening = match &last {
+                Some(prev) => prev.closing_balance,
+                None => {
+                    statement_debt_at(
+                        tx,
+                        card.account_id,
+                        card.current_debt,
+                        cycle.start_date - Duration::days(1),
+                    )
+                    .await?
+                }
+            };
+            let (charges, payments) =
+                period_totals(&mut **tx, card.account_id, cycle.start_date, cycle.end_date).await?;
+            let (installment_principal, installment_converted) = statement_installments(
+                &mut **tx,
+                card.account_id,
+                cycle.start_date,
+                cycle.end_date,
+            )
+            .await?;
+            let fees: Decimal = sqlx::query_scalar(
+                r#"
+                SELECT COALESCE(SUM(late_fee), 0) FROM credit_card_statements
+                WHERE card_id = $1 AND late_fee_transaction_id IS NOT NULL
+                  AND due_date + 1 BETWEEN $2 AND $3
+                "#,
+            )
+            .bind(card.id)
+            .bind(cycle.start_date)
+            .bind(cycle.end_date)
+            .fetch_one(&mut **tx)
+            .await?;
+
+            // 上期未在到期日前全额还款：对上期欠款按日计息
+            let interest = match &last {
+                Some(prev)
+                    if prev.closing_balance > Decimal::ZERO
+                        && prev.paid_by_due.unwrap_or(Decimal::ZERO) < prev.closing_balance =>
+                {
+                    let credits =
+                        credit_rows(&mut **tx, card.account_id, cycle.start_date, cycle.end_date)
+                            .await?;
+                    daily_balance_interest(
+                        prev.closing_balance,
+                        &credits,
+                        cycle.start_date,
+                        cycle.end_date,
+                        card.apr,
+                    )
+                }
+                _ => Decimal::ZERO,
+            };
+            let interest_transaction = if interest > Decimal::ZERO {
+                let id = post_charge(tx, card, cycle.end_date, interest, "信用卡利息").await?;
+                posted.push(id);
+                Some(id)
+            } else {
+                None
+            };
+
+            let closing =
+                opening + charges - installment_converted + installment_principal + interest
+                    - payments;
+            // 当期分期本金与利息、费用一样须全额计入最低还款额
+            let minimum = minimum_due(
+                closing,
+                interest + fees + installment_principal,
+                card.minimum_payment_rate,
+                card.minimum_payment_floor,
+            );
+            let status = statement_status(closing, minimum, Decimal::ZERO, None);
+            let statement = sqlx::query_as::<_, CreditCardStatement>(&format!(
+                r#"
+                INSERT INTO credit_card_statements
+                    (card_id, period_start, period_end, statement_date, due_date,
+                     opening_balance, new_charges, payments, interest_charged, fees_charged,
+                     installment_principal, installment_converted, closing_balance, minimum_due,
+                     status, interest_transaction_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                RETURNING {}
+                "#,
+                STATEMENT_COLUMNS
+            ))
+            .bind(card.id)
+            .bind(cycle.start_date)
+            .bind(cycle.end_date)
+            .bind(cycle.statement_date)
+            .bind(cycle.due_date)
+            .bind(opening)
+            .bind(charges - fees)
+            .bind(payments)
+            .bind(interest)
+            .bind(fees)
+            .bind(installment_principal)
+            .bind(installment_converted)
+            .bind(closing)
+            .bind(minimum)
+            .bind(status.as_str())
+            .bind(interest_transaction)
+            .fetch_one(&mut **tx)
+            .await?;
+
+            statement_date = terms.next_statement_date(statement_date + Duration::days(1));
+            last = Some(statement.clone());
+            generated.push(statement);
+        }
+        Ok(generated)
+    }
+
+    /// 未结清账单的已还金额：账单日之后到下一张账单（或今天）的还款
+    async fn refresh_payments(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        card: &CreditCard,
+        today: NaiveDate,
+    ) -> Result<(), ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.id, s.period_end, s.closing_balance, s.minimum_due, s.paid_by_due,
+                   (SELECT MIN(n.period_end) FROM credit_card_statements n
+                    WHERE n.card_id = s.card_id AND n.statement_date > s.statement_date)
+                       AS next_period_end
+            FROM credit_card_statements s
+            WHERE s.card_id = $1 AND s.status <> 'paid'
+            "#,
+        )
+        .bind(card.id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for row in rows {
+            let period_end: NaiveDate = row.get("period_end");
+            let until = row
+                .get::<Option<NaiveDate>, _>("next_period_end")
+                .unwrap_or(today);
+            let (_, paid) = period_totals(
+                &mut **tx,
+                card.account_id,
+                period_end + Duration::days(1),
+                until,
+            )
+            .await?;
+            let status = statement_status(
+                row.get("closing_balance"),
+                row.get("minimum_due"),
+                paid,
+                row.get("paid_by_due"),
+            );
+            sqlx::query(
+                r#"
+                UPDATE credit_card_statements
+                SET paid_amount = $2, status = $3, updated_at = NOW()
+                WHERE id = $1 AND (paid_amount <> $2 OR status <> $3)
+                "#,
+            )
+            .bind(row.get::<Uuid, _>("id"))
+            .bind(paid)
+            .bind(status.as_str())
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 临近还款日且未全额还款的账单，每张只提醒一次
+    async fn due_reminders(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        card: &CreditCard,
+        today: NaiveDate,
+    ) -> Result<Vec<CreditCardStatement>, ServiceError> {
+        let statements = sqlx::query_as::<_, CreditCardStatement>(&format!(
+            r#"
+            UPDATE credit_card_statements
+            SET reminder_sent_at = NOW()
+            WHERE card_id = $1 AND status = 'open' AND reminder_sent_at IS NULL
+              AND closing_balance > paid_amount
+              AND due_date >= $2 AND due_date - $2 <= $3
+            RETURNING {}
+            "#,
+            STATEMENT_COLUMNS
+        ))
+        .bind(card.id)
+        .bind(today)
+        .bind(card.reminder_days_before as i32)
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(statements)
+    }
+}
+
+/// 卡账户在 [from, to] 内的消费与还款（均为正数）
+async fn period_totals<'e, E>(
+    executor: E,
+    account_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(Decimal, Decimal), ServiceError>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let row = sqlx::query(
+        r#"
+        SELECT
+            COALESCE(SUM(amount) FILTER (WHERE transaction_type <> 'income'), 0) AS charges,
+            COALESCE(SUM(amount) FILTER (WHERE transaction_type = 'income'), 0) AS payments
+        FROM transactions
+        WHERE account_id = $1 AND deleted_at IS NULL
+          AND transaction_date BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(account_id)
+    .bind(from)
+    .bind(to)
+    .fetch_one(executor)
+    .await?;
+    Ok((row.get("charges"), row.get("payments")))
+}
+
+/// [from, to] 内的每笔还款
+async fn credit_rows<'e, E>(
+    executor: E,
+    account_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<(NaiveDate, Decimal)>, ServiceError>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let rows = sqlx::query(
+        r#"
+        SELECT transaction_date, amount
+        FROM transactions
+        WHERE account_id = $1 AND deleted_at IS NULL AND transaction_type = 'income'
+          AND transaction_date BETWEEN $2 AND $3
+        ORDER BY transaction_date
+        "#,
+    )
+    .bind(account_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(executor)
+    .await?;
+    Ok(rows
+        .iter()
+        .map(|row| (row.get("transaction_date"), row.get("amount")))
+        .collect())
+}
+
+/// 由当前欠款倒推 `date` 日终的欠款
+async fn debt_at<'e, E>(
+    executor: E,
+    account_id: Uuid,
+    current_debt: Decimal,
+    date: NaiveDate,
+) -> Result<Decimal, ServiceError>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let later: Decimal = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(CASE WHEN transaction_type = 'income' THEN -amount ELSE amount END), 0)
+        FROM transactions
+        WHERE account_id = $1 AND deleted_at IS NULL AND transaction_date > $2
+        "#,
+    )
+    .bind(account_id)
+    .bind(date)
+    .fetch_one(executor)
+    .await?;
+    Ok(current_debt - later)
+}
+
+/// `date` 日终计入账单的欠款：账户欠款扣除已转分期但尚未入账的本金
+async fn statement_debt_at(
+    conn: &mut sqlx::PgConnection,
+    account_id: Uuid,
+    current_debt: Decimal,
+    date: NaiveDate,
+) -> Result<Decimal, ServiceError> {
+    let debt = debt_at(&mut *conn, account_id, current_debt, date).await?;
+    let unbilled = unbilled_installments(&mut *conn, account_id, date).await?;
+    Ok(debt - unbilled)
+}
+
+/// 在卡账户上记一笔利息或违约金支出，并同步账户余额
+async fn post_charge(
+    tx: &mut Transaction<'_, Postgres>,
+    card: &CreditCard,
+    date: NaiveDate,
+    amount: Decimal,
+    label: &str,
+) -> Result<Uuid, ServiceError> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (
+            id, ledger_id, account_id, transaction_date, amount, transaction_type,
+            category_name, payee, description, status, created_by, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, 'expense', $6, $7, $6, 'completed', $8, NOW(), NOW())
+        "#,
+    )
+    .bind(id)
+    .bind(card.ledger_id)
+    .bind(card.account_id)
+    .bind(date)
+    .bind(amount)
+    .bind(label)
+    .bind(card.bank_name.as_deref().unwrap_or(&card.account_name))
+    .bind(card.created_by)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "UPDATE accounts SET current_balance = current_balance - $1, updated_at = NOW() WHERE id = $2",
+    )
+    .bind(amount)
+    .bind(card.account_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(id)
+}
+
+fn card_label(card: &CreditCard) -> String {
+    match &card.card_number_last4 {
+        Some(last4) => format!("{}（尾号 {}）", card.account_name, last4),
+        None => card.account_name.clone(),
+    }
+}
+
+fn notification(
+    card: &CreditCard,
+    statement: &CreditCardStatement,
+    kind: &str,
+    priority: NotificationPriority,
+    title: String,
+    body: String,
+) -> NewNotification {
+    NewNotification {
+        user_id: card.created_by,
+        family_id: Some(card.family_id),
+        kind: kind.to_string(),
+        priority,
+        title,
+        body,
+        data: serde_json::json!({
+            "card_id": card.id,
+            "account_id": card.account_id,
+            "statement_id": statement.id,
+            "statement_date": statement.statement_date,
+            "due_date": statement.due_date,
+            "closing_balance": statement.closing_balance,
+            "minimum_due": statement.minimum_due,
+            "paid_amount": statement.paid_amount,
+        }),
+        action_url: None,
+    }
+}
+
+fn statement_notification(
+    card: &CreditCard,
+    statement: &CreditCardStatement,
+) -> Option<NewNotification> {
+    (statement.closing_balance > Decimal::ZERO).then(|| {
+        notification(
+            card,
+            statement,
+            "credit_card_statement",
+            NotificationPriority::Normal,
+            format!("{} 已出账", card_label(card)),
+            format!(
+                "本期应还 {}，最低还款 {}，到期还款日 {}",
+                statement.closing_balance, statement.minimum_due, statement.due_date
+            ),
+        )
+    })
+}
+
+fn reminder_notification(card: &CreditCard, statement: &CreditCardStatement) -> NewNotification {
+    notification(
+        card,
+        statement,
+        "credit_card_due",
+        NotificationPriority::High,
+        format!("{} 还款提醒", card_label(card)),
+        format!(
+            "{} 到期，尚需还款 {}（最低还款 {}）",
+            statement.due_date,
+            statement.closing_balance - statement.paid_amount,
+            statement.minimum_due
+        ),
+    )
+}
+
+fn overdue_notification(card: &CreditCard, statement: &CreditCardStatement) -> NewNotification {
+    notification(
+        card,
+        statement,
+        "credit_card_overdue",
+        NotificationPriority::High,
+        format!("{} 已逾期", card_label(card)),
+        format!(
+            "{} 到期的账单未还足最低还款额，已产生违约金 {}",
+            statement.due_date, statement.late_fee
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn terms(bill_day: u32, in_previous: bool) -> BillingTerms {
+        BillingTerms {
+            bill_day,
+            bill_calculation_in_previous_period: in_previous,
+            payment_date_type: PaymentDateType::FixedDate,
+            payment_day: Some(5),
+            payment_days_after_bill: None,
+        }
+    }
+
+    #[test]
+    fn test_billing_cycle() {
+        let t = terms(15, false);
+        let cycle = t.cycle_containing(date(2025, 3, 15));
+        // 账单日当天的消费计入下一期
+        assert_eq!(cycle.statement_date, date(2025, 4, 15));
+        assert_eq!(cycle.start_date, date(2025, 3, 15));
+        assert_eq!(cycle.end_date, date(2025, 4, 14));
+        assert_eq!(cycle.due_date, date(2025, 5, 5));
+
+        let t = terms(15, true);
+        let cycle = t.cycle_containing(date(2025, 3, 15));
+        assert_eq!(cycle.statement_date, date(2025, 3, 15));
+        assert_eq!(cycle.start_date, date(2025, 2, 16));
+        assert_eq!(cycle.end_date, date(2025, 3, 15));
+
+        // 31 日出账：小月取月末
+        let t = terms(31, true);
+        let cycle = t.cycle_containing(date(2025, 2, 10));
+        assert_eq!(cycle.statement_date, date(2025, 2, 28));
+        assert_eq!(cycle.start_date, date(2025, 2, 1));
+        assert_eq!(t.cycle(date(2025, 3, 31)).start_date, date(2025, 3, 1));
+
+        let after = BillingTerms {
+            payment_date_type: PaymentDateType::DaysAfterBill,
+            payment_days_after_bill: Some(20),
+            ..terms(25, true)
+        };
+        assert_eq!(after.due_date(date(2025, 12, 25)), date(2026, 1, 14));
+    }
+
+    #[test]
+    fn test_minimum_due_and_late_fee() {
+        // 本金 1000 × 10% + 利息费用 30
+        assert_eq!(
+            minimum_due(dec!(1030), dec!(30), dec!(0.1), Decimal::ZERO),
+            dec!(130)
+        );
+        assert_eq!(
+            minimum_due(dec!(50), Decimal::ZERO, dec!(0.1), dec!(100)),
+            dec!(50)
+        );
+        assert_eq!(
+            minimum_due(dec!(-10), Decimal::ZERO, dec!(0.1), dec!(100)),
+            Decimal::ZERO
+        );
+
+        assert_eq!(
+            late_fee(dec!(130), dec!(30), dec!(10), dec!(0.05)),
+            dec!(15)
+        );
+        assert_eq!(
+            late_fee(dec!(130), dec!(130), dec!(10), dec!(0.05)),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_daily_balance_interest() {
+        // 1000 元计息 10 天，第 6 天还 600
+        let interest = daily_balance_interest(
+            dec!(1000),
+            &[(date(2025, 1, 6), dec!(600))],
+            date(2025, 1, 1),
+            date(2025, 1, 10),
+            dec!(0.1825),
+        );
+        // (5 × 1000 + 5 × 400) × 0.0005
+        assert_eq!(interest, dec!(3.5));
+
+        assert_eq!(
+            statement_status(dec!(1000), dec!(100), dec!(100), Some(dec!(100))),
+            StatementStatus::MinimumPaid
+        );
+        assert_eq!(
+            statement_status(dec!(1000), dec!(100), dec!(50), Some(dec!(50))),
+            StatementStatus::Overdue
+        );
+        assert_eq!(
+            statement_status(dec!(1000), dec!(100), dec!(1000), None),
+            StatementStatus::Paid
+        );
+    }
+}
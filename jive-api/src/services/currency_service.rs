@@ -5,12 +5,18 @@ use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use super::rate_graph::{RateGraph, ResolvedRate};
+use super::transaction_valuation_service::{
+    TransactionValuationService, ValuationReason, ValuationScope,
+};
 use super::ServiceError;
+use crate::config::RateResolverConfig;
 // remove duplicate import of NaiveDate
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Currency {
     pub code: String,
     pub name: String,
@@ -19,7 +25,7 @@ pub struct Currency {
     pub is_active: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ExchangeRate {
     pub id: Uuid,
     pub from_currency: String,
@@ -30,14 +36,14 @@ pub struct ExchangeRate {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CurrencyPreference {
     pub currency_code: String,
     pub is_primary: bool,
     pub display_order: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct FamilyCurrencySettings {
     pub family_id: Uuid,
     pub base_currency: String,
@@ -46,7 +52,7 @@ pub struct FamilyCurrencySettings {
     pub supported_currencies: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCurrencySettingsRequest {
     pub base_currency: Option<String>,
     pub allow_multi_currency: Option<bool>,
@@ -54,7 +60,7 @@ pub struct UpdateCurrencySettingsRequest {
     pub supported_currencies: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AddExchangeRateRequest {
     pub from_currency: String,
     pub to_currency: String,
@@ -65,13 +71,13 @@ pub struct AddExchangeRateRequest {
     pub manual_rate_expiry: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ClearManualRateRequest {
     pub from_currency: String,
     pub to_currency: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ClearManualRatesBatchRequest {
     pub from_currency: String,
     pub to_currencies: Option<Vec<String>>, // if None -> all target currencies
@@ -236,12 +242,34 @@ impl CurrencyService {
         }
     }
 
+    /// 家庭本位币：货币设置优先，未设置时取家庭资料中的默认货币（families.currency）
+    pub async fn family_base_currency(&self, family_id: Uuid) -> Result<String, ServiceError> {
+        let currency: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            SELECT UPPER(COALESCE(fcs.base_currency, f.currency))
+            FROM families f
+            LEFT JOIN family_currency_settings fcs ON fcs.family_id = f.id
+            WHERE f.id = $1
+            "#,
+        )
+        .bind(family_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        currency
+            .ok_or_else(|| ServiceError::not_found("Family", family_id))?
+            .ok_or_else(|| ServiceError::validation("家庭未设置默认货币"))
+    }
+
     /// 更新家庭的货币设置
     pub async fn update_family_currency_settings(
         &self,
         family_id: Uuid,
         request: UpdateCurrencySettingsRequest,
     ) -> Result<FamilyCurrencySettings, ServiceError> {
+        let previous_base = self
+            .get_family_currency_settings(family_id)
+            .await?
+            .base_currency;
         let mut tx = self.pool.begin().await?;
 
         // 插入或更新设置
@@ -267,7 +295,33 @@ impl CurrencyService {
 
         tx.commit().await?;
 
-        self.get_family_currency_settings(family_id).await
+        let settings = self.get_family_currency_settings(family_id).await?;
+        if !settings.base_currency.eq_ignore_ascii_case(&previous_base) {
+            // 本位币变化后重算该家庭全部交易的本位币金额
+            self.enqueue_valuation(
+                ValuationReason::BaseCurrencyChanged,
+                ValuationScope {
+                    family_id: Some(family_id),
+                    ..Default::default()
+                },
+            )
+            .await;
+        }
+        Ok(settings)
+    }
+
+    /// 登记交易本位币重算任务；失败只记录日志，不影响当前操作
+    async fn enqueue_valuation(&self, reason: ValuationReason, scope: ValuationScope) {
+        if let Err(e) = TransactionValuationService::new(self.pool.clone())
+            .enqueue(reason, scope)
+            .await
+        {
+            tracing::warn!(
+                "Failed to enqueue {} valuation job: {:?}",
+                reason.as_str(),
+                e
+            );
+        }
     }
 
     /// 获取汇率
@@ -276,9 +330,10 @@ impl CurrencyService {
         from_currency: &'a str,
         to_currency: &'a str,
         date: Option<NaiveDate>,
+        config: &'a RateResolverConfig,
     ) -> Pin<Box<dyn Future<Output = Result<Decimal, ServiceError>> + Send + 'a>> {
         Box::pin(async move {
-            self.get_exchange_rate_impl(from_currency, to_currency, date)
+            self.get_exchange_rate_impl(from_currency, to_currency, date, config)
                 .await
         })
     }
@@ -288,71 +343,37 @@ impl CurrencyService {
         from_currency: &str,
         to_currency: &str,
         date: Option<NaiveDate>,
+        config: &RateResolverConfig,
     ) -> Result<Decimal, ServiceError> {
         if from_currency == to_currency {
             return Ok(Decimal::ONE);
         }
+        Ok(self
+            .resolve_exchange_rate(from_currency, to_currency, date, config)
+            .await?
+            .rate)
+    }
 
-        let effective_date = date.unwrap_or_else(|| Utc::now().date_naive());
-
-        // 尝试直接获取汇率
-        let rate = sqlx::query_scalar!(
-            r#"
-            SELECT rate
-            FROM exchange_rates
-            WHERE from_currency = $1 
-            AND to_currency = $2
-            AND effective_date <= $3
-            ORDER BY effective_date DESC
-            LIMIT 1
-            "#,
-            from_currency,
-            to_currency,
-            effective_date
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(rate) = rate {
-            return Ok(rate);
-        }
-
-        // 尝试获取反向汇率
-        let reverse_rate = sqlx::query_scalar!(
-            r#"
-            SELECT rate
-            FROM exchange_rates
-            WHERE from_currency = $2 
-            AND to_currency = $1
-            AND effective_date <= $3
-            ORDER BY effective_date DESC
-            LIMIT 1
-            "#,
-            from_currency,
-            to_currency,
-            effective_date
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(rate) = reverse_rate {
-            return Ok(Decimal::ONE / rate);
-        }
-
-        // 尝试通过USD中转（最常见的中转货币）
-        let from_to_usd =
-            Box::pin(self.get_exchange_rate_impl(from_currency, "USD", Some(effective_date))).await;
-        let usd_to_target =
-            Box::pin(self.get_exchange_rate_impl("USD", to_currency, Some(effective_date))).await;
-
-        if let (Ok(rate1), Ok(rate2)) = (from_to_usd, usd_to_target) {
-            return Ok(rate1 * rate2);
-        }
+    /// 加载某一日期可用的全部汇率，用于批量换算
+    pub async fn load_rate_graph(
+        &self,
+        date: Option<NaiveDate>,
+        config: &RateResolverConfig,
+    ) -> Result<RateGraph, ServiceError> {
+        let as_of = date.unwrap_or_else(|| Utc::now().date_naive());
+        RateGraph::load(&self.pool, as_of, config).await
+    }
 
-        Err(ServiceError::NotFound {
-            resource_type: "ExchangeRate".to_string(),
-            id: format!("{}-{}", from_currency, to_currency),
-        })
+    /// 获取汇率及换算路径（可经任意中间货币，取段数最少、最新的路径）
+    pub async fn resolve_exchange_rate(
+        &self,
+        from_currency: &str,
+        to_currency: &str,
+        date: Option<NaiveDate>,
+        config: &RateResolverConfig,
+    ) -> Result<ResolvedRate, ServiceError> {
+        let graph = self.load_rate_graph(date, config).await?;
+        graph.resolve_with(from_currency, to_currency, config)
     }
 
     /// 批量获取汇率
@@ -361,12 +382,14 @@ impl CurrencyService {
         base_currency: &str,
         target_currencies: Vec<String>,
         date: Option<NaiveDate>,
+        config: &RateResolverConfig,
     ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let graph = self.load_rate_graph(date, config).await?;
         let mut rates = HashMap::new();
 
         for currency in target_currencies {
-            if let Ok(rate) = self.get_exchange_rate(base_currency, &currency, date).await {
-                rates.insert(currency, rate);
+            if let Ok(resolved) = graph.resolve_with(base_currency, &currency, config) {
+                rates.insert(currency, resolved.rate);
             }
         }
 
@@ -410,6 +433,20 @@ impl CurrencyService {
         .fetch_one(&self.pool)
         .await?;
 
+        // 手动修正汇率后重算生效日起涉及该币种对的交易
+        self.enqueue_valuation(
+            ValuationReason::RateCorrected,
+            ValuationScope {
+                currencies: Some(vec![
+                    request.from_currency.clone(),
+                    request.to_currency.clone(),
+                ]),
+                since_date: Some(effective_date),
+                ..Default::default()
+            },
+        )
+        .await;
+
         Ok(ExchangeRate {
             id: rec.get("id"),
             from_currency: rec.get("from_currency"),
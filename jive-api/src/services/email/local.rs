@@ -0,0 +1,106 @@
+//! 本地发送后端（开发 / 测试）
+
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+use super::{EmailMessage, Mailer};
+use crate::services::ServiceError;
+
+/// 将邮件写成 .eml 文件，便于用邮件客户端直接打开检查
+pub struct FileMailer {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>, from: &str) -> Self {
+        Self {
+            dir: dir.into(),
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<(), ServiceError> {
+        let raw = message.to_message(&self.from)?.formatted();
+        let file_name = format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            uuid::Uuid::new_v4().simple()
+        );
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|_| ServiceError::InternalError)?;
+        tokio::fs::write(self.dir.join(&file_name), raw)
+            .await
+            .map_err(|_| ServiceError::InternalError)?;
+
+        tracing::debug!(to = %message.to, file = %file_name, "email written to file");
+        Ok(())
+    }
+}
+
+/// 直接打印到标准输出
+pub struct StdoutMailer {
+    from: String,
+}
+
+impl StdoutMailer {
+    pub fn new(from: &str) -> Self {
+        Self {
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for StdoutMailer {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<(), ServiceError> {
+        println!(
+            "=== EMAIL ===\nFrom: {}\nTo: {}\nSubject: {}\n\n{}\n=============",
+            self.from, message.to, message.subject, message.text_body
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::email::{EmailLocale, EmailTemplate};
+
+    #[tokio::test]
+    async fn test_file_mailer_writes_eml() {
+        let dir = std::env::temp_dir().join(format!("jive-mail-{}", uuid::Uuid::new_v4()));
+        let mailer = FileMailer::new(&dir, "Jive Money <noreply@jive.money>");
+        let rendered = EmailTemplate::VerificationCode {
+            code: "1234".to_string(),
+            operation: "delete_family".to_string(),
+            expires_minutes: 5,
+        }
+        .render(EmailLocale::En);
+
+        mailer
+            .send(&EmailMessage::new("alice@example.com", rendered))
+            .await
+            .unwrap();
+
+        let mut entries = std::fs::read_dir(&dir).unwrap();
+        let path = entries.next().unwrap().unwrap().path();
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("To: alice@example.com"));
+        assert!(raw.contains("1234"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,102 @@
+//! 邮件发送子系统
+//!
+//! - `Mailer`：发送后端抽象（SMTP / 本地文件 / 标准输出）
+//! - `templates`：多语言（zh-CN / en）邮件模板
+//! - `outbox`：持久化发件箱，业务代码只负责入队，由定时任务带重试地投递
+
+pub mod local;
+pub mod outbox;
+pub mod smtp;
+pub mod templates;
+
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::Message;
+use std::sync::Arc;
+
+use crate::config::{EmailBackend, EmailConfig};
+use crate::services::ServiceError;
+
+pub use local::{FileMailer, StdoutMailer};
+pub use outbox::EmailOutbox;
+pub use smtp::SmtpMailer;
+pub use templates::{EmailLocale, EmailTemplate, RenderedEmail};
+
+/// 待发送邮件
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+}
+
+impl EmailMessage {
+    pub fn new(to: impl Into<String>, rendered: RenderedEmail) -> Self {
+        Self {
+            to: to.into(),
+            subject: rendered.subject,
+            text_body: rendered.text,
+            html_body: Some(rendered.html),
+        }
+    }
+
+    /// 构建 RFC 5322 邮件
+    pub fn to_message(&self, from: &str) -> Result<Message, ServiceError> {
+        let from: Mailbox = from
+            .parse()
+            .map_err(|e| ServiceError::ValidationError(format!("无效的发件人地址: {}", e)))?;
+        let to: Mailbox = self
+            .to
+            .parse()
+            .map_err(|e| ServiceError::ValidationError(format!("无效的收件人地址: {}", e)))?;
+
+        let builder = Message::builder().from(from).to(to).subject(&self.subject);
+        let message = match &self.html_body {
+            Some(html) => builder.multipart(
+                MultiPart::alternative()
+                    .singlepart(
+                        SinglePart::builder()
+                            .header(ContentType::TEXT_PLAIN)
+                            .body(self.text_body.clone()),
+                    )
+                    .singlepart(
+                        SinglePart::builder()
+                            .header(ContentType::TEXT_HTML)
+                            .body(html.clone()),
+                    ),
+            ),
+            None => builder
+                .header(ContentType::TEXT_PLAIN)
+                .body(self.text_body.clone()),
+        };
+
+        message.map_err(|e| ServiceError::ValidationError(format!("邮件构建失败: {}", e)))
+    }
+}
+
+/// 邮件发送后端
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// 后端名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 发送单封邮件；返回错误时由 outbox 负责重试
+    async fn send(&self, message: &EmailMessage) -> Result<(), ServiceError>;
+}
+
+/// 根据配置创建发送后端；`Disabled` 时返回 None
+pub fn build_mailer(config: &EmailConfig) -> Result<Option<Arc<dyn Mailer>>, ServiceError> {
+    let mailer: Arc<dyn Mailer> = match config.backend {
+        EmailBackend::Smtp => Arc::new(SmtpMailer::new(config)?),
+        EmailBackend::File => Arc::new(FileMailer::new(&config.file_dir, &config.from)),
+        EmailBackend::Stdout => Arc::new(StdoutMailer::new(&config.from)),
+        EmailBackend::Disabled => return Ok(None),
+    };
+    Ok(Some(mailer))
+}
+
+/// 占位邮箱（无邮箱注册的用户）不投递
+pub fn is_deliverable(address: &str) -> bool {
+    address.contains('@') && !address.ends_with("@noemail.local")
+}
@@ -0,0 +1,278 @@
+//! 持久化发件箱
+//!
+//! 业务代码调用 `enqueue` 写入 email_outbox（入队时即完成渲染），
+//! 定时任务调用 `process_due` 批量投递；失败按指数退避重试，
+//! 超过最大次数标记为 failed。进程重启不会丢信。
+//! 含一次性验证码的邮件在发送或失败后清空主题与正文，过期未发出的直接作废。
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::{is_deliverable, EmailLocale, EmailMessage, EmailTemplate, Mailer};
+use crate::config::EmailConfig;
+use crate::services::ServiceError;
+
+/// 首次重试间隔（秒）
+const RETRY_BASE_SECS: u64 = 30;
+/// 重试间隔上限（秒）
+const RETRY_MAX_SECS: u64 = 6 * 60 * 60;
+/// sending 状态超过该时长视为投递进程已崩溃，可被重新领取
+const STALE_LOCK_SECS: i64 = 10 * 60;
+/// 清空含验证码邮件的主题与正文
+const REDACT_SECRET: &str = "subject = CASE WHEN contains_secret THEN '' ELSE subject END, \
+     text_body = CASE WHEN contains_secret THEN '' ELSE text_body END, \
+     html_body = CASE WHEN contains_secret THEN NULL ELSE html_body END";
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OutboxEmail {
+    pub id: Uuid,
+    pub to_address: String,
+    pub template: String,
+    pub locale: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 一次投递批次的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OutboxRunStats {
+    pub sent: usize,
+    pub retried: usize,
+    pub failed: usize,
+}
+
+#[derive(Clone)]
+pub struct EmailOutbox {
+    pool: PgPool,
+    /// 收件人没有语言偏好时使用
+    default_locale: String,
+}
+
+impl EmailOutbox {
+    pub fn new(pool: PgPool, config: &EmailConfig) -> Self {
+        Self {
+            pool,
+            default_locale: config.default_locale.clone(),
+        }
+    }
+
+    /// 语言标签对应的邮件语言，未设置时使用配置默认值
+    pub fn locale_or_default(&self, tag: Option<&str>) -> EmailLocale {
+        EmailLocale::from_tag(tag.unwrap_or(&self.default_locale))
+    }
+
+    /// 渲染并入队；占位邮箱直接跳过并返回 None
+    pub async fn enqueue(
+        &self,
+        to: &str,
+        template: &EmailTemplate,
+        locale: EmailLocale,
+    ) -> Result<Option<Uuid>, ServiceError> {
+        if !is_deliverable(to) {
+            tracing::debug!(to = %to, template = template.kind(), "skip undeliverable address");
+            return Ok(None);
+        }
+
+        let rendered = template.render(locale);
+        let secret_ttl = template.secret_ttl();
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO email_outbox (
+                id, to_address, template, locale, subject, text_body, html_body,
+                contains_secret, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW() + make_interval(secs => $9))
+            "#,
+        )
+        .bind(id)
+        .bind(to)
+        .bind(template.kind())
+        .bind(locale.as_tag())
+        .bind(&rendered.subject)
+        .bind(&rendered.text)
+        .bind(&rendered.html)
+        .bind(secret_ttl.is_some())
+        .bind(secret_ttl.map(|ttl| ttl.as_secs() as f64))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Some(id))
+    }
+
+    /// 按收件人所在家庭的语言设置入队
+    pub async fn enqueue_for_user(
+        &self,
+        to: &str,
+        template: &EmailTemplate,
+    ) -> Result<Option<Uuid>, ServiceError> {
+        let locale = self.locale_for_email(to).await?;
+        self.enqueue(to, template, locale).await
+    }
+
+    /// 查询收件人语言：家庭 locale，找不到时使用配置默认值
+    pub async fn locale_for_email(&self, email: &str) -> Result<EmailLocale, ServiceError> {
+        let locale: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT f.locale
+            FROM users u
+            JOIN family_members fm ON fm.user_id = u.id
+            JOIN families f ON f.id = fm.family_id
+            WHERE LOWER(u.email) = LOWER($1) AND f.locale IS NOT NULL
+            ORDER BY fm.joined_at
+            LIMIT 1
+            "#,
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(self.locale_or_default(locale.as_deref()))
+    }
+
+    /// 领取到期邮件并投递
+    pub async fn process_due(
+        &self,
+        mailer: &dyn Mailer,
+        batch_size: i64,
+        max_attempts: u32,
+    ) -> Result<OutboxRunStats, ServiceError> {
+        let expired = self.expire_secrets().await?;
+
+        let claimed = sqlx::query_as::<_, OutboxEmail>(
+            r#"
+            UPDATE email_outbox
+            SET status = 'sending', locked_at = NOW(), updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM email_outbox
+                WHERE (status = 'pending' AND next_attempt_at <= NOW())
+                   OR (status = 'sending' AND locked_at < NOW() - make_interval(secs => $2))
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, to_address, template, locale, subject, text_body, html_body,
+                      status, attempts, last_error, next_attempt_at, created_at
+            "#,
+        )
+        .bind(batch_size)
+        .bind(STALE_LOCK_SECS as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = OutboxRunStats {
+            failed: expired,
+            ..Default::default()
+        };
+        for email in claimed {
+            let message = EmailMessage {
+                to: email.to_address.clone(),
+                subject: email.subject.clone(),
+                text_body: email.text_body.clone(),
+                html_body: email.html_body.clone(),
+            };
+
+            match mailer.send(&message).await {
+                Ok(()) => {
+                    sqlx::query(&format!(
+                        r#"
+                        UPDATE email_outbox
+                        SET status = 'sent', attempts = attempts + 1, sent_at = NOW(),
+                            locked_at = NULL, last_error = NULL, updated_at = NOW(),
+                            {REDACT_SECRET}
+                        WHERE id = $1
+                        "#
+                    ))
+                    .bind(email.id)
+                    .execute(&self.pool)
+                    .await?;
+                    stats.sent += 1;
+                }
+                Err(e) => {
+                    let attempts = email.attempts.max(0) as u32 + 1;
+                    let exhausted = attempts >= max_attempts;
+                    let delay = retry_delay(attempts);
+                    tracing::warn!(
+                        id = %email.id, to = %email.to_address, attempts, exhausted,
+                        backend = mailer.name(), error = %e, "email delivery failed"
+                    );
+                    sqlx::query(
+                        r#"
+                        UPDATE email_outbox
+                        SET status = $2, attempts = $3, last_error = $4, locked_at = NULL,
+                            next_attempt_at = NOW() + make_interval(secs => $5),
+                            updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(email.id)
+                    .bind(if exhausted { "failed" } else { "pending" })
+                    .bind(attempts as i32)
+                    .bind(e.to_string())
+                    .bind(delay.as_secs() as f64)
+                    .execute(&self.pool)
+                    .await?;
+                    if exhausted {
+                        stats.failed += 1;
+                    } else {
+                        stats.retried += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// 作废过期仍未发出的验证码邮件，并清空所有已结束的验证码邮件正文；返回作废条数
+    async fn expire_secrets(&self) -> Result<usize, ServiceError> {
+        let expired: Vec<bool> = sqlx::query_scalar(&format!(
+            r#"
+            UPDATE email_outbox
+            SET status = CASE WHEN status IN ('pending', 'sending') THEN 'failed' ELSE status END,
+                last_error = CASE WHEN status IN ('pending', 'sending') THEN 'expired'
+                                  ELSE last_error END,
+                locked_at = NULL, updated_at = NOW(),
+                {REDACT_SECRET}
+            WHERE contains_secret AND text_body <> ''
+              AND (expires_at <= NOW() OR status IN ('sent', 'failed'))
+            RETURNING status = 'failed' AND last_error = 'expired'
+            "#
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(expired.into_iter().filter(|e| *e).count())
+    }
+}
+
+/// 第 n 次失败后的重试间隔：30s、60s、120s ... 上限 6 小时
+pub fn retry_delay(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(20);
+    Duration::from_secs(
+        RETRY_BASE_SECS
+            .saturating_mul(1u64 << exponent)
+            .min(RETRY_MAX_SECS),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(4), Duration::from_secs(240));
+        assert_eq!(retry_delay(30), Duration::from_secs(RETRY_MAX_SECS));
+    }
+}
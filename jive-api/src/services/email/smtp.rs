@@ -0,0 +1,62 @@
+//! SMTP 发送后端
+
+use async_trait::async_trait;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+use super::{EmailMessage, Mailer};
+use crate::config::{EmailConfig, SmtpTls};
+use crate::services::ServiceError;
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &EmailConfig) -> Result<Self, ServiceError> {
+        let builder = match config.smtp_tls {
+            SmtpTls::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
+            }
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.smtp_host,
+            )),
+        }
+        .map_err(|e| ServiceError::ExternalApi {
+            message: format!("SMTP 配置错误: {}", e),
+        })?
+        .port(config.smtp_port);
+
+        let builder = match (&config.smtp_username, &config.smtp_password) {
+            (Some(user), Some(pass)) => {
+                builder.credentials(Credentials::new(user.clone(), pass.clone()))
+            }
+            _ => builder,
+        };
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<(), ServiceError> {
+        let email = message.to_message(&self.from)?;
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| ServiceError::ExternalApi {
+                message: format!("SMTP 发送失败: {}", e),
+            })?;
+        Ok(())
+    }
+}
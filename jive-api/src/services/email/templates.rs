@@ -0,0 +1,708 @@
+//! 邮件模板（zh-CN / en）
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::services::financial_digest_service::{
+    percent_change, signed, DigestPeriod, DigestReport,
+};
+
+/// 邮件语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailLocale {
+    ZhCn,
+    En,
+}
+
+impl EmailLocale {
+    /// 从 "zh-CN" / "en-US" / "zh_TW" 等语言标签解析，未知语言回退到英文
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.trim().to_ascii_lowercase().starts_with("zh") {
+            Self::ZhCn
+        } else {
+            Self::En
+        }
+    }
+
+    pub fn as_tag(&self) -> &'static str {
+        match self {
+            Self::ZhCn => "zh-CN",
+            Self::En => "en",
+        }
+    }
+}
+
+/// 渲染结果
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// 邮件模板
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+    /// 敏感操作验证码
+    VerificationCode {
+        code: String,
+        operation: String,
+        expires_minutes: u32,
+    },
+    /// 家庭邀请
+    Invitation {
+        inviter_name: String,
+        family_name: String,
+        invite_code: String,
+        expires_at: DateTime<Utc>,
+    },
+    /// 找回密码验证码
+    PasswordReset { code: String, expires_minutes: u32 },
+    /// 预算提醒
+    BudgetAlert {
+        budget_name: String,
+        spent: Decimal,
+        limit: Decimal,
+        currency: String,
+        percentage: f64,
+    },
+    /// 周度 / 月度财务摘要
+    FinancialDigest { report: Box<DigestReport> },
+    /// 账户因多次登录失败被锁定
+    AccountLocked {
+        failed_attempts: u32,
+        locked_seconds: u64,
+        ip: Option<String>,
+    },
+    /// 新设备登录
+    NewDeviceLogin { device: String, ip: Option<String> },
+    /// 新位置登录
+    NewLocationLogin { ip: Option<String> },
+    /// 单条站内通知的邮件副本
+    Notification {
+        title: String,
+        body: String,
+        action_url: Option<String>,
+    },
+    /// 免打扰时段或摘要周期内累积的多条通知
+    NotificationDigest { items: Vec<(String, String)> },
+}
+
+impl EmailTemplate {
+    /// 模板标识（写入 outbox 便于统计排查）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::VerificationCode { .. } => "verification_code",
+            Self::Invitation { .. } => "invitation",
+            Self::PasswordReset { .. } => "password_reset",
+            Self::BudgetAlert { .. } => "budget_alert",
+            Self::FinancialDigest { report } => report.period.kind(),
+            Self::AccountLocked { .. } => "account_locked",
+            Self::NewDeviceLogin { .. } => "new_device_login",
+            Self::NewLocationLogin { .. } => "new_location_login",
+            Self::Notification { .. } => "notification",
+            Self::NotificationDigest { .. } => "notification_digest",
+        }
+    }
+
+    /// 正文含一次性验证码的模板返回验证码有效期；这类邮件发送后不保留正文
+    pub fn secret_ttl(&self) -> Option<std::time::Duration> {
+        match self {
+            Self::VerificationCode {
+                expires_minutes, ..
+            }
+            | Self::PasswordReset {
+                expires_minutes, ..
+            } => Some(std::time::Duration::from_secs(
+                u64::from(*expires_minutes) * 60,
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, locale: EmailLocale) -> RenderedEmail {
+        let zh = locale == EmailLocale::ZhCn;
+        let unknown_ip = if zh { "未知" } else { "unknown" };
+
+        let (subject, lines): (String, Vec<String>) = match self {
+            Self::VerificationCode {
+                code,
+                operation,
+                expires_minutes,
+            } => {
+                if zh {
+                    (
+                        format!("Jive Money 验证码：{}", code),
+                        vec![
+                            format!("您正在进行敏感操作（{}），验证码为：{}", operation, code),
+                            format!("验证码 {} 分钟内有效，请勿泄露给他人。", expires_minutes),
+                            "如非本人操作，请忽略本邮件。".to_string(),
+                        ],
+                    )
+                } else {
+                    (
+                        format!("Your Jive Money verification code: {}", code),
+                        vec![
+                            format!("Your verification code for \"{}\" is: {}", operation, code),
+                            format!(
+                                "The code expires in {} minutes. Never share it with anyone.",
+                                expires_minutes
+                            ),
+                            "If you did not request this, you can ignore this email.".to_string(),
+                        ],
+                    )
+                }
+            }
+            Self::Invitation {
+                inviter_name,
+                family_name,
+                invite_code,
+                expires_at,
+            } => {
+                let expires = expires_at.format("%Y-%m-%d %H:%M UTC");
+                if zh {
+                    (
+                        format!("{} 邀请您加入「{}」", inviter_name, family_name),
+                        vec![
+                            format!(
+                                "{} 邀请您加入 Jive Money 家庭「{}」，一起管理账本。",
+                                inviter_name, family_name
+                            ),
+                            format!("邀请码：{}", invite_code),
+                            format!("邀请将于 {} 过期。", expires),
+                        ],
+                    )
+                } else {
+                    (
+                        format!("{} invited you to join \"{}\"", inviter_name, family_name),
+                        vec![
+                            format!(
+                                "{} invited you to join the Jive Money family \"{}\".",
+                                inviter_name, family_name
+                            ),
+                            format!("Invite code: {}", invite_code),
+                            format!("This invitation expires at {}.", expires),
+                        ],
+                    )
+                }
+            }
+            Self::PasswordReset {
+                code,
+                expires_minutes,
+            } => {
+                if zh {
+                    (
+                        "Jive Money 重置密码".to_string(),
+                        vec![
+                            format!("您的重置密码验证码为：{}", code),
+                            format!("验证码 {} 分钟内有效，且只能使用一次。", expires_minutes),
+                            "如非本人操作，请忽略本邮件，您的密码不会被修改。".to_string(),
+                        ],
+                    )
+                } else {
+                    (
+                        "Reset your Jive Money password".to_string(),
+                        vec![
+                            format!("Your password reset code is: {}", code),
+                            format!(
+                                "The code expires in {} minutes and can only be used once.",
+                                expires_minutes
+                            ),
+                            "If you did not request a reset, ignore this email; your password will not change.".to_string(),
+                        ],
+                    )
+                }
+            }
+            Self::BudgetAlert {
+                budget_name,
+                spent,
+                limit,
+                currency,
+                percentage,
+            } => {
+                if zh {
+                    (
+                        format!("预算提醒：「{}」已使用 {:.0}%", budget_name, percentage),
+                        vec![
+                            format!(
+                                "预算「{}」已支出 {} {}，预算额度 {} {}（{:.1}%）。",
+                                budget_name, spent, currency, limit, currency, percentage
+                            ),
+                            "请留意后续支出。".to_string(),
+                        ],
+                    )
+                } else {
+                    (
+                        format!("Budget alert: \"{}\" is at {:.0}%", budget_name, percentage),
+                        vec![
+                            format!(
+                                "You have spent {} {} of your {} {} budget \"{}\" ({:.1}%).",
+                                spent, currency, limit, currency, budget_name, percentage
+                            ),
+                            "Keep an eye on upcoming expenses.".to_string(),
+                        ],
+                    )
+                }
+            }
+            Self::FinancialDigest { report } => render_financial_digest(report, zh),
+            Self::AccountLocked {
+                failed_attempts,
+                locked_seconds,
+                ip,
+            } => {
+                let ip = ip.as_deref().unwrap_or(unknown_ip);
+                if zh {
+                    (
+                        "账户已被临时锁定".to_string(),
+                        vec![
+                            format!(
+                                "您的账户连续 {} 次登录失败，已锁定 {} 秒。来源 IP：{}。",
+                                failed_attempts, locked_seconds, ip
+                            ),
+                            "如非本人操作，请尽快修改密码。".to_string(),
+                        ],
+                    )
+                } else {
+                    (
+                        "Your account has been temporarily locked".to_string(),
+                        vec![
+                            format!(
+                                "After {} failed sign-in attempts your account is locked for {} seconds. Source IP: {}.",
+                                failed_attempts, locked_seconds, ip
+                            ),
+                            "If this wasn't you, change your password as soon as possible.".to_string(),
+                        ],
+                    )
+                }
+            }
+            Self::NewDeviceLogin { device, ip } => {
+                let ip = ip.as_deref().unwrap_or(unknown_ip);
+                if zh {
+                    (
+                        "新设备登录提醒".to_string(),
+                        vec![
+                            format!("您的账户刚刚在新设备上登录：{}（IP：{}）。", device, ip),
+                            "如非本人操作，请立即修改密码。".to_string(),
+                        ],
+                    )
+                } else {
+                    (
+                        "New device sign-in".to_string(),
+                        vec![
+                            format!("Your account was just used to sign in on a new device: {} (IP: {}).", device, ip),
+                            "If this wasn't you, change your password immediately.".to_string(),
+                        ],
+                    )
+                }
+            }
+            Self::NewLocationLogin { ip } => {
+                let ip = ip.as_deref().unwrap_or(unknown_ip);
+                if zh {
+                    (
+                        "异地登录提醒".to_string(),
+                        vec![
+                            format!("您的账户刚刚从新的网络位置登录（IP：{}）。", ip),
+                            "如非本人操作，请立即修改密码。".to_string(),
+                        ],
+                    )
+                } else {
+                    (
+                        "Sign-in from a new location".to_string(),
+                        vec![
+                            format!("Your account was just used to sign in from a new network location (IP: {}).", ip),
+                            "If this wasn't you, change your password immediately.".to_string(),
+                        ],
+                    )
+                }
+            }
+            Self::Notification {
+                title,
+                body,
+                action_url,
+            } => {
+                let mut lines = vec![body.clone()];
+                if let Some(url) = action_url {
+                    lines.push(if zh {
+                        format!("查看详情：{}", url)
+                    } else {
+                        format!("View details: {}", url)
+                    });
+                }
+                (format!("Jive Money：{}", title), lines)
+            }
+            Self::NotificationDigest { items } => {
+                let mut lines = vec![if zh {
+                    format!("您有 {} 条新通知：", items.len())
+                } else {
+                    format!("You have {} new notifications:", items.len())
+                }];
+                lines.extend(
+                    items
+                        .iter()
+                        .map(|(title, body)| format!("· {}：{}", title, body)),
+                );
+                let subject = if zh {
+                    format!("Jive Money 通知摘要（{} 条）", items.len())
+                } else {
+                    format!("Your Jive Money notifications ({})", items.len())
+                };
+                (subject, lines)
+            }
+        };
+
+        let footer = if zh {
+            "—— Jive Money 团队"
+        } else {
+            "— The Jive Money team"
+        };
+
+        let text = format!("{}\n\n{}\n", lines.join("\n"), footer);
+        let html = format!(
+            "<!DOCTYPE html><html lang=\"{}\"><body style=\"font-family:sans-serif;line-height:1.6\">{}<p style=\"color:#888\">{}</p></body></html>",
+            locale.as_tag(),
+            lines
+                .iter()
+                .map(|l| format!("<p>{}</p>", escape_html(l)))
+                .collect::<String>(),
+            escape_html(footer)
+        );
+
+        RenderedEmail {
+            subject,
+            text,
+            html,
+        }
+    }
+}
+
+/// 财务摘要正文：收支概览、分类、预算、到期、异常交易与净资产各占一段
+fn render_financial_digest(report: &DigestReport, zh: bool) -> (String, Vec<String>) {
+    let cur = &report.currency;
+    let separator = if zh { "，" } else { ", " };
+    let change = |current: Decimal, previous: Decimal| {
+        percent_change(current, previous)
+            .map(|p| format!("{}{}%", separator, signed(p)))
+            .unwrap_or_default()
+    };
+    let (last, heading) = match (report.period, zh) {
+        (DigestPeriod::Weekly, true) => ("上周", "周报"),
+        (DigestPeriod::Monthly, true) => ("上月", "月报"),
+        (DigestPeriod::Weekly, false) => ("last week", "weekly summary"),
+        (DigestPeriod::Monthly, false) => ("last month", "monthly summary"),
+    };
+    let subject = if zh {
+        format!(
+            "Jive Money {}：{}（{} 至 {}）",
+            heading, report.family_name, report.period_start, report.period_end
+        )
+    } else {
+        format!(
+            "Your Jive Money {}: {} ({} - {})",
+            heading, report.family_name, report.period_start, report.period_end
+        )
+    };
+
+    let mut lines = if zh {
+        vec![
+            format!("统计周期：{} 至 {}", report.period_start, report.period_end),
+            format!(
+                "支出：{} {}（{} {} {}{}）",
+                report.expense,
+                cur,
+                last,
+                report.previous_expense,
+                cur,
+                change(report.expense, report.previous_expense)
+            ),
+            format!(
+                "收入：{} {}（{} {} {}）",
+                report.income, cur, last, report.previous_income, cur
+            ),
+            format!("交易笔数：{}", report.transaction_count),
+        ]
+    } else {
+        vec![
+            format!("Period: {} to {}", report.period_start, report.period_end),
+            format!(
+                "Spending: {} {} ({} {} {}{})",
+                report.expense,
+                cur,
+                last,
+                report.previous_expense,
+                cur,
+                change(report.expense, report.previous_expense)
+            ),
+            format!(
+                "Income: {} {} ({} {} {})",
+                report.income, cur, last, report.previous_income, cur
+            ),
+            format!("Transactions: {}", report.transaction_count),
+        ]
+    };
+    if report.unvalued_count > 0 {
+        lines.push(if zh {
+            format!(
+                "另有 {} 笔交易尚无汇率，未计入以上金额",
+                report.unvalued_count
+            )
+        } else {
+            format!(
+                "{} transactions without an exchange rate are not included above",
+                report.unvalued_count
+            )
+        });
+    }
+
+    if !report.top_categories.is_empty() {
+        lines.push(
+            if zh {
+                "支出最多的分类："
+            } else {
+                "Top spending categories:"
+            }
+            .to_string(),
+        );
+        for c in &report.top_categories {
+            let name = if c.name.is_empty() {
+                if zh {
+                    "未分类"
+                } else {
+                    "Uncategorized"
+                }
+            } else {
+                c.name.as_str()
+            };
+            lines.push(if zh {
+                format!(
+                    "· {}：{} {}（{} {}）",
+                    name, c.amount, cur, last, c.previous_amount
+                )
+            } else {
+                format!(
+                    "· {}: {} {} ({} {})",
+                    name, c.amount, cur, last, c.previous_amount
+                )
+            });
+        }
+    }
+    if !report.budgets_at_risk.is_empty() {
+        lines.push(
+            if zh {
+                "接近或超出上限的预算："
+            } else {
+                "Budgets at risk:"
+            }
+            .to_string(),
+        );
+        for b in &report.budgets_at_risk {
+            lines.push(if zh {
+                format!(
+                    "· {}：已用 {} / {} {}（{}%）",
+                    b.name, b.spent, b.limit, cur, b.percentage
+                )
+            } else {
+                format!(
+                    "· {}: {} of {} {} used ({}%)",
+                    b.name, b.spent, b.limit, cur, b.percentage
+                )
+            });
+        }
+    }
+    if !report.upcoming.is_empty() {
+        lines.push(if zh { "即将到期：" } else { "Coming up:" }.to_string());
+        for u in &report.upcoming {
+            let kind = match (u.kind.as_str(), zh) {
+                ("credit_card", true) => "信用卡还款",
+                ("loan", true) => "贷款还款",
+                ("installment", true) => "分期",
+                (_, true) => "计划交易",
+                ("credit_card", false) => "Credit card payment",
+                ("loan", false) => "Loan payment",
+                ("installment", false) => "Installment",
+                (_, false) => "Scheduled",
+            };
+            lines.push(if zh {
+                format!("· {} {}（{}）：{} {}", u.date, kind, u.name, u.amount, cur)
+            } else {
+                format!("· {} {} ({}): {} {}", u.date, kind, u.name, u.amount, cur)
+            });
+        }
+    }
+    if !report.unusual.is_empty() {
+        lines.push(
+            if zh {
+                "异常支出："
+            } else {
+                "Unusual spending:"
+            }
+            .to_string(),
+        );
+        for u in &report.unusual {
+            let category = u.category.as_deref().unwrap_or_default();
+            lines.push(if zh {
+                format!(
+                    "· {} {}（{}）：{} {}，该分类通常约 {} {}",
+                    u.date, u.description, category, u.amount, cur, u.typical_amount, cur
+                )
+            } else {
+                format!(
+                    "· {} {} ({}): {} {}, usually about {} {}",
+                    u.date, u.description, category, u.amount, cur, u.typical_amount, cur
+                )
+            });
+        }
+    }
+    if let Some(n) = &report.net_worth {
+        lines.push(if zh {
+            format!(
+                "净资产：{} {}（期初 {}，变化 {}）",
+                n.end,
+                cur,
+                n.start,
+                signed(n.change)
+            )
+        } else {
+            format!(
+                "Net worth: {} {} (from {}, {})",
+                n.end,
+                cur,
+                n.start,
+                signed(n.change)
+            )
+        });
+    }
+    (subject, lines)
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(EmailLocale::from_tag("zh-CN"), EmailLocale::ZhCn);
+        assert_eq!(EmailLocale::from_tag("zh_TW"), EmailLocale::ZhCn);
+        assert_eq!(EmailLocale::from_tag("en-US"), EmailLocale::En);
+        assert_eq!(EmailLocale::from_tag("fr"), EmailLocale::En);
+    }
+
+    #[test]
+    fn test_verification_code_is_localized() {
+        let t = EmailTemplate::VerificationCode {
+            code: "4821".to_string(),
+            operation: "delete_family".to_string(),
+            expires_minutes: 5,
+        };
+        let zh = t.render(EmailLocale::ZhCn);
+        let en = t.render(EmailLocale::En);
+        assert!(zh.subject.contains("验证码") && zh.subject.contains("4821"));
+        assert!(en.subject.contains("verification code") && en.subject.contains("4821"));
+        assert!(zh.text.contains("5 分钟"));
+        assert!(en.text.contains("5 minutes"));
+    }
+
+    #[test]
+    fn test_financial_digest_is_localized() {
+        use crate::services::financial_digest_service::{DigestBudget, DigestUpcoming};
+        use chrono::NaiveDate;
+        use std::str::FromStr;
+
+        let d = |s: &str| Decimal::from_str(s).unwrap();
+        let date = |m, day| NaiveDate::from_ymd_opt(2026, m, day).unwrap();
+        let template = EmailTemplate::FinancialDigest {
+            report: Box::new(DigestReport {
+                period: DigestPeriod::Monthly,
+                period_start: date(9, 1),
+                period_end: date(9, 30),
+                family_name: "A & B".to_string(),
+                currency: "CNY".to_string(),
+                income: d("8000"),
+                expense: d("4500"),
+                previous_income: d("8000"),
+                previous_expense: d("5000"),
+                transaction_count: 42,
+                unvalued_count: 0,
+                top_categories: Vec::new(),
+                budgets_at_risk: vec![DigestBudget {
+                    budget_id: uuid::Uuid::nil(),
+                    name: "餐饮".to_string(),
+                    spent: d("1900"),
+                    limit: d("2000"),
+                    percentage: d("95.0"),
+                }],
+                upcoming: vec![DigestUpcoming {
+                    date: date(10, 20),
+                    kind: "credit_card".to_string(),
+                    name: "招行信用卡".to_string(),
+                    amount: d("3200"),
+                }],
+                unusual: Vec::new(),
+                net_worth: None,
+            }),
+        };
+        assert_eq!(template.kind(), "monthly_digest");
+
+        let zh = template.render(EmailLocale::ZhCn);
+        assert!(zh.subject.contains("月报") && zh.subject.contains("2026-09-01"));
+        assert!(zh.text.contains("支出：4500 CNY（上月 5000 CNY，-10.0%）"));
+        assert!(zh.text.contains("餐饮：已用 1900 / 2000 CNY（95.0%）"));
+        assert!(zh
+            .text
+            .contains("2026-10-20 信用卡还款（招行信用卡）：3200 CNY"));
+        let en = template.render(EmailLocale::En);
+        assert!(en.subject.contains("monthly summary"));
+        assert!(en
+            .text
+            .contains("Spending: 4500 CNY (last month 5000 CNY, -10.0%)"));
+        assert!(en
+            .text
+            .contains("2026-10-20 Credit card payment (招行信用卡): 3200 CNY"));
+        assert!(en.subject.contains("A & B"));
+        assert!(en.html.contains("<p>Period: 2026-09-01 to 2026-09-30</p>"));
+    }
+
+    #[test]
+    fn test_html_body_is_escaped() {
+        let rendered = EmailTemplate::Invitation {
+            inviter_name: "<script>".to_string(),
+            family_name: "Tom & Jerry".to_string(),
+            invite_code: "ABC123".to_string(),
+            expires_at: Utc::now(),
+        }
+        .render(EmailLocale::En);
+        assert!(rendered.html.contains("&lt;script&gt;"));
+        assert!(rendered.html.contains("Tom &amp; Jerry"));
+        assert!(!rendered.html.contains("<script>"));
+        // 纯文本正文保持原样
+        assert!(rendered.text.contains("Tom & Jerry"));
+    }
+
+    #[test]
+    fn test_secret_ttl_only_for_code_templates() {
+        let reset = EmailTemplate::PasswordReset {
+            code: "123456".to_string(),
+            expires_minutes: 15,
+        };
+        assert_eq!(
+            reset.secret_ttl(),
+            Some(std::time::Duration::from_secs(15 * 60))
+        );
+        let alert = EmailTemplate::NewLocationLogin { ip: None };
+        assert_eq!(alert.secret_ttl(), None);
+    }
+}
@@ -7,7 +7,7 @@ use crate::models::{
     permission::{MemberRole, Permission},
 };
 
-use super::{ServiceContext, ServiceError};
+use super::{ServiceContext, ServiceError, WebhookService};
 
 pub struct FamilyService {
     pool: PgPool,
@@ -366,6 +366,10 @@ impl FamilyService {
 
         tx.commit().await?;
 
+        WebhookService::new(self.pool.clone())
+            .member_joined(family.id, user_id)
+            .await;
+
         Ok(family)
     }
 
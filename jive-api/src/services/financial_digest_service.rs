@@ -0,0 +1,1243 @@
+//! 周度 / 月度财务摘要
+//!
+//! 定时任务按成员所在时区，在周一（周报）或每月 1 日（月报）的 `NOTIFICATION_DIGEST_HOUR`
+//! 之后为上一个完整周期生成摘要：支出与上期对比、支出最多的分类、接近上限的预算、
+//! 即将到期的还款与计划交易、异常交易以及净资产变化。统计范围只包含成员按账本权限
+//! 可见的账本（交易、账户、预算分别按各自的查看权限过滤）。
+//!
+//! 每个成员、家庭、周期类型与周期起始日只生成一次（`financial_digests` 唯一约束），
+//! 生成后写入站内通知；开启邮件的成员另外收到按家庭语言渲染的 HTML 邮件。
+//! 没有任何活动的周期只记录不发送。
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::email::{EmailLocale, EmailOutbox, EmailTemplate};
+use super::loan_service::{due_date, next_row, LoanState, RepaymentMethod};
+use super::notification_service::{NewNotification, NotificationPriority, NotificationService};
+use super::{AuthService, CurrencyService, LedgerAclService, ServiceContext, ServiceError};
+use crate::config::AppConfig;
+use crate::models::permission::Permission;
+use crate::ws::WsConnectionManager;
+
+/// 摘要中每个列表最多保留的条目数
+const MAX_TOP_CATEGORIES: i64 = 5;
+const MAX_ITEMS: usize = 10;
+const MAX_UNUSUAL: usize = 5;
+/// 异常交易：与该分类此前多少天内的支出比较
+const UNUSUAL_HISTORY_DAYS: i64 = 90;
+/// 分类历史样本不足时不判断异常
+const UNUSUAL_MIN_SAMPLES: i64 = 5;
+/// 预算未设置提醒阈值时的默认值（%）
+const DEFAULT_BUDGET_THRESHOLD: i64 = 80;
+
+/// 摘要周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestPeriod {
+    Weekly,
+    Monthly,
+}
+
+impl DigestPeriod {
+    pub const ALL: [DigestPeriod; 2] = [DigestPeriod::Weekly, DigestPeriod::Monthly];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "weekly" => Some(Self::Weekly),
+            "monthly" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+
+    /// 站内通知类型；加入 `muted_kinds` 即可关闭对应摘要
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly_digest",
+            Self::Monthly => "monthly_digest",
+        }
+    }
+
+    /// `today` 之前最近一个完整周期（周一至周日 / 自然月）
+    pub fn last_completed(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::Weekly => {
+                let end = today - Duration::days(today.weekday().num_days_from_monday() as i64 + 1);
+                (end - Duration::days(6), end)
+            }
+            Self::Monthly => {
+                let end = today.with_day(1).unwrap_or(today) - Duration::days(1);
+                (end.with_day(1).unwrap_or(end), end)
+            }
+        }
+    }
+
+    /// 从 `start` 开始的周期之前的一个周期
+    pub fn previous(&self, start: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let end = start - Duration::days(1);
+        match self {
+            Self::Weekly => (end - Duration::days(6), end),
+            Self::Monthly => (end.with_day(1).unwrap_or(end), end),
+        }
+    }
+
+    /// 「即将到期」的统计范围：今天起一周 / 一个月
+    pub fn upcoming_window(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let end = match self {
+            Self::Weekly => today + Duration::days(7),
+            Self::Monthly => today
+                .checked_add_months(Months::new(1))
+                .unwrap_or(today + Duration::days(30)),
+        };
+        (today, end - Duration::days(1))
+    }
+
+    /// 本地时间 `local_now` 时应生成摘要的周期；周期刚结束且未到 `digest_hour` 时为 None
+    pub fn due(
+        &self,
+        local_now: NaiveDateTime,
+        digest_hour: u32,
+    ) -> Option<(NaiveDate, NaiveDate)> {
+        let today = local_now.date();
+        let (start, end) = self.last_completed(today);
+        if end + Duration::days(1) == today && local_now.hour() < digest_hour {
+            return None;
+        }
+        Some((start, end))
+    }
+}
+
+/// 分类支出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestCategory {
+    pub name: String,
+    pub amount: Decimal,
+    /// 上一周期同一分类的支出
+    pub previous_amount: Decimal,
+}
+
+/// 接近或超出上限的预算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestBudget {
+    pub budget_id: Uuid,
+    pub name: String,
+    /// 预算当期（包含摘要周期最后一天的预算周期）已支出
+    pub spent: Decimal,
+    pub limit: Decimal,
+    /// 已用百分比
+    pub percentage: Decimal,
+}
+
+/// 即将到期的还款或计划交易
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestUpcoming {
+    pub date: NaiveDate,
+    /// credit_card / loan / installment / scheduled
+    pub kind: String,
+    pub name: String,
+    pub amount: Decimal,
+}
+
+/// 明显高于该分类历史水平的支出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestUnusual {
+    pub transaction_id: Uuid,
+    pub date: NaiveDate,
+    pub description: String,
+    pub category: Option<String>,
+    pub amount: Decimal,
+    /// 该分类此前 90 天的平均单笔支出
+    pub typical_amount: Decimal,
+}
+
+/// 净资产变化（只统计家庭本位币账户）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestNetWorth {
+    pub start: Decimal,
+    pub end: Decimal,
+    pub change: Decimal,
+}
+
+/// 摘要内容（金额均为家庭本位币）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestReport {
+    pub period: DigestPeriod,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub family_name: String,
+    pub currency: String,
+    pub income: Decimal,
+    pub expense: Decimal,
+    pub previous_income: Decimal,
+    pub previous_expense: Decimal,
+    pub transaction_count: i64,
+    /// 尚无汇率、未计入金额的交易数
+    #[serde(default)]
+    pub unvalued_count: i64,
+    pub top_categories: Vec<DigestCategory>,
+    pub budgets_at_risk: Vec<DigestBudget>,
+    pub upcoming: Vec<DigestUpcoming>,
+    pub unusual: Vec<DigestUnusual>,
+    pub net_worth: Option<DigestNetWorth>,
+}
+
+impl DigestReport {
+    /// 支出较上期变化百分比；上期没有支出时为 None
+    pub fn expense_change_percent(&self) -> Option<Decimal> {
+        percent_change(self.expense, self.previous_expense)
+    }
+
+    /// 本期与上期都没有交易，也没有预算或到期提醒
+    pub fn is_empty(&self) -> bool {
+        self.transaction_count == 0
+            && self.previous_expense.is_zero()
+            && self.previous_income.is_zero()
+            && self.budgets_at_risk.is_empty()
+            && self.upcoming.is_empty()
+    }
+
+    /// 站内通知的标题与正文
+    pub fn headline(&self, locale: EmailLocale) -> (String, String) {
+        let zh = locale == EmailLocale::ZhCn;
+        let title = match (self.period, zh) {
+            (DigestPeriod::Weekly, true) => format!("{} 周报", self.family_name),
+            (DigestPeriod::Monthly, true) => format!("{} 月报", self.family_name),
+            (DigestPeriod::Weekly, false) => format!("{} weekly summary", self.family_name),
+            (DigestPeriod::Monthly, false) => format!("{} monthly summary", self.family_name),
+        };
+        let mut parts = Vec::new();
+        let change = self
+            .expense_change_percent()
+            .map(|p| format!(" ({}%)", signed(p)))
+            .unwrap_or_default();
+        parts.push(if zh {
+            format!(
+                "支出 {} {}{}，收入 {} {}",
+                self.expense, self.currency, change, self.income, self.currency
+            )
+        } else {
+            format!(
+                "Spent {} {}{}, earned {} {}",
+                self.expense, self.currency, change, self.income, self.currency
+            )
+        });
+        if !self.budgets_at_risk.is_empty() {
+            parts.push(if zh {
+                format!("{} 项预算接近上限", self.budgets_at_risk.len())
+            } else {
+                format!("{} budget(s) near their limit", self.budgets_at_risk.len())
+            });
+        }
+        if !self.upcoming.is_empty() {
+            parts.push(if zh {
+                format!("{} 笔即将到期", self.upcoming.len())
+            } else {
+                format!("{} upcoming payment(s)", self.upcoming.len())
+            });
+        }
+        if !self.unusual.is_empty() {
+            parts.push(if zh {
+                format!("{} 笔异常支出", self.unusual.len())
+            } else {
+                format!("{} unusual transaction(s)", self.unusual.len())
+            });
+        }
+        (title, parts.join(if zh { "；" } else { "; " }))
+    }
+}
+
+/// 相对上期的变化百分比（保留一位小数）
+pub fn percent_change(current: Decimal, previous: Decimal) -> Option<Decimal> {
+    if previous.is_zero() {
+        return None;
+    }
+    Some(((current - previous) / previous.abs() * Decimal::from(100)).round_dp(1))
+}
+
+/// 带正负号的数字（+12.5 / -3）
+pub fn signed(value: Decimal) -> String {
+    if value > Decimal::ZERO {
+        format!("+{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// 单笔支出是否明显偏离该分类的历史水平：样本足够，且同时超过均值的 2 倍与均值加 3 个标准差
+pub fn is_unusual(amount: Decimal, mean: Decimal, stddev: Option<Decimal>, samples: i64) -> bool {
+    if samples < UNUSUAL_MIN_SAMPLES || mean <= Decimal::ZERO {
+        return false;
+    }
+    let spread = stddev.unwrap_or(Decimal::ZERO) * Decimal::from(3);
+    amount >= mean * Decimal::from(2) && amount > mean + spread
+}
+
+/// 包含 `reference` 的预算周期（`budgets.period`）；未知周期按月处理
+pub fn budget_window(period: &str, reference: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let month_start = |date: NaiveDate| date.with_day(1).unwrap_or(date);
+    let span = |start: NaiveDate, months: u32| {
+        let end = start
+            .checked_add_months(Months::new(months))
+            .unwrap_or(start)
+            - Duration::days(1);
+        (start, end)
+    };
+    match period {
+        "daily" => (reference, reference),
+        "weekly" => {
+            let start =
+                reference - Duration::days(reference.weekday().num_days_from_monday() as i64);
+            (start, start + Duration::days(6))
+        }
+        "quarterly" => {
+            let month = (reference.month0() / 3) * 3 + 1;
+            span(
+                NaiveDate::from_ymd_opt(reference.year(), month, 1).unwrap_or(reference),
+                3,
+            )
+        }
+        "yearly" => span(
+            NaiveDate::from_ymd_opt(reference.year(), 1, 1).unwrap_or(reference),
+            12,
+        ),
+        _ => span(month_start(reference), 1),
+    }
+}
+
+/// 已生成的摘要
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct FinancialDigest {
+    pub id: Uuid,
+    pub family_id: Uuid,
+    pub period: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    #[schema(value_type = DigestReport)]
+    pub report: Json<DigestReport>,
+    /// 无活动的周期只记录不通知，此时为空
+    pub notification_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DigestQuery {
+    /// weekly / monthly
+    pub period: Option<String>,
+    /// 默认 20，最多 100
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DigestPreviewQuery {
+    /// weekly / monthly，默认 weekly
+    pub period: Option<String>,
+}
+
+/// 一次任务运行的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DigestRunStats {
+    pub generated: usize,
+    /// 无活动或已由其他实例生成
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// 待检查的家庭成员
+#[derive(Debug, sqlx::FromRow)]
+struct DigestCandidate {
+    user_id: Uuid,
+    family_id: Uuid,
+    email: String,
+    locale: Option<String>,
+    email_enabled: bool,
+    digest_frequency: String,
+    muted_kinds: Vec<String>,
+    local_now: NaiveDateTime,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct BudgetRow {
+    id: Uuid,
+    ledger_id: Uuid,
+    category_id: Option<Uuid>,
+    name: String,
+    amount: Decimal,
+    period: String,
+    alert_threshold: Option<Decimal>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct UnusualRow {
+    id: Uuid,
+    transaction_date: NaiveDate,
+    description: Option<String>,
+    category: Option<String>,
+    amount: Decimal,
+    mean: Decimal,
+    stddev: Option<Decimal>,
+    samples: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct LoanRow {
+    account_name: String,
+    first_payment_date: NaiveDate,
+    repayment_method: String,
+    outstanding_principal: Decimal,
+    next_period: i16,
+    remaining_periods: i16,
+    current_installment: Decimal,
+    current_rate: Decimal,
+}
+
+/// 成员在家庭中按权限可见的账本
+struct VisibleLedgers {
+    transactions: Vec<Uuid>,
+    accounts: Vec<Uuid>,
+    budgets: Vec<Uuid>,
+}
+
+const DIGEST_COLUMNS: &str =
+    "id, family_id, period, period_start, period_end, report, notification_id, created_at";
+
+/// 计入统计的交易：未删除、非待入账、未取消（`alias` 为表别名前缀，如 "t."）
+fn counted(alias: &str) -> String {
+    format!(
+        "{a}deleted_at IS NULL AND COALESCE({a}status, 'completed') NOT IN ('pending', 'cancelled')",
+        a = alias
+    )
+}
+
+/// 预算在 `start..=end` 内的已用金额（优先本位币金额，按月入账的分期只计当期本金）
+pub async fn budget_spent(
+    pool: &PgPool,
+    ledger_id: Uuid,
+    category_id: Option<Uuid>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Decimal, ServiceError> {
+    Ok(sqlx::query_scalar(&format!(
+        r#"
+        SELECT COALESCE(SUM(base_amount), 0)::numeric
+        FROM budget_expenses
+        WHERE ledger_id = $1 AND transaction_date BETWEEN $2 AND $3
+          AND ($4::uuid IS NULL OR category_id = $4) AND {}
+        "#,
+        counted("")
+    ))
+    .bind(ledger_id)
+    .bind(start)
+    .bind(end)
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?)
+}
+
+pub struct FinancialDigestService {
+    pool: PgPool,
+}
+
+impl FinancialDigestService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 为所有到期的成员生成摘要；单个成员失败不影响其他成员
+    pub async fn run_due(
+        &self,
+        config: &AppConfig,
+        ws: &Arc<WsConnectionManager>,
+    ) -> Result<DigestRunStats, ServiceError> {
+        let digest_hour = config.notification.digest_hour;
+        let candidates: Vec<DigestCandidate> = sqlx::query_as(
+            r#"
+            SELECT fm.user_id, fm.family_id, u.email, f.locale,
+                   COALESCE(np.email_enabled, false) AS email_enabled,
+                   COALESCE(np.digest_frequency, 'realtime') AS digest_frequency,
+                   COALESCE(np.muted_kinds, '{}') AS muted_kinds,
+                   (NOW() AT TIME ZONE COALESCE(
+                       np.timezone,
+                       (SELECT name FROM pg_timezone_names WHERE name = f.timezone),
+                       'UTC'
+                   )) AS local_now
+            FROM family_members fm
+            JOIN users u ON u.id = fm.user_id
+            JOIN families f ON f.id = fm.family_id
+            LEFT JOIN notification_preferences np ON np.user_id = fm.user_id
+            WHERE COALESCE(u.is_active, true) AND COALESCE(fm.is_active, true)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = DigestRunStats::default();
+        for candidate in &candidates {
+            for period in DigestPeriod::ALL {
+                if candidate.muted_kinds.iter().any(|k| k == period.kind()) {
+                    continue;
+                }
+                let Some((start, end)) = period.due(candidate.local_now, digest_hour) else {
+                    continue;
+                };
+                let exists: bool = sqlx::query_scalar(
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM financial_digests
+                        WHERE user_id = $1 AND family_id = $2 AND period = $3 AND period_start = $4
+                    )
+                    "#,
+                )
+                .bind(candidate.user_id)
+                .bind(candidate.family_id)
+                .bind(period.as_str())
+                .bind(start)
+                .fetch_one(&self.pool)
+                .await?;
+                if exists {
+                    continue;
+                }
+                match self
+                    .generate(candidate, period, start, end, config, ws)
+                    .await
+                {
+                    Ok(true) => stats.generated += 1,
+                    Ok(false) => stats.skipped += 1,
+                    Err(e) => {
+                        tracing::warn!(
+                            user = %candidate.user_id,
+                            family = %candidate.family_id,
+                            period = period.as_str(),
+                            "Financial digest failed: {}",
+                            e
+                        );
+                        stats.failed += 1;
+                    }
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 生成并发送一份摘要；返回是否发送了通知
+    async fn generate(
+        &self,
+        candidate: &DigestCandidate,
+        period: DigestPeriod,
+        start: NaiveDate,
+        end: NaiveDate,
+        config: &AppConfig,
+        ws: &Arc<WsConnectionManager>,
+    ) -> Result<bool, ServiceError> {
+        let today = candidate.local_now.date();
+        let ctx = match self.context(candidate.user_id, candidate.family_id).await {
+            Ok(ctx) => ctx,
+            // 成员已被移出或停用
+            Err(ServiceError::PermissionDenied) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let report = self.build_report(&ctx, period, start, end, today).await?;
+
+        let digest_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO financial_digests (
+                user_id, family_id, period, period_start, period_end, currency, report
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (user_id, family_id, period, period_start) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(candidate.user_id)
+        .bind(candidate.family_id)
+        .bind(period.as_str())
+        .bind(start)
+        .bind(end)
+        .bind(&report.currency)
+        .bind(Json(&report))
+        .fetch_optional(&self.pool)
+        .await?;
+        // 已由其他实例生成，或本周期没有任何活动
+        let Some(digest_id) = digest_id else {
+            return Ok(false);
+        };
+        if report.is_empty() {
+            return Ok(false);
+        }
+
+        let outbox = EmailOutbox::new(self.pool.clone(), &config.email);
+        let locale = outbox.locale_or_default(candidate.locale.as_deref());
+        let (title, body) = report.headline(locale);
+        let notifier =
+            NotificationService::new(self.pool.clone(), &config.notification, ws.clone());
+        let notification = notifier
+            .notify(NewNotification {
+                user_id: candidate.user_id,
+                family_id: Some(candidate.family_id),
+                kind: period.kind().to_string(),
+                priority: NotificationPriority::Low,
+                title,
+                body,
+                data: serde_json::json!({
+                    "digest_id": digest_id,
+                    "period": period.as_str(),
+                    "period_start": start,
+                    "period_end": end,
+                }),
+                action_url: None,
+            })
+            .await?;
+
+        let email_id = if candidate.email_enabled && candidate.digest_frequency != "never" {
+            outbox
+                .enqueue(
+                    &candidate.email,
+                    &EmailTemplate::FinancialDigest {
+                        report: Box::new(report),
+                    },
+                    locale,
+                )
+                .await?
+        } else {
+            None
+        };
+
+        sqlx::query(
+            "UPDATE financial_digests SET notification_id = $2, email_outbox_id = $3 WHERE id = $1",
+        )
+        .bind(digest_id)
+        .bind(notification.as_ref().map(|n| n.id))
+        .bind(email_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(true)
+    }
+
+    async fn context(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> Result<ServiceContext, ServiceError> {
+        AuthService::new(self.pool.clone())
+            .validate_family_access(user_id, family_id)
+            .await
+            .map_err(|_| ServiceError::PermissionDenied)
+    }
+
+    async fn visible_ledgers(&self, ctx: &ServiceContext) -> Result<VisibleLedgers, ServiceError> {
+        let acl = LedgerAclService::new(self.pool.clone());
+        Ok(VisibleLedgers {
+            transactions: acl
+                .authorized_ledgers(ctx, Permission::ViewTransactions)
+                .await?,
+            accounts: acl
+                .authorized_ledgers(ctx, Permission::ViewAccounts)
+                .await?,
+            budgets: acl.authorized_ledgers(ctx, Permission::ViewBudgets).await?,
+        })
+    }
+
+    /// 当前上一个完整周期的摘要（不保存、不发送）
+    pub async fn preview(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        period: DigestPeriod,
+    ) -> Result<DigestReport, ServiceError> {
+        let ctx = self.context(user_id, family_id).await?;
+        let today = Utc::now().date_naive();
+        let (start, end) = period.last_completed(today);
+        self.build_report(&ctx, period, start, end, today).await
+    }
+
+    /// 按成员可见的账本统计周期 [start, end]
+    pub async fn build_report(
+        &self,
+        ctx: &ServiceContext,
+        period: DigestPeriod,
+        start: NaiveDate,
+        end: NaiveDate,
+        today: NaiveDate,
+    ) -> Result<DigestReport, ServiceError> {
+        let family_name: String = sqlx::query_scalar("SELECT name FROM families WHERE id = $1")
+            .bind(ctx.family_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("family", ctx.family_id))?;
+        let currency = CurrencyService::new(self.pool.clone())
+            .family_base_currency(ctx.family_id)
+            .await?;
+        let ledgers = self.visible_ledgers(ctx).await?;
+        let (previous_start, previous_end) = period.previous(start);
+
+        let (income, expense, transaction_count, unvalued_count) =
+            self.totals(&ledgers.transactions, start, end).await?;
+        let (previous_income, previous_expense, ..) = self
+            .totals(&ledgers.transactions, previous_start, previous_end)
+            .await?;
+        let (window_start, window_end) = period.upcoming_window(today);
+
+        Ok(DigestReport {
+            period,
+            period_start: start,
+            period_end: end,
+            family_name,
+            income,
+            expense,
+            previous_income,
+            previous_expense,
+            transaction_count,
+            unvalued_count,
+            top_categories: self
+                .top_categories(&ledgers.transactions, previous_start, start, end)
+                .await?,
+            budgets_at_risk: self.budgets_at_risk(&ledgers.budgets, end).await?,
+            upcoming: self
+                .upcoming(&ledgers, today, window_start, window_end)
+                .await?,
+            unusual: self.unusual(&ledgers.transactions, start, end).await?,
+            net_worth: self
+                .net_worth(&ledgers.accounts, &currency, start - Duration::days(1), end)
+                .await?,
+            currency,
+        })
+    }
+
+    /// (收入, 支出, 交易笔数, 未估值笔数)；未估值的交易币种不同，不计入金额
+    async fn totals(
+        &self,
+        ledgers: &[Uuid],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<(Decimal, Decimal, i64, i64), ServiceError> {
+        Ok(sqlx::query_as(&format!(
+            r#"
+            SELECT COALESCE(SUM(base_amount) FILTER (WHERE transaction_type = 'income'), 0)::numeric,
+                   COALESCE(SUM(base_amount) FILTER (WHERE transaction_type = 'expense'), 0)::numeric,
+                   COUNT(*),
+                   COUNT(*) FILTER (WHERE base_amount IS NULL)
+            FROM transactions
+            WHERE ledger_id = ANY($1) AND transaction_date BETWEEN $2 AND $3 AND {}
+            "#,
+            counted("")
+        ))
+        .bind(ledgers)
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// 本期支出最多的分类及其上期支出
+    async fn top_categories(
+        &self,
+        ledgers: &[Uuid],
+        previous_start: NaiveDate,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<DigestCategory>, ServiceError> {
+        let rows: Vec<(Option<String>, Decimal, Decimal)> = sqlx::query_as(&format!(
+            r#"
+            SELECT COALESCE(c.name, t.category_name) AS name,
+                   COALESCE(SUM(t.base_amount)
+                       FILTER (WHERE t.transaction_date >= $3), 0)::numeric AS amount,
+                   COALESCE(SUM(t.base_amount)
+                       FILTER (WHERE t.transaction_date < $3), 0)::numeric AS previous_amount
+            FROM transactions t
+            LEFT JOIN categories c ON c.id = t.category_id
+            WHERE t.ledger_id = ANY($1) AND t.transaction_type = 'expense'
+              AND t.transaction_date BETWEEN $2 AND $4 AND {}
+            GROUP BY 1
+            HAVING SUM(t.base_amount) FILTER (WHERE t.transaction_date >= $3) > 0
+            ORDER BY 2 DESC
+            LIMIT $5
+            "#,
+            counted("t.")
+        ))
+        .bind(ledgers)
+        .bind(previous_start)
+        .bind(start)
+        .bind(end)
+        .bind(MAX_TOP_CATEGORIES)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(name, amount, previous_amount)| DigestCategory {
+                name: name.unwrap_or_default(),
+                amount,
+                previous_amount,
+            })
+            .collect())
+    }
+
+    /// 包含 `reference` 的预算周期内已用比例达到提醒阈值的预算
+    async fn budgets_at_risk(
+        &self,
+        ledgers: &[Uuid],
+        reference: NaiveDate,
+    ) -> Result<Vec<DigestBudget>, ServiceError> {
+        let budgets: Vec<BudgetRow> = sqlx::query_as(
+            r#"
+                SELECT id, ledger_id, category_id, name, amount, period, alert_threshold
+                FROM budgets
+                WHERE ledger_id = ANY($1) AND COALESCE(is_active, true) AND amount > 0
+                  AND start_date <= $2 AND (end_date IS NULL OR end_date >= $2)
+                "#,
+        )
+        .bind(ledgers)
+        .bind(reference)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut at_risk = Vec::new();
+        for budget in budgets {
+            let (window_start, _) = budget_window(&budget.period, reference);
+            let spent = budget_spent(
+                &self.pool,
+                budget.ledger_id,
+                budget.category_id,
+                window_start,
+                reference,
+            )
+            .await?;
+            let percentage = (spent / budget.amount * Decimal::from(100)).round_dp(1);
+            let threshold = budget
+                .alert_threshold
+                .unwrap_or(Decimal::from(DEFAULT_BUDGET_THRESHOLD));
+            if percentage >= threshold {
+                at_risk.push(DigestBudget {
+                    budget_id: budget.id,
+                    name: budget.name,
+                    spent,
+                    limit: budget.amount,
+                    percentage,
+                });
+            }
+        }
+        at_risk.sort_by_key(|b| std::cmp::Reverse(b.percentage));
+        at_risk.truncate(MAX_ITEMS);
+        Ok(at_risk)
+    }
+
+    /// 信用卡还款、贷款月供、分期入账与未来日期的计划交易
+    async fn upcoming(
+        &self,
+        ledgers: &VisibleLedgers,
+        today: NaiveDate,
+        window_start: NaiveDate,
+        window_end: NaiveDate,
+    ) -> Result<Vec<DigestUpcoming>, ServiceError> {
+        let mut items = Vec::new();
+
+        let statements: Vec<(NaiveDate, String, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT s.due_date, a.name, s.closing_balance - s.paid_amount
+            FROM credit_card_statements s
+            JOIN credit_cards cc ON cc.id = s.card_id
+            JOIN accounts a ON a.id = cc.account_id
+            WHERE a.ledger_id = ANY($1) AND s.status IN ('open', 'minimum_paid')
+              AND s.closing_balance > s.paid_amount
+              AND s.due_date BETWEEN $2 AND $3
+            "#,
+        )
+        .bind(&ledgers.accounts)
+        .bind(window_start)
+        .bind(window_end)
+        .fetch_all(&self.pool)
+        .await?;
+        items.extend(
+            statements
+                .into_iter()
+                .map(|(date, name, amount)| DigestUpcoming {
+                    date,
+                    kind: "credit_card".to_string(),
+                    name,
+                    amount,
+                }),
+        );
+
+        let loans: Vec<LoanRow> = sqlx::query_as(
+            r#"
+            SELECT a.name AS account_name, l.first_payment_date, l.repayment_method,
+                   l.outstanding_principal, l.next_period, l.remaining_periods,
+                   l.current_installment, l.current_rate
+            FROM loan_terms l
+            JOIN accounts a ON a.id = l.account_id
+            WHERE a.ledger_id = ANY($1) AND l.status = 'active' AND l.remaining_periods > 0
+            "#,
+        )
+        .bind(&ledgers.accounts)
+        .fetch_all(&self.pool)
+        .await?;
+        for loan in loans {
+            let due = due_date(loan.first_payment_date, loan.next_period);
+            if due < window_start || due > window_end {
+                continue;
+            }
+            let state = LoanState {
+                balance: loan.outstanding_principal,
+                next_period: loan.next_period,
+                remaining_periods: loan.remaining_periods,
+                installment: loan.current_installment,
+                rate: loan.current_rate,
+            };
+            let method = RepaymentMethod::parse(&loan.repayment_method)
+                .unwrap_or(RepaymentMethod::EqualPayment);
+            let (row, _) = next_row(method, &state, due, loan.current_rate);
+            items.push(DigestUpcoming {
+                date: due,
+                kind: "loan".to_string(),
+                name: loan.account_name,
+                amount: row.payment,
+            });
+        }
+
+        let installments: Vec<(NaiveDate, String, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT ip.posting_date, COALESCE(t.description, a.name), ip.principal + ip.fee
+            FROM installment_periods ip
+            JOIN installment_plans p ON p.id = ip.plan_id
+            JOIN accounts a ON a.id = p.account_id
+            LEFT JOIN transactions t ON t.id = p.transaction_id
+            WHERE a.ledger_id = ANY($1) AND p.status = 'active' AND ip.posted_at IS NULL
+              AND ip.posting_date BETWEEN $2 AND $3
+            "#,
+        )
+        .bind(&ledgers.accounts)
+        .bind(window_start)
+        .bind(window_end)
+        .fetch_all(&self.pool)
+        .await?;
+        items.extend(
+            installments
+                .into_iter()
+                .map(|(date, name, amount)| DigestUpcoming {
+                    date,
+                    kind: "installment".to_string(),
+                    name,
+                    amount,
+                }),
+        );
+
+        let scheduled: Vec<(NaiveDate, Option<String>, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT transaction_date,
+                   COALESCE(description, payee, merchant, category_name),
+                   base_amount
+            FROM transactions
+            WHERE ledger_id = ANY($1) AND deleted_at IS NULL AND base_amount IS NOT NULL
+              AND COALESCE(status, 'completed') <> 'cancelled'
+              AND transaction_type IN ('expense', 'income')
+              AND transaction_date > $2 AND transaction_date <= $3
+            "#,
+        )
+        .bind(&ledgers.transactions)
+        .bind(today)
+        .bind(window_end)
+        .fetch_all(&self.pool)
+        .await?;
+        items.extend(
+            scheduled
+                .into_iter()
+                .map(|(date, name, amount)| DigestUpcoming {
+                    date,
+                    kind: "scheduled".to_string(),
+                    name: name.unwrap_or_default(),
+                    amount,
+                }),
+        );
+
+        items.sort_by(|a, b| a.date.cmp(&b.date).then(b.amount.cmp(&a.amount)));
+        items.truncate(MAX_ITEMS);
+        Ok(items)
+    }
+
+    /// 本期明显高于该分类历史水平的支出
+    async fn unusual(
+        &self,
+        ledgers: &[Uuid],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<DigestUnusual>, ServiceError> {
+        let rows: Vec<UnusualRow> = sqlx::query_as(&format!(
+            r#"
+            WITH history AS (
+                SELECT category_id,
+                       AVG(base_amount) AS mean,
+                       STDDEV_SAMP(base_amount) AS stddev,
+                       COUNT(*) AS samples
+                FROM transactions
+                WHERE ledger_id = ANY($1) AND transaction_type = 'expense'
+                  AND category_id IS NOT NULL AND base_amount IS NOT NULL AND {history}
+                  AND transaction_date >= $2 AND transaction_date < $3
+                GROUP BY category_id
+            )
+            SELECT t.id, t.transaction_date,
+                   COALESCE(t.description, t.payee, t.merchant) AS description,
+                   COALESCE(c.name, t.category_name) AS category,
+                   t.base_amount AS amount,
+                   h.mean, h.stddev, h.samples
+            FROM transactions t
+            JOIN history h ON h.category_id = t.category_id
+            LEFT JOIN categories c ON c.id = t.category_id
+            WHERE t.ledger_id = ANY($1) AND t.transaction_type = 'expense'
+              AND t.base_amount IS NOT NULL AND {current}
+              AND t.transaction_date BETWEEN $3 AND $4
+            "#,
+            history = counted(""),
+            current = counted("t.")
+        ))
+        .bind(ledgers)
+        .bind(start - Duration::days(UNUSUAL_HISTORY_DAYS))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut rows: Vec<UnusualRow> = rows
+            .into_iter()
+            .filter(|r| is_unusual(r.amount, r.mean, r.stddev, r.samples))
+            .collect();
+        // 偏离倍数最大的排在前面
+        rows.sort_by_key(|r| std::cmp::Reverse(r.amount / r.mean));
+        Ok(rows
+            .into_iter()
+            .take(MAX_UNUSUAL)
+            .map(|r| DigestUnusual {
+                transaction_id: r.id,
+                date: r.transaction_date,
+                description: r.description.unwrap_or_default(),
+                category: r.category,
+                amount: r.amount,
+                typical_amount: r.mean.round_dp(2),
+            })
+            .collect())
+    }
+
+    /// 期初（`start` 当天结束时）与期末的净资产：取每个账户当天及之前最近的余额记录，
+    /// 没有记录时使用当前余额（期间内新建的账户期初按 0 计）
+    async fn net_worth(
+        &self,
+        ledgers: &[Uuid],
+        currency: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Option<DigestNetWorth>, ServiceError> {
+        let row: (i64, Decimal, Decimal) = sqlx::query_as(
+            r#"
+            WITH balances AS (
+                SELECT CASE WHEN a.account_main_type = 'liability' THEN -1 ELSE 1 END AS sign,
+                       COALESCE(
+                           (SELECT b.balance FROM account_balances b
+                            WHERE b.account_id = a.id AND b.balance_date <= $3
+                            ORDER BY b.balance_date DESC LIMIT 1),
+                           CASE WHEN a.created_at::date > $3 THEN 0 END,
+                           a.current_balance, 0) AS start_balance,
+                       COALESCE(
+                           (SELECT b.balance FROM account_balances b
+                            WHERE b.account_id = a.id AND b.balance_date <= $4
+                            ORDER BY b.balance_date DESC LIMIT 1),
+                           a.current_balance, 0) AS end_balance
+                FROM accounts a
+                WHERE a.ledger_id = ANY($1) AND a.deleted_at IS NULL
+                  AND COALESCE(a.is_included_in_total, true)
+                  AND COALESCE(a.currency, 'CNY') = $2
+            )
+            SELECT COUNT(*),
+                   COALESCE(SUM(CASE WHEN sign < 0 THEN -ABS(start_balance) ELSE start_balance END), 0)::numeric,
+                   COALESCE(SUM(CASE WHEN sign < 0 THEN -ABS(end_balance) ELSE end_balance END), 0)::numeric
+            FROM balances
+            "#,
+        )
+        .bind(ledgers)
+        .bind(currency)
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.pool)
+        .await?;
+        let (accounts, start, end) = row;
+        Ok((accounts > 0).then(|| DigestNetWorth {
+            start,
+            end,
+            change: end - start,
+        }))
+    }
+
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        query: &DigestQuery,
+    ) -> Result<Vec<FinancialDigest>, ServiceError> {
+        let period = match query.period.as_deref() {
+            Some(value) => Some(
+                DigestPeriod::parse(value)
+                    .ok_or_else(|| ServiceError::validation("period 只能是 weekly 或 monthly"))?,
+            ),
+            None => None,
+        };
+        Ok(sqlx::query_as(&format!(
+            r#"
+            SELECT {} FROM financial_digests
+            WHERE user_id = $1 AND ($2::text IS NULL OR period = $2)
+            ORDER BY period_start DESC, created_at DESC
+            LIMIT $3
+            "#,
+            DIGEST_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(period.map(|p| p.as_str()))
+        .bind(query.limit.unwrap_or(20).clamp(1, 100))
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get(&self, user_id: Uuid, id: Uuid) -> Result<FinancialDigest, ServiceError> {
+        sqlx::query_as(&format!(
+            "SELECT {} FROM financial_digests WHERE id = $1 AND user_id = $2",
+            DIGEST_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("FinancialDigest", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_digest_periods() {
+        // 2026-10-18 是周日
+        let sunday = date(2026, 10, 18);
+        assert_eq!(
+            DigestPeriod::Weekly.last_completed(sunday),
+            (date(2026, 10, 5), date(2026, 10, 11))
+        );
+        let monday = date(2026, 10, 19);
+        assert_eq!(
+            DigestPeriod::Weekly.last_completed(monday),
+            (date(2026, 10, 12), date(2026, 10, 18))
+        );
+        assert_eq!(
+            DigestPeriod::Weekly.previous(date(2026, 10, 12)),
+            (date(2026, 10, 5), date(2026, 10, 11))
+        );
+        assert_eq!(
+            DigestPeriod::Monthly.last_completed(date(2026, 3, 1)),
+            (date(2026, 2, 1), date(2026, 2, 28))
+        );
+        assert_eq!(
+            DigestPeriod::Monthly.previous(date(2026, 3, 1)),
+            (date(2026, 2, 1), date(2026, 2, 28))
+        );
+        assert_eq!(
+            DigestPeriod::Monthly.upcoming_window(date(2026, 1, 31)),
+            (date(2026, 1, 31), date(2026, 2, 27))
+        );
+
+        // 周期刚结束时等到摘要时刻再生成，之后任何时候都补发同一个周期
+        let at = |d: NaiveDate, h: u32| d.and_hms_opt(h, 30, 0).unwrap();
+        assert_eq!(DigestPeriod::Weekly.due(at(monday, 7), 8), None);
+        assert_eq!(
+            DigestPeriod::Weekly.due(at(monday, 8), 8),
+            Some((date(2026, 10, 12), date(2026, 10, 18)))
+        );
+        assert_eq!(
+            DigestPeriod::Weekly.due(at(date(2026, 10, 21), 0), 8),
+            Some((date(2026, 10, 12), date(2026, 10, 18)))
+        );
+        assert_eq!(DigestPeriod::Monthly.due(at(date(2026, 11, 1), 6), 8), None);
+    }
+
+    #[test]
+    fn test_budget_window() {
+        let reference = date(2026, 8, 19);
+        assert_eq!(budget_window("daily", reference), (reference, reference));
+        assert_eq!(
+            budget_window("weekly", reference),
+            (date(2026, 8, 17), date(2026, 8, 23))
+        );
+        assert_eq!(
+            budget_window("monthly", reference),
+            (date(2026, 8, 1), date(2026, 8, 31))
+        );
+        assert_eq!(
+            budget_window("quarterly", reference),
+            (date(2026, 7, 1), date(2026, 9, 30))
+        );
+        assert_eq!(
+            budget_window("yearly", reference),
+            (date(2026, 1, 1), date(2026, 12, 31))
+        );
+    }
+
+    #[test]
+    fn test_unusual_detection() {
+        // 样本不足
+        assert!(!is_unusual(dec!(1000), dec!(50), Some(dec!(10)), 4));
+        // 超过 2 倍均值且超出 3 个标准差
+        assert!(is_unusual(dec!(1000), dec!(50), Some(dec!(10)), 12));
+        // 波动本来就很大的分类
+        assert!(!is_unusual(dec!(300), dec!(100), Some(dec!(80)), 12));
+        // 只有一种金额（标准差为 0）时，翻倍即视为异常
+        assert!(is_unusual(dec!(60), dec!(30), Some(dec!(0)), 6));
+        assert!(!is_unusual(dec!(59), dec!(30), None, 6));
+    }
+
+    #[test]
+    fn test_headline_is_localized() {
+        let report = DigestReport {
+            period: DigestPeriod::Weekly,
+            period_start: date(2026, 10, 12),
+            period_end: date(2026, 10, 18),
+            family_name: "小家".to_string(),
+            currency: "CNY".to_string(),
+            income: dec!(5000),
+            expense: dec!(1200),
+            previous_income: dec!(0),
+            previous_expense: dec!(1000),
+            transaction_count: 18,
+            unvalued_count: 0,
+            top_categories: Vec::new(),
+            budgets_at_risk: vec![DigestBudget {
+                budget_id: Uuid::nil(),
+                name: "餐饮".to_string(),
+                spent: dec!(900),
+                limit: dec!(1000),
+                percentage: dec!(90.0),
+            }],
+            upcoming: Vec::new(),
+            unusual: Vec::new(),
+            net_worth: None,
+        };
+        assert_eq!(report.expense_change_percent(), Some(dec!(20.0)));
+        assert!(!report.is_empty());
+
+        let (title, body) = report.headline(EmailLocale::ZhCn);
+        assert_eq!(title, "小家 周报");
+        assert_eq!(
+            body,
+            "支出 1200 CNY (+20.0%)，收入 5000 CNY；1 项预算接近上限"
+        );
+        let (title, body) = report.headline(EmailLocale::En);
+        assert_eq!(title, "小家 weekly summary");
+        assert!(body.starts_with("Spent 1200 CNY (+20.0%), earned 5000 CNY; 1 budget(s)"));
+    }
+}
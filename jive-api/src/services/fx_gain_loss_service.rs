@@ -0,0 +1,603 @@
+//! 外币账户汇兑损益报表
+//!
+//! 把外币账户在期间内的本位币价值变动拆分为：
+//! - 资金流动：期间内各笔收支按交易日汇率折算（优先使用交易已保存的 base_amount）
+//! - 汇率重估：价值变动中扣除资金流动后的部分
+//!
+//! 汇率重估进一步拆分为已实现与未实现损益。账户持仓按移动加权平均成本记账：
+//! 流出时按平均成本结转，与流出当日价值之差即已实现损益，其中转账流出视为换汇单独列示；
+//! 期末持仓市值与剩余成本之差为未实现损益。因此恒有
+//! `汇率重估 = 已实现损益 + 未实现损益变动`。
+//!
+//! 期初余额由当前余额倒推（当前余额减去之后的交易），期初/期末汇率优先取
+//! `account_snapshots.exchange_rate`，没有快照时按汇率图换算。
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::currency_service::CurrencyService;
+use super::rate_graph::RateGraph;
+use super::ServiceError;
+use crate::config::RateResolverConfig;
+
+/// 移动加权平均成本持仓（支持负持仓，如外币信用卡欠款）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AverageCostPosition {
+    /// 持有的外币数量
+    pub units: Decimal,
+    /// 对应的本位币成本
+    pub cost: Decimal,
+}
+
+impl AverageCostPosition {
+    pub fn new(units: Decimal, rate: Decimal) -> Self {
+        Self {
+            units,
+            cost: units * rate,
+        }
+    }
+
+    /// 平均成本汇率
+    pub fn average_rate(&self) -> Option<Decimal> {
+        (!self.units.is_zero()).then(|| self.cost / self.units)
+    }
+
+    pub fn market_value(&self, rate: Decimal) -> Decimal {
+        self.units * rate
+    }
+
+    /// 按当日汇率记入一笔变动（正数流入、负数流出），返回已实现损益
+    ///
+    /// 与持仓同向的变动按当日汇率加仓；反向变动先按平均成本冲减持仓，
+    /// 超出持仓的部分按当日汇率建立反向持仓。
+    pub fn apply(&mut self, delta: Decimal, rate: Decimal) -> Decimal {
+        if delta.is_zero() {
+            return Decimal::ZERO;
+        }
+        if self.units.is_zero() || self.units.is_sign_positive() == delta.is_sign_positive() {
+            self.units += delta;
+            self.cost += delta * rate;
+            return Decimal::ZERO;
+        }
+
+        let reduce = delta.abs().min(self.units.abs());
+        let closed = if self.units.is_sign_positive() {
+            reduce
+        } else {
+            -reduce
+        };
+        let cost_removed = self.cost * closed / self.units;
+        let realized = closed * rate - cost_removed;
+        self.units -= closed;
+        self.cost -= cost_removed;
+        if self.units.is_zero() {
+            self.cost = Decimal::ZERO;
+        }
+
+        let remainder = delta + closed;
+        if !remainder.is_zero() {
+            self.units += remainder;
+            self.cost += remainder * rate;
+        }
+        realized
+    }
+}
+
+/// 账户的一笔余额变动
+#[derive(Debug, Clone)]
+pub struct FxFlow {
+    pub date: NaiveDate,
+    /// 外币金额，流入为正
+    pub units: Decimal,
+    /// 交易日汇率（外币 -> 本位币）
+    pub rate: Decimal,
+    /// 转出到其他币种（或未知目标）账户的转账
+    pub is_conversion: bool,
+}
+
+/// 期间分析结果（本位币金额未舍入）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FxPeriodAnalysis {
+    pub opening_units: Decimal,
+    pub closing_units: Decimal,
+    pub opening_value: Decimal,
+    pub closing_value: Decimal,
+    pub net_flows: Decimal,
+    pub fx_revaluation: Decimal,
+    pub realized_gain: Decimal,
+    pub realized_conversion_gain: Decimal,
+    pub unrealized_opening: Decimal,
+    pub unrealized_closing: Decimal,
+    pub closing_cost: Decimal,
+}
+
+/// 从开户持仓开始按时间顺序回放变动，计算 [start, end] 期间的汇兑损益
+///
+/// `flows` 需按日期升序且不晚于 `end`。
+pub fn analyze_period(
+    initial: AverageCostPosition,
+    flows: &[FxFlow],
+    start: NaiveDate,
+    opening_rate: Decimal,
+    closing_rate: Decimal,
+) -> FxPeriodAnalysis {
+    let mut position = initial;
+    let mut flows = flows.iter().peekable();
+    while let Some(flow) = flows.next_if(|f| f.date < start) {
+        position.apply(flow.units, flow.rate);
+    }
+
+    let mut analysis = FxPeriodAnalysis {
+        opening_units: position.units,
+        opening_value: position.market_value(opening_rate),
+        unrealized_opening: position.market_value(opening_rate) - position.cost,
+        ..Default::default()
+    };
+
+    for flow in flows {
+        analysis.net_flows += flow.units * flow.rate;
+        let realized = position.apply(flow.units, flow.rate);
+        analysis.realized_gain += realized;
+        if flow.is_conversion {
+            analysis.realized_conversion_gain += realized;
+        }
+    }
+
+    analysis.closing_units = position.units;
+    analysis.closing_value = position.market_value(closing_rate);
+    analysis.closing_cost = position.cost;
+    analysis.unrealized_closing = analysis.closing_value - position.cost;
+    analysis.fx_revaluation = analysis.closing_value - analysis.opening_value - analysis.net_flows;
+    analysis
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccountFxGainLoss {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub currency: String,
+    /// 期初 / 期末外币余额
+    pub opening_balance: Decimal,
+    pub closing_balance: Decimal,
+    /// 期初 / 期末汇率（外币 -> 本位币）
+    pub opening_rate: Option<Decimal>,
+    pub closing_rate: Option<Decimal>,
+    /// 以下为本位币金额
+    pub opening_value: Decimal,
+    pub closing_value: Decimal,
+    /// 资金流动（按交易日汇率折算）
+    pub net_flows: Decimal,
+    /// 汇率重估 = 期末价值 - 期初价值 - 资金流动
+    pub fx_revaluation: Decimal,
+    /// 已实现损益（全部流出）
+    pub realized_gain: Decimal,
+    /// 其中：换汇转账的已实现损益
+    pub realized_conversion_gain: Decimal,
+    pub unrealized_gain_opening: Decimal,
+    pub unrealized_gain_closing: Decimal,
+    pub unrealized_change: Decimal,
+    /// 期末持仓的平均成本
+    pub cost_basis: Decimal,
+    /// 期初或期末汇率缺失，本位币金额无法计算
+    pub missing_rate: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct FxGainLossTotals {
+    pub opening_value: Decimal,
+    pub closing_value: Decimal,
+    pub net_flows: Decimal,
+    pub fx_revaluation: Decimal,
+    pub realized_gain: Decimal,
+    pub realized_conversion_gain: Decimal,
+    pub unrealized_change: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FxGainLossReport {
+    pub base_currency: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub accounts: Vec<AccountFxGainLoss>,
+    pub totals: FxGainLossTotals,
+}
+
+/// 按日期缓存的汇率图
+struct RateLookup<'a> {
+    pool: &'a PgPool,
+    base_currency: String,
+    config: &'a RateResolverConfig,
+    graphs: HashMap<NaiveDate, RateGraph>,
+}
+
+impl RateLookup<'_> {
+    async fn rate(
+        &mut self,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Decimal>, ServiceError> {
+        if currency.eq_ignore_ascii_case(&self.base_currency) {
+            return Ok(Some(Decimal::ONE));
+        }
+        if !self.graphs.contains_key(&date) {
+            let graph = RateGraph::load(self.pool, date, self.config).await?;
+            self.graphs.insert(date, graph);
+        }
+        Ok(self.graphs[&date]
+            .resolve_with(currency, &self.base_currency, self.config)
+            .ok()
+            .map(|resolved| resolved.rate))
+    }
+}
+
+struct AccountRow {
+    id: Uuid,
+    name: String,
+    currency: String,
+    current_balance: Decimal,
+    opened_on: NaiveDate,
+}
+
+struct FlowRow {
+    date: NaiveDate,
+    units: Decimal,
+    base_amount: Option<Decimal>,
+    is_conversion: bool,
+}
+
+pub struct FxGainLossService {
+    pool: PgPool,
+}
+
+impl FxGainLossService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn report(
+        &self,
+        family_id: Uuid,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        config: &RateResolverConfig,
+    ) -> Result<FxGainLossReport, ServiceError> {
+        if start_date > end_date {
+            return Err(ServiceError::validation(
+                "start_date must not be after end_date",
+            ));
+        }
+
+        let base_currency = CurrencyService::new(self.pool.clone())
+            .get_family_currency_settings(family_id)
+            .await?
+            .base_currency
+            .to_uppercase();
+        let mut rates = RateLookup {
+            pool: &self.pool,
+            base_currency: base_currency.clone(),
+            config,
+            graphs: HashMap::new(),
+        };
+
+        let accounts = self.foreign_accounts(family_id, &base_currency).await?;
+        let mut flows_by_account = self
+            .account_flows(
+                &accounts.iter().map(|a| a.id).collect::<Vec<_>>(),
+                &base_currency,
+            )
+            .await?;
+
+        let mut report = FxGainLossReport {
+            base_currency,
+            start_date,
+            end_date,
+            accounts: Vec::with_capacity(accounts.len()),
+            totals: FxGainLossTotals::default(),
+        };
+
+        for account in accounts {
+            let rows = flows_by_account.remove(&account.id).unwrap_or_default();
+            let entry = self
+                .analyze_account(&mut rates, &account, rows, start_date, end_date)
+                .await?;
+
+            if !entry.missing_rate {
+                let totals = &mut report.totals;
+                totals.opening_value += entry.opening_value;
+                totals.closing_value += entry.closing_value;
+                totals.net_flows += entry.net_flows;
+                totals.fx_revaluation += entry.fx_revaluation;
+                totals.realized_gain += entry.realized_gain;
+                totals.realized_conversion_gain += entry.realized_conversion_gain;
+                totals.unrealized_change += entry.unrealized_change;
+            }
+            report.accounts.push(entry);
+        }
+
+        Ok(report)
+    }
+
+    async fn analyze_account(
+        &self,
+        rates: &mut RateLookup<'_>,
+        account: &AccountRow,
+        rows: Vec<FlowRow>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<AccountFxGainLoss, ServiceError> {
+        let opening_rate = match self.snapshot_rate(account.id, start_date).await? {
+            Some(rate) => Some(rate),
+            None => rates.rate(&account.currency, start_date).await?,
+        };
+        let closing_rate = match self.snapshot_rate(account.id, end_date).await? {
+            Some(rate) => Some(rate),
+            None => rates.rate(&account.currency, end_date).await?,
+        };
+
+        // 开户余额 = 当前余额 - 全部交易变动
+        let total_units: Decimal = rows.iter().map(|r| r.units).sum();
+        let initial_units = account.current_balance - total_units;
+
+        // 汇率缺失时沿用上一笔已知汇率，开头缺失则使用期初汇率
+        let mut last_rate = rates.rate(&account.currency, account.opened_on).await?;
+        let initial_rate = last_rate.or(opening_rate).unwrap_or(Decimal::ZERO);
+
+        let mut flows = Vec::new();
+        for row in rows.iter().filter(|r| r.date <= end_date) {
+            let rate = match row.base_amount {
+                Some(base) if !row.units.is_zero() => Some((base / row.units).abs()),
+                _ => rates.rate(&account.currency, row.date).await?,
+            };
+            if rate.is_some() {
+                last_rate = rate;
+            }
+            flows.push(FxFlow {
+                date: row.date,
+                units: row.units,
+                rate: rate.or(last_rate).or(opening_rate).unwrap_or(Decimal::ZERO),
+                is_conversion: row.is_conversion,
+            });
+        }
+
+        let analysis = analyze_period(
+            AverageCostPosition::new(initial_units, initial_rate),
+            &flows,
+            start_date,
+            opening_rate.unwrap_or(Decimal::ZERO),
+            closing_rate.unwrap_or(Decimal::ZERO),
+        );
+        let missing_rate = opening_rate.is_none() || closing_rate.is_none();
+        let money = |value: Decimal| {
+            if missing_rate {
+                Decimal::ZERO
+            } else {
+                value.round_dp(2)
+            }
+        };
+
+        Ok(AccountFxGainLoss {
+            account_id: account.id,
+            account_name: account.name.clone(),
+            currency: account.currency.clone(),
+            opening_balance: analysis.opening_units,
+            closing_balance: analysis.closing_units,
+            opening_rate,
+            closing_rate,
+            opening_value: money(analysis.opening_value),
+            closing_value: money(analysis.closing_value),
+            net_flows: money(analysis.net_flows),
+            fx_revaluation: money(analysis.fx_revaluation),
+            realized_gain: money(analysis.realized_gain),
+            realized_conversion_gain: money(analysis.realized_conversion_gain),
+            unrealized_gain_opening: money(analysis.unrealized_opening),
+            unrealized_gain_closing: money(analysis.unrealized_closing),
+            unrealized_change: money(analysis.unrealized_closing - analysis.unrealized_opening),
+            cost_basis: money(analysis.closing_cost),
+            missing_rate,
+        })
+    }
+
+    async fn foreign_accounts(
+        &self,
+        family_id: Uuid,
+        base_currency: &str,
+    ) -> Result<Vec<AccountRow>, ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.id, a.name, UPPER(a.currency) AS currency,
+                   COALESCE(a.current_balance, 0)::numeric AS current_balance,
+                   a.created_at
+            FROM accounts a
+            JOIN ledgers l ON l.id = a.ledger_id
+            WHERE l.family_id = $1
+              AND a.deleted_at IS NULL
+              AND a.currency IS NOT NULL
+              AND UPPER(a.currency) <> $2
+            ORDER BY UPPER(a.currency), a.name
+            "#,
+        )
+        .bind(family_id)
+        .bind(base_currency)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let created_at: Option<DateTime<Utc>> = row.get("created_at");
+                AccountRow {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    currency: row.get("currency"),
+                    current_balance: row.get("current_balance"),
+                    opened_on: created_at.unwrap_or_else(Utc::now).date_naive(),
+                }
+            })
+            .collect())
+    }
+
+    /// 各账户的余额变动，符号与交易处理器更新余额的方式一致：收入为正，支出和转出为负
+    async fn account_flows(
+        &self,
+        account_ids: &[Uuid],
+        base_currency: &str,
+    ) -> Result<HashMap<Uuid, Vec<FlowRow>>, ServiceError> {
+        let mut flows: HashMap<Uuid, Vec<FlowRow>> = HashMap::new();
+        if account_ids.is_empty() {
+            return Ok(flows);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT t.account_id, t.transaction_date,
+                   CASE WHEN t.transaction_type = 'income' THEN t.amount ELSE -t.amount END AS units,
+                   -- 本位币变更后尚未重算的估值不可用，改按汇率图换算
+                   CASE WHEN UPPER(t.base_currency) <> $2 THEN NULL
+                        WHEN t.transaction_type = 'income' THEN t.base_amount
+                        ELSE -t.base_amount END AS base_amount,
+                   (t.transaction_type = 'transfer'
+                    AND (ta.currency IS NULL OR UPPER(ta.currency) <> UPPER(a.currency)))
+                       AS is_conversion
+            FROM transactions t
+            JOIN accounts a ON a.id = t.account_id
+            LEFT JOIN accounts ta ON ta.id = t.to_account_id
+            WHERE t.account_id = ANY($1)
+              AND t.deleted_at IS NULL
+            ORDER BY t.account_id, t.transaction_date, t.created_at, t.id
+            "#,
+        )
+        .bind(account_ids)
+        .bind(base_currency)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in rows {
+            flows
+                .entry(row.get("account_id"))
+                .or_default()
+                .push(FlowRow {
+                    date: row.get("transaction_date"),
+                    units: row.get("units"),
+                    base_amount: row.get("base_amount"),
+                    is_conversion: row.get::<Option<bool>, _>("is_conversion").unwrap_or(false),
+                });
+        }
+        Ok(flows)
+    }
+
+    /// 当天净资产快照中记录的账户汇率
+    async fn snapshot_rate(
+        &self,
+        account_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<Option<Decimal>, ServiceError> {
+        let rate = sqlx::query_scalar::<_, Decimal>(
+            r#"
+            SELECT s.exchange_rate
+            FROM account_snapshots s
+            JOIN balance_snapshots b ON b.id = s.balance_snapshot_id
+            WHERE s.account_id = $1
+              AND b.snapshot_date = $2
+              AND s.exchange_rate > 0
+            LIMIT 1
+            "#,
+        )
+        .bind(account_id)
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn flow(d: u32, units: Decimal, rate: Decimal, is_conversion: bool) -> FxFlow {
+        FxFlow {
+            date: date(2026, 3, d),
+            units,
+            rate,
+            is_conversion,
+        }
+    }
+
+    #[test]
+    fn test_average_cost_realization() {
+        let mut position = AverageCostPosition::new(dec!(100), dec!(7));
+        assert_eq!(position.apply(dec!(100), dec!(7.2)), Decimal::ZERO);
+        assert_eq!(position.average_rate(), Some(dec!(7.1)));
+
+        // 按 7.3 换出 50：成本 355，价值 365
+        assert_eq!(position.apply(dec!(-50), dec!(7.3)), dec!(10));
+        assert_eq!(position.units, dec!(150));
+        assert_eq!(position.cost, dec!(1065));
+
+        // 超出持仓的流出建立负持仓
+        let realized = position.apply(dec!(-200), dec!(7.0));
+        assert_eq!(realized, dec!(-15));
+        assert_eq!(position.units, dec!(-50));
+        assert_eq!(position.cost, dec!(-350));
+    }
+
+    #[test]
+    fn test_short_position_buyback_realizes_loss() {
+        let mut position = AverageCostPosition::new(dec!(-100), dec!(7));
+        assert_eq!(position.apply(dec!(40), dec!(7.2)), dec!(-8));
+        assert_eq!(position.units, dec!(-60));
+    }
+
+    #[test]
+    fn test_period_identity() {
+        let flows = vec![
+            flow(1, dec!(200), dec!(7.0), false),
+            flow(10, dec!(100), dec!(7.2), false),
+            flow(15, dec!(-150), dec!(7.3), true),
+            flow(20, dec!(-20), dec!(7.25), false),
+        ];
+        let a = analyze_period(
+            AverageCostPosition::new(dec!(0), dec!(7)),
+            &flows,
+            date(2026, 3, 5),
+            dec!(7.1),
+            dec!(7.4),
+        );
+
+        assert_eq!(a.opening_units, dec!(200));
+        assert_eq!(a.closing_units, dec!(130));
+        assert_eq!(a.opening_value, dec!(1420));
+        assert_eq!(a.closing_value, dec!(962));
+        // 720 - 1095 - 145
+        assert_eq!(a.net_flows, dec!(-520));
+        assert_eq!(a.fx_revaluation, dec!(62));
+        // 平均成本除不尽，比较时忽略末位舍入
+        assert_eq!(
+            a.fx_revaluation,
+            (a.realized_gain + a.unrealized_closing - a.unrealized_opening).round_dp(10)
+        );
+        assert!(a.realized_conversion_gain > Decimal::ZERO);
+        assert!(a.realized_gain > a.realized_conversion_gain);
+    }
+
+    #[test]
+    fn test_no_activity_is_pure_revaluation() {
+        let a = analyze_period(
+            AverageCostPosition::new(dec!(1000), dec!(0.05)),
+            &[],
+            date(2026, 3, 1),
+            dec!(0.048),
+            dec!(0.050),
+        );
+        assert_eq!(a.net_flows, Decimal::ZERO);
+        assert_eq!(a.fx_revaluation, dec!(2));
+        assert_eq!(a.realized_gain, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,639 @@
+//! 历史汇率离线导入
+//!
+//! 支持的文件格式：
+//! - ECB `eurofxref-hist.csv`：`Date,USD,JPY,...`，每行为 EUR 对各币种的汇率，缺失值为 `N/A`
+//! - ECB `eurofxref-hist.xml`：`<Cube time="..."><Cube currency="USD" rate="..."/></Cube>`
+//! - 通用 CSV：`date,from,to,rate`，表头可选，`#` 开头的行为注释
+//!
+//! 写入依赖 018 迁移的唯一索引 `(from_currency, to_currency, date)`，重复导入不会产生新行；
+//! 手动汇率不会被覆盖。导入后重算受影响日期的 `change_24h/7d/30d`（042 迁移）。
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+use super::transaction_valuation_service::{
+    TransactionValuationService, ValuationReason, ValuationScope,
+};
+use super::ServiceError;
+
+/// 每条 INSERT 语句写入的行数
+const INSERT_CHUNK: usize = 1000;
+/// 报告中保留的解析错误条数
+const MAX_REPORTED_ERRORS: usize = 20;
+/// 计算 24h/7d/30d 变化时，向前查找最近一条汇率的宽限天数（周末与节假日无报价）
+const LOOKBACK_GRACE_DAYS: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum FxImportFormat {
+    EcbCsv,
+    EcbXml,
+    Csv,
+}
+
+impl FxImportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FxImportFormat::EcbCsv => "ecb-csv",
+            FxImportFormat::EcbXml => "ecb-xml",
+            FxImportFormat::Csv => "csv",
+        }
+    }
+
+    /// 根据内容判断格式
+    pub fn detect(content: &str) -> Self {
+        let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+        if trimmed.starts_with('<') {
+            return FxImportFormat::EcbXml;
+        }
+        let header: Vec<String> = trimmed
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .map(|c| c.trim().to_ascii_lowercase())
+            .collect();
+        let is_ecb = header.first().map(|c| c == "date").unwrap_or(false)
+            && header.get(1).map(|c| c != "from").unwrap_or(false);
+        if is_ecb {
+            FxImportFormat::EcbCsv
+        } else {
+            FxImportFormat::Csv
+        }
+    }
+
+    /// 数据默认来源名称（写入 exchange_rates.source）
+    pub fn default_source(&self) -> &'static str {
+        match self {
+            FxImportFormat::EcbCsv | FxImportFormat::EcbXml => "ecb",
+            FxImportFormat::Csv => "import",
+        }
+    }
+}
+
+impl FromStr for FxImportFormat {
+    type Err = ServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ecb-csv" | "ecb_csv" => Ok(FxImportFormat::EcbCsv),
+            "ecb-xml" | "ecb_xml" | "xml" => Ok(FxImportFormat::EcbXml),
+            "csv" | "generic" => Ok(FxImportFormat::Csv),
+            other => Err(ServiceError::ValidationError(format!(
+                "Unsupported rate file format: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoricalRate {
+    pub date: NaiveDate,
+    pub from: String,
+    pub to: String,
+    pub rate: Decimal,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedRates {
+    pub rates: Vec<HistoricalRate>,
+    /// 无法解析的行/值数量
+    pub rejected: usize,
+    pub errors: Vec<String>,
+}
+
+impl ParsedRates {
+    fn reject(&mut self, message: String) {
+        self.rejected += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(message);
+        }
+    }
+}
+
+/// 解析汇率文件；单行错误计入 `rejected`，文件结构错误直接返回错误
+pub fn parse_rates(format: FxImportFormat, content: &str) -> Result<ParsedRates, ServiceError> {
+    let content = content.trim_start_matches('\u{feff}');
+    match format {
+        FxImportFormat::EcbCsv => parse_ecb_csv(content),
+        FxImportFormat::EcbXml => parse_ecb_xml(content),
+        FxImportFormat::Csv => Ok(parse_generic_csv(content)),
+    }
+}
+
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").ok()
+}
+
+fn parse_rate(raw: &str) -> Option<Decimal> {
+    let raw = raw.trim();
+    Decimal::from_str(raw)
+        .or_else(|_| Decimal::from_scientific(raw))
+        .ok()
+        .filter(|r| *r > Decimal::ZERO)
+}
+
+fn is_currency_code(code: &str) -> bool {
+    (2..=10).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn parse_ecb_csv(content: &str) -> Result<ParsedRates, ServiceError> {
+    let mut lines = content.lines().enumerate();
+    let header = lines
+        .next()
+        .map(|(_, l)| l)
+        .filter(|l| l.trim().to_ascii_lowercase().starts_with("date"))
+        .ok_or_else(|| {
+            ServiceError::ValidationError("ECB CSV must start with a Date header".to_string())
+        })?;
+    let currencies: Vec<String> = header
+        .split(',')
+        .skip(1)
+        .map(|c| c.trim().to_uppercase())
+        .collect();
+
+    let mut parsed = ParsedRates::default();
+    for (idx, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut cells = line.split(',');
+        let Some(date) = cells.next().and_then(parse_date) else {
+            parsed.reject(format!("line {}: invalid date", idx + 1));
+            continue;
+        };
+        for (code, cell) in currencies.iter().zip(cells) {
+            let cell = cell.trim();
+            if code.is_empty() || cell.is_empty() || cell.eq_ignore_ascii_case("N/A") {
+                continue;
+            }
+            match parse_rate(cell) {
+                Some(rate) => parsed.rates.push(HistoricalRate {
+                    date,
+                    from: "EUR".to_string(),
+                    to: code.clone(),
+                    rate,
+                }),
+                None => parsed.reject(format!("line {}: invalid rate for {}", idx + 1, code)),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// 读取标签中的属性值（单/双引号均可）
+fn tag_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(name) {
+        let before_ok = pos == 0
+            || rest[..pos]
+                .chars()
+                .last()
+                .map(|c| c.is_whitespace())
+                .unwrap_or(false);
+        let after = rest[pos + name.len()..].trim_start();
+        if before_ok {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let body = &value[1..];
+                    return body.find(quote).map(|end| &body[..end]);
+                }
+            }
+        }
+        rest = &rest[pos + name.len()..];
+    }
+    None
+}
+
+fn parse_ecb_xml(content: &str) -> Result<ParsedRates, ServiceError> {
+    let mut parsed = ParsedRates::default();
+    let mut current_date: Option<NaiveDate> = None;
+    let mut seen_cube = false;
+    let mut rest = content;
+
+    while let Some(start) = rest.find("<Cube") {
+        let after = &rest[start + "<Cube".len()..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let tag = &after[..end];
+        rest = &after[end + 1..];
+        seen_cube = true;
+
+        if let Some(time) = tag_attr(tag, "time") {
+            current_date = parse_date(time);
+            if current_date.is_none() {
+                parsed.reject(format!("invalid time attribute: {}", time));
+            }
+            continue;
+        }
+        let (Some(code), Some(rate)) = (tag_attr(tag, "currency"), tag_attr(tag, "rate")) else {
+            continue;
+        };
+        let Some(date) = current_date else {
+            continue;
+        };
+        match parse_rate(rate) {
+            Some(rate) => parsed.rates.push(HistoricalRate {
+                date,
+                from: "EUR".to_string(),
+                to: code.trim().to_uppercase(),
+                rate,
+            }),
+            None => parsed.reject(format!("{}: invalid rate for {}", date, code)),
+        }
+    }
+
+    if !seen_cube {
+        return Err(ServiceError::ValidationError(
+            "ECB XML contains no Cube elements".to_string(),
+        ));
+    }
+    Ok(parsed)
+}
+
+fn parse_generic_csv(content: &str) -> ParsedRates {
+    let mut parsed = ParsedRates::default();
+    let mut first = true;
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        let is_header = first && cells.first().is_some_and(|c| parse_date(c).is_none());
+        first = false;
+        if is_header {
+            continue;
+        }
+        if cells.len() < 4 {
+            parsed.reject(format!("line {}: expected date,from,to,rate", idx + 1));
+            continue;
+        }
+        let from = cells[1].to_uppercase();
+        let to = cells[2].to_uppercase();
+        let row = (
+            parse_date(cells[0]),
+            is_currency_code(&from) && is_currency_code(&to) && from != to,
+            parse_rate(cells[3]),
+        );
+        match row {
+            (Some(date), true, Some(rate)) => parsed.rates.push(HistoricalRate {
+                date,
+                from,
+                to,
+                rate,
+            }),
+            (None, _, _) => parsed.reject(format!("line {}: invalid date", idx + 1)),
+            (_, false, _) => parsed.reject(format!("line {}: invalid currency pair", idx + 1)),
+            (_, _, None) => parsed.reject(format!("line {}: invalid rate", idx + 1)),
+        }
+    }
+    parsed
+}
+
+/// 导入选项
+#[derive(Debug, Clone, Default)]
+pub struct FxImportOptions {
+    /// 写入 exchange_rates.source；为空时按格式取默认值
+    pub source: Option<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// 只导入涉及这些币种的汇率（大写）
+    pub currencies: Option<Vec<String>>,
+}
+
+impl FxImportOptions {
+    /// 过滤并去重（同一币种对同一天以文件中最后一条为准），结果按日期排序
+    pub fn select(&self, rates: Vec<HistoricalRate>) -> Vec<HistoricalRate> {
+        let currencies: Option<BTreeSet<String>> = self
+            .currencies
+            .as_ref()
+            .map(|list| list.iter().map(|c| c.trim().to_uppercase()).collect());
+        let mut unique: BTreeMap<(NaiveDate, String, String), Decimal> = BTreeMap::new();
+        for rate in rates {
+            if self.since.is_some_and(|d| rate.date < d)
+                || self.until.is_some_and(|d| rate.date > d)
+            {
+                continue;
+            }
+            if let Some(set) = &currencies {
+                if !set.contains(&rate.from) && !set.contains(&rate.to) {
+                    continue;
+                }
+            }
+            unique.insert((rate.date, rate.from, rate.to), rate.rate);
+        }
+        unique
+            .into_iter()
+            .map(|((date, from, to), rate)| HistoricalRate {
+                date,
+                from,
+                to,
+                rate,
+            })
+            .collect()
+    }
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FxImportSummary {
+    pub format: FxImportFormat,
+    pub source: String,
+    /// 过滤去重后的汇率条数
+    pub selected: usize,
+    pub inserted: u64,
+    pub updated: u64,
+    /// 已存在且汇率相同，或为手动汇率而跳过的条数
+    pub unchanged: u64,
+    pub rejected: usize,
+    pub errors: Vec<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub currency_pairs: usize,
+    /// 重算了变化率的汇率行数
+    pub changes_recomputed: u64,
+}
+
+pub struct FxHistoryImporter {
+    pool: PgPool,
+}
+
+impl FxHistoryImporter {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 解析并导入文件内容；`format` 为空时自动识别
+    pub async fn import_content(
+        &self,
+        content: &str,
+        format: Option<FxImportFormat>,
+        options: &FxImportOptions,
+    ) -> Result<FxImportSummary, ServiceError> {
+        let format = format.unwrap_or_else(|| FxImportFormat::detect(content));
+        let parsed = parse_rates(format, content)?;
+        let rates = options.select(parsed.rates);
+        let source = options
+            .source
+            .clone()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| format.default_source().to_string());
+
+        let (inserted, updated) = self.upsert(&rates, &source).await?;
+
+        let pairs: BTreeSet<(String, String)> = rates
+            .iter()
+            .map(|r| (r.from.clone(), r.to.clone()))
+            .collect();
+        let start_date = rates.first().map(|r| r.date);
+        let end_date = rates.last().map(|r| r.date);
+
+        let changes_recomputed = match (start_date, end_date) {
+            (Some(start), Some(end)) => self.recompute_changes(&pairs, start, end).await?,
+            _ => 0,
+        };
+
+        if inserted + updated > 0 {
+            // 历史汇率变化后重算相关交易的本位币金额
+            let currencies: BTreeSet<String> = pairs
+                .iter()
+                .flat_map(|(from, to)| [from.clone(), to.clone()])
+                .collect();
+            if let Err(e) = TransactionValuationService::new(self.pool.clone())
+                .enqueue(
+                    ValuationReason::RateCorrected,
+                    ValuationScope {
+                        currencies: Some(currencies.into_iter().collect()),
+                        since_date: start_date,
+                        ..Default::default()
+                    },
+                )
+                .await
+            {
+                tracing::warn!("Failed to enqueue valuation after FX import: {:?}", e);
+            }
+        }
+
+        Ok(FxImportSummary {
+            format,
+            source,
+            selected: rates.len(),
+            inserted,
+            updated,
+            unchanged: (rates.len() as u64).saturating_sub(inserted + updated),
+            rejected: parsed.rejected,
+            errors: parsed.errors,
+            start_date,
+            end_date,
+            currency_pairs: pairs.len(),
+            changes_recomputed,
+        })
+    }
+
+    /// 批量写入；返回 (新增, 更新) 条数
+    async fn upsert(
+        &self,
+        rates: &[HistoricalRate],
+        source: &str,
+    ) -> Result<(u64, u64), ServiceError> {
+        let mut inserted = 0u64;
+        let mut updated = 0u64;
+
+        for chunk in rates.chunks(INSERT_CHUNK) {
+            let froms: Vec<String> = chunk.iter().map(|r| r.from.clone()).collect();
+            let tos: Vec<String> = chunk.iter().map(|r| r.to.clone()).collect();
+            let values: Vec<Decimal> = chunk.iter().map(|r| r.rate).collect();
+            let dates: Vec<NaiveDate> = chunk.iter().map(|r| r.date).collect();
+
+            let rows = sqlx::query(
+                r#"
+                INSERT INTO exchange_rates
+                    (id, from_currency, to_currency, rate, source, date, effective_date, is_manual)
+                SELECT gen_random_uuid(), x.from_currency, x.to_currency, x.rate, $5, x.date, x.date, false
+                FROM UNNEST($1::text[], $2::text[], $3::numeric[], $4::date[])
+                     AS x(from_currency, to_currency, rate, date)
+                ON CONFLICT (from_currency, to_currency, date)
+                DO UPDATE SET
+                    rate = EXCLUDED.rate,
+                    source = EXCLUDED.source,
+                    effective_date = EXCLUDED.effective_date,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE exchange_rates.is_manual IS NOT TRUE
+                  AND exchange_rates.rate IS DISTINCT FROM EXCLUDED.rate
+                RETURNING (xmax = 0) AS inserted
+                "#,
+            )
+            .bind(&froms)
+            .bind(&tos)
+            .bind(&values)
+            .bind(&dates)
+            .bind(source)
+            .fetch_all(&self.pool)
+            .await?;
+
+            for row in rows {
+                if row.get::<bool, _>("inserted") {
+                    inserted += 1;
+                } else {
+                    updated += 1;
+                }
+            }
+        }
+        Ok((inserted, updated))
+    }
+
+    /// 重算 `[start, end + 30天]` 内这些币种对的 24h/7d/30d 变化
+    ///
+    /// 基准取目标日期前 1/7/30 天当天或更早（最多再早 5 天）的最近一条汇率。
+    pub async fn recompute_changes(
+        &self,
+        pairs: &BTreeSet<(String, String)>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<u64, ServiceError> {
+        if pairs.is_empty() {
+            return Ok(0);
+        }
+        let froms: Vec<String> = pairs.iter().map(|(f, _)| f.clone()).collect();
+        let tos: Vec<String> = pairs.iter().map(|(_, t)| t.clone()).collect();
+
+        let result = sqlx::query(
+            r#"
+            WITH pairs AS (
+                SELECT * FROM UNNEST($1::text[], $2::text[]) AS p(from_currency, to_currency)
+            ),
+            targets AS (
+                SELECT er.id, er.rate,
+                    (SELECT p.rate FROM exchange_rates p
+                     WHERE p.from_currency = er.from_currency AND p.to_currency = er.to_currency
+                       AND p.date BETWEEN er.date - (1 + $5) AND er.date - 1
+                     ORDER BY p.date DESC LIMIT 1) AS rate_1d,
+                    (SELECT p.rate FROM exchange_rates p
+                     WHERE p.from_currency = er.from_currency AND p.to_currency = er.to_currency
+                       AND p.date BETWEEN er.date - (7 + $5) AND er.date - 7
+                     ORDER BY p.date DESC LIMIT 1) AS rate_7d,
+                    (SELECT p.rate FROM exchange_rates p
+                     WHERE p.from_currency = er.from_currency AND p.to_currency = er.to_currency
+                       AND p.date BETWEEN er.date - (30 + $5) AND er.date - 30
+                     ORDER BY p.date DESC LIMIT 1) AS rate_30d
+                FROM exchange_rates er
+                JOIN pairs ON pairs.from_currency = er.from_currency
+                          AND pairs.to_currency = er.to_currency
+                WHERE er.date BETWEEN $3 AND $4 + 30
+            )
+            UPDATE exchange_rates e
+            SET price_24h_ago = t.rate_1d,
+                price_7d_ago = t.rate_7d,
+                price_30d_ago = t.rate_30d,
+                change_24h = CASE WHEN t.rate_1d > 0
+                    THEN ROUND((t.rate - t.rate_1d) / t.rate_1d * 100, 4) END,
+                change_7d = CASE WHEN t.rate_7d > 0
+                    THEN ROUND((t.rate - t.rate_7d) / t.rate_7d * 100, 4) END,
+                change_30d = CASE WHEN t.rate_30d > 0
+                    THEN ROUND((t.rate - t.rate_30d) / t.rate_30d * 100, 4) END
+            FROM targets t
+            WHERE e.id = t.id
+            "#,
+        )
+        .bind(&froms)
+        .bind(&tos)
+        .bind(start)
+        .bind(end)
+        .bind(LOOKBACK_GRACE_DAYS)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_ecb_csv() {
+        let content = "\u{feff}Date,USD,JPY,CYP,\n\
+                       2024-01-05,1.0921,158.95,N/A,\n\
+                       2024-01-04,1.0953,abc,N/A,\n";
+        assert_eq!(FxImportFormat::detect(content), FxImportFormat::EcbCsv);
+        let parsed = parse_rates(FxImportFormat::EcbCsv, content).unwrap();
+        assert_eq!(parsed.rates.len(), 3);
+        assert_eq!(
+            parsed.rates[0],
+            HistoricalRate {
+                date: date(2024, 1, 5),
+                from: "EUR".into(),
+                to: "USD".into(),
+                rate: dec!(1.0921),
+            }
+        );
+        assert_eq!(parsed.rejected, 1);
+        assert!(parse_rates(FxImportFormat::EcbCsv, "USD,JPY\n1,2").is_err());
+    }
+
+    #[test]
+    fn test_parse_ecb_xml() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+  <gesmes:subject>Reference rates</gesmes:subject>
+  <Cube>
+    <Cube time="2024-01-05">
+      <Cube currency="USD" rate="1.0921"/>
+      <Cube currency='JPY' rate='158.95'/>
+    </Cube>
+    <Cube time="2024-01-04">
+      <Cube currency="USD" rate="1.0953"/>
+    </Cube>
+  </Cube>
+</gesmes:Envelope>"#;
+        assert_eq!(FxImportFormat::detect(content), FxImportFormat::EcbXml);
+        let parsed = parse_rates(FxImportFormat::EcbXml, content).unwrap();
+        assert_eq!(parsed.rates.len(), 3);
+        assert_eq!(parsed.rates[1].to, "JPY");
+        assert_eq!(parsed.rates[2].date, date(2024, 1, 4));
+        assert!(parse_rates(FxImportFormat::EcbXml, "<html></html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_generic_csv_and_select() {
+        let content = "date,from,to,rate\n\
+                       # comment\n\
+                       2024-01-02,usd,cny,7.10\n\
+                       2024-01-02,USD,CNY,7.12\n\
+                       2024-01-01,BTC,USD,42000\n\
+                       2024-01-03,USD,USD,1\n\
+                       2024-13-01,USD,JPY,140\n\
+                       2024-01-03,USD,JPY,-1\n";
+        assert_eq!(FxImportFormat::detect(content), FxImportFormat::Csv);
+        let parsed = parse_rates(FxImportFormat::Csv, content).unwrap();
+        assert_eq!(parsed.rates.len(), 3);
+        assert_eq!(parsed.rejected, 3);
+
+        // 同一天重复的币种对以最后一条为准，结果按日期排序
+        let selected = FxImportOptions::default().select(parsed.rates.clone());
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].from, "BTC");
+        assert_eq!(selected[1].rate, dec!(7.12));
+
+        let options = FxImportOptions {
+            since: Some(date(2024, 1, 2)),
+            currencies: Some(vec!["cny".into()]),
+            ..Default::default()
+        };
+        let selected = options.select(parsed.rates);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].to, "CNY");
+    }
+}
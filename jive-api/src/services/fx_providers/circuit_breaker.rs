@@ -0,0 +1,167 @@
+//! 数据源熔断器
+//!
+//! Closed：正常放行；连续失败达到阈值后进入 Open。
+//! Open：直接跳过该数据源；冷却期过后进入 HalfOpen。
+//! HalfOpen：只放行一次探测请求，成功则恢复 Closed，失败则重新 Open。
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// HalfOpen 状态下探测请求是否已发出
+    probe_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_in_flight: false,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// 是否允许发起请求；冷却期已过的 Open 会转为 HalfOpen 并放行一次
+    pub fn allow_request(&mut self, now: Instant) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                let elapsed = self
+                    .opened_at
+                    .map(|t| now.saturating_duration_since(t))
+                    .unwrap_or_default();
+                if elapsed >= self.cooldown {
+                    self.state = CircuitState::HalfOpen;
+                    self.probe_in_flight = true;
+                    true
+                } else {
+                    false
+                }
+            }
+            CircuitState::HalfOpen => {
+                if self.probe_in_flight {
+                    false
+                } else {
+                    self.probe_in_flight = true;
+                    true
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.probe_in_flight = false;
+    }
+
+    pub fn record_failure(&mut self, now: Instant) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.probe_in_flight = false;
+        let trip = match self.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => self.consecutive_failures >= self.failure_threshold,
+            CircuitState::Open => false,
+        };
+        if trip {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+        }
+    }
+
+    /// 距离允许下一次探测还有多久（仅 Open 状态）
+    pub fn retry_after(&self, now: Instant) -> Option<Duration> {
+        match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(t)) => Some(
+                self.cooldown
+                    .saturating_sub(now.saturating_duration_since(t)),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let now = Instant::now();
+        let mut cb = CircuitBreaker::new(3, Duration::from_secs(60));
+        for _ in 0..2 {
+            assert!(cb.allow_request(now));
+            cb.record_failure(now);
+        }
+        assert_eq!(cb.state(), CircuitState::Closed);
+        cb.record_failure(now);
+        assert_eq!(cb.state(), CircuitState::Open);
+        assert!(!cb.allow_request(now + Duration::from_secs(59)));
+        assert_eq!(
+            cb.retry_after(now + Duration::from_secs(50)),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_half_open_allows_single_probe() {
+        let now = Instant::now();
+        let mut cb = CircuitBreaker::new(1, Duration::from_secs(30));
+        cb.record_failure(now);
+        assert_eq!(cb.state(), CircuitState::Open);
+
+        let later = now + Duration::from_secs(30);
+        assert!(cb.allow_request(later));
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        assert!(!cb.allow_request(later));
+
+        // 探测失败重新熔断
+        cb.record_failure(later);
+        assert_eq!(cb.state(), CircuitState::Open);
+        assert!(!cb.allow_request(later + Duration::from_secs(1)));
+
+        // 再次探测成功后恢复
+        let recovered = later + Duration::from_secs(30);
+        assert!(cb.allow_request(recovered));
+        cb.record_success();
+        assert_eq!(cb.state(), CircuitState::Closed);
+        assert_eq!(cb.consecutive_failures(), 0);
+        assert!(cb.allow_request(recovered));
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let now = Instant::now();
+        let mut cb = CircuitBreaker::new(2, Duration::from_secs(30));
+        cb.record_failure(now);
+        cb.record_success();
+        cb.record_failure(now);
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+}
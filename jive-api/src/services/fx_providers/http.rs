@@ -0,0 +1,736 @@
+//! 公共 HTTP 汇率/价格数据源
+//!
+//! 原先写在 `ExchangeRateApiService` 中的私有方法，拆分为独立的 `RateProvider` 实现，
+//! 由 `ProviderRegistry` 按配置顺序调度。
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use super::{ProviderKind, RateProvider};
+use crate::services::ServiceError;
+
+// ============================================
+// 外部API响应模型
+// ============================================
+
+// Frankfurter API 响应
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct FrankfurterResponse {
+    amount: f64,
+    base: String,
+    date: String,
+    rates: HashMap<String, f64>,
+}
+
+// FXRatesAPI 响应
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct FxRatesApiResponse {
+    base: String,
+    rates: HashMap<String, f64>,
+    date: String,
+}
+
+// CoinGecko 币种列表响应
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct CoinGeckoCoinListItem {
+    id: String,
+    symbol: String,
+    name: String,
+}
+
+// CoinMarketCap API 响应
+#[derive(Debug, Deserialize)]
+struct CoinMarketCapResponse {
+    data: HashMap<String, Vec<CoinMarketCapQuote>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinMarketCapQuote {
+    quote: HashMap<String, CoinMarketCapQuoteData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct CoinMarketCapQuoteData {
+    price: f64,
+    percent_change_24h: Option<f64>,
+    percent_change_7d: Option<f64>,
+    percent_change_30d: Option<f64>,
+}
+
+// CoinCap API 响应
+#[derive(Debug, Deserialize)]
+struct CoinCapResponse {
+    data: CoinCapData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct CoinCapData {
+    id: String,
+    symbol: String,
+    price_usd: String,
+    change_percent_24_hr: Option<String>,
+    market_cap_usd: Option<String>,
+    volume_usd_24_hr: Option<String>,
+}
+
+// Binance ticker response
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct BinanceTicker {
+    symbol: String,
+    price: String,
+}
+
+// OKX API 响应
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct OkxResponse {
+    code: String,
+    data: Vec<OkxTickerData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+#[serde(rename_all = "camelCase")]
+struct OkxTickerData {
+    inst_id: String, // 交易对 BTC-USDT
+    last: String,    // 最新价格
+}
+
+// Gate.io API 响应
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct GateioTicker {
+    currency_pair: String, // 交易对 BTC_USDT
+    last: String,          // 最新价格
+}
+
+// ============================================
+// 币种ID映射结构
+// ============================================
+
+#[derive(Debug, Clone)]
+pub struct CoinIdMapping {
+    /// Symbol -> CoinGecko ID
+    pub coingecko: HashMap<String, String>,
+    /// Symbol -> CoinCap ID
+    pub coincap: HashMap<String, String>,
+    /// 最后更新时间
+    pub last_updated: DateTime<Utc>,
+}
+
+pub type SharedCoinMappings = Arc<RwLock<CoinIdMapping>>;
+
+impl CoinIdMapping {
+    pub fn new() -> Self {
+        Self {
+            coingecko: HashMap::new(),
+            coincap: Self::default_coincap_mapping(),
+            // 设置为过去的时间，强制第一次调用时加载映射
+            last_updated: Utc::now() - Duration::hours(25),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() - self.last_updated > Duration::hours(24)
+    }
+
+    /// CoinCap 默认映射（较少币种，手动维护）
+    fn default_coincap_mapping() -> HashMap<String, String> {
+        [
+            ("BTC", "bitcoin"),
+            ("ETH", "ethereum"),
+            ("USDT", "tether"),
+            ("BNB", "binance-coin"),
+            ("SOL", "solana"),
+            ("XRP", "xrp"),
+            ("USDC", "usd-coin"),
+            ("ADA", "cardano"),
+            ("AVAX", "avalanche"),
+            ("DOGE", "dogecoin"),
+            ("DOT", "polkadot"),
+            ("MATIC", "polygon"),
+            ("LINK", "chainlink"),
+            ("LTC", "litecoin"),
+            ("UNI", "uniswap"),
+            ("ATOM", "cosmos"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+}
+
+impl Default for CoinIdMapping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 进程内共享的币种ID映射（CoinGecko/CoinCap 数据源与历史价格查询共用）
+pub fn shared_coin_mappings() -> SharedCoinMappings {
+    static MAPPINGS: OnceLock<SharedCoinMappings> = OnceLock::new();
+    MAPPINGS
+        .get_or_init(|| Arc::new(RwLock::new(CoinIdMapping::new())))
+        .clone()
+}
+
+/// 确保币种ID映射已加载并且是最新的
+pub async fn ensure_coin_mappings(
+    client: &reqwest::Client,
+    mappings: &SharedCoinMappings,
+) -> Result<(), ServiceError> {
+    if !mappings.read().await.is_expired() {
+        debug!("Coin mappings are up-to-date");
+        return Ok(());
+    }
+
+    info!("Coin mappings expired, refreshing from CoinGecko API");
+    let coins: Vec<CoinGeckoCoinListItem> = get_json(
+        client.get("https://api.coingecko.com/api/v3/coins/list"),
+        "CoinGecko coin list",
+    )
+    .await?;
+
+    // 构建 symbol -> id 映射
+    let mut new_map = HashMap::new();
+    for coin in coins {
+        // 优先保留第一个出现的映射（通常是主网币种）
+        new_map.entry(coin.symbol.to_uppercase()).or_insert(coin.id);
+    }
+
+    let mut mappings = mappings.write().await;
+    mappings.coingecko = new_map;
+    mappings.last_updated = Utc::now();
+    info!(
+        "Successfully refreshed {} CoinGecko coin mappings",
+        mappings.coingecko.len()
+    );
+    Ok(())
+}
+
+// ============================================
+// 请求辅助
+// ============================================
+
+pub fn build_client(timeout_secs: u64) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .build()
+        .unwrap_or_default()
+}
+
+async fn get_json<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    label: &str,
+) -> Result<T, ServiceError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ServiceError::ExternalApi {
+            message: format!("Failed to fetch from {}: {}", label, e),
+        })?;
+
+    if !response.status().is_success() {
+        return Err(ServiceError::ExternalApi {
+            message: format!("{} API returned status: {}", label, response.status()),
+        });
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| ServiceError::ExternalApi {
+            message: format!("Failed to parse {} response: {}", label, e),
+        })
+}
+
+fn to_decimal(value: f64) -> Option<Decimal> {
+    Decimal::from_str(&value.to_string()).ok()
+}
+
+fn with_base(mut rates: HashMap<String, Decimal>, base_currency: &str) -> HashMap<String, Decimal> {
+    // 添加基础货币本身
+    rates.insert(base_currency.to_uppercase(), Decimal::ONE);
+    rates
+}
+
+// ============================================
+// 法定货币汇率
+// ============================================
+
+/// Frankfurter（欧洲央行数据）
+pub struct FrankfurterProvider {
+    client: reqwest::Client,
+}
+
+impl FrankfurterProvider {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl RateProvider for FrankfurterProvider {
+    fn name(&self) -> &str {
+        "frankfurter"
+    }
+
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Fiat
+    }
+
+    async fn fetch_fiat_rates(
+        &self,
+        base_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let url = format!("https://api.frankfurter.app/latest?from={}", base_currency);
+        let data: FrankfurterResponse = get_json(self.client.get(&url), "Frankfurter").await?;
+        let rates = data
+            .rates
+            .into_iter()
+            .filter_map(|(code, rate)| to_decimal(rate).map(|d| (code, d)))
+            .collect();
+        Ok(with_base(rates, base_currency))
+    }
+}
+
+/// FXRatesAPI
+pub struct FxRatesProvider {
+    client: reqwest::Client,
+}
+
+impl FxRatesProvider {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl RateProvider for FxRatesProvider {
+    fn name(&self) -> &str {
+        "fxrates"
+    }
+
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Fiat
+    }
+
+    async fn fetch_fiat_rates(
+        &self,
+        base_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let url = format!("https://api.fxratesapi.com/latest?base={}", base_currency);
+        let data: FxRatesApiResponse = get_json(self.client.get(&url), "FXRates").await?;
+        let rates = data
+            .rates
+            .into_iter()
+            .filter_map(|(code, rate)| to_decimal(rate).map(|d| (code, d)))
+            .collect();
+        Ok(with_base(rates, base_currency))
+    }
+}
+
+/// ExchangeRate-API（兼容 open.er-api 与 exchangerate-api 两种格式）
+pub struct ExchangeRateApiProvider {
+    client: reqwest::Client,
+}
+
+impl ExchangeRateApiProvider {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl RateProvider for ExchangeRateApiProvider {
+    fn name(&self) -> &str {
+        "exchangerate-api"
+    }
+
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Fiat
+    }
+
+    async fn fetch_fiat_rates(
+        &self,
+        base_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        // 优先尝试 open.er-api.com（无需密钥，速率较高）
+        let try_urls = [
+            format!("https://open.er-api.com/v6/latest/{}", base_currency),
+            format!(
+                "https://api.exchangerate-api.com/v4/latest/{}",
+                base_currency
+            ),
+        ];
+
+        let mut last_err: Option<String> = None;
+        for url in try_urls {
+            let v: serde_json::Value =
+                match get_json(self.client.get(&url), "ExchangeRate-API").await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        last_err = Some(e.to_string());
+                        continue;
+                    }
+                };
+            // 允许两种字段名：rates 或 conversion_rates
+            let map_node = v.get("rates").or_else(|| v.get("conversion_rates"));
+            if let Some(map) = map_node.and_then(|n| n.as_object()) {
+                let rates: HashMap<String, Decimal> = map
+                    .iter()
+                    .filter_map(|(code, val)| {
+                        val.as_f64()
+                            .and_then(to_decimal)
+                            .map(|d| (code.to_uppercase(), d))
+                    })
+                    .collect();
+                if !rates.is_empty() {
+                    return Ok(with_base(rates, base_currency));
+                }
+            }
+            last_err = Some("missing rates map".to_string());
+        }
+        Err(ServiceError::ExternalApi {
+            message: format!(
+                "Failed to fetch/parse ExchangeRate-API: {}",
+                last_err.unwrap_or_else(|| "unknown".to_string())
+            ),
+        })
+    }
+}
+
+// ============================================
+// 加密货币价格
+// ============================================
+
+/// CoinGecko（动态币种映射）
+pub struct CoinGeckoProvider {
+    client: reqwest::Client,
+    mappings: SharedCoinMappings,
+}
+
+impl CoinGeckoProvider {
+    pub fn new(client: reqwest::Client, mappings: SharedCoinMappings) -> Self {
+        Self { client, mappings }
+    }
+}
+
+#[async_trait]
+impl RateProvider for CoinGeckoProvider {
+    fn name(&self) -> &str {
+        "coingecko"
+    }
+
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Crypto
+    }
+
+    async fn fetch_crypto_prices(
+        &self,
+        crypto_codes: &[&str],
+        fiat_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        if let Err(e) = ensure_coin_mappings(&self.client, &self.mappings).await {
+            warn!("Failed to refresh coin mappings: {}", e);
+        }
+
+        // 获取币种ID列表，并保留 ID -> Symbol 的反向映射
+        let reverse_map: HashMap<String, String> = {
+            let mappings = self.mappings.read().await;
+            crypto_codes
+                .iter()
+                .filter_map(|code| {
+                    let symbol = code.to_uppercase();
+                    mappings
+                        .coingecko
+                        .get(&symbol)
+                        .map(|id| (id.clone(), symbol))
+                })
+                .collect()
+        };
+
+        if reverse_map.is_empty() {
+            return Err(ServiceError::ExternalApi {
+                message: "No CoinGecko IDs found for requested crypto codes".to_string(),
+            });
+        }
+
+        let ids: Vec<&str> = reverse_map.keys().map(String::as_str).collect();
+        let vs = fiat_currency.to_lowercase();
+        let url = format!(
+            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies={}&include_24hr_change=true&include_market_cap=true&include_24hr_vol=true",
+            ids.join(","),
+            vs
+        );
+        let data: HashMap<String, HashMap<String, f64>> =
+            get_json(self.client.get(&url), "CoinGecko").await?;
+
+        let mut prices = HashMap::new();
+        for (coin_id, price_data) in data {
+            if let (Some(symbol), Some(price)) = (reverse_map.get(&coin_id), price_data.get(&vs)) {
+                if let Some(decimal_price) = to_decimal(*price) {
+                    prices.insert(symbol.clone(), decimal_price);
+                }
+            }
+        }
+        Ok(prices)
+    }
+}
+
+/// CoinMarketCap（需要 COINMARKETCAP_API_KEY）
+pub struct CoinMarketCapProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl CoinMarketCapProvider {
+    pub fn new(client: reqwest::Client, api_key: Option<String>) -> Self {
+        Self { client, api_key }
+    }
+}
+
+#[async_trait]
+impl RateProvider for CoinMarketCapProvider {
+    fn name(&self) -> &str {
+        "coinmarketcap"
+    }
+
+    /// 未配置 API Key 时视为不可用，调度时直接跳过
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Crypto && self.api_key.is_some()
+    }
+
+    async fn fetch_crypto_prices(
+        &self,
+        crypto_codes: &[&str],
+        fiat_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or_else(|| ServiceError::ExternalApi {
+                message: "COINMARKETCAP_API_KEY not set".to_string(),
+            })?;
+        let url = format!(
+            "https://pro-api.coinmarketcap.com/v2/cryptocurrency/quotes/latest?symbol={}&convert={}",
+            crypto_codes.join(","),
+            fiat_currency
+        );
+        let data: CoinMarketCapResponse = get_json(
+            self.client.get(&url).header("X-CMC_PRO_API_KEY", api_key),
+            "CoinMarketCap",
+        )
+        .await?;
+
+        let fiat = fiat_currency.to_uppercase();
+        let mut prices = HashMap::new();
+        for (symbol, quotes) in data.data {
+            if let Some(quote_data) = quotes.first().and_then(|q| q.quote.get(&fiat)) {
+                if let Some(decimal_price) = to_decimal(quote_data.price) {
+                    prices.insert(symbol, decimal_price);
+                }
+            }
+        }
+        Ok(prices)
+    }
+}
+
+/// CoinCap（逐个币种查询，仅 USD 报价）
+pub struct CoinCapProvider {
+    client: reqwest::Client,
+    mappings: SharedCoinMappings,
+}
+
+impl CoinCapProvider {
+    pub fn new(client: reqwest::Client, mappings: SharedCoinMappings) -> Self {
+        Self { client, mappings }
+    }
+}
+
+#[async_trait]
+impl RateProvider for CoinCapProvider {
+    fn name(&self) -> &str {
+        "coincap"
+    }
+
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Crypto
+    }
+
+    fn supports_quote_currency(&self, fiat_currency: &str) -> bool {
+        fiat_currency.eq_ignore_ascii_case("USD")
+    }
+
+    async fn fetch_crypto_prices(
+        &self,
+        crypto_codes: &[&str],
+        _fiat_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let mut prices = HashMap::new();
+        for code in crypto_codes {
+            let symbol = code.to_uppercase();
+            let coin_id = self.mappings.read().await.coincap.get(&symbol).cloned();
+            let Some(coin_id) = coin_id else {
+                debug!("No CoinCap ID mapping for {}", symbol);
+                continue;
+            };
+            let url = format!("https://api.coincap.io/v2/assets/{}", coin_id);
+            match get_json::<CoinCapResponse>(self.client.get(&url), "CoinCap").await {
+                Ok(data) => {
+                    if let Ok(price) = Decimal::from_str(&data.data.price_usd) {
+                        prices.insert(symbol, price);
+                    }
+                }
+                Err(e) => debug!("CoinCap failed for {}: {}", symbol, e),
+            }
+        }
+        Ok(prices)
+    }
+}
+
+/// 交易所类数据源的交易对格式
+#[derive(Debug, Clone, Copy)]
+enum Exchange {
+    Binance,
+    Okx,
+    Gateio,
+}
+
+impl Exchange {
+    fn ticker_url(self, symbol: &str) -> String {
+        match self {
+            Exchange::Binance => format!(
+                "https://api.binance.com/api/v3/ticker/price?symbol={}USDT",
+                symbol
+            ),
+            // OKX使用 BTC-USDT 格式
+            Exchange::Okx => format!(
+                "https://www.okx.com/api/v5/market/ticker?instId={}-USDT",
+                symbol
+            ),
+            // Gate.io使用 BTC_USDT 格式
+            Exchange::Gateio => format!(
+                "https://api.gateio.ws/api/v4/spot/tickers?currency_pair={}_USDT",
+                symbol
+            ),
+        }
+    }
+
+    async fn fetch_price(
+        self,
+        client: &reqwest::Client,
+        symbol: &str,
+    ) -> Result<Option<Decimal>, ServiceError> {
+        let url = self.ticker_url(symbol);
+        let last = match self {
+            Exchange::Binance => get_json::<BinanceTicker>(client.get(&url), "Binance")
+                .await
+                .map(|t| Some(t.price))?,
+            Exchange::Okx => {
+                let data = get_json::<OkxResponse>(client.get(&url), "OKX").await?;
+                // OKX返回code="0"表示成功
+                if data.code != "0" {
+                    debug!("OKX returned error code {} for {}", data.code, symbol);
+                    None
+                } else {
+                    data.data.into_iter().next().map(|t| t.last)
+                }
+            }
+            // Gate.io返回数组
+            Exchange::Gateio => get_json::<Vec<GateioTicker>>(client.get(&url), "Gate.io")
+                .await?
+                .into_iter()
+                .next()
+                .map(|t| t.last),
+        };
+        Ok(last.and_then(|p| Decimal::from_str(&p).ok()))
+    }
+}
+
+/// 交易所 USDT 交易对价格（近似 USD）：Binance、OKX、Gate.io
+pub struct ExchangeTickerProvider {
+    client: reqwest::Client,
+    exchange: Exchange,
+}
+
+impl ExchangeTickerProvider {
+    pub fn binance(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            exchange: Exchange::Binance,
+        }
+    }
+
+    pub fn okx(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            exchange: Exchange::Okx,
+        }
+    }
+
+    pub fn gateio(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            exchange: Exchange::Gateio,
+        }
+    }
+}
+
+#[async_trait]
+impl RateProvider for ExchangeTickerProvider {
+    fn name(&self) -> &str {
+        match self.exchange {
+            Exchange::Binance => "binance",
+            Exchange::Okx => "okx",
+            Exchange::Gateio => "gateio",
+        }
+    }
+
+    fn supports(&self, kind: ProviderKind) -> bool {
+        kind == ProviderKind::Crypto
+    }
+
+    fn supports_quote_currency(&self, fiat_currency: &str) -> bool {
+        fiat_currency.eq_ignore_ascii_case("USD")
+    }
+
+    async fn fetch_crypto_prices(
+        &self,
+        crypto_codes: &[&str],
+        _fiat_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let mut result = HashMap::new();
+        for code in crypto_codes {
+            let symbol = code.to_uppercase();
+            if symbol == "USD" || symbol == "USDT" {
+                result.insert(symbol, Decimal::ONE);
+                continue;
+            }
+            // 单个币种失败不影响其他币种
+            match self.exchange.fetch_price(&self.client, &symbol).await {
+                Ok(Some(price)) => {
+                    result.insert(symbol, price);
+                }
+                Ok(None) => debug!("{} has no ticker for {}", self.name(), symbol),
+                Err(e) => debug!("{} failed for {}: {}", self.name(), symbol, e),
+            }
+        }
+        Ok(result)
+    }
+}
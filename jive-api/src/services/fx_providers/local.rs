@@ -0,0 +1,347 @@
+//! 本地数据源：从 JSON 文件或 HTTP 桩读取汇率，用于离线开发与集成测试
+//!
+//! 文档格式（数值可为字符串或数字）：
+//!
+//! ```json
+//! {
+//!   "fiat":   { "USD": { "EUR": "0.92", "CNY": "7.10" } },
+//!   "crypto": { "USD": { "BTC": "65000", "ETH": "3200" } }
+//! }
+//! ```
+//!
+//! 请求的基准货币没有对应表时，会通过已有表中的任一货币换算（例如只有 USD 表时请求 EUR 基准）。
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use super::{ProviderKind, RateProvider};
+use crate::services::ServiceError;
+
+type RateTable = HashMap<String, HashMap<String, Decimal>>;
+
+/// 本地汇率文档
+#[derive(Debug, Clone, Default)]
+pub struct LocalRates {
+    /// 基准货币 -> (目标货币 -> 汇率)
+    pub fiat: RateTable,
+    /// 计价法币 -> (加密货币 -> 价格)
+    pub crypto: RateTable,
+}
+
+impl LocalRates {
+    pub fn from_json(raw: &str) -> Result<Self, ServiceError> {
+        let doc: serde_json::Value =
+            serde_json::from_str(raw).map_err(|e| ServiceError::ExternalApi {
+                message: format!("Invalid local rates document: {}", e),
+            })?;
+        Ok(Self {
+            fiat: parse_table(doc.get("fiat"))?,
+            crypto: parse_table(doc.get("crypto"))?,
+        })
+    }
+
+    pub fn with_fiat(mut self, base: &str, rates: &[(&str, Decimal)]) -> Self {
+        insert_rows(&mut self.fiat, base, rates);
+        self
+    }
+
+    pub fn with_crypto(mut self, fiat: &str, prices: &[(&str, Decimal)]) -> Self {
+        insert_rows(&mut self.crypto, fiat, prices);
+        self
+    }
+
+    /// 以 `base` 为基准的法币汇率；缺少对应表时通过其他基准换算
+    pub fn fiat_rates(&self, base: &str) -> Option<HashMap<String, Decimal>> {
+        let base = base.to_uppercase();
+        if let Some(table) = self.fiat.get(&base) {
+            let mut rates = table.clone();
+            rates.insert(base, Decimal::ONE);
+            return Some(rates);
+        }
+
+        // 换算：pivot->X / pivot->base
+        let mut pivots: Vec<&String> = self.fiat.keys().collect();
+        pivots.sort();
+        for pivot in pivots {
+            let table = &self.fiat[pivot];
+            let Some(pivot_to_base) = table.get(&base).copied().filter(|r| !r.is_zero()) else {
+                continue;
+            };
+            let mut rates: HashMap<String, Decimal> = table
+                .iter()
+                .map(|(code, rate)| (code.clone(), *rate / pivot_to_base))
+                .collect();
+            rates.insert(pivot.clone(), Decimal::ONE / pivot_to_base);
+            rates.insert(base, Decimal::ONE);
+            return Some(rates);
+        }
+        None
+    }
+
+    /// 加密货币以 `fiat` 计价的价格；缺少对应表时通过法币汇率换算
+    pub fn crypto_prices(&self, codes: &[&str], fiat: &str) -> HashMap<String, Decimal> {
+        let fiat = fiat.to_uppercase();
+        let mut prices = HashMap::new();
+        let fiat_rates = self.fiat_rates(&fiat);
+        for code in codes {
+            let code = code.to_uppercase();
+            if let Some(price) = self.crypto.get(&fiat).and_then(|t| t.get(&code)) {
+                prices.insert(code, *price);
+                continue;
+            }
+            // 以其他法币计价的价格 × (fiat->quote)^-1
+            let converted = fiat_rates.as_ref().and_then(|rates| {
+                self.crypto.iter().find_map(|(quote, table)| {
+                    let price = table.get(&code)?;
+                    let fiat_to_quote = rates.get(quote).filter(|r| !r.is_zero())?;
+                    Some(*price / *fiat_to_quote)
+                })
+            });
+            if let Some(price) = converted {
+                prices.insert(code, price);
+            }
+        }
+        prices
+    }
+}
+
+fn insert_rows(table: &mut RateTable, key: &str, rows: &[(&str, Decimal)]) {
+    let entry = table.entry(key.to_uppercase()).or_default();
+    for (code, rate) in rows {
+        entry.insert(code.to_uppercase(), *rate);
+    }
+}
+
+fn parse_table(node: Option<&serde_json::Value>) -> Result<RateTable, ServiceError> {
+    let mut table = RateTable::new();
+    let Some(obj) = node.and_then(|n| n.as_object()) else {
+        return Ok(table);
+    };
+    for (base, row) in obj {
+        let row = row.as_object().ok_or_else(|| ServiceError::ExternalApi {
+            message: format!("Local rates for {} must be an object", base),
+        })?;
+        let entry = table.entry(base.to_uppercase()).or_default();
+        for (code, value) in row {
+            let parsed = match value {
+                serde_json::Value::String(s) => Decimal::from_str(s.trim()).ok(),
+                serde_json::Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+                _ => None,
+            };
+            let rate = parsed.ok_or_else(|| ServiceError::ExternalApi {
+                message: format!("Invalid local rate {}->{}: {}", base, code, value),
+            })?;
+            entry.insert(code.to_uppercase(), rate);
+        }
+    }
+    Ok(table)
+}
+
+/// 本地数据来源
+#[derive(Debug, Clone)]
+pub enum LocalSource {
+    /// 每次请求重新读取文件，便于测试中途修改
+    File(PathBuf),
+    /// 返回同格式 JSON 的 HTTP 桩
+    Http(String),
+    /// 内存数据
+    Static(LocalRates),
+}
+
+/// 本地数据源
+pub struct LocalRateProvider {
+    name: String,
+    source: RwLock<LocalSource>,
+    client: reqwest::Client,
+    /// 模拟数据源不可用（测试降级与熔断）
+    offline: AtomicBool,
+}
+
+impl LocalRateProvider {
+    pub fn new(source: LocalSource) -> Self {
+        Self {
+            name: "local".to_string(),
+            source: RwLock::new(source),
+            client: reqwest::Client::new(),
+            offline: AtomicBool::new(false),
+        }
+    }
+
+    /// 自定义名称（同一注册表中挂多个本地数据源时使用）
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn set_source(&self, source: LocalSource) {
+        if let Ok(mut guard) = self.source.write() {
+            *guard = source;
+        }
+    }
+
+    async fn load(&self) -> Result<LocalRates, ServiceError> {
+        if self.offline.load(Ordering::Relaxed) {
+            return Err(ServiceError::ExternalApi {
+                message: format!("Provider {} is offline", self.name),
+            });
+        }
+        let source = self
+            .source
+            .read()
+            .map(|s| s.clone())
+            .map_err(|_| ServiceError::InternalError)?;
+        match source {
+            LocalSource::Static(rates) => Ok(rates),
+            LocalSource::File(path) => {
+                let raw = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                    ServiceError::ExternalApi {
+                        message: format!("Failed to read {}: {}", path.display(), e),
+                    }
+                })?;
+                LocalRates::from_json(&raw)
+            }
+            LocalSource::Http(url) => {
+                let response =
+                    self.client
+                        .get(&url)
+                        .send()
+                        .await
+                        .map_err(|e| ServiceError::ExternalApi {
+                            message: format!("Failed to fetch from {}: {}", url, e),
+                        })?;
+                if !response.status().is_success() {
+                    return Err(ServiceError::ExternalApi {
+                        message: format!("{} returned status: {}", url, response.status()),
+                    });
+                }
+                let raw = response
+                    .text()
+                    .await
+                    .map_err(|e| ServiceError::ExternalApi {
+                        message: format!("Failed to read response from {}: {}", url, e),
+                    })?;
+                LocalRates::from_json(&raw)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RateProvider for LocalRateProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports(&self, _kind: ProviderKind) -> bool {
+        true
+    }
+
+    async fn fetch_fiat_rates(
+        &self,
+        base_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        self.load()
+            .await?
+            .fiat_rates(base_currency)
+            .ok_or_else(|| ServiceError::ExternalApi {
+                message: format!("No local rates for base {}", base_currency),
+            })
+    }
+
+    async fn fetch_crypto_prices(
+        &self,
+        crypto_codes: &[&str],
+        fiat_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        Ok(self
+            .load()
+            .await?
+            .crypto_prices(crypto_codes, fiat_currency))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const DOC: &str = r#"{
+        "fiat": { "USD": { "EUR": "0.8", "CNY": 7.2 } },
+        "crypto": { "USD": { "BTC": "60000" } }
+    }"#;
+
+    #[test]
+    fn test_parse_and_rebase() {
+        let rates = LocalRates::from_json(DOC).unwrap();
+        let usd = rates.fiat_rates("usd").unwrap();
+        assert_eq!(usd["EUR"], dec!(0.8));
+        assert_eq!(usd["CNY"], dec!(7.2));
+        assert_eq!(usd["USD"], Decimal::ONE);
+
+        let eur = rates.fiat_rates("EUR").unwrap();
+        assert_eq!(eur["USD"], dec!(1.25));
+        assert_eq!(eur["CNY"], dec!(9));
+        assert_eq!(eur["EUR"], Decimal::ONE);
+        assert!(rates.fiat_rates("JPY").is_none());
+
+        let prices = rates.crypto_prices(&["btc", "ETH"], "EUR");
+        assert_eq!(prices["BTC"], dec!(48000));
+        assert!(!prices.contains_key("ETH"));
+    }
+
+    #[test]
+    fn test_invalid_document() {
+        assert!(LocalRates::from_json(r#"{"fiat": {"USD": {"EUR": "abc"}}}"#).is_err());
+        assert!(LocalRates::from_json("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_source_and_offline_toggle() {
+        let path = std::env::temp_dir().join(format!("fx-local-{}.json", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, DOC).await.unwrap();
+
+        let provider = LocalRateProvider::new(LocalSource::File(path.clone()));
+        let rates = provider.fetch_fiat_rates("USD").await.unwrap();
+        assert_eq!(rates["EUR"], dec!(0.8));
+
+        provider.set_offline(true);
+        assert!(provider.fetch_fiat_rates("USD").await.is_err());
+        provider.set_offline(false);
+
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(provider.fetch_fiat_rates("USD").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_stub_source() {
+        use axum::{routing::get, Router};
+
+        let app = Router::new().route("/rates", get(|| async { DOC }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let provider = LocalRateProvider::new(LocalSource::Http(format!("http://{}/rates", addr)));
+        let prices = provider.fetch_crypto_prices(&["BTC"], "USD").await.unwrap();
+        assert_eq!(prices["BTC"], dec!(60000));
+
+        provider.set_source(LocalSource::Http(format!("http://{}/missing", addr)));
+        assert!(provider.fetch_fiat_rates("USD").await.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! 汇率与加密货币价格数据源
+//!
+//! - `RateProvider`：数据源抽象（Frankfurter、CoinGecko 等公共 API，以及本地文件/HTTP 桩）
+//! - `CircuitBreaker`：连续失败后熔断，冷却期过后放行一次探测请求
+//! - `ProviderRegistry`：按 `FxProviderConfig` 中的顺序降级调用，记录健康状态与延迟
+
+pub mod circuit_breaker;
+pub mod http;
+pub mod local;
+pub mod registry;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::services::ServiceError;
+
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+pub use local::{LocalRateProvider, LocalRates, LocalSource};
+pub use registry::{ProviderHealth, ProviderRegistry, ProviderResult};
+
+/// 数据源类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// 法币汇率
+    Fiat,
+    /// 加密货币价格
+    Crypto,
+}
+
+/// 汇率数据源
+#[async_trait]
+pub trait RateProvider: Send + Sync {
+    /// 数据源名称（与 FIAT_PROVIDER_ORDER / CRYPTO_PROVIDER_ORDER 中的名称一致）
+    fn name(&self) -> &str;
+
+    /// 是否提供该类数据
+    fn supports(&self, kind: ProviderKind) -> bool;
+
+    /// 是否支持以该法币计价的加密货币价格（交易所类数据源只有 USDT 交易对）
+    fn supports_quote_currency(&self, _fiat_currency: &str) -> bool {
+        true
+    }
+
+    /// 以 `base_currency` 为基准的法币汇率（包含基准货币自身，值为 1）
+    async fn fetch_fiat_rates(
+        &self,
+        base_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        Err(unsupported(self.name(), base_currency))
+    }
+
+    /// 加密货币以 `fiat_currency` 计价的价格，键为大写币种代码
+    async fn fetch_crypto_prices(
+        &self,
+        _crypto_codes: &[&str],
+        fiat_currency: &str,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        Err(unsupported(self.name(), fiat_currency))
+    }
+}
+
+fn unsupported(provider: &str, currency: &str) -> ServiceError {
+    ServiceError::ExternalApi {
+        message: format!("Provider {} does not support {}", provider, currency),
+    }
+}
+
+/// 数据源名称的规范形式（兼容历史配置中的别名）
+pub fn canonical_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.as_str() {
+        "exchange-rate-api" => "exchangerate-api".to_string(),
+        "fx-rates-api" | "fxratesapi" => "fxrates".to_string(),
+        "gate.io" => "gateio".to_string(),
+        "mock" | "file" => "local".to_string(),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_name_aliases() {
+        assert_eq!(canonical_name("Exchange-Rate-API"), "exchangerate-api");
+        assert_eq!(canonical_name("fxratesapi"), "fxrates");
+        assert_eq!(canonical_name(" gate.io "), "gateio");
+        assert_eq!(canonical_name("mock"), "local");
+        assert_eq!(canonical_name("coingecko"), "coingecko");
+    }
+}
@@ -0,0 +1,465 @@
+//! 数据源注册表：按配置顺序降级调用，维护熔断器、健康状态与延迟统计
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::http::{
+    build_client, shared_coin_mappings, CoinCapProvider, CoinGeckoProvider, CoinMarketCapProvider,
+    ExchangeRateApiProvider, ExchangeTickerProvider, FrankfurterProvider, FxRatesProvider,
+};
+use super::{canonical_name, CircuitBreaker, CircuitState, LocalRateProvider, LocalSource};
+use super::{ProviderKind, RateProvider};
+use crate::config::FxProviderConfig;
+use crate::services::ServiceError;
+
+/// 一次成功调用的结果
+#[derive(Debug, Clone)]
+pub struct ProviderResult {
+    pub rates: HashMap<String, Decimal>,
+    /// 实际提供数据的数据源名称
+    pub source: String,
+}
+
+/// 数据源健康状态快照（/health 与 /metrics 使用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub name: String,
+    pub kinds: Vec<ProviderKind>,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    pub last_latency_ms: Option<u64>,
+    /// 指数移动平均延迟
+    pub avg_latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    /// 熔断状态下距离下一次探测的秒数
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug)]
+struct ProviderStats {
+    breaker: CircuitBreaker,
+    total_requests: u64,
+    total_failures: u64,
+    last_latency_ms: Option<u64>,
+    avg_latency_ms: Option<f64>,
+    last_error: Option<String>,
+    last_success_at: Option<DateTime<Utc>>,
+    last_failure_at: Option<DateTime<Utc>>,
+}
+
+impl ProviderStats {
+    fn record_latency(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        self.total_requests += 1;
+        self.last_latency_ms = Some(ms);
+        self.avg_latency_ms = Some(match self.avg_latency_ms {
+            Some(avg) => avg * 0.8 + ms as f64 * 0.2,
+            None => ms as f64,
+        });
+    }
+}
+
+struct ProviderEntry {
+    provider: Arc<dyn RateProvider>,
+    stats: Mutex<ProviderStats>,
+}
+
+enum Request<'a> {
+    Fiat(&'a str),
+    Crypto(&'a [&'a str], &'a str),
+}
+
+impl Request<'_> {
+    fn kind(&self) -> ProviderKind {
+        match self {
+            Request::Fiat(_) => ProviderKind::Fiat,
+            Request::Crypto(..) => ProviderKind::Crypto,
+        }
+    }
+}
+
+/// 数据源注册表
+pub struct ProviderRegistry {
+    entries: HashMap<String, ProviderEntry>,
+    fiat_order: Vec<String>,
+    crypto_order: Vec<String>,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl ProviderRegistry {
+    /// 空注册表；顺序中未注册的数据源会被忽略
+    pub fn new(
+        fiat_order: Vec<String>,
+        crypto_order: Vec<String>,
+        failure_threshold: u32,
+        cooldown: Duration,
+    ) -> Self {
+        Self {
+            entries: HashMap::new(),
+            fiat_order: fiat_order.iter().map(|n| canonical_name(n)).collect(),
+            crypto_order: crypto_order.iter().map(|n| canonical_name(n)).collect(),
+            failure_threshold,
+            cooldown,
+        }
+    }
+
+    /// 按配置注册全部内置数据源
+    ///
+    /// 配置了 FX_LOCAL_FILE / FX_LOCAL_URL 时注册 "local"；
+    /// 若顺序中未列出 "local"，则作为最后一级降级追加到末尾。
+    pub fn from_config(config: &FxProviderConfig) -> Self {
+        let mut registry = Self::new(
+            config.fiat_order.clone(),
+            config.crypto_order.clone(),
+            config.breaker_failure_threshold,
+            Duration::from_secs(config.breaker_cooldown_secs),
+        );
+
+        let client = build_client(config.request_timeout_secs);
+        let mappings = shared_coin_mappings();
+        registry.register(Arc::new(ExchangeRateApiProvider::new(client.clone())));
+        registry.register(Arc::new(FrankfurterProvider::new(client.clone())));
+        registry.register(Arc::new(FxRatesProvider::new(client.clone())));
+        registry.register(Arc::new(CoinGeckoProvider::new(
+            client.clone(),
+            mappings.clone(),
+        )));
+        registry.register(Arc::new(ExchangeTickerProvider::okx(client.clone())));
+        registry.register(Arc::new(ExchangeTickerProvider::gateio(client.clone())));
+        registry.register(Arc::new(CoinMarketCapProvider::new(
+            client.clone(),
+            config.coinmarketcap_api_key.clone(),
+        )));
+        registry.register(Arc::new(ExchangeTickerProvider::binance(client.clone())));
+        registry.register(Arc::new(CoinCapProvider::new(client.clone(), mappings)));
+
+        let local_source = config
+            .local_file
+            .as_ref()
+            .map(|p| LocalSource::File(PathBuf::from(p)))
+            .or_else(|| config.local_url.clone().map(LocalSource::Http));
+        if let Some(source) = local_source {
+            info!("Local FX provider enabled: {:?}", source);
+            registry.register(Arc::new(LocalRateProvider::new(source).with_client(client)));
+            for order in [&mut registry.fiat_order, &mut registry.crypto_order] {
+                if !order.iter().any(|n| n == "local") {
+                    order.push("local".to_string());
+                }
+            }
+        }
+
+        for name in registry.fiat_order.iter().chain(&registry.crypto_order) {
+            if !registry.entries.contains_key(name) {
+                warn!("Unknown FX provider in configured order: {}", name);
+            }
+        }
+
+        registry
+    }
+
+    /// 注册数据源；同名数据源会被替换
+    pub fn register(&mut self, provider: Arc<dyn RateProvider>) {
+        let name = canonical_name(provider.name());
+        let stats = ProviderStats {
+            breaker: CircuitBreaker::new(self.failure_threshold, self.cooldown),
+            total_requests: 0,
+            total_failures: 0,
+            last_latency_ms: None,
+            avg_latency_ms: None,
+            last_error: None,
+            last_success_at: None,
+            last_failure_at: None,
+        };
+        self.entries.insert(
+            name,
+            ProviderEntry {
+                provider,
+                stats: Mutex::new(stats),
+            },
+        );
+    }
+
+    pub fn fiat_order(&self) -> &[String] {
+        &self.fiat_order
+    }
+
+    pub fn crypto_order(&self) -> &[String] {
+        &self.crypto_order
+    }
+
+    /// 按 fiat_order 降级获取法币汇率
+    pub async fn fetch_fiat(&self, base_currency: &str) -> Result<ProviderResult, ServiceError> {
+        self.fetch_in_order(&self.fiat_order, Request::Fiat(base_currency))
+            .await
+    }
+
+    /// 按 crypto_order 降级获取加密货币价格
+    pub async fn fetch_crypto(
+        &self,
+        crypto_codes: &[&str],
+        fiat_currency: &str,
+    ) -> Result<ProviderResult, ServiceError> {
+        self.fetch_in_order(
+            &self.crypto_order,
+            Request::Crypto(crypto_codes, fiat_currency),
+        )
+        .await
+    }
+
+    /// 指定数据源获取法币汇率（不参与降级；熔断中的数据源直接返回错误）
+    pub async fn fetch_fiat_from(
+        &self,
+        provider: &str,
+        base_currency: &str,
+    ) -> Result<ProviderResult, ServiceError> {
+        let name = canonical_name(provider);
+        let entry = self
+            .entries
+            .get(&name)
+            .filter(|e| e.provider.supports(ProviderKind::Fiat))
+            .ok_or_else(|| ServiceError::ExternalApi {
+                message: format!("Unknown fiat provider: {}", provider),
+            })?;
+        if !Self::allow(entry) {
+            return Err(ServiceError::ExternalApi {
+                message: format!("Provider {} circuit open", name),
+            });
+        }
+        let rates = self.call(entry, &Request::Fiat(base_currency)).await?;
+        Ok(ProviderResult {
+            rates,
+            source: name,
+        })
+    }
+
+    async fn fetch_in_order(
+        &self,
+        order: &[String],
+        request: Request<'_>,
+    ) -> Result<ProviderResult, ServiceError> {
+        let kind = request.kind();
+        let mut errors = Vec::new();
+
+        for name in order {
+            let Some(entry) = self.entries.get(name) else {
+                continue;
+            };
+            if !entry.provider.supports(kind) {
+                debug!("Provider {} does not support {:?}, skipping", name, kind);
+                continue;
+            }
+            if let Request::Crypto(_, fiat) = request {
+                if !entry.provider.supports_quote_currency(fiat) {
+                    continue;
+                }
+            }
+            if !Self::allow(entry) {
+                debug!("Provider {} circuit open, skipping", name);
+                continue;
+            }
+
+            match self.call(entry, &request).await {
+                Ok(rates) => {
+                    info!("Fetched {} {:?} rates from {}", rates.len(), kind, name);
+                    return Ok(ProviderResult {
+                        rates,
+                        source: name.clone(),
+                    });
+                }
+                Err(e) => {
+                    warn!("Failed to fetch from {}: {}", name, e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        Err(ServiceError::ExternalApi {
+            message: if errors.is_empty() {
+                format!("No available {:?} provider", kind)
+            } else {
+                format!("All providers failed ({})", errors.join("; "))
+            },
+        })
+    }
+
+    fn allow(entry: &ProviderEntry) -> bool {
+        entry
+            .stats
+            .lock()
+            .map(|mut s| s.breaker.allow_request(Instant::now()))
+            .unwrap_or(false)
+    }
+
+    /// 调用单个数据源并更新统计；空结果视为失败
+    async fn call(
+        &self,
+        entry: &ProviderEntry,
+        request: &Request<'_>,
+    ) -> Result<HashMap<String, Decimal>, ServiceError> {
+        let started = Instant::now();
+        let result = match request {
+            Request::Fiat(base) => entry.provider.fetch_fiat_rates(base).await,
+            Request::Crypto(codes, fiat) => entry.provider.fetch_crypto_prices(codes, fiat).await,
+        }
+        .and_then(|rates| {
+            if rates.is_empty() {
+                Err(ServiceError::ExternalApi {
+                    message: "empty result".to_string(),
+                })
+            } else {
+                Ok(rates)
+            }
+        });
+
+        if let Ok(mut stats) = entry.stats.lock() {
+            stats.record_latency(started.elapsed());
+            match &result {
+                Ok(_) => {
+                    stats.breaker.record_success();
+                    stats.last_success_at = Some(Utc::now());
+                }
+                Err(e) => {
+                    stats.breaker.record_failure(Instant::now());
+                    stats.total_failures += 1;
+                    stats.last_error = Some(e.to_string());
+                    stats.last_failure_at = Some(Utc::now());
+                }
+            }
+        }
+        result
+    }
+
+    /// 所有数据源的健康状态（按名称排序）
+    pub fn health(&self) -> Vec<ProviderHealth> {
+        let now = Instant::now();
+        let mut list: Vec<ProviderHealth> = self
+            .entries
+            .iter()
+            .filter_map(|(name, entry)| {
+                let stats = entry.stats.lock().ok()?;
+                let kinds = [ProviderKind::Fiat, ProviderKind::Crypto]
+                    .into_iter()
+                    .filter(|k| entry.provider.supports(*k))
+                    .collect();
+                Some(ProviderHealth {
+                    name: name.clone(),
+                    kinds,
+                    state: stats.breaker.state(),
+                    consecutive_failures: stats.breaker.consecutive_failures(),
+                    total_requests: stats.total_requests,
+                    total_failures: stats.total_failures,
+                    last_latency_ms: stats.last_latency_ms,
+                    avg_latency_ms: stats.avg_latency_ms,
+                    last_error: stats.last_error.clone(),
+                    last_success_at: stats.last_success_at,
+                    last_failure_at: stats.last_failure_at,
+                    retry_after_secs: stats.breaker.retry_after(now).map(|d| d.as_secs()),
+                })
+            })
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fx_providers::LocalRates;
+    use rust_decimal_macros::dec;
+
+    fn provider(name: &str, eur: Decimal) -> Arc<LocalRateProvider> {
+        let rates = LocalRates::default()
+            .with_fiat("USD", &[("EUR", eur)])
+            .with_crypto("USD", &[("BTC", dec!(60000))]);
+        Arc::new(LocalRateProvider::new(LocalSource::Static(rates)).with_name(name))
+    }
+
+    fn registry(
+        primary: &Arc<LocalRateProvider>,
+        backup: &Arc<LocalRateProvider>,
+    ) -> ProviderRegistry {
+        let order = vec!["primary".to_string(), "backup".to_string()];
+        let mut registry =
+            ProviderRegistry::new(order.clone(), order, 2, Duration::from_secs(3600));
+        registry.register(primary.clone());
+        registry.register(backup.clone());
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_fallback_follows_order() {
+        let primary = provider("primary", dec!(0.9));
+        let backup = provider("backup", dec!(0.8));
+        let registry = registry(&primary, &backup);
+
+        let result = registry.fetch_fiat("USD").await.unwrap();
+        assert_eq!(result.source, "primary");
+        assert_eq!(result.rates["EUR"], dec!(0.9));
+
+        primary.set_offline(true);
+        let result = registry.fetch_fiat("USD").await.unwrap();
+        assert_eq!(result.source, "backup");
+        assert_eq!(result.rates["EUR"], dec!(0.8));
+
+        let result = registry.fetch_crypto(&["BTC"], "USD").await.unwrap();
+        assert_eq!(result.source, "backup");
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_is_skipped() {
+        let primary = provider("primary", dec!(0.9));
+        let backup = provider("backup", dec!(0.8));
+        let registry = registry(&primary, &backup);
+
+        primary.set_offline(true);
+        registry.fetch_fiat("USD").await.unwrap();
+        registry.fetch_fiat("USD").await.unwrap();
+
+        let health = registry.health();
+        let primary_health = health.iter().find(|h| h.name == "primary").unwrap();
+        assert_eq!(primary_health.state, CircuitState::Open);
+        assert_eq!(primary_health.total_failures, 2);
+        assert!(primary_health.last_error.is_some());
+
+        // 熔断期间即使恢复也不会被调用
+        primary.set_offline(false);
+        let result = registry.fetch_fiat("USD").await.unwrap();
+        assert_eq!(result.source, "backup");
+        let health = registry.health();
+        let primary_health = health.iter().find(|h| h.name == "primary").unwrap();
+        assert_eq!(primary_health.total_requests, 2);
+        assert!(primary_health.retry_after_secs.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_all_failed_and_explicit_provider() {
+        let primary = provider("primary", dec!(0.9));
+        let backup = provider("backup", dec!(0.8));
+        let registry = registry(&primary, &backup);
+
+        primary.set_offline(true);
+        backup.set_offline(true);
+        let err = registry.fetch_fiat("USD").await.unwrap_err();
+        assert!(err.to_string().contains("primary"));
+
+        backup.set_offline(false);
+        let result = registry.fetch_fiat_from("backup", "USD").await.unwrap();
+        assert_eq!(result.source, "backup");
+        assert!(registry.fetch_fiat_from("nope", "USD").await.is_err());
+
+        // 空结果视为失败
+        assert!(registry.fetch_crypto(&["DOGE"], "USD").await.is_err());
+    }
+}
@@ -0,0 +1,804 @@
+//! 分期：信用卡与花呗 / 白条等先买后付账户上的分期计划
+//!
+//! 一笔消费转为分期后，按期数生成还款计划：每期本金均分（尾差计入最后一期），手续费按
+//! 本金 × 每期费率或每期固定金额计算。
+//!
+//! - 账户余额仍以原消费记全部欠款；每期手续费在入账日作为卡账户支出记账。
+//! - 信用卡账单中，转分期的本金在转换日所在周期冲减，各期本金在入账日所在周期计入。
+//! - 提前还款时剩余本金合并为一期，在还款日全部入账，并按剩余本金收取提前还款手续费。
+//! - 预算按计划的 `budget_mode` 统计：`full` 在消费日计全额，`monthly` 在各期入账日计分摊本金。
+
+use chrono::{Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::transaction_valuation_service::TransactionValuationService;
+use super::ServiceError;
+use crate::config::RateResolverConfig;
+
+/// 可办理分期的账户子类型
+pub const INSTALLMENT_ACCOUNT_SUB_TYPES: &[&str] = &[
+    "credit_card",
+    "huabei",
+    "jd_white_bar",
+    "meituan_monthly",
+    "douyin_monthly",
+    "wechat_installment",
+];
+
+const MAX_INSTALLMENTS: i16 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeMode {
+    /// 免息分期
+    None,
+    /// 每期按本金 × 费率收费
+    Rate,
+    /// 每期固定手续费
+    Fixed,
+}
+
+impl FeeMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeeMode::None => "none",
+            FeeMode::Rate => "rate",
+            FeeMode::Fixed => "fixed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(FeeMode::None),
+            "rate" => Some(FeeMode::Rate),
+            "fixed" => Some(FeeMode::Fixed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetMode {
+    /// 消费当期计入全额
+    Full,
+    /// 每期计入分摊本金
+    Monthly,
+}
+
+impl BudgetMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetMode::Full => "full",
+            BudgetMode::Monthly => "monthly",
+        }
+    }
+}
+
+/// 还款计划中的一期
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledPeriod {
+    pub period_number: i16,
+    pub posting_date: NaiveDate,
+    pub principal: Decimal,
+    pub fee: Decimal,
+}
+
+/// 每期手续费
+pub fn period_fee(principal: Decimal, mode: FeeMode, rate: Decimal, fixed: Decimal) -> Decimal {
+    match mode {
+        FeeMode::None => Decimal::ZERO,
+        FeeMode::Rate => (principal * rate).round_dp(2),
+        FeeMode::Fixed => fixed.round_dp(2),
+    }
+}
+
+/// 生成还款计划：第 k 期在首期入账日之后 k-1 个月入账（月末自动顺延到当月最后一天）
+pub fn build_schedule(
+    principal: Decimal,
+    installments: i16,
+    fee: Decimal,
+    first_posting_date: NaiveDate,
+) -> Vec<ScheduledPeriod> {
+    if installments <= 0 {
+        return Vec::new();
+    }
+    let count = Decimal::from(installments);
+    let slice = (principal / count).trunc_with_scale(2);
+    (1..=installments)
+        .map(|n| ScheduledPeriod {
+            period_number: n,
+            posting_date: first_posting_date
+                .checked_add_months(Months::new(n as u32 - 1))
+                .unwrap_or(first_posting_date),
+            principal: if n == installments {
+                principal - slice * (count - Decimal::ONE)
+            } else {
+                slice
+            },
+            fee,
+        })
+        .collect()
+}
+
+/// 提前还款手续费
+pub fn payoff_fee(remaining_principal: Decimal, early_payoff_fee_rate: Decimal) -> Decimal {
+    if remaining_principal <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (remaining_principal * early_payoff_fee_rate).round_dp(2)
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct InstallmentPlan {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub ledger_id: Uuid,
+    pub purchase_date: NaiveDate,
+    pub purchase_amount: Decimal,
+    pub purchase_payee: Option<String>,
+    pub principal: Decimal,
+    pub installments: i16,
+    pub fee_mode: String,
+    pub fee_rate: Decimal,
+    pub fee_per_period: Decimal,
+    pub early_payoff_fee_rate: Decimal,
+    pub converted_on: NaiveDate,
+    pub first_posting_date: NaiveDate,
+    pub budget_mode: String,
+    pub status: String,
+    pub paid_off_on: Option<NaiveDate>,
+    /// 尚未入账的本金
+    pub remaining_principal: Decimal,
+    /// 尚未入账的手续费
+    pub remaining_fees: Decimal,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct InstallmentPeriod {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub period_number: i16,
+    pub posting_date: NaiveDate,
+    pub principal: Decimal,
+    pub fee: Decimal,
+    pub is_payoff: bool,
+    pub fee_transaction_id: Option<Uuid>,
+    pub posted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct InstallmentPlanDetail {
+    pub plan: InstallmentPlan,
+    pub periods: Vec<InstallmentPeriod>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateInstallmentPlanRequest {
+    /// 要转分期的消费
+    pub transaction_id: Uuid,
+    /// 分期本金，默认为消费全额
+    pub principal: Option<Decimal>,
+    pub installments: i16,
+    pub fee_mode: Option<FeeMode>,
+    /// 每期费率（按本金），fee_mode = rate 时必填
+    pub fee_rate: Option<Decimal>,
+    /// 每期固定手续费，fee_mode = fixed 时必填
+    pub fee_per_period: Option<Decimal>,
+    pub early_payoff_fee_rate: Option<Decimal>,
+    /// 转分期日期，默认为消费日
+    pub converted_on: Option<NaiveDate>,
+    /// 首期入账日，默认为转分期日期
+    pub first_posting_date: Option<NaiveDate>,
+    pub budget_mode: Option<BudgetMode>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateInstallmentPlanRequest {
+    pub budget_mode: BudgetMode,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct PayoffInstallmentRequest {
+    /// 提前还款日期，默认今天
+    pub date: Option<NaiveDate>,
+}
+
+const PLAN_SELECT: &str = r#"
+    SELECT p.id, p.transaction_id, p.account_id, a.name AS account_name, t.ledger_id,
+           t.transaction_date AS purchase_date, t.amount AS purchase_amount,
+           t.payee AS purchase_payee, p.principal, p.installments, p.fee_mode, p.fee_rate,
+           p.fee_per_period, p.early_payoff_fee_rate, p.converted_on, p.first_posting_date,
+           p.budget_mode, p.status, p.paid_off_on,
+           COALESCE(r.principal, 0) AS remaining_principal,
+           COALESCE(r.fees, 0) AS remaining_fees,
+           p.created_by
+    FROM installment_plans p
+    JOIN accounts a ON a.id = p.account_id
+    JOIN transactions t ON t.id = p.transaction_id
+    LEFT JOIN LATERAL (
+        SELECT SUM(ip.principal) AS principal, SUM(ip.fee) AS fees
+        FROM installment_periods ip
+        WHERE ip.plan_id = p.id AND ip.posted_at IS NULL
+    ) r ON true
+"#;
+
+const PERIOD_COLUMNS: &str = r#"
+    id, plan_id, period_number, posting_date, principal, fee, is_payoff, fee_transaction_id,
+    posted_at IS NOT NULL AS posted
+"#;
+
+pub struct InstallmentService {
+    pool: PgPool,
+}
+
+impl InstallmentService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_plans(
+        &self,
+        ledger_ids: &[Uuid],
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<InstallmentPlan>, ServiceError> {
+        let plans = sqlx::query_as::<_, InstallmentPlan>(&format!(
+            r#"
+            {}
+            WHERE a.ledger_id = ANY($1) AND ($2::uuid IS NULL OR p.account_id = $2)
+            ORDER BY p.converted_on DESC, p.created_at DESC
+            "#,
+            PLAN_SELECT
+        ))
+        .bind(ledger_ids)
+        .bind(account_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(plans)
+    }
+
+    pub async fn get_plan(&self, id: Uuid) -> Result<InstallmentPlan, ServiceError> {
+        sqlx::query_as::<_, InstallmentPlan>(&format!("{} WHERE p.id = $1", PLAN_SELECT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("installment_plan", id))
+    }
+
+    pub async fn get_plan_detail(&self, id: Uuid) -> Result<InstallmentPlanDetail, ServiceError> {
+        let plan = self.get_plan(id).await?;
+        let periods = sqlx::query_as::<_, InstallmentPeriod>(&format!(
+            "SELECT {} FROM installment_periods WHERE plan_id = $1 ORDER BY period_number",
+            PERIOD_COLUMNS
+        ))
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(InstallmentPlanDetail { plan, periods })
+    }
+
+    /// 消费所在的账户，用于在创建分期前校验权限
+    pub async fn purchase_account(&self, transaction_id: Uuid) -> Result<Uuid, ServiceError> {
+        sqlx::query_scalar(
+            "SELECT account_id FROM transactions WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("transaction", transaction_id))
+    }
+
+    /// 将一笔消费转为分期并生成还款计划；入账日已到的期次立即入账
+    pub async fn create_plan(
+        &self,
+        user_id: Uuid,
+        request: CreateInstallmentPlanRequest,
+        config: &RateResolverConfig,
+    ) -> Result<InstallmentPlanDetail, ServiceError> {
+        if !(2..=MAX_INSTALLMENTS).contains(&request.installments) {
+            return Err(ServiceError::validation(
+                "installments must be between 2 and 60",
+            ));
+        }
+        let fee_mode = request.fee_mode.unwrap_or(FeeMode::None);
+        let fee_rate = request.fee_rate.unwrap_or(Decimal::ZERO);
+        let fee_per_period = request.fee_per_period.unwrap_or(Decimal::ZERO);
+        let early_payoff_fee_rate = request.early_payoff_fee_rate.unwrap_or(Decimal::ZERO);
+        match fee_mode {
+            FeeMode::Rate if request.fee_rate.is_none() => {
+                return Err(ServiceError::validation(
+                    "fee_rate is required when fee_mode is rate",
+                ))
+            }
+            FeeMode::Fixed if request.fee_per_period.is_none() => {
+                return Err(ServiceError::validation(
+                    "fee_per_period is required when fee_mode is fixed",
+                ))
+            }
+            _ => {}
+        }
+        if fee_rate < Decimal::ZERO || fee_rate >= Decimal::ONE {
+            return Err(ServiceError::validation("fee_rate must be in [0, 1)"));
+        }
+        if early_payoff_fee_rate < Decimal::ZERO || early_payoff_fee_rate >= Decimal::ONE {
+            return Err(ServiceError::validation(
+                "early_payoff_fee_rate must be in [0, 1)",
+            ));
+        }
+        if fee_per_period < Decimal::ZERO {
+            return Err(ServiceError::validation(
+                "fee_per_period must not be negative",
+            ));
+        }
+
+        let purchase = sqlx::query(
+            r#"
+            SELECT t.account_id, t.amount, t.transaction_type, t.transaction_date,
+                   a.account_type, a.account_sub_type
+            FROM transactions t
+            JOIN accounts a ON a.id = t.account_id
+            WHERE t.id = $1 AND t.deleted_at IS NULL AND a.deleted_at IS NULL
+            "#,
+        )
+        .bind(request.transaction_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("transaction", request.transaction_id))?;
+        let transaction_type: String = purchase.get("transaction_type");
+        if transaction_type != "expense" {
+            return Err(ServiceError::business_rule(
+                "Only expenses can be converted to installments",
+            ));
+        }
+        let sub_type: Option<String> = purchase.get("account_sub_type");
+        let account_type: String = purchase.get("account_type");
+        let eligible = match sub_type.as_deref() {
+            Some(sub_type) => INSTALLMENT_ACCOUNT_SUB_TYPES.contains(&sub_type),
+            None => account_type == "credit",
+        };
+        if !eligible {
+            return Err(ServiceError::business_rule(
+                "Installments are only available on credit card and buy-now-pay-later accounts",
+            ));
+        }
+
+        let account_id: Uuid = purchase.get("account_id");
+        let amount: Decimal = purchase.get("amount");
+        let purchase_date: NaiveDate = purchase.get("transaction_date");
+        let principal = request.principal.unwrap_or(amount).round_dp(2);
+        if principal <= Decimal::ZERO || principal > amount {
+            return Err(ServiceError::validation(
+                "principal must be positive and not exceed the purchase amount",
+            ));
+        }
+        let converted_on = request.converted_on.unwrap_or(purchase_date);
+        if converted_on < purchase_date {
+            return Err(ServiceError::validation(
+                "converted_on must not be before the purchase date",
+            ));
+        }
+        let first_posting_date = request.first_posting_date.unwrap_or(converted_on);
+        if first_posting_date < converted_on {
+            return Err(ServiceError::validation(
+                "first_posting_date must not be before converted_on",
+            ));
+        }
+        let fee = period_fee(principal, fee_mode, fee_rate, fee_per_period);
+        let schedule = build_schedule(principal, request.installments, fee, first_posting_date);
+
+        let mut tx = self.pool.begin().await?;
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM installment_plans WHERE transaction_id = $1)",
+        )
+        .bind(request.transaction_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if exists {
+            return Err(ServiceError::conflict(
+                "The transaction is already in an installment plan",
+            ));
+        }
+        let plan_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO installment_plans
+                (transaction_id, account_id, principal, installments, fee_mode, fee_rate,
+                 fee_per_period, early_payoff_fee_rate, converted_on, first_posting_date,
+                 budget_mode, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id
+            "#,
+        )
+        .bind(request.transaction_id)
+        .bind(account_id)
+        .bind(principal)
+        .bind(request.installments)
+        .bind(fee_mode.as_str())
+        .bind(fee_rate)
+        .bind(fee_per_period)
+        .bind(early_payoff_fee_rate)
+        .bind(converted_on)
+        .bind(first_posting_date)
+        .bind(request.budget_mode.unwrap_or(BudgetMode::Full).as_str())
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        for period in &schedule {
+            sqlx::query(
+                r#"
+                INSERT INTO installment_periods (plan_id, period_number, posting_date, principal, fee)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(plan_id)
+            .bind(period.period_number)
+            .bind(period.posting_date)
+            .bind(period.principal)
+            .bind(period.fee)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.post_due(Some(account_id), Utc::now().date_naive(), config)
+            .await?;
+        self.get_plan_detail(plan_id).await
+    }
+
+    /// 切换预算统计方式
+    pub async fn update_plan(
+        &self,
+        id: Uuid,
+        request: UpdateInstallmentPlanRequest,
+    ) -> Result<InstallmentPlan, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE installment_plans SET budget_mode = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .bind(request.budget_mode.as_str())
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("installment_plan", id));
+        }
+        self.get_plan(id).await
+    }
+
+    /// 提前还款：`date` 之前的期次照常入账，剩余本金合并为一期在 `date` 入账
+    pub async fn pay_off(
+        &self,
+        id: Uuid,
+        date: Option<NaiveDate>,
+        config: &RateResolverConfig,
+    ) -> Result<InstallmentPlanDetail, ServiceError> {
+        let today = Utc::now().date_naive();
+        let date = date.unwrap_or(today);
+        let plan = self.get_plan(id).await?;
+        if plan.status != "active" {
+            return Err(ServiceError::business_rule(
+                "Only active installment plans can be paid off",
+            ));
+        }
+        if date > today || date < plan.converted_on {
+            return Err(ServiceError::validation(
+                "payoff date must be between converted_on and today",
+            ));
+        }
+        self.post_due(Some(plan.account_id), date, config).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let status: String =
+            sqlx::query_scalar("SELECT status FROM installment_plans WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if status != "active" {
+            return Err(ServiceError::business_rule(
+                "Only active installment plans can be paid off",
+            ));
+        }
+        let remaining = sqlx::query(
+            r#"
+            SELECT MIN(period_number) AS next_period, COALESCE(SUM(principal), 0) AS principal
+            FROM installment_periods
+            WHERE plan_id = $1 AND posted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let next_period: Option<i16> = remaining.get("next_period");
+        let Some(next_period) = next_period else {
+            return Err(ServiceError::business_rule(
+                "All installments have already been billed",
+            ));
+        };
+        let principal: Decimal = remaining.get("principal");
+        let fee = payoff_fee(principal, plan.early_payoff_fee_rate);
+
+        sqlx::query("DELETE FROM installment_periods WHERE plan_id = $1 AND posted_at IS NULL")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let period_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO installment_periods
+                (plan_id, period_number, posting_date, principal, fee, is_payoff)
+            VALUES ($1, $2, $3, $4, $5, true)
+            RETURNING id
+            "#,
+        )
+        .bind(id)
+        .bind(next_period)
+        .bind(date)
+        .bind(principal)
+        .bind(fee)
+        .fetch_one(&mut *tx)
+        .await?;
+        let fee_transaction =
+            post_period(&mut tx, &plan, period_id, date, fee, "提前还款手续费").await?;
+        sqlx::query(
+            r#"
+            UPDATE installment_plans
+            SET status = 'paid_off', paid_off_on = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if let Some(transaction_id) = fee_transaction {
+            self.value_fee(transaction_id, config).await;
+        }
+        self.get_plan_detail(id).await
+    }
+
+    /// 为入账日已到的期次记手续费并标记入账；`account_id` 为空时处理全部账户
+    ///
+    /// 返回入账的期数。
+    pub async fn post_due(
+        &self,
+        account_id: Option<Uuid>,
+        today: NaiveDate,
+        config: &RateResolverConfig,
+    ) -> Result<usize, ServiceError> {
+        let plans = sqlx::query_as::<_, InstallmentPlan>(&format!(
+            r#"
+            {}
+            WHERE p.status = 'active' AND ($1::uuid IS NULL OR p.account_id = $1)
+              AND EXISTS (
+                  SELECT 1 FROM installment_periods ip
+                  WHERE ip.plan_id = p.id AND ip.posted_at IS NULL AND ip.posting_date <= $2
+              )
+            "#,
+            PLAN_SELECT
+        ))
+        .bind(account_id)
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut posted = 0;
+        let mut fee_transactions = Vec::new();
+        for plan in plans {
+            let mut tx = self.pool.begin().await?;
+            let due = sqlx::query(
+                r#"
+                SELECT id, period_number, posting_date, fee
+                FROM installment_periods
+                WHERE plan_id = $1 AND posted_at IS NULL AND posting_date <= $2
+                ORDER BY period_number
+                FOR UPDATE SKIP LOCKED
+                "#,
+            )
+            .bind(plan.id)
+            .bind(today)
+            .fetch_all(&mut *tx)
+            .await?;
+            for row in &due {
+                let number: i16 = row.get("period_number");
+                let label = format!("分期手续费 {}/{}", number, plan.installments);
+                if let Some(id) = post_period(
+                    &mut tx,
+                    &plan,
+                    row.get("id"),
+                    row.get("posting_date"),
+                    row.get("fee"),
+                    &label,
+                )
+                .await?
+                {
+                    fee_transactions.push(id);
+                }
+            }
+            sqlx::query(
+                r#"
+                UPDATE installment_plans p
+                SET status = 'completed', updated_at = NOW()
+                WHERE p.id = $1 AND p.status = 'active'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM installment_periods ip
+                      WHERE ip.plan_id = p.id AND ip.posted_at IS NULL
+                  )
+                "#,
+            )
+            .bind(plan.id)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            posted += due.len();
+        }
+
+        for id in fee_transactions {
+            self.value_fee(id, config).await;
+        }
+        Ok(posted)
+    }
+
+    async fn value_fee(&self, transaction_id: Uuid, config: &RateResolverConfig) {
+        if let Err(e) = TransactionValuationService::new(self.pool.clone())
+            .value_transaction(transaction_id, config)
+            .await
+        {
+            tracing::warn!(
+                "Failed to value installment fee {}: {:?}",
+                transaction_id,
+                e
+            );
+        }
+    }
+}
+
+/// 标记一期已入账；手续费大于零时在卡账户记一笔支出并同步余额
+async fn post_period(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    plan: &InstallmentPlan,
+    period_id: Uuid,
+    date: NaiveDate,
+    fee: Decimal,
+    label: &str,
+) -> Result<Option<Uuid>, ServiceError> {
+    let fee_transaction = if fee > Decimal::ZERO {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (
+                id, ledger_id, account_id, transaction_date, amount, transaction_type,
+                category_name, payee, description, status, created_by, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, 'expense', '分期手续费', $6, $7, 'completed', $8, NOW(), NOW())
+            "#,
+        )
+        .bind(id)
+        .bind(plan.ledger_id)
+        .bind(plan.account_id)
+        .bind(date)
+        .bind(fee)
+        .bind(plan.purchase_payee.as_deref().unwrap_or(&plan.account_name))
+        .bind(label)
+        .bind(plan.created_by)
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query(
+            "UPDATE accounts SET current_balance = current_balance - $1, updated_at = NOW() WHERE id = $2",
+        )
+        .bind(fee)
+        .bind(plan.account_id)
+        .execute(&mut **tx)
+        .await?;
+        Some(id)
+    } else {
+        None
+    };
+    sqlx::query(
+        "UPDATE installment_periods SET posted_at = NOW(), fee_transaction_id = $2 WHERE id = $1",
+    )
+    .bind(period_id)
+    .bind(fee_transaction)
+    .execute(&mut **tx)
+    .await?;
+    Ok(fee_transaction)
+}
+
+/// 账户在 [from, to] 内计入账单的分期本金与转分期冲减的本金
+pub(crate) async fn statement_installments<'e, E>(
+    executor: E,
+    account_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(Decimal, Decimal), ServiceError>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let row = sqlx::query(
+        r#"
+        SELECT
+            (SELECT COALESCE(SUM(ip.principal), 0)
+             FROM installment_periods ip
+             JOIN installment_plans p ON p.id = ip.plan_id
+             WHERE p.account_id = $1 AND ip.posting_date BETWEEN $2 AND $3) AS billed,
+            (SELECT COALESCE(SUM(p.principal), 0)
+             FROM installment_plans p
+             WHERE p.account_id = $1 AND p.converted_on BETWEEN $2 AND $3) AS converted
+        "#,
+    )
+    .bind(account_id)
+    .bind(from)
+    .bind(to)
+    .fetch_one(executor)
+    .await?;
+    Ok((row.get("billed"), row.get("converted")))
+}
+
+/// `date` 日终已转分期但尚未计入账单的本金
+pub(crate) async fn unbilled_installments<'e, E>(
+    executor: E,
+    account_id: Uuid,
+    date: NaiveDate,
+) -> Result<Decimal, ServiceError>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let unbilled: Decimal = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(ip.principal), 0)
+        FROM installment_periods ip
+        JOIN installment_plans p ON p.id = ip.plan_id
+        WHERE p.account_id = $1 AND p.converted_on <= $2 AND ip.posting_date > $2
+        "#,
+    )
+    .bind(account_id)
+    .bind(date)
+    .fetch_one(executor)
+    .await?;
+    Ok(unbilled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_build_schedule() {
+        let fee = period_fee(dec!(1000), FeeMode::Rate, dec!(0.006), Decimal::ZERO);
+        assert_eq!(fee, dec!(6));
+
+        let schedule = build_schedule(dec!(1000), 3, fee, date(2025, 1, 31));
+        assert_eq!(schedule.len(), 3);
+        // 尾差计入最后一期
+        assert_eq!(schedule[0].principal, dec!(333.33));
+        assert_eq!(schedule[2].principal, dec!(333.34));
+        let total: Decimal = schedule.iter().map(|p| p.principal).sum();
+        assert_eq!(total, dec!(1000));
+        // 小月取月末
+        assert_eq!(schedule[1].posting_date, date(2025, 2, 28));
+        assert_eq!(schedule[2].posting_date, date(2025, 3, 31));
+        assert!(schedule.iter().all(|p| p.fee == dec!(6)));
+
+        assert_eq!(
+            period_fee(dec!(1000), FeeMode::Fixed, dec!(0.006), dec!(5.5)),
+            dec!(5.5)
+        );
+        assert_eq!(
+            period_fee(dec!(1000), FeeMode::None, dec!(0.006), dec!(5.5)),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_payoff_fee() {
+        assert_eq!(payoff_fee(dec!(666.67), dec!(0.03)), dec!(20));
+        assert_eq!(payoff_fee(Decimal::ZERO, dec!(0.03)), Decimal::ZERO);
+        assert_eq!(payoff_fee(dec!(500), Decimal::ZERO), Decimal::ZERO);
+    }
+}
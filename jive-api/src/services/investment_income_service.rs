@@ -0,0 +1,768 @@
+//! 投资收益：股息 / 利息（现金或再投资）、预扣税、收益率与未来一年的收益日历
+//!
+//! 每笔收益按除息日的持股数计算税前金额，扣除预扣税后为净额。选择再投资（DRIP）时
+//! 以净额在派息日买入同一证券，生成 `reinvest` 交易与新批次；现金收益视为从组合取出。
+//! 预测按过去一年的常规派息（股息、利息）顺延一年，乘以当前持股数并沿用上次的预扣税率。
+
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::investment_service::{
+    lock_account, split_factor_after, InvestmentService, InvestmentTrade, TaxLot, AMOUNT_DP,
+};
+use super::ServiceError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IncomeType {
+    Dividend,
+    SpecialDividend,
+    Interest,
+    CapitalGainDistribution,
+}
+
+impl IncomeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncomeType::Dividend => "dividend",
+            IncomeType::SpecialDividend => "special_dividend",
+            IncomeType::Interest => "interest",
+            IncomeType::CapitalGainDistribution => "capital_gain_distribution",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "dividend" => Some(IncomeType::Dividend),
+            "special_dividend" => Some(IncomeType::SpecialDividend),
+            "interest" => Some(IncomeType::Interest),
+            "capital_gain_distribution" => Some(IncomeType::CapitalGainDistribution),
+            _ => None,
+        }
+    }
+
+    /// 常规派息参与未来收益预测；特别股息与资本利得分配不可预期
+    pub fn is_recurring(&self) -> bool {
+        matches!(self, IncomeType::Dividend | IncomeType::Interest)
+    }
+}
+
+/// 一笔收益的金额拆分
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncomeAmounts {
+    pub amount_per_share: Decimal,
+    pub gross: Decimal,
+    pub withholding: Decimal,
+    pub net: Decimal,
+}
+
+/// 由每股金额或总额、预扣税额或税率计算收益金额
+pub fn compute_income(
+    shares: Decimal,
+    amount_per_share: Option<Decimal>,
+    gross_amount: Option<Decimal>,
+    withholding_tax: Option<Decimal>,
+    withholding_rate: Option<Decimal>,
+) -> Result<IncomeAmounts, ServiceError> {
+    if shares <= Decimal::ZERO {
+        return Err(ServiceError::validation("shares must be positive"));
+    }
+    let (amount_per_share, gross) = match (amount_per_share, gross_amount) {
+        (Some(per_share), None) => (per_share, (per_share * shares).round_dp(AMOUNT_DP)),
+        (None, Some(gross)) => ((gross / shares).round_dp(AMOUNT_DP), gross),
+        (Some(per_share), Some(gross)) => (per_share, gross),
+        (None, None) => {
+            return Err(ServiceError::validation(
+                "amount_per_share or gross_amount is required",
+            ))
+        }
+    };
+    if amount_per_share < Decimal::ZERO || gross < Decimal::ZERO {
+        return Err(ServiceError::validation(
+            "income amount must not be negative",
+        ));
+    }
+    let withholding = match (withholding_tax, withholding_rate) {
+        (Some(_), Some(_)) => {
+            return Err(ServiceError::validation(
+                "Specify either withholding_tax or withholding_rate",
+            ))
+        }
+        (Some(tax), None) => tax,
+        (None, Some(rate)) if rate >= Decimal::ZERO && rate <= Decimal::ONE => {
+            (gross * rate).round_dp(AMOUNT_DP)
+        }
+        (None, Some(_)) => {
+            return Err(ServiceError::validation(
+                "withholding_rate must be between 0 and 1",
+            ))
+        }
+        (None, None) => Decimal::ZERO,
+    };
+    if withholding < Decimal::ZERO || withholding > gross {
+        return Err(ServiceError::validation(
+            "withholding_tax must be between 0 and the gross amount",
+        ));
+    }
+    Ok(IncomeAmounts {
+        amount_per_share,
+        gross,
+        withholding,
+        net: gross - withholding,
+    })
+}
+
+/// 收益率，保留 6 位小数
+pub fn income_yield(income: Decimal, base: Option<Decimal>) -> Option<Decimal> {
+    base.filter(|b| *b > Decimal::ZERO)
+        .map(|b| (income / b).round_dp(6))
+}
+
+/// 过去的一次派息，用于预测
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastPayment {
+    pub security_id: Uuid,
+    pub income_type: IncomeType,
+    pub ex_date: NaiveDate,
+    pub payment_date: NaiveDate,
+    pub amount_per_share: Decimal,
+    /// 预扣税 / 税前金额
+    pub withholding_ratio: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ProjectedPayment {
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub income_type: IncomeType,
+    pub ex_date: NaiveDate,
+    pub payment_date: NaiveDate,
+    pub shares: Decimal,
+    pub amount_per_share: Decimal,
+    pub gross_amount: Decimal,
+    pub net_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IncomeCalendarMonth {
+    /// YYYY-MM
+    pub month: String,
+    pub gross_amount: Decimal,
+    pub net_amount: Decimal,
+    pub payments: Vec<ProjectedPayment>,
+}
+
+/// 将过去一年 (from - 1 年, from] 内的常规派息顺延一年
+pub fn project_payments(
+    past: &[PastPayment],
+    shares: &HashMap<Uuid, Decimal>,
+    tickers: &HashMap<Uuid, String>,
+    from: NaiveDate,
+) -> Vec<ProjectedPayment> {
+    let year = Months::new(12);
+    let Some(window_start) = from.checked_sub_months(year) else {
+        return Vec::new();
+    };
+    let mut projected: Vec<ProjectedPayment> = past
+        .iter()
+        .filter(|p| p.income_type.is_recurring())
+        .filter(|p| p.payment_date > window_start && p.payment_date <= from)
+        .filter_map(|p| {
+            let held = shares.get(&p.security_id).copied()?;
+            if held <= Decimal::ZERO {
+                return None;
+            }
+            let gross = (p.amount_per_share * held).round_dp(2);
+            Some(ProjectedPayment {
+                security_id: p.security_id,
+                ticker: tickers.get(&p.security_id).cloned().unwrap_or_default(),
+                income_type: p.income_type,
+                ex_date: p.ex_date.checked_add_months(year)?,
+                payment_date: p.payment_date.checked_add_months(year)?,
+                shares: held,
+                amount_per_share: p.amount_per_share,
+                gross_amount: gross,
+                net_amount: (gross * (Decimal::ONE - p.withholding_ratio)).round_dp(2),
+            })
+        })
+        .collect();
+    projected.sort_by(|a, b| {
+        a.payment_date
+            .cmp(&b.payment_date)
+            .then_with(|| a.ticker.cmp(&b.ticker))
+    });
+    projected
+}
+
+/// 按派息月份汇总，覆盖 from 所在月起的 12 个月（无派息的月份金额为 0）
+pub fn income_calendar(
+    payments: Vec<ProjectedPayment>,
+    from: NaiveDate,
+) -> Vec<IncomeCalendarMonth> {
+    let first = NaiveDate::from_ymd_opt(from.year(), from.month(), 1).unwrap_or(from);
+    let mut months: BTreeMap<String, IncomeCalendarMonth> = (0..13)
+        .filter_map(|i| first.checked_add_months(Months::new(i)))
+        .map(|d| {
+            let month = d.format("%Y-%m").to_string();
+            (
+                month.clone(),
+                IncomeCalendarMonth {
+                    month,
+                    gross_amount: Decimal::ZERO,
+                    net_amount: Decimal::ZERO,
+                    payments: Vec::new(),
+                },
+            )
+        })
+        .collect();
+    for payment in payments {
+        let key = payment.payment_date.format("%Y-%m").to_string();
+        if let Some(month) = months.get_mut(&key) {
+            month.gross_amount += payment.gross_amount;
+            month.net_amount += payment.net_amount;
+            month.payments.push(payment);
+        }
+    }
+    let mut months: Vec<_> = months.into_values().collect();
+    // 窗口跨 13 个自然月时，末月只在有派息时保留
+    if months.last().is_some_and(|m| m.payments.is_empty()) {
+        months.pop();
+    }
+    months
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecordIncomeRequest {
+    pub security_id: Uuid,
+    pub income_type: IncomeType,
+    pub ex_date: NaiveDate,
+    /// 默认为除息日
+    pub payment_date: Option<NaiveDate>,
+    pub amount_per_share: Option<Decimal>,
+    pub gross_amount: Option<Decimal>,
+    /// 默认按除息日前买入、除息日仍持有的批次计算
+    pub shares: Option<Decimal>,
+    pub withholding_tax: Option<Decimal>,
+    /// 0-1 之间的预扣税率，与 withholding_tax 二选一
+    pub withholding_rate: Option<Decimal>,
+    /// 再投资（DRIP）：以净额在派息日买入同一证券
+    #[serde(default)]
+    pub reinvest: bool,
+    /// 再投资价格，或直接给出再投资股数
+    pub reinvest_price: Option<Decimal>,
+    pub reinvest_quantity: Option<Decimal>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct InvestmentIncome {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub security_id: Uuid,
+    pub income_type: String,
+    pub amount_per_share: Decimal,
+    pub shares: Decimal,
+    pub gross_amount: Decimal,
+    pub withholding_tax: Decimal,
+    pub net_amount: Decimal,
+    pub ex_date: NaiveDate,
+    pub payment_date: NaiveDate,
+    pub reinvested: bool,
+    pub reinvest_trade_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IncomeRecordResult {
+    pub income: InvestmentIncome,
+    pub reinvest_trade: Option<InvestmentTrade>,
+    pub reinvest_lot: Option<TaxLot>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct YearlyIncome {
+    pub year: i32,
+    pub gross_amount: Decimal,
+    pub withholding_tax: Decimal,
+    pub net_amount: Decimal,
+    pub reinvested_amount: Decimal,
+}
+
+/// 单个证券过去 12 个月的收益与收益率
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SecurityIncomeSummary {
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub name: String,
+    pub trailing_gross: Decimal,
+    pub trailing_net: Decimal,
+    pub payments: i64,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    pub market_value: Option<Decimal>,
+    /// 过去 12 个月税前收益 / 当前市值
+    pub current_yield: Option<Decimal>,
+    /// 过去 12 个月税前收益 / 持仓成本
+    pub yield_on_cost: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IncomeHistory {
+    pub account_id: Uuid,
+    pub records: Vec<InvestmentIncome>,
+    pub by_year: Vec<YearlyIncome>,
+    pub by_security: Vec<SecurityIncomeSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IncomeProjection {
+    pub account_id: Uuid,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub total_gross: Decimal,
+    pub total_net: Decimal,
+    pub months: Vec<IncomeCalendarMonth>,
+}
+
+const INCOME_COLUMNS: &str = r#"
+    id, account_id, security_id, income_type, amount_per_share, shares, gross_amount,
+    withholding_tax, net_amount, ex_date, payment_date, reinvested, reinvest_trade_id, notes
+"#;
+
+pub struct InvestmentIncomeService {
+    pool: PgPool,
+}
+
+impl InvestmentIncomeService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 记录一笔股息 / 利息，选择再投资时同时建立再投资批次
+    pub async fn record_income(
+        &self,
+        account_id: Uuid,
+        user_id: Uuid,
+        request: RecordIncomeRequest,
+    ) -> Result<IncomeRecordResult, ServiceError> {
+        InvestmentService::new(self.pool.clone())
+            .get_security(request.security_id)
+            .await?;
+        let payment_date = request.payment_date.unwrap_or(request.ex_date);
+        if payment_date < request.ex_date {
+            return Err(ServiceError::validation(
+                "payment_date must not be before ex_date",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        lock_account(&mut tx, account_id).await?;
+        let shares = match request.shares {
+            Some(shares) => shares,
+            None => {
+                eligible_shares(&mut tx, account_id, request.security_id, request.ex_date).await?
+            }
+        };
+        if shares <= Decimal::ZERO {
+            return Err(ServiceError::business_rule(
+                "No shares of the security were held on the ex-date",
+            ));
+        }
+        let amounts = compute_income(
+            shares,
+            request.amount_per_share,
+            request.gross_amount,
+            request.withholding_tax,
+            request.withholding_rate,
+        )?;
+
+        let (reinvest_trade, reinvest_lot) = if request.reinvest {
+            let (quantity, price) = match (request.reinvest_quantity, request.reinvest_price) {
+                _ if amounts.net <= Decimal::ZERO => {
+                    return Err(ServiceError::validation(
+                        "Nothing to reinvest after withholding tax",
+                    ))
+                }
+                (Some(q), _) if q > Decimal::ZERO => (q, (amounts.net / q).round_dp(AMOUNT_DP)),
+                (None, Some(p)) if p > Decimal::ZERO => ((amounts.net / p).round_dp(AMOUNT_DP), p),
+                _ => {
+                    return Err(ServiceError::validation(
+                        "A positive reinvest_price or reinvest_quantity is required to reinvest",
+                    ))
+                }
+            };
+            let trade = sqlx::query_as::<_, InvestmentTrade>(
+                r#"
+                INSERT INTO investment_trades
+                    (account_id, security_id, trade_type, quantity, price, total_amount,
+                     trade_date, notes, created_by)
+                VALUES ($1, $2, 'reinvest', $3, $4, $5, $6, $7, $8)
+                RETURNING *
+                "#,
+            )
+            .bind(account_id)
+            .bind(request.security_id)
+            .bind(quantity)
+            .bind(price)
+            .bind(amounts.net)
+            .bind(payment_date)
+            .bind(&request.notes)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let factor =
+                split_factor_after(&mut tx, account_id, request.security_id, payment_date).await?;
+            let lot_quantity = (quantity * factor).round_dp(AMOUNT_DP);
+            let lot = sqlx::query_as::<_, TaxLot>(
+                r#"
+                INSERT INTO investment_lots
+                    (account_id, security_id, open_trade_id, acquired_date,
+                     original_quantity, quantity, cost_basis)
+                VALUES ($1, $2, $3, $4, $5, $5, $6)
+                RETURNING id, account_id, security_id, open_trade_id, acquired_date,
+                          original_quantity, quantity, cost_basis, closed_at
+                "#,
+            )
+            .bind(account_id)
+            .bind(request.security_id)
+            .bind(trade.id)
+            .bind(payment_date)
+            .bind(lot_quantity)
+            .bind(amounts.net)
+            .fetch_one(&mut *tx)
+            .await?;
+            (Some(trade), Some(lot))
+        } else {
+            (None, None)
+        };
+
+        let income = sqlx::query_as::<_, InvestmentIncome>(&format!(
+            r#"
+            INSERT INTO investment_income
+                (account_id, security_id, income_type, amount_per_share, shares, gross_amount,
+                 withholding_tax, net_amount, ex_date, payment_date, reinvested,
+                 reinvest_trade_id, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING {}
+            "#,
+            INCOME_COLUMNS
+        ))
+        .bind(account_id)
+        .bind(request.security_id)
+        .bind(request.income_type.as_str())
+        .bind(amounts.amount_per_share)
+        .bind(shares)
+        .bind(amounts.gross)
+        .bind(amounts.withholding)
+        .bind(amounts.net)
+        .bind(request.ex_date)
+        .bind(payment_date)
+        .bind(request.reinvest)
+        .bind(reinvest_trade.as_ref().map(|t| t.id))
+        .bind(&request.notes)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(IncomeRecordResult {
+            income,
+            reinvest_trade,
+            reinvest_lot,
+        })
+    }
+
+    /// 收益明细、按年汇总与各证券过去 12 个月的收益率
+    pub async fn income_history(
+        &self,
+        account_id: Uuid,
+        year: Option<i32>,
+        security_id: Option<Uuid>,
+    ) -> Result<IncomeHistory, ServiceError> {
+        let records = sqlx::query_as::<_, InvestmentIncome>(&format!(
+            r#"
+            SELECT {}
+            FROM investment_income
+            WHERE account_id = $1
+              AND ($2::int IS NULL OR EXTRACT(YEAR FROM payment_date)::int = $2)
+              AND ($3::uuid IS NULL OR security_id = $3)
+            ORDER BY payment_date DESC, created_at DESC
+            "#,
+            INCOME_COLUMNS
+        ))
+        .bind(account_id)
+        .bind(year)
+        .bind(security_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let by_year = sqlx::query(
+            r#"
+            SELECT EXTRACT(YEAR FROM payment_date)::int AS year,
+                   SUM(gross_amount) AS gross_amount,
+                   SUM(withholding_tax) AS withholding_tax,
+                   SUM(net_amount) AS net_amount,
+                   COALESCE(SUM(net_amount) FILTER (WHERE reinvested), 0) AS reinvested_amount
+            FROM investment_income
+            WHERE account_id = $1 AND ($2::uuid IS NULL OR security_id = $2)
+            GROUP BY 1
+            ORDER BY 1 DESC
+            "#,
+        )
+        .bind(account_id)
+        .bind(security_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| YearlyIncome {
+            year: row.get("year"),
+            gross_amount: row.get("gross_amount"),
+            withholding_tax: row.get("withholding_tax"),
+            net_amount: row.get("net_amount"),
+            reinvested_amount: row.get("reinvested_amount"),
+        })
+        .collect();
+
+        let today = Utc::now().date_naive();
+        let window_start = today.checked_sub_months(Months::new(12)).unwrap_or(today);
+        let trailing = sqlx::query(
+            r#"
+            SELECT i.security_id, s.ticker, s.name,
+                   SUM(i.gross_amount) AS gross_amount,
+                   SUM(i.net_amount) AS net_amount,
+                   COUNT(*) AS payments
+            FROM investment_income i
+            JOIN securities s ON s.id = i.security_id
+            WHERE i.account_id = $1 AND i.payment_date > $2 AND i.payment_date <= $3
+              AND ($4::uuid IS NULL OR i.security_id = $4)
+            GROUP BY i.security_id, s.ticker, s.name
+            "#,
+        )
+        .bind(account_id)
+        .bind(window_start)
+        .bind(today)
+        .bind(security_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let holdings = InvestmentService::new(self.pool.clone())
+            .get_holdings(account_id)
+            .await?;
+
+        let mut by_security: Vec<SecurityIncomeSummary> = trailing
+            .into_iter()
+            .map(|row| {
+                let id: Uuid = row.get("security_id");
+                let gross: Decimal = row.get("gross_amount");
+                let holding = holdings.iter().find(|h| h.security_id == id);
+                let cost_basis = holding.map(|h| h.cost_basis).unwrap_or(Decimal::ZERO);
+                let market_value = holding.and_then(|h| h.market_value);
+                SecurityIncomeSummary {
+                    security_id: id,
+                    ticker: row.get("ticker"),
+                    name: row.get("name"),
+                    trailing_gross: gross,
+                    trailing_net: row.get("net_amount"),
+                    payments: row.get("payments"),
+                    quantity: holding.map(|h| h.quantity).unwrap_or(Decimal::ZERO),
+                    cost_basis,
+                    market_value,
+                    current_yield: income_yield(gross, market_value),
+                    yield_on_cost: income_yield(gross, Some(cost_basis)),
+                }
+            })
+            .collect();
+        by_security.sort_by_key(|s| std::cmp::Reverse(s.trailing_gross));
+
+        Ok(IncomeHistory {
+            account_id,
+            records,
+            by_year,
+            by_security,
+        })
+    }
+
+    /// 未来一年的预计收益日历
+    pub async fn projected_income(
+        &self,
+        account_id: Uuid,
+        from: Option<NaiveDate>,
+    ) -> Result<IncomeProjection, ServiceError> {
+        let from = from.unwrap_or_else(|| Utc::now().date_naive());
+        let to = from.checked_add_months(Months::new(12)).unwrap_or(from);
+        let window_start = from.checked_sub_months(Months::new(12)).unwrap_or(from);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT security_id, income_type, ex_date, payment_date, amount_per_share,
+                   gross_amount, withholding_tax
+            FROM investment_income
+            WHERE account_id = $1 AND payment_date > $2 AND payment_date <= $3
+              AND income_type IN ('dividend', 'interest')
+            ORDER BY payment_date
+            "#,
+        )
+        .bind(account_id)
+        .bind(window_start)
+        .bind(from)
+        .fetch_all(&self.pool)
+        .await?;
+        let past: Vec<PastPayment> = rows
+            .iter()
+            .filter_map(|row| {
+                let gross: Decimal = row.get("gross_amount");
+                let withholding: Decimal = row.get("withholding_tax");
+                Some(PastPayment {
+                    security_id: row.get("security_id"),
+                    income_type: IncomeType::parse(row.get::<&str, _>("income_type"))?,
+                    ex_date: row.get("ex_date"),
+                    payment_date: row.get("payment_date"),
+                    amount_per_share: row.get("amount_per_share"),
+                    withholding_ratio: if gross.is_zero() {
+                        Decimal::ZERO
+                    } else {
+                        withholding / gross
+                    },
+                })
+            })
+            .collect();
+
+        let holdings = InvestmentService::new(self.pool.clone())
+            .get_holdings(account_id)
+            .await?;
+        let shares = holdings
+            .iter()
+            .map(|h| (h.security_id, h.quantity))
+            .collect();
+        let tickers = holdings
+            .iter()
+            .map(|h| (h.security_id, h.ticker.clone()))
+            .collect();
+
+        let payments = project_payments(&past, &shares, &tickers, from);
+        let total_gross = payments.iter().map(|p| p.gross_amount).sum();
+        let total_net = payments.iter().map(|p| p.net_amount).sum();
+        Ok(IncomeProjection {
+            account_id,
+            from_date: from,
+            to_date: to,
+            total_gross,
+            total_net,
+            months: income_calendar(payments, from),
+        })
+    }
+}
+
+/// 除息日登记的持股数：除息日前买入且当时仍持有的批次
+///
+/// 当前仍持有的数量按除息日之后的拆股还原；除息日及之后卖出的部分按卖出时的数量加回。
+async fn eligible_shares(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account_id: Uuid,
+    security_id: Uuid,
+    ex_date: NaiveDate,
+) -> Result<Decimal, ServiceError> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            COALESCE((SELECT SUM(quantity) FROM investment_lots
+                      WHERE account_id = $1 AND security_id = $2 AND acquired_date < $3), 0)
+                AS held,
+            COALESCE((SELECT SUM(d.quantity) FROM investment_lot_disposals d
+                      JOIN investment_lots l ON l.id = d.lot_id
+                      WHERE l.account_id = $1 AND l.security_id = $2
+                        AND l.acquired_date < $3 AND d.disposed_date >= $3), 0)
+                AS disposed
+        "#,
+    )
+    .bind(account_id)
+    .bind(security_id)
+    .bind(ex_date)
+    .fetch_one(&mut **tx)
+    .await?;
+    let factor = split_factor_after(tx, account_id, security_id, ex_date).await?;
+    let held: Decimal = row.get("held");
+    let disposed: Decimal = row.get("disposed");
+    Ok((held / factor + disposed).round_dp(AMOUNT_DP))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_compute_income_with_withholding() {
+        let amounts =
+            compute_income(dec!(200), Some(dec!(0.24)), None, None, Some(dec!(0.1))).unwrap();
+        assert_eq!(amounts.gross, dec!(48));
+        assert_eq!(amounts.withholding, dec!(4.8));
+        assert_eq!(amounts.net, dec!(43.2));
+
+        let from_total =
+            compute_income(dec!(300), None, Some(dec!(90)), Some(dec!(13.5)), None).unwrap();
+        assert_eq!(from_total.amount_per_share, dec!(0.3));
+        assert_eq!(from_total.net, dec!(76.5));
+
+        assert!(compute_income(dec!(10), Some(dec!(1)), None, Some(dec!(11)), None).is_err());
+        assert!(compute_income(dec!(10), None, None, None, None).is_err());
+        assert!(compute_income(
+            dec!(10),
+            Some(dec!(1)),
+            None,
+            Some(dec!(1)),
+            Some(dec!(0.1))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_projection_calendar() {
+        let aapl = Uuid::from_u128(1);
+        let bond = Uuid::from_u128(2);
+        let payment = |security_id, income_type, m, d, per_share, ratio| PastPayment {
+            security_id,
+            income_type,
+            ex_date: date(2025, m, d),
+            payment_date: date(2025, m, d),
+            amount_per_share: per_share,
+            withholding_ratio: ratio,
+        };
+        let past = vec![
+            payment(aapl, IncomeType::Dividend, 2, 15, dec!(0.25), dec!(0.1)),
+            payment(aapl, IncomeType::Dividend, 5, 15, dec!(0.25), dec!(0.1)),
+            payment(aapl, IncomeType::SpecialDividend, 5, 20, dec!(5), dec!(0)),
+            payment(bond, IncomeType::Interest, 3, 1, dec!(2), dec!(0)),
+        ];
+        let shares = HashMap::from([(aapl, dec!(100))]);
+        let tickers = HashMap::from([(aapl, "AAPL".to_string())]);
+
+        let payments = project_payments(&past, &shares, &tickers, date(2025, 6, 30));
+        // 特别股息不预测，已清仓的债券不预测
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].payment_date, date(2026, 2, 15));
+        assert_eq!(payments[0].gross_amount, dec!(25));
+        assert_eq!(payments[0].net_amount, dec!(22.5));
+
+        let calendar = income_calendar(payments, date(2025, 6, 30));
+        assert_eq!(calendar.len(), 12);
+        assert_eq!(calendar[0].month, "2025-06");
+        let may = calendar.iter().find(|m| m.month == "2026-05").unwrap();
+        assert_eq!(may.net_amount, dec!(22.5));
+        let total: Decimal = calendar.iter().map(|m| m.gross_amount).sum();
+        assert_eq!(total, dec!(50));
+    }
+
+    #[test]
+    fn test_income_yield() {
+        assert_eq!(income_yield(dec!(30), Some(dec!(1000))), Some(dec!(0.03)));
+        assert_eq!(income_yield(dec!(30), Some(Decimal::ZERO)), None);
+        assert_eq!(income_yield(dec!(30), None), None);
+    }
+}
@@ -0,0 +1,687 @@
+//! 投资组合绩效：时间加权收益（TWR）、资金加权收益（XIRR）、风险指标与基准比较
+//!
+//! 组合价值取自 `valuations` 中的每日估值，外部现金流为买入（投入）与卖出、现金收益、
+//! 合并所得现金（取出）。
+//! 收益率、波动率等均以小数表示（0.05 即 5%）。
+
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::ServiceError;
+
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// 某日收盘后的组合价值（包含当日现金流的影响）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValuationPoint {
+    pub date: NaiveDate,
+    pub value: Decimal,
+}
+
+/// 外部现金流：正数为投入，负数为取出
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalFlow {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+}
+
+/// 相邻两个估值点之间的收益
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodReturn {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub net_flow: Decimal,
+    pub value: f64,
+}
+
+/// 按估值点切分子区间，剔除区间内（不含起点、含终点）的现金流后计算收益
+///
+/// 起点价值为零的区间（尚未建仓或已清仓）不计收益。
+pub fn period_returns(points: &[ValuationPoint], flows: &[ExternalFlow]) -> Vec<PeriodReturn> {
+    let mut returns = Vec::new();
+    for pair in points.windows(2) {
+        let (prev, cur) = (&pair[0], &pair[1]);
+        let net_flow: Decimal = flows
+            .iter()
+            .filter(|f| f.date > prev.date && f.date <= cur.date)
+            .map(|f| f.amount)
+            .sum();
+        if prev.value <= Decimal::ZERO {
+            continue;
+        }
+        let value = ((cur.value - net_flow) / prev.value - Decimal::ONE)
+            .to_f64()
+            .unwrap_or(0.0);
+        returns.push(PeriodReturn {
+            start: prev.date,
+            end: cur.date,
+            net_flow,
+            value,
+        });
+    }
+    returns
+}
+
+/// 链接子区间收益得到时间加权收益
+pub fn time_weighted_return(returns: &[PeriodReturn]) -> f64 {
+    returns.iter().fold(1.0, |acc, r| acc * (1.0 + r.value)) - 1.0
+}
+
+/// 按持有天数年化
+pub fn annualize(total_return: f64, days: i64) -> Option<f64> {
+    if days <= 0 || total_return <= -1.0 {
+        return None;
+    }
+    Some((1.0 + total_return).powf(DAYS_PER_YEAR / days as f64) - 1.0)
+}
+
+/// 不规则现金流的内部收益率（年化）。现金流须同时包含正负值
+pub fn xirr(flows: &[(NaiveDate, f64)]) -> Option<f64> {
+    let first = flows.iter().map(|(d, _)| *d).min()?;
+    let has_positive = flows.iter().any(|(_, v)| *v > 0.0);
+    let has_negative = flows.iter().any(|(_, v)| *v < 0.0);
+    if !has_positive || !has_negative {
+        return None;
+    }
+    let times: Vec<(f64, f64)> = flows
+        .iter()
+        .map(|(d, v)| ((*d - first).num_days() as f64 / DAYS_PER_YEAR, *v))
+        .collect();
+    let npv = |rate: f64| -> f64 { times.iter().map(|(t, v)| v / (1.0 + rate).powf(*t)).sum() };
+    let derivative = |rate: f64| -> f64 {
+        times
+            .iter()
+            .map(|(t, v)| -t * v / (1.0 + rate).powf(t + 1.0))
+            .sum()
+    };
+
+    // 牛顿法，失败时退回二分法
+    let mut rate = 0.1;
+    for _ in 0..100 {
+        let value = npv(rate);
+        if value.abs() < 1e-9 {
+            return Some(rate);
+        }
+        let slope = derivative(rate);
+        if slope == 0.0 || !slope.is_finite() {
+            break;
+        }
+        let next = rate - value / slope;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        if (next - rate).abs() < 1e-12 {
+            return Some(next);
+        }
+        rate = next;
+    }
+
+    let mut low = -0.999_999;
+    let mut high = 1.0;
+    let low_sign = npv(low).signum();
+    while npv(high).signum() == low_sign {
+        high *= 2.0;
+        if high > 1e6 {
+            return None;
+        }
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        let value = npv(mid);
+        if value.abs() < 1e-9 || (high - low) < 1e-12 {
+            return Some(mid);
+        }
+        if value.signum() == low_sign {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+/// 样本标准差
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// 年化波动率：子区间收益的标准差按平均区间长度折算到一年
+pub fn annualized_volatility(returns: &[PeriodReturn]) -> Option<f64> {
+    let days: i64 = returns.iter().map(|r| (r.end - r.start).num_days()).sum();
+    if days <= 0 {
+        return None;
+    }
+    let values: Vec<f64> = returns.iter().map(|r| r.value).collect();
+    let periods_per_year = DAYS_PER_YEAR * values.len() as f64 / days as f64;
+    std_dev(&values).map(|sd| sd * periods_per_year.sqrt())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drawdown {
+    /// 正数，0.2 表示自高点回撤 20%
+    pub depth: f64,
+    pub peak_date: Option<NaiveDate>,
+    pub trough_date: Option<NaiveDate>,
+}
+
+/// 基于收益指数（剔除现金流）的最大回撤
+pub fn max_drawdown(returns: &[PeriodReturn]) -> Drawdown {
+    let mut drawdown = Drawdown {
+        depth: 0.0,
+        peak_date: None,
+        trough_date: None,
+    };
+    let Some(first) = returns.first() else {
+        return drawdown;
+    };
+    let mut index = 1.0;
+    let mut peak = 1.0;
+    let mut peak_date = first.start;
+    for r in returns {
+        index *= 1.0 + r.value;
+        if index > peak {
+            peak = index;
+            peak_date = r.end;
+        } else {
+            let depth = 1.0 - index / peak;
+            if depth > drawdown.depth {
+                drawdown = Drawdown {
+                    depth,
+                    peak_date: Some(peak_date),
+                    trough_date: Some(r.end),
+                };
+            }
+        }
+    }
+    drawdown
+}
+
+/// 贝塔与相关系数（两组收益需按区间对齐）
+pub fn beta_and_correlation(portfolio: &[f64], benchmark: &[f64]) -> (Option<f64>, Option<f64>) {
+    let n = portfolio.len().min(benchmark.len());
+    if n < 2 {
+        return (None, None);
+    }
+    let (p, b) = (&portfolio[..n], &benchmark[..n]);
+    let mean_p = p.iter().sum::<f64>() / n as f64;
+    let mean_b = b.iter().sum::<f64>() / n as f64;
+    let cov = p
+        .iter()
+        .zip(b)
+        .map(|(x, y)| (x - mean_p) * (y - mean_b))
+        .sum::<f64>();
+    let var_p = p.iter().map(|x| (x - mean_p).powi(2)).sum::<f64>();
+    let var_b = b.iter().map(|y| (y - mean_b).powi(2)).sum::<f64>();
+    let beta = (var_b > 0.0).then(|| cov / var_b);
+    let correlation = (var_p > 0.0 && var_b > 0.0).then(|| cov / (var_p * var_b).sqrt());
+    (beta, correlation)
+}
+
+fn round6(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PerformanceMetrics {
+    /// 时间加权收益（不受投入 / 取出时点影响）
+    pub time_weighted_return: f64,
+    /// 区间满一年时的年化 TWR
+    pub annualized_twr: Option<f64>,
+    /// 资金加权收益（XIRR，年化）
+    pub money_weighted_return: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RiskMetrics {
+    pub volatility: Option<f64>,
+    pub max_drawdown: f64,
+    pub max_drawdown_peak: Option<NaiveDate>,
+    pub max_drawdown_trough: Option<NaiveDate>,
+    pub sharpe_ratio: Option<f64>,
+    pub risk_free_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BenchmarkComparison {
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub start_price: Decimal,
+    pub end_price: Decimal,
+    pub total_return: f64,
+    pub annualized_return: Option<f64>,
+    /// 组合 TWR - 基准收益
+    pub excess_return: f64,
+    pub beta: Option<f64>,
+    pub correlation: Option<f64>,
+    /// 超额收益的年化标准差
+    pub tracking_error: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PerformancePoint {
+    pub date: NaiveDate,
+    pub value: Decimal,
+    pub net_flow: Decimal,
+    /// 截至当日的累计 TWR
+    pub cumulative_return: f64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PortfolioPerformanceReport {
+    pub account_id: Uuid,
+    /// 实际使用的首个估值日（请求起始日之前最近的估值，或区间内首个估值）
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub start_value: Decimal,
+    pub end_value: Decimal,
+    /// 区间内净投入（买入 - 卖出）
+    pub net_contributions: Decimal,
+    /// 期末价值 - 期初价值 - 净投入
+    pub investment_gain: Decimal,
+    pub performance: PerformanceMetrics,
+    pub risk: RiskMetrics,
+    pub benchmark: Option<BenchmarkComparison>,
+    pub series: Vec<PerformancePoint>,
+}
+
+/// 基准证券的收盘价序列，按日期升序
+#[derive(Debug, Clone)]
+pub struct BenchmarkSeries {
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub prices: Vec<(NaiveDate, Decimal)>,
+}
+
+/// 由估值、现金流与（可选）基准价格计算报告
+pub fn build_report(
+    account_id: Uuid,
+    points: &[ValuationPoint],
+    flows: &[ExternalFlow],
+    risk_free_rate: f64,
+    benchmark: Option<&BenchmarkSeries>,
+) -> Option<PortfolioPerformanceReport> {
+    let first = points.first()?;
+    let last = points.last()?;
+    let days = (last.date - first.date).num_days();
+    let flows: Vec<ExternalFlow> = flows
+        .iter()
+        .filter(|f| f.date > first.date && f.date <= last.date)
+        .cloned()
+        .collect();
+    let net_contributions: Decimal = flows.iter().map(|f| f.amount).sum();
+
+    let returns = period_returns(points, &flows);
+    let twr = time_weighted_return(&returns);
+
+    // XIRR：期初价值视为投入，期末价值视为取出（投资者视角下投入为负）
+    let mut cash_flows = vec![(first.date, -first.value.to_f64().unwrap_or(0.0))];
+    cash_flows.extend(
+        flows
+            .iter()
+            .map(|f| (f.date, -f.amount.to_f64().unwrap_or(0.0))),
+    );
+    cash_flows.push((last.date, last.value.to_f64().unwrap_or(0.0)));
+    let mwr = xirr(&cash_flows);
+
+    let volatility = annualized_volatility(&returns);
+    let drawdown = max_drawdown(&returns);
+    let sharpe = match (annualize(twr, days), volatility) {
+        (Some(annual), Some(vol)) if vol > 0.0 => Some((annual - risk_free_rate) / vol),
+        _ => None,
+    };
+
+    let mut series = Vec::with_capacity(points.len());
+    let mut growth = 1.0;
+    for point in points {
+        let period = returns.iter().find(|r| r.end == point.date);
+        if let Some(r) = period {
+            growth *= 1.0 + r.value;
+        }
+        series.push(PerformancePoint {
+            date: point.date,
+            value: point.value,
+            net_flow: flows
+                .iter()
+                .filter(|f| f.date == point.date)
+                .map(|f| f.amount)
+                .sum(),
+            cumulative_return: round6(growth - 1.0),
+        });
+    }
+
+    let benchmark = benchmark
+        .and_then(|series| compare_benchmark(series, &returns, first.date, last.date, twr));
+
+    Some(PortfolioPerformanceReport {
+        account_id,
+        start_date: first.date,
+        end_date: last.date,
+        start_value: first.value,
+        end_value: last.value,
+        net_contributions,
+        investment_gain: last.value - first.value - net_contributions,
+        performance: PerformanceMetrics {
+            time_weighted_return: round6(twr),
+            annualized_twr: (days >= 365)
+                .then(|| annualize(twr, days))
+                .flatten()
+                .map(round6),
+            money_weighted_return: mwr.map(round6),
+        },
+        risk: RiskMetrics {
+            volatility: volatility.map(round6),
+            max_drawdown: round6(drawdown.depth),
+            max_drawdown_peak: drawdown.peak_date,
+            max_drawdown_trough: drawdown.trough_date,
+            sharpe_ratio: sharpe.map(round6),
+            risk_free_rate,
+        },
+        benchmark,
+        series,
+    })
+}
+
+/// 某日（含）之前最近的收盘价；`prices` 按日期升序
+fn price_on(prices: &[(NaiveDate, Decimal)], date: NaiveDate) -> Option<Decimal> {
+    let idx = prices.partition_point(|(d, _)| *d <= date);
+    idx.checked_sub(1).map(|i| prices[i].1)
+}
+
+fn compare_benchmark(
+    series: &BenchmarkSeries,
+    returns: &[PeriodReturn],
+    start: NaiveDate,
+    end: NaiveDate,
+    portfolio_twr: f64,
+) -> Option<BenchmarkComparison> {
+    let prices = &series.prices;
+    let start_price = price_on(prices, start)?;
+    let end_price = price_on(prices, end)?;
+    let total_return = (end_price / start_price - Decimal::ONE).to_f64()?;
+
+    // 与组合子区间对齐的基准收益
+    let mut aligned_portfolio = Vec::new();
+    let mut aligned_benchmark = Vec::new();
+    let mut excess = Vec::new();
+    for r in returns {
+        if let (Some(p0), Some(p1)) = (price_on(prices, r.start), price_on(prices, r.end)) {
+            let b = (p1 / p0 - Decimal::ONE).to_f64().unwrap_or(0.0);
+            aligned_portfolio.push(r.value);
+            aligned_benchmark.push(b);
+            excess.push(PeriodReturn {
+                value: r.value - b,
+                ..r.clone()
+            });
+        }
+    }
+    let (beta, correlation) = beta_and_correlation(&aligned_portfolio, &aligned_benchmark);
+    let days = (end - start).num_days();
+
+    Some(BenchmarkComparison {
+        security_id: series.security_id,
+        ticker: series.ticker.clone(),
+        start_price,
+        end_price,
+        total_return: round6(total_return),
+        annualized_return: (days >= 365)
+            .then(|| annualize(total_return, days))
+            .flatten()
+            .map(round6),
+        excess_return: round6(portfolio_twr - total_return),
+        beta: beta.map(round6),
+        correlation: correlation.map(round6),
+        tracking_error: annualized_volatility(&excess).map(round6),
+    })
+}
+
+pub struct InvestmentPerformanceService {
+    pool: PgPool,
+}
+
+impl InvestmentPerformanceService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 计算账户在 [start, end] 内的绩效；起始日没有估值时取其之前最近的一次
+    pub async fn report(
+        &self,
+        account_id: Uuid,
+        start: NaiveDate,
+        end: NaiveDate,
+        benchmark_security_id: Option<Uuid>,
+        risk_free_rate: f64,
+    ) -> Result<PortfolioPerformanceReport, ServiceError> {
+        if start >= end {
+            return Err(ServiceError::validation(
+                "start_date must be before end_date",
+            ));
+        }
+
+        // 每天取一条估值，市价估值优先
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (valuation_date) valuation_date, amount
+            FROM valuations
+            WHERE account_id = $1
+              AND valuation_date <= $3
+              AND valuation_date >= COALESCE(
+                  (SELECT MAX(valuation_date) FROM valuations
+                   WHERE account_id = $1 AND valuation_date <= $2),
+                  $2)
+            ORDER BY valuation_date, (valuation_type = 'market') DESC, updated_at DESC
+            "#,
+        )
+        .bind(account_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+        let points: Vec<ValuationPoint> = rows
+            .into_iter()
+            .map(|row| ValuationPoint {
+                date: row.get("valuation_date"),
+                value: row.get("amount"),
+            })
+            .collect();
+        if points.len() < 2 {
+            return Err(ServiceError::business_rule(
+                "At least two valuations are required in the selected period",
+            ));
+        }
+
+        let flow_rows = sqlx::query(
+            r#"
+            SELECT flow_date AS trade_date, SUM(amount) AS amount
+            FROM (
+                SELECT trade_date AS flow_date,
+                       CASE WHEN trade_type = 'buy' THEN total_amount ELSE -total_amount END
+                           AS amount
+                FROM investment_trades
+                WHERE account_id = $1 AND trade_type IN ('buy', 'sell', 'merger')
+                UNION ALL
+                -- 现金收益从组合取出；再投资的收益留在组合内，不是外部现金流
+                SELECT payment_date, -net_amount
+                FROM investment_income
+                WHERE account_id = $1 AND NOT reinvested
+            ) f
+            WHERE flow_date > $2 AND flow_date <= $3
+            GROUP BY flow_date
+            ORDER BY flow_date
+            "#,
+        )
+        .bind(account_id)
+        .bind(points[0].date)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+        let flows: Vec<ExternalFlow> = flow_rows
+            .into_iter()
+            .map(|row| ExternalFlow {
+                date: row.get("trade_date"),
+                amount: row.get("amount"),
+            })
+            .collect();
+
+        let benchmark = match benchmark_security_id {
+            Some(security_id) => {
+                let ticker: String =
+                    sqlx::query_scalar("SELECT ticker FROM securities WHERE id = $1")
+                        .bind(security_id)
+                        .fetch_optional(&self.pool)
+                        .await?
+                        .ok_or_else(|| ServiceError::not_found("Security", security_id))?;
+                let prices: Vec<(NaiveDate, Decimal)> = sqlx::query_as(
+                    r#"
+                    SELECT price_date, close_price FROM security_prices
+                    WHERE security_id = $1 AND price_date <= $3
+                      AND price_date >= COALESCE(
+                          (SELECT MAX(price_date) FROM security_prices
+                           WHERE security_id = $1 AND price_date <= $2),
+                          $2)
+                    ORDER BY price_date
+                    "#,
+                )
+                .bind(security_id)
+                .bind(points[0].date)
+                .bind(end)
+                .fetch_all(&self.pool)
+                .await?;
+                if prices.is_empty() {
+                    return Err(ServiceError::business_rule(format!(
+                        "No price history for benchmark {}",
+                        ticker
+                    )));
+                }
+                Some(BenchmarkSeries {
+                    security_id,
+                    ticker,
+                    prices,
+                })
+            }
+            None => None,
+        };
+
+        build_report(
+            account_id,
+            &points,
+            &flows,
+            risk_free_rate,
+            benchmark.as_ref(),
+        )
+        .ok_or(ServiceError::InternalError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn point(m: u32, d: u32, value: Decimal) -> ValuationPoint {
+        ValuationPoint {
+            date: date(2025, m, d),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_twr_ignores_contributions() {
+        // 1000 涨 10% 到 1100，当天追加 1100 至 2200，之后再涨 10%
+        let points = vec![
+            point(1, 1, dec!(1000)),
+            point(1, 2, dec!(2200)),
+            point(1, 3, dec!(2420)),
+        ];
+        let flows = vec![ExternalFlow {
+            date: date(2025, 1, 2),
+            amount: dec!(1100),
+        }];
+        let returns = period_returns(&points, &flows);
+        assert_eq!(returns.len(), 2);
+        assert!((returns[0].value - 0.1).abs() < 1e-12);
+        assert!((time_weighted_return(&returns) - 0.21).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_xirr_single_year() {
+        let flows = vec![
+            (NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), -1000.0),
+            (NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(), 1100.0),
+        ];
+        // 365 天恰好一年
+        let rate = xirr(&flows).unwrap();
+        assert!((rate - 0.1).abs() < 1e-6, "{rate}");
+        assert!(xirr(&[(date(2025, 1, 1), -1.0)]).is_none());
+    }
+
+    #[test]
+    fn test_xirr_with_interim_flows() {
+        // 年初投入 1000，年中再投 1000，年末 2150
+        let flows = vec![
+            (NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(), -1000.0),
+            (NaiveDate::from_ymd_opt(2023, 7, 2).unwrap(), -1000.0),
+            (NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 2150.0),
+        ];
+        let rate = xirr(&flows).unwrap();
+        let npv: f64 = flows
+            .iter()
+            .map(|(d, v)| v / (1.0 + rate).powf((*d - flows[0].0).num_days() as f64 / 365.0))
+            .sum();
+        assert!(npv.abs() < 1e-6);
+        assert!(rate > 0.09 && rate < 0.11, "{rate}");
+    }
+
+    #[test]
+    fn test_max_drawdown_and_volatility() {
+        let points = vec![
+            point(1, 1, dec!(100)),
+            point(1, 2, dec!(120)),
+            point(1, 3, dec!(90)),
+            point(1, 4, dec!(108)),
+            point(1, 5, dec!(130)),
+        ];
+        let returns = period_returns(&points, &[]);
+        let dd = max_drawdown(&returns);
+        assert!((dd.depth - 0.25).abs() < 1e-12);
+        assert_eq!(dd.peak_date, Some(date(2025, 1, 2)));
+        assert_eq!(dd.trough_date, Some(date(2025, 1, 3)));
+        assert!(annualized_volatility(&returns).unwrap() > 0.0);
+        assert!(annualized_volatility(&returns[..1]).is_none());
+    }
+
+    #[test]
+    fn test_benchmark_comparison() {
+        let points = vec![
+            point(1, 1, dec!(100)),
+            point(1, 2, dec!(110)),
+            point(1, 3, dec!(99)),
+        ];
+        // 基准 1 月 2 日无价格，沿用前一日
+        let series = BenchmarkSeries {
+            security_id: Uuid::nil(),
+            ticker: "IDX".to_string(),
+            prices: vec![(date(2025, 1, 1), dec!(50)), (date(2025, 1, 3), dec!(55))],
+        };
+        let report = build_report(Uuid::nil(), &points, &[], 0.0, Some(&series)).unwrap();
+        let benchmark = report.benchmark.unwrap();
+        assert_eq!(benchmark.total_return, 0.1);
+        assert_eq!(report.performance.time_weighted_return, -0.01);
+        assert_eq!(benchmark.excess_return, -0.11);
+        assert_eq!(report.series.last().unwrap().cumulative_return, -0.01);
+        // 组合与基准只有一个区间同时变动，beta 仍可计算
+        assert!(benchmark.beta.is_some());
+    }
+}
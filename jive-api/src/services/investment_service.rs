@@ -0,0 +1,1389 @@
+//! 投资账户：证券、交易、税务批次（lot）与已实现损益
+//!
+//! 每笔买入建立一个批次；卖出按账户的成本计价方式（FIFO / LIFO / 移动平均）从
+//! 交易日及之前买入的批次中结转，逐批次记录已实现损益。持仓由未结清的批次汇总得到。
+//!
+//! 拆股 / 合股按账户记录：交易日早于除权日的批次按比例调整数量，总成本不变。
+//! 补录的交易按其后的拆股折算为当前股数，因此录入顺序不影响结果。
+//!
+//! 合并 / 分拆视为成本重分配：新证券批次沿用原批次的买入日期，合并时收到的现金
+//! 按其分摊的成本计入已实现损益。
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::ServiceError;
+
+/// 批次成本与损益的小数位（与 investment_lots.cost_basis 精度一致）
+pub(crate) const AMOUNT_DP: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasisMethod {
+    /// 先进先出
+    Fifo,
+    /// 后进先出
+    Lifo,
+    /// 移动加权平均
+    Average,
+}
+
+impl CostBasisMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostBasisMethod::Fifo => "fifo",
+            CostBasisMethod::Lifo => "lifo",
+            CostBasisMethod::Average => "average",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fifo" => Some(CostBasisMethod::Fifo),
+            "lifo" => Some(CostBasisMethod::Lifo),
+            "average" => Some(CostBasisMethod::Average),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeType {
+    Buy,
+    Sell,
+}
+
+impl TradeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeType::Buy => "buy",
+            TradeType::Sell => "sell",
+        }
+    }
+}
+
+/// 未结清的批次（数量与成本均为当前股数口径）
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenLot {
+    pub id: Uuid,
+    pub acquired_date: NaiveDate,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+}
+
+/// 一笔卖出在某个批次上的结转
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedDisposal {
+    pub lot_id: Uuid,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    pub proceeds: Decimal,
+    pub realized_gain: Decimal,
+    pub acquired_date: NaiveDate,
+    pub holding_days: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisposalPlan {
+    pub disposals: Vec<PlannedDisposal>,
+    /// 参与计算的批次结转后的状态（平均法会调整全部批次的成本）
+    pub lots: Vec<OpenLot>,
+}
+
+/// 计算卖出的批次结转
+///
+/// `lots` 为交易日及之前买入的未结清批次，按买入先后升序；`proceeds` 为扣除佣金后的卖出所得，
+/// 按数量分摊到各批次，最后一个批次承担舍入差额。
+pub fn plan_disposals(
+    method: CostBasisMethod,
+    lots: &[OpenLot],
+    quantity: Decimal,
+    proceeds: Decimal,
+    trade_date: NaiveDate,
+) -> Result<DisposalPlan, ServiceError> {
+    if quantity <= Decimal::ZERO {
+        return Err(ServiceError::validation("quantity must be positive"));
+    }
+    let available: Decimal = lots.iter().map(|lot| lot.quantity).sum();
+    if available < quantity {
+        return Err(ServiceError::business_rule(format!(
+            "Insufficient holdings: {} available, {} requested",
+            available.normalize(),
+            quantity.normalize()
+        )));
+    }
+
+    let mut lots = lots.to_vec();
+    if method == CostBasisMethod::Average && !available.is_zero() {
+        // 平均法：先把各批次成本统一为平均单位成本，再按先进先出结转（保留持有期信息）
+        let total_cost: Decimal = lots.iter().map(|lot| lot.cost_basis).sum();
+        let mut remaining_cost = total_cost;
+        let last = lots.len() - 1;
+        for (i, lot) in lots.iter_mut().enumerate() {
+            lot.cost_basis = if i == last {
+                remaining_cost
+            } else {
+                (total_cost * lot.quantity / available).round_dp(AMOUNT_DP)
+            };
+            remaining_cost -= lot.cost_basis;
+        }
+    }
+
+    let order: Vec<usize> = match method {
+        CostBasisMethod::Lifo => (0..lots.len()).rev().collect(),
+        CostBasisMethod::Fifo | CostBasisMethod::Average => (0..lots.len()).collect(),
+    };
+
+    let mut disposals = Vec::new();
+    let mut remaining_qty = quantity;
+    let mut remaining_proceeds = proceeds;
+    for index in order {
+        if remaining_qty.is_zero() {
+            break;
+        }
+        let lot = &mut lots[index];
+        if lot.quantity.is_zero() {
+            continue;
+        }
+        let take = remaining_qty.min(lot.quantity);
+        let cost = if take == lot.quantity {
+            lot.cost_basis
+        } else {
+            (lot.cost_basis * take / lot.quantity).round_dp(AMOUNT_DP)
+        };
+        remaining_qty -= take;
+        let lot_proceeds = if remaining_qty.is_zero() {
+            remaining_proceeds
+        } else {
+            (proceeds * take / quantity).round_dp(AMOUNT_DP)
+        };
+        remaining_proceeds -= lot_proceeds;
+
+        lot.quantity -= take;
+        lot.cost_basis -= cost;
+        disposals.push(PlannedDisposal {
+            lot_id: lot.id,
+            quantity: take,
+            cost_basis: cost,
+            proceeds: lot_proceeds,
+            realized_gain: lot_proceeds - cost,
+            acquired_date: lot.acquired_date,
+            holding_days: (trade_date - lot.acquired_date).num_days(),
+        });
+    }
+
+    Ok(DisposalPlan { disposals, lots })
+}
+
+/// 按拆股比例折算数量：1 拆 2 时 ratio_from = 1, ratio_to = 2
+pub fn split_quantity(quantity: Decimal, ratio_from: Decimal, ratio_to: Decimal) -> Decimal {
+    (quantity * ratio_to / ratio_from).round_dp(AMOUNT_DP)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CorporateActionType {
+    /// 合并：原证券换成新证券（可附带现金），原批次结清
+    Merger,
+    /// 分拆：保留原证券，按比例获得新证券，并从原批次划出部分成本
+    Spinoff,
+}
+
+impl CorporateActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorporateActionType::Merger => "merger",
+            CorporateActionType::Spinoff => "spinoff",
+        }
+    }
+}
+
+/// 公司行动对单个批次的成本重分配
+#[derive(Debug, Clone, PartialEq)]
+pub struct LotReallocation {
+    pub lot_id: Uuid,
+    pub acquired_date: NaiveDate,
+    pub quantity: Decimal,
+    /// 原批次调整后的数量与成本
+    pub remaining_quantity: Decimal,
+    pub remaining_cost: Decimal,
+    /// 新证券批次，沿用原批次的买入日期
+    pub new_quantity: Decimal,
+    pub new_cost: Decimal,
+    /// 合并时收到的现金及其分摊的成本
+    pub cash: Decimal,
+    pub cash_cost: Decimal,
+}
+
+/// 计算合并 / 分拆的成本重分配
+///
+/// `cost_allocation` 为划入新证券的成本比例：合并不付现金时为 1，付现金时需指定；
+/// 分拆时必须在 (0, 1) 之间，通常按公告的公允价值比例确定。
+pub fn plan_corporate_action(
+    action: CorporateActionType,
+    lots: &[OpenLot],
+    ratio_from: Decimal,
+    ratio_to: Decimal,
+    cost_allocation: Option<Decimal>,
+    cash_per_share: Decimal,
+) -> Result<Vec<LotReallocation>, ServiceError> {
+    if ratio_from <= Decimal::ZERO || ratio_to <= Decimal::ZERO {
+        return Err(ServiceError::validation("ratio must be positive"));
+    }
+    if cash_per_share < Decimal::ZERO {
+        return Err(ServiceError::validation(
+            "cash_per_share must not be negative",
+        ));
+    }
+    let allocation = match (action, cost_allocation) {
+        (CorporateActionType::Merger, None) if cash_per_share.is_zero() => Decimal::ONE,
+        (CorporateActionType::Merger, None) => {
+            return Err(ServiceError::validation(
+                "cost_allocation is required when the merger pays cash",
+            ))
+        }
+        (CorporateActionType::Merger, Some(a)) if a > Decimal::ZERO && a <= Decimal::ONE => a,
+        (CorporateActionType::Spinoff, _) if !cash_per_share.is_zero() => {
+            return Err(ServiceError::validation("spin-offs do not pay cash"))
+        }
+        (CorporateActionType::Spinoff, Some(a)) if a > Decimal::ZERO && a < Decimal::ONE => a,
+        _ => {
+            return Err(ServiceError::validation(
+                "cost_allocation must be in (0, 1] for mergers and (0, 1) for spin-offs",
+            ))
+        }
+    };
+
+    Ok(lots
+        .iter()
+        .filter(|lot| lot.quantity > Decimal::ZERO)
+        .map(|lot| {
+            let new_cost = if allocation == Decimal::ONE {
+                lot.cost_basis
+            } else {
+                (lot.cost_basis * allocation).round_dp(AMOUNT_DP)
+            };
+            let new_quantity = split_quantity(lot.quantity, ratio_from, ratio_to);
+            match action {
+                CorporateActionType::Merger => LotReallocation {
+                    lot_id: lot.id,
+                    acquired_date: lot.acquired_date,
+                    quantity: lot.quantity,
+                    remaining_quantity: Decimal::ZERO,
+                    remaining_cost: Decimal::ZERO,
+                    new_quantity,
+                    new_cost,
+                    cash: (lot.quantity * cash_per_share).round_dp(AMOUNT_DP),
+                    cash_cost: lot.cost_basis - new_cost,
+                },
+                CorporateActionType::Spinoff => LotReallocation {
+                    lot_id: lot.id,
+                    acquired_date: lot.acquired_date,
+                    quantity: lot.quantity,
+                    remaining_quantity: lot.quantity,
+                    remaining_cost: lot.cost_basis - new_cost,
+                    new_quantity,
+                    new_cost,
+                    cash: Decimal::ZERO,
+                    cash_cost: Decimal::ZERO,
+                },
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Security {
+    pub id: Uuid,
+    pub ticker: String,
+    pub name: String,
+    pub security_type: String,
+    pub exchange: Option<String>,
+    pub currency: String,
+    pub current_price: Option<Decimal>,
+    pub price_updated_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSecurityRequest {
+    pub ticker: String,
+    pub name: String,
+    /// stock / etf / mutual_fund / bond / option / cryptocurrency / commodity / index
+    pub security_type: Option<String>,
+    pub exchange: Option<String>,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TradeRequest {
+    pub security_id: Uuid,
+    pub trade_type: TradeType,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub commission: Option<Decimal>,
+    /// 默认今天
+    pub trade_date: Option<NaiveDate>,
+    pub settlement_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SplitRequest {
+    pub security_id: Uuid,
+    /// 除权日，早于该日买入的批次参与调整
+    pub ex_date: NaiveDate,
+    /// 拆股比例 ratio_from : ratio_to，如 1:2 拆股、10:1 合股
+    pub ratio_from: Decimal,
+    pub ratio_to: Decimal,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CorporateActionRequest {
+    pub action_type: CorporateActionType,
+    /// 原证券
+    pub security_id: Uuid,
+    /// 合并后或分拆出的证券
+    pub new_security_id: Uuid,
+    /// 生效日，早于该日买入的批次参与调整
+    pub effective_date: NaiveDate,
+    /// 每 ratio_from 股原证券对应 ratio_to 股新证券
+    pub ratio_from: Decimal,
+    pub ratio_to: Decimal,
+    /// 划入新证券的成本比例
+    pub cost_allocation: Option<Decimal>,
+    /// 合并时每股原证券收到的现金
+    pub cash_per_share: Option<Decimal>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct InvestmentTrade {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub security_id: Uuid,
+    pub trade_type: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub commission: Decimal,
+    pub total_amount: Decimal,
+    pub realized_gain: Option<Decimal>,
+    pub cost_basis_method: Option<String>,
+    pub split_from: Option<Decimal>,
+    pub split_to: Option<Decimal>,
+    /// 合并 / 分拆涉及的新证券
+    pub related_security_id: Option<Uuid>,
+    pub cost_allocation: Option<Decimal>,
+    pub trade_date: NaiveDate,
+    pub settlement_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct TaxLot {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub security_id: Uuid,
+    pub open_trade_id: Option<Uuid>,
+    pub acquired_date: NaiveDate,
+    pub original_quantity: Decimal,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct LotDisposal {
+    pub id: Uuid,
+    pub trade_id: Uuid,
+    pub lot_id: Uuid,
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    pub proceeds: Decimal,
+    pub realized_gain: Decimal,
+    pub acquired_date: NaiveDate,
+    pub disposed_date: NaiveDate,
+    pub holding_days: i32,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TradeExecution {
+    pub trade: InvestmentTrade,
+    /// 买入新建的批次
+    pub lot: Option<TaxLot>,
+    /// 卖出结转的批次明细
+    pub disposals: Vec<LotDisposal>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CorporateActionResult {
+    pub trade: InvestmentTrade,
+    /// 新证券的批次
+    pub new_lots: Vec<TaxLot>,
+    /// 合并现金部分的已实现损益
+    pub disposals: Vec<LotDisposal>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Holding {
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub name: String,
+    pub currency: String,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    pub avg_cost: Decimal,
+    pub open_lots: i64,
+    pub first_purchase_date: Option<NaiveDate>,
+    pub current_price: Option<Decimal>,
+    pub market_value: Option<Decimal>,
+    pub unrealized_gain: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RealizedGainsReport {
+    pub account_id: Uuid,
+    pub year: Option<i32>,
+    pub total_proceeds: Decimal,
+    pub total_cost_basis: Decimal,
+    pub total_realized_gain: Decimal,
+    /// 持有一年及以上
+    pub long_term_gain: Decimal,
+    pub short_term_gain: Decimal,
+    pub disposals: Vec<LotDisposal>,
+}
+
+const DISPOSAL_COLUMNS: &str = r#"
+    d.id, d.trade_id, d.lot_id, l.security_id, s.ticker, d.quantity, d.cost_basis, d.proceeds,
+    d.realized_gain, d.acquired_date, d.disposed_date, d.holding_days
+"#;
+
+pub struct InvestmentService {
+    pool: PgPool,
+}
+
+impl InvestmentService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_securities(
+        &self,
+        query: Option<&str>,
+    ) -> Result<Vec<Security>, ServiceError> {
+        let pattern = query
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(|q| format!("%{}%", q.to_uppercase()));
+        let securities = sqlx::query_as::<_, Security>(
+            r#"
+            SELECT * FROM securities
+            WHERE $1::text IS NULL OR UPPER(ticker) LIKE $1 OR UPPER(name) LIKE $1
+            ORDER BY ticker
+            LIMIT 200
+            "#,
+        )
+        .bind(pattern)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(securities)
+    }
+
+    pub async fn get_security(&self, id: Uuid) -> Result<Security, ServiceError> {
+        sqlx::query_as::<_, Security>("SELECT * FROM securities WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Security", id))
+    }
+
+    pub async fn create_security(
+        &self,
+        request: CreateSecurityRequest,
+    ) -> Result<Security, ServiceError> {
+        let ticker = request.ticker.trim().to_uppercase();
+        let name = request.name.trim();
+        if ticker.is_empty() || ticker.len() > 32 {
+            return Err(ServiceError::validation("Invalid ticker"));
+        }
+        if name.is_empty() {
+            return Err(ServiceError::validation("name is required"));
+        }
+        let exchange = request
+            .exchange
+            .map(|e| e.trim().to_uppercase())
+            .filter(|e| !e.is_empty());
+        let currency = request
+            .currency
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| "CNY".to_string());
+
+        let security = sqlx::query_as::<_, Security>(
+            r#"
+            INSERT INTO securities (ticker, name, security_type, exchange, currency)
+            VALUES ($1, $2, COALESCE($3, 'stock'), $4, $5)
+            ON CONFLICT DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(&ticker)
+        .bind(name)
+        .bind(request.security_type.map(|t| t.to_lowercase()))
+        .bind(&exchange)
+        .bind(&currency)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some("23514") => {
+                ServiceError::validation("Invalid security_type")
+            }
+            other => other.into(),
+        })?;
+
+        security.ok_or_else(|| {
+            ServiceError::conflict(format!(
+                "Security {}{} already exists",
+                ticker,
+                exchange.map(|e| format!(".{}", e)).unwrap_or_default()
+            ))
+        })
+    }
+
+    pub async fn cost_basis_method(
+        &self,
+        account_id: Uuid,
+    ) -> Result<CostBasisMethod, ServiceError> {
+        let method: String =
+            sqlx::query_scalar("SELECT cost_basis_method FROM accounts WHERE id = $1")
+                .bind(account_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| ServiceError::not_found("account", account_id))?;
+        Ok(CostBasisMethod::parse(&method).unwrap_or(CostBasisMethod::Fifo))
+    }
+
+    /// 修改账户的成本计价方式，只影响之后的卖出
+    pub async fn set_cost_basis_method(
+        &self,
+        account_id: Uuid,
+        method: CostBasisMethod,
+    ) -> Result<(), ServiceError> {
+        let result = sqlx::query(
+            "UPDATE accounts SET cost_basis_method = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(account_id)
+        .bind(method.as_str())
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("account", account_id));
+        }
+        Ok(())
+    }
+
+    /// 执行买入 / 卖出并维护批次
+    pub async fn execute_trade(
+        &self,
+        account_id: Uuid,
+        user_id: Uuid,
+        request: TradeRequest,
+    ) -> Result<TradeExecution, ServiceError> {
+        if request.quantity <= Decimal::ZERO {
+            return Err(ServiceError::validation("quantity must be positive"));
+        }
+        if request.price < Decimal::ZERO {
+            return Err(ServiceError::validation("price must not be negative"));
+        }
+        let commission = request.commission.unwrap_or(Decimal::ZERO).round_dp(2);
+        if commission < Decimal::ZERO {
+            return Err(ServiceError::validation("commission must not be negative"));
+        }
+        let trade_date = request
+            .trade_date
+            .unwrap_or_else(|| Utc::now().date_naive());
+        self.get_security(request.security_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let method = lock_account(&mut tx, account_id).await?;
+        // 补录交易：折算为当前（拆股后的）股数
+        let factor =
+            split_factor_after(&mut tx, account_id, request.security_id, trade_date).await?;
+        let lot_quantity = (request.quantity * factor).round_dp(AMOUNT_DP);
+        let gross = request.quantity * request.price;
+
+        let (total_amount, realized, plan) = match request.trade_type {
+            TradeType::Buy => ((gross + commission).round_dp(AMOUNT_DP), None, None),
+            TradeType::Sell => {
+                let proceeds = (gross - commission).round_dp(AMOUNT_DP);
+                let lots = open_lots(&mut tx, account_id, request.security_id, trade_date).await?;
+                let plan = plan_disposals(method, &lots, lot_quantity, proceeds, trade_date)?;
+                let realized: Decimal = plan.disposals.iter().map(|d| d.realized_gain).sum();
+                (proceeds, Some(realized), Some(plan))
+            }
+        };
+
+        let trade = sqlx::query_as::<_, InvestmentTrade>(
+            r#"
+            INSERT INTO investment_trades
+                (account_id, security_id, trade_type, quantity, price, commission, total_amount,
+                 realized_gain, cost_basis_method, trade_date, settlement_date, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *
+            "#,
+        )
+        .bind(account_id)
+        .bind(request.security_id)
+        .bind(request.trade_type.as_str())
+        .bind(request.quantity)
+        .bind(request.price)
+        .bind(commission)
+        .bind(total_amount)
+        .bind(realized)
+        .bind(plan.as_ref().map(|_| method.as_str()))
+        .bind(trade_date)
+        .bind(request.settlement_date)
+        .bind(&request.notes)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut lot = None;
+        let mut disposals = Vec::new();
+        match plan {
+            None => {
+                lot = Some(
+                    sqlx::query_as::<_, TaxLot>(
+                        r#"
+                        INSERT INTO investment_lots
+                            (account_id, security_id, open_trade_id, acquired_date,
+                             original_quantity, quantity, cost_basis)
+                        VALUES ($1, $2, $3, $4, $5, $5, $6)
+                        RETURNING id, account_id, security_id, open_trade_id, acquired_date,
+                                  original_quantity, quantity, cost_basis, closed_at
+                        "#,
+                    )
+                    .bind(account_id)
+                    .bind(request.security_id)
+                    .bind(trade.id)
+                    .bind(trade_date)
+                    .bind(lot_quantity)
+                    .bind(total_amount)
+                    .fetch_one(&mut *tx)
+                    .await?,
+                );
+            }
+            Some(plan) => {
+                for updated in &plan.lots {
+                    sqlx::query(
+                        r#"
+                        UPDATE investment_lots
+                        SET quantity = $2,
+                            cost_basis = $3,
+                            closed_at = CASE WHEN $2 = 0 THEN NOW() ELSE NULL END,
+                            updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(updated.id)
+                    .bind(updated.quantity)
+                    .bind(updated.cost_basis)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                for d in &plan.disposals {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO investment_lot_disposals
+                            (trade_id, lot_id, quantity, cost_basis, proceeds, realized_gain,
+                             acquired_date, disposed_date, holding_days)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                        "#,
+                    )
+                    .bind(trade.id)
+                    .bind(d.lot_id)
+                    .bind(d.quantity)
+                    .bind(d.cost_basis)
+                    .bind(d.proceeds)
+                    .bind(d.realized_gain)
+                    .bind(d.acquired_date)
+                    .bind(trade_date)
+                    .bind(d.holding_days as i32)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+        tx.commit().await?;
+
+        if trade.trade_type == "sell" {
+            disposals = self.disposals_for_trade(trade.id).await?;
+        }
+        Ok(TradeExecution {
+            trade,
+            lot,
+            disposals,
+        })
+    }
+
+    /// 记录拆股 / 合股，调整除权日前买入的批次
+    pub async fn record_split(
+        &self,
+        account_id: Uuid,
+        user_id: Uuid,
+        request: SplitRequest,
+    ) -> Result<InvestmentTrade, ServiceError> {
+        if request.ratio_from <= Decimal::ZERO || request.ratio_to <= Decimal::ZERO {
+            return Err(ServiceError::validation("split ratio must be positive"));
+        }
+        if request.ratio_from == request.ratio_to {
+            return Err(ServiceError::validation(
+                "split ratio must change the share count",
+            ));
+        }
+        self.get_security(request.security_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        lock_account(&mut tx, account_id).await?;
+
+        let lots = sqlx::query(
+            r#"
+            SELECT id, quantity, original_quantity
+            FROM investment_lots
+            WHERE account_id = $1 AND security_id = $2 AND acquired_date < $3
+            FOR UPDATE
+            "#,
+        )
+        .bind(account_id)
+        .bind(request.security_id)
+        .bind(request.ex_date)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut position = Decimal::ZERO;
+        for row in lots {
+            let quantity =
+                split_quantity(row.get("quantity"), request.ratio_from, request.ratio_to);
+            let original = split_quantity(
+                row.get("original_quantity"),
+                request.ratio_from,
+                request.ratio_to,
+            );
+            position += quantity;
+            sqlx::query(
+                r#"
+                UPDATE investment_lots
+                SET quantity = $2, original_quantity = $3, updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(row.get::<Uuid, _>("id"))
+            .bind(quantity)
+            .bind(original)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let trade = sqlx::query_as::<_, InvestmentTrade>(
+            r#"
+            INSERT INTO investment_trades
+                (account_id, security_id, trade_type, quantity, split_from, split_to,
+                 trade_date, notes, created_by)
+            VALUES ($1, $2, 'split', $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(account_id)
+        .bind(request.security_id)
+        .bind(position)
+        .bind(request.ratio_from)
+        .bind(request.ratio_to)
+        .bind(request.ex_date)
+        .bind(&request.notes)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(trade)
+    }
+
+    /// 记录合并 / 分拆，把原证券批次的成本重新分配到新证券
+    pub async fn record_corporate_action(
+        &self,
+        account_id: Uuid,
+        user_id: Uuid,
+        request: CorporateActionRequest,
+    ) -> Result<CorporateActionResult, ServiceError> {
+        if request.security_id == request.new_security_id {
+            return Err(ServiceError::validation(
+                "new_security_id must differ from security_id",
+            ));
+        }
+        self.get_security(request.security_id).await?;
+        self.get_security(request.new_security_id).await?;
+        let cash_per_share = request.cash_per_share.unwrap_or(Decimal::ZERO);
+        let date = request.effective_date;
+
+        let mut tx = self.pool.begin().await?;
+        lock_account(&mut tx, account_id).await?;
+        let lots = open_lots(
+            &mut tx,
+            account_id,
+            request.security_id,
+            date - chrono::Duration::days(1),
+        )
+        .await?;
+        if lots.is_empty() {
+            return Err(ServiceError::business_rule(
+                "No open lots of the security before the effective date",
+            ));
+        }
+        let plan = plan_corporate_action(
+            request.action_type,
+            &lots,
+            request.ratio_from,
+            request.ratio_to,
+            request.cost_allocation,
+            cash_per_share,
+        )?;
+
+        let quantity: Decimal = plan.iter().map(|r| r.quantity).sum();
+        let cash: Decimal = plan.iter().map(|r| r.cash).sum();
+        let realized =
+            (!cash.is_zero()).then(|| plan.iter().map(|r| r.cash - r.cash_cost).sum::<Decimal>());
+        let allocation = plan
+            .iter()
+            .map(|r| r.new_cost)
+            .sum::<Decimal>()
+            .checked_div(lots.iter().map(|l| l.cost_basis).sum())
+            .map(|a| a.round_dp(AMOUNT_DP))
+            .or(request.cost_allocation);
+
+        let trade = sqlx::query_as::<_, InvestmentTrade>(
+            r#"
+            INSERT INTO investment_trades
+                (account_id, security_id, related_security_id, trade_type, quantity, price,
+                 total_amount, realized_gain, split_from, split_to, cost_allocation,
+                 trade_date, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING *
+            "#,
+        )
+        .bind(account_id)
+        .bind(request.security_id)
+        .bind(request.new_security_id)
+        .bind(request.action_type.as_str())
+        .bind(quantity)
+        .bind(cash_per_share)
+        .bind(cash)
+        .bind(realized)
+        .bind(request.ratio_from)
+        .bind(request.ratio_to)
+        .bind(allocation)
+        .bind(date)
+        .bind(&request.notes)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut new_lots = Vec::new();
+        for r in &plan {
+            sqlx::query(
+                r#"
+                UPDATE investment_lots
+                SET quantity = $2,
+                    cost_basis = $3,
+                    closed_at = CASE WHEN $2 = 0 THEN NOW() ELSE NULL END,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(r.lot_id)
+            .bind(r.remaining_quantity)
+            .bind(r.remaining_cost)
+            .execute(&mut *tx)
+            .await?;
+
+            if r.new_quantity > Decimal::ZERO {
+                new_lots.push(
+                    sqlx::query_as::<_, TaxLot>(
+                        r#"
+                        INSERT INTO investment_lots
+                            (account_id, security_id, open_trade_id, acquired_date,
+                             original_quantity, quantity, cost_basis)
+                        VALUES ($1, $2, $3, $4, $5, $5, $6)
+                        RETURNING id, account_id, security_id, open_trade_id, acquired_date,
+                                  original_quantity, quantity, cost_basis, closed_at
+                        "#,
+                    )
+                    .bind(account_id)
+                    .bind(request.new_security_id)
+                    .bind(trade.id)
+                    .bind(r.acquired_date)
+                    .bind(r.new_quantity)
+                    .bind(r.new_cost)
+                    .fetch_one(&mut *tx)
+                    .await?,
+                );
+            }
+
+            if r.cash > Decimal::ZERO {
+                sqlx::query(
+                    r#"
+                    INSERT INTO investment_lot_disposals
+                        (trade_id, lot_id, quantity, cost_basis, proceeds, realized_gain,
+                         acquired_date, disposed_date, holding_days)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    "#,
+                )
+                .bind(trade.id)
+                .bind(r.lot_id)
+                .bind(r.quantity)
+                .bind(r.cash_cost)
+                .bind(r.cash)
+                .bind(r.cash - r.cash_cost)
+                .bind(r.acquired_date)
+                .bind(date)
+                .bind((date - r.acquired_date).num_days() as i32)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+
+        let disposals = if cash.is_zero() {
+            Vec::new()
+        } else {
+            self.disposals_for_trade(trade.id).await?
+        };
+        Ok(CorporateActionResult {
+            trade,
+            new_lots,
+            disposals,
+        })
+    }
+
+    pub async fn list_trades(
+        &self,
+        account_id: Uuid,
+        security_id: Option<Uuid>,
+    ) -> Result<Vec<InvestmentTrade>, ServiceError> {
+        let trades = sqlx::query_as::<_, InvestmentTrade>(
+            r#"
+            SELECT * FROM investment_trades
+            WHERE account_id = $1 AND ($2::uuid IS NULL OR security_id = $2)
+            ORDER BY trade_date DESC, created_at DESC
+            "#,
+        )
+        .bind(account_id)
+        .bind(security_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(trades)
+    }
+
+    pub async fn list_lots(
+        &self,
+        account_id: Uuid,
+        security_id: Option<Uuid>,
+        include_closed: bool,
+    ) -> Result<Vec<TaxLot>, ServiceError> {
+        let lots = sqlx::query_as::<_, TaxLot>(
+            r#"
+            SELECT id, account_id, security_id, open_trade_id, acquired_date,
+                   original_quantity, quantity, cost_basis, closed_at
+            FROM investment_lots
+            WHERE account_id = $1
+              AND ($2::uuid IS NULL OR security_id = $2)
+              AND ($3 OR quantity > 0)
+            ORDER BY security_id, acquired_date, created_at
+            "#,
+        )
+        .bind(account_id)
+        .bind(security_id)
+        .bind(include_closed)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(lots)
+    }
+
+    /// 由未结清批次汇总的持仓
+    pub async fn get_holdings(&self, account_id: Uuid) -> Result<Vec<Holding>, ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.id AS security_id, s.ticker, s.name, s.currency, s.current_price,
+                   SUM(l.quantity) AS quantity,
+                   SUM(l.cost_basis) AS cost_basis,
+                   COUNT(*) AS open_lots,
+                   MIN(l.acquired_date) AS first_purchase_date
+            FROM investment_lots l
+            JOIN securities s ON s.id = l.security_id
+            WHERE l.account_id = $1 AND l.quantity > 0
+            GROUP BY s.id
+            ORDER BY s.ticker
+            "#,
+        )
+        .bind(account_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut holdings: Vec<Holding> = rows
+            .into_iter()
+            .map(|row| {
+                let quantity: Decimal = row.get("quantity");
+                let cost_basis: Decimal = row.get("cost_basis");
+                let current_price: Option<Decimal> = row.get("current_price");
+                let market_value = current_price.map(|p| (p * quantity).round_dp(2));
+                Holding {
+                    security_id: row.get("security_id"),
+                    ticker: row.get("ticker"),
+                    name: row.get("name"),
+                    currency: row.get("currency"),
+                    quantity,
+                    cost_basis: cost_basis.round_dp(2),
+                    avg_cost: (cost_basis / quantity).round_dp(4),
+                    open_lots: row.get("open_lots"),
+                    first_purchase_date: row.get("first_purchase_date"),
+                    current_price,
+                    market_value,
+                    unrealized_gain: market_value.map(|v| v - cost_basis.round_dp(2)),
+                }
+            })
+            .collect();
+        holdings.sort_by(|a, b| {
+            b.market_value
+                .unwrap_or(b.cost_basis)
+                .cmp(&a.market_value.unwrap_or(a.cost_basis))
+        });
+        Ok(holdings)
+    }
+
+    /// 按批次列出已实现损益，可按卖出年份筛选
+    pub async fn realized_gains(
+        &self,
+        account_id: Uuid,
+        year: Option<i32>,
+    ) -> Result<RealizedGainsReport, ServiceError> {
+        let disposals = sqlx::query_as::<_, LotDisposal>(&format!(
+            r#"
+            SELECT {}
+            FROM investment_lot_disposals d
+            JOIN investment_lots l ON l.id = d.lot_id
+            JOIN securities s ON s.id = l.security_id
+            WHERE l.account_id = $1
+              AND ($2::int IS NULL OR EXTRACT(YEAR FROM d.disposed_date)::int = $2)
+            ORDER BY d.disposed_date, d.created_at
+            "#,
+            DISPOSAL_COLUMNS
+        ))
+        .bind(account_id)
+        .bind(year)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut report = RealizedGainsReport {
+            account_id,
+            year,
+            total_proceeds: Decimal::ZERO,
+            total_cost_basis: Decimal::ZERO,
+            total_realized_gain: Decimal::ZERO,
+            long_term_gain: Decimal::ZERO,
+            short_term_gain: Decimal::ZERO,
+            disposals: Vec::new(),
+        };
+        for d in &disposals {
+            report.total_proceeds += d.proceeds;
+            report.total_cost_basis += d.cost_basis;
+            report.total_realized_gain += d.realized_gain;
+            if d.holding_days >= 365 {
+                report.long_term_gain += d.realized_gain;
+            } else {
+                report.short_term_gain += d.realized_gain;
+            }
+        }
+        report.disposals = disposals;
+        Ok(report)
+    }
+
+    async fn disposals_for_trade(&self, trade_id: Uuid) -> Result<Vec<LotDisposal>, ServiceError> {
+        let disposals = sqlx::query_as::<_, LotDisposal>(&format!(
+            r#"
+            SELECT {}
+            FROM investment_lot_disposals d
+            JOIN investment_lots l ON l.id = d.lot_id
+            JOIN securities s ON s.id = l.security_id
+            WHERE d.trade_id = $1
+            ORDER BY d.created_at
+            "#,
+            DISPOSAL_COLUMNS
+        ))
+        .bind(trade_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(disposals)
+    }
+}
+
+/// 锁定账户以串行化同一账户的交易，返回成本计价方式
+pub(crate) async fn lock_account(
+    tx: &mut Transaction<'_, Postgres>,
+    account_id: Uuid,
+) -> Result<CostBasisMethod, ServiceError> {
+    let method: String = sqlx::query_scalar(
+        "SELECT cost_basis_method FROM accounts WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(account_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| ServiceError::not_found("account", account_id))?;
+    Ok(CostBasisMethod::parse(&method).unwrap_or(CostBasisMethod::Fifo))
+}
+
+/// 交易日之后记录的拆股累积比例
+pub(crate) async fn split_factor_after(
+    tx: &mut Transaction<'_, Postgres>,
+    account_id: Uuid,
+    security_id: Uuid,
+    trade_date: NaiveDate,
+) -> Result<Decimal, ServiceError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT split_from, split_to
+        FROM investment_trades
+        WHERE account_id = $1 AND security_id = $2 AND trade_type = 'split' AND trade_date > $3
+        "#,
+    )
+    .bind(account_id)
+    .bind(security_id)
+    .bind(trade_date)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(rows.iter().fold(Decimal::ONE, |factor, row| {
+        factor * row.get::<Decimal, _>("split_to") / row.get::<Decimal, _>("split_from")
+    }))
+}
+
+async fn open_lots(
+    tx: &mut Transaction<'_, Postgres>,
+    account_id: Uuid,
+    security_id: Uuid,
+    trade_date: NaiveDate,
+) -> Result<Vec<OpenLot>, ServiceError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, acquired_date, quantity, cost_basis
+        FROM investment_lots
+        WHERE account_id = $1 AND security_id = $2 AND quantity > 0 AND acquired_date <= $3
+        ORDER BY acquired_date, created_at
+        FOR UPDATE
+        "#,
+    )
+    .bind(account_id)
+    .bind(security_id)
+    .bind(trade_date)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| OpenLot {
+            id: row.get("id"),
+            acquired_date: row.get("acquired_date"),
+            quantity: row.get("quantity"),
+            cost_basis: row.get("cost_basis"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn lots() -> Vec<OpenLot> {
+        vec![
+            OpenLot {
+                id: Uuid::from_u128(1),
+                acquired_date: date(2025, 1, 10),
+                quantity: dec!(100),
+                cost_basis: dec!(1000),
+            },
+            OpenLot {
+                id: Uuid::from_u128(2),
+                acquired_date: date(2025, 3, 10),
+                quantity: dec!(100),
+                cost_basis: dec!(1500),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_fifo_spans_lots() {
+        // 卖出 150 股，所得 2400
+        let plan = plan_disposals(
+            CostBasisMethod::Fifo,
+            &lots(),
+            dec!(150),
+            dec!(2400),
+            date(2025, 6, 1),
+        )
+        .unwrap();
+        assert_eq!(plan.disposals.len(), 2);
+        let first = &plan.disposals[0];
+        assert_eq!(first.lot_id, Uuid::from_u128(1));
+        assert_eq!(first.quantity, dec!(100));
+        assert_eq!(first.cost_basis, dec!(1000));
+        assert_eq!(first.proceeds, dec!(1600));
+        assert_eq!(first.realized_gain, dec!(600));
+        assert_eq!(first.holding_days, 142);
+        let second = &plan.disposals[1];
+        assert_eq!(second.quantity, dec!(50));
+        assert_eq!(second.cost_basis, dec!(750));
+        assert_eq!(second.realized_gain, dec!(50));
+        assert_eq!(plan.lots[0].quantity, Decimal::ZERO);
+        assert_eq!(plan.lots[1].quantity, dec!(50));
+        assert_eq!(plan.lots[1].cost_basis, dec!(750));
+    }
+
+    #[test]
+    fn test_lifo_takes_latest_lot() {
+        let plan = plan_disposals(
+            CostBasisMethod::Lifo,
+            &lots(),
+            dec!(50),
+            dec!(800),
+            date(2025, 6, 1),
+        )
+        .unwrap();
+        assert_eq!(plan.disposals.len(), 1);
+        assert_eq!(plan.disposals[0].lot_id, Uuid::from_u128(2));
+        assert_eq!(plan.disposals[0].cost_basis, dec!(750));
+        assert_eq!(plan.disposals[0].realized_gain, dec!(50));
+        assert_eq!(plan.lots[0].quantity, dec!(100));
+    }
+
+    #[test]
+    fn test_average_cost_rebalances_lots() {
+        let plan = plan_disposals(
+            CostBasisMethod::Average,
+            &lots(),
+            dec!(50),
+            dec!(800),
+            date(2025, 6, 1),
+        )
+        .unwrap();
+        // 平均成本 12.5
+        assert_eq!(plan.disposals[0].cost_basis, dec!(625));
+        assert_eq!(plan.disposals[0].realized_gain, dec!(175));
+        let remaining: Decimal = plan.lots.iter().map(|l| l.cost_basis).sum();
+        assert_eq!(remaining, dec!(1875));
+        assert_eq!(plan.lots[1].cost_basis, dec!(1250));
+    }
+
+    #[test]
+    fn test_proceeds_allocation_sums_exactly() {
+        let plan = plan_disposals(
+            CostBasisMethod::Fifo,
+            &lots(),
+            dec!(150),
+            dec!(1000.01),
+            date(2025, 6, 1),
+        )
+        .unwrap();
+        let total: Decimal = plan.disposals.iter().map(|d| d.proceeds).sum();
+        assert_eq!(total, dec!(1000.01));
+    }
+
+    #[test]
+    fn test_insufficient_holdings() {
+        let err = plan_disposals(
+            CostBasisMethod::Fifo,
+            &lots(),
+            dec!(201),
+            dec!(1),
+            date(2025, 6, 1),
+        );
+        assert!(matches!(err, Err(ServiceError::BusinessRuleViolation(_))));
+    }
+
+    #[test]
+    fn test_merger_with_cash() {
+        // 每股换 0.5 股新证券并收到 2 元现金，80% 成本划入新证券
+        let plan = plan_corporate_action(
+            CorporateActionType::Merger,
+            &lots(),
+            dec!(2),
+            dec!(1),
+            Some(dec!(0.8)),
+            dec!(2),
+        )
+        .unwrap();
+        assert_eq!(plan.len(), 2);
+        let first = &plan[0];
+        assert_eq!(first.remaining_quantity, Decimal::ZERO);
+        assert_eq!(first.new_quantity, dec!(50));
+        assert_eq!(first.new_cost, dec!(800));
+        assert_eq!(first.cash, dec!(200));
+        assert_eq!(first.cash_cost, dec!(200));
+        assert_eq!(first.acquired_date, date(2025, 1, 10));
+
+        let missing_allocation = plan_corporate_action(
+            CorporateActionType::Merger,
+            &lots(),
+            dec!(1),
+            dec!(1),
+            None,
+            dec!(2),
+        );
+        assert!(missing_allocation.is_err());
+    }
+
+    #[test]
+    fn test_spinoff_moves_cost() {
+        let plan = plan_corporate_action(
+            CorporateActionType::Spinoff,
+            &lots(),
+            dec!(3),
+            dec!(1),
+            Some(dec!(0.25)),
+            Decimal::ZERO,
+        )
+        .unwrap();
+        let second = &plan[1];
+        assert_eq!(second.remaining_quantity, dec!(100));
+        assert_eq!(second.remaining_cost, dec!(1125));
+        assert_eq!(second.new_cost, dec!(375));
+        assert_eq!(second.new_quantity, dec!(33.33333333));
+        let total: Decimal = plan.iter().map(|r| r.remaining_cost + r.new_cost).sum();
+        assert_eq!(total, dec!(2500));
+
+        assert!(plan_corporate_action(
+            CorporateActionType::Spinoff,
+            &lots(),
+            dec!(1),
+            dec!(1),
+            Some(Decimal::ONE),
+            Decimal::ZERO,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_split_quantity() {
+        assert_eq!(split_quantity(dec!(100), dec!(1), dec!(2)), dec!(200));
+        assert_eq!(split_quantity(dec!(100), dec!(10), dec!(1)), dec!(10));
+        assert_eq!(split_quantity(dec!(7), dec!(2), dec!(3)), dec!(10.5));
+    }
+}
@@ -7,7 +7,9 @@ use crate::models::{
     permission::Permission,
 };
 
-use super::{ServiceContext, ServiceError};
+use super::email::{EmailOutbox, EmailTemplate};
+use super::{ServiceContext, ServiceError, WebhookService};
+use crate::config::EmailConfig;
 
 pub struct InvitationService {
     pool: PgPool,
@@ -22,6 +24,7 @@ impl InvitationService {
         &self,
         ctx: &ServiceContext,
         request: CreateInvitationRequest,
+        email: &EmailConfig,
     ) -> Result<InvitationResponse, ServiceError> {
         ctx.require_permission(Permission::InviteMembers)?;
 
@@ -70,12 +73,32 @@ impl InvitationService {
         .fetch_one(&self.pool)
         .await?;
 
-        // Get family name for response
-        let family_name =
-            sqlx::query_scalar::<_, String>("SELECT name FROM families WHERE id = $1")
-                .bind(ctx.family_id)
-                .fetch_one(&self.pool)
-                .await?;
+        // Get family name (and locale for the invitation email)
+        let (family_name, family_locale) = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT name, locale FROM families WHERE id = $1",
+        )
+        .bind(ctx.family_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        // 邀请邮件入队；失败不影响邀请本身（邀请码仍可通过其他渠道分享）
+        let template = EmailTemplate::Invitation {
+            inviter_name: ctx
+                .user_name
+                .clone()
+                .unwrap_or_else(|| ctx.user_email.clone()),
+            family_name: family_name.clone(),
+            invite_code: invitation.invite_code.clone(),
+            expires_at: invitation.expires_at,
+        };
+        let outbox = EmailOutbox::new(self.pool.clone(), email);
+        let locale = outbox.locale_or_default(family_locale.as_deref());
+        if let Err(e) = outbox
+            .enqueue(&invitation.invitee_email, &template, locale)
+            .await
+        {
+            tracing::warn!(invitation_id = %invitation.id, error = ?e, "failed to enqueue invitation email");
+        }
 
         Ok(InvitationResponse {
             id: invitation.id,
@@ -213,6 +236,10 @@ impl InvitationService {
 
         tx.commit().await?;
 
+        WebhookService::new(self.pool.clone())
+            .member_joined(invitation.family_id, user_id)
+            .await;
+
         Ok(invitation.family_id)
     }
 
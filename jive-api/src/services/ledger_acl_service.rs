@@ -0,0 +1,802 @@
+//! 账本级权限与只读分享
+//!
+//! - ledger_acl 中的记录覆盖成员的家庭角色（可提升也可降为 none）；家庭 owner 不受 ACL 限制
+//! - 没有 ACL 记录时沿用家庭权限（包含成员的自定义权限）
+//! - 分享链接只保存令牌的 SHA-256 哈希，可设置过期时间并随时撤销，持有者只能查看报表
+
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rand::Rng;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool, Row};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{AuditService, AuthService, CurrencyService, ServiceContext, ServiceError};
+use crate::models::audit::{AuditAction, CreateAuditLogRequest};
+use crate::models::permission::{LedgerRole, MemberRole, Permission};
+
+/// 分享链接最长有效期（天）
+pub const MAX_SHARE_LINK_DAYS: i64 = 90;
+/// 默认有效期（天）
+pub const DEFAULT_SHARE_LINK_DAYS: i64 = 7;
+
+/// 归属于某个账本的资源，用于按 ID 反查账本
+#[derive(Debug, Clone, Copy)]
+pub enum LedgerResource {
+    Account,
+    Transaction,
+    Category,
+}
+
+impl LedgerResource {
+    fn lookup_sql(&self) -> &'static str {
+        match self {
+            LedgerResource::Account => "SELECT ledger_id FROM accounts WHERE id = $1",
+            LedgerResource::Transaction => "SELECT ledger_id FROM transactions WHERE id = $1",
+            LedgerResource::Category => "SELECT ledger_id FROM categories WHERE id = $1",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            LedgerResource::Account => "account",
+            LedgerResource::Transaction => "transaction",
+            LedgerResource::Category => "category",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct LedgerAclEntry {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub family_role: String,
+    pub role: String,
+    pub granted_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ShareLink {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub name: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+    pub access_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 新建分享链接的返回：明文令牌只在创建时出现一次
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CreatedShareLink {
+    #[serde(flatten)]
+    pub link: ShareLink,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct ShareReportQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SharedCategoryTotal {
+    pub category_name: String,
+    pub transaction_type: String,
+    pub amount: Decimal,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SharedMonthTotal {
+    pub month: String,
+    pub income: Decimal,
+    pub expense: Decimal,
+    pub net: Decimal,
+}
+
+/// 分享链接可见的报表（不含交易明细、账户号等敏感信息）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SharedLedgerReport {
+    pub ledger_name: String,
+    /// 金额单位：家庭本位币
+    pub currency: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub total_income: Decimal,
+    pub total_expense: Decimal,
+    pub net: Decimal,
+    pub transaction_count: i64,
+    /// 尚未折算为本位币的交易数；这些交易不计入任何金额合计
+    pub unvalued_count: i64,
+    pub by_category: Vec<SharedCategoryTotal>,
+    pub by_month: Vec<SharedMonthTotal>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct LedgerAclService {
+    pool: PgPool,
+}
+
+impl LedgerAclService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 在给定 ACL 覆盖下成员是否拥有某项权限
+    pub fn allows(ctx: &ServiceContext, acl: Option<LedgerRole>, permission: Permission) -> bool {
+        if ctx.role == MemberRole::Owner {
+            return true;
+        }
+        match acl {
+            Some(role) if LedgerRole::is_ledger_scoped(permission) => role.allows(permission),
+            _ => ctx.can_perform(permission),
+        }
+    }
+
+    /// 成员在账本上的有效角色（用于 ACL / 分享链接管理）
+    pub fn effective_role(ctx: &ServiceContext, acl: Option<LedgerRole>) -> LedgerRole {
+        if ctx.role == MemberRole::Owner {
+            return LedgerRole::Admin;
+        }
+        acl.unwrap_or_else(|| LedgerRole::from_member_role(ctx.role))
+    }
+
+    /// 校验账本属于当前家庭且成员拥有该权限
+    pub async fn authorize(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+        permission: Permission,
+    ) -> Result<(), ServiceError> {
+        let acl = self.ledger_acl_for(ctx, ledger_id).await?;
+        if Self::allows(ctx, acl, permission) {
+            Ok(())
+        } else {
+            Err(ServiceError::PermissionDenied)
+        }
+    }
+
+    /// 以账本所属家庭构建成员上下文（不依赖令牌中的当前家庭）
+    pub async fn context_for_ledger(
+        &self,
+        user_id: Uuid,
+        ledger_id: Uuid,
+    ) -> Result<ServiceContext, ServiceError> {
+        let family_id: Uuid = sqlx::query_scalar("SELECT family_id FROM ledgers WHERE id = $1")
+            .bind(ledger_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("ledger", ledger_id))?;
+
+        AuthService::new(self.pool.clone())
+            .validate_family_access(user_id, family_id)
+            .await
+            .map_err(|_| ServiceError::PermissionDenied)
+    }
+
+    /// 构建上下文并校验权限
+    pub async fn authorize_user(
+        &self,
+        user_id: Uuid,
+        ledger_id: Uuid,
+        permission: Permission,
+    ) -> Result<ServiceContext, ServiceError> {
+        let ctx = self.context_for_ledger(user_id, ledger_id).await?;
+        self.authorize(&ctx, ledger_id, permission).await?;
+        Ok(ctx)
+    }
+
+    /// 按资源 ID 反查账本并校验权限，返回账本 ID
+    pub async fn authorize_resource(
+        &self,
+        ctx: &ServiceContext,
+        resource: LedgerResource,
+        id: Uuid,
+        permission: Permission,
+    ) -> Result<Uuid, ServiceError> {
+        let ledger_id = self.ledger_of(resource, id).await?;
+        self.authorize(ctx, ledger_id, permission).await?;
+        Ok(ledger_id)
+    }
+
+    /// 同 authorize_user，按资源 ID 反查账本
+    pub async fn authorize_user_resource(
+        &self,
+        user_id: Uuid,
+        resource: LedgerResource,
+        id: Uuid,
+        permission: Permission,
+    ) -> Result<ServiceContext, ServiceError> {
+        let ledger_id = self.ledger_of(resource, id).await?;
+        self.authorize_user(user_id, ledger_id, permission).await
+    }
+
+    pub async fn ledger_of(
+        &self,
+        resource: LedgerResource,
+        id: Uuid,
+    ) -> Result<Uuid, ServiceError> {
+        sqlx::query_scalar(resource.lookup_sql())
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found(resource.name(), id))
+    }
+
+    /// 当前家庭中成员拥有该权限的账本
+    pub async fn authorized_ledgers(
+        &self,
+        ctx: &ServiceContext,
+        permission: Permission,
+    ) -> Result<Vec<Uuid>, ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT l.id, a.role
+            FROM ledgers l
+            LEFT JOIN ledger_acl a ON a.ledger_id = l.id AND a.user_id = $2
+            WHERE l.family_id = $1
+            "#,
+        )
+        .bind(ctx.family_id)
+        .bind(ctx.user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut ledgers = Vec::with_capacity(rows.len());
+        for row in rows {
+            let role: Option<String> = row.try_get("role")?;
+            let acl = role.as_deref().and_then(LedgerRole::from_str_name);
+            if Self::allows(ctx, acl, permission) {
+                ledgers.push(row.try_get("id")?);
+            }
+        }
+        Ok(ledgers)
+    }
+
+    /// 账本必须属于上下文所在家庭；返回成员在该账本上的 ACL 覆盖
+    async fn ledger_acl_for(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+    ) -> Result<Option<LedgerRole>, ServiceError> {
+        let row = sqlx::query(
+            r#"
+            SELECT l.family_id, a.role
+            FROM ledgers l
+            LEFT JOIN ledger_acl a ON a.ledger_id = l.id AND a.user_id = $2
+            WHERE l.id = $1
+            "#,
+        )
+        .bind(ledger_id)
+        .bind(ctx.user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("ledger", ledger_id))?;
+
+        let family_id: Uuid = row.try_get("family_id")?;
+        if family_id != ctx.family_id {
+            return Err(ServiceError::PermissionDenied);
+        }
+        let role: Option<String> = row.try_get("role")?;
+        Ok(role.as_deref().and_then(LedgerRole::from_str_name))
+    }
+
+    /// 管理 ACL / 分享链接需要账本管理员
+    async fn require_ledger_admin(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+    ) -> Result<(), ServiceError> {
+        let acl = self.ledger_acl_for(ctx, ledger_id).await?;
+        if Self::effective_role(ctx, acl) == LedgerRole::Admin {
+            Ok(())
+        } else {
+            Err(ServiceError::PermissionDenied)
+        }
+    }
+
+    // ---------------- ACL 管理 ----------------
+
+    /// 列出家庭成员及其在该账本上的角色（未覆盖时 role 为 inherit）
+    pub async fn list_acl(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+    ) -> Result<Vec<LedgerAclEntry>, ServiceError> {
+        self.require_ledger_admin(ctx, ledger_id).await?;
+
+        let entries = sqlx::query_as::<_, LedgerAclEntry>(
+            r#"
+            SELECT u.id AS user_id, u.email, u.name,
+                   fm.role AS family_role,
+                   COALESCE(a.role, 'inherit') AS role,
+                   a.granted_by,
+                   COALESCE(a.updated_at, fm.joined_at) AS updated_at
+            FROM family_members fm
+            JOIN users u ON u.id = fm.user_id
+            LEFT JOIN ledger_acl a ON a.ledger_id = $2 AND a.user_id = fm.user_id
+            WHERE fm.family_id = $1
+            ORDER BY fm.joined_at
+            "#,
+        )
+        .bind(ctx.family_id)
+        .bind(ledger_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entries)
+    }
+
+    /// 设置成员在账本上的角色
+    pub async fn set_acl(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+        user_id: Uuid,
+        role: LedgerRole,
+    ) -> Result<(), ServiceError> {
+        self.require_ledger_admin(ctx, ledger_id).await?;
+        let target_role = self.family_role_of(ctx.family_id, user_id).await?;
+        if target_role == MemberRole::Owner {
+            return Err(ServiceError::CannotChangeOwnerRole);
+        }
+        if user_id == ctx.user_id && role < LedgerRole::Admin {
+            return Err(ServiceError::BusinessRuleViolation(
+                "不能降低自己在账本上的权限".to_string(),
+            ));
+        }
+
+        let old_role: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT role FROM ledger_acl WHERE ledger_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(ledger_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ledger_acl (ledger_id, user_id, role, granted_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (ledger_id, user_id)
+            DO UPDATE SET role = EXCLUDED.role, granted_by = EXCLUDED.granted_by, updated_at = NOW()
+            "#,
+        )
+        .bind(ledger_id)
+        .bind(user_id)
+        .bind(role.to_string())
+        .bind(ctx.user_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.audit(
+            ctx,
+            AuditAction::PermissionChanged,
+            "ledger_acl",
+            ledger_id,
+            old_role.map(|r| serde_json::json!({ "user_id": user_id, "role": r })),
+            Some(serde_json::json!({ "user_id": user_id, "role": role })),
+        )
+        .await;
+        Ok(())
+    }
+
+    /// 删除覆盖，恢复为家庭角色
+    pub async fn remove_acl(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), ServiceError> {
+        self.require_ledger_admin(ctx, ledger_id).await?;
+
+        let removed: Option<String> = sqlx::query_scalar(
+            "DELETE FROM ledger_acl WHERE ledger_id = $1 AND user_id = $2 RETURNING role",
+        )
+        .bind(ledger_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(old_role) = removed else {
+            return Err(ServiceError::not_found("ledger_acl", user_id));
+        };
+
+        self.audit(
+            ctx,
+            AuditAction::PermissionChanged,
+            "ledger_acl",
+            ledger_id,
+            Some(serde_json::json!({ "user_id": user_id, "role": old_role })),
+            Some(serde_json::json!({ "user_id": user_id, "role": "inherit" })),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn family_role_of(
+        &self,
+        family_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemberRole, ServiceError> {
+        let role: String = sqlx::query_scalar(
+            "SELECT role FROM family_members WHERE family_id = $1 AND user_id = $2",
+        )
+        .bind(family_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("member", user_id))?;
+        MemberRole::from_str_name(&role)
+            .ok_or_else(|| ServiceError::ValidationError(format!("Invalid role: {}", role)))
+    }
+
+    // ---------------- 分享链接 ----------------
+
+    /// 生成 URL 安全的随机令牌
+    pub fn generate_token() -> String {
+        let bytes: [u8; 32] = rand::thread_rng().gen();
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    pub fn hash_token(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+    }
+
+    pub async fn create_share_link(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+        name: Option<String>,
+        expires_in_days: Option<i64>,
+    ) -> Result<CreatedShareLink, ServiceError> {
+        self.require_ledger_admin(ctx, ledger_id).await?;
+
+        let days = expires_in_days.unwrap_or(DEFAULT_SHARE_LINK_DAYS);
+        if !(1..=MAX_SHARE_LINK_DAYS).contains(&days) {
+            return Err(ServiceError::ValidationError(format!(
+                "有效期需在 1-{} 天之间",
+                MAX_SHARE_LINK_DAYS
+            )));
+        }
+
+        let token = Self::generate_token();
+        let link = sqlx::query_as::<_, ShareLink>(
+            r#"
+            INSERT INTO ledger_share_links (id, ledger_id, token_hash, name, created_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, ledger_id, name, created_by, expires_at, revoked_at,
+                      last_accessed_at, access_count, created_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(ledger_id)
+        .bind(Self::hash_token(&token))
+        .bind(name)
+        .bind(ctx.user_id)
+        .bind(Utc::now() + Duration::days(days))
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.audit(
+            ctx,
+            AuditAction::Create,
+            "ledger_share_link",
+            link.id,
+            None,
+            Some(serde_json::json!({ "ledger_id": ledger_id, "expires_at": link.expires_at })),
+        )
+        .await;
+        Ok(CreatedShareLink { link, token })
+    }
+
+    pub async fn list_share_links(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+    ) -> Result<Vec<ShareLink>, ServiceError> {
+        self.require_ledger_admin(ctx, ledger_id).await?;
+
+        let links = sqlx::query_as::<_, ShareLink>(
+            r#"
+            SELECT id, ledger_id, name, created_by, expires_at, revoked_at,
+                   last_accessed_at, access_count, created_at
+            FROM ledger_share_links
+            WHERE ledger_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(ledger_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(links)
+    }
+
+    pub async fn revoke_share_link(
+        &self,
+        ctx: &ServiceContext,
+        ledger_id: Uuid,
+        link_id: Uuid,
+    ) -> Result<(), ServiceError> {
+        self.require_ledger_admin(ctx, ledger_id).await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE ledger_share_links SET revoked_at = NOW()
+            WHERE id = $1 AND ledger_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(link_id)
+        .bind(ledger_id)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("ledger_share_link", link_id));
+        }
+
+        self.audit(
+            ctx,
+            AuditAction::Delete,
+            "ledger_share_link",
+            link_id,
+            Some(serde_json::json!({ "ledger_id": ledger_id })),
+            None,
+        )
+        .await;
+        Ok(())
+    }
+
+    /// 校验令牌（未过期、未撤销）并记录访问，返回链接
+    pub async fn resolve_share_token(&self, token: &str) -> Result<ShareLink, ServiceError> {
+        sqlx::query_as::<_, ShareLink>(
+            r#"
+            UPDATE ledger_share_links
+            SET last_accessed_at = NOW(), access_count = access_count + 1
+            WHERE token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            RETURNING id, ledger_id, name, created_by, expires_at, revoked_at,
+                      last_accessed_at, access_count, created_at
+            "#,
+        )
+        .bind(Self::hash_token(token))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::AuthenticationError("分享链接无效或已过期".to_string()))
+    }
+
+    /// 通过分享令牌读取账本报表
+    pub async fn shared_report(
+        &self,
+        token: &str,
+        query: &ShareReportQuery,
+    ) -> Result<SharedLedgerReport, ServiceError> {
+        let link = self.resolve_share_token(token).await?;
+
+        let ledger = sqlx::query("SELECT name, currency, family_id FROM ledgers WHERE id = $1")
+            .bind(link.ledger_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("ledger", link.ledger_id))?;
+
+        let summary = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) AS transaction_count,
+                COUNT(*) FILTER (WHERE base_amount IS NULL) AS unvalued_count,
+                COALESCE(SUM(CASE WHEN transaction_type = 'income' THEN base_amount ELSE 0 END), 0) AS income,
+                COALESCE(SUM(CASE WHEN transaction_type = 'expense' THEN base_amount ELSE 0 END), 0) AS expense
+            FROM transactions
+            WHERE ledger_id = $1 AND deleted_at IS NULL
+              AND ($2::date IS NULL OR transaction_date >= $2)
+              AND ($3::date IS NULL OR transaction_date <= $3)
+            "#,
+        )
+        .bind(link.ledger_id)
+        .bind(query.start_date)
+        .bind(query.end_date)
+        .fetch_one(&self.pool)
+        .await?;
+        let total_income: Decimal = summary.try_get("income")?;
+        let total_expense: Decimal = summary.try_get("expense")?;
+
+        let by_category = sqlx::query(
+            r#"
+            SELECT COALESCE(c.name, '未分类') AS category_name, t.transaction_type,
+                   COALESCE(SUM(t.base_amount), 0) AS amount, COUNT(*) AS count
+            FROM transactions t
+            LEFT JOIN categories c ON c.id = t.category_id
+            WHERE t.ledger_id = $1 AND t.deleted_at IS NULL
+              AND t.transaction_type IN ('income', 'expense')
+              AND ($2::date IS NULL OR t.transaction_date >= $2)
+              AND ($3::date IS NULL OR t.transaction_date <= $3)
+            GROUP BY 1, 2
+            ORDER BY amount DESC
+            "#,
+        )
+        .bind(link.ledger_id)
+        .bind(query.start_date)
+        .bind(query.end_date)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| -> Result<SharedCategoryTotal, sqlx::Error> {
+            Ok(SharedCategoryTotal {
+                category_name: row.try_get("category_name")?,
+                transaction_type: row.try_get("transaction_type")?,
+                amount: row.try_get("amount")?,
+                count: row.try_get("count")?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let by_month = sqlx::query(
+            r#"
+            SELECT TO_CHAR(transaction_date, 'YYYY-MM') AS month,
+                   COALESCE(SUM(CASE WHEN transaction_type = 'income' THEN base_amount ELSE 0 END), 0) AS income,
+                   COALESCE(SUM(CASE WHEN transaction_type = 'expense' THEN base_amount ELSE 0 END), 0) AS expense
+            FROM transactions
+            WHERE ledger_id = $1 AND deleted_at IS NULL
+              AND transaction_date >= COALESCE($2::date, CURRENT_DATE - INTERVAL '12 months')
+              AND ($3::date IS NULL OR transaction_date <= $3)
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(link.ledger_id)
+        .bind(query.start_date)
+        .bind(query.end_date)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| -> Result<SharedMonthTotal, sqlx::Error> {
+            let income: Decimal = row.try_get("income")?;
+            let expense: Decimal = row.try_get("expense")?;
+            Ok(SharedMonthTotal {
+                month: row.try_get("month")?,
+                income,
+                expense,
+                net: income - expense,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+        // 金额为家庭本位币；不属于家庭的账本没有本位币金额，退回账本货币
+        let currency = match ledger.try_get::<Option<Uuid>, _>("family_id")? {
+            Some(family_id) => {
+                CurrencyService::new(self.pool.clone())
+                    .family_base_currency(family_id)
+                    .await?
+            }
+            None => ledger
+                .try_get::<Option<String>, _>("currency")?
+                .ok_or_else(|| ServiceError::validation("账本未设置货币"))?,
+        };
+
+        Ok(SharedLedgerReport {
+            ledger_name: ledger.try_get("name")?,
+            currency,
+            start_date: query.start_date,
+            end_date: query.end_date,
+            total_income,
+            total_expense,
+            net: total_income - total_expense,
+            transaction_count: summary.try_get("transaction_count")?,
+            unvalued_count: summary.try_get("unvalued_count")?,
+            by_category,
+            by_month,
+            expires_at: link.expires_at,
+        })
+    }
+
+    /// 权限变更审计；失败只记录日志
+    async fn audit(
+        &self,
+        ctx: &ServiceContext,
+        action: AuditAction,
+        entity_type: &str,
+        entity_id: Uuid,
+        old_values: Option<serde_json::Value>,
+        new_values: Option<serde_json::Value>,
+    ) {
+        let request = CreateAuditLogRequest {
+            action,
+            entity_type: entity_type.to_string(),
+            entity_id: Some(entity_id),
+            old_values,
+            new_values,
+        };
+        if let Err(e) = AuditService::new(self.pool.clone())
+            .log_action(ctx.family_id, ctx.user_id, request, None, None)
+            .await
+        {
+            tracing::warn!(entity_type, entity_id = %entity_id, error = ?e, "failed to write ledger acl audit log");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(role: MemberRole) -> ServiceContext {
+        ServiceContext::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            role,
+            role.default_permissions(),
+            "member@example.com".to_string(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_acl_overrides_family_role() {
+        let viewer = ctx(MemberRole::Viewer);
+        assert!(!LedgerAclService::allows(
+            &viewer,
+            None,
+            Permission::CreateTransactions
+        ));
+        assert!(LedgerAclService::allows(
+            &viewer,
+            Some(LedgerRole::Editor),
+            Permission::CreateTransactions
+        ));
+
+        let member = ctx(MemberRole::Member);
+        assert!(LedgerAclService::allows(
+            &member,
+            None,
+            Permission::ViewTransactions
+        ));
+        assert!(!LedgerAclService::allows(
+            &member,
+            Some(LedgerRole::None),
+            Permission::ViewTransactions
+        ));
+        // ACL 不影响家庭级权限
+        assert!(LedgerAclService::allows(
+            &member,
+            Some(LedgerRole::None),
+            Permission::ViewMembers
+        ));
+    }
+
+    #[test]
+    fn test_owner_ignores_acl() {
+        let owner = ctx(MemberRole::Owner);
+        assert!(LedgerAclService::allows(
+            &owner,
+            Some(LedgerRole::None),
+            Permission::DeleteAccounts
+        ));
+        assert_eq!(
+            LedgerAclService::effective_role(&owner, Some(LedgerRole::Viewer)),
+            LedgerRole::Admin
+        );
+        assert_eq!(
+            LedgerAclService::effective_role(&ctx(MemberRole::Viewer), None),
+            LedgerRole::Viewer
+        );
+    }
+
+    #[test]
+    fn test_share_token_hashing() {
+        let token = LedgerAclService::generate_token();
+        assert_eq!(token.len(), 43);
+        assert_ne!(token, LedgerAclService::generate_token());
+        assert_eq!(
+            LedgerAclService::hash_token(&token),
+            LedgerAclService::hash_token(&format!(" {} ", token))
+        );
+        assert_eq!(LedgerAclService::hash_token(&token).len(), 64);
+    }
+}
@@ -0,0 +1,231 @@
+//! 大模型后端
+//!
+//! - `LlmProvider`：对话补全抽象（消息 + 可选工具定义 → 回复或工具调用）
+//! - `OpenAiCompatibleProvider`：OpenAI 兼容的 `/chat/completions` 接口，Ollama、llama.cpp
+//!   server、vLLM 等本地部署均可直接使用，家庭数据无需发送给第三方
+//! - `StubProvider`：确定性的离线实现，按关键字分类、按问题选择工具，用于开发与测试
+//!
+//! 模型名称由 `LlmBackend` 持有（分类与对话可使用不同模型），提供方本身不绑定模型。
+
+pub mod openai;
+pub mod stub;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::config::LlmConfig;
+use crate::services::ServiceError;
+
+pub use openai::OpenAiCompatibleProvider;
+pub use stub::StubProvider;
+
+/// 大模型调用错误
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum LlmError {
+    #[error("LLM request failed: {0}")]
+    Http(String),
+    #[error("LLM API error ({status}): {message}")]
+    Api { status: u16, message: String },
+    #[error("Invalid LLM response: {0}")]
+    InvalidResponse(String),
+}
+
+impl From<LlmError> for ServiceError {
+    fn from(e: LlmError) -> Self {
+        ServiceError::ExternalApi {
+            message: e.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+impl ChatRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+            ChatRole::Tool => "tool",
+        }
+    }
+}
+
+/// 模型发起的工具调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// JSON 编码的参数
+    pub arguments: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: Option<String>,
+    /// 仅 assistant 消息
+    pub tool_calls: Vec<ToolCall>,
+    /// 仅 tool 消息：对应的工具调用 id
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    fn text(role: ChatRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: Some(content.into()),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::text(ChatRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::text(ChatRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::text(ChatRole::Assistant, content)
+    }
+
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::text(ChatRole::Tool, content)
+        }
+    }
+}
+
+/// 提供给模型的工具（函数）定义
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// 参数的 JSON Schema
+    pub parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub tools: Vec<ToolDefinition>,
+    pub temperature: f32,
+    /// 要求模型返回 JSON 对象
+    pub json_response: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    /// assistant 消息（文本回复或工具调用）
+    pub message: ChatMessage,
+    /// 实际响应的模型
+    pub model: String,
+}
+
+/// 对话补全提供方
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// 提供方名称（记录在分类来源中）
+    fn name(&self) -> &str;
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<Completion, LlmError>;
+}
+
+/// 已配置的提供方与模型
+pub struct LlmBackend {
+    pub provider: Arc<dyn LlmProvider>,
+    pub categorize_model: String,
+    pub chat_model: String,
+}
+
+impl LlmBackend {
+    pub fn new(
+        provider: Arc<dyn LlmProvider>,
+        categorize_model: impl Into<String>,
+        chat_model: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider,
+            categorize_model: categorize_model.into(),
+            chat_model: chat_model.into(),
+        }
+    }
+
+    /// 按配置构建；未启用时返回 None
+    pub fn from_config(config: &LlmConfig) -> Option<Self> {
+        if !config.is_enabled() {
+            return None;
+        }
+        let provider: Arc<dyn LlmProvider> = match config.provider.as_str() {
+            "stub" => Arc::new(StubProvider::new()),
+            _ => Arc::new(OpenAiCompatibleProvider::new(
+                config.base_url.clone(),
+                config.api_key.clone(),
+                config.request_timeout_secs,
+            )),
+        };
+        Some(Self::new(
+            provider,
+            config.categorize_model.clone(),
+            config.chat_model.clone(),
+        ))
+    }
+
+    pub fn provider_name(&self) -> &str {
+        self.provider.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: &str, base_url: &str, api_key: Option<&str>) -> LlmConfig {
+        LlmConfig {
+            provider: provider.to_string(),
+            base_url: base_url.to_string(),
+            api_key: api_key.map(str::to_string),
+            categorize_model: "qwen2.5:7b".to_string(),
+            chat_model: "llama3.1:8b".to_string(),
+            request_timeout_secs: 30,
+            categorize_batch_size: 50,
+            min_confidence: 0.6,
+            max_tool_rounds: 5,
+        }
+    }
+
+    #[test]
+    fn test_backend_from_config() {
+        // 官方接口未配置密钥时不启用
+        assert!(
+            LlmBackend::from_config(&config("openai", "https://api.openai.com/v1", None)).is_none()
+        );
+        assert!(
+            LlmBackend::from_config(&config("disabled", "http://localhost:11434/v1", None))
+                .is_none()
+        );
+
+        // 本地模型无需密钥，模型名称按配置传递
+        let local =
+            LlmBackend::from_config(&config("openai", "http://localhost:11434/v1", None)).unwrap();
+        assert_eq!(local.provider_name(), "openai_compatible");
+        assert_eq!(local.categorize_model, "qwen2.5:7b");
+        assert_eq!(local.chat_model, "llama3.1:8b");
+
+        let stub = LlmBackend::from_config(&config("stub", "", None)).unwrap();
+        assert_eq!(stub.provider_name(), "stub");
+    }
+}
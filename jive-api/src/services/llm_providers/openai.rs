@@ -0,0 +1,317 @@
+//! OpenAI 兼容的对话补全接口（`POST {base_url}/chat/completions`）
+//!
+//! 同一实现覆盖 OpenAI 官方接口与 Ollama、llama.cpp server、vLLM 等本地部署：
+//! 未配置密钥时不发送 `Authorization` 头；部分本地服务把工具参数返回为 JSON 对象而不是
+//! 字符串，两种形式都能解析。
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{
+    ChatMessage, ChatRole, Completion, CompletionRequest, LlmError, LlmProvider, ToolCall,
+};
+
+pub struct OpenAiCompatibleProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    name: String,
+}
+
+impl OpenAiCompatibleProvider {
+    /// `base_url` 包含版本前缀，例如 `https://api.openai.com/v1`、`http://localhost:11434/v1`
+    pub fn new(base_url: String, api_key: Option<String>, timeout_secs: u64) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        // 来源记录中区分官方接口与自建服务
+        let name = if base_url.contains("api.openai.com") {
+            "openai"
+        } else {
+            "openai_compatible"
+        };
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(timeout_secs))
+                .build()
+                .unwrap_or_default(),
+            base_url,
+            api_key,
+            name: name.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WireMessage<'a> {
+    role: &'static str,
+    content: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<&'a str>,
+}
+
+impl<'a> From<&'a ChatMessage> for WireMessage<'a> {
+    fn from(message: &'a ChatMessage) -> Self {
+        Self {
+            role: message.role.as_str(),
+            content: message.content.as_deref(),
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(|call| {
+                    json!({
+                        "id": call.id,
+                        "type": "function",
+                        "function": { "name": call.name, "arguments": call.arguments },
+                    })
+                })
+                .collect(),
+            tool_call_id: message.tool_call_id.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionResponse {
+    model: Option<String>,
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ResponseToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseToolCall {
+    id: Option<String>,
+    function: ResponseFunction,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseFunction {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// `{"error": {"message": ...}}`（OpenAI）或 `{"error": "..."}`（Ollama）
+fn error_message(body: &Value) -> Option<String> {
+    match body.get("error")? {
+        Value::String(message) => Some(message.clone()),
+        error => error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<Completion, LlmError> {
+        let mut body = json!({
+            "model": req.model,
+            "messages": req.messages.iter().map(WireMessage::from).collect::<Vec<_>>(),
+            "temperature": req.temperature,
+        });
+        if !req.tools.is_empty() {
+            body["tools"] = req
+                .tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "type": "function",
+                        "function": {
+                            "name": tool.name,
+                            "description": tool.description,
+                            "parameters": tool.parameters,
+                        },
+                    })
+                })
+                .collect();
+        }
+        if req.json_response {
+            body["response_format"] = json!({ "type": "json_object" });
+        }
+
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| LlmError::Http(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response
+                .json::<Value>()
+                .await
+                .ok()
+                .and_then(|body| error_message(&body))
+                .unwrap_or_else(|| status.to_string());
+            return Err(LlmError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+
+        let parsed: CompletionResponse = response
+            .json()
+            .await
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        let choice = parsed
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| LlmError::InvalidResponse("no choices".to_string()))?;
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|(i, call)| ToolCall {
+                id: call.id.unwrap_or_else(|| format!("call_{}", i)),
+                name: call.function.name,
+                arguments: match call.function.arguments {
+                    Value::String(arguments) => arguments,
+                    Value::Null => "{}".to_string(),
+                    other => other.to_string(),
+                },
+            })
+            .collect();
+
+        Ok(Completion {
+            message: ChatMessage {
+                role: ChatRole::Assistant,
+                content: choice.message.content.filter(|c| !c.is_empty()),
+                tool_calls,
+                tool_call_id: None,
+            },
+            model: parsed.model.unwrap_or_else(|| req.model.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm_providers::ToolDefinition;
+    use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Seen = Arc<Mutex<Vec<(Option<String>, Value)>>>;
+
+    /// 模拟 Ollama：有工具时返回对象形式的参数，模型不存在时返回 404
+    async fn completions(
+        State(seen): State<Seen>,
+        headers: HeaderMap,
+        Json(body): Json<Value>,
+    ) -> (axum::http::StatusCode, Json<Value>) {
+        let auth = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        seen.lock().unwrap().push((auth, body.clone()));
+        if body["model"] == "missing" {
+            return (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "error": "model \"missing\" not found" })),
+            );
+        }
+        let message = if body.get("tools").is_some() {
+            json!({ "role": "assistant", "content": "", "tool_calls": [
+                { "function": { "name": "get_accounts", "arguments": { "limit": 5 } } }
+            ] })
+        } else {
+            json!({ "role": "assistant", "content": "你好" })
+        };
+        (
+            axum::http::StatusCode::OK,
+            Json(json!({ "model": body["model"], "choices": [{ "message": message }] })),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_roundtrip() {
+        let seen: Seen = Arc::default();
+        let app = Router::new()
+            .route("/v1/chat/completions", post(completions))
+            .with_state(seen.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/v1", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let local = OpenAiCompatibleProvider::new(base.clone(), None, 5);
+        assert_eq!(local.name(), "openai_compatible");
+        let mut req = CompletionRequest {
+            model: "qwen2.5:7b".to_string(),
+            messages: vec![ChatMessage::system("sys"), ChatMessage::user("有哪些账户")],
+            tools: vec![ToolDefinition {
+                name: "get_accounts".to_string(),
+                description: "list accounts".to_string(),
+                parameters: json!({ "type": "object", "properties": {} }),
+            }],
+            temperature: 0.0,
+            json_response: false,
+        };
+        let completion = local.complete(&req).await.unwrap();
+        assert_eq!(completion.model, "qwen2.5:7b");
+        assert_eq!(completion.message.content, None);
+        assert_eq!(
+            completion.message.tool_calls,
+            vec![ToolCall {
+                id: "call_0".to_string(),
+                name: "get_accounts".to_string(),
+                arguments: r#"{"limit":5}"#.to_string(),
+            }]
+        );
+
+        // 把工具调用与结果回传，并要求 JSON 输出
+        req.messages.push(completion.message);
+        req.messages
+            .push(ChatMessage::tool_result("call_0", r#"{"accounts":[]}"#));
+        req.tools.clear();
+        req.json_response = true;
+        let keyed = OpenAiCompatibleProvider::new(base.clone(), Some("sk-test".to_string()), 5);
+        let reply = keyed.complete(&req).await.unwrap();
+        assert_eq!(reply.message.content.as_deref(), Some("你好"));
+
+        {
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen[0].0, None);
+            assert_eq!(seen[0].1["tools"][0]["function"]["name"], "get_accounts");
+            assert_eq!(seen[1].0.as_deref(), Some("Bearer sk-test"));
+            let body = &seen[1].1;
+            assert_eq!(body["response_format"]["type"], "json_object");
+            assert_eq!(body["messages"][2]["tool_calls"][0]["id"], "call_0");
+            assert_eq!(body["messages"][3]["role"], "tool");
+            assert_eq!(body["messages"][3]["tool_call_id"], "call_0");
+        }
+
+        req.model = "missing".to_string();
+        match local.complete(&req).await {
+            Err(LlmError::Api { status, message }) => {
+                assert_eq!(status, 404);
+                assert!(message.contains("not found"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
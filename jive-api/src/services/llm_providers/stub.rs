@@ -0,0 +1,261 @@
+//! 确定性的离线提供方
+//!
+//! - 分类：识别 `AiService` 发送的分类请求（JSON，含 `categories` 与 `transactions`），
+//!   分类名直接出现在描述或收款方中时置信度 0.9，命中内置关键字表时 0.7，否则不分类
+//! - 对话：带工具且最后一条是用户消息时，按问题中的关键字选择工具；收到工具结果后
+//!   返回包含结果的文本回复；其余情况原样复述
+//!
+//! 所有请求都会被记录，测试可据此检查发送给模型的内容（例如脱敏结果）。
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+use super::{
+    ChatMessage, ChatRole, Completion, CompletionRequest, LlmError, LlmProvider, ToolCall,
+};
+
+/// 描述关键字 → 分类名候选
+const KEYWORDS: &[(&[&str], &[&str])] = &[
+    (
+        &[
+            "starbucks",
+            "coffee",
+            "restaurant",
+            "mcdonald",
+            "kfc",
+            "咖啡",
+            "餐",
+            "饭",
+            "外卖",
+            "美团",
+            "饿了么",
+        ],
+        &["餐饮", "food", "dining", "restaurant"],
+    ),
+    (
+        &[
+            "uber", "lyft", "taxi", "shell", "gas", "滴滴", "地铁", "公交", "加油", "打车",
+        ],
+        &["交通", "transport"],
+    ),
+    (
+        &[
+            "amazon",
+            "walmart",
+            "target",
+            "淘宝",
+            "京东",
+            "拼多多",
+            "超市",
+            "购物",
+        ],
+        &["购物", "shopping"],
+    ),
+    (
+        &["netflix", "spotify", "cinema", "电影", "游戏", "娱乐"],
+        &["娱乐", "entertainment"],
+    ),
+    (
+        &[
+            "electric", "water", "utility", "电费", "水费", "燃气", "话费",
+        ],
+        &["水电", "缴费", "utilities"],
+    ),
+    (
+        &["salary", "payroll", "工资", "薪"],
+        &["工资", "薪", "salary", "income", "收入"],
+    ),
+];
+
+#[derive(Default)]
+pub struct StubProvider {
+    requests: Mutex<Vec<CompletionRequest>>,
+}
+
+impl StubProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已收到的请求（按顺序）
+    pub fn requests(&self) -> Vec<CompletionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+/// 为一笔交易挑选分类：(分类 ref, 置信度, 理由)
+fn pick_category(transaction: &Value, categories: &[Value]) -> (Option<String>, f64, String) {
+    let text = format!(
+        "{} {}",
+        str_field(transaction, "description"),
+        str_field(transaction, "payee")
+    )
+    .to_lowercase();
+    let kind = str_field(transaction, "type");
+    let candidates: Vec<&Value> = categories
+        .iter()
+        .filter(|c| kind.is_empty() || str_field(c, "type") == kind)
+        .collect();
+
+    for category in &candidates {
+        let name = str_field(category, "name").to_lowercase();
+        if !name.is_empty() && text.contains(&name) {
+            return (
+                Some(str_field(category, "ref").to_string()),
+                0.9,
+                format!("描述包含分类名“{}”", str_field(category, "name")),
+            );
+        }
+    }
+    for (keywords, hints) in KEYWORDS {
+        let Some(keyword) = keywords.iter().find(|k| text.contains(*k)) else {
+            continue;
+        };
+        let matched = candidates.iter().find(|c| {
+            let name = str_field(c, "name").to_lowercase();
+            hints.iter().any(|hint| name.contains(hint))
+        });
+        if let Some(category) = matched {
+            return (
+                Some(str_field(category, "ref").to_string()),
+                0.7,
+                format!("关键字“{}”", keyword),
+            );
+        }
+    }
+    (None, 0.0, "没有匹配的关键字".to_string())
+}
+
+fn categorize(payload: &Value) -> Option<String> {
+    let categories = payload.get("categories")?.as_array()?;
+    let transactions = payload.get("transactions")?.as_array()?;
+    let results: Vec<Value> = transactions
+        .iter()
+        .map(|transaction| {
+            let (category, confidence, reason) = pick_category(transaction, categories);
+            json!({
+                "ref": str_field(transaction, "ref"),
+                "category": category,
+                "confidence": confidence,
+                "reason": reason,
+            })
+        })
+        .collect();
+    Some(json!({ "results": results }).to_string())
+}
+
+/// 按问题关键字选择工具
+fn pick_tool(question: &str, tools: &[String]) -> Option<(String, Value)> {
+    let question = question.to_lowercase();
+    let wanted = if ["余额", "净资产", "资产", "负债", "balance", "net worth"]
+        .iter()
+        .any(|k| question.contains(k))
+    {
+        ("get_balance_sheet", json!({}))
+    } else if ["交易", "花", "消费", "支出", "transaction", "spent"]
+        .iter()
+        .any(|k| question.contains(k))
+    {
+        ("get_transactions", json!({ "limit": 10, "days": 30 }))
+    } else if ["账户", "account"].iter().any(|k| question.contains(k)) {
+        ("get_accounts", json!({}))
+    } else {
+        return None;
+    };
+    tools
+        .iter()
+        .any(|t| t == wanted.0)
+        .then(|| (wanted.0.to_string(), wanted.1))
+}
+
+fn respond(req: &CompletionRequest) -> ChatMessage {
+    let last = req.messages.last();
+    let content = last.and_then(|m| m.content.as_deref()).unwrap_or("");
+
+    if req.json_response {
+        if let Some(reply) = serde_json::from_str::<Value>(content)
+            .ok()
+            .and_then(|payload| categorize(&payload))
+        {
+            return ChatMessage::assistant(reply);
+        }
+    }
+
+    match last.map(|m| m.role) {
+        Some(ChatRole::User) if !req.tools.is_empty() => {
+            let names: Vec<String> = req.tools.iter().map(|t| t.name.clone()).collect();
+            if let Some((name, arguments)) = pick_tool(content, &names) {
+                let round = req
+                    .messages
+                    .iter()
+                    .filter(|m| m.role == ChatRole::Assistant)
+                    .count();
+                return ChatMessage {
+                    role: ChatRole::Assistant,
+                    content: None,
+                    tool_calls: vec![ToolCall {
+                        id: format!("call_{}", round + 1),
+                        name,
+                        arguments: arguments.to_string(),
+                    }],
+                    tool_call_id: None,
+                };
+            }
+            ChatMessage::assistant(format!("收到：{}", content))
+        }
+        Some(ChatRole::Tool) => {
+            let summary: String = content.chars().take(500).collect();
+            ChatMessage::assistant(format!("查询结果：{}", summary))
+        }
+        _ => ChatMessage::assistant(format!("收到：{}", content)),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for StubProvider {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    async fn complete(&self, req: &CompletionRequest) -> Result<Completion, LlmError> {
+        self.requests.lock().unwrap().push(req.clone());
+        Ok(Completion {
+            message: respond(req),
+            model: req.model.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stub_categorizes_by_name_then_keyword() {
+        let categories = vec![
+            json!({ "ref": "c1", "name": "餐饮", "type": "expense" }),
+            json!({ "ref": "c2", "name": "Transportation", "type": "expense" }),
+            json!({ "ref": "c3", "name": "工资", "type": "income" }),
+        ];
+        let pick = |description: &str, kind: &str| {
+            pick_category(
+                &json!({ "description": description, "payee": null, "type": kind }),
+                &categories,
+            )
+        };
+
+        assert_eq!(pick("公司餐饮报销", "expense").0.as_deref(), Some("c1"));
+        assert_eq!(pick("公司餐饮报销", "expense").1, 0.9);
+        let (category, confidence, _) = pick("UBER *TRIP", "expense");
+        assert_eq!((category.as_deref(), confidence), (Some("c2"), 0.7));
+        assert_eq!(pick("十月工资", "income").0.as_deref(), Some("c3"));
+        // 类型不一致的分类不会被选中
+        assert_eq!(pick("十月工资", "expense").0, None);
+        assert_eq!(pick("ATM withdrawal", "expense").1, 0.0);
+    }
+}
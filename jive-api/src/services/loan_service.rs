@@ -0,0 +1,1096 @@
+//! 贷款：还款计划、浮动利率重定价、还款拆分与提前还款测算
+//!
+//! 贷款条款挂在子类型为 loan / mortgage 的账户上，账户余额为负数表示欠款。每期利息按
+//! 剩余本金 × 年利率 / 12 计算：
+//! - 等额本息（`equal_payment`）：每期还款额固定，利率重定价或提前还款后按剩余期数重新计算；
+//! - 等额本金（`equal_principal`）：每期归还固定本金，利息随本金递减。
+//!
+//! 录入条款时，首个还款日早于今天的期次视为已还。之后每笔还款自动拆分为本金（由还款账户
+//! 转入贷款账户）与利息（还款账户支出）两笔交易，并推进剩余本金与期数。
+
+use chrono::{Months, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::transaction_valuation_service::TransactionValuationService;
+use super::ServiceError;
+use crate::config::RateResolverConfig;
+
+/// 可设置贷款条款的账户子类型
+pub const LOAN_ACCOUNT_SUB_TYPES: &[&str] = &["loan", "mortgage"];
+
+const MAX_TERM_MONTHS: i16 = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RepaymentMethod {
+    /// 等额本息
+    EqualPayment,
+    /// 等额本金
+    EqualPrincipal,
+}
+
+impl RepaymentMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepaymentMethod::EqualPayment => "equal_payment",
+            RepaymentMethod::EqualPrincipal => "equal_principal",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "equal_payment" => Some(RepaymentMethod::EqualPayment),
+            "equal_principal" => Some(RepaymentMethod::EqualPrincipal),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrepaymentStrategy {
+    /// 月供不变，缩短期限
+    ShortenTerm,
+    /// 期限不变，减少月供
+    ReducePayment,
+}
+
+impl PrepaymentStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrepaymentStrategy::ShortenTerm => "shorten_term",
+            PrepaymentStrategy::ReducePayment => "reduce_payment",
+        }
+    }
+}
+
+/// 贷款在某一时点的状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoanState {
+    pub balance: Decimal,
+    pub next_period: i16,
+    pub remaining_periods: i16,
+    /// 等额本息为每期还款额，等额本金为每期本金
+    pub installment: Decimal,
+    pub rate: Decimal,
+}
+
+/// 还款计划中的一期
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ScheduleRow {
+    pub period_number: i16,
+    pub due_date: NaiveDate,
+    pub annual_rate: Decimal,
+    pub payment: Decimal,
+    pub principal: Decimal,
+    pub interest: Decimal,
+    pub balance_after: Decimal,
+}
+
+/// 还款计划汇总
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct LoanProjection {
+    pub payoff_date: Option<NaiveDate>,
+    pub remaining_periods: usize,
+    pub next_payment: Option<Decimal>,
+    pub total_interest: Decimal,
+    pub total_payment: Decimal,
+}
+
+/// 一笔模拟的提前还款
+#[derive(Debug, Clone, PartialEq, Deserialize, ToSchema)]
+pub struct SimulatedPrepayment {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub strategy: PrepaymentStrategy,
+}
+
+fn monthly_interest(balance: Decimal, annual_rate: Decimal) -> Decimal {
+    (balance * annual_rate / Decimal::from(12)).round_dp(2)
+}
+
+/// 等额本息每期还款额
+pub fn annuity_payment(balance: Decimal, annual_rate: Decimal, periods: i16) -> Decimal {
+    if periods <= 0 || balance <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let n = Decimal::from(periods);
+    let r = annual_rate / Decimal::from(12);
+    if r.is_zero() {
+        return (balance / n).round_dp(2);
+    }
+    let mut growth = Decimal::ONE;
+    for _ in 0..periods {
+        growth *= Decimal::ONE + r;
+    }
+    (balance * r * growth / (growth - Decimal::ONE)).round_dp(2)
+}
+
+/// 按还款方式计算每期还款额（等额本息）或每期本金（等额本金）
+pub fn installment_for(
+    method: RepaymentMethod,
+    balance: Decimal,
+    annual_rate: Decimal,
+    periods: i16,
+) -> Decimal {
+    match method {
+        RepaymentMethod::EqualPayment => annuity_payment(balance, annual_rate, periods),
+        RepaymentMethod::EqualPrincipal if periods > 0 => {
+            (balance / Decimal::from(periods)).round_dp(2)
+        }
+        RepaymentMethod::EqualPrincipal => Decimal::ZERO,
+    }
+}
+
+/// 第 `period` 期还款日：首个还款日之后每月同一天（小月取月末）
+pub fn due_date(first_payment_date: NaiveDate, period: i16) -> NaiveDate {
+    first_payment_date
+        .checked_add_months(Months::new(period.max(1) as u32 - 1))
+        .unwrap_or(first_payment_date)
+}
+
+/// `date` 当天适用的年利率；`changes` 按生效日期升序
+pub fn rate_on(base_rate: Decimal, changes: &[(NaiveDate, Decimal)], date: NaiveDate) -> Decimal {
+    changes
+        .iter()
+        .take_while(|(effective, _)| *effective <= date)
+        .last()
+        .map(|(_, rate)| *rate)
+        .unwrap_or(base_rate)
+}
+
+/// 计算下一期，返回该期明细与还款后的状态
+///
+/// 等额本息在利率变化时按剩余本金与期数重新计算月供；最后一期结清全部剩余本金。
+pub fn next_row(
+    method: RepaymentMethod,
+    state: &LoanState,
+    due: NaiveDate,
+    annual_rate: Decimal,
+) -> (ScheduleRow, LoanState) {
+    let mut installment = state.installment;
+    if method == RepaymentMethod::EqualPayment && annual_rate != state.rate {
+        installment = annuity_payment(state.balance, annual_rate, state.remaining_periods);
+    }
+    let interest = monthly_interest(state.balance, annual_rate);
+    let principal = if state.remaining_periods <= 1 {
+        state.balance
+    } else {
+        match method {
+            RepaymentMethod::EqualPayment => (installment - interest).max(Decimal::ZERO),
+            RepaymentMethod::EqualPrincipal => installment,
+        }
+        .min(state.balance)
+    };
+    let balance = state.balance - principal;
+    let row = ScheduleRow {
+        period_number: state.next_period,
+        due_date: due,
+        annual_rate,
+        payment: principal + interest,
+        principal,
+        interest,
+        balance_after: balance,
+    };
+    let next = LoanState {
+        balance,
+        next_period: state.next_period + 1,
+        remaining_periods: if balance.is_zero() {
+            0
+        } else {
+            state.remaining_periods - 1
+        },
+        installment,
+        rate: annual_rate,
+    };
+    (row, next)
+}
+
+/// 保持每期还款额（本金）不变时还清 `balance` 需要的期数
+pub fn periods_needed(
+    method: RepaymentMethod,
+    balance: Decimal,
+    annual_rate: Decimal,
+    installment: Decimal,
+) -> i16 {
+    if balance <= Decimal::ZERO {
+        return 0;
+    }
+    if installment <= Decimal::ZERO {
+        return MAX_TERM_MONTHS;
+    }
+    match method {
+        RepaymentMethod::EqualPrincipal => (balance / installment)
+            .ceil()
+            .to_i16()
+            .map_or(MAX_TERM_MONTHS, |n| n.min(MAX_TERM_MONTHS)),
+        RepaymentMethod::EqualPayment => {
+            let mut remaining = balance;
+            let mut periods = 0;
+            while remaining > Decimal::ZERO && periods < MAX_TERM_MONTHS {
+                let principal = installment - monthly_interest(remaining, annual_rate);
+                if principal <= Decimal::ZERO {
+                    return MAX_TERM_MONTHS;
+                }
+                remaining -= principal;
+                periods += 1;
+            }
+            periods
+        }
+    }
+}
+
+/// 提前归还 `amount` 本金后的状态
+pub fn apply_prepayment(
+    method: RepaymentMethod,
+    state: &LoanState,
+    amount: Decimal,
+    strategy: PrepaymentStrategy,
+) -> LoanState {
+    let balance = (state.balance - amount).max(Decimal::ZERO);
+    if balance.is_zero() {
+        return LoanState {
+            balance,
+            remaining_periods: 0,
+            ..state.clone()
+        };
+    }
+    match strategy {
+        PrepaymentStrategy::ReducePayment => LoanState {
+            balance,
+            installment: installment_for(method, balance, state.rate, state.remaining_periods),
+            ..state.clone()
+        },
+        PrepaymentStrategy::ShortenTerm => LoanState {
+            balance,
+            remaining_periods: periods_needed(method, balance, state.rate, state.installment)
+                .min(state.remaining_periods),
+            ..state.clone()
+        },
+    }
+}
+
+/// 从 `state` 起推算剩余还款计划；`prepayments` 在其日期之后的第一期之前生效
+pub fn project_schedule(
+    method: RepaymentMethod,
+    state: &LoanState,
+    first_payment_date: NaiveDate,
+    base_rate: Decimal,
+    rate_changes: &[(NaiveDate, Decimal)],
+    prepayments: &[SimulatedPrepayment],
+) -> Vec<ScheduleRow> {
+    let mut prepayments: Vec<&SimulatedPrepayment> = prepayments.iter().collect();
+    prepayments.sort_by_key(|p| p.date);
+    let mut pending = prepayments.into_iter().peekable();
+
+    let mut state = state.clone();
+    let mut rows = Vec::new();
+    while state.remaining_periods > 0 && state.balance > Decimal::ZERO {
+        let due = due_date(first_payment_date, state.next_period);
+        while let Some(prepayment) = pending.next_if(|p| p.date < due) {
+            state = apply_prepayment(method, &state, prepayment.amount, prepayment.strategy);
+        }
+        if state.balance.is_zero() {
+            break;
+        }
+        let (row, next) = next_row(method, &state, due, rate_on(base_rate, rate_changes, due));
+        rows.push(row);
+        state = next;
+    }
+    rows
+}
+
+pub fn summarize(rows: &[ScheduleRow]) -> LoanProjection {
+    LoanProjection {
+        payoff_date: rows.last().map(|r| r.due_date),
+        remaining_periods: rows.len(),
+        next_payment: rows.first().map(|r| r.payment),
+        total_interest: rows.iter().map(|r| r.interest).sum(),
+        total_payment: rows.iter().map(|r| r.payment).sum(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct LoanTerms {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub ledger_id: Uuid,
+    pub principal: Decimal,
+    pub annual_rate: Decimal,
+    pub term_months: i16,
+    pub start_date: NaiveDate,
+    pub first_payment_date: NaiveDate,
+    pub repayment_method: String,
+    pub outstanding_principal: Decimal,
+    pub next_period: i16,
+    pub remaining_periods: i16,
+    pub current_installment: Decimal,
+    pub current_rate: Decimal,
+    pub status: String,
+    pub created_by: Uuid,
+}
+
+impl LoanTerms {
+    pub fn method(&self) -> RepaymentMethod {
+        RepaymentMethod::parse(&self.repayment_method).unwrap_or(RepaymentMethod::EqualPayment)
+    }
+
+    pub fn state(&self) -> LoanState {
+        LoanState {
+            balance: self.outstanding_principal,
+            next_period: self.next_period,
+            remaining_periods: self.remaining_periods,
+            installment: self.current_installment,
+            rate: self.current_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct LoanPayment {
+    pub id: Uuid,
+    pub loan_id: Uuid,
+    pub period_number: Option<i16>,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub principal: Decimal,
+    pub interest: Decimal,
+    pub balance_after: Decimal,
+    pub prepayment_strategy: Option<String>,
+    pub from_account_id: Uuid,
+    pub principal_transaction_id: Option<Uuid>,
+    pub interest_transaction_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LoanRateChange {
+    pub effective_date: NaiveDate,
+    pub annual_rate: Decimal,
+}
+
+/// 已还记录与剩余还款计划
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LoanSchedule {
+    pub loan: LoanTerms,
+    pub rate_changes: Vec<LoanRateChange>,
+    pub payments: Vec<LoanPayment>,
+    pub projected: Vec<ScheduleRow>,
+    pub projection: LoanProjection,
+}
+
+/// 提前还款测算：与不提前还款的计划对比
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PrepaymentSimulation {
+    pub baseline: LoanProjection,
+    pub simulated: LoanProjection,
+    pub interest_saved: Decimal,
+    pub periods_saved: i64,
+    pub schedule: Vec<ScheduleRow>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateLoanTermsRequest {
+    pub principal: Decimal,
+    /// 年利率，如 0.049
+    pub annual_rate: Decimal,
+    pub term_months: i16,
+    pub start_date: NaiveDate,
+    /// 默认为放款日一个月后
+    pub first_payment_date: Option<NaiveDate>,
+    pub repayment_method: RepaymentMethod,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RateChangeRequest {
+    pub effective_date: NaiveDate,
+    pub annual_rate: Decimal,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecordLoanPaymentRequest {
+    /// 还款账户
+    pub from_account_id: Uuid,
+    /// 默认今天
+    pub payment_date: Option<NaiveDate>,
+    /// 默认为当期应还金额；提前还款时必填
+    pub amount: Option<Decimal>,
+    /// 设置时为提前还款，金额全部计入本金
+    pub prepayment: Option<PrepaymentStrategy>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SimulatePrepaymentRequest {
+    pub prepayments: Vec<SimulatedPrepayment>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LoanPaymentResult {
+    pub payment: LoanPayment,
+    pub loan: LoanTerms,
+}
+
+const TERMS_SELECT: &str = r#"
+    SELECT l.id, l.account_id, a.name AS account_name, a.ledger_id, l.principal, l.annual_rate,
+           l.term_months, l.start_date, l.first_payment_date, l.repayment_method,
+           l.outstanding_principal, l.next_period, l.remaining_periods, l.current_installment,
+           l.current_rate, l.status, l.created_by
+    FROM loan_terms l
+    JOIN accounts a ON a.id = l.account_id
+"#;
+
+const PAYMENT_COLUMNS: &str = r#"
+    id, loan_id, period_number, payment_date, amount, principal, interest, balance_after,
+    prepayment_strategy, from_account_id, principal_transaction_id, interest_transaction_id
+"#;
+
+fn validate_rate(annual_rate: Decimal) -> Result<(), ServiceError> {
+    if annual_rate < Decimal::ZERO || annual_rate >= Decimal::ONE {
+        return Err(ServiceError::validation("annual_rate must be in [0, 1)"));
+    }
+    Ok(())
+}
+
+pub struct LoanService {
+    pool: PgPool,
+}
+
+impl LoanService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_terms(&self, account_id: Uuid) -> Result<LoanTerms, ServiceError> {
+        sqlx::query_as::<_, LoanTerms>(&format!("{} WHERE l.account_id = $1", TERMS_SELECT))
+            .bind(account_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("loan_terms", account_id))
+    }
+
+    /// 为贷款账户设置条款；已过还款日的期次视为已还，账户余额同步为剩余本金
+    pub async fn create_terms(
+        &self,
+        user_id: Uuid,
+        account_id: Uuid,
+        request: CreateLoanTermsRequest,
+    ) -> Result<LoanTerms, ServiceError> {
+        if request.principal <= Decimal::ZERO {
+            return Err(ServiceError::validation("principal must be positive"));
+        }
+        if !(1..=MAX_TERM_MONTHS).contains(&request.term_months) {
+            return Err(ServiceError::validation(
+                "term_months must be between 1 and 600",
+            ));
+        }
+        validate_rate(request.annual_rate)?;
+        let first_payment_date = match request.first_payment_date {
+            Some(date) => date,
+            None => due_date(request.start_date, 2),
+        };
+        if first_payment_date <= request.start_date {
+            return Err(ServiceError::validation(
+                "first_payment_date must be after start_date",
+            ));
+        }
+
+        let account = sqlx::query(
+            "SELECT account_type, account_sub_type FROM accounts WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(account_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("account", account_id))?;
+        let sub_type: Option<String> = account.get("account_sub_type");
+        let account_type: String = account.get("account_type");
+        let is_loan = match sub_type.as_deref() {
+            Some(sub_type) => LOAN_ACCOUNT_SUB_TYPES.contains(&sub_type),
+            None => account_type == "loan",
+        };
+        if !is_loan {
+            return Err(ServiceError::business_rule(
+                "Loan terms can only be set on loan or mortgage accounts",
+            ));
+        }
+
+        let method = request.repayment_method;
+        let mut state = LoanState {
+            balance: request.principal.round_dp(2),
+            next_period: 1,
+            remaining_periods: request.term_months,
+            installment: installment_for(
+                method,
+                request.principal,
+                request.annual_rate,
+                request.term_months,
+            ),
+            rate: request.annual_rate,
+        };
+        let today = Utc::now().date_naive();
+        while state.remaining_periods > 0 && due_date(first_payment_date, state.next_period) < today
+        {
+            let due = due_date(first_payment_date, state.next_period);
+            state = next_row(method, &state, due, request.annual_rate).1;
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let exists: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM loan_terms WHERE account_id = $1)")
+                .bind(account_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if exists {
+            return Err(ServiceError::conflict("The account already has loan terms"));
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO loan_terms
+                (account_id, principal, annual_rate, term_months, start_date, first_payment_date,
+                 repayment_method, outstanding_principal, next_period, remaining_periods,
+                 current_installment, current_rate, status, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(account_id)
+        .bind(request.principal)
+        .bind(request.annual_rate)
+        .bind(request.term_months)
+        .bind(request.start_date)
+        .bind(first_payment_date)
+        .bind(method.as_str())
+        .bind(state.balance)
+        .bind(state.next_period)
+        .bind(state.remaining_periods)
+        .bind(state.installment)
+        .bind(state.rate)
+        .bind(if state.balance.is_zero() {
+            "paid_off"
+        } else {
+            "active"
+        })
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE accounts SET current_balance = $1, updated_at = NOW() WHERE id = $2")
+            .bind(-state.balance)
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        self.get_terms(account_id).await
+    }
+
+    /// 删除贷款条款及还款记录，已生成的交易保留
+    pub async fn delete_terms(&self, account_id: Uuid) -> Result<(), ServiceError> {
+        let result = sqlx::query("DELETE FROM loan_terms WHERE account_id = $1")
+            .bind(account_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("loan_terms", account_id));
+        }
+        Ok(())
+    }
+
+    async fn rate_changes(&self, loan_id: Uuid) -> Result<Vec<LoanRateChange>, ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT effective_date, annual_rate FROM loan_rate_changes
+            WHERE loan_id = $1
+            ORDER BY effective_date
+            "#,
+        )
+        .bind(loan_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| LoanRateChange {
+                effective_date: row.get("effective_date"),
+                annual_rate: row.get("annual_rate"),
+            })
+            .collect())
+    }
+
+    fn project(
+        loan: &LoanTerms,
+        rate_changes: &[LoanRateChange],
+        prepayments: &[SimulatedPrepayment],
+    ) -> Vec<ScheduleRow> {
+        let changes: Vec<(NaiveDate, Decimal)> = rate_changes
+            .iter()
+            .map(|c| (c.effective_date, c.annual_rate))
+            .collect();
+        project_schedule(
+            loan.method(),
+            &loan.state(),
+            loan.first_payment_date,
+            loan.annual_rate,
+            &changes,
+            prepayments,
+        )
+    }
+
+    /// 已还记录与按当前状态推算的剩余计划
+    pub async fn schedule(&self, account_id: Uuid) -> Result<LoanSchedule, ServiceError> {
+        let loan = self.get_terms(account_id).await?;
+        let rate_changes = self.rate_changes(loan.id).await?;
+        let payments = sqlx::query_as::<_, LoanPayment>(&format!(
+            "SELECT {} FROM loan_payments WHERE loan_id = $1 ORDER BY payment_date, created_at",
+            PAYMENT_COLUMNS
+        ))
+        .bind(loan.id)
+        .fetch_all(&self.pool)
+        .await?;
+        let projected = Self::project(&loan, &rate_changes, &[]);
+        let projection = summarize(&projected);
+        Ok(LoanSchedule {
+            loan,
+            rate_changes,
+            payments,
+            projected,
+            projection,
+        })
+    }
+
+    /// 设置利率重定价；尚未还款的期次自生效日起按新利率计息
+    pub async fn set_rate_change(
+        &self,
+        account_id: Uuid,
+        request: RateChangeRequest,
+    ) -> Result<LoanSchedule, ServiceError> {
+        validate_rate(request.annual_rate)?;
+        let loan = self.get_terms(account_id).await?;
+        if request.effective_date < loan.start_date {
+            return Err(ServiceError::validation(
+                "effective_date must not be before the loan start date",
+            ));
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO loan_rate_changes (loan_id, effective_date, annual_rate)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (loan_id, effective_date) DO UPDATE SET annual_rate = EXCLUDED.annual_rate
+            "#,
+        )
+        .bind(loan.id)
+        .bind(request.effective_date)
+        .bind(request.annual_rate)
+        .execute(&self.pool)
+        .await?;
+        self.schedule(account_id).await
+    }
+
+    /// 提前还款测算，不落库
+    pub async fn simulate(
+        &self,
+        account_id: Uuid,
+        request: SimulatePrepaymentRequest,
+    ) -> Result<PrepaymentSimulation, ServiceError> {
+        if request
+            .prepayments
+            .iter()
+            .any(|p| p.amount <= Decimal::ZERO)
+        {
+            return Err(ServiceError::validation(
+                "prepayment amounts must be positive",
+            ));
+        }
+        let loan = self.get_terms(account_id).await?;
+        let rate_changes = self.rate_changes(loan.id).await?;
+        let baseline = summarize(&Self::project(&loan, &rate_changes, &[]));
+        let schedule = Self::project(&loan, &rate_changes, &request.prepayments);
+        let simulated = summarize(&schedule);
+        Ok(PrepaymentSimulation {
+            interest_saved: baseline.total_interest - simulated.total_interest,
+            periods_saved: baseline.remaining_periods as i64 - simulated.remaining_periods as i64,
+            baseline,
+            simulated,
+            schedule,
+        })
+    }
+
+    /// 记录一笔还款：按当期计划拆分利息与本金（提前还款全部计入本金）并生成交易
+    pub async fn record_payment(
+        &self,
+        user_id: Uuid,
+        account_id: Uuid,
+        request: RecordLoanPaymentRequest,
+        config: &RateResolverConfig,
+    ) -> Result<LoanPaymentResult, ServiceError> {
+        if request.from_account_id == account_id {
+            return Err(ServiceError::validation(
+                "from_account_id must differ from the loan account",
+            ));
+        }
+        if request.amount.is_some_and(|a| a <= Decimal::ZERO) {
+            return Err(ServiceError::validation("amount must be positive"));
+        }
+        let payment_date = request.payment_date.unwrap_or(Utc::now().date_naive());
+
+        let mut tx = self.pool.begin().await?;
+        let loan = sqlx::query_as::<_, LoanTerms>(&format!(
+            "{} WHERE l.account_id = $1 FOR UPDATE OF l",
+            TERMS_SELECT
+        ))
+        .bind(account_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("loan_terms", account_id))?;
+        if loan.status != "active" || loan.outstanding_principal.is_zero() {
+            return Err(ServiceError::business_rule("The loan is already paid off"));
+        }
+        let from_ledger: Option<Uuid> = sqlx::query_scalar(
+            "SELECT ledger_id FROM accounts WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(request.from_account_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match from_ledger {
+            None => return Err(ServiceError::not_found("account", request.from_account_id)),
+            Some(ledger) if ledger != loan.ledger_id => {
+                return Err(ServiceError::validation(
+                    "from_account_id must be in the same ledger as the loan",
+                ))
+            }
+            _ => {}
+        }
+
+        let method = loan.method();
+        let state = loan.state();
+        let (period_number, principal, interest, next) = match request.prepayment {
+            Some(strategy) => {
+                let amount = request.amount.ok_or_else(|| {
+                    ServiceError::validation("amount is required for prepayments")
+                })?;
+                if amount > state.balance {
+                    return Err(ServiceError::validation(
+                        "prepayment exceeds the outstanding principal",
+                    ));
+                }
+                let next = apply_prepayment(method, &state, amount, strategy);
+                (None, amount, Decimal::ZERO, next)
+            }
+            None => {
+                let rate_changes: Vec<(NaiveDate, Decimal)> = self
+                    .rate_changes(loan.id)
+                    .await?
+                    .iter()
+                    .map(|c| (c.effective_date, c.annual_rate))
+                    .collect();
+                let due = due_date(loan.first_payment_date, state.next_period);
+                let (row, mut next) = next_row(
+                    method,
+                    &state,
+                    due,
+                    rate_on(loan.annual_rate, &rate_changes, due),
+                );
+                let amount = request.amount.unwrap_or(row.payment);
+                let interest = row.interest.min(amount);
+                let principal = (amount - interest).min(state.balance);
+                if principal != row.principal {
+                    next.balance = state.balance - principal;
+                    if next.balance.is_zero() {
+                        next.remaining_periods = 0;
+                    }
+                }
+                (Some(row.period_number), principal, interest, next)
+            }
+        };
+
+        // 本金由还款账户转入贷款账户，利息记为还款账户的支出
+        let principal_transaction = if principal > Decimal::ZERO {
+            let source = insert_transaction(
+                &mut tx,
+                &loan,
+                Posting {
+                    account_id: request.from_account_id,
+                    to_account_id: Some(loan.account_id),
+                    transaction_type: "transfer",
+                    category_name: "贷款本金",
+                    amount: principal,
+                },
+                payment_date,
+                user_id,
+            )
+            .await?;
+            let target = insert_transaction(
+                &mut tx,
+                &loan,
+                Posting {
+                    account_id: loan.account_id,
+                    to_account_id: None,
+                    transaction_type: "income",
+                    category_name: "贷款本金",
+                    amount: principal,
+                },
+                payment_date,
+                user_id,
+            )
+            .await?;
+            Some((source, target))
+        } else {
+            None
+        };
+        let interest_transaction = if interest > Decimal::ZERO {
+            Some(
+                insert_transaction(
+                    &mut tx,
+                    &loan,
+                    Posting {
+                        account_id: request.from_account_id,
+                        to_account_id: None,
+                        transaction_type: "expense",
+                        category_name: "贷款利息",
+                        amount: interest,
+                    },
+                    payment_date,
+                    user_id,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+
+        let payment = sqlx::query_as::<_, LoanPayment>(&format!(
+            r#"
+            INSERT INTO loan_payments
+                (loan_id, period_number, payment_date, amount, principal, interest, balance_after,
+                 prepayment_strategy, from_account_id, principal_transaction_id,
+                 interest_transaction_id, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING {}
+            "#,
+            PAYMENT_COLUMNS
+        ))
+        .bind(loan.id)
+        .bind(period_number)
+        .bind(payment_date)
+        .bind(principal + interest)
+        .bind(principal)
+        .bind(interest)
+        .bind(next.balance)
+        .bind(request.prepayment.map(|s| s.as_str()))
+        .bind(request.from_account_id)
+        .bind(principal_transaction.map(|(source, _)| source))
+        .bind(interest_transaction)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE loan_terms
+            SET outstanding_principal = $2, next_period = $3, remaining_periods = $4,
+                current_installment = $5, current_rate = $6,
+                status = CASE WHEN $2 = 0 THEN 'paid_off' ELSE status END,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(loan.id)
+        .bind(next.balance)
+        .bind(next.next_period)
+        .bind(next.remaining_periods)
+        .bind(next.installment)
+        .bind(next.rate)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let valuation = TransactionValuationService::new(self.pool.clone());
+        let posted = principal_transaction
+            .into_iter()
+            .flat_map(|(source, target)| [source, target])
+            .chain(interest_transaction);
+        for id in posted {
+            if let Err(e) = valuation.value_transaction(id, config).await {
+                tracing::warn!("Failed to value loan payment transaction {}: {:?}", id, e);
+            }
+        }
+
+        Ok(LoanPaymentResult {
+            payment,
+            loan: self.get_terms(account_id).await?,
+        })
+    }
+}
+
+/// 还款生成的一笔交易
+struct Posting<'a> {
+    account_id: Uuid,
+    to_account_id: Option<Uuid>,
+    transaction_type: &'a str,
+    category_name: &'a str,
+    amount: Decimal,
+}
+
+/// 插入交易并同步账户余额：收入增加余额，支出与转出减少余额
+async fn insert_transaction(
+    tx: &mut Transaction<'_, Postgres>,
+    loan: &LoanTerms,
+    posting: Posting<'_>,
+    date: NaiveDate,
+    user_id: Uuid,
+) -> Result<Uuid, ServiceError> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (
+            id, ledger_id, account_id, to_account_id, transaction_date, amount, transaction_type,
+            category_name, payee, description, status, created_by, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $8, 'completed', $10, NOW(), NOW())
+        "#,
+    )
+    .bind(id)
+    .bind(loan.ledger_id)
+    .bind(posting.account_id)
+    .bind(posting.to_account_id)
+    .bind(date)
+    .bind(posting.amount)
+    .bind(posting.transaction_type)
+    .bind(posting.category_name)
+    .bind(&loan.account_name)
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+    let delta = if posting.transaction_type == "income" {
+        posting.amount
+    } else {
+        -posting.amount
+    };
+    sqlx::query(
+        "UPDATE accounts SET current_balance = current_balance + $1, updated_at = NOW() WHERE id = $2",
+    )
+    .bind(delta)
+    .bind(posting.account_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn initial(method: RepaymentMethod, principal: Decimal, rate: Decimal, term: i16) -> LoanState {
+        LoanState {
+            balance: principal,
+            next_period: 1,
+            remaining_periods: term,
+            installment: installment_for(method, principal, rate, term),
+            rate,
+        }
+    }
+
+    #[test]
+    fn test_equal_payment_schedule() {
+        // 100 万、年利率 4.9%、30 年等额本息
+        assert_eq!(
+            annuity_payment(dec!(1000000), dec!(0.049), 360),
+            dec!(5307.27)
+        );
+        let state = initial(RepaymentMethod::EqualPayment, dec!(120000), dec!(0.06), 12);
+        let rows = project_schedule(
+            RepaymentMethod::EqualPayment,
+            &state,
+            date(2025, 1, 31),
+            dec!(0.06),
+            &[],
+            &[],
+        );
+        assert_eq!(rows.len(), 12);
+        assert_eq!(rows[0].payment, dec!(10327.97));
+        assert_eq!(rows[0].interest, dec!(600));
+        assert_eq!(rows[1].due_date, date(2025, 2, 28));
+        let principal: Decimal = rows.iter().map(|r| r.principal).sum();
+        assert_eq!(principal, dec!(120000));
+        assert_eq!(rows.last().unwrap().balance_after, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_equal_principal_and_rate_reset() {
+        let state = initial(
+            RepaymentMethod::EqualPrincipal,
+            dec!(1200000),
+            dec!(0.049),
+            360,
+        );
+        let rows = project_schedule(
+            RepaymentMethod::EqualPrincipal,
+            &state,
+            date(2025, 1, 15),
+            dec!(0.049),
+            &[],
+            &[],
+        );
+        assert_eq!(rows[0].principal, dec!(3333.33));
+        assert_eq!(rows[0].payment, dec!(8233.33));
+        assert!(rows[1].interest < rows[0].interest);
+
+        // 第 3 期起利率降至 3.6%：等额本息按剩余本金与期数重新计算月供
+        let state = initial(RepaymentMethod::EqualPayment, dec!(120000), dec!(0.06), 12);
+        let rows = project_schedule(
+            RepaymentMethod::EqualPayment,
+            &state,
+            date(2025, 1, 15),
+            dec!(0.06),
+            &[(date(2025, 3, 1), dec!(0.036))],
+            &[],
+        );
+        assert_eq!(rows[1].annual_rate, dec!(0.06));
+        assert_eq!(rows[2].annual_rate, dec!(0.036));
+        assert!(rows[2].payment < rows[1].payment);
+        assert_eq!(rows.len(), 12);
+        assert_eq!(rows.last().unwrap().balance_after, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_prepayment_strategies() {
+        let state = initial(
+            RepaymentMethod::EqualPayment,
+            dec!(1000000),
+            dec!(0.049),
+            360,
+        );
+        let prepay = |strategy| SimulatedPrepayment {
+            date: date(2025, 6, 1),
+            amount: dec!(200000),
+            strategy,
+        };
+        let project = |prepayments: &[SimulatedPrepayment]| {
+            project_schedule(
+                RepaymentMethod::EqualPayment,
+                &state,
+                date(2025, 1, 20),
+                dec!(0.049),
+                &[],
+                prepayments,
+            )
+        };
+        let baseline = summarize(&project(&[]));
+        let shorter = project(&[prepay(PrepaymentStrategy::ShortenTerm)]);
+        let lower = project(&[prepay(PrepaymentStrategy::ReducePayment)]);
+
+        assert_eq!(baseline.remaining_periods, 360);
+        // 缩短期限：月供不变，期数减少
+        assert!(shorter.len() < 360);
+        assert_eq!(shorter[10].payment, shorter[0].payment);
+        // 减少月供：期数不变，月供下降
+        assert_eq!(lower.len(), 360);
+        assert!(lower[10].payment < lower[0].payment);
+        // 缩短期限节省的利息更多
+        let shorter_interest = summarize(&shorter).total_interest;
+        let lower_interest = summarize(&lower).total_interest;
+        assert!(shorter_interest < lower_interest);
+        assert!(lower_interest < baseline.total_interest);
+    }
+}
@@ -0,0 +1,291 @@
+//! 登录安全服务
+//!
+//! - 按账户统计连续失败次数，达到阈值后指数退避锁定
+//! - 登录成功时记录会话（设备指纹 + 网段），识别新设备 / 新位置登录
+//! - 失败、锁定、可疑登录写入 audit_logs，并可选发送邮件提醒
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use super::email::{EmailOutbox, EmailTemplate};
+use super::{AuditService, ServiceError};
+use crate::config::{AppConfig, LoginSecurityConfig};
+use crate::middleware::client_ip::network_prefix;
+use crate::models::audit::AuditAction;
+use crate::models::session::DeviceInfo;
+
+/// 会话有效期（与 JWT 过期时间保持一致）
+const SESSION_TTL_HOURS: i64 = 24;
+
+/// 登录失败处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    /// 仅记录失败
+    Recorded { failed_attempts: u32 },
+    /// 本次失败触发了锁定
+    Locked { retry_after: u64 },
+}
+
+/// 登录成功时识别出的可疑信号
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoginSignals {
+    pub session_id: Uuid,
+    pub new_device: bool,
+    pub new_location: bool,
+}
+
+pub struct LoginSecurityService {
+    pool: PgPool,
+    config: LoginSecurityConfig,
+    outbox: EmailOutbox,
+}
+
+impl LoginSecurityService {
+    pub fn new(pool: PgPool, config: &AppConfig) -> Self {
+        Self {
+            outbox: EmailOutbox::new(pool.clone(), &config.email),
+            config: config.login_security.clone(),
+            pool,
+        }
+    }
+
+    /// 检查账户是否处于锁定中，返回剩余锁定秒数
+    pub async fn check_lockout(&self, user_id: Uuid) -> Result<Option<u64>, ServiceError> {
+        let locked_until: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT locked_until FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .flatten();
+
+        Ok(locked_until.and_then(|until| {
+            let remaining = (until - Utc::now()).num_seconds();
+            (remaining > 0).then_some(remaining as u64)
+        }))
+    }
+
+    /// 记录一次密码错误
+    pub async fn record_failure(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        ip: Option<IpAddr>,
+        user_agent: Option<&str>,
+    ) -> Result<FailureOutcome, ServiceError> {
+        let row = sqlx::query(
+            r#"
+            UPDATE users
+            SET failed_login_attempts = failed_login_attempts + 1,
+                last_failed_login_at = NOW()
+            WHERE id = $1
+            RETURNING failed_login_attempts
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        let failed_attempts = row.try_get::<i32, _>("failed_login_attempts")?.max(0) as u32;
+
+        let audit = AuditService::new(self.pool.clone());
+        let ip_str = ip.map(|ip| ip.to_string());
+        let ua = user_agent.map(|s| s.to_string());
+        audit
+            .log_user_event(
+                user_id,
+                AuditAction::LoginFailed,
+                Some(serde_json::json!({ "failed_attempts": failed_attempts })),
+                ip_str.clone(),
+                ua.clone(),
+            )
+            .await?;
+
+        let Some(duration) = self.config.lockout_duration(failed_attempts) else {
+            return Ok(FailureOutcome::Recorded { failed_attempts });
+        };
+
+        let retry_after = duration.as_secs();
+        sqlx::query(
+            "UPDATE users SET locked_until = NOW() + make_interval(secs => $2) WHERE id = $1",
+        )
+        .bind(user_id)
+        .bind(retry_after as f64)
+        .execute(&self.pool)
+        .await?;
+
+        audit
+            .log_user_event(
+                user_id,
+                AuditAction::AccountLocked,
+                Some(serde_json::json!({
+                    "failed_attempts": failed_attempts,
+                    "locked_seconds": retry_after,
+                })),
+                ip_str.clone(),
+                ua,
+            )
+            .await?;
+
+        tracing::warn!(user_id=%user_id, failed_attempts, retry_after, "account locked after repeated login failures");
+        self.send_alert(
+            email,
+            EmailTemplate::AccountLocked {
+                failed_attempts,
+                locked_seconds: retry_after,
+                ip: ip_str,
+            },
+        )
+        .await;
+
+        Ok(FailureOutcome::Locked { retry_after })
+    }
+
+    /// 删除已过期或已撤销、且超过保留期的会话记录；返回删除条数
+    ///
+    /// 保留期内的会话仍参与新设备 / 新位置识别
+    pub async fn prune_sessions(&self) -> Result<u64, ServiceError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM user_sessions
+            WHERE last_seen_at < NOW() - make_interval(days => $1)
+              AND (expires_at < NOW() OR revoked_at IS NOT NULL)
+            "#,
+        )
+        .bind(self.config.session_retention_days as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 登录成功：清零失败计数、记录会话并检测新设备 / 新位置
+    pub async fn record_success(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        ip: Option<IpAddr>,
+        user_agent: Option<&str>,
+    ) -> Result<LoginSignals, ServiceError> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET failed_login_attempts = 0, locked_until = NULL
+            WHERE id = $1 AND (failed_login_attempts <> 0 OR locked_until IS NOT NULL)
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        let device = DeviceInfo::from_user_agent(user_agent.unwrap_or_default());
+        let fingerprint = device.fingerprint();
+        let prefix = ip.as_ref().map(network_prefix);
+
+        // 首次登录（无历史会话）不视为可疑；旧版指纹只会出现在升级前的会话中
+        let history = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE device_fingerprint IN ($2, $4)) AS same_device,
+                COUNT(*) FILTER (WHERE network_prefix = $3) AS same_network
+            FROM user_sessions
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(&fingerprint)
+        .bind(&prefix)
+        .bind(device.legacy_fingerprint())
+        .fetch_one(&self.pool)
+        .await?;
+        let total: i64 = history.try_get("total")?;
+        let same_device: i64 = history.try_get("same_device")?;
+        let same_network: i64 = history.try_get("same_network")?;
+
+        let session_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO user_sessions (
+                id, user_id, device_fingerprint, device_type, os, browser,
+                app_version, user_agent, ip_address, network_prefix,
+                created_at, last_seen_at, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9::inet, $10,
+                    NOW(), NOW(), NOW() + make_interval(hours => $11))
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(&fingerprint)
+        .bind(&device.device_type)
+        .bind(&device.os)
+        .bind(&device.browser)
+        .bind(&device.app_version)
+        .bind(user_agent)
+        .bind(ip.map(|ip| ip.to_string()))
+        .bind(&prefix)
+        .bind(SESSION_TTL_HOURS as i32)
+        .execute(&self.pool)
+        .await?;
+
+        let signals = LoginSignals {
+            session_id,
+            new_device: total > 0 && same_device == 0,
+            new_location: total > 0 && prefix.is_some() && same_network == 0,
+        };
+
+        let audit = AuditService::new(self.pool.clone());
+        let ip_str = ip.map(|ip| ip.to_string());
+        if signals.new_device {
+            audit
+                .log_user_event(
+                    user_id,
+                    AuditAction::NewDeviceLogin,
+                    Some(serde_json::json!({ "session_id": session_id, "device": device })),
+                    ip_str.clone(),
+                    user_agent.map(|s| s.to_string()),
+                )
+                .await?;
+            self.send_alert(
+                email,
+                EmailTemplate::NewDeviceLogin {
+                    device: format!(
+                        "{} / {}",
+                        device.os,
+                        device.browser.as_deref().unwrap_or(&device.device_type)
+                    ),
+                    ip: ip_str.clone(),
+                },
+            )
+            .await;
+        }
+        if signals.new_location {
+            audit
+                .log_user_event(
+                    user_id,
+                    AuditAction::NewLocationLogin,
+                    Some(serde_json::json!({ "session_id": session_id, "network": prefix })),
+                    ip_str.clone(),
+                    user_agent.map(|s| s.to_string()),
+                )
+                .await?;
+            self.send_alert(
+                email,
+                EmailTemplate::NewLocationLogin { ip: ip_str.clone() },
+            )
+            .await;
+        }
+
+        Ok(signals)
+    }
+
+    /// 安全提醒邮件入队；失败只记录日志，不影响登录流程
+    async fn send_alert(&self, email: &str, template: EmailTemplate) {
+        if !self.config.alert_email_enabled {
+            return;
+        }
+        if let Err(e) = self.outbox.enqueue_for_user(email, &template).await {
+            tracing::warn!(to = %email, template = template.kind(), error = ?e, "failed to enqueue security alert");
+        }
+    }
+}
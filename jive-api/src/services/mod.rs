@@ -1,21 +1,51 @@
 #![allow(dead_code)]
 
+pub mod ai_service;
 pub mod audit_service;
 pub mod auth_service;
 pub mod avatar_service;
+pub mod bank_connectors;
+pub mod bank_sync_service;
 pub mod budget_service;
+pub mod category_classifier;
+pub mod category_suggestion_service;
 pub mod context;
+pub mod credit_card_service;
 pub mod currency_service;
+pub mod email;
 pub mod error;
 pub mod exchange_rate_api;
 pub mod exchange_rate_service;
 pub mod family_service;
+pub mod financial_digest_service;
+pub mod fx_gain_loss_service;
+pub mod fx_history_import;
+pub mod fx_providers;
+pub mod installment_service;
+pub mod investment_income_service;
+pub mod investment_performance_service;
+pub mod investment_service;
 pub mod invitation_service;
+pub mod ledger_acl_service;
+pub mod llm_providers;
+pub mod loan_service;
+pub mod login_security_service;
 pub mod member_service;
+pub mod notification_service;
+pub mod password_reset_service;
+pub mod push;
+pub mod quote_providers;
+pub mod rate_alert_service;
+pub mod rate_graph;
 pub mod scheduled_tasks;
+pub mod security_price_service;
 pub mod tag_service;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod transaction_service;
+pub mod transaction_valuation_service;
 pub mod verification_service;
+pub mod webhook_service;
 
 pub use audit_service::AuditService;
 pub use auth_service::AuthService;
@@ -26,12 +56,21 @@ pub use budget_service::BudgetService;
 pub use context::ServiceContext;
 #[allow(unused_imports)]
 pub use currency_service::{Currency, CurrencyService, ExchangeRate, FamilyCurrencySettings};
+#[allow(unused_imports)]
+pub use email::{EmailLocale, EmailOutbox, EmailTemplate};
 pub use error::ServiceError;
 pub use family_service::FamilyService;
 pub use invitation_service::InvitationService;
+pub use ledger_acl_service::{LedgerAclService, LedgerResource};
+pub use login_security_service::LoginSecurityService;
 pub use member_service::MemberService;
+pub use notification_service::NotificationService;
+pub use password_reset_service::PasswordResetService;
+pub use rate_alert_service::RateAlertService;
 #[allow(unused_imports)]
 pub use tag_service::{TagDto, TagService, TagSummary};
 #[allow(unused_imports)]
 pub use transaction_service::TransactionService;
+pub use transaction_valuation_service::TransactionValuationService;
 pub use verification_service::VerificationService;
+pub use webhook_service::WebhookService;
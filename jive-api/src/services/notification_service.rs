@@ -0,0 +1,1305 @@
+//! 通知中心
+//!
+//! 通知先写入 `notifications` 表，再通过 WebSocket 推送给在线用户；
+//! 离线用户之后仍可从通知表中读取。
+//!
+//! 站外渠道（邮件、个人 Webhook、设备推送）按用户偏好写入 `notification_deliveries`，由定时任务投递：
+//! 免打扰时段内的投递顺延到时段结束；邮件按摘要频率（实时 / 每日 / 每周）合并为一封，
+//! 发出前已读或已忽略的通知不再发邮件或推送。`urgent` 通知不受免打扰与摘要限制，也不能被屏蔽。
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveTime, Utc, Weekday};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::email::{EmailOutbox, EmailTemplate};
+use super::push::{PushDeviceService, PushError, PushMessage, PushRegistry};
+use super::ServiceError;
+use crate::config::NotificationConfig;
+use crate::utils::outbound::{self, OutboundError};
+use crate::utils::signature::signature_header;
+use crate::ws::{WsConnectionManager, WsMessage};
+
+/// Webhook 签名头
+pub const SIGNATURE_HEADER: &str = "X-Jive-Signature";
+/// 单次列表最多返回的通知数
+const MAX_LIST_LIMIT: i64 = 200;
+const DEFAULT_LIST_LIMIT: i64 = 50;
+/// 屏蔽类型数量上限
+const MAX_MUTED_KINDS: usize = 50;
+/// sending 状态超过该时长视为投递进程已崩溃，可被重新领取
+const STALE_LOCK_MINUTES: i64 = 10;
+/// 自带邮件模板的通知类型（财务摘要），不再经通知邮件渠道重复发送
+const OWN_EMAIL_KINDS: &[&str] = &["weekly_digest", "monthly_digest"];
+const NOTIFICATION_COLUMNS: &str =
+    "id, user_id, family_id, kind, priority, title, body, data, action_url, read_at, dismissed_at, created_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Option<Uuid>,
+    /// 通知类型（rate_alert 等）
+    pub kind: String,
+    /// low / normal / high / urgent
+    pub priority: String,
+    pub title: String,
+    pub body: String,
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+    /// 客户端跳转链接
+    pub action_url: Option<String>,
+    pub read_at: Option<DateTime<Utc>>,
+    pub dismissed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 通知优先级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NotificationPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    /// 不受免打扰、摘要与屏蔽设置影响
+    Urgent,
+}
+
+impl NotificationPriority {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "high" => Some(Self::High),
+            "urgent" => Some(Self::Urgent),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Urgent => "urgent",
+        }
+    }
+}
+
+/// 待发送的通知
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub family_id: Option<Uuid>,
+    pub kind: String,
+    pub priority: NotificationPriority,
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+    pub action_url: Option<String>,
+}
+
+/// 邮件摘要频率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestFrequency {
+    /// 每条通知单独发送
+    Realtime,
+    /// 每天一封
+    Daily,
+    /// 每周一一封
+    Weekly,
+    /// 不发送邮件
+    Never,
+}
+
+impl DigestFrequency {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "realtime" => Some(Self::Realtime),
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Realtime => "realtime",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Never => "never",
+        }
+    }
+}
+
+/// 站外渠道的投递时间策略（用户本地时间）
+#[derive(Debug, Clone, Copy)]
+pub struct DeliveryPolicy {
+    /// 免打扰时段 [开始, 结束)，开始晚于结束时跨午夜
+    pub quiet_hours: Option<(NaiveTime, NaiveTime)>,
+    pub utc_offset: FixedOffset,
+    pub digest: DigestFrequency,
+    /// 摘要发送时刻（本地小时）
+    pub digest_hour: u32,
+}
+
+impl DeliveryPolicy {
+    fn in_quiet_hours(&self, time: NaiveTime) -> bool {
+        match self.quiet_hours {
+            Some((start, end)) if start < end => time >= start && time < end,
+            Some((start, end)) if start > end => time >= start || time < end,
+            _ => false,
+        }
+    }
+
+    /// 落在免打扰时段内的时间顺延到时段结束
+    pub fn after_quiet_hours(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let local = at.with_timezone(&self.utc_offset);
+        let Some((start, end)) = self.quiet_hours else {
+            return at;
+        };
+        if !self.in_quiet_hours(local.time()) {
+            return at;
+        }
+        let mut end_date = local.date_naive();
+        if start > end && local.time() >= start {
+            end_date = end_date.succ_opt().unwrap_or(end_date);
+        }
+        end_date
+            .and_time(end)
+            .and_local_timezone(self.utc_offset)
+            .single()
+            .map_or(at, |t| t.with_timezone(&Utc))
+    }
+
+    /// 下一个摘要发送时间（严格晚于 `now`）
+    fn next_digest(&self, now: DateTime<Utc>, weekly: bool) -> DateTime<Utc> {
+        let local = now.with_timezone(&self.utc_offset);
+        let at_hour = NaiveTime::from_hms_opt(self.digest_hour.min(23), 0, 0).unwrap_or_default();
+        let mut date = local.date_naive();
+        loop {
+            let candidate = date.and_time(at_hour);
+            if candidate > local.naive_local() && (!weekly || date.weekday() == Weekday::Mon) {
+                break;
+            }
+            date = date.succ_opt().unwrap_or(date);
+        }
+        date.and_time(at_hour)
+            .and_local_timezone(self.utc_offset)
+            .single()
+            .map_or(now, |t| t.with_timezone(&Utc))
+    }
+
+    /// 邮件投递时间；None 表示不发送邮件
+    pub fn email_after(
+        &self,
+        now: DateTime<Utc>,
+        priority: NotificationPriority,
+    ) -> Option<DateTime<Utc>> {
+        if priority == NotificationPriority::Urgent {
+            return Some(now);
+        }
+        let at = match self.digest {
+            DigestFrequency::Never => return None,
+            DigestFrequency::Realtime => now,
+            DigestFrequency::Daily => self.next_digest(now, false),
+            DigestFrequency::Weekly => self.next_digest(now, true),
+        };
+        Some(self.after_quiet_hours(at))
+    }
+
+    /// Webhook 与设备推送的投递时间（只受免打扰时段影响）
+    pub fn realtime_after(
+        &self,
+        now: DateTime<Utc>,
+        priority: NotificationPriority,
+    ) -> DateTime<Utc> {
+        if priority == NotificationPriority::Urgent {
+            now
+        } else {
+            self.after_quiet_hours(now)
+        }
+    }
+}
+
+/// 通知偏好
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NotificationPreferences {
+    /// 是否发送邮件
+    pub email_enabled: bool,
+    /// 是否推送到已注册的设备（默认开启）
+    pub push_enabled: bool,
+    /// 个人 Webhook（如 Home Assistant），为空表示不推送
+    pub webhook_url: Option<String>,
+    /// Webhook 签名密钥：HMAC-SHA256，请求头 `X-Jive-Signature: t=<unix>,v1=<hex>`
+    pub webhook_secret: Option<String>,
+    /// 屏蔽的通知类型（紧急通知不受影响）
+    pub muted_kinds: Vec<String>,
+    /// 免打扰开始时间（HH:MM，本地时间）
+    pub quiet_hours_start: Option<String>,
+    /// 免打扰结束时间（HH:MM）
+    pub quiet_hours_end: Option<String>,
+    /// IANA 时区，如 Asia/Shanghai
+    pub timezone: String,
+    /// realtime / daily / weekly / never
+    pub digest_frequency: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 更新通知偏好（整体替换）
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct UpdatePreferencesRequest {
+    pub email_enabled: bool,
+    /// 默认 true
+    #[serde(default = "default_push_enabled")]
+    pub push_enabled: bool,
+    pub webhook_url: Option<String>,
+    pub muted_kinds: Vec<String>,
+    /// HH:MM，与结束时间同时设置或同时为空
+    pub quiet_hours_start: Option<String>,
+    pub quiet_hours_end: Option<String>,
+    /// 默认 Asia/Shanghai
+    pub timezone: Option<String>,
+    /// 默认 realtime
+    pub digest_frequency: Option<String>,
+    /// 重新生成 Webhook 签名密钥
+    pub rotate_webhook_secret: bool,
+}
+
+fn default_push_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct PreferencesRow {
+    email_enabled: bool,
+    push_enabled: bool,
+    webhook_url: Option<String>,
+    webhook_secret: Option<String>,
+    muted_kinds: Vec<String>,
+    quiet_hours_start: Option<NaiveTime>,
+    quiet_hours_end: Option<NaiveTime>,
+    timezone: String,
+    digest_frequency: String,
+    updated_at: Option<DateTime<Utc>>,
+    /// 时区当前相对 UTC 的偏移（由数据库按 IANA 时区计算）
+    utc_offset_secs: i32,
+}
+
+impl PreferencesRow {
+    fn policy(&self, digest_hour: u32) -> DeliveryPolicy {
+        DeliveryPolicy {
+            quiet_hours: self.quiet_hours_start.zip(self.quiet_hours_end),
+            utc_offset: FixedOffset::east_opt(self.utc_offset_secs)
+                .unwrap_or_else(|| FixedOffset::east_opt(0).expect("UTC offset")),
+            digest: DigestFrequency::parse(&self.digest_frequency)
+                .unwrap_or(DigestFrequency::Realtime),
+            digest_hour,
+        }
+    }
+
+    fn into_preferences(self) -> NotificationPreferences {
+        let format = |t: NaiveTime| t.format("%H:%M").to_string();
+        NotificationPreferences {
+            email_enabled: self.email_enabled,
+            push_enabled: self.push_enabled,
+            webhook_url: self.webhook_url,
+            webhook_secret: self.webhook_secret,
+            muted_kinds: self.muted_kinds,
+            quiet_hours_start: self.quiet_hours_start.map(format),
+            quiet_hours_end: self.quiet_hours_end.map(format),
+            timezone: self.timezone,
+            digest_frequency: self.digest_frequency,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            email_enabled: false,
+            push_enabled: true,
+            webhook_url: None,
+            webhook_secret: None,
+            muted_kinds: Vec::new(),
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            timezone: "Asia/Shanghai".to_string(),
+            digest_frequency: DigestFrequency::Realtime.as_str().to_string(),
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct NotificationQuery {
+    /// 只返回未读
+    pub unread_only: bool,
+    pub kind: Option<String>,
+    /// 包含已忽略的通知
+    pub include_dismissed: bool,
+    /// 分页游标：返回早于该时间的通知
+    pub before: Option<DateTime<Utc>>,
+    /// 默认 50，最多 200
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct KindCount {
+    pub kind: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UnreadCount {
+    pub total: i64,
+    pub by_kind: Vec<KindCount>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct MarkAllReadRequest {
+    /// 只标记该类型，为空时全部标记
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MarkAllReadResult {
+    pub updated: usize,
+}
+
+/// 一次投递批次的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryRunStats {
+    pub sent: usize,
+    pub retried: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DueDelivery {
+    #[sqlx(rename = "delivery_id")]
+    id: Uuid,
+    channel: String,
+    attempts: i32,
+    email: Option<String>,
+    webhook_url: Option<String>,
+    webhook_secret: Option<String>,
+    #[sqlx(flatten)]
+    notification: Notification,
+}
+
+/// Webhook 请求体
+pub fn webhook_payload(delivery_id: Uuid, notification: &Notification) -> serde_json::Value {
+    json!({
+        "delivery_id": delivery_id,
+        "event": "notification.created",
+        "notification": {
+            "id": notification.id,
+            "family_id": notification.family_id,
+            "kind": notification.kind,
+            "priority": notification.priority,
+            "title": notification.title,
+            "body": notification.body,
+            "data": notification.data,
+            "action_url": notification.action_url,
+            "created_at": notification.created_at,
+        },
+    })
+}
+
+/// 发送签名的 Webhook 请求；2xx 视为成功
+pub async fn post_signed(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    body: &[u8],
+) -> Result<(), String> {
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header(
+            SIGNATURE_HEADER,
+            signature_header(secret.as_bytes(), Utc::now().timestamp(), body),
+        )
+        .body(body.to_vec())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+/// 生成 Webhook 签名密钥
+pub fn generate_webhook_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Webhook 地址只允许 http(s)，且主机只能解析到公网地址（防止借 Webhook 访问内网服务）；
+/// 保存时与每次发送前都会检查
+pub async fn validate_webhook_url(url: &str) -> Result<(), ServiceError> {
+    match outbound::check_url(url, &["http", "https"]).await {
+        Ok(_) => Ok(()),
+        Err(OutboundError::InvalidUrl) => Err(ServiceError::validation("Webhook 地址格式不正确")),
+        Err(OutboundError::Scheme) => {
+            Err(ServiceError::validation("Webhook 地址必须是 http(s) URL"))
+        }
+        Err(OutboundError::Unresolvable) => Err(ServiceError::validation("Webhook 地址无法解析")),
+        Err(OutboundError::NonPublic) => Err(ServiceError::validation(
+            "Webhook 地址不能指向本机、内网或保留地址",
+        )),
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, ServiceError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| ServiceError::validation("时间格式应为 HH:MM"))
+}
+
+pub struct NotificationService {
+    pool: PgPool,
+    /// 摘要发送时刻（本地小时）
+    digest_hour: u32,
+    /// 向用户的在线连接实时推送
+    ws: Arc<WsConnectionManager>,
+}
+
+impl NotificationService {
+    pub fn new(pool: PgPool, config: &NotificationConfig, ws: Arc<WsConnectionManager>) -> Self {
+        Self {
+            pool,
+            digest_hour: config.digest_hour,
+            ws,
+        }
+    }
+
+    async fn preferences_row(&self, user_id: Uuid) -> Result<Option<PreferencesRow>, ServiceError> {
+        Ok(sqlx::query_as(
+            r#"
+            SELECT email_enabled, push_enabled, webhook_url, webhook_secret, muted_kinds, quiet_hours_start,
+                   quiet_hours_end, timezone, digest_frequency, updated_at,
+                   EXTRACT(EPOCH FROM (NOW() AT TIME ZONE timezone) - (NOW() AT TIME ZONE 'UTC'))::INT
+                       AS utc_offset_secs
+            FROM notification_preferences
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// 保存通知、推送到用户的在线连接并安排站外渠道投递；
+    /// 用户屏蔽了该类型时不保存，返回 None
+    pub async fn notify(&self, new: NewNotification) -> Result<Option<Notification>, ServiceError> {
+        let preferences = self.preferences_row(new.user_id).await?;
+        if new.priority != NotificationPriority::Urgent
+            && preferences
+                .as_ref()
+                .is_some_and(|p| p.muted_kinds.contains(&new.kind))
+        {
+            return Ok(None);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let notification = sqlx::query_as::<_, Notification>(&format!(
+            r#"
+            INSERT INTO notifications (user_id, family_id, kind, priority, title, body, data, action_url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {}
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(new.user_id)
+        .bind(new.family_id)
+        .bind(&new.kind)
+        .bind(new.priority.as_str())
+        .bind(&new.title)
+        .bind(&new.body)
+        .bind(&new.data)
+        .bind(&new.action_url)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let now = Utc::now();
+        let policy = preferences.as_ref().map(|p| p.policy(self.digest_hour));
+        let mut deliveries = Vec::new();
+        if let (Some(preferences), Some(policy)) = (&preferences, &policy) {
+            if preferences.email_enabled && !OWN_EMAIL_KINDS.contains(&new.kind.as_str()) {
+                if let Some(at) = policy.email_after(now, new.priority) {
+                    deliveries.push(("email", at));
+                }
+            }
+            if preferences.webhook_url.is_some() {
+                deliveries.push(("webhook", policy.realtime_after(now, new.priority)));
+            }
+        }
+        // 没有偏好记录时默认推送到已注册的设备
+        if preferences.as_ref().is_none_or(|p| p.push_enabled) {
+            let has_device: bool =
+                sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM push_devices WHERE user_id = $1)")
+                    .bind(new.user_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            if has_device {
+                let at = policy.map_or(now, |p| p.realtime_after(now, new.priority));
+                deliveries.push(("push", at));
+            }
+        }
+        for (channel, deliver_after) in deliveries {
+            sqlx::query(
+                r#"
+                INSERT INTO notification_deliveries (notification_id, user_id, channel, deliver_after)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(notification.id)
+            .bind(new.user_id)
+            .bind(channel)
+            .bind(deliver_after)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.push(&notification).await;
+        Ok(Some(notification))
+    }
+
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        query: &NotificationQuery,
+    ) -> Result<Vec<Notification>, ServiceError> {
+        Ok(sqlx::query_as(&format!(
+            r#"
+            SELECT {}
+            FROM notifications
+            WHERE user_id = $1
+              AND (NOT $2 OR read_at IS NULL)
+              AND ($3::text IS NULL OR kind = $3)
+              AND ($4 OR dismissed_at IS NULL)
+              AND ($5::timestamptz IS NULL OR created_at < $5)
+            ORDER BY created_at DESC
+            LIMIT $6
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(query.unread_only)
+        .bind(&query.kind)
+        .bind(query.include_dismissed)
+        .bind(query.before)
+        .bind(
+            query
+                .limit
+                .unwrap_or(DEFAULT_LIST_LIMIT)
+                .clamp(1, MAX_LIST_LIMIT),
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn unread_count(&self, user_id: Uuid) -> Result<UnreadCount, ServiceError> {
+        let by_kind: Vec<KindCount> = sqlx::query_as(
+            r#"
+            SELECT kind, COUNT(*) AS count
+            FROM notifications
+            WHERE user_id = $1 AND read_at IS NULL AND dismissed_at IS NULL
+            GROUP BY kind
+            ORDER BY kind
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(UnreadCount {
+            total: by_kind.iter().map(|k| k.count).sum(),
+            by_kind,
+        })
+    }
+
+    pub async fn mark_read(&self, user_id: Uuid, id: Uuid) -> Result<Notification, ServiceError> {
+        let notification = sqlx::query_as::<_, Notification>(&format!(
+            r#"
+            UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            RETURNING {}
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("Notification", id))?;
+        self.sync_read(user_id, vec![id]).await;
+        Ok(notification)
+    }
+
+    pub async fn mark_all_read(
+        &self,
+        user_id: Uuid,
+        kind: Option<&str>,
+    ) -> Result<MarkAllReadResult, ServiceError> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE notifications SET read_at = NOW()
+            WHERE user_id = $1 AND read_at IS NULL AND ($2::text IS NULL OR kind = $2)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(kind)
+        .fetch_all(&self.pool)
+        .await?;
+        let updated = ids.len();
+        if updated > 0 {
+            self.sync_read(user_id, ids).await;
+        }
+        Ok(MarkAllReadResult { updated })
+    }
+
+    /// 忽略通知：从收件箱隐藏，同时视为已读
+    pub async fn dismiss(&self, user_id: Uuid, id: Uuid) -> Result<(), ServiceError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET dismissed_at = COALESCE(dismissed_at, NOW()), read_at = COALESCE(read_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("Notification", id));
+        }
+        self.sync_read(user_id, vec![id]).await;
+        Ok(())
+    }
+
+    /// 把已读状态同步到用户的其他在线设备
+    async fn sync_read(&self, user_id: Uuid, ids: Vec<Uuid>) {
+        let unread_count = match self.unread_count(user_id).await {
+            Ok(count) => count.total,
+            Err(e) => {
+                tracing::warn!("Failed to count unread notifications: {:?}", e);
+                return;
+            }
+        };
+        self.ws
+            .send_to_user(user_id, &WsMessage::NotificationsRead { ids, unread_count })
+            .await;
+    }
+
+    /// 推送失败不影响通知落库，用户之后仍可查询
+    async fn push(&self, notification: &Notification) -> usize {
+        let message = WsMessage::Notification {
+            id: notification.id,
+            kind: notification.kind.clone(),
+            priority: notification.priority.clone(),
+            title: notification.title.clone(),
+            body: notification.body.clone(),
+            data: notification.data.clone(),
+            action_url: notification.action_url.clone(),
+            created_at: notification.created_at,
+        };
+        self.ws.send_to_user(notification.user_id, &message).await
+    }
+
+    pub async fn preferences(
+        &self,
+        user_id: Uuid,
+    ) -> Result<NotificationPreferences, ServiceError> {
+        Ok(self
+            .preferences_row(user_id)
+            .await?
+            .map(PreferencesRow::into_preferences)
+            .unwrap_or_default())
+    }
+
+    pub async fn update_preferences(
+        &self,
+        user_id: Uuid,
+        req: UpdatePreferencesRequest,
+    ) -> Result<NotificationPreferences, ServiceError> {
+        let webhook_url = req
+            .webhook_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|u| !u.is_empty());
+        if let Some(url) = webhook_url {
+            validate_webhook_url(url).await?;
+        }
+        let quiet_hours = match (
+            req.quiet_hours_start
+                .as_deref()
+                .filter(|s| !s.trim().is_empty()),
+            req.quiet_hours_end
+                .as_deref()
+                .filter(|s| !s.trim().is_empty()),
+        ) {
+            (Some(start), Some(end)) => Some((parse_time(start)?, parse_time(end)?)),
+            (None, None) => None,
+            _ => return Err(ServiceError::validation("免打扰开始与结束时间需要同时设置")),
+        };
+        let digest = req.digest_frequency.as_deref().unwrap_or("realtime");
+        if DigestFrequency::parse(digest).is_none() {
+            return Err(ServiceError::validation(
+                "digest_frequency 只能是 realtime、daily、weekly 或 never",
+            ));
+        }
+        let timezone = req
+            .timezone
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .unwrap_or("Asia/Shanghai");
+        let known: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+                .bind(timezone)
+                .fetch_one(&self.pool)
+                .await?;
+        if !known {
+            return Err(ServiceError::validation(format!("未知时区：{}", timezone)));
+        }
+        let mut muted_kinds: Vec<String> = req
+            .muted_kinds
+            .iter()
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+        muted_kinds.sort();
+        muted_kinds.dedup();
+        if muted_kinds.len() > MAX_MUTED_KINDS {
+            return Err(ServiceError::validation("屏蔽的通知类型过多"));
+        }
+
+        // 设置 Webhook 时沿用原密钥（除非要求轮换），移除 Webhook 时一并清除密钥
+        let existing_secret = self
+            .preferences_row(user_id)
+            .await?
+            .and_then(|p| p.webhook_secret);
+        let webhook_secret = webhook_url.map(|_| match existing_secret {
+            Some(secret) if !req.rotate_webhook_secret => secret,
+            _ => generate_webhook_secret(),
+        });
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (
+                user_id, email_enabled, webhook_url, webhook_secret, muted_kinds,
+                quiet_hours_start, quiet_hours_end, timezone, digest_frequency, push_enabled
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (user_id) DO UPDATE
+            SET email_enabled = EXCLUDED.email_enabled,
+                push_enabled = EXCLUDED.push_enabled,
+                webhook_url = EXCLUDED.webhook_url,
+                webhook_secret = EXCLUDED.webhook_secret,
+                muted_kinds = EXCLUDED.muted_kinds,
+                quiet_hours_start = EXCLUDED.quiet_hours_start,
+                quiet_hours_end = EXCLUDED.quiet_hours_end,
+                timezone = EXCLUDED.timezone,
+                digest_frequency = EXCLUDED.digest_frequency,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(req.email_enabled)
+        .bind(webhook_url)
+        .bind(&webhook_secret)
+        .bind(&muted_kinds)
+        .bind(quiet_hours.map(|q| q.0))
+        .bind(quiet_hours.map(|q| q.1))
+        .bind(timezone)
+        .bind(digest)
+        .bind(req.push_enabled)
+        .execute(&self.pool)
+        .await?;
+
+        self.preferences(user_id).await
+    }
+
+    /// 领取到期的站外投递并发送：邮件按用户合并后写入发件箱，Webhook 直接 POST，
+    /// 设备推送交给对应的推送服务
+    pub async fn deliver_due(
+        &self,
+        client: &reqwest::Client,
+        push: &PushRegistry,
+        outbox: &EmailOutbox,
+        batch_size: i64,
+        max_attempts: u32,
+    ) -> Result<DeliveryRunStats, ServiceError> {
+        let due: Vec<DueDelivery> = sqlx::query_as(&format!(
+            r#"
+            WITH claimed AS (
+                UPDATE notification_deliveries
+                SET status = 'sending', locked_at = NOW()
+                WHERE id IN (
+                    SELECT id FROM notification_deliveries
+                    WHERE (status = 'pending' AND deliver_after <= NOW())
+                       OR (status = 'sending' AND locked_at < NOW() - make_interval(mins => $2))
+                    ORDER BY deliver_after
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, notification_id, channel, attempts
+            )
+            SELECT c.id AS delivery_id, c.channel, c.attempts, u.email, p.webhook_url, p.webhook_secret,
+                   {}
+            FROM claimed c
+            JOIN notifications n ON n.id = c.notification_id
+            JOIN users u ON u.id = n.user_id
+            LEFT JOIN notification_preferences p ON p.user_id = n.user_id
+            ORDER BY n.created_at
+            "#,
+            NOTIFICATION_COLUMNS
+                .split(", ")
+                .map(|c| format!("n.{}", c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .bind(batch_size.max(1))
+        .bind(STALE_LOCK_MINUTES as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = DeliveryRunStats::default();
+        let mut emails: BTreeMap<Uuid, Vec<&DueDelivery>> = BTreeMap::new();
+        for delivery in &due {
+            match delivery.channel.as_str() {
+                "email" => emails
+                    .entry(delivery.notification.user_id)
+                    .or_default()
+                    .push(delivery),
+                "push" => {
+                    self.deliver_push(push, delivery, max_attempts, &mut stats)
+                        .await?
+                }
+                _ => {
+                    self.deliver_webhook(client, delivery, max_attempts, &mut stats)
+                        .await?
+                }
+            }
+        }
+        for deliveries in emails.values() {
+            self.deliver_email(outbox, deliveries, &mut stats).await?;
+        }
+        Ok(stats)
+    }
+
+    async fn finish(
+        &self,
+        id: Uuid,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"
+            UPDATE notification_deliveries
+            SET status = $2, last_error = $3, locked_at = NULL,
+                delivered_at = CASE WHEN $2 = 'sent' THEN NOW() END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn deliver_webhook(
+        &self,
+        client: &reqwest::Client,
+        delivery: &DueDelivery,
+        max_attempts: u32,
+        stats: &mut DeliveryRunStats,
+    ) -> Result<(), ServiceError> {
+        // 用户之后移除了 Webhook
+        let (Some(url), Some(secret)) = (&delivery.webhook_url, &delivery.webhook_secret) else {
+            stats.skipped += 1;
+            return self
+                .finish(delivery.id, "skipped", Some("webhook removed"))
+                .await;
+        };
+        // 地址的解析结果可能在保存后改变，发送前再检查一次
+        match outbound::check_url(url, &["http", "https"]).await {
+            Ok(_) => {}
+            Err(OutboundError::Unresolvable) => {
+                return self
+                    .reschedule(delivery, "host could not be resolved", max_attempts, stats)
+                    .await;
+            }
+            Err(e) => {
+                stats.failed += 1;
+                return self
+                    .finish(delivery.id, "failed", Some(&e.to_string()))
+                    .await;
+            }
+        }
+        let body = serde_json::to_vec(&webhook_payload(delivery.id, &delivery.notification))?;
+        match post_signed(client, url, secret, &body).await {
+            Ok(()) => {
+                stats.sent += 1;
+                self.finish(delivery.id, "sent", None).await
+            }
+            Err(error) => self.reschedule(delivery, &error, max_attempts, stats).await,
+        }
+    }
+
+    /// 推送到用户的全部设备：失效的令牌删除设备；只要有设备送达或不存在可重试的失败即视为完成
+    async fn deliver_push(
+        &self,
+        push: &PushRegistry,
+        delivery: &DueDelivery,
+        max_attempts: u32,
+        stats: &mut DeliveryRunStats,
+    ) -> Result<(), ServiceError> {
+        if delivery.notification.read_at.is_some() || delivery.notification.dismissed_at.is_some() {
+            stats.skipped += 1;
+            return self
+                .finish(delivery.id, "skipped", Some("already read"))
+                .await;
+        }
+        let devices = PushDeviceService::new(self.pool.clone());
+        let targets = devices.targets(delivery.notification.user_id).await?;
+        let message = PushMessage::from_notification(&delivery.notification);
+        let mut delivered = 0;
+        let mut retryable: Option<String> = None;
+        for target in &targets {
+            let Some(sender) = push.get(target.provider) else {
+                continue;
+            };
+            match sender.send(target, &message).await {
+                Ok(()) => {
+                    delivered += 1;
+                    devices.record_success(target.device_id).await?;
+                }
+                Err(PushError::InvalidToken(reason)) => {
+                    devices.prune(target.device_id, &reason).await?;
+                }
+                Err(PushError::Failed(error)) => {
+                    devices.record_failure(target.device_id, &error).await?;
+                    retryable = Some(error);
+                }
+            }
+        }
+
+        match retryable {
+            Some(error) if delivered == 0 => {
+                self.reschedule(delivery, &error, max_attempts, stats).await
+            }
+            _ if delivered > 0 => {
+                stats.sent += 1;
+                self.finish(delivery.id, "sent", None).await
+            }
+            _ => {
+                stats.skipped += 1;
+                self.finish(delivery.id, "skipped", Some("no reachable push device"))
+                    .await
+            }
+        }
+    }
+
+    /// 投递失败：按指数退避重新排队，超过最大次数后标记为 failed
+    async fn reschedule(
+        &self,
+        delivery: &DueDelivery,
+        error: &str,
+        max_attempts: u32,
+        stats: &mut DeliveryRunStats,
+    ) -> Result<(), ServiceError> {
+        let attempts = delivery.attempts + 1;
+        if attempts as u32 >= max_attempts {
+            tracing::warn!(delivery = %delivery.id, channel = %delivery.channel, "Notification delivery failed permanently: {}", error);
+            stats.failed += 1;
+            sqlx::query(
+                r#"
+                UPDATE notification_deliveries
+                SET status = 'failed', attempts = $2, last_error = $3, locked_at = NULL
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(error)
+            .execute(&self.pool)
+            .await?;
+        } else {
+            stats.retried += 1;
+            let delay = super::email::outbox::retry_delay(attempts as u32);
+            sqlx::query(
+                r#"
+                UPDATE notification_deliveries
+                SET status = 'pending', attempts = $2, last_error = $3, locked_at = NULL,
+                    deliver_after = NOW() + make_interval(secs => $4)
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(error)
+            .bind(delay.as_secs() as f64)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 同一用户同一批到期的邮件合并为一封；已读或已忽略的通知不再发送
+    async fn deliver_email(
+        &self,
+        outbox: &EmailOutbox,
+        deliveries: &[&DueDelivery],
+        stats: &mut DeliveryRunStats,
+    ) -> Result<(), ServiceError> {
+        let (pending, seen): (Vec<&DueDelivery>, Vec<&DueDelivery>) =
+            deliveries.iter().partition(|d| {
+                d.notification.read_at.is_none() && d.notification.dismissed_at.is_none()
+            });
+        for delivery in seen {
+            stats.skipped += 1;
+            self.finish(delivery.id, "skipped", Some("already read"))
+                .await?;
+        }
+        let Some(first) = pending.first() else {
+            return Ok(());
+        };
+
+        let template = match pending.as_slice() {
+            [only] => EmailTemplate::Notification {
+                title: only.notification.title.clone(),
+                body: only.notification.body.clone(),
+                action_url: only.notification.action_url.clone(),
+            },
+            many => EmailTemplate::NotificationDigest {
+                items: many
+                    .iter()
+                    .map(|d| (d.notification.title.clone(), d.notification.body.clone()))
+                    .collect(),
+            },
+        };
+        let queued = match first.email.as_deref() {
+            Some(email) => outbox.enqueue_for_user(email, &template).await?,
+            None => None,
+        };
+        let (status, error) = match queued {
+            Some(_) => ("sent", None),
+            None => ("skipped", Some("no deliverable email address")),
+        };
+        for delivery in &pending {
+            if queued.is_some() {
+                stats.sent += 1;
+            } else {
+                stats.skipped += 1;
+            }
+            self.finish(delivery.id, status, error).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn policy(quiet: Option<(&str, &str)>, digest: DigestFrequency) -> DeliveryPolicy {
+        DeliveryPolicy {
+            quiet_hours: quiet.map(|(s, e)| (parse_time(s).unwrap(), parse_time(e).unwrap())),
+            // Asia/Shanghai
+            utc_offset: FixedOffset::east_opt(8 * 3600).unwrap(),
+            digest,
+            digest_hour: 8,
+        }
+    }
+
+    /// 上海本地时间 → UTC
+    fn local(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(8 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 10, d, h, m, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_quiet_hours_defer_delivery() {
+        let overnight = policy(Some(("22:00", "07:30")), DigestFrequency::Realtime);
+        // 2026-10-16 是星期五
+        assert_eq!(
+            overnight.after_quiet_hours(local(16, 21, 59)),
+            local(16, 21, 59)
+        );
+        assert_eq!(
+            overnight.after_quiet_hours(local(16, 23, 10)),
+            local(17, 7, 30)
+        );
+        assert_eq!(
+            overnight.after_quiet_hours(local(17, 3, 0)),
+            local(17, 7, 30)
+        );
+        assert_eq!(
+            overnight.after_quiet_hours(local(17, 7, 30)),
+            local(17, 7, 30)
+        );
+
+        let lunch = policy(Some(("12:00", "13:00")), DigestFrequency::Realtime);
+        assert_eq!(lunch.after_quiet_hours(local(16, 12, 15)), local(16, 13, 0));
+        assert_eq!(lunch.after_quiet_hours(local(16, 23, 0)), local(16, 23, 0));
+
+        // 紧急通知不受影响
+        assert_eq!(
+            overnight.realtime_after(local(16, 23, 10), NotificationPriority::Urgent),
+            local(16, 23, 10)
+        );
+        assert_eq!(
+            overnight.email_after(local(16, 23, 10), NotificationPriority::Normal),
+            Some(local(17, 7, 30))
+        );
+    }
+
+    #[test]
+    fn test_digest_schedule() {
+        let daily = policy(None, DigestFrequency::Daily);
+        assert_eq!(
+            daily.email_after(local(16, 6, 0), NotificationPriority::High),
+            Some(local(16, 8, 0))
+        );
+        assert_eq!(
+            daily.email_after(local(16, 8, 0), NotificationPriority::Low),
+            Some(local(17, 8, 0))
+        );
+
+        // 每周一发送
+        let weekly = policy(None, DigestFrequency::Weekly);
+        assert_eq!(
+            weekly.email_after(local(16, 9, 0), NotificationPriority::Normal),
+            Some(local(19, 8, 0))
+        );
+        assert_eq!(
+            weekly.email_after(local(19, 7, 0), NotificationPriority::Normal),
+            Some(local(19, 8, 0))
+        );
+
+        // 摘要时刻落在免打扰时段内
+        let late = policy(Some(("06:00", "09:00")), DigestFrequency::Daily);
+        assert_eq!(
+            late.email_after(local(16, 10, 0), NotificationPriority::Normal),
+            Some(local(17, 9, 0))
+        );
+
+        let never = policy(None, DigestFrequency::Never);
+        assert_eq!(
+            never.email_after(local(16, 10, 0), NotificationPriority::High),
+            None
+        );
+        assert_eq!(
+            never.email_after(local(16, 10, 0), NotificationPriority::Urgent),
+            Some(local(16, 10, 0))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_webhook_delivery() {
+        use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Router};
+        use std::sync::{Arc, Mutex};
+
+        type Seen = Arc<Mutex<Vec<(String, Bytes)>>>;
+        async fn receive(
+            State(seen): State<Seen>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> axum::http::StatusCode {
+            let signature = headers
+                .get(SIGNATURE_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            seen.lock().unwrap().push((signature, body));
+            axum::http::StatusCode::NO_CONTENT
+        }
+
+        let seen: Seen = Arc::default();
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .route("/gone", post(|| async { axum::http::StatusCode::GONE }))
+            .with_state(seen.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let notification = Notification {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            family_id: None,
+            kind: "credit_card_due".to_string(),
+            priority: "high".to_string(),
+            title: "还款提醒".to_string(),
+            body: "10-25 到期".to_string(),
+            data: json!({ "card_id": 1 }),
+            action_url: None,
+            read_at: None,
+            dismissed_at: None,
+            created_at: Utc::now(),
+        };
+        let secret = generate_webhook_secret();
+        let body = serde_json::to_vec(&webhook_payload(Uuid::new_v4(), &notification)).unwrap();
+        let client = reqwest::Client::new();
+        post_signed(&client, &format!("{}/hook", base), &secret, &body)
+            .await
+            .unwrap();
+        assert_eq!(
+            post_signed(&client, &format!("{}/gone", base), &secret, &body).await,
+            Err("HTTP 410 Gone".to_string())
+        );
+
+        let (signature, received) = seen.lock().unwrap().pop().unwrap();
+        crate::utils::signature::verify_header(
+            secret.as_bytes(),
+            &signature,
+            &received,
+            Utc::now().timestamp(),
+            300,
+        )
+        .unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&received).unwrap();
+        assert_eq!(payload["event"], "notification.created");
+        assert_eq!(payload["notification"]["kind"], "credit_card_due");
+
+        assert!(validate_webhook_url("https://93.184.216.34/hook")
+            .await
+            .is_ok());
+        assert!(
+            validate_webhook_url("http://169.254.169.254/latest/meta-data")
+                .await
+                .is_err()
+        );
+        assert!(validate_webhook_url("http://localhost:5432").await.is_err());
+        assert!(
+            validate_webhook_url("http://192.168.1.10:8123/api/webhook/x")
+                .await
+                .is_err()
+        );
+        assert!(validate_webhook_url("ftp://example.com").await.is_err());
+        assert!(validate_webhook_url("not a url").await.is_err());
+    }
+}
@@ -0,0 +1,472 @@
+//! 找回密码服务
+//!
+//! 通过邮件发送一次性验证码重置密码：
+//! - 仅保存验证码的 HMAC-SHA256（服务端密钥），单次有效，15 分钟过期，错误次数超限即作废
+//! - 优先存 Redis，Redis 不可用时回退到 password_reset_tokens 表
+//! - 每个用户在 1 小时窗口内最多发 3 次验证码、验证失败 10 次（password_reset_limits），
+//!   重新申请只会作废旧验证码，不会重置失败次数
+//! - 邮箱是否注册不影响响应时间：发码在后台完成，未注册邮箱的验证走同样的查询
+//! - 重置成功后吊销该用户所有已签发的令牌并写审计日志
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use sha2::Sha256;
+use sqlx::{PgPool, Row};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use super::email::{EmailOutbox, EmailTemplate};
+use super::{AuditService, ServiceError};
+use crate::config::AppConfig;
+use crate::models::audit::AuditAction;
+use crate::utils::password::generate_argon2_hash;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 验证码有效期（秒）
+const RESET_CODE_TTL_SECS: u64 = 15 * 60;
+/// 单个验证码允许的错误次数
+const MAX_VERIFY_ATTEMPTS: i64 = 5;
+/// 每用户限额的统计窗口（秒）
+const LIMIT_WINDOW_SECS: u64 = 60 * 60;
+/// 窗口内最多发出的验证码数
+const MAX_CODES_PER_WINDOW: i32 = 3;
+/// 窗口内所有验证码累计允许的错误次数
+const MAX_FAILED_PER_WINDOW: i32 = 10;
+/// 新密码最小长度
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+#[derive(Clone)]
+pub struct PasswordResetService {
+    pool: PgPool,
+    redis: Option<ConnectionManager>,
+    outbox: EmailOutbox,
+    code_secret: String,
+}
+
+impl PasswordResetService {
+    pub fn new(pool: PgPool, redis: Option<ConnectionManager>, config: &AppConfig) -> Self {
+        Self {
+            outbox: EmailOutbox::new(pool.clone(), &config.email),
+            code_secret: config.password_reset.code_secret.clone(),
+            pool,
+            redis,
+        }
+    }
+
+    /// 生成 6 位数字验证码
+    pub fn generate_code() -> String {
+        let code: u32 = rand::thread_rng().gen_range(0..1_000_000);
+        format!("{:06}", code)
+    }
+
+    /// 验证码哈希：以服务端密钥做 HMAC，并绑定用户 ID（避免不同用户的相同验证码哈希一致）
+    pub fn hash_code(secret: &str, user_id: Uuid, code: &str) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC key of any length");
+        mac.update(user_id.as_bytes());
+        mac.update(b":");
+        mac.update(code.trim().as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 申请重置：只做邮箱查询即返回，发码在后台完成，响应时间不暴露邮箱是否注册
+    pub async fn request_reset(
+        &self,
+        email: &str,
+        ip: Option<IpAddr>,
+        user_agent: Option<&str>,
+    ) -> Result<(), ServiceError> {
+        let Some((user_id, user_email)) = self.find_active_user(email).await? else {
+            tracing::debug!("password reset requested for unknown or inactive account");
+            return Ok(());
+        };
+
+        let service = self.clone();
+        let user_agent = user_agent.map(|s| s.to_string());
+        tokio::spawn(async move {
+            if let Err(e) = service
+                .issue_code(user_id, &user_email, ip, user_agent)
+                .await
+            {
+                tracing::error!(user_id = %user_id, error = ?e, "failed to issue password reset code");
+            }
+        });
+        Ok(())
+    }
+
+    /// 作废旧验证码并发送新验证码；超过窗口内的发码上限时不再发送
+    async fn issue_code(
+        &self,
+        user_id: Uuid,
+        user_email: &str,
+        ip: Option<IpAddr>,
+        user_agent: Option<String>,
+    ) -> Result<(), ServiceError> {
+        let (codes_issued, _) = self.bump_limits(user_id, 1, 0).await?;
+        if codes_issued > MAX_CODES_PER_WINDOW {
+            tracing::warn!(user_id = %user_id, "password reset code limit reached");
+            return Ok(());
+        }
+
+        // 同一用户只保留最新的验证码
+        self.invalidate_codes(user_id).await?;
+
+        let code = Self::generate_code();
+        let token_hash = Self::hash_code(&self.code_secret, user_id, &code);
+        if !self.store_in_redis(user_id, &token_hash).await {
+            self.store_in_db(user_id, &token_hash, ip).await?;
+        }
+
+        let template = EmailTemplate::PasswordReset {
+            code,
+            expires_minutes: (RESET_CODE_TTL_SECS / 60) as u32,
+        };
+        self.outbox.enqueue_for_user(user_email, &template).await?;
+
+        AuditService::new(self.pool.clone())
+            .log_user_event(
+                user_id,
+                AuditAction::PasswordResetRequested,
+                None,
+                ip.map(|ip| ip.to_string()),
+                user_agent,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// 使用验证码重置密码，成功返回用户 ID
+    pub async fn reset_password(
+        &self,
+        email: &str,
+        code: &str,
+        new_password: &str,
+        ip: Option<IpAddr>,
+        user_agent: Option<&str>,
+    ) -> Result<Uuid, ServiceError> {
+        if new_password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(ServiceError::ValidationError(format!(
+                "密码长度至少 {} 位",
+                MIN_PASSWORD_LEN
+            )));
+        }
+
+        let invalid = || ServiceError::AuthenticationError("验证码无效或已过期".to_string());
+        // 未注册的邮箱用随机 ID 走同样的查询（不会命中任何记录），响应时间不暴露邮箱是否注册
+        let user = self.find_active_user(email).await?;
+        let user_id = user
+            .as_ref()
+            .map(|(id, _)| *id)
+            .unwrap_or_else(Uuid::new_v4);
+
+        if self.failed_attempts(user_id).await? >= MAX_FAILED_PER_WINDOW {
+            self.invalidate_codes(user_id).await?;
+            return Err(invalid());
+        }
+
+        let token_hash = Self::hash_code(&self.code_secret, user_id, code);
+        let consumed = match self.consume_from_redis(user_id, &token_hash).await {
+            Some(true) => true,
+            // Redis 中没有（或 Redis 故障）时检查数据库回退存储
+            Some(false) | None => self.consume_from_db(user_id, &token_hash).await?,
+        };
+        if !consumed || user.is_none() {
+            self.bump_limits(user_id, 0, 1).await?;
+            return Err(invalid());
+        }
+
+        let new_hash =
+            generate_argon2_hash(new_password).map_err(|_| ServiceError::InternalError)?;
+
+        let mut tx = self.pool.begin().await?;
+        // 新密码生效，同时解除登录锁定并吊销所有已签发令牌
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET password_hash = $2,
+                failed_login_attempts = 0,
+                locked_until = NULL,
+                sessions_revoked_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(new_hash)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE user_sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM password_reset_limits WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        crate::auth::forget_revocation(user_id);
+
+        AuditService::new(self.pool.clone())
+            .log_user_event(
+                user_id,
+                AuditAction::PasswordReset,
+                Some(serde_json::json!({ "sessions_revoked": true })),
+                ip.map(|ip| ip.to_string()),
+                user_agent.map(|s| s.to_string()),
+            )
+            .await?;
+
+        Ok(user_id)
+    }
+
+    async fn find_active_user(&self, email: &str) -> Result<Option<(Uuid, String)>, ServiceError> {
+        let row = sqlx::query(
+            "SELECT id, email FROM users WHERE LOWER(email) = LOWER($1) AND COALESCE(is_active, true)",
+        )
+        .bind(email.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some((row.try_get("id")?, row.try_get("email")?))),
+            None => Ok(None),
+        }
+    }
+
+    /// 累加窗口内的发码 / 失败次数（窗口过期时重新计数），返回累加后的值；
+    /// 用户不存在时不写入并返回 (0, 0)
+    async fn bump_limits(
+        &self,
+        user_id: Uuid,
+        codes: i32,
+        failures: i32,
+    ) -> Result<(i32, i32), ServiceError> {
+        let row: Option<(i32, i32)> = sqlx::query_as(
+            r#"
+            INSERT INTO password_reset_limits AS l (user_id, codes_issued, failed_attempts)
+            SELECT id, $2, $3 FROM users WHERE id = $1
+            ON CONFLICT (user_id) DO UPDATE SET
+                codes_issued = CASE WHEN l.window_started_at <= NOW() - make_interval(secs => $4)
+                    THEN EXCLUDED.codes_issued ELSE l.codes_issued + EXCLUDED.codes_issued END,
+                failed_attempts = CASE WHEN l.window_started_at <= NOW() - make_interval(secs => $4)
+                    THEN EXCLUDED.failed_attempts ELSE l.failed_attempts + EXCLUDED.failed_attempts END,
+                window_started_at = CASE WHEN l.window_started_at <= NOW() - make_interval(secs => $4)
+                    THEN NOW() ELSE l.window_started_at END
+            RETURNING codes_issued, failed_attempts
+            "#,
+        )
+        .bind(user_id)
+        .bind(codes)
+        .bind(failures)
+        .bind(LIMIT_WINDOW_SECS as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.unwrap_or_default())
+    }
+
+    /// 当前窗口内的验证失败次数
+    async fn failed_attempts(&self, user_id: Uuid) -> Result<i32, ServiceError> {
+        let failed: Option<i32> = sqlx::query_scalar(
+            r#"
+            SELECT failed_attempts FROM password_reset_limits
+            WHERE user_id = $1 AND window_started_at > NOW() - make_interval(secs => $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(LIMIT_WINDOW_SECS as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(failed.unwrap_or(0))
+    }
+
+    /// 作废该用户尚未使用的验证码（Redis 与数据库）
+    async fn invalidate_codes(&self, user_id: Uuid) -> Result<(), ServiceError> {
+        if let Some(redis) = &self.redis {
+            let mut conn = redis.clone();
+            let deleted: redis::RedisResult<i64> = conn.del(Self::redis_key(user_id)).await;
+            if let Err(e) = deleted {
+                tracing::warn!(error = ?e, "failed to delete reset token from redis");
+            }
+        }
+        sqlx::query(
+            "UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    fn redis_key(user_id: Uuid) -> String {
+        format!("password_reset:{}", user_id)
+    }
+
+    /// 写入 Redis；未配置或失败时返回 false，由调用方回退到数据库
+    async fn store_in_redis(&self, user_id: Uuid, token_hash: &str) -> bool {
+        let Some(redis) = &self.redis else {
+            return false;
+        };
+        let mut conn = redis.clone();
+        let key = Self::redis_key(user_id);
+
+        let result: redis::RedisResult<()> = redis::pipe()
+            .atomic()
+            .del(&key)
+            .hset_multiple(&key, &[("hash", token_hash), ("attempts", "0")])
+            .expire(&key, RESET_CODE_TTL_SECS as i64)
+            .query_async(&mut conn)
+            .await;
+
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(error = ?e, "redis unavailable for reset token, falling back to database");
+                false
+            }
+        }
+    }
+
+    /// 从 Redis 校验并消费；None 表示 Redis 不可用或无记录
+    async fn consume_from_redis(&self, user_id: Uuid, token_hash: &str) -> Option<bool> {
+        let mut conn = self.redis.as_ref()?.clone();
+        let key = Self::redis_key(user_id);
+
+        let stored: Option<String> = conn.hget(&key, "hash").await.ok()?;
+        let stored = stored?;
+
+        if constant_time_eq(stored.as_bytes(), token_hash.as_bytes()) {
+            // DEL 返回 1 的请求才算消费成功，防止并发重复使用
+            let deleted: i64 = conn.del(&key).await.ok()?;
+            return Some(deleted == 1);
+        }
+
+        let attempts: i64 = conn.hincr(&key, "attempts", 1).await.ok()?;
+        if attempts >= MAX_VERIFY_ATTEMPTS {
+            let _: redis::RedisResult<i64> = conn.del(&key).await;
+        }
+        Some(false)
+    }
+
+    async fn store_in_db(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, request_ip)
+            VALUES ($1, $2, $3, NOW() + make_interval(secs => $4), $5::inet)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(RESET_CODE_TTL_SECS as f64)
+        .bind(ip.map(|ip| ip.to_string()))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn consume_from_db(&self, user_id: Uuid, token_hash: &str) -> Result<bool, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            SELECT id, token_hash
+            FROM password_reset_tokens
+            WHERE user_id = $1 AND used_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at DESC
+            LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let id: Uuid = row.try_get("id")?;
+        let stored: String = row.try_get("token_hash")?;
+
+        let matched = constant_time_eq(stored.as_bytes(), token_hash.as_bytes());
+        if matched {
+            sqlx::query("UPDATE password_reset_tokens SET used_at = NOW() WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            // 错误次数超限后作废
+            sqlx::query(
+                r#"
+                UPDATE password_reset_tokens
+                SET attempts = attempts + 1,
+                    used_at = CASE WHEN attempts + 1 >= $2 THEN NOW() ELSE used_at END
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .bind(MAX_VERIFY_ATTEMPTS as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(matched)
+    }
+}
+
+/// 常量时间比较，避免时序侧信道
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_code_is_six_digits() {
+        for _ in 0..100 {
+            let code = PasswordResetService::generate_code();
+            assert_eq!(code.len(), 6);
+            assert!(code.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_hash_code_binds_user_and_secret() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        assert_eq!(
+            PasswordResetService::hash_code("k", a, "123456"),
+            PasswordResetService::hash_code("k", a, " 123456 ")
+        );
+        assert_ne!(
+            PasswordResetService::hash_code("k", a, "123456"),
+            PasswordResetService::hash_code("k", b, "123456")
+        );
+        assert_ne!(
+            PasswordResetService::hash_code("k", a, "123456"),
+            PasswordResetService::hash_code("other", a, "123456")
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+}
@@ -0,0 +1,273 @@
+//! 推送设备注册
+//!
+//! 注册时按 User-Agent 解析 `DeviceInfo`，并关联同一设备指纹最近的有效登录会话；
+//! 会话被吊销（如重置密码）后该设备不再接收推送，并在下次推送时删除。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::webpush::{decode_base64url, is_valid_auth_secret, is_valid_p256dh};
+use super::{PushProvider, PushTarget};
+use crate::models::session::DeviceInfo;
+use crate::services::ServiceError;
+use crate::utils::outbound::{self, OutboundError};
+
+/// 每个用户最多保留的设备数，超出时删除最久未活动的设备
+const MAX_DEVICES_PER_USER: i64 = 20;
+/// 令牌长度上限（FCM 令牌约 160 字符，Web Push 端点为 URL）
+const MAX_TOKEN_LEN: usize = 2048;
+
+/// 已注册的推送设备
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct PushDevice {
+    pub id: Uuid,
+    /// webpush / fcm
+    pub provider: String,
+    /// FCM 令牌或 Web Push 订阅端点
+    pub token: String,
+    /// 注册时所在的登录会话
+    pub session_id: Option<Uuid>,
+    pub device_type: String,
+    pub os: String,
+    pub browser: Option<String>,
+    pub app_version: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub last_success_at: Option<DateTime<Utc>>,
+}
+
+/// 注册或刷新推送设备
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RegisterDeviceRequest {
+    /// webpush / fcm
+    pub provider: PushProvider,
+    /// FCM 注册令牌，或浏览器 PushSubscription 的 endpoint
+    pub token: String,
+    /// PushSubscription 的 `keys.p256dh`（仅 webpush）
+    pub p256dh: Option<String>,
+    /// PushSubscription 的 `keys.auth`（仅 webpush）
+    pub auth: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TargetRow {
+    id: Uuid,
+    provider: String,
+    token: String,
+    p256dh: Option<String>,
+    auth_secret: Option<String>,
+}
+
+const DEVICE_COLUMNS: &str = "id, provider, token, session_id, device_type, os, browser, app_version, created_at, last_seen_at, last_success_at";
+
+pub struct PushDeviceService {
+    pool: PgPool,
+}
+
+impl PushDeviceService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 注册设备；同一令牌重复注册时更新归属用户、会话与密钥
+    pub async fn register(
+        &self,
+        user_id: Uuid,
+        user_agent: Option<&str>,
+        req: &RegisterDeviceRequest,
+    ) -> Result<PushDevice, ServiceError> {
+        let token = req.token.trim();
+        if token.is_empty() || token.len() > MAX_TOKEN_LEN {
+            return Err(ServiceError::validation("推送令牌无效"));
+        }
+        let (p256dh, auth) = match req.provider {
+            PushProvider::WebPush => {
+                // 推送服务都是公网 https 地址；拒绝指向本机或内网的端点，防止借推送访问内部服务
+                outbound::check_url(token, &["https"])
+                    .await
+                    .map_err(|e| match e {
+                        OutboundError::NonPublic => {
+                            ServiceError::validation("Web Push 订阅端点不能指向本机或内网地址")
+                        }
+                        _ => ServiceError::validation("Web Push 订阅端点必须是 https URL"),
+                    })?;
+                let p256dh = req
+                    .p256dh
+                    .as_deref()
+                    .filter(|k| decode_base64url(k).is_some_and(|k| is_valid_p256dh(&k)))
+                    .ok_or_else(|| ServiceError::validation("p256dh 必须是未压缩的 P-256 公钥"))?;
+                let auth = req
+                    .auth
+                    .as_deref()
+                    .filter(|k| decode_base64url(k).is_some_and(|k| is_valid_auth_secret(&k)))
+                    .ok_or_else(|| ServiceError::validation("auth 必须是 16 字节的认证密钥"))?;
+                (Some(p256dh.trim()), Some(auth.trim()))
+            }
+            PushProvider::Fcm => (None, None),
+        };
+
+        let device = DeviceInfo::from_user_agent(user_agent.unwrap_or_default());
+        let fingerprint = device.fingerprint();
+        let session_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM user_sessions
+            WHERE user_id = $1 AND device_fingerprint = $2
+              AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY last_seen_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(&fingerprint)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let registered = sqlx::query_as::<_, PushDevice>(&format!(
+            r#"
+            INSERT INTO push_devices (
+                user_id, session_id, provider, token, p256dh, auth_secret,
+                device_fingerprint, device_type, os, browser, app_version
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (provider, token) DO UPDATE
+            SET user_id = EXCLUDED.user_id,
+                session_id = EXCLUDED.session_id,
+                p256dh = EXCLUDED.p256dh,
+                auth_secret = EXCLUDED.auth_secret,
+                device_fingerprint = EXCLUDED.device_fingerprint,
+                device_type = EXCLUDED.device_type,
+                os = EXCLUDED.os,
+                browser = EXCLUDED.browser,
+                app_version = EXCLUDED.app_version,
+                failure_count = 0,
+                last_error = NULL,
+                last_seen_at = NOW()
+            RETURNING {}
+            "#,
+            DEVICE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(session_id)
+        .bind(req.provider.as_str())
+        .bind(token)
+        .bind(p256dh)
+        .bind(auth)
+        .bind(&fingerprint)
+        .bind(&device.device_type)
+        .bind(&device.os)
+        .bind(&device.browser)
+        .bind(&device.app_version)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM push_devices
+            WHERE user_id = $1 AND id NOT IN (
+                SELECT id FROM push_devices WHERE user_id = $1
+                ORDER BY last_seen_at DESC LIMIT $2
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(MAX_DEVICES_PER_USER)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(registered)
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<PushDevice>, ServiceError> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM push_devices WHERE user_id = $1 ORDER BY last_seen_at DESC",
+            DEVICE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn unregister(&self, user_id: Uuid, id: Uuid) -> Result<(), ServiceError> {
+        let result = sqlx::query("DELETE FROM push_devices WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("PushDevice", id));
+        }
+        Ok(())
+    }
+
+    /// 用户当前可推送的设备；先删除关联会话已被吊销的设备
+    pub async fn targets(&self, user_id: Uuid) -> Result<Vec<PushTarget>, ServiceError> {
+        sqlx::query(
+            r#"
+            DELETE FROM push_devices d
+            USING user_sessions s
+            WHERE d.session_id = s.id AND d.user_id = $1 AND s.revoked_at IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        let rows: Vec<TargetRow> = sqlx::query_as(
+            r#"
+            SELECT id, provider, token, p256dh, auth_secret
+            FROM push_devices
+            WHERE user_id = $1
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(PushTarget {
+                    device_id: row.id,
+                    provider: PushProvider::parse(&row.provider)?,
+                    token: row.token,
+                    p256dh: row.p256dh,
+                    auth: row.auth_secret,
+                })
+            })
+            .collect())
+    }
+
+    /// 推送服务报告令牌失效：删除设备
+    pub async fn prune(&self, id: Uuid, reason: &str) -> Result<(), ServiceError> {
+        tracing::info!(device = %id, "Pruning push device: {}", reason);
+        sqlx::query("DELETE FROM push_devices WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn record_success(&self, id: Uuid) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE push_devices SET failure_count = 0, last_error = NULL, last_success_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_failure(&self, id: Uuid, error: &str) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE push_devices SET failure_count = failure_count + 1, last_error = $2 WHERE id = $1",
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
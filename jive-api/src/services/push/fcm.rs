@@ -0,0 +1,302 @@
+//! Firebase Cloud Messaging HTTP v1
+//!
+//! 用服务账号私钥签发 RS256 JWT 换取 OAuth 访问令牌（缓存到过期前一分钟），
+//! 再调用 `projects/{project_id}/messages:send`。
+
+use async_trait::async_trait;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{PushError, PushMessage, PushSender, PushTarget};
+use crate::services::ServiceError;
+
+const FCM_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+/// 访问令牌在过期前多久刷新（秒）
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 60;
+
+fn default_token_uri() -> String {
+    DEFAULT_TOKEN_URI.to_string()
+}
+
+/// 服务账号（Firebase 控制台下载的 JSON 中用到的字段）
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceAccount {
+    pub project_id: String,
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default = "default_token_uri")]
+    pub token_uri: String,
+}
+
+impl ServiceAccount {
+    pub fn from_json(json: &str) -> Result<Self, ServiceError> {
+        serde_json::from_str(json)
+            .map_err(|e| ServiceError::validation(format!("FCM 服务账号 JSON 无效: {}", e)))
+    }
+
+    /// `PUSH_FCM_CREDENTIALS` 可以是 JSON 原文或文件路径
+    pub fn load(credentials: &str) -> Result<Self, ServiceError> {
+        let trimmed = credentials.trim();
+        if trimmed.starts_with('{') {
+            return Self::from_json(trimmed);
+        }
+        let json = std::fs::read_to_string(trimmed).map_err(|e| {
+            ServiceError::validation(format!("无法读取 FCM 服务账号文件 {}: {}", trimmed, e))
+        })?;
+        Self::from_json(&json)
+    }
+}
+
+#[derive(Serialize)]
+struct AssertionClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+}
+
+/// FCM 错误分类：UNREGISTERED、SENDER_ID_MISMATCH、404 与无效的令牌格式视为令牌失效，其余可重试
+pub fn classify_error(status: StatusCode, body: &str) -> PushError {
+    let parsed: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+    let error = &parsed["error"];
+    let message = error["message"].as_str().unwrap_or(body).trim();
+    let error_code = error["details"].as_array().and_then(|details| {
+        details
+            .iter()
+            .find_map(|detail| detail["errorCode"].as_str())
+    });
+    let detail = format!("HTTP {}: {}", status.as_u16(), message);
+
+    let invalid = match error_code {
+        Some("UNREGISTERED") | Some("SENDER_ID_MISMATCH") => true,
+        Some(_) => false,
+        None => {
+            status == StatusCode::NOT_FOUND
+                || (status == StatusCode::BAD_REQUEST
+                    && error["status"] == "INVALID_ARGUMENT"
+                    && message.to_lowercase().contains("registration token"))
+        }
+    };
+    if invalid {
+        PushError::InvalidToken(detail)
+    } else {
+        PushError::Failed(detail)
+    }
+}
+
+/// FCM 消息体
+pub fn message_body(token: &str, message: &PushMessage, ttl_secs: u64) -> serde_json::Value {
+    // data 字段只接受字符串值
+    let mut data = serde_json::Map::new();
+    data.insert(
+        "notification_id".into(),
+        message.notification_id.to_string().into(),
+    );
+    data.insert("kind".into(), message.kind.clone().into());
+    data.insert("priority".into(), message.priority.as_str().into());
+    if let Some(url) = &message.action_url {
+        data.insert("action_url".into(), url.clone().into());
+    }
+    data.insert("data".into(), message.data.to_string().into());
+
+    let high = message.is_high_priority();
+    serde_json::json!({
+        "message": {
+            "token": token,
+            "notification": {
+                "title": message.title,
+                "body": message.body,
+            },
+            "data": data,
+            "android": {
+                "priority": if high { "HIGH" } else { "NORMAL" },
+                "ttl": format!("{}s", ttl_secs),
+                "collapse_key": message.kind,
+            },
+            "apns": {
+                "headers": {
+                    "apns-priority": if high { "10" } else { "5" },
+                    "apns-expiration": (chrono::Utc::now().timestamp() + ttl_secs as i64).to_string(),
+                },
+            },
+        }
+    })
+}
+
+/// FCM 推送后端
+pub struct FcmSender {
+    client: reqwest::Client,
+    account: ServiceAccount,
+    key: EncodingKey,
+    send_url: String,
+    ttl_secs: u64,
+    access_token: Mutex<Option<(String, i64)>>,
+}
+
+impl FcmSender {
+    /// `endpoint` 为 API 根地址（默认 https://fcm.googleapis.com）
+    pub fn new(
+        client: reqwest::Client,
+        account: ServiceAccount,
+        endpoint: &str,
+        ttl_secs: u64,
+    ) -> Result<Self, ServiceError> {
+        let key = EncodingKey::from_rsa_pem(account.private_key.as_bytes())
+            .map_err(|e| ServiceError::validation(format!("FCM 服务账号私钥无效: {}", e)))?;
+        Ok(Self {
+            send_url: format!(
+                "{}/v1/projects/{}/messages:send",
+                endpoint.trim_end_matches('/'),
+                account.project_id
+            ),
+            client,
+            account,
+            key,
+            ttl_secs,
+            access_token: Mutex::new(None),
+        })
+    }
+
+    async fn access_token(&self) -> Result<String, PushError> {
+        let mut cached = self.access_token.lock().await;
+        let now = chrono::Utc::now().timestamp();
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if *expires_at - TOKEN_REFRESH_MARGIN_SECS > now {
+                return Ok(token.clone());
+            }
+        }
+
+        let claims = AssertionClaims {
+            iss: &self.account.client_email,
+            scope: FCM_SCOPE,
+            aud: &self.account.token_uri,
+            iat: now,
+            exp: now + 3600,
+        };
+        let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+            .map_err(|e| PushError::Failed(format!("FCM assertion signing failed: {}", e)))?;
+        let response = self
+            .client
+            .post(&self.account.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| PushError::Failed(format!("FCM token request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(PushError::Failed(format!(
+                "FCM token request failed: HTTP {}",
+                response.status()
+            )));
+        }
+        let token: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| PushError::Failed(format!("FCM token response invalid: {}", e)))?;
+        *cached = Some((token.access_token.clone(), now + token.expires_in));
+        Ok(token.access_token)
+    }
+}
+
+#[async_trait]
+impl PushSender for FcmSender {
+    fn name(&self) -> &'static str {
+        "fcm"
+    }
+
+    async fn send(&self, target: &PushTarget, message: &PushMessage) -> Result<(), PushError> {
+        let access_token = self.access_token().await?;
+        let response = self
+            .client
+            .post(&self.send_url)
+            .bearer_auth(access_token)
+            .json(&message_body(&target.token, message, self.ttl_secs))
+            .send()
+            .await
+            .map_err(|e| PushError::Failed(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        if status == StatusCode::UNAUTHORIZED {
+            // 访问令牌被提前吊销，下次重新获取
+            *self.access_token.lock().await = None;
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(classify_error(status, &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notification_service::NotificationPriority;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_classify_fcm_errors() {
+        let unregistered = r#"{"error":{"code":404,"message":"Requested entity was not found.","status":"NOT_FOUND","details":[{"@type":"type.googleapis.com/google.firebase.fcm.v1.FcmError","errorCode":"UNREGISTERED"}]}}"#;
+        assert!(matches!(
+            classify_error(StatusCode::NOT_FOUND, unregistered),
+            PushError::InvalidToken(_)
+        ));
+        let bad_token = r#"{"error":{"code":400,"message":"The registration token is not a valid FCM registration token","status":"INVALID_ARGUMENT"}}"#;
+        assert!(matches!(
+            classify_error(StatusCode::BAD_REQUEST, bad_token),
+            PushError::InvalidToken(_)
+        ));
+        let quota = r#"{"error":{"code":429,"message":"Quota exceeded","status":"RESOURCE_EXHAUSTED","details":[{"@type":"type.googleapis.com/google.firebase.fcm.v1.FcmError","errorCode":"QUOTA_EXCEEDED"}]}}"#;
+        assert_eq!(
+            classify_error(StatusCode::TOO_MANY_REQUESTS, quota),
+            PushError::Failed("HTTP 429: Quota exceeded".to_string())
+        );
+        let bad_payload = r#"{"error":{"code":400,"message":"Invalid value at 'message.data'","status":"INVALID_ARGUMENT"}}"#;
+        assert!(matches!(
+            classify_error(StatusCode::BAD_REQUEST, bad_payload),
+            PushError::Failed(_)
+        ));
+        assert!(matches!(
+            classify_error(StatusCode::SERVICE_UNAVAILABLE, "upstream"),
+            PushError::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_message_body_uses_string_data() {
+        let message = PushMessage {
+            notification_id: Uuid::nil(),
+            kind: "budget_exceeded".to_string(),
+            priority: NotificationPriority::High,
+            title: "预算超支".to_string(),
+            body: "餐饮已超出 120".to_string(),
+            action_url: Some("jive://budgets/1".to_string()),
+            data: serde_json::json!({ "budget_id": 1 }),
+        };
+        let body = message_body("device-token", &message, 3600);
+        let msg = &body["message"];
+        assert_eq!(msg["token"], "device-token");
+        assert_eq!(msg["notification"]["title"], "预算超支");
+        assert_eq!(msg["data"]["data"], r#"{"budget_id":1}"#);
+        assert_eq!(msg["data"]["action_url"], "jive://budgets/1");
+        assert!(msg["data"]
+            .as_object()
+            .unwrap()
+            .values()
+            .all(|v| v.is_string()));
+        assert_eq!(msg["android"]["priority"], "HIGH");
+        assert_eq!(msg["android"]["ttl"], "3600s");
+        assert_eq!(msg["apns"]["headers"]["apns-priority"], "10");
+    }
+}
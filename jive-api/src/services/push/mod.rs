@@ -0,0 +1,232 @@
+//! 推送通知子系统
+//!
+//! - `PushSender`：推送服务抽象，按设备的 provider 选择
+//! - `WebPushSender`：浏览器推送（VAPID 认证 + RFC 8291 aes128gcm 加密）
+//! - `FcmSender`：Firebase Cloud Messaging HTTP v1（服务账号 OAuth）
+//! - `MockPushGateway`：本地模拟推送网关，既可直接作为发送后端，也可通过 `router()`
+//!   提供与 FCM / Web Push 兼容的 HTTP 接口，用于离线开发与集成测试
+//! - `devices`：设备注册，与登录会话关联；推送服务报告令牌失效时自动清理
+
+pub mod devices;
+pub mod fcm;
+pub mod mock;
+pub mod webpush;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::{PushBackend, PushConfig};
+use crate::services::notification_service::{Notification, NotificationPriority};
+use crate::services::ServiceError;
+use crate::utils::outbound;
+
+pub use devices::{PushDevice, PushDeviceService, RegisterDeviceRequest};
+pub use fcm::{FcmSender, ServiceAccount};
+pub use mock::MockPushGateway;
+pub use webpush::{VapidKey, WebPushSender};
+
+/// 推送消息正文最多保留的字符数（Web Push 加密后整体不能超过 4KB）
+const MAX_BODY_CHARS: usize = 500;
+
+/// 推送服务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PushProvider {
+    /// 浏览器 / PWA
+    WebPush,
+    /// Android / iOS（Flutter firebase_messaging）
+    Fcm,
+}
+
+impl PushProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WebPush => "webpush",
+            Self::Fcm => "fcm",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "webpush" => Some(Self::WebPush),
+            "fcm" => Some(Self::Fcm),
+            _ => None,
+        }
+    }
+}
+
+/// 推送错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PushError {
+    /// 令牌或订阅已失效（卸载、退订、过期），应删除该设备
+    #[error("Invalid push token: {0}")]
+    InvalidToken(String),
+    /// 网络或推送服务暂时不可用，可重试
+    #[error("Push failed: {0}")]
+    Failed(String),
+}
+
+/// 推送目标设备
+#[derive(Debug, Clone)]
+pub struct PushTarget {
+    pub device_id: Uuid,
+    pub provider: PushProvider,
+    /// FCM 令牌或 Web Push 订阅端点
+    pub token: String,
+    /// Web Push 订阅公钥（base64url）
+    pub p256dh: Option<String>,
+    /// Web Push 订阅认证密钥（base64url）
+    pub auth: Option<String>,
+}
+
+/// 推送消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushMessage {
+    pub notification_id: Uuid,
+    pub kind: String,
+    pub priority: NotificationPriority,
+    pub title: String,
+    pub body: String,
+    pub action_url: Option<String>,
+    pub data: serde_json::Value,
+}
+
+impl PushMessage {
+    pub fn from_notification(notification: &Notification) -> Self {
+        Self {
+            notification_id: notification.id,
+            kind: notification.kind.clone(),
+            priority: NotificationPriority::parse(&notification.priority).unwrap_or_default(),
+            title: notification.title.clone(),
+            body: notification.body.chars().take(MAX_BODY_CHARS).collect(),
+            action_url: notification.action_url.clone(),
+            data: notification.data.clone(),
+        }
+    }
+
+    /// 客户端解析的消息体（Web Push 加密前的明文、FCM 的 data 字段来源）
+    pub fn payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "notification_id": self.notification_id,
+            "kind": self.kind,
+            "priority": self.priority.as_str(),
+            "title": self.title,
+            "body": self.body,
+            "action_url": self.action_url,
+            "data": self.data,
+        })
+    }
+
+    /// 是否要求推送服务立即唤醒设备
+    pub fn is_high_priority(&self) -> bool {
+        self.priority >= NotificationPriority::High
+    }
+}
+
+/// 推送发送后端
+#[async_trait]
+pub trait PushSender: Send + Sync {
+    /// 后端名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 推送到单个设备；`InvalidToken` 时调用方删除设备，`Failed` 时稍后重试
+    async fn send(&self, target: &PushTarget, message: &PushMessage) -> Result<(), PushError>;
+}
+
+/// 已启用的推送后端
+#[derive(Default)]
+pub struct PushRegistry {
+    senders: HashMap<PushProvider, Arc<dyn PushSender>>,
+    vapid_public_key: Option<String>,
+}
+
+impl PushRegistry {
+    pub fn new(senders: Vec<(PushProvider, Arc<dyn PushSender>)>) -> Self {
+        Self {
+            senders: senders.into_iter().collect(),
+            vapid_public_key: None,
+        }
+    }
+
+    pub fn with_vapid_public_key(mut self, key: Option<String>) -> Self {
+        self.vapid_public_key = key;
+        self
+    }
+
+    /// 按配置构建：配置了 VAPID 密钥时启用 Web Push，配置了服务账号时启用 FCM；
+    /// `PUSH_BACKEND=mock` 时两者都交给本地模拟网关。凭据无效时记录错误并禁用对应后端
+    pub fn from_config(config: &PushConfig) -> Self {
+        let vapid_public_key = config.vapid_public_key.clone();
+        match config.backend {
+            PushBackend::Disabled => return Self::default(),
+            PushBackend::Mock => {
+                let gateway: Arc<dyn PushSender> = Arc::new(MockPushGateway::new());
+                return Self::new(vec![
+                    (PushProvider::WebPush, gateway.clone()),
+                    (PushProvider::Fcm, gateway),
+                ])
+                .with_vapid_public_key(vapid_public_key);
+            }
+            PushBackend::Live => {}
+        }
+
+        let timeout = std::time::Duration::from_secs(config.request_timeout_secs.max(1));
+        // Web Push 端点由浏览器提供，只允许连接公网地址且不跟随重定向；FCM 地址来自配置
+        let (webpush_client, fcm_client) = match (
+            outbound::client(timeout),
+            reqwest::Client::builder().timeout(timeout).build(),
+        ) {
+            (Ok(webpush), Ok(fcm)) => (webpush, fcm),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!("Failed to build push client: {:?}", e);
+                return Self::default();
+            }
+        };
+        let mut senders: Vec<(PushProvider, Arc<dyn PushSender>)> = Vec::new();
+        if let (Some(public_key), Some(private_key)) =
+            (&config.vapid_public_key, &config.vapid_private_key)
+        {
+            match VapidKey::from_base64(private_key, public_key, &config.vapid_subject) {
+                Ok(vapid) => senders.push((
+                    PushProvider::WebPush,
+                    Arc::new(WebPushSender::new(webpush_client, vapid, config.ttl_secs)),
+                )),
+                Err(e) => tracing::error!("Web Push disabled: {}", e),
+            }
+        }
+        if let Some(credentials) = &config.fcm_credentials {
+            match ServiceAccount::load(credentials).and_then(|account| {
+                FcmSender::new(fcm_client, account, &config.fcm_endpoint, config.ttl_secs)
+            }) {
+                Ok(sender) => senders.push((PushProvider::Fcm, Arc::new(sender))),
+                Err(e) => tracing::error!("FCM disabled: {}", e),
+            }
+        }
+        Self::new(senders).with_vapid_public_key(vapid_public_key)
+    }
+
+    pub fn get(&self, provider: PushProvider) -> Option<Arc<dyn PushSender>> {
+        self.senders.get(&provider).cloned()
+    }
+
+    pub fn require(&self, provider: PushProvider) -> Result<Arc<dyn PushSender>, ServiceError> {
+        self.get(provider).ok_or_else(|| {
+            ServiceError::validation(format!("推送渠道 {} 未启用", provider.as_str()))
+        })
+    }
+
+    pub fn providers(&self) -> Vec<PushProvider> {
+        let mut providers: Vec<PushProvider> = self.senders.keys().copied().collect();
+        providers.sort_by_key(|p| p.as_str());
+        providers
+    }
+
+    /// 浏览器订阅时使用的 applicationServerKey
+    pub fn vapid_public_key(&self) -> Option<&str> {
+        self.vapid_public_key.as_deref()
+    }
+}
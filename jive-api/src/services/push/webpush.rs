@@ -0,0 +1,399 @@
+//! Web Push（RFC 8030）
+//!
+//! - 认证：VAPID（RFC 8292），每个推送服务源签发一个 ES256 JWT
+//! - 加密：aes128gcm（RFC 8291 / RFC 8188），每条消息使用新的临时 ECDH 密钥与随机盐，
+//!   整条消息作为单条记录
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use ring::{aead, agreement};
+use sha2::Sha256;
+
+use super::{PushError, PushMessage, PushSender, PushTarget};
+use crate::services::ServiceError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 记录大小（RFC 8188 头部字段）；消息只有一条记录，取推送服务保证接受的上限
+const RECORD_SIZE: u32 = 4096;
+/// 头部：salt(16) + rs(4) + idlen(1) + keyid(65)
+const HEADER_LEN: usize = 16 + 4 + 1 + 65;
+/// 明文上限：记录减去头部、分隔符与 GCM 标签
+const MAX_PLAINTEXT: usize = RECORD_SIZE as usize - HEADER_LEN - 1 - 16;
+/// VAPID JWT 有效期（RFC 8292 要求不超过 24 小时）
+const VAPID_TTL_SECS: i64 = 12 * 60 * 60;
+
+/// 兼容标准 base64 与 base64url、带或不带填充（浏览器 `getKey()` 的结果常被客户端用 btoa 编码）
+pub fn decode_base64url(value: &str) -> Option<Vec<u8>> {
+    let normalized: String = value
+        .trim()
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect();
+    URL_SAFE_NO_PAD.decode(normalized).ok()
+}
+
+/// 订阅公钥必须是未压缩的 P-256 点
+pub fn is_valid_p256dh(key: &[u8]) -> bool {
+    key.len() == 65 && key[0] == 0x04
+}
+
+/// 订阅认证密钥为 16 字节
+pub fn is_valid_auth_secret(secret: &[u8]) -> bool {
+    secret.len() == 16
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    // HMAC accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// RFC 8291 §3.3-3.4：由 ECDH 共享密钥推导内容加密密钥与 nonce（HKDF-SHA256，输出均短于一个分组）
+pub(super) fn derive_key_and_nonce(
+    ecdh_secret: &[u8],
+    auth_secret: &[u8],
+    ua_public: &[u8],
+    as_public: &[u8],
+    salt: &[u8],
+) -> ([u8; 16], [u8; 12]) {
+    let prk_key = hmac_sha256(auth_secret, &[ecdh_secret]);
+    let ikm = hmac_sha256(&prk_key, &[b"WebPush: info\0", ua_public, as_public, &[1]]);
+    let prk = hmac_sha256(salt, &[&ikm]);
+    let cek = hmac_sha256(&prk, &[b"Content-Encoding: aes128gcm\0", &[1]]);
+    let nonce = hmac_sha256(&prk, &[b"Content-Encoding: nonce\0", &[1]]);
+
+    let mut key = [0u8; 16];
+    key.copy_from_slice(&cek[..16]);
+    let mut iv = [0u8; 12];
+    iv.copy_from_slice(&nonce[..12]);
+    (key, iv)
+}
+
+/// 用已协商的共享密钥加密为 aes128gcm 消息体（头部 + 单条记录）
+fn seal(
+    ecdh_secret: &[u8],
+    auth_secret: &[u8],
+    ua_public: &[u8],
+    as_public: &[u8],
+    salt: &[u8; 16],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, PushError> {
+    let (cek, nonce) = derive_key_and_nonce(ecdh_secret, auth_secret, ua_public, as_public, salt);
+    let key = aead::UnboundKey::new(&aead::AES_128_GCM, &cek)
+        .map(aead::LessSafeKey::new)
+        .map_err(|_| PushError::Failed("invalid content encryption key".to_string()))?;
+
+    let mut record = Vec::with_capacity(plaintext.len() + 1 + aead::AES_128_GCM.tag_len());
+    record.extend_from_slice(plaintext);
+    // 最后一条记录的分隔符
+    record.push(0x02);
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::empty(),
+        &mut record,
+    )
+    .map_err(|_| PushError::Failed("encryption failed".to_string()))?;
+
+    let mut body = Vec::with_capacity(HEADER_LEN + record.len());
+    body.extend_from_slice(salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(as_public);
+    body.extend_from_slice(&record);
+    Ok(body)
+}
+
+/// 为订阅加密消息
+pub fn encrypt(
+    ua_public: &[u8],
+    auth_secret: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, PushError> {
+    if plaintext.len() > MAX_PLAINTEXT {
+        return Err(PushError::Failed(format!(
+            "payload too large: {} bytes",
+            plaintext.len()
+        )));
+    }
+    if !is_valid_p256dh(ua_public) || !is_valid_auth_secret(auth_secret) {
+        return Err(PushError::InvalidToken(
+            "invalid subscription keys".to_string(),
+        ));
+    }
+
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt)
+        .map_err(|_| PushError::Failed("random source unavailable".to_string()))?;
+    let private_key = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+        .map_err(|_| PushError::Failed("key generation failed".to_string()))?;
+    let as_public = private_key
+        .compute_public_key()
+        .map_err(|_| PushError::Failed("key generation failed".to_string()))?;
+    let peer = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, ua_public);
+    let ecdh_secret = agreement::agree_ephemeral(private_key, &peer, |secret| secret.to_vec())
+        .map_err(|_| PushError::InvalidToken("invalid subscription public key".to_string()))?;
+
+    seal(
+        &ecdh_secret,
+        auth_secret,
+        ua_public,
+        as_public.as_ref(),
+        &salt,
+        plaintext,
+    )
+}
+
+/// VAPID 密钥（应用服务器身份）
+pub struct VapidKey {
+    key_pair: EcdsaKeyPair,
+    public_key: String,
+    subject: String,
+}
+
+impl VapidKey {
+    /// 从 base64url 编码的原始私钥（32 字节）与公钥（65 字节）加载，即 web-push 工具生成的格式
+    pub fn from_base64(
+        private_key: &str,
+        public_key: &str,
+        subject: &str,
+    ) -> Result<Self, ServiceError> {
+        let invalid = || ServiceError::validation("VAPID 密钥无效");
+        let private = decode_base64url(private_key).ok_or_else(invalid)?;
+        let public = decode_base64url(public_key).ok_or_else(invalid)?;
+        let key_pair = EcdsaKeyPair::from_private_key_and_public_key(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &private,
+            &public,
+            &SystemRandom::new(),
+        )
+        .map_err(|_| invalid())?;
+        Ok(Self {
+            public_key: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+            key_pair,
+            subject: subject.to_string(),
+        })
+    }
+
+    /// base64url 公钥（浏览器订阅时的 applicationServerKey）
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// `Authorization: vapid t=<JWT>, k=<公钥>`，JWT 的 aud 为推送端点的源
+    pub fn authorization(&self, endpoint: &str, now: i64) -> Result<String, PushError> {
+        let url = reqwest::Url::parse(endpoint)
+            .map_err(|_| PushError::InvalidToken("invalid endpoint".to_string()))?;
+        let header = URL_SAFE_NO_PAD.encode(br#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = serde_json::json!({
+            "aud": url.origin().ascii_serialization(),
+            "exp": now + VAPID_TTL_SECS,
+            "sub": self.subject,
+        });
+        let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let signing_input = format!("{}.{}", header, claims);
+        let signature = self
+            .key_pair
+            .sign(&SystemRandom::new(), signing_input.as_bytes())
+            .map_err(|_| PushError::Failed("VAPID signing failed".to_string()))?;
+        Ok(format!(
+            "vapid t={}.{}, k={}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.as_ref()),
+            self.public_key
+        ))
+    }
+}
+
+/// 推送服务响应分类：404 / 410 表示订阅已失效（RFC 8030 §7.3），其余错误可重试
+pub fn classify_response(status: StatusCode, body: &str) -> Result<(), PushError> {
+    if status.is_success() {
+        return Ok(());
+    }
+    let detail = format!("HTTP {} {}", status.as_u16(), body.trim())
+        .trim()
+        .to_string();
+    match status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => Err(PushError::InvalidToken(detail)),
+        _ => Err(PushError::Failed(detail)),
+    }
+}
+
+/// 浏览器推送后端
+pub struct WebPushSender {
+    client: reqwest::Client,
+    vapid: VapidKey,
+    ttl_secs: u64,
+}
+
+impl WebPushSender {
+    pub fn new(client: reqwest::Client, vapid: VapidKey, ttl_secs: u64) -> Self {
+        Self {
+            client,
+            vapid,
+            ttl_secs,
+        }
+    }
+}
+
+#[async_trait]
+impl PushSender for WebPushSender {
+    fn name(&self) -> &'static str {
+        "webpush"
+    }
+
+    async fn send(&self, target: &PushTarget, message: &PushMessage) -> Result<(), PushError> {
+        let (Some(ua_public), Some(auth_secret)) = (
+            target.p256dh.as_deref().and_then(decode_base64url),
+            target.auth.as_deref().and_then(decode_base64url),
+        ) else {
+            return Err(PushError::InvalidToken(
+                "missing subscription keys".to_string(),
+            ));
+        };
+        let plaintext =
+            serde_json::to_vec(&message.payload()).map_err(|e| PushError::Failed(e.to_string()))?;
+        let body = encrypt(&ua_public, &auth_secret, &plaintext)?;
+        let authorization = self
+            .vapid
+            .authorization(&target.token, chrono::Utc::now().timestamp())?;
+
+        let response = self
+            .client
+            .post(&target.token)
+            .header("Authorization", authorization)
+            .header("TTL", self.ttl_secs.to_string())
+            .header(
+                "Urgency",
+                if message.is_high_priority() {
+                    "high"
+                } else {
+                    "normal"
+                },
+            )
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| PushError::Failed(e.to_string()))?;
+        let status = response.status();
+        let text = if status.is_success() {
+            String::new()
+        } else {
+            response.text().await.unwrap_or_default()
+        };
+        classify_response(status, &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn b64(value: &str) -> Vec<u8> {
+        decode_base64url(value).unwrap()
+    }
+
+    // RFC 8291 附录 A 的测试向量
+    const AS_PUBLIC: &str =
+        "BP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A8";
+    const AS_PRIVATE: &str = "yfWPiYE-n46HLnH0KqZOF1fJJU3MYrct3AELtAQ-oRw";
+    const UA_PUBLIC: &str =
+        "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+    const ECDH_SECRET: &str = "kyrL1jIIOHEzg3sM2ZWRHDRB62YACZhhSlknJ672kSs";
+    const AUTH_SECRET: &str = "BTBZMqHH6r4Tts7J_aSIgg";
+    const SALT: &str = "DGv6ra1nlYgDCS1FRnbzlw";
+
+    #[test]
+    fn test_rfc8291_encryption_vector() {
+        let (cek, nonce) = derive_key_and_nonce(
+            &b64(ECDH_SECRET),
+            &b64(AUTH_SECRET),
+            &b64(UA_PUBLIC),
+            &b64(AS_PUBLIC),
+            &b64(SALT),
+        );
+        assert_eq!(URL_SAFE_NO_PAD.encode(cek), "oIhVW04MRdy2XN9CiKLxTg");
+        assert_eq!(URL_SAFE_NO_PAD.encode(nonce), "4h_95klXJ5E_qnoN");
+
+        let salt: [u8; 16] = b64(SALT).try_into().unwrap();
+        let body = seal(
+            &b64(ECDH_SECRET),
+            &b64(AUTH_SECRET),
+            &b64(UA_PUBLIC),
+            &b64(AS_PUBLIC),
+            &salt,
+            b"When I grow up, I want to be a watermelon",
+        )
+        .unwrap();
+        assert_eq!(
+            URL_SAFE_NO_PAD.encode(body),
+            "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBWLVWGNWQexSgSxsj_Qulcy4a-fN"
+        );
+    }
+
+    #[test]
+    fn test_vapid_authorization_is_verifiable() {
+        let vapid = VapidKey::from_base64(AS_PRIVATE, AS_PUBLIC, "mailto:ops@jive.money").unwrap();
+        assert_eq!(vapid.public_key(), AS_PUBLIC);
+
+        let header = vapid
+            .authorization("https://push.example.net:8443/wpush/abc", 1_700_000_000)
+            .unwrap();
+        let (token, key) = header
+            .strip_prefix("vapid t=")
+            .and_then(|rest| rest.split_once(", k="))
+            .unwrap();
+        assert_eq!(key, AS_PUBLIC);
+
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+        ring::signature::UnparsedPublicKey::new(
+            &ring::signature::ECDSA_P256_SHA256_FIXED,
+            b64(AS_PUBLIC),
+        )
+        .verify(signing_input.as_bytes(), &b64(signature))
+        .unwrap();
+        let claims: serde_json::Value =
+            serde_json::from_slice(&b64(signing_input.split('.').nth(1).unwrap())).unwrap();
+        assert_eq!(claims["aud"], "https://push.example.net:8443");
+        assert_eq!(claims["sub"], "mailto:ops@jive.money");
+        assert_eq!(claims["exp"], 1_700_000_000 + VAPID_TTL_SECS);
+
+        assert!(VapidKey::from_base64(AS_PRIVATE, UA_PUBLIC, "mailto:x@y.z").is_err());
+    }
+
+    #[test]
+    fn test_classify_response() {
+        assert!(classify_response(StatusCode::CREATED, "").is_ok());
+        assert!(matches!(
+            classify_response(StatusCode::GONE, "expired"),
+            Err(PushError::InvalidToken(_))
+        ));
+        assert!(matches!(
+            classify_response(StatusCode::NOT_FOUND, ""),
+            Err(PushError::InvalidToken(_))
+        ));
+        assert!(matches!(
+            classify_response(StatusCode::TOO_MANY_REQUESTS, ""),
+            Err(PushError::Failed(_))
+        ));
+        assert_eq!(
+            decode_base64url("BTBZMqHH6r4Tts7J/aSIgg=="),
+            Some(b64(AUTH_SECRET))
+        );
+    }
+}
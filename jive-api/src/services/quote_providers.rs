@@ -0,0 +1,267 @@
+//! 证券行情数据源
+//!
+//! `QuoteProvider` 为证券收盘价来源的抽象；内置的 `LocalQuoteProvider` 从 JSON 文件、
+//! HTTP 桩或内存读取行情，用于离线开发与测试。文档格式（数值可为字符串或数字）：
+//!
+//! ```json
+//! {
+//!   "date": "2025-06-02",
+//!   "quotes": { "AAPL": "189.50", "600519.SSE": { "close": "1688", "date": "2025-05-30" } }
+//! }
+//! ```
+//!
+//! 键为 `代码.交易所` 或 `代码`，前者优先；单条行情未给出日期时使用文档的 `date`，再缺省为今天。
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::ServiceError;
+
+/// 需要报价的证券
+#[derive(Debug, Clone)]
+pub struct QuoteRequest {
+    pub security_id: Uuid,
+    pub ticker: String,
+    pub exchange: Option<String>,
+    pub currency: String,
+}
+
+/// 某证券某日的收盘价
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub security_id: Uuid,
+    pub price_date: NaiveDate,
+    pub close: Decimal,
+}
+
+/// 证券行情数据源
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+    /// 数据源名称，写入 `security_prices.source`
+    fn name(&self) -> &str;
+
+    /// 返回能取到的行情；取不到的证券直接省略
+    async fn fetch_quotes(&self, requests: &[QuoteRequest]) -> Result<Vec<Quote>, ServiceError>;
+}
+
+/// 本地行情文档
+#[derive(Debug, Clone, Default)]
+pub struct LocalQuotes {
+    /// 大写的 `代码.交易所` 或 `代码` -> (日期, 收盘价)
+    pub quotes: HashMap<String, (Option<NaiveDate>, Decimal)>,
+    pub date: Option<NaiveDate>,
+}
+
+impl LocalQuotes {
+    pub fn from_json(raw: &str) -> Result<Self, ServiceError> {
+        let invalid = |message: String| ServiceError::ExternalApi { message };
+        let doc: serde_json::Value = serde_json::from_str(raw)
+            .map_err(|e| invalid(format!("Invalid local quotes document: {}", e)))?;
+        let date = doc
+            .get("date")
+            .and_then(|d| d.as_str())
+            .map(|d| {
+                NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d")
+                    .map_err(|_| invalid(format!("Invalid quotes date: {}", d)))
+            })
+            .transpose()?;
+
+        let mut quotes = HashMap::new();
+        if let Some(obj) = doc.get("quotes").and_then(|q| q.as_object()) {
+            for (key, value) in obj {
+                let (price, quote_date) = match value {
+                    serde_json::Value::Object(entry) => (
+                        entry.get("close").and_then(json_decimal),
+                        entry
+                            .get("date")
+                            .and_then(|d| d.as_str())
+                            .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok()),
+                    ),
+                    other => (json_decimal(other), None),
+                };
+                let price = price.filter(|p| *p > Decimal::ZERO).ok_or_else(|| {
+                    invalid(format!("Invalid local quote for {}: {}", key, value))
+                })?;
+                quotes.insert(key.trim().to_uppercase(), (quote_date, price));
+            }
+        }
+        Ok(Self { quotes, date })
+    }
+
+    pub fn with_quote(mut self, key: &str, price: Decimal) -> Self {
+        self.quotes.insert(key.to_uppercase(), (None, price));
+        self
+    }
+
+    /// 按 `代码.交易所`、`代码` 的顺序查找
+    pub fn lookup(&self, request: &QuoteRequest, today: NaiveDate) -> Option<Quote> {
+        let ticker = request.ticker.to_uppercase();
+        let qualified = request
+            .exchange
+            .as_ref()
+            .map(|e| format!("{}.{}", ticker, e.to_uppercase()));
+        let (date, close) = qualified
+            .and_then(|key| self.quotes.get(&key))
+            .or_else(|| self.quotes.get(&ticker))?;
+        Some(Quote {
+            security_id: request.security_id,
+            price_date: date.or(self.date).unwrap_or(today),
+            close: *close,
+        })
+    }
+}
+
+fn json_decimal(value: &serde_json::Value) -> Option<Decimal> {
+    match value {
+        serde_json::Value::String(s) => Decimal::from_str(s.trim()).ok(),
+        serde_json::Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+        _ => None,
+    }
+}
+
+/// 本地行情来源
+#[derive(Debug, Clone)]
+pub enum LocalQuoteSource {
+    /// 每次刷新重新读取文件
+    File(PathBuf),
+    /// 返回同格式 JSON 的 HTTP 桩
+    Http(String),
+    /// 内存数据
+    Static(LocalQuotes),
+}
+
+/// 本地行情数据源
+pub struct LocalQuoteProvider {
+    source: LocalQuoteSource,
+    client: reqwest::Client,
+}
+
+impl LocalQuoteProvider {
+    pub fn new(source: LocalQuoteSource) -> Self {
+        Self {
+            source,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn load(&self) -> Result<LocalQuotes, ServiceError> {
+        match &self.source {
+            LocalQuoteSource::Static(quotes) => Ok(quotes.clone()),
+            LocalQuoteSource::File(path) => {
+                let raw = tokio::fs::read_to_string(path).await.map_err(|e| {
+                    ServiceError::ExternalApi {
+                        message: format!("Failed to read {}: {}", path.display(), e),
+                    }
+                })?;
+                LocalQuotes::from_json(&raw)
+            }
+            LocalQuoteSource::Http(url) => {
+                let response =
+                    self.client
+                        .get(url)
+                        .send()
+                        .await
+                        .map_err(|e| ServiceError::ExternalApi {
+                            message: format!("Failed to fetch from {}: {}", url, e),
+                        })?;
+                if !response.status().is_success() {
+                    return Err(ServiceError::ExternalApi {
+                        message: format!("{} returned status: {}", url, response.status()),
+                    });
+                }
+                let raw = response
+                    .text()
+                    .await
+                    .map_err(|e| ServiceError::ExternalApi {
+                        message: format!("Failed to read response from {}: {}", url, e),
+                    })?;
+                LocalQuotes::from_json(&raw)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for LocalQuoteProvider {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    async fn fetch_quotes(&self, requests: &[QuoteRequest]) -> Result<Vec<Quote>, ServiceError> {
+        let quotes = self.load().await?;
+        let today = Utc::now().date_naive();
+        Ok(requests
+            .iter()
+            .filter_map(|request| quotes.lookup(request, today))
+            .collect())
+    }
+}
+
+/// 按环境变量构建行情数据源：`SECURITY_QUOTES_FILE` / `SECURITY_QUOTES_URL`
+///
+/// 均未配置时返回 None，定时刷新只按已有价格重估持仓。
+pub fn quote_provider_from_env() -> Option<Arc<dyn QuoteProvider>> {
+    let non_empty = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    let source = if let Some(path) = non_empty("SECURITY_QUOTES_FILE") {
+        LocalQuoteSource::File(PathBuf::from(path))
+    } else {
+        LocalQuoteSource::Http(non_empty("SECURITY_QUOTES_URL")?)
+    };
+    Some(Arc::new(LocalQuoteProvider::new(source)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(ticker: &str, exchange: Option<&str>) -> QuoteRequest {
+        QuoteRequest {
+            security_id: Uuid::nil(),
+            ticker: ticker.to_string(),
+            exchange: exchange.map(str::to_string),
+            currency: "USD".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_quotes_lookup() {
+        let raw = r#"{
+            "date": "2025-06-02",
+            "quotes": {
+                "aapl": "189.50",
+                "600519.SSE": { "close": 1688, "date": "2025-05-30" },
+                "600519": "1"
+            }
+        }"#;
+        let provider = LocalQuoteProvider::new(LocalQuoteSource::Static(
+            LocalQuotes::from_json(raw).unwrap(),
+        ));
+        let quotes = provider
+            .fetch_quotes(&[
+                request("AAPL", Some("NASDAQ")),
+                request("600519", Some("sse")),
+                request("MSFT", None),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].close, Decimal::from_str("189.50").unwrap());
+        assert_eq!(
+            quotes[0].price_date,
+            NaiveDate::from_ymd_opt(2025, 6, 2).unwrap()
+        );
+        assert_eq!(quotes[1].close, Decimal::from(1688));
+        assert_eq!(
+            quotes[1].price_date,
+            NaiveDate::from_ymd_opt(2025, 5, 30).unwrap()
+        );
+
+        assert!(LocalQuotes::from_json(r#"{"quotes":{"X":"-1"}}"#).is_err());
+    }
+}
@@ -0,0 +1,694 @@
+//! 汇率 / 加密货币价格提醒
+//!
+//! 用户为币种对设置规则（如 USD/CNY 高于 7.3、BTC/USD 24 小时波动超过 5%），
+//! 每次定时刷新汇率或加密货币价格后统一评估。币种对的当前值取自当天的汇率图，
+//! 因此没有直接报价的币种对也可经中转计算。
+//!
+//! 涨跌幅规则的参考值来自 `rate_alert_samples`：每次评估都会记录各币种对的当前值。
+//! 触发后在冷却时间内不会重复提醒，冷却判断与触发记录在同一条 UPDATE 中完成，
+//! 法币与加密货币两个刷新任务并发评估时也只会提醒一次。
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::notification_service::{NewNotification, NotificationPriority, NotificationService};
+use super::rate_graph::RateGraph;
+use super::ServiceError;
+use crate::config::AppConfig;
+use crate::ws::WsConnectionManager;
+
+/// 采样保留天数（需覆盖最长的 30d 窗口）
+const SAMPLE_RETENTION_DAYS: i32 = 31;
+const DEFAULT_COOLDOWN_MINUTES: i32 = 360;
+/// 冷却时间上限：30 天
+const MAX_COOLDOWN_MINUTES: i32 = 30 * 24 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// 汇率高于阈值
+    Above,
+    /// 汇率低于阈值
+    Below,
+    /// 窗口内上涨超过阈值（百分比）
+    ChangeUp,
+    /// 窗口内下跌超过阈值（百分比）
+    ChangeDown,
+    /// 窗口内涨跌幅绝对值超过阈值（百分比）
+    ChangeAbs,
+}
+
+impl AlertCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertCondition::Above => "above",
+            AlertCondition::Below => "below",
+            AlertCondition::ChangeUp => "change_up",
+            AlertCondition::ChangeDown => "change_down",
+            AlertCondition::ChangeAbs => "change_abs",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "above" => Some(AlertCondition::Above),
+            "below" => Some(AlertCondition::Below),
+            "change_up" => Some(AlertCondition::ChangeUp),
+            "change_down" => Some(AlertCondition::ChangeDown),
+            "change_abs" => Some(AlertCondition::ChangeAbs),
+            _ => None,
+        }
+    }
+
+    pub fn is_change(&self) -> bool {
+        !matches!(self, AlertCondition::Above | AlertCondition::Below)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum AlertWindow {
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+}
+
+impl AlertWindow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertWindow::Day => "24h",
+            AlertWindow::Week => "7d",
+            AlertWindow::Month => "30d",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "24h" => Some(AlertWindow::Day),
+            "7d" => Some(AlertWindow::Week),
+            "30d" => Some(AlertWindow::Month),
+            _ => None,
+        }
+    }
+
+    pub fn hours(&self) -> i32 {
+        match self {
+            AlertWindow::Day => 24,
+            AlertWindow::Week => 7 * 24,
+            AlertWindow::Month => 30 * 24,
+        }
+    }
+
+    /// 参考采样允许早于窗口起点的时长（刷新间隔、服务重启造成的采样空档）
+    pub fn grace_hours(&self) -> i32 {
+        match self {
+            AlertWindow::Day => 3,
+            AlertWindow::Week => 12,
+            AlertWindow::Month => 48,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct RateAlert {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub condition: String,
+    pub threshold: Decimal,
+    pub change_window: Option<String>,
+    pub cooldown_minutes: i32,
+    pub is_active: bool,
+    pub note: Option<String>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub last_value: Option<Decimal>,
+    pub trigger_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRateAlertRequest {
+    /// 币种对的基础币种，如 USD、BTC
+    pub base_currency: String,
+    /// 计价币种，如 CNY、USD
+    pub quote_currency: String,
+    pub condition: AlertCondition,
+    /// 汇率阈值；涨跌幅规则为百分比（5 表示 5%）
+    pub threshold: Decimal,
+    /// 涨跌幅规则必填
+    pub change_window: Option<AlertWindow>,
+    pub cooldown_minutes: Option<i32>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRateAlertRequest {
+    pub condition: Option<AlertCondition>,
+    pub threshold: Option<Decimal>,
+    pub change_window: Option<AlertWindow>,
+    pub cooldown_minutes: Option<i32>,
+    pub is_active: Option<bool>,
+    pub note: Option<String>,
+}
+
+/// 一次评估的统计
+#[derive(Debug, Clone, Default)]
+pub struct AlertRunStats {
+    pub evaluated: usize,
+    pub triggered: usize,
+    /// 当前值或参考值缺失而跳过的规则
+    pub skipped: usize,
+}
+
+/// 规则命中时返回观测值：above/below 为当前汇率，涨跌幅规则为百分比变动
+pub fn evaluate_condition(
+    condition: AlertCondition,
+    threshold: Decimal,
+    current: Decimal,
+    reference: Option<Decimal>,
+) -> Option<Decimal> {
+    match condition {
+        AlertCondition::Above => (current > threshold).then_some(current),
+        AlertCondition::Below => (current < threshold).then_some(current),
+        AlertCondition::ChangeUp => {
+            percent_change(current, reference?).filter(|change| *change >= threshold)
+        }
+        AlertCondition::ChangeDown => {
+            percent_change(current, reference?).filter(|change| -*change >= threshold)
+        }
+        AlertCondition::ChangeAbs => {
+            percent_change(current, reference?).filter(|change| change.abs() >= threshold)
+        }
+    }
+}
+
+/// 相对参考值的百分比变动，保留四位小数
+pub fn percent_change(current: Decimal, reference: Decimal) -> Option<Decimal> {
+    if reference <= Decimal::ZERO {
+        return None;
+    }
+    Some(((current - reference) / reference * Decimal::from(100)).round_dp(4))
+}
+
+pub fn cooldown_elapsed(
+    last_triggered_at: Option<DateTime<Utc>>,
+    cooldown_minutes: i32,
+    now: DateTime<Utc>,
+) -> bool {
+    last_triggered_at
+        .is_none_or(|last| now - last >= Duration::minutes(i64::from(cooldown_minutes)))
+}
+
+/// 通知正文，如「USD/CNY 当前 7.3125，高于 7.3」
+pub fn describe_trigger(alert: &RateAlert, current: Decimal, observed: Decimal) -> String {
+    let pair = format!("{}/{}", alert.base_currency, alert.quote_currency);
+    let threshold = alert.threshold.normalize();
+    let current = current.round_dp(6).normalize();
+    let window = alert.change_window.as_deref().unwrap_or("");
+    match AlertCondition::parse(&alert.condition) {
+        Some(AlertCondition::Above) => format!("{} 当前 {}，高于 {}", pair, current, threshold),
+        Some(AlertCondition::Below) => format!("{} 当前 {}，低于 {}", pair, current, threshold),
+        _ => format!(
+            "{} {} 内变动 {:+}%（阈值 {}%），当前 {}",
+            pair,
+            window,
+            observed.round_dp(2).normalize(),
+            threshold,
+            current
+        ),
+    }
+}
+
+fn normalize_code(code: &str, field: &str) -> Result<String, ServiceError> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() || code.len() > 15 || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(ServiceError::validation(format!("Invalid {}", field)));
+    }
+    Ok(code)
+}
+
+fn validate_rule(
+    condition: AlertCondition,
+    threshold: Decimal,
+    window: Option<AlertWindow>,
+    cooldown_minutes: i32,
+) -> Result<(), ServiceError> {
+    if threshold <= Decimal::ZERO {
+        return Err(ServiceError::validation("threshold must be positive"));
+    }
+    if condition.is_change() && window.is_none() {
+        return Err(ServiceError::validation(
+            "change_window is required for change conditions",
+        ));
+    }
+    if !(0..=MAX_COOLDOWN_MINUTES).contains(&cooldown_minutes) {
+        return Err(ServiceError::validation(format!(
+            "cooldown_minutes must be between 0 and {}",
+            MAX_COOLDOWN_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+pub struct RateAlertService {
+    pool: PgPool,
+    ws: Arc<WsConnectionManager>,
+}
+
+impl RateAlertService {
+    pub fn new(pool: PgPool, ws: Arc<WsConnectionManager>) -> Self {
+        Self { pool, ws }
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<RateAlert>, ServiceError> {
+        let alerts = sqlx::query_as::<_, RateAlert>(
+            "SELECT * FROM rate_alerts WHERE user_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(alerts)
+    }
+
+    pub async fn get(&self, user_id: Uuid, id: Uuid) -> Result<RateAlert, ServiceError> {
+        sqlx::query_as::<_, RateAlert>("SELECT * FROM rate_alerts WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("RateAlert", id))
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        request: CreateRateAlertRequest,
+    ) -> Result<RateAlert, ServiceError> {
+        let base = normalize_code(&request.base_currency, "base_currency")?;
+        let quote = normalize_code(&request.quote_currency, "quote_currency")?;
+        if base == quote {
+            return Err(ServiceError::validation(
+                "base_currency and quote_currency must differ",
+            ));
+        }
+        let cooldown = request.cooldown_minutes.unwrap_or(DEFAULT_COOLDOWN_MINUTES);
+        // above/below 不使用窗口，忽略传入值
+        let window = request
+            .change_window
+            .filter(|_| request.condition.is_change());
+        validate_rule(request.condition, request.threshold, window, cooldown)?;
+
+        let alert = sqlx::query_as::<_, RateAlert>(
+            r#"
+            INSERT INTO rate_alerts
+                (user_id, base_currency, quote_currency, condition, threshold,
+                 change_window, cooldown_minutes, note)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(&base)
+        .bind(&quote)
+        .bind(request.condition.as_str())
+        .bind(request.threshold)
+        .bind(window.map(|w| w.as_str()))
+        .bind(cooldown)
+        .bind(&request.note)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(alert)
+    }
+
+    pub async fn update(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        request: UpdateRateAlertRequest,
+    ) -> Result<RateAlert, ServiceError> {
+        let existing = self.get(user_id, id).await?;
+        let condition = match request.condition {
+            Some(condition) => condition,
+            None => AlertCondition::parse(&existing.condition)
+                .ok_or_else(|| ServiceError::validation("Unknown alert condition"))?,
+        };
+        let threshold = request.threshold.unwrap_or(existing.threshold);
+        let window = request
+            .change_window
+            .or_else(|| {
+                existing
+                    .change_window
+                    .as_deref()
+                    .and_then(AlertWindow::parse)
+            })
+            .filter(|_| condition.is_change());
+        let cooldown = request
+            .cooldown_minutes
+            .unwrap_or(existing.cooldown_minutes);
+        validate_rule(condition, threshold, window, cooldown)?;
+
+        let alert = sqlx::query_as::<_, RateAlert>(
+            r#"
+            UPDATE rate_alerts
+            SET condition = $3,
+                threshold = $4,
+                change_window = $5,
+                cooldown_minutes = $6,
+                is_active = COALESCE($7, is_active),
+                note = COALESCE($8, note),
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(condition.as_str())
+        .bind(threshold)
+        .bind(window.map(|w| w.as_str()))
+        .bind(cooldown)
+        .bind(request.is_active)
+        .bind(&request.note)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("RateAlert", id))?;
+        Ok(alert)
+    }
+
+    pub async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<(), ServiceError> {
+        let result = sqlx::query("DELETE FROM rate_alerts WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("RateAlert", id));
+        }
+        Ok(())
+    }
+
+    /// 评估全部启用的规则，命中且已过冷却的规则发送通知
+    pub async fn evaluate_all(&self, config: &AppConfig) -> Result<AlertRunStats, ServiceError> {
+        let alerts = sqlx::query_as::<_, RateAlert>(
+            "SELECT * FROM rate_alerts WHERE is_active ORDER BY base_currency, quote_currency",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut stats = AlertRunStats::default();
+        if alerts.is_empty() {
+            return Ok(stats);
+        }
+
+        let now = Utc::now();
+        let resolver = &config.rate_resolver;
+        let graph = RateGraph::load(&self.pool, now.date_naive(), resolver).await?;
+
+        let mut current: HashMap<(String, String), Option<Decimal>> = HashMap::new();
+        for alert in &alerts {
+            let pair = (alert.base_currency.clone(), alert.quote_currency.clone());
+            current.entry(pair).or_insert_with_key(|(base, quote)| {
+                graph
+                    .resolve_with(base, quote, resolver)
+                    .ok()
+                    .map(|resolved| resolved.rate.round_dp(12))
+            });
+        }
+        self.record_samples(&current).await?;
+
+        let mut references: HashMap<(String, String, AlertWindow), Option<Decimal>> =
+            HashMap::new();
+        let notifier =
+            NotificationService::new(self.pool.clone(), &config.notification, self.ws.clone());
+
+        for alert in &alerts {
+            stats.evaluated += 1;
+            let pair = (alert.base_currency.clone(), alert.quote_currency.clone());
+            let Some(value) = current.get(&pair).copied().flatten() else {
+                stats.skipped += 1;
+                continue;
+            };
+            let Some(condition) = AlertCondition::parse(&alert.condition) else {
+                stats.skipped += 1;
+                continue;
+            };
+
+            let reference = match alert.change_window.as_deref().and_then(AlertWindow::parse) {
+                Some(window) if condition.is_change() => {
+                    let key = (pair.0.clone(), pair.1.clone(), window);
+                    if !references.contains_key(&key) {
+                        let value = self.reference_value(&pair.0, &pair.1, window).await?;
+                        references.insert(key.clone(), value);
+                    }
+                    let reference = references[&key];
+                    if reference.is_none() {
+                        stats.skipped += 1;
+                        continue;
+                    }
+                    reference
+                }
+                _ => None,
+            };
+
+            let Some(observed) = evaluate_condition(condition, alert.threshold, value, reference)
+            else {
+                continue;
+            };
+            if !cooldown_elapsed(alert.last_triggered_at, alert.cooldown_minutes, now) {
+                continue;
+            }
+            if self.trigger(&notifier, alert, value, observed).await? {
+                stats.triggered += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    async fn record_samples(
+        &self,
+        values: &HashMap<(String, String), Option<Decimal>>,
+    ) -> Result<(), ServiceError> {
+        let mut bases = Vec::new();
+        let mut quotes = Vec::new();
+        let mut samples = Vec::new();
+        for ((base, quote), value) in values {
+            if let Some(value) = value {
+                bases.push(base.clone());
+                quotes.push(quote.clone());
+                samples.push(*value);
+            }
+        }
+
+        if !samples.is_empty() {
+            sqlx::query(
+                r#"
+                INSERT INTO rate_alert_samples (base_currency, quote_currency, value)
+                SELECT * FROM UNNEST($1::text[], $2::text[], $3::numeric[])
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(&bases)
+            .bind(&quotes)
+            .bind(&samples)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        sqlx::query(
+            "DELETE FROM rate_alert_samples WHERE sampled_at < NOW() - make_interval(days => $1)",
+        )
+        .bind(SAMPLE_RETENTION_DAYS)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 窗口起点前最近的一次采样；宽限期内没有采样时返回 None
+    async fn reference_value(
+        &self,
+        base: &str,
+        quote: &str,
+        window: AlertWindow,
+    ) -> Result<Option<Decimal>, ServiceError> {
+        let value = sqlx::query_scalar::<_, Decimal>(
+            r#"
+            SELECT value
+            FROM rate_alert_samples
+            WHERE base_currency = $1
+              AND quote_currency = $2
+              AND sampled_at <= NOW() - make_interval(hours => $3)
+              AND sampled_at >= NOW() - make_interval(hours => $3 + $4)
+            ORDER BY sampled_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(base)
+        .bind(quote)
+        .bind(window.hours())
+        .bind(window.grace_hours())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(value)
+    }
+
+    /// 记录触发并发送通知；冷却期内已被其他评估触发时返回 false
+    async fn trigger(
+        &self,
+        notifier: &NotificationService,
+        alert: &RateAlert,
+        value: Decimal,
+        observed: Decimal,
+    ) -> Result<bool, ServiceError> {
+        let claimed = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE rate_alerts
+            SET last_triggered_at = NOW(),
+                last_value = $2,
+                trigger_count = trigger_count + 1,
+                updated_at = NOW()
+            WHERE id = $1
+              AND is_active
+              AND (last_triggered_at IS NULL
+                   OR last_triggered_at <= NOW() - make_interval(mins => cooldown_minutes))
+            RETURNING id
+            "#,
+        )
+        .bind(alert.id)
+        .bind(value)
+        .fetch_optional(&self.pool)
+        .await?;
+        if claimed.is_none() {
+            return Ok(false);
+        }
+
+        let change_percent = AlertCondition::parse(&alert.condition)
+            .filter(|c| c.is_change())
+            .map(|_| observed);
+        notifier
+            .notify(NewNotification {
+                user_id: alert.user_id,
+                family_id: None,
+                kind: "rate_alert".to_string(),
+                priority: NotificationPriority::Normal,
+                title: format!("汇率提醒：{}/{}", alert.base_currency, alert.quote_currency),
+                body: describe_trigger(alert, value, observed),
+                data: serde_json::json!({
+                    "alert_id": alert.id,
+                    "base_currency": alert.base_currency,
+                    "quote_currency": alert.quote_currency,
+                    "condition": alert.condition,
+                    "threshold": alert.threshold,
+                    "change_window": alert.change_window,
+                    "value": value,
+                    "change_percent": change_percent,
+                }),
+                action_url: None,
+            })
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_threshold_conditions() {
+        let t = dec!(7.3);
+        assert_eq!(
+            evaluate_condition(AlertCondition::Above, t, dec!(7.31), None),
+            Some(dec!(7.31))
+        );
+        assert_eq!(
+            evaluate_condition(AlertCondition::Above, t, dec!(7.3), None),
+            None
+        );
+        assert_eq!(
+            evaluate_condition(AlertCondition::Below, t, dec!(7.2), None),
+            Some(dec!(7.2))
+        );
+        assert_eq!(
+            evaluate_condition(AlertCondition::Below, t, dec!(7.4), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_change_conditions() {
+        let five = dec!(5);
+        let reference = Some(dec!(60000));
+        // +6%
+        assert_eq!(
+            evaluate_condition(AlertCondition::ChangeUp, five, dec!(63600), reference),
+            Some(dec!(6))
+        );
+        assert_eq!(
+            evaluate_condition(AlertCondition::ChangeDown, five, dec!(63600), reference),
+            None
+        );
+        // -5% 恰好达到阈值
+        assert_eq!(
+            evaluate_condition(AlertCondition::ChangeDown, five, dec!(57000), reference),
+            Some(dec!(-5))
+        );
+        assert_eq!(
+            evaluate_condition(AlertCondition::ChangeAbs, five, dec!(57000), reference),
+            Some(dec!(-5))
+        );
+        assert_eq!(
+            evaluate_condition(AlertCondition::ChangeAbs, five, dec!(61000), reference),
+            None
+        );
+        // 没有参考值时不触发
+        assert_eq!(
+            evaluate_condition(AlertCondition::ChangeAbs, five, dec!(90000), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let now = Utc::now();
+        assert!(cooldown_elapsed(None, 360, now));
+        assert!(!cooldown_elapsed(
+            Some(now - Duration::minutes(359)),
+            360,
+            now
+        ));
+        assert!(cooldown_elapsed(
+            Some(now - Duration::minutes(360)),
+            360,
+            now
+        ));
+        assert!(cooldown_elapsed(Some(now), 0, now));
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        assert!(validate_rule(AlertCondition::Above, dec!(7.3), None, 60).is_ok());
+        assert!(validate_rule(AlertCondition::ChangeAbs, dec!(5), None, 60).is_err());
+        assert!(validate_rule(
+            AlertCondition::ChangeAbs,
+            dec!(5),
+            Some(AlertWindow::Day),
+            60
+        )
+        .is_ok());
+        assert!(validate_rule(AlertCondition::Above, Decimal::ZERO, None, 60).is_err());
+        assert!(validate_rule(AlertCondition::Above, dec!(1), None, -1).is_err());
+        assert!(normalize_code(" btc ", "base_currency").unwrap() == "BTC");
+        assert!(normalize_code("US D", "base_currency").is_err());
+    }
+}
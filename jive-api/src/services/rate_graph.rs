@@ -0,0 +1,406 @@
+//! 汇率换算图
+//!
+//! 把某一日期可用的全部汇率（exchange_rates 与 crypto_prices）视为无向图：
+//! 每条汇率同时提供正向与反向两条边。换算时按「段数最少 → 最旧一段最新」选择路径，
+//! 因此 CNY→JPY 在只有 EUR 基准汇率的日期也能通过 EUR 中转，加密货币可经 USDT 挂钩到 USD。
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use utoipa::ToSchema;
+
+use super::ServiceError;
+use crate::config::RateResolverConfig;
+
+/// 换算路径中的一段
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RateLeg {
+    pub from: String,
+    pub to: String,
+    pub rate: Decimal,
+    /// 数据来源（api / manual / peg 等）
+    pub source: String,
+    /// 汇率生效日期
+    pub effective_date: NaiveDate,
+    /// 汇率最后更新时间
+    pub updated_at: Option<DateTime<Utc>>,
+    /// 是否由反向汇率取倒数得到
+    pub inverted: bool,
+}
+
+/// 换算结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ResolvedRate {
+    pub from: String,
+    pub to: String,
+    pub rate: Decimal,
+    /// 换算所基于的日期
+    pub as_of: NaiveDate,
+    /// 路径中最旧一段汇率的生效日期
+    pub oldest_effective_date: NaiveDate,
+    pub path: Vec<RateLeg>,
+}
+
+/// 某一日期的汇率图
+#[derive(Debug, Clone)]
+pub struct RateGraph {
+    as_of: NaiveDate,
+    /// from -> to -> 最新的一条边
+    edges: HashMap<String, HashMap<String, RateLeg>>,
+}
+
+impl RateGraph {
+    pub fn new(as_of: NaiveDate) -> Self {
+        Self {
+            as_of,
+            edges: HashMap::new(),
+        }
+    }
+
+    pub fn as_of(&self) -> NaiveDate {
+        self.as_of
+    }
+
+    /// 加入一条汇率（同时加入反向边）；同一币种对保留最新的一条，生效日期相同时直连优先
+    pub fn add_rate(
+        &mut self,
+        from: &str,
+        to: &str,
+        rate: Decimal,
+        source: &str,
+        effective_date: NaiveDate,
+        updated_at: Option<DateTime<Utc>>,
+    ) {
+        let from = from.to_uppercase();
+        let to = to.to_uppercase();
+        if from == to || rate <= Decimal::ZERO || effective_date > self.as_of {
+            return;
+        }
+        let forward = RateLeg {
+            from: from.clone(),
+            to: to.clone(),
+            rate,
+            source: source.to_string(),
+            effective_date,
+            updated_at,
+            inverted: false,
+        };
+        let backward = RateLeg {
+            from: to,
+            to: from,
+            rate: Decimal::ONE / rate,
+            inverted: true,
+            ..forward.clone()
+        };
+        self.insert_edge(forward);
+        self.insert_edge(backward);
+    }
+
+    /// 加入 1:1 挂钩关系（如 USDT:USD），视为当天的汇率
+    pub fn add_peg(&mut self, a: &str, b: &str) {
+        self.add_rate(a, b, Decimal::ONE, "peg", self.as_of, None);
+    }
+
+    fn insert_edge(&mut self, leg: RateLeg) {
+        let slot = self.edges.entry(leg.from.clone()).or_default();
+        let replace = match slot.get(&leg.to) {
+            None => true,
+            Some(existing) => {
+                (leg.effective_date, !leg.inverted, leg.updated_at)
+                    > (
+                        existing.effective_date,
+                        !existing.inverted,
+                        existing.updated_at,
+                    )
+            }
+        };
+        if replace {
+            slot.insert(leg.to.clone(), leg);
+        }
+    }
+
+    pub fn currencies(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// 查找换算路径
+    ///
+    /// 代价为 (段数, 最旧一段距 as_of 的天数)，按字典序最小化；
+    /// 超过 `max_age_days`（>0 时）的汇率不参与换算。
+    pub fn resolve(
+        &self,
+        from: &str,
+        to: &str,
+        max_hops: usize,
+        max_age_days: i64,
+    ) -> Option<ResolvedRate> {
+        let from = from.to_uppercase();
+        let to = to.to_uppercase();
+        if from == to {
+            return Some(ResolvedRate {
+                from: from.clone(),
+                to,
+                rate: Decimal::ONE,
+                as_of: self.as_of,
+                oldest_effective_date: self.as_of,
+                path: vec![],
+            });
+        }
+
+        let age = |leg: &RateLeg| (self.as_of - leg.effective_date).num_days();
+        let mut best: HashMap<&str, (usize, i64)> = HashMap::new();
+        let mut prev: HashMap<&str, &RateLeg> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        best.insert(from.as_str(), (0, 0));
+        heap.push(Reverse((0usize, 0i64, from.as_str())));
+
+        while let Some(Reverse((hops, stale, node))) = heap.pop() {
+            if best.get(node).is_some_and(|b| *b < (hops, stale)) {
+                continue;
+            }
+            if node == to {
+                break;
+            }
+            if hops >= max_hops {
+                continue;
+            }
+            let Some(neighbours) = self.edges.get(node) else {
+                continue;
+            };
+            for (next, leg) in neighbours {
+                let leg_age = age(leg);
+                if max_age_days > 0 && leg_age > max_age_days {
+                    continue;
+                }
+                let cost = (hops + 1, stale.max(leg_age));
+                let improved = best.get(next.as_str()).is_none_or(|b| cost < *b);
+                if improved {
+                    best.insert(next.as_str(), cost);
+                    prev.insert(next.as_str(), leg);
+                    heap.push(Reverse((cost.0, cost.1, next.as_str())));
+                }
+            }
+        }
+
+        prev.get(to.as_str())?;
+        let mut path = Vec::new();
+        let mut cursor = to.as_str();
+        while cursor != from {
+            let leg = prev.get(cursor)?;
+            path.push((*leg).clone());
+            cursor = leg.from.as_str();
+        }
+        path.reverse();
+
+        let rate = path.iter().fold(Decimal::ONE, |acc, leg| acc * leg.rate);
+        let oldest_effective_date = path
+            .iter()
+            .map(|leg| leg.effective_date)
+            .min()
+            .unwrap_or(self.as_of);
+        Some(ResolvedRate {
+            from,
+            to,
+            rate,
+            as_of: self.as_of,
+            oldest_effective_date,
+            path,
+        })
+    }
+
+    /// 按配置换算；找不到路径时返回 NotFound
+    pub fn resolve_with(
+        &self,
+        from: &str,
+        to: &str,
+        config: &RateResolverConfig,
+    ) -> Result<ResolvedRate, ServiceError> {
+        self.resolve(from, to, config.max_hops, config.max_rate_age_days)
+            .ok_or_else(|| ServiceError::NotFound {
+                resource_type: "ExchangeRate".to_string(),
+                id: format!("{}-{}", from, to),
+            })
+    }
+
+    /// 从数据库加载 `as_of` 当天可用的汇率（每个币种对取最新一条）
+    pub async fn load(
+        pool: &PgPool,
+        as_of: NaiveDate,
+        config: &RateResolverConfig,
+    ) -> Result<Self, ServiceError> {
+        let earliest = (config.max_rate_age_days > 0)
+            .then(|| as_of - chrono::Duration::days(config.max_rate_age_days));
+
+        let mut graph = Self::new(as_of);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (from_currency, to_currency)
+                   from_currency, to_currency, rate, source, effective_date, updated_at
+            FROM exchange_rates
+            WHERE effective_date <= $1
+              AND ($2::date IS NULL OR effective_date >= $2)
+            ORDER BY from_currency, to_currency, effective_date DESC, updated_at DESC NULLS LAST
+            "#,
+        )
+        .bind(as_of)
+        .bind(earliest)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            graph.add_rate(
+                row.get::<String, _>("from_currency").as_str(),
+                row.get::<String, _>("to_currency").as_str(),
+                row.get("rate"),
+                row.get::<Option<String>, _>("source")
+                    .as_deref()
+                    .unwrap_or("manual"),
+                row.get("effective_date"),
+                row.get("updated_at"),
+            );
+        }
+
+        // crypto_prices 只保存最新价格，仅在其更新日期落入窗口时参与换算
+        let rows = sqlx::query(
+            r#"
+            SELECT crypto_code, base_currency, price, source, last_updated
+            FROM crypto_prices
+            WHERE last_updated IS NOT NULL
+              AND last_updated::date <= $1
+              AND ($2::date IS NULL OR last_updated::date >= $2)
+            "#,
+        )
+        .bind(as_of)
+        .bind(earliest)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            let last_updated: DateTime<Utc> = row.get("last_updated");
+            graph.add_rate(
+                row.get::<String, _>("crypto_code").as_str(),
+                row.get::<String, _>("base_currency").as_str(),
+                row.get("price"),
+                row.get::<Option<String>, _>("source")
+                    .as_deref()
+                    .unwrap_or("api"),
+                last_updated.date_naive(),
+                Some(last_updated),
+            );
+        }
+
+        for (a, b) in &config.pegs {
+            graph.add_peg(a, b);
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_direct_and_inverse() {
+        let mut g = RateGraph::new(date(2026, 10, 10));
+        g.add_rate("USD", "CNY", dec!(7.2), "api", date(2026, 10, 10), None);
+
+        let r = g.resolve("usd", "cny", 4, 0).unwrap();
+        assert_eq!(r.rate, dec!(7.2));
+        assert_eq!(r.path.len(), 1);
+        assert!(!r.path[0].inverted);
+
+        let r = g.resolve("CNY", "USD", 4, 0).unwrap();
+        assert_eq!(r.rate, Decimal::ONE / dec!(7.2));
+        assert!(r.path[0].inverted);
+
+        assert_eq!(g.resolve("USD", "USD", 4, 0).unwrap().rate, Decimal::ONE);
+        assert!(g.resolve("USD", "JPY", 4, 0).is_none());
+    }
+
+    #[test]
+    fn test_cross_rate_through_non_usd_pivot() {
+        // 只有 EUR 基准汇率
+        let mut g = RateGraph::new(date(2026, 10, 10));
+        g.add_rate("EUR", "CNY", dec!(8), "api", date(2026, 10, 10), None);
+        g.add_rate("EUR", "JPY", dec!(160), "api", date(2026, 10, 10), None);
+
+        let r = g.resolve("CNY", "JPY", 4, 0).unwrap();
+        assert_eq!(r.rate, dec!(20));
+        let hops: Vec<(&str, &str)> = r
+            .path
+            .iter()
+            .map(|l| (l.from.as_str(), l.to.as_str()))
+            .collect();
+        assert_eq!(hops, vec![("CNY", "EUR"), ("EUR", "JPY")]);
+    }
+
+    #[test]
+    fn test_prefers_fewer_hops_then_fresher_path() {
+        let mut g = RateGraph::new(date(2026, 10, 10));
+        // 两段路径：经 USD（旧）或经 EUR（新）
+        g.add_rate("USD", "CNY", dec!(7), "api", date(2026, 10, 2), None);
+        g.add_rate("USD", "JPY", dec!(140), "api", date(2026, 10, 10), None);
+        g.add_rate("EUR", "CNY", dec!(8), "api", date(2026, 10, 9), None);
+        g.add_rate("EUR", "JPY", dec!(160), "api", date(2026, 10, 9), None);
+        // 三段但全新的路径不应胜出
+        g.add_rate("CNY", "HKD", dec!(1.1), "api", date(2026, 10, 10), None);
+        g.add_rate("HKD", "SGD", dec!(0.17), "api", date(2026, 10, 10), None);
+        g.add_rate("SGD", "JPY", dec!(110), "api", date(2026, 10, 10), None);
+
+        let r = g.resolve("CNY", "JPY", 4, 0).unwrap();
+        assert_eq!(r.path.len(), 2);
+        assert_eq!(r.path[0].to, "EUR");
+        assert_eq!(r.oldest_effective_date, date(2026, 10, 9));
+    }
+
+    #[test]
+    fn test_newer_rate_for_same_pair_wins() {
+        let mut g = RateGraph::new(date(2026, 10, 10));
+        g.add_rate("USD", "EUR", dec!(0.9), "api", date(2026, 10, 8), None);
+        g.add_rate("EUR", "USD", dec!(1.25), "manual", date(2026, 10, 9), None);
+
+        let r = g.resolve("USD", "EUR", 4, 0).unwrap();
+        assert_eq!(r.rate, dec!(0.8));
+        assert_eq!(r.path[0].source, "manual");
+        assert!(r.path[0].inverted);
+    }
+
+    #[test]
+    fn test_staleness_limit_and_future_rates() {
+        let mut g = RateGraph::new(date(2026, 10, 20));
+        g.add_rate("USD", "EUR", dec!(0.9), "api", date(2026, 10, 1), None);
+        g.add_rate("USD", "GBP", dec!(0.8), "api", date(2026, 10, 21), None);
+
+        assert!(g.resolve("USD", "EUR", 4, 7).is_none());
+        assert!(g.resolve("USD", "EUR", 4, 0).is_some());
+        // 晚于 as_of 的汇率不会加入
+        assert!(g.resolve("USD", "GBP", 4, 0).is_none());
+    }
+
+    #[test]
+    fn test_crypto_via_usdt_peg() {
+        let mut g = RateGraph::new(date(2026, 10, 10));
+        g.add_rate(
+            "BTC",
+            "USDT",
+            dec!(60000),
+            "binance",
+            date(2026, 10, 10),
+            None,
+        );
+        g.add_rate("USD", "CNY", dec!(7), "api", date(2026, 10, 10), None);
+        g.add_peg("USDT", "USD");
+
+        let r = g.resolve("BTC", "CNY", 4, 0).unwrap();
+        assert_eq!(r.rate, dec!(420000));
+        assert_eq!(r.path.len(), 3);
+        assert_eq!(r.path[1].source, "peg");
+
+        assert!(g.resolve("BTC", "CNY", 2, 0).is_none());
+    }
+}
@@ -4,16 +4,46 @@ use std::sync::Arc;
 use tokio::time::{interval, Duration as TokioDuration};
 use tracing::{error, info, warn};
 
+use super::credit_card_service::CreditCardService;
 use super::currency_service::CurrencyService;
+use super::email::{build_mailer, EmailOutbox, Mailer};
+use super::financial_digest_service::FinancialDigestService;
+use super::installment_service::InstallmentService;
+use super::login_security_service::LoginSecurityService;
+use super::notification_service::NotificationService;
+use super::push::PushRegistry;
+use super::quote_providers::{quote_provider_from_env, QuoteProvider};
+use super::rate_alert_service::RateAlertService;
+use super::security_price_service::SecurityPriceService;
+use super::transaction_valuation_service::{
+    TransactionValuationService, ValuationReason, ValuationScope,
+};
+use super::webhook_service::WebhookService;
+use crate::config::AppConfig;
+use crate::utils::outbound;
+use crate::ws::WsConnectionManager;
 
 /// 定时任务管理器
 pub struct ScheduledTaskManager {
     pool: Arc<PgPool>,
+    config: Arc<AppConfig>,
+    push: Arc<PushRegistry>,
+    ws: Arc<WsConnectionManager>,
 }
 
 impl ScheduledTaskManager {
-    pub fn new(pool: Arc<PgPool>) -> Self {
-        Self { pool }
+    pub fn new(
+        pool: Arc<PgPool>,
+        config: Arc<AppConfig>,
+        push: Arc<PushRegistry>,
+        ws: Arc<WsConnectionManager>,
+    ) -> Self {
+        Self {
+            pool,
+            config,
+            push,
+            ws,
+        }
     }
 
     /// 启动所有定时任务
@@ -76,6 +106,17 @@ impl ScheduledTaskManager {
             manager_clone.run_manual_overrides_cleanup_task(mins).await;
         });
 
+        // 启动登录会话清理任务（延迟100秒后开始，每天执行）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!(
+                "Login session cleanup task will start in 100 seconds, retention: {} days",
+                manager_clone.config.login_security.session_retention_days
+            );
+            tokio::time::sleep(TokioDuration::from_secs(100)).await;
+            manager_clone.run_session_cleanup_task().await;
+        });
+
         // 启动全球市场统计更新任务（延迟45秒后开始，每10分钟执行）
         let manager_clone = Arc::clone(&self);
         tokio::spawn(async move {
@@ -84,9 +125,370 @@ impl ScheduledTaskManager {
             manager_clone.run_global_market_stats_task().await;
         });
 
+        // 启动邮件发件箱投递任务（延迟10秒后开始，间隔由 EMAIL_OUTBOX_INTERVAL_SECS 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            let config = &manager_clone.config.email;
+            let mailer = match build_mailer(config) {
+                Ok(Some(mailer)) => mailer,
+                Ok(None) => {
+                    info!("Email outbox task disabled by EMAIL_BACKEND");
+                    return;
+                }
+                Err(e) => {
+                    error!("Failed to initialize mailer: {:?}", e);
+                    return;
+                }
+            };
+            info!(
+                "Email outbox task ({}) will start in 10 seconds, interval: {} seconds",
+                mailer.name(),
+                config.outbox_interval_secs
+            );
+            tokio::time::sleep(TokioDuration::from_secs(10)).await;
+            manager_clone.run_email_outbox_task(mailer).await;
+        });
+
+        // 启动交易本位币估值任务（延迟40秒后开始，间隔由 VALUATION_INTERVAL_SECS 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            let secs = std::env::var("VALUATION_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60);
+            let batch_size = std::env::var("VALUATION_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(500);
+            info!(
+                "Transaction valuation task will start in 40 seconds, interval: {} seconds",
+                secs
+            );
+            tokio::time::sleep(TokioDuration::from_secs(40)).await;
+            manager_clone.run_valuation_task(secs, batch_size).await;
+        });
+
+        // 启动证券行情刷新与持仓重估任务（延迟50秒后开始，间隔由 SECURITY_QUOTE_INTERVAL_MIN 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            let mins = std::env::var("SECURITY_QUOTE_INTERVAL_MIN")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60);
+            let provider = quote_provider_from_env();
+            info!(
+                "Security quote refresh task ({}) will start in 50 seconds, interval: {} minutes",
+                provider
+                    .as_ref()
+                    .map(|p| p.name())
+                    .unwrap_or("revalue only"),
+                mins
+            );
+            tokio::time::sleep(TokioDuration::from_secs(50)).await;
+            manager_clone.run_security_quote_task(provider, mins).await;
+        });
+
+        // 启动信用卡账单任务（延迟70秒后开始，间隔由 CREDIT_CARD_INTERVAL_MIN 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            let mins = std::env::var("CREDIT_CARD_INTERVAL_MIN")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(360);
+            info!(
+                "Credit card statement task will start in 70 seconds, interval: {} minutes",
+                mins
+            );
+            tokio::time::sleep(TokioDuration::from_secs(70)).await;
+            manager_clone.run_credit_card_task(mins).await;
+        });
+
+        // 启动通知站外投递任务（延迟15秒后开始，间隔由 NOTIFICATION_DELIVERY_INTERVAL_SECS 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!(
+                "Notification delivery task will start in 15 seconds, interval: {} seconds",
+                manager_clone.config.notification.delivery_interval_secs
+            );
+            tokio::time::sleep(TokioDuration::from_secs(15)).await;
+            manager_clone.run_notification_delivery_task().await;
+        });
+
+        // 启动财务摘要任务（延迟120秒后开始，间隔由 FINANCIAL_DIGEST_INTERVAL_SECS 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            let config = &manager_clone.config.notification;
+            if !config.financial_digest_enabled {
+                info!("Financial digest task disabled by FINANCIAL_DIGEST_ENABLED");
+                return;
+            }
+            info!(
+                "Financial digest task will start in 120 seconds, interval: {} seconds",
+                config.financial_digest_interval_secs
+            );
+            tokio::time::sleep(TokioDuration::from_secs(120)).await;
+            manager_clone.run_financial_digest_task().await;
+        });
+
+        // 启动家庭 Webhook 投递任务（延迟20秒后开始，间隔由 WEBHOOK_DELIVERY_INTERVAL_SECS 控制）
+        let manager_clone = Arc::clone(&self);
+        tokio::spawn(async move {
+            let config = &manager_clone.config.webhook;
+            if !config.enabled {
+                info!("Webhook delivery task disabled by WEBHOOK_DELIVERY_ENABLED");
+                return;
+            }
+            info!(
+                "Webhook delivery task will start in 20 seconds, interval: {} seconds",
+                config.delivery_interval_secs
+            );
+            tokio::time::sleep(TokioDuration::from_secs(20)).await;
+            manager_clone.run_webhook_delivery_task().await;
+        });
+
         info!("All scheduled tasks initialized (will start after delay)");
     }
 
+    /// 交易本位币估值任务：处理 valuation_jobs 队列，并每天为缺少汇率的交易安排一次重试
+    async fn run_valuation_task(&self, interval_secs: u64, batch_size: i64) {
+        let service = TransactionValuationService::new((*self.pool).clone());
+        let mut interval = interval(TokioDuration::from_secs(interval_secs.max(1)));
+        let retry_every = std::time::Duration::from_secs(24 * 60 * 60);
+        let mut last_retry: Option<std::time::Instant> = None;
+
+        loop {
+            interval.tick().await;
+
+            if last_retry.is_none_or(|t| t.elapsed() >= retry_every) {
+                let scope = ValuationScope {
+                    only_missing: true,
+                    ..Default::default()
+                };
+                match service.enqueue(ValuationReason::RetryMissing, scope).await {
+                    Ok(_) => last_retry = Some(std::time::Instant::now()),
+                    Err(e) => warn!("Failed to enqueue valuation retry: {:?}", e),
+                }
+            }
+
+            match service
+                .run_pending(batch_size.max(1), &self.config.rate_resolver)
+                .await
+            {
+                Ok(stats) if stats.processed > 0 => {
+                    info!(
+                        "Transaction valuation: jobs={}, processed={}, unresolved={}",
+                        stats.jobs_completed, stats.processed, stats.unresolved
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Transaction valuation failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 证券行情刷新任务：拉取最新收盘价后重估投资账户
+    async fn run_security_quote_task(
+        &self,
+        provider: Option<Arc<dyn QuoteProvider>>,
+        interval_minutes: u64,
+    ) {
+        let service = SecurityPriceService::new((*self.pool).clone());
+        let mut interval = interval(TokioDuration::from_secs(interval_minutes.max(1) * 60));
+
+        loop {
+            interval.tick().await;
+            match service
+                .refresh_quotes(provider.as_deref(), &self.config.rate_resolver)
+                .await
+            {
+                Ok(stats) => {
+                    info!(
+                        "Security quotes: requested={}, quoted={}, accounts_revalued={}, accounts_skipped={}",
+                        stats.requested, stats.quoted, stats.accounts_revalued, stats.accounts_skipped
+                    );
+                }
+                Err(e) => {
+                    error!("Security quote refresh failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 信用卡账单任务：记入到期的分期、判定到期账单、生成新账单并发送还款提醒
+    async fn run_credit_card_task(&self, interval_minutes: u64) {
+        let service = CreditCardService::new((*self.pool).clone());
+        let installments = InstallmentService::new((*self.pool).clone());
+        let mut interval = interval(TokioDuration::from_secs(interval_minutes.max(1) * 60));
+
+        loop {
+            interval.tick().await;
+            let today = chrono::Utc::now().date_naive();
+            // 未挂信用卡的先买后付账户也在这里入账分期
+            match installments
+                .post_due(None, today, &self.config.rate_resolver)
+                .await
+            {
+                Ok(posted) if posted > 0 => info!("Installments: posted {} periods", posted),
+                Ok(_) => {}
+                Err(e) => error!("Installment posting failed: {:?}", e),
+            }
+            match service.run_due(today, &self.config, &self.ws).await {
+                Ok(stats) => {
+                    info!(
+                        "Credit cards: cards={}, statements={}, assessed={}, reminders={}, failed={}",
+                        stats.cards,
+                        stats.statements_generated,
+                        stats.statements_assessed,
+                        stats.reminders_sent,
+                        stats.failed
+                    );
+                }
+                Err(e) => {
+                    error!("Credit card statement task failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 邮件发件箱投递任务
+    async fn run_email_outbox_task(&self, mailer: Arc<dyn Mailer>) {
+        let config = &self.config.email;
+        let outbox = EmailOutbox::new((*self.pool).clone(), config);
+        let mut interval = interval(TokioDuration::from_secs(config.outbox_interval_secs.max(1)));
+
+        loop {
+            interval.tick().await;
+            match outbox
+                .process_due(
+                    mailer.as_ref(),
+                    config.outbox_batch_size,
+                    config.outbox_max_attempts,
+                )
+                .await
+            {
+                Ok(stats) if stats.sent + stats.retried + stats.failed > 0 => {
+                    info!(
+                        "Email outbox: sent={}, retried={}, failed={}",
+                        stats.sent, stats.retried, stats.failed
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Email outbox processing failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 财务摘要任务：为到达摘要时刻的成员生成上一周 / 上一月的摘要
+    async fn run_financial_digest_task(&self) {
+        let service = FinancialDigestService::new((*self.pool).clone());
+        let mut interval = interval(TokioDuration::from_secs(
+            self.config
+                .notification
+                .financial_digest_interval_secs
+                .max(60),
+        ));
+
+        loop {
+            interval.tick().await;
+            match service.run_due(&self.config, &self.ws).await {
+                Ok(stats) if stats != Default::default() => {
+                    info!(
+                        "Financial digests: generated={}, skipped={}, failed={}",
+                        stats.generated, stats.skipped, stats.failed
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Financial digest task failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 通知站外投递任务：邮件写入发件箱，Webhook 与设备推送直接发送
+    async fn run_notification_delivery_task(&self) {
+        let config = &self.config.notification;
+        let service = NotificationService::new((*self.pool).clone(), config, self.ws.clone());
+        let outbox = EmailOutbox::new((*self.pool).clone(), &self.config.email);
+        let client = match outbound::client(std::time::Duration::from_secs(
+            config.webhook_timeout_secs.max(1),
+        )) {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to build notification webhook client: {:?}", e);
+                return;
+            }
+        };
+        let mut interval = interval(TokioDuration::from_secs(
+            config.delivery_interval_secs.max(1),
+        ));
+
+        loop {
+            interval.tick().await;
+            match service
+                .deliver_due(
+                    &client,
+                    &self.push,
+                    &outbox,
+                    config.delivery_batch_size,
+                    config.webhook_max_attempts,
+                )
+                .await
+            {
+                Ok(stats) if stats != Default::default() => {
+                    info!(
+                        "Notification delivery: sent={}, retried={}, failed={}, skipped={}",
+                        stats.sent, stats.retried, stats.failed, stats.skipped
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Notification delivery failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 家庭 Webhook 投递任务：发送到期的事件，失败按指数退避重试
+    async fn run_webhook_delivery_task(&self) {
+        let config = &self.config.webhook;
+        let service = WebhookService::new((*self.pool).clone());
+        let client =
+            match outbound::client(std::time::Duration::from_secs(config.timeout_secs.max(1))) {
+                Ok(client) => client,
+                Err(e) => {
+                    error!("Failed to build webhook client: {:?}", e);
+                    return;
+                }
+            };
+        let mut interval = interval(TokioDuration::from_secs(
+            config.delivery_interval_secs.max(1),
+        ));
+
+        loop {
+            interval.tick().await;
+            match service
+                .deliver_due(&client, config.delivery_batch_size, config.max_attempts)
+                .await
+            {
+                Ok(stats) if stats != Default::default() => {
+                    info!(
+                        "Webhook delivery: sent={}, retried={}, failed={}",
+                        stats.sent, stats.retried, stats.failed
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Webhook delivery failed: {:?}", e);
+                }
+            }
+        }
+    }
+
     /// 汇率更新任务
     async fn run_exchange_rate_update_task(&self) {
         let mut interval = interval(TokioDuration::from_secs(15 * 60)); // 15分钟
@@ -94,11 +496,13 @@ impl ScheduledTaskManager {
         // 第一次执行汇率更新
         info!("Starting initial exchange rate update");
         self.update_exchange_rates().await;
+        self.evaluate_rate_alerts().await;
 
         loop {
             interval.tick().await;
             info!("Running scheduled exchange rate update");
             self.update_exchange_rates().await;
+            self.evaluate_rate_alerts().await;
         }
     }
 
@@ -133,6 +537,23 @@ impl ScheduledTaskManager {
         }
     }
 
+    /// 刷新汇率或加密货币价格后评估用户的提醒规则
+    async fn evaluate_rate_alerts(&self) {
+        let service = RateAlertService::new((*self.pool).clone(), self.ws.clone());
+        match service.evaluate_all(&self.config).await {
+            Ok(stats) if stats.triggered > 0 => {
+                info!(
+                    "Rate alerts: evaluated={}, triggered={}, skipped={}",
+                    stats.evaluated, stats.triggered, stats.skipped
+                );
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Rate alert evaluation failed: {:?}", e);
+            }
+        }
+    }
+
     /// 加密货币价格更新任务
     async fn run_crypto_price_update_task(&self) {
         let mut interval = interval(TokioDuration::from_secs(5 * 60)); // 5分钟
@@ -140,11 +561,13 @@ impl ScheduledTaskManager {
         // 第一次执行
         info!("Starting initial crypto price update");
         self.update_crypto_prices().await;
+        self.evaluate_rate_alerts().await;
 
         loop {
             interval.tick().await;
             info!("Running scheduled crypto price update");
             self.update_crypto_prices().await;
+            self.evaluate_rate_alerts().await;
         }
     }
 
@@ -352,6 +775,20 @@ impl ScheduledTaskManager {
         }
     }
 
+    /// 登录会话清理任务：删除超过保留期的过期 / 已撤销会话
+    async fn run_session_cleanup_task(&self) {
+        let service = LoginSecurityService::new((*self.pool).clone(), &self.config);
+        let mut interval = interval(TokioDuration::from_secs(24 * 60 * 60));
+        loop {
+            interval.tick().await;
+            match service.prune_sessions().await {
+                Ok(n) if n > 0 => info!("Pruned {} expired login sessions", n),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to prune login sessions: {:?}", e),
+            }
+        }
+    }
+
     /// 获取所有活跃的基础货币
     async fn get_active_base_currencies(&self) -> Result<Vec<String>, sqlx::Error> {
         let raw = sqlx::query_scalar!(
@@ -483,7 +920,12 @@ impl ScheduledTaskManager {
 }
 
 /// 初始化并启动定时任务
-pub async fn init_scheduled_tasks(pool: Arc<PgPool>) {
-    let manager = Arc::new(ScheduledTaskManager::new(pool));
+pub async fn init_scheduled_tasks(
+    pool: Arc<PgPool>,
+    config: Arc<AppConfig>,
+    push: Arc<PushRegistry>,
+    ws: Arc<WsConnectionManager>,
+) {
+    let manager = Arc::new(ScheduledTaskManager::new(pool, config, push, ws));
     manager.start_all_tasks().await;
 }
@@ -0,0 +1,729 @@
+//! 证券价格历史：CSV 导入、行情刷新与持仓重估
+//!
+//! 每日收盘价保存在 `security_prices`，`securities.current_price` 始终取最新一天的价格。
+//! 每次刷新后按未结清批次与最新价格重估投资账户，写入当天的 `valuations`（`market` 类型）。
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::quote_providers::{QuoteProvider, QuoteRequest};
+use super::rate_graph::RateGraph;
+use super::ServiceError;
+use crate::config::RateResolverConfig;
+
+const MAX_REPORTED_ERRORS: usize = 20;
+const INSERT_CHUNK: usize = 1000;
+
+/// CSV 中的一行行情
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedQuote {
+    /// 未提供时使用导入参数中的证券
+    pub ticker: Option<String>,
+    pub exchange: Option<String>,
+    pub price_date: NaiveDate,
+    pub close: Decimal,
+    pub open: Option<Decimal>,
+    pub high: Option<Decimal>,
+    pub low: Option<Decimal>,
+    pub volume: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedQuotes {
+    pub quotes: Vec<ImportedQuote>,
+    pub rejected: usize,
+    pub errors: Vec<String>,
+}
+
+impl ParsedQuotes {
+    fn reject(&mut self, message: String) {
+        self.rejected += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(message);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Columns {
+    date: usize,
+    ticker: Option<usize>,
+    exchange: Option<usize>,
+    close: usize,
+    open: Option<usize>,
+    high: Option<usize>,
+    low: Option<usize>,
+    volume: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(cells: &[&str]) -> Result<Self, ServiceError> {
+        let find = |names: &[&str]| {
+            cells.iter().position(|c| {
+                let c = c.to_ascii_lowercase().replace([' ', '-'], "_");
+                names.contains(&c.as_str())
+            })
+        };
+        let missing = |name: &str| {
+            ServiceError::ValidationError(format!("Quote CSV header has no {} column", name))
+        };
+        Ok(Self {
+            date: find(&["date", "trade_date", "price_date"]).ok_or_else(|| missing("date"))?,
+            ticker: find(&["ticker", "symbol", "code"]),
+            exchange: find(&["exchange", "market"]),
+            close: find(&["close", "close_price", "price"]).ok_or_else(|| missing("close"))?,
+            open: find(&["open", "open_price"]),
+            high: find(&["high", "high_price"]),
+            low: find(&["low", "low_price"]),
+            volume: find(&["volume"]),
+        })
+    }
+
+    /// 无表头时的列顺序：date,ticker,close
+    fn positional() -> Self {
+        Self {
+            date: 0,
+            ticker: Some(1),
+            close: 2,
+            ..Default::default()
+        }
+    }
+}
+
+fn parse_price(raw: &str) -> Option<Decimal> {
+    let raw = raw.trim().replace(',', "");
+    Decimal::from_str(&raw)
+        .or_else(|_| Decimal::from_scientific(&raw))
+        .ok()
+        .filter(|p| *p > Decimal::ZERO)
+}
+
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    let raw = raw.trim();
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(raw, "%Y/%m/%d"))
+        .or_else(|_| NaiveDate::parse_from_str(raw, "%Y%m%d"))
+        .ok()
+}
+
+/// 解析行情 CSV
+///
+/// 有表头时按列名识别（date、ticker/symbol、exchange、close/price、open、high、low、volume），
+/// 可直接导入常见行情软件导出的 `Date,Open,High,Low,Close,Volume`；无表头时按 `date,ticker,close`。
+/// 单行错误计入 `rejected`。
+pub fn parse_quotes_csv(content: &str) -> Result<ParsedQuotes, ServiceError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut parsed = ParsedQuotes::default();
+    let mut columns: Option<Columns> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cells: Vec<&str> = line
+            .split(',')
+            .map(|c| c.trim().trim_matches('"'))
+            .collect();
+        let cols = match columns {
+            Some(cols) => cols,
+            None if cells.first().is_some_and(|c| parse_date(c).is_none()) => {
+                columns = Some(Columns::from_header(&cells)?);
+                continue;
+            }
+            None => *columns.insert(Columns::positional()),
+        };
+
+        let cell = |i: Option<usize>| i.and_then(|i| cells.get(i)).filter(|c| !c.is_empty());
+        let Some(price_date) = cell(Some(cols.date)).and_then(|c| parse_date(c)) else {
+            parsed.reject(format!("line {}: invalid date", idx + 1));
+            continue;
+        };
+        let Some(close) = cell(Some(cols.close)).and_then(|c| parse_price(c)) else {
+            parsed.reject(format!("line {}: invalid close price", idx + 1));
+            continue;
+        };
+        parsed.quotes.push(ImportedQuote {
+            ticker: cell(cols.ticker).map(|t| t.to_uppercase()),
+            exchange: cell(cols.exchange).map(|e| e.to_uppercase()),
+            price_date,
+            close,
+            open: cell(cols.open).and_then(|c| parse_price(c)),
+            high: cell(cols.high).and_then(|c| parse_price(c)),
+            low: cell(cols.low).and_then(|c| parse_price(c)),
+            volume: cell(cols.volume).and_then(|c| Decimal::from_str(&c.replace(',', "")).ok()),
+        });
+    }
+    Ok(parsed)
+}
+
+/// 单个证券在账户中的持仓（价格与成本已换算为账户币种）
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionValue {
+    pub security_id: Uuid,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    pub price: Decimal,
+}
+
+/// 账户估值，对应 `valuations` 的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountValuation {
+    pub amount: Decimal,
+    pub quantity: Decimal,
+    pub cost_basis: Decimal,
+    /// 只持有一只证券时记录其价格
+    pub market_price: Option<Decimal>,
+}
+
+pub fn summarize_positions(positions: &[PositionValue]) -> AccountValuation {
+    let amount: Decimal = positions.iter().map(|p| p.quantity * p.price).sum();
+    let securities: BTreeSet<Uuid> = positions.iter().map(|p| p.security_id).collect();
+    AccountValuation {
+        amount: amount.round_dp(2),
+        quantity: positions
+            .iter()
+            .map(|p| p.quantity)
+            .sum::<Decimal>()
+            .round_dp(6),
+        cost_basis: positions
+            .iter()
+            .map(|p| p.cost_basis)
+            .sum::<Decimal>()
+            .round_dp(2),
+        market_price: (securities.len() == 1)
+            .then(|| positions.first().map(|p| p.price.round_dp(6)))
+            .flatten(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct SecurityPrice {
+    pub security_id: Uuid,
+    pub price_date: NaiveDate,
+    pub close_price: Decimal,
+    pub open_price: Option<Decimal>,
+    pub high_price: Option<Decimal>,
+    pub low_price: Option<Decimal>,
+    pub volume: Option<Decimal>,
+    pub source: String,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuoteImportSummary {
+    pub source: String,
+    /// 匹配到证券并去重后的行情条数
+    pub selected: usize,
+    pub inserted: u64,
+    pub updated: u64,
+    pub rejected: usize,
+    pub errors: Vec<String>,
+    /// 证券表中找不到的代码
+    pub unknown_tickers: Vec<String>,
+    pub securities: usize,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+/// 刷新结果
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct QuoteRefreshStats {
+    pub requested: usize,
+    pub quoted: usize,
+    pub accounts_revalued: usize,
+    /// 缺少价格或汇率而未能重估的账户
+    pub accounts_skipped: usize,
+}
+
+struct PriceRow {
+    security_id: Uuid,
+    price_date: NaiveDate,
+    close: Decimal,
+    open: Option<Decimal>,
+    high: Option<Decimal>,
+    low: Option<Decimal>,
+    volume: Option<Decimal>,
+}
+
+pub struct SecurityPriceService {
+    pool: PgPool,
+}
+
+impl SecurityPriceService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_prices(
+        &self,
+        security_id: Uuid,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<Vec<SecurityPrice>, ServiceError> {
+        let prices = sqlx::query_as::<_, SecurityPrice>(
+            r#"
+            SELECT security_id, price_date, close_price, open_price, high_price, low_price,
+                   volume, source
+            FROM security_prices
+            WHERE security_id = $1
+              AND ($2::date IS NULL OR price_date >= $2)
+              AND ($3::date IS NULL OR price_date <= $3)
+            ORDER BY price_date
+            "#,
+        )
+        .bind(security_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(prices)
+    }
+
+    /// 导入行情 CSV；`security_id` 用于不含代码列的单证券文件
+    pub async fn import_csv(
+        &self,
+        content: &str,
+        security_id: Option<Uuid>,
+        source: Option<String>,
+    ) -> Result<QuoteImportSummary, ServiceError> {
+        let parsed = parse_quotes_csv(content)?;
+        let source = source
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "import".to_string());
+
+        let securities = sqlx::query(
+            "SELECT id, UPPER(ticker) AS ticker, UPPER(exchange) AS exchange FROM securities",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut by_ticker: HashMap<String, Vec<(Uuid, Option<String>)>> = HashMap::new();
+        for row in securities {
+            by_ticker
+                .entry(row.get("ticker"))
+                .or_default()
+                .push((row.get("id"), row.get("exchange")));
+        }
+        if let Some(id) = security_id {
+            if !by_ticker.values().flatten().any(|(sid, _)| *sid == id) {
+                return Err(ServiceError::not_found("Security", id));
+            }
+        }
+
+        let mut rejected = parsed.rejected;
+        let mut errors = parsed.errors;
+        let mut unknown = BTreeSet::new();
+        // 同一证券同一天只保留最后一行
+        let mut rows: BTreeMap<(Uuid, NaiveDate), PriceRow> = BTreeMap::new();
+        for quote in parsed.quotes {
+            let resolved = match &quote.ticker {
+                None => security_id,
+                Some(ticker) => by_ticker.get(ticker).and_then(|candidates| {
+                    match &quote.exchange {
+                        Some(exchange) => candidates
+                            .iter()
+                            .find(|(_, e)| e.as_deref() == Some(exchange.as_str())),
+                        None if candidates.len() == 1 => candidates.first(),
+                        None => None,
+                    }
+                    .map(|(id, _)| *id)
+                }),
+            };
+            let Some(id) = resolved else {
+                match &quote.ticker {
+                    Some(ticker) => {
+                        unknown.insert(match &quote.exchange {
+                            Some(exchange) => format!("{}.{}", ticker, exchange),
+                            None => ticker.clone(),
+                        });
+                    }
+                    None => {
+                        rejected += 1;
+                        if errors.len() < MAX_REPORTED_ERRORS {
+                            errors.push(format!(
+                                "{}: no ticker column and no security_id given",
+                                quote.price_date
+                            ));
+                        }
+                    }
+                }
+                continue;
+            };
+            rows.insert(
+                (id, quote.price_date),
+                PriceRow {
+                    security_id: id,
+                    price_date: quote.price_date,
+                    close: quote.close,
+                    open: quote.open,
+                    high: quote.high,
+                    low: quote.low,
+                    volume: quote.volume,
+                },
+            );
+        }
+
+        let rows: Vec<PriceRow> = rows.into_values().collect();
+        let (inserted, updated) = self.upsert_prices(&rows, &source).await?;
+        let touched: Vec<Uuid> = rows
+            .iter()
+            .map(|r| r.security_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        self.sync_current_prices(&touched).await?;
+
+        Ok(QuoteImportSummary {
+            source,
+            selected: rows.len(),
+            inserted,
+            updated,
+            rejected,
+            errors,
+            unknown_tickers: unknown.into_iter().collect(),
+            securities: touched.len(),
+            start_date: rows.iter().map(|r| r.price_date).min(),
+            end_date: rows.iter().map(|r| r.price_date).max(),
+        })
+    }
+
+    /// 从数据源拉取行情并重估全部投资账户
+    pub async fn refresh_quotes(
+        &self,
+        provider: Option<&dyn QuoteProvider>,
+        config: &RateResolverConfig,
+    ) -> Result<QuoteRefreshStats, ServiceError> {
+        let mut stats = QuoteRefreshStats::default();
+        if let Some(provider) = provider {
+            let requests: Vec<QuoteRequest> = sqlx::query(
+                "SELECT id, ticker, exchange, currency FROM securities WHERE is_active = true",
+            )
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| QuoteRequest {
+                security_id: row.get("id"),
+                ticker: row.get("ticker"),
+                exchange: row.get("exchange"),
+                currency: row.get("currency"),
+            })
+            .collect();
+            stats.requested = requests.len();
+
+            if !requests.is_empty() {
+                let quotes = provider.fetch_quotes(&requests).await?;
+                let rows: Vec<PriceRow> = quotes
+                    .iter()
+                    .filter(|q| q.close > Decimal::ZERO)
+                    .map(|q| PriceRow {
+                        security_id: q.security_id,
+                        price_date: q.price_date,
+                        close: q.close,
+                        open: None,
+                        high: None,
+                        low: None,
+                        volume: None,
+                    })
+                    .collect();
+                stats.quoted = rows.len();
+                self.upsert_prices(&rows, provider.name()).await?;
+                let touched: Vec<Uuid> = rows.iter().map(|r| r.security_id).collect();
+                self.sync_current_prices(&touched).await?;
+            }
+        }
+
+        let (revalued, skipped) = self
+            .revalue_holdings(Utc::now().date_naive(), config)
+            .await?;
+        stats.accounts_revalued = revalued;
+        stats.accounts_skipped = skipped;
+        Ok(stats)
+    }
+
+    /// 按未结清批次与证券最新价格写入当天的账户估值；返回 (已重估, 跳过) 账户数
+    pub async fn revalue_holdings(
+        &self,
+        date: NaiveDate,
+        config: &RateResolverConfig,
+    ) -> Result<(usize, usize), ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT l.account_id, a.currency AS account_currency, l.security_id,
+                   s.currency AS security_currency, s.current_price,
+                   SUM(l.quantity) AS quantity, SUM(l.cost_basis) AS cost_basis
+            FROM investment_lots l
+            JOIN accounts a ON a.id = l.account_id
+            JOIN securities s ON s.id = l.security_id
+            WHERE l.quantity > 0 AND a.deleted_at IS NULL
+            GROUP BY l.account_id, a.currency, l.security_id, s.currency, s.current_price
+            ORDER BY l.account_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut graph: Option<RateGraph> = None;
+        let mut accounts: BTreeMap<Uuid, Option<Vec<PositionValue>>> = BTreeMap::new();
+        for row in rows {
+            let account_id: Uuid = row.get("account_id");
+            let account_currency: String = row.get("account_currency");
+            let security_currency: String = row.get("security_currency");
+            let price: Option<Decimal> = row.get("current_price");
+
+            let rate = if account_currency.eq_ignore_ascii_case(&security_currency) {
+                Some(Decimal::ONE)
+            } else {
+                if graph.is_none() {
+                    graph = Some(RateGraph::load(&self.pool, date, config).await?);
+                }
+                graph
+                    .as_ref()
+                    .and_then(|g| {
+                        g.resolve_with(&security_currency, &account_currency, config)
+                            .ok()
+                    })
+                    .map(|resolved| resolved.rate)
+            };
+
+            let entry = accounts
+                .entry(account_id)
+                .or_insert_with(|| Some(Vec::new()));
+            match (entry.as_mut(), price, rate) {
+                (Some(positions), Some(price), Some(rate)) => positions.push(PositionValue {
+                    security_id: row.get("security_id"),
+                    quantity: row.get("quantity"),
+                    cost_basis: row.get::<Decimal, _>("cost_basis") * rate,
+                    price: price * rate,
+                }),
+                // 任一持仓缺少价格或汇率时整个账户不估值，避免写入偏低的数值
+                _ => *entry = None,
+            }
+        }
+
+        // 已清仓但上次市价估值不为零的账户写入零值，保持估值序列连续
+        let liquidated: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT account_id FROM (
+                SELECT DISTINCT ON (v.account_id) v.account_id, v.amount
+                FROM valuations v
+                WHERE v.valuation_type = 'market' AND v.valuation_date < $1
+                ORDER BY v.account_id, v.valuation_date DESC
+            ) latest
+            WHERE latest.amount <> 0
+              AND NOT EXISTS (
+                  SELECT 1 FROM investment_lots l
+                  WHERE l.account_id = latest.account_id AND l.quantity > 0
+              )
+              AND EXISTS (SELECT 1 FROM investment_lots l WHERE l.account_id = latest.account_id)
+            "#,
+        )
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+        for account_id in liquidated {
+            accounts
+                .entry(account_id)
+                .or_insert_with(|| Some(Vec::new()));
+        }
+
+        let mut revalued = 0;
+        let mut skipped = 0;
+        for (account_id, positions) in accounts {
+            let Some(positions) = positions else {
+                skipped += 1;
+                continue;
+            };
+            let valuation = summarize_positions(&positions);
+            sqlx::query(
+                r#"
+                INSERT INTO valuations
+                    (account_id, amount, valuation_date, valuation_type, market_price, quantity,
+                     cost_basis, notes)
+                VALUES ($1, $2, $3, 'market', $4, $5, $6, 'quote refresh')
+                ON CONFLICT (account_id, valuation_date, valuation_type)
+                DO UPDATE SET
+                    amount = EXCLUDED.amount,
+                    market_price = EXCLUDED.market_price,
+                    quantity = EXCLUDED.quantity,
+                    cost_basis = EXCLUDED.cost_basis,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(account_id)
+            .bind(valuation.amount)
+            .bind(date)
+            .bind(valuation.market_price)
+            .bind(valuation.quantity)
+            .bind(valuation.cost_basis)
+            .execute(&self.pool)
+            .await?;
+            revalued += 1;
+        }
+        Ok((revalued, skipped))
+    }
+
+    /// 批量写入；返回 (新增, 更新) 条数
+    async fn upsert_prices(
+        &self,
+        rows: &[PriceRow],
+        source: &str,
+    ) -> Result<(u64, u64), ServiceError> {
+        let mut inserted = 0u64;
+        let mut updated = 0u64;
+        for chunk in rows.chunks(INSERT_CHUNK) {
+            let ids: Vec<Uuid> = chunk.iter().map(|r| r.security_id).collect();
+            let dates: Vec<NaiveDate> = chunk.iter().map(|r| r.price_date).collect();
+            let closes: Vec<Decimal> = chunk.iter().map(|r| r.close).collect();
+            let opens: Vec<Option<Decimal>> = chunk.iter().map(|r| r.open).collect();
+            let highs: Vec<Option<Decimal>> = chunk.iter().map(|r| r.high).collect();
+            let lows: Vec<Option<Decimal>> = chunk.iter().map(|r| r.low).collect();
+            let volumes: Vec<Option<Decimal>> = chunk.iter().map(|r| r.volume).collect();
+
+            let result = sqlx::query(
+                r#"
+                INSERT INTO security_prices
+                    (security_id, price_date, close_price, open_price, high_price, low_price,
+                     volume, source)
+                SELECT x.security_id, x.price_date, x.close_price, x.open_price, x.high_price,
+                       x.low_price, x.volume, $8
+                FROM UNNEST($1::uuid[], $2::date[], $3::numeric[], $4::numeric[], $5::numeric[],
+                            $6::numeric[], $7::numeric[])
+                     AS x(security_id, price_date, close_price, open_price, high_price,
+                          low_price, volume)
+                ON CONFLICT (security_id, price_date)
+                DO UPDATE SET
+                    close_price = EXCLUDED.close_price,
+                    open_price = COALESCE(EXCLUDED.open_price, security_prices.open_price),
+                    high_price = COALESCE(EXCLUDED.high_price, security_prices.high_price),
+                    low_price = COALESCE(EXCLUDED.low_price, security_prices.low_price),
+                    volume = COALESCE(EXCLUDED.volume, security_prices.volume),
+                    source = EXCLUDED.source,
+                    updated_at = NOW()
+                WHERE security_prices.close_price IS DISTINCT FROM EXCLUDED.close_price
+                RETURNING (xmax = 0) AS inserted
+                "#,
+            )
+            .bind(&ids)
+            .bind(&dates)
+            .bind(&closes)
+            .bind(&opens)
+            .bind(&highs)
+            .bind(&lows)
+            .bind(&volumes)
+            .bind(source)
+            .fetch_all(&self.pool)
+            .await?;
+            for row in result {
+                if row.get::<bool, _>("inserted") {
+                    inserted += 1;
+                } else {
+                    updated += 1;
+                }
+            }
+        }
+        Ok((inserted, updated))
+    }
+
+    /// 以最新一天的收盘价更新 `securities.current_price`
+    async fn sync_current_prices(&self, security_ids: &[Uuid]) -> Result<(), ServiceError> {
+        if security_ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query(
+            r#"
+            UPDATE securities s
+            SET current_price = p.close_price, price_updated_at = NOW(), updated_at = NOW()
+            FROM (
+                SELECT DISTINCT ON (security_id) security_id, close_price
+                FROM security_prices
+                WHERE security_id = ANY($1)
+                ORDER BY security_id, price_date DESC
+            ) p
+            WHERE s.id = p.security_id
+              AND s.current_price IS DISTINCT FROM p.close_price
+            "#,
+        )
+        .bind(security_ids)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_quotes_with_header() {
+        let csv = "\u{feff}Date,Open,High,Low,Close,Adj Close,Volume\n\
+                   2025-06-02,100,110,95,105.5,105.5,12345\n\
+                   2025-06-03,,,,bad,,\n\
+                   2025/06/04,106,108,104,107,107,\n";
+        let parsed = parse_quotes_csv(csv).unwrap();
+        assert_eq!(parsed.quotes.len(), 2);
+        assert_eq!(parsed.rejected, 1);
+        let first = &parsed.quotes[0];
+        assert_eq!(first.ticker, None);
+        assert_eq!(first.close, dec!(105.5));
+        assert_eq!(first.high, Some(dec!(110)));
+        assert_eq!(first.volume, Some(dec!(12345)));
+        assert_eq!(
+            parsed.quotes[1].price_date,
+            NaiveDate::from_ymd_opt(2025, 6, 4).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_quotes_positional() {
+        let csv = "2025-06-02,aapl,189.5\n2025-06-02,MSFT\n";
+        let parsed = parse_quotes_csv(csv).unwrap();
+        assert_eq!(parsed.quotes.len(), 1);
+        assert_eq!(parsed.quotes[0].ticker.as_deref(), Some("AAPL"));
+        assert_eq!(parsed.rejected, 1);
+        assert!(parse_quotes_csv("ticker,close\nAAPL,1\n").is_err());
+    }
+
+    #[test]
+    fn test_summarize_positions() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let single = summarize_positions(&[PositionValue {
+            security_id: a,
+            quantity: dec!(10),
+            cost_basis: dec!(900),
+            price: dec!(101.2345678),
+        }]);
+        assert_eq!(single.amount, dec!(1012.35));
+        assert_eq!(single.market_price, Some(dec!(101.234568)));
+
+        let mixed = summarize_positions(&[
+            PositionValue {
+                security_id: a,
+                quantity: dec!(10),
+                cost_basis: dec!(900),
+                price: dec!(100),
+            },
+            PositionValue {
+                security_id: b,
+                quantity: dec!(5),
+                cost_basis: dec!(400),
+                price: dec!(90),
+            },
+        ]);
+        assert_eq!(mixed.amount, dec!(1450));
+        assert_eq!(mixed.quantity, dec!(15));
+        assert_eq!(mixed.cost_basis, dec!(1300));
+        assert_eq!(mixed.market_price, None);
+
+        assert_eq!(summarize_positions(&[]).amount, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,8 @@
+//! 服务层单元测试共用的辅助函数
+
+use chrono::NaiveDate;
+
+/// 构造日期，参数非法时直接 panic
+pub fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
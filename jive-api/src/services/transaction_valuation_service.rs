@@ -0,0 +1,494 @@
+//! 交易本位币估值
+//!
+//! 每笔交易按交易日当天可用的汇率折算为家庭本位币，结果写入
+//! `transactions.base_currency / base_amount / base_rate / base_rate_date`。
+//! 统计与预算只合计 `base_amount`，尚未估值的交易单独计数（`unvalued_count`），不按原币相加。
+//!
+//! 回填与重算通过 `valuation_jobs` 排队：按交易 id 升序分批处理，每批与游标在同一事务内提交，
+//! 进程中断或出错后下一轮从游标继续。
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::rate_graph::RateGraph;
+use super::ServiceError;
+use crate::config::RateResolverConfig;
+
+/// 运行中任务的锁超过该时长视为进程已退出，可被重新领取
+const STALE_LOCK_SECS: i64 = 600;
+/// 单次运行中缓存的汇率图数量上限
+const GRAPH_CACHE_LIMIT: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValuationReason {
+    Backfill,
+    BaseCurrencyChanged,
+    RateCorrected,
+    RetryMissing,
+}
+
+impl ValuationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValuationReason::Backfill => "backfill",
+            ValuationReason::BaseCurrencyChanged => "base_currency_changed",
+            ValuationReason::RateCorrected => "rate_corrected",
+            ValuationReason::RetryMissing => "retry_missing",
+        }
+    }
+}
+
+/// 任务覆盖的交易范围
+#[derive(Debug, Clone, Default)]
+pub struct ValuationScope {
+    pub family_id: Option<Uuid>,
+    /// 交易币种或本位币在列表中的交易
+    pub currencies: Option<Vec<String>>,
+    pub since_date: Option<NaiveDate>,
+    /// 仅处理尚无本位币金额的交易
+    pub only_missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ValuationJob {
+    pub id: Uuid,
+    pub family_id: Option<Uuid>,
+    pub reason: String,
+    pub currencies: Option<Vec<String>>,
+    pub since_date: Option<NaiveDate>,
+    pub only_missing: bool,
+    pub status: String,
+    pub cursor_id: Option<Uuid>,
+    pub processed_count: i64,
+    pub unresolved_count: i64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ValuationRunStats {
+    pub jobs_completed: u32,
+    pub processed: u64,
+    /// 找不到交易日汇率的交易（本位币金额置空，等待后续重试）
+    pub unresolved: u64,
+}
+
+/// 单笔交易的估值结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseValuation {
+    pub base_currency: String,
+    pub base_amount: Option<Decimal>,
+    pub rate: Option<Decimal>,
+    pub rate_date: Option<NaiveDate>,
+}
+
+/// 按汇率图折算金额，保留两位小数（与 transactions.amount 精度一致）
+pub fn value_amount(
+    amount: Decimal,
+    currency: &str,
+    base_currency: &str,
+    graph: &RateGraph,
+    config: &RateResolverConfig,
+) -> BaseValuation {
+    let base_currency = base_currency.to_uppercase();
+    if currency.eq_ignore_ascii_case(&base_currency) {
+        return BaseValuation {
+            base_currency,
+            base_amount: Some(amount),
+            rate: Some(Decimal::ONE),
+            rate_date: Some(graph.as_of()),
+        };
+    }
+    match graph.resolve_with(currency, &base_currency, config) {
+        Ok(resolved) => BaseValuation {
+            base_currency,
+            base_amount: Some((amount * resolved.rate).round_dp(2)),
+            rate: Some(resolved.rate.round_dp(12)),
+            rate_date: Some(resolved.oldest_effective_date),
+        },
+        Err(_) => BaseValuation {
+            base_currency,
+            base_amount: None,
+            rate: None,
+            rate_date: None,
+        },
+    }
+}
+
+struct PendingRow {
+    id: Uuid,
+    amount: Decimal,
+    currency: String,
+    base_currency: String,
+    transaction_date: NaiveDate,
+}
+
+pub struct TransactionValuationService {
+    pool: PgPool,
+}
+
+impl TransactionValuationService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 加入估值任务；已有尚未开始且范围覆盖本次请求的任务时直接复用
+    pub async fn enqueue(
+        &self,
+        reason: ValuationReason,
+        scope: ValuationScope,
+    ) -> Result<Uuid, ServiceError> {
+        let currencies = scope
+            .currencies
+            .map(|list| list.iter().map(|c| c.to_uppercase()).collect::<Vec<_>>());
+
+        let existing: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM valuation_jobs
+            WHERE status = 'pending'
+              AND cursor_id IS NULL
+              AND (family_id IS NULL OR family_id IS NOT DISTINCT FROM $1)
+              AND (currencies IS NULL OR currencies = $2)
+              AND (since_date IS NULL OR ($3::date IS NOT NULL AND since_date <= $3))
+              AND (NOT only_missing OR $4)
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+        )
+        .bind(scope.family_id)
+        .bind(&currencies)
+        .bind(scope.since_date)
+        .bind(scope.only_missing)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(id) = existing {
+            return Ok(id);
+        }
+
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO valuation_jobs (family_id, reason, currencies, since_date, only_missing)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(scope.family_id)
+        .bind(reason.as_str())
+        .bind(&currencies)
+        .bind(scope.since_date)
+        .bind(scope.only_missing)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// 家庭最近的估值任务
+    pub async fn list_jobs(
+        &self,
+        family_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<ValuationJob>, ServiceError> {
+        let jobs = sqlx::query_as::<_, ValuationJob>(
+            r#"
+            SELECT id, family_id, reason, currencies, since_date, only_missing, status,
+                   cursor_id, processed_count, unresolved_count, last_error
+            FROM valuation_jobs
+            WHERE family_id = $1 OR family_id IS NULL
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(family_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(jobs)
+    }
+
+    /// 立即为单笔交易估值（创建/修改交易后调用）
+    pub async fn value_transaction(
+        &self,
+        transaction_id: Uuid,
+        config: &RateResolverConfig,
+    ) -> Result<Option<BaseValuation>, ServiceError> {
+        let rows = self
+            .fetch_rows(Some(transaction_id), &ValuationScope::default(), None, 1)
+            .await?;
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let graph = RateGraph::load(&self.pool, row.transaction_date, config).await?;
+        let valuation = value_amount(
+            row.amount,
+            &row.currency,
+            &row.base_currency,
+            &graph,
+            config,
+        );
+        store_valuation(&self.pool, row.id, &valuation).await?;
+        Ok(Some(valuation))
+    }
+
+    /// 依次处理待办任务，直到队列为空
+    pub async fn run_pending(
+        &self,
+        batch_size: i64,
+        config: &RateResolverConfig,
+    ) -> Result<ValuationRunStats, ServiceError> {
+        let mut stats = ValuationRunStats::default();
+        let mut graphs: HashMap<NaiveDate, RateGraph> = HashMap::new();
+
+        while let Some(job) = self.claim_job().await? {
+            match self
+                .run_job(&job, batch_size, config, &mut graphs, &mut stats)
+                .await
+            {
+                Ok(()) => stats.jobs_completed += 1,
+                Err(e) => {
+                    // 释放任务并记录错误，下次从游标继续
+                    sqlx::query(
+                        r#"
+                        UPDATE valuation_jobs
+                        SET status = 'pending', locked_at = NULL, last_error = $2, updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(job.id)
+                    .bind(e.to_string())
+                    .execute(&self.pool)
+                    .await?;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    async fn claim_job(&self) -> Result<Option<ValuationJob>, ServiceError> {
+        let job = sqlx::query_as::<_, ValuationJob>(
+            r#"
+            UPDATE valuation_jobs
+            SET status = 'running', locked_at = NOW(),
+                started_at = COALESCE(started_at, NOW()), updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM valuation_jobs
+                WHERE status = 'pending'
+                   OR (status = 'running' AND locked_at < NOW() - make_interval(secs => $1))
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, family_id, reason, currencies, since_date, only_missing, status,
+                      cursor_id, processed_count, unresolved_count, last_error
+            "#,
+        )
+        .bind(STALE_LOCK_SECS as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(job)
+    }
+
+    async fn run_job(
+        &self,
+        job: &ValuationJob,
+        batch_size: i64,
+        config: &RateResolverConfig,
+        graphs: &mut HashMap<NaiveDate, RateGraph>,
+        stats: &mut ValuationRunStats,
+    ) -> Result<(), ServiceError> {
+        let scope = ValuationScope {
+            family_id: job.family_id,
+            currencies: job.currencies.clone(),
+            since_date: job.since_date,
+            only_missing: job.only_missing,
+        };
+        let mut cursor = job.cursor_id;
+
+        loop {
+            let rows = self.fetch_rows(None, &scope, cursor, batch_size).await?;
+            let Some(last) = rows.last().map(|r| r.id) else {
+                break;
+            };
+
+            let mut tx = self.pool.begin().await?;
+            let mut unresolved = 0i64;
+            for row in &rows {
+                if !graphs.contains_key(&row.transaction_date) {
+                    if graphs.len() >= GRAPH_CACHE_LIMIT {
+                        graphs.clear();
+                    }
+                    let graph = RateGraph::load(&self.pool, row.transaction_date, config).await?;
+                    graphs.insert(row.transaction_date, graph);
+                }
+                let graph = &graphs[&row.transaction_date];
+                let valuation =
+                    value_amount(row.amount, &row.currency, &row.base_currency, graph, config);
+                if valuation.base_amount.is_none() {
+                    unresolved += 1;
+                }
+                store_valuation(&mut *tx, row.id, &valuation).await?;
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE valuation_jobs
+                SET cursor_id = $2,
+                    processed_count = processed_count + $3,
+                    unresolved_count = unresolved_count + $4,
+                    locked_at = NOW(), updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(job.id)
+            .bind(last)
+            .bind(rows.len() as i64)
+            .bind(unresolved)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            stats.processed += rows.len() as u64;
+            stats.unresolved += unresolved as u64;
+            cursor = Some(last);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE valuation_jobs
+            SET status = 'completed', locked_at = NULL, last_error = NULL,
+                completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(job.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 读取待估值交易（按 id 升序，从游标之后开始）
+    async fn fetch_rows(
+        &self,
+        transaction_id: Option<Uuid>,
+        scope: &ValuationScope,
+        cursor: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<PendingRow>, ServiceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.amount, t.transaction_date,
+                   UPPER(COALESCE(a.currency, t.currency, fcs.base_currency, f.currency)) AS currency,
+                   UPPER(COALESCE(fcs.base_currency, f.currency)) AS base_currency
+            FROM transactions t
+            JOIN ledgers l ON l.id = t.ledger_id
+            JOIN families f ON f.id = l.family_id
+            LEFT JOIN accounts a ON a.id = t.account_id
+            LEFT JOIN family_currency_settings fcs ON fcs.family_id = l.family_id
+            WHERE t.deleted_at IS NULL
+              AND COALESCE(fcs.base_currency, f.currency) IS NOT NULL
+              AND ($1::uuid IS NULL OR t.id = $1)
+              AND ($2::uuid IS NULL OR l.family_id = $2)
+              AND ($3::text[] IS NULL
+                   OR UPPER(COALESCE(a.currency, t.currency, '')) = ANY($3)
+                   OR UPPER(COALESCE(fcs.base_currency, f.currency)) = ANY($3))
+              AND ($4::date IS NULL OR t.transaction_date >= $4)
+              AND (NOT $5 OR t.base_amount IS NULL)
+              AND ($6::uuid IS NULL OR t.id > $6)
+            ORDER BY t.id
+            LIMIT $7
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(scope.family_id)
+        .bind(&scope.currencies)
+        .bind(scope.since_date)
+        .bind(scope.only_missing)
+        .bind(cursor)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PendingRow {
+                id: row.get("id"),
+                amount: row.get("amount"),
+                currency: row.get("currency"),
+                base_currency: row.get("base_currency"),
+                transaction_date: row.get("transaction_date"),
+            })
+            .collect())
+    }
+}
+
+async fn store_valuation<'e, E>(
+    executor: E,
+    transaction_id: Uuid,
+    valuation: &BaseValuation,
+) -> Result<(), ServiceError>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
+    sqlx::query(
+        r#"
+        UPDATE transactions
+        SET base_currency = $2, base_amount = $3, base_rate = $4,
+            base_rate_date = $5, base_valued_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(&valuation.base_currency)
+    .bind(valuation.base_amount)
+    .bind(valuation.rate)
+    .bind(valuation.rate_date)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::date;
+    use rust_decimal_macros::dec;
+
+    fn config() -> RateResolverConfig {
+        RateResolverConfig {
+            max_rate_age_days: 30,
+            max_hops: 4,
+            pegs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_value_amount_uses_rate_at_transaction_date() {
+        let mut graph = RateGraph::new(date(2026, 3, 10));
+        graph.add_rate("USD", "CNY", dec!(7.1), "api", date(2026, 3, 8), None);
+        // 交易日之后的汇率不参与
+        graph.add_rate("USD", "CNY", dec!(7.3), "api", date(2026, 3, 12), None);
+
+        let v = value_amount(dec!(12.34), "usd", "CNY", &graph, &config());
+        assert_eq!(v.base_currency, "CNY");
+        assert_eq!(v.base_amount, Some(dec!(87.61)));
+        assert_eq!(v.rate, Some(dec!(7.1)));
+        assert_eq!(v.rate_date, Some(date(2026, 3, 8)));
+    }
+
+    #[test]
+    fn test_value_amount_same_currency_and_missing_rate() {
+        let graph = RateGraph::new(date(2026, 3, 10));
+        let same = value_amount(dec!(5.00), "CNY", "cny", &graph, &config());
+        assert_eq!(same.base_amount, Some(dec!(5.00)));
+        assert_eq!(same.rate, Some(Decimal::ONE));
+
+        let missing = value_amount(dec!(5.00), "JPY", "CNY", &graph, &config());
+        assert_eq!(missing.base_currency, "CNY");
+        assert!(missing.base_amount.is_none());
+        assert!(missing.rate.is_none());
+    }
+}
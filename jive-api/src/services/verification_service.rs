@@ -4,15 +4,29 @@ use redis::AsyncCommands;
 // Duration 未使用，移除以消除 warning
 // use std::time::Duration;
 
+use super::email::{is_deliverable, EmailOutbox, EmailTemplate};
 use super::ServiceError;
 
+/// 验证码有效期（秒）
+const CODE_TTL_SECS: u64 = 300;
+
 pub struct VerificationService {
     redis: Option<ConnectionManager>,
+    outbox: Option<EmailOutbox>,
 }
 
 impl VerificationService {
     pub fn new(redis: Option<ConnectionManager>) -> Self {
-        Self { redis }
+        Self {
+            redis,
+            outbox: None,
+        }
+    }
+
+    /// 启用邮件投递（验证码经 email_outbox 发送）
+    pub fn with_email_outbox(mut self, outbox: EmailOutbox) -> Self {
+        self.outbox = Some(outbox);
+        self
     }
 
     /// Generate a 4-digit verification code
@@ -35,7 +49,7 @@ impl VerificationService {
 
             // Store code with 5 minutes expiration
             // 显式标注返回类型，避免 2024 edition never type fallback 潜在错误
-            conn.set_ex::<_, _, ()>(&key, code, 300)
+            conn.set_ex::<_, _, ()>(&key, code, CODE_TTL_SECS)
                 .await
                 .map_err(|_e| ServiceError::InternalError)?;
 
@@ -86,7 +100,7 @@ impl VerificationService {
         }
     }
 
-    /// Send verification code (placeholder for email/SMS integration)
+    /// Send verification code (email via outbox; SMS not yet integrated)
     pub async fn send_verification_code(
         &self,
         user_id: &str,
@@ -99,14 +113,26 @@ impl VerificationService {
         self.store_verification_code(user_id, operation, &code)
             .await?;
 
-        // In production, this would send an email or SMS
-        // For now, we'll just return the code for testing
-        tracing::info!(
-            "验证码 {} 已发送至 {} (操作: {})",
-            code,
-            destination,
-            operation
-        );
+        match &self.outbox {
+            Some(outbox) if is_deliverable(destination) => {
+                let template = EmailTemplate::VerificationCode {
+                    code: code.clone(),
+                    operation: operation.to_string(),
+                    expires_minutes: (CODE_TTL_SECS / 60) as u32,
+                };
+                outbox.enqueue_for_user(destination, &template).await?;
+                tracing::info!("验证码邮件已入队: {} (操作: {})", destination, operation);
+            }
+            _ => {
+                // 无邮件通道（或短信）时仅记录日志，便于开发调试
+                tracing::info!(
+                    "验证码 {} 已发送至 {} (操作: {})",
+                    code,
+                    destination,
+                    operation
+                );
+            }
+        }
 
         Ok(code)
     }
@@ -0,0 +1,1134 @@
+//! 家庭 Webhook：家庭管理员配置外发地址并订阅事件类型，事件发生时签名后 POST 到这些地址
+//!
+//! 事件先写入 `webhook_events`，按订阅扇出为 `webhook_deliveries`，由定时任务投递：
+//! 非 2xx 响应或网络错误按指数退避重试，超过最大次数后标记为 failed。每次请求的响应码、
+//! 响应体片段与耗时记入 `webhook_delivery_attempts`。手动重新投递为同一事件新建一条投递，
+//! 请求体不变，接收方可按事件 `id` 去重。
+//!
+//! 请求头：`X-Jive-Signature: t=<unix>,v1=<hex>`（HMAC-SHA256，见 `utils::signature`）、
+//! `X-Jive-Event`（事件类型）、`X-Jive-Delivery`（投递 id）。
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+use std::time::Instant;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::financial_digest_service::{budget_spent, budget_window};
+use super::notification_service::{
+    generate_webhook_secret, validate_webhook_url, SIGNATURE_HEADER,
+};
+use super::{ServiceContext, ServiceError};
+use crate::config::WebhookConfig;
+use crate::models::permission::Permission;
+use crate::utils::outbound::{self, OutboundError};
+use crate::utils::signature::signature_header;
+
+/// 事件类型请求头
+pub const EVENT_HEADER: &str = "X-Jive-Event";
+/// 投递 id 请求头
+pub const DELIVERY_HEADER: &str = "X-Jive-Delivery";
+/// sending 状态超过该时长视为投递进程已崩溃，可被重新领取
+const STALE_LOCK_MINUTES: i64 = 10;
+const MAX_LIST_LIMIT: i64 = 200;
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_DESCRIPTION_CHARS: usize = 200;
+
+const ENDPOINT_COLUMNS: &str =
+    "id, family_id, url, description, event_types, is_active, created_by, \
+     last_delivery_at, last_delivery_status, created_at, updated_at";
+const DELIVERY_COLUMNS: &str = "d.id, d.endpoint_id, d.event_id, ev.event_type, d.redelivery_of, \
+     d.status, d.attempts, d.response_status, d.last_error, d.deliver_after, d.delivered_at, d.created_at";
+
+/// 可订阅的事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEventType {
+    #[serde(rename = "transaction.created")]
+    TransactionCreated,
+    #[serde(rename = "transaction.updated")]
+    TransactionUpdated,
+    #[serde(rename = "transaction.deleted")]
+    TransactionDeleted,
+    /// 预算周期内支出首次超过预算金额（每个预算周期只触发一次）
+    #[serde(rename = "budget.exceeded")]
+    BudgetExceeded,
+    #[serde(rename = "member.joined")]
+    MemberJoined,
+    /// 银行连接同步完成且有交易变化
+    #[serde(rename = "import.completed")]
+    ImportCompleted,
+}
+
+impl WebhookEventType {
+    pub const ALL: [WebhookEventType; 6] = [
+        Self::TransactionCreated,
+        Self::TransactionUpdated,
+        Self::TransactionDeleted,
+        Self::BudgetExceeded,
+        Self::MemberJoined,
+        Self::ImportCompleted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TransactionCreated => "transaction.created",
+            Self::TransactionUpdated => "transaction.updated",
+            Self::TransactionDeleted => "transaction.deleted",
+            Self::BudgetExceeded => "budget.exceeded",
+            Self::MemberJoined => "member.joined",
+            Self::ImportCompleted => "import.completed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == value)
+    }
+}
+
+/// Webhook 地址
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub family_id: Uuid,
+    pub url: String,
+    pub description: Option<String>,
+    /// 订阅的事件类型
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    /// 最近一次请求的结果：sent / failed
+    pub last_delivery_status: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// 签名密钥，只在创建与轮换时返回
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// http(s) 地址
+    pub url: String,
+    pub description: Option<String>,
+    pub event_types: Vec<WebhookEventType>,
+    /// 默认 true
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    /// 空字符串清除描述
+    pub description: Option<String>,
+    pub event_types: Option<Vec<WebhookEventType>>,
+    pub is_active: Option<bool>,
+    /// 生成新的签名密钥并在响应中返回
+    #[serde(default)]
+    pub rotate_secret: bool,
+}
+
+/// 一次投递（同一事件的每次重新投递是独立的一条）
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub redelivery_of: Option<Uuid>,
+    /// pending / sending / sent / failed
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub deliver_after: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 投递日志中的一次请求
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct WebhookDeliveryAttempt {
+    pub attempt: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 投递详情：请求体与每次请求的日志
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDeliveryDetail {
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    /// 发送的请求体
+    pub payload: serde_json::Value,
+    pub attempt_log: Vec<WebhookDeliveryAttempt>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct WebhookDeliveryQuery {
+    /// pending / sending / sent / failed
+    pub status: Option<String>,
+    /// 默认 50，最多 200
+    pub limit: Option<i64>,
+}
+
+/// 一次投递任务运行的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WebhookRunStats {
+    pub sent: usize,
+    pub retried: usize,
+    pub failed: usize,
+}
+
+/// 一次 HTTP 请求的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttemptOutcome {
+    pub response_status: Option<u16>,
+    /// 网络错误或非 2xx 响应
+    pub error: Option<String>,
+    pub duration_ms: i32,
+}
+
+impl AttemptOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// 事件请求体；重新投递时原样发送
+pub fn event_payload(
+    event_id: Uuid,
+    event_type: WebhookEventType,
+    family_id: Uuid,
+    created_at: DateTime<Utc>,
+    data: serde_json::Value,
+) -> serde_json::Value {
+    json!({
+        "id": event_id,
+        "type": event_type.as_str(),
+        "family_id": family_id,
+        "created_at": created_at,
+        "data": data,
+    })
+}
+
+/// 发送签名的事件请求；2xx 视为成功，其余响应与网络错误都记入 `error`
+pub async fn send_signed(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    event_type: &str,
+    delivery_id: Uuid,
+    body: &[u8],
+) -> AttemptOutcome {
+    let started = Instant::now();
+    let result = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "Jive-Webhooks/1.0")
+        .header(
+            SIGNATURE_HEADER,
+            signature_header(secret.as_bytes(), Utc::now().timestamp(), body),
+        )
+        .header(EVENT_HEADER, event_type)
+        .header(DELIVERY_HEADER, delivery_id.to_string())
+        .body(body.to_vec())
+        .send()
+        .await;
+    // 只记录响应码，不读取响应体：接收方的响应内容不会回显给家庭成员
+    let (response_status, error) = match result {
+        Ok(response) => {
+            let status = response.status();
+            let error = (!status.is_success()).then(|| format!("HTTP {}", status));
+            (Some(status.as_u16()), error)
+        }
+        Err(e) => (None, Some(e.to_string())),
+    };
+    AttemptOutcome {
+        response_status,
+        error,
+        duration_ms: started.elapsed().as_millis().min(i32::MAX as u128) as i32,
+    }
+}
+
+/// 订阅列表：至少一个，去重并保持固定顺序
+fn normalize_event_types(types: &[WebhookEventType]) -> Result<Vec<String>, ServiceError> {
+    let selected: Vec<String> = WebhookEventType::ALL
+        .iter()
+        .filter(|t| types.contains(t))
+        .map(|t| t.as_str().to_string())
+        .collect();
+    if selected.is_empty() {
+        return Err(ServiceError::validation("至少订阅一种事件类型"));
+    }
+    Ok(selected)
+}
+
+fn normalize_description(description: Option<&str>) -> Result<Option<String>, ServiceError> {
+    let description = description.map(str::trim).filter(|d| !d.is_empty());
+    if description.is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_CHARS) {
+        return Err(ServiceError::validation("描述不能超过 200 个字符"));
+    }
+    Ok(description.map(str::to_string))
+}
+
+/// 事件中的交易快照
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TransactionSnapshot {
+    id: Uuid,
+    ledger_id: Uuid,
+    account_id: Uuid,
+    transaction_type: String,
+    amount: Decimal,
+    currency: Option<String>,
+    base_amount: Option<Decimal>,
+    transaction_date: NaiveDate,
+    category_id: Option<Uuid>,
+    category: Option<String>,
+    payee: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    created_by: Option<Uuid>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct BudgetRow {
+    id: Uuid,
+    name: String,
+    category_id: Option<Uuid>,
+    amount: Decimal,
+    period: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    endpoint_id: Uuid,
+    attempts: i32,
+    url: String,
+    secret: String,
+    is_active: bool,
+    event_type: String,
+    payload: serde_json::Value,
+}
+
+pub struct WebhookService {
+    pool: PgPool,
+}
+
+impl WebhookService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self, ctx: &ServiceContext) -> Result<Vec<WebhookEndpoint>, ServiceError> {
+        ctx.require_permission(Permission::ManageIntegrations)?;
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM webhook_endpoints WHERE family_id = $1 ORDER BY created_at",
+            ENDPOINT_COLUMNS
+        ))
+        .bind(ctx.family_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get(
+        &self,
+        ctx: &ServiceContext,
+        id: Uuid,
+    ) -> Result<WebhookEndpoint, ServiceError> {
+        ctx.require_permission(Permission::ManageIntegrations)?;
+        sqlx::query_as(&format!(
+            "SELECT {} FROM webhook_endpoints WHERE id = $1 AND family_id = $2",
+            ENDPOINT_COLUMNS
+        ))
+        .bind(id)
+        .bind(ctx.family_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ServiceError::not_found("WebhookEndpoint", id))
+    }
+
+    /// 新建地址；响应中包含签名密钥
+    pub async fn create(
+        &self,
+        ctx: &ServiceContext,
+        req: &CreateWebhookRequest,
+        config: &WebhookConfig,
+    ) -> Result<WebhookEndpoint, ServiceError> {
+        ctx.require_permission(Permission::ManageIntegrations)?;
+        let url = req.url.trim();
+        validate_webhook_url(url).await?;
+        let event_types = normalize_event_types(&req.event_types)?;
+        let description = normalize_description(req.description.as_deref())?;
+
+        let max_endpoints = config.max_endpoints;
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM webhook_endpoints WHERE family_id = $1")
+                .bind(ctx.family_id)
+                .fetch_one(&self.pool)
+                .await?;
+        if count >= max_endpoints {
+            return Err(ServiceError::business_rule(format!(
+                "每个家庭最多配置 {} 个 Webhook",
+                max_endpoints
+            )));
+        }
+
+        let secret = generate_webhook_secret();
+        let mut endpoint: WebhookEndpoint = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO webhook_endpoints
+                (family_id, url, description, secret, event_types, is_active, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            ENDPOINT_COLUMNS
+        ))
+        .bind(ctx.family_id)
+        .bind(url)
+        .bind(description)
+        .bind(&secret)
+        .bind(&event_types)
+        .bind(req.is_active.unwrap_or(true))
+        .bind(ctx.user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        endpoint.secret = Some(secret);
+        Ok(endpoint)
+    }
+
+    /// 修改地址；`rotate_secret` 时生成新密钥并在响应中返回
+    pub async fn update(
+        &self,
+        ctx: &ServiceContext,
+        id: Uuid,
+        req: &UpdateWebhookRequest,
+    ) -> Result<WebhookEndpoint, ServiceError> {
+        let existing = self.get(ctx, id).await?;
+        let url = match req.url.as_deref().map(str::trim) {
+            Some(url) => {
+                validate_webhook_url(url).await?;
+                url.to_string()
+            }
+            None => existing.url,
+        };
+        let event_types = match &req.event_types {
+            Some(types) => normalize_event_types(types)?,
+            None => existing.event_types,
+        };
+        let description = match &req.description {
+            Some(description) => normalize_description(Some(description))?,
+            None => existing.description,
+        };
+        let secret = req.rotate_secret.then(generate_webhook_secret);
+
+        let mut endpoint: WebhookEndpoint = sqlx::query_as(&format!(
+            r#"
+            UPDATE webhook_endpoints
+            SET url = $3, description = $4, event_types = $5, is_active = $6,
+                secret = COALESCE($7, secret), updated_at = NOW()
+            WHERE id = $1 AND family_id = $2
+            RETURNING {}
+            "#,
+            ENDPOINT_COLUMNS
+        ))
+        .bind(id)
+        .bind(ctx.family_id)
+        .bind(url)
+        .bind(description)
+        .bind(&event_types)
+        .bind(req.is_active.unwrap_or(existing.is_active))
+        .bind(&secret)
+        .fetch_one(&self.pool)
+        .await?;
+        endpoint.secret = secret;
+        Ok(endpoint)
+    }
+
+    /// 删除地址及其投递记录
+    pub async fn delete(&self, ctx: &ServiceContext, id: Uuid) -> Result<(), ServiceError> {
+        ctx.require_permission(Permission::ManageIntegrations)?;
+        let result = sqlx::query("DELETE FROM webhook_endpoints WHERE id = $1 AND family_id = $2")
+            .bind(id)
+            .bind(ctx.family_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::not_found("WebhookEndpoint", id));
+        }
+        Ok(())
+    }
+
+    /// 地址的投递记录，最新的在前
+    pub async fn deliveries(
+        &self,
+        ctx: &ServiceContext,
+        endpoint_id: Uuid,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, ServiceError> {
+        self.get(ctx, endpoint_id).await?;
+        let status = query.status.as_deref().map(str::trim);
+        if status.is_some_and(|s| !matches!(s, "pending" | "sending" | "sent" | "failed")) {
+            return Err(ServiceError::validation(
+                "status 只能是 pending、sending、sent 或 failed",
+            ));
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        Ok(sqlx::query_as(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_deliveries d
+            JOIN webhook_events ev ON ev.id = d.event_id
+            WHERE d.endpoint_id = $1 AND ($2::text IS NULL OR d.status = $2)
+            ORDER BY d.created_at DESC
+            LIMIT $3
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(endpoint_id)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn find_delivery(
+        &self,
+        endpoint_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<(WebhookDelivery, serde_json::Value), ServiceError> {
+        let delivery: Option<WebhookDelivery> = sqlx::query_as(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_deliveries d
+            JOIN webhook_events ev ON ev.id = d.event_id
+            WHERE d.id = $1 AND d.endpoint_id = $2
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(delivery_id)
+        .bind(endpoint_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let delivery =
+            delivery.ok_or_else(|| ServiceError::not_found("WebhookDelivery", delivery_id))?;
+        let payload = sqlx::query_scalar("SELECT payload FROM webhook_events WHERE id = $1")
+            .bind(delivery.event_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok((delivery, payload))
+    }
+
+    /// 投递详情：请求体与投递日志
+    pub async fn delivery(
+        &self,
+        ctx: &ServiceContext,
+        endpoint_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<WebhookDeliveryDetail, ServiceError> {
+        self.get(ctx, endpoint_id).await?;
+        let (delivery, payload) = self.find_delivery(endpoint_id, delivery_id).await?;
+        let attempt_log = sqlx::query_as(
+            r#"
+            SELECT attempt, response_status, error, duration_ms, created_at
+            FROM webhook_delivery_attempts
+            WHERE delivery_id = $1
+            ORDER BY attempt
+            "#,
+        )
+        .bind(delivery_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(WebhookDeliveryDetail {
+            delivery,
+            payload,
+            attempt_log,
+        })
+    }
+
+    /// 手动重新投递：为同一事件新建一条立即投递的记录
+    pub async fn redeliver(
+        &self,
+        ctx: &ServiceContext,
+        endpoint_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<WebhookDelivery, ServiceError> {
+        let endpoint = self.get(ctx, endpoint_id).await?;
+        if !endpoint.is_active {
+            return Err(ServiceError::business_rule(
+                "Webhook 已停用，启用后才能重新投递",
+            ));
+        }
+        let (original, _) = self.find_delivery(endpoint_id, delivery_id).await?;
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO webhook_deliveries (endpoint_id, event_id, redelivery_of)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(endpoint_id)
+        .bind(original.event_id)
+        .bind(original.id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(self.find_delivery(endpoint_id, id).await?.0)
+    }
+
+    /// 记录事件并为订阅它的启用地址排队投递，返回排队的投递数
+    ///
+    /// 没有订阅者时不保存事件；`dedupe_key` 相同的事件只记录一次。
+    pub async fn publish(
+        &self,
+        family_id: Uuid,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+        dedupe_key: Option<&str>,
+    ) -> Result<u64, ServiceError> {
+        let event_id = Uuid::new_v4();
+        let payload = event_payload(event_id, event_type, family_id, Utc::now(), data);
+        let result = sqlx::query(
+            r#"
+            WITH endpoints AS (
+                SELECT id FROM webhook_endpoints
+                WHERE family_id = $1 AND is_active AND $2 = ANY(event_types)
+            ), event AS (
+                INSERT INTO webhook_events (id, family_id, event_type, payload, dedupe_key)
+                SELECT $3, $1, $2, $4, $5
+                WHERE EXISTS (SELECT 1 FROM endpoints)
+                ON CONFLICT (family_id, dedupe_key) DO NOTHING
+                RETURNING id
+            )
+            INSERT INTO webhook_deliveries (endpoint_id, event_id)
+            SELECT endpoints.id, event.id FROM endpoints CROSS JOIN event
+            "#,
+        )
+        .bind(family_id)
+        .bind(event_type.as_str())
+        .bind(event_id)
+        .bind(&payload)
+        .bind(dedupe_key)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 发布事件；失败只记录日志，不影响触发事件的业务操作
+    pub async fn emit(
+        &self,
+        family_id: Uuid,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+    ) {
+        if let Err(e) = self.publish(family_id, event_type, data, None).await {
+            tracing::warn!(
+                "Failed to publish webhook event {} for family {}: {}",
+                event_type.as_str(),
+                family_id,
+                e
+            );
+        }
+    }
+
+    /// 事件中使用的交易快照；删除前先取快照，删除后再发布 `transaction.deleted`
+    pub async fn snapshots(&self, family_id: Uuid, ids: &[Uuid]) -> Vec<TransactionSnapshot> {
+        let result = sqlx::query_as(
+            r#"
+            SELECT t.id, t.ledger_id, t.account_id, t.transaction_type, t.amount, t.currency,
+                   t.base_amount, t.transaction_date, t.category_id,
+                   COALESCE(c.name, t.category_name) AS category, t.payee, t.description,
+                   t.status, t.created_by, t.created_at, t.updated_at, t.deleted_at
+            FROM transactions t
+            JOIN ledgers l ON l.id = t.ledger_id
+            LEFT JOIN categories c ON c.id = t.category_id
+            WHERE t.id = ANY($1) AND l.family_id = $2
+            ORDER BY t.transaction_date, t.created_at
+            "#,
+        )
+        .bind(ids)
+        .bind(family_id)
+        .fetch_all(&self.pool)
+        .await;
+        result.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load transactions for webhooks: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// 交易新增 / 修改；支出变化后检查所在预算是否超支
+    pub async fn transactions_changed(
+        &self,
+        family_id: Uuid,
+        ids: &[Uuid],
+        event_type: WebhookEventType,
+    ) {
+        for snapshot in self.snapshots(family_id, ids).await {
+            self.emit(family_id, event_type, json!({ "transaction": &snapshot }))
+                .await;
+            if snapshot.transaction_type == "expense" {
+                if let Err(e) = self.check_budgets(family_id, &snapshot).await {
+                    tracing::warn!(
+                        "Failed to check budgets for transaction {}: {}",
+                        snapshot.id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    /// 交易已删除；`snapshots` 为删除前取得的快照
+    pub async fn transactions_deleted(&self, family_id: Uuid, snapshots: Vec<TransactionSnapshot>) {
+        for snapshot in snapshots {
+            self.emit(
+                family_id,
+                WebhookEventType::TransactionDeleted,
+                json!({ "transaction": snapshot }),
+            )
+            .await;
+        }
+    }
+
+    /// 交易所在预算周期（未结束的）支出超过预算时发布 `budget.exceeded`，每个预算周期一次
+    async fn check_budgets(
+        &self,
+        family_id: Uuid,
+        transaction: &TransactionSnapshot,
+    ) -> Result<(), ServiceError> {
+        let date = transaction.transaction_date;
+        let budgets: Vec<BudgetRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, category_id, amount, period
+            FROM budgets
+            WHERE ledger_id = $1 AND COALESCE(is_active, true) AND amount > 0
+              AND (category_id IS NULL OR category_id = $2)
+              AND start_date <= $3 AND (end_date IS NULL OR end_date >= $3)
+            "#,
+        )
+        .bind(transaction.ledger_id)
+        .bind(transaction.category_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let today = Utc::now().date_naive();
+        for budget in budgets {
+            let (start, end) = budget_window(&budget.period, date);
+            if end < today {
+                continue;
+            }
+            let spent = budget_spent(
+                &self.pool,
+                transaction.ledger_id,
+                budget.category_id,
+                start,
+                end,
+            )
+            .await?;
+            if spent <= budget.amount {
+                continue;
+            }
+            let data = json!({
+                "budget": {
+                    "id": budget.id,
+                    "ledger_id": transaction.ledger_id,
+                    "name": budget.name,
+                    "category_id": budget.category_id,
+                    "period": budget.period,
+                    "amount": budget.amount,
+                },
+                "period_start": start,
+                "period_end": end,
+                "spent": spent,
+                "percentage": (spent / budget.amount * Decimal::from(100)).round_dp(1),
+                "transaction_id": transaction.id,
+            });
+            let key = format!("budget.exceeded:{}:{}", budget.id, start);
+            self.publish(
+                family_id,
+                WebhookEventType::BudgetExceeded,
+                data,
+                Some(&key),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 成员通过邀请码或邀请链接加入家庭
+    pub async fn member_joined(&self, family_id: Uuid, user_id: Uuid) {
+        let member: Result<Option<serde_json::Value>, _> = sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object(
+                'user_id', u.id, 'name', u.name, 'role', m.role, 'joined_at', m.joined_at
+            )
+            FROM family_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.family_id = $1 AND m.user_id = $2
+            "#,
+        )
+        .bind(family_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await;
+        match member {
+            Ok(Some(member)) => {
+                self.emit(
+                    family_id,
+                    WebhookEventType::MemberJoined,
+                    json!({ "member": member }),
+                )
+                .await
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load member {} for webhook: {}", user_id, e),
+        }
+    }
+
+    /// 领取到期的投递并发送
+    pub async fn deliver_due(
+        &self,
+        client: &reqwest::Client,
+        batch_size: i64,
+        max_attempts: u32,
+    ) -> Result<WebhookRunStats, ServiceError> {
+        let due: Vec<DueDelivery> = sqlx::query_as(
+            r#"
+            WITH claimed AS (
+                UPDATE webhook_deliveries
+                SET status = 'sending', locked_at = NOW()
+                WHERE id IN (
+                    SELECT id FROM webhook_deliveries
+                    WHERE (status = 'pending' AND deliver_after <= NOW())
+                       OR (status = 'sending' AND locked_at < NOW() - make_interval(mins => $2))
+                    ORDER BY deliver_after
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, endpoint_id, event_id, attempts
+            )
+            SELECT c.id, c.endpoint_id, c.attempts, e.url, e.secret, e.is_active,
+                   ev.event_type, ev.payload
+            FROM claimed c
+            JOIN webhook_endpoints e ON e.id = c.endpoint_id
+            JOIN webhook_events ev ON ev.id = c.event_id
+            ORDER BY ev.created_at
+            "#,
+        )
+        .bind(batch_size.max(1))
+        .bind(STALE_LOCK_MINUTES as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = WebhookRunStats::default();
+        for delivery in &due {
+            if !delivery.is_active {
+                // 地址在排队期间被停用
+                stats.failed += 1;
+                self.fail(delivery.id, delivery.attempts, "webhook disabled")
+                    .await?;
+                continue;
+            }
+            // 地址的解析结果可能在保存后改变，发送前再检查一次
+            match outbound::check_url(&delivery.url, &["http", "https"]).await {
+                Ok(_) => {}
+                Err(OutboundError::Unresolvable) => {
+                    let outcome = AttemptOutcome {
+                        response_status: None,
+                        error: Some(OutboundError::Unresolvable.to_string()),
+                        duration_ms: 0,
+                    };
+                    self.record(delivery, &outcome, max_attempts, &mut stats)
+                        .await?;
+                    continue;
+                }
+                Err(e) => {
+                    stats.failed += 1;
+                    self.fail(delivery.id, delivery.attempts, &e.to_string())
+                        .await?;
+                    continue;
+                }
+            }
+            let body = serde_json::to_vec(&delivery.payload)?;
+            let outcome = send_signed(
+                client,
+                &delivery.url,
+                &delivery.secret,
+                &delivery.event_type,
+                delivery.id,
+                &body,
+            )
+            .await;
+            self.record(delivery, &outcome, max_attempts, &mut stats)
+                .await?;
+        }
+        Ok(stats)
+    }
+
+    /// 写入投递日志并更新投递状态：失败时按指数退避重新排队，超过最大次数后标记为 failed
+    async fn record(
+        &self,
+        delivery: &DueDelivery,
+        outcome: &AttemptOutcome,
+        max_attempts: u32,
+        stats: &mut WebhookRunStats,
+    ) -> Result<(), ServiceError> {
+        let attempts = delivery.attempts + 1;
+        let response_status = outcome.response_status.map(i32::from);
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_delivery_attempts
+                (delivery_id, attempt, response_status, error, duration_ms)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(attempts)
+        .bind(response_status)
+        .bind(&outcome.error)
+        .bind(outcome.duration_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        let status = match &outcome.error {
+            None => {
+                stats.sent += 1;
+                "sent"
+            }
+            Some(error) if attempts as u32 >= max_attempts => {
+                tracing::warn!(delivery = %delivery.id, endpoint = %delivery.endpoint_id, "Webhook delivery failed permanently: {}", error);
+                stats.failed += 1;
+                "failed"
+            }
+            Some(_) => {
+                stats.retried += 1;
+                "pending"
+            }
+        };
+        let delay = super::email::outbox::retry_delay(attempts as u32);
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $2, attempts = $3, response_status = $4, last_error = $5,
+                locked_at = NULL,
+                delivered_at = CASE WHEN $2 = 'sent' THEN NOW() END,
+                deliver_after = CASE WHEN $2 = 'pending'
+                                     THEN NOW() + make_interval(secs => $6)
+                                     ELSE deliver_after END
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(status)
+        .bind(attempts)
+        .bind(response_status)
+        .bind(&outcome.error)
+        .bind(delay.as_secs() as f64)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE webhook_endpoints
+            SET last_delivery_at = NOW(), last_delivery_status = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.endpoint_id)
+        .bind(if outcome.succeeded() {
+            "sent"
+        } else {
+            "failed"
+        })
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn fail(&self, id: Uuid, attempts: i32, error: &str) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'failed', attempts = $2, last_error = $3, locked_at = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(attempts)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_types_and_payload() {
+        for event_type in WebhookEventType::ALL {
+            assert_eq!(
+                WebhookEventType::parse(event_type.as_str()),
+                Some(event_type)
+            );
+            assert_eq!(
+                serde_json::to_value(event_type).unwrap(),
+                json!(event_type.as_str())
+            );
+        }
+        assert_eq!(WebhookEventType::parse("transaction.exploded"), None);
+
+        let types: Vec<WebhookEventType> = serde_json::from_value(json!([
+            "member.joined",
+            "transaction.created",
+            "member.joined"
+        ]))
+        .unwrap();
+        assert_eq!(
+            normalize_event_types(&types).unwrap(),
+            vec!["transaction.created", "member.joined"]
+        );
+        assert!(normalize_event_types(&[]).is_err());
+
+        let event_id = Uuid::new_v4();
+        let family_id = Uuid::new_v4();
+        let payload = event_payload(
+            event_id,
+            WebhookEventType::BudgetExceeded,
+            family_id,
+            Utc::now(),
+            json!({ "spent": "120.00" }),
+        );
+        assert_eq!(payload["id"], json!(event_id));
+        assert_eq!(payload["type"], "budget.exceeded");
+        assert_eq!(payload["family_id"], json!(family_id));
+        assert_eq!(payload["data"]["spent"], "120.00");
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery_and_attempt_outcome() {
+        use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Router};
+        use std::sync::{Arc, Mutex};
+
+        type Seen = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+        async fn receive(
+            State(seen): State<Seen>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> &'static str {
+            seen.lock().unwrap().push((headers, body));
+            "ok"
+        }
+
+        let seen: Seen = Arc::default();
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .route(
+                "/broken",
+                post(|| async { (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "boom") }),
+            )
+            .route(
+                "/moved",
+                post(|| async { axum::response::Redirect::temporary("/hook") }),
+            )
+            .with_state(seen.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = outbound::client(std::time::Duration::from_secs(5)).unwrap();
+        let secret = generate_webhook_secret();
+        let delivery_id = Uuid::new_v4();
+        let body = serde_json::to_vec(&event_payload(
+            Uuid::new_v4(),
+            WebhookEventType::TransactionCreated,
+            Uuid::new_v4(),
+            Utc::now(),
+            json!({ "transaction": { "amount": "35.50" } }),
+        ))
+        .unwrap();
+
+        let ok = send_signed(
+            &client,
+            &format!("{}/hook", base),
+            &secret,
+            "transaction.created",
+            delivery_id,
+            &body,
+        )
+        .await;
+        assert!(ok.succeeded());
+        assert_eq!(ok.response_status, Some(200));
+
+        let (headers, received) = seen.lock().unwrap().pop().unwrap();
+        assert_eq!(headers[EVENT_HEADER], "transaction.created");
+        assert_eq!(headers[DELIVERY_HEADER], delivery_id.to_string().as_str());
+        crate::utils::signature::verify_header(
+            secret.as_bytes(),
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            &received,
+            Utc::now().timestamp(),
+            300,
+        )
+        .unwrap();
+
+        let broken = send_signed(
+            &client,
+            &format!("{}/broken", base),
+            &secret,
+            "transaction.created",
+            delivery_id,
+            &body,
+        )
+        .await;
+        assert!(!broken.succeeded());
+        assert_eq!(broken.response_status, Some(500));
+        assert_eq!(
+            broken.error.as_deref(),
+            Some("HTTP 500 Internal Server Error")
+        );
+
+        // 不跟随重定向
+        let moved = send_signed(
+            &client,
+            &format!("{}/moved", base),
+            &secret,
+            "transaction.created",
+            delivery_id,
+            &body,
+        )
+        .await;
+        assert_eq!(moved.response_status, Some(307));
+        assert!(!moved.succeeded());
+        assert!(seen.lock().unwrap().is_empty());
+
+        // 连接失败没有响应码
+        let unreachable = send_signed(
+            &client,
+            "http://127.0.0.1:1/hook",
+            &secret,
+            "transaction.created",
+            delivery_id,
+            &body,
+        )
+        .await;
+        assert_eq!(unreachable.response_status, None);
+        assert!(unreachable.error.is_some());
+    }
+}
@@ -1,3 +1,6 @@
 //! Utility modules for common functionality
 
+pub mod outbound;
 pub mod password;
+pub mod redact;
+pub mod signature;
@@ -0,0 +1,197 @@
+//! Guards for HTTP requests sent to user-supplied URLs (webhooks, push endpoints)
+//!
+//! A target is only accepted when every address it resolves to is publicly
+//! routable: loopback, private, link-local, unique-local, CGNAT and other
+//! special-purpose ranges are rejected. URLs are checked when they are saved
+//! and again before each request; the client built by [`client`] additionally
+//! filters DNS answers through [`PublicResolver`] (so a rebinding answer cannot
+//! slip in between the check and the connect) and never follows redirects.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use reqwest::Url;
+
+/// Reasons an outbound target is rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum OutboundError {
+    #[error("invalid URL")]
+    InvalidUrl,
+    #[error("URL scheme not allowed")]
+    Scheme,
+    #[error("host could not be resolved")]
+    Unresolvable,
+    #[error("host resolves to a non-public address")]
+    NonPublic,
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // 0.0.0.0/8 "this network"
+        || a == 0
+        // 100.64.0.0/10 carrier-grade NAT
+        || (a == 100 && (b & 0xc0) == 64)
+        // 192.0.0.0/24 IETF protocol assignments
+        || (a == 192 && b == 0 && ip.octets()[2] == 0)
+        // 198.18.0.0/15 benchmarking
+        || (a == 198 && (b & 0xfe) == 18)
+        // 240.0.0.0/4 reserved
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(v4);
+    }
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // fc00::/7 unique local
+        || (first & 0xfe00) == 0xfc00
+        // fe80::/10 link-local
+        || (first & 0xffc0) == 0xfe80
+        // 2001:db8::/32 documentation
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8)
+        // 64:ff9b::/96 NAT64 may reach internal IPv4 hosts
+        || (first == 0x0064 && ip.segments()[1] == 0xff9b))
+}
+
+/// Whether the address is publicly routable
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_ipv4(v4),
+        IpAddr::V6(v6) => is_public_ipv6(v6),
+    }
+}
+
+/// Parse `url`, require one of `schemes` and make sure the host only resolves
+/// to public addresses
+pub async fn check_url(url: &str, schemes: &[&str]) -> Result<Url, OutboundError> {
+    let parsed = Url::parse(url).map_err(|_| OutboundError::InvalidUrl)?;
+    if !schemes.contains(&parsed.scheme()) {
+        return Err(OutboundError::Scheme);
+    }
+    let port = parsed
+        .port_or_known_default()
+        .ok_or(OutboundError::InvalidUrl)?;
+    let host = parsed.host_str().ok_or(OutboundError::InvalidUrl)?;
+    // IPv6 literals keep their brackets in host_str
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        ensure_public([ip])?;
+    } else {
+        let addrs = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| OutboundError::Unresolvable)?
+            .map(|addr| addr.ip())
+            .collect::<Vec<_>>();
+        if addrs.is_empty() {
+            return Err(OutboundError::Unresolvable);
+        }
+        ensure_public(addrs)?;
+    }
+    Ok(parsed)
+}
+
+fn ensure_public(addrs: impl IntoIterator<Item = IpAddr>) -> Result<(), OutboundError> {
+    if addrs.into_iter().all(is_public_ip) {
+        Ok(())
+    } else {
+        Err(OutboundError::NonPublic)
+    }
+}
+
+/// DNS resolver that drops non-public answers
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+            if addrs.is_empty() {
+                return Err(Box::new(OutboundError::NonPublic) as _);
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// HTTP client for user-supplied URLs: public-only DNS, no redirects
+pub fn client(timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["8.8.8.8", "93.184.216.34", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_url() {
+        let http = &["http", "https"];
+        assert!(check_url("https://93.184.216.34/hook", http).await.is_ok());
+        assert_eq!(
+            check_url("http://169.254.169.254/latest/meta-data", http).await,
+            Err(OutboundError::NonPublic)
+        );
+        assert_eq!(
+            check_url("http://localhost:5432/", http).await,
+            Err(OutboundError::NonPublic)
+        );
+        assert_eq!(
+            check_url("http://[::1]:8080/", http).await,
+            Err(OutboundError::NonPublic)
+        );
+        assert_eq!(
+            check_url("http://93.184.216.34/", &["https"]).await,
+            Err(OutboundError::Scheme)
+        );
+        assert_eq!(
+            check_url("not a url", http).await,
+            Err(OutboundError::InvalidUrl)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Masking of sensitive numbers in text sent to third parties
+//!
+//! Card, account and phone numbers are recognised as runs of at least 8 digits,
+//! optionally grouped by spaces or dashes (`6222 0212 3456 7890`). Each run is
+//! replaced by `****` plus its last 4 digits. Dates (`2026-10-18`) and decimal
+//! amounts (`12345678.90`) are left untouched.
+
+use std::borrow::Cow;
+
+/// Minimum number of digits for a run to be treated as an account number
+const MIN_DIGITS: usize = 8;
+/// Digits kept visible at the end of a masked run
+const VISIBLE_DIGITS: usize = 4;
+/// Groups separated by spaces or dashes must have at least this many digits
+const MIN_GROUP: usize = 3;
+
+fn group_len(chars: &[char], start: usize) -> usize {
+    chars[start..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count()
+}
+
+/// Mask account-like digit runs in `text`
+pub fn mask_account_numbers(text: &str) -> Cow<'_, str> {
+    if text.chars().filter(|c| c.is_ascii_digit()).count() < MIN_DIGITS {
+        return Cow::Borrowed(text);
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        // Collect a run of digit groups joined by single spaces or dashes
+        let start = i;
+        let mut digits = String::new();
+        let mut end = i;
+        loop {
+            let len = group_len(&chars, end);
+            digits.extend(&chars[end..end + len]);
+            end += len;
+            let joinable = end + 1 < chars.len()
+                && matches!(chars[end], ' ' | '-')
+                && len >= MIN_GROUP
+                && group_len(&chars, end + 1) >= MIN_GROUP;
+            if !joinable {
+                break;
+            }
+            end += 1;
+        }
+
+        let fraction_follows =
+            end + 1 < chars.len() && chars[end] == '.' && chars[end + 1].is_ascii_digit();
+        let is_fraction = start > 0 && chars[start - 1] == '.';
+        if digits.len() >= MIN_DIGITS && !fraction_follows && !is_fraction {
+            out.push_str("****");
+            out.push_str(&digits[digits.len() - VISIBLE_DIGITS..]);
+            changed = true;
+        } else {
+            out.extend(&chars[start..end]);
+        }
+        i = end;
+    }
+
+    if changed {
+        Cow::Owned(out)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_account_numbers() {
+        assert_eq!(
+            mask_account_numbers("Card 6222021234567890 purchase"),
+            "Card ****7890 purchase"
+        );
+        assert_eq!(
+            mask_account_numbers("尾号 6222 0212 3456 7890 消费"),
+            "尾号 ****7890 消费"
+        );
+        assert_eq!(
+            mask_account_numbers("转账至 6222-0212-3456-7891"),
+            "转账至 ****7891"
+        );
+        assert_eq!(mask_account_numbers("手机 13812345678"), "手机 ****5678");
+        // a date right before a card number does not swallow it
+        assert_eq!(
+            mask_account_numbers("2026-10-18 6222021234567890"),
+            "2026-10-18 ****7890"
+        );
+
+        // left alone: short numbers, dates, decimal amounts
+        for text in [
+            "Starbucks #1234",
+            "2026-10-18 12:30",
+            "余额 12345678.90",
+            "amount 0.12345678",
+            "",
+        ] {
+            assert!(matches!(mask_account_numbers(text), Cow::Borrowed(_)));
+        }
+    }
+}
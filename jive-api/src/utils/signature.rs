@@ -0,0 +1,131 @@
+//! HMAC-SHA256 request signatures
+//!
+//! Header format: `t=<unix seconds>,v1=<hex hmac>`, where the HMAC is computed
+//! over `"<t>.<raw body>"`. The timestamp is part of the signed payload so a
+//! captured request cannot be replayed outside the tolerance window.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Reasons a signature header is rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("malformed signature header")]
+    Malformed,
+    #[error("signature timestamp outside tolerance")]
+    Expired,
+    #[error("signature mismatch")]
+    Mismatch,
+}
+
+fn mac_for(secret: &[u8], timestamp: i64, body: &[u8]) -> HmacSha256 {
+    // HMAC accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC key of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Hex-encoded HMAC-SHA256 of `"<timestamp>.<body>"`
+pub fn sign(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    hex::encode(mac_for(secret, timestamp, body).finalize().into_bytes())
+}
+
+/// Build a `t=...,v1=...` signature header value
+pub fn signature_header(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    format!("t={},v1={}", timestamp, sign(secret, timestamp, body))
+}
+
+/// Verify a `t=...,v1=...` header against the raw body
+///
+/// Several `v1` entries are accepted (any match passes) so senders can sign
+/// with both the old and new secret while rotating.
+pub fn verify_header(
+    secret: &[u8],
+    header: &str,
+    body: &[u8],
+    now: i64,
+    tolerance_secs: i64,
+) -> Result<(), SignatureError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => {
+                timestamp = Some(
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| SignatureError::Malformed)?,
+                )
+            }
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or(SignatureError::Malformed)?;
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed);
+    }
+    if (now - timestamp).abs() > tolerance_secs {
+        return Err(SignatureError::Expired);
+    }
+
+    let matched = signatures.iter().any(|candidate| {
+        hex::decode(candidate)
+            .map(|bytes| {
+                mac_for(secret, timestamp, body)
+                    .verify_slice(&bytes)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    });
+    if matched {
+        Ok(())
+    } else {
+        Err(SignatureError::Mismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_roundtrip_and_rejections() {
+        let secret = b"whsec_test";
+        let body = br#"{"webhook_type":"TRANSACTIONS"}"#;
+        let header = signature_header(secret, 1_700_000_000, body);
+
+        assert_eq!(
+            verify_header(secret, &header, body, 1_700_000_100, 300),
+            Ok(())
+        );
+        // tampered body / wrong secret
+        assert_eq!(
+            verify_header(secret, &header, b"{}", 1_700_000_100, 300),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_header(b"other", &header, body, 1_700_000_100, 300),
+            Err(SignatureError::Mismatch)
+        );
+        // replay outside the window
+        assert_eq!(
+            verify_header(secret, &header, body, 1_700_000_301, 300),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify_header(secret, "v1=abc", body, 1_700_000_000, 300),
+            Err(SignatureError::Malformed)
+        );
+        // rotation: any matching v1 passes
+        let rotated = format!("{},v1={}", header, sign(b"old", 1_700_000_000, body));
+        assert_eq!(
+            verify_header(b"old", &rotated, body, 1_700_000_000, 300),
+            Ok(())
+        );
+    }
+}
@@ -7,23 +7,30 @@ use axum::{
     },
     response::Response,
 };
+use chrono::{DateTime, Utc};
 use futures_util::{sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::auth::Claims;
 
 /// WebSocket连接管理器
 pub struct WsConnectionManager {
     connections: Arc<RwLock<HashMap<String, tokio::sync::mpsc::UnboundedSender<String>>>>,
+    /// 用户 -> 该用户的连接 id（同一用户可多端在线）
+    users: Arc<RwLock<HashMap<Uuid, HashSet<String>>>>,
 }
 
 impl WsConnectionManager {
     pub fn new() -> Self {
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            users: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -31,8 +38,45 @@ impl WsConnectionManager {
         self.connections.write().await.insert(id, tx);
     }
 
+    /// 登记已认证用户的连接
+    pub async fn add_user_connection(
+        &self,
+        user_id: Uuid,
+        id: String,
+        tx: tokio::sync::mpsc::UnboundedSender<String>,
+    ) {
+        self.add_connection(id.clone(), tx).await;
+        self.users
+            .write()
+            .await
+            .entry(user_id)
+            .or_default()
+            .insert(id);
+    }
+
     pub async fn remove_connection(&self, id: &str) {
         self.connections.write().await.remove(id);
+        let mut users = self.users.write().await;
+        users.retain(|_, ids| {
+            ids.remove(id);
+            !ids.is_empty()
+        });
+    }
+
+    /// 推送给用户的全部在线连接，返回送达的连接数
+    pub async fn send_to_user(&self, user_id: Uuid, message: &WsMessage) -> usize {
+        let Ok(text) = serde_json::to_string(message) else {
+            return 0;
+        };
+        let ids: Vec<String> = match self.users.read().await.get(&user_id) {
+            Some(ids) => ids.iter().cloned().collect(),
+            None => return 0,
+        };
+        let connections = self.connections.read().await;
+        ids.iter()
+            .filter_map(|id| connections.get(id))
+            .filter(|tx| tx.send(text.clone()).is_ok())
+            .count()
     }
 
     pub async fn send_message(&self, id: &str, message: String) -> Result<(), String> {
@@ -60,10 +104,31 @@ pub struct WsQuery {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum WsMessage {
-    Connected { user_id: String },
+    Connected {
+        user_id: String,
+    },
     Ping,
     Pong,
-    Error { message: String },
+    Error {
+        message: String,
+    },
+    /// 服务端推送的通知（如汇率提醒）
+    Notification {
+        id: Uuid,
+        kind: String,
+        /// low / normal / high / urgent
+        priority: String,
+        title: String,
+        body: String,
+        data: serde_json::Value,
+        action_url: Option<String>,
+        created_at: DateTime<Utc>,
+    },
+    /// 通知已读 / 已忽略（同步用户的其他在线设备）
+    NotificationsRead {
+        ids: Vec<Uuid>,
+        unread_count: i64,
+    },
 }
 
 /// 处理WebSocket升级请求
@@ -71,6 +136,7 @@ pub async fn ws_handler(
     ws: WebSocketUpgrade,
     Query(query): Query<WsQuery>,
     State(pool): State<PgPool>,
+    State(manager): State<Arc<WsConnectionManager>>,
 ) -> Response {
     // 简单的令牌验证（实际应验证JWT）
     if query.token.is_empty() {
@@ -87,16 +153,36 @@ pub async fn ws_handler(
         });
     }
 
-    ws.on_upgrade(move |socket| handle_socket(socket, query.token, pool))
+    ws.on_upgrade(move |socket| handle_socket(socket, query.token, pool, manager))
 }
 
 /// 处理WebSocket连接
-pub async fn handle_socket(socket: WebSocket, token: String, _pool: PgPool) {
+pub async fn handle_socket(
+    socket: WebSocket,
+    token: String,
+    pool: PgPool,
+    manager: Arc<WsConnectionManager>,
+) {
     let (mut sender, mut receiver) = socket.split();
 
+    // 令牌有效且未被吊销（退出全部设备、重置密码、账户锁定）时登记到用户，以便接收服务端推送
+    let user_id = match Claims::from_token(&token) {
+        Ok(claims) if !claims.is_revoked(&pool).await => claims.user_id().ok(),
+        _ => None,
+    };
+    let connection_id = Uuid::new_v4().to_string();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    if let Some(user_id) = user_id {
+        manager
+            .add_user_connection(user_id, connection_id.clone(), tx)
+            .await;
+    }
+
     // 发送连接成功消息
     let connected_msg = WsMessage::Connected {
-        user_id: "test-user".to_string(),
+        user_id: user_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "test-user".to_string()),
     };
 
     if let Ok(msg_str) = serde_json::to_string(&connected_msg) {
@@ -106,24 +192,61 @@ pub async fn handle_socket(socket: WebSocket, token: String, _pool: PgPool) {
     info!("WebSocket connected with token: {}", token);
 
     // 处理消息循环
-    while let Some(msg) = receiver.next().await {
-        match msg {
-            Ok(Message::Text(text)) => {
+    loop {
+        tokio::select! {
+            msg = receiver.next() => match msg {
                 // 简单的ping/pong处理
-                if text.contains("\"Ping\"") || text.contains("ping") {
+                Some(Ok(Message::Text(text)))
+                    if text.contains("\"Ping\"") || text.contains("ping") =>
+                {
                     let pong = serde_json::to_string(&WsMessage::Pong).unwrap();
                     let _ = sender.send(Message::Text(pong)).await;
                 }
+                Some(Ok(Message::Close(_))) | None => {
+                    info!("WebSocket connection closed");
+                    break;
+                }
+                Some(Err(e)) => {
+                    error!("WebSocket error: {}", e);
+                    break;
+                }
+                _ => {}
+            },
+            Some(push) = rx.recv() => {
+                if sender.send(Message::Text(push)).await.is_err() {
+                    break;
+                }
             }
-            Ok(Message::Close(_)) => {
-                info!("WebSocket connection closed");
-                break;
-            }
-            Err(e) => {
-                error!("WebSocket error: {}", e);
-                break;
-            }
-            _ => {}
         }
     }
+
+    manager.remove_connection(&connection_id).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_send_to_user_reaches_all_connections() {
+        let manager = WsConnectionManager::new();
+        let user = Uuid::new_v4();
+        let (tx1, mut rx1) = tokio::sync::mpsc::unbounded_channel();
+        let (tx2, mut rx2) = tokio::sync::mpsc::unbounded_channel();
+        manager.add_user_connection(user, "a".into(), tx1).await;
+        manager.add_user_connection(user, "b".into(), tx2).await;
+
+        assert_eq!(manager.send_to_user(user, &WsMessage::Ping).await, 2);
+        assert_eq!(rx1.recv().await.unwrap(), r#"{"type":"Ping"}"#);
+        assert!(rx2.recv().await.is_some());
+        assert_eq!(
+            manager.send_to_user(Uuid::new_v4(), &WsMessage::Ping).await,
+            0
+        );
+
+        manager.remove_connection("a").await;
+        assert_eq!(manager.send_to_user(user, &WsMessage::Ping).await, 1);
+        manager.remove_connection("b").await;
+        assert!(manager.users.read().await.is_empty());
+    }
 }
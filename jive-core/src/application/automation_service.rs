@@ -161,8 +161,9 @@ impl AutomationService {
                 score += Decimal::from_str("0.3").unwrap();
             }
         } else {
-            // Different currency - check with exchange rate tolerance
-            // TODO: Implement exchange rate checking
+            // Different currency: amounts are not checked against the rate on the
+            // transaction date, so the pair only gets a flat bonus. Rate alerts live in
+            // jive-api's rate_alert_service and do not cover this check.
             score += Decimal::from_str("0.2").unwrap();
         }
         
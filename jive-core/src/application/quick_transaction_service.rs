@@ -398,7 +398,8 @@ impl QuickTransactionService {
         history_patterns: &HistoryPatterns,
         rule_matches: &RuleMatches,
     ) -> Option<CategorySuggestion> {
-        // TODO: 实现分类建议逻辑
+        // 分类建议由 jive-api 的 CategorySuggestionService 提供（按家庭历史训练的本地分类器，
+        // GET /api/v1/transactions/suggestions）；jive-core 不持有模型，这里不给建议
         None
     }
 
@@ -408,7 +409,7 @@ impl QuickTransactionService {
         description: &str,
         family_id: &str,
     ) -> Result<Option<PayeeSuggestion>> {
-        // TODO: 基于描述和历史记录建议收款人
+        // 收款人建议同样由 jive-api 的 CategorySuggestionService 返回（未填写收款人时）
         Ok(None)
     }
 